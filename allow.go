@@ -0,0 +1,104 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"slices"
+	"strings"
+)
+
+// Names of the assembler warnings, which ;@allow takes and which end the
+// message of each warning
+const (
+	WARN_TRUNCATED       = "truncated-number"
+	WARN_NEGATIVE        = "negative-constant"
+	WARN_LITERAL_DC      = "literal-repeats-dc"
+	WARN_LITERAL_LOOP    = "literal-in-loop"
+	WARN_UNSIGNED_SIGNED = "unsigned-as-signed"
+	WARN_UNCHECKED_INDEX = "unchecked-index"
+	WARN_INDEX_RANGE     = "index-out-of-range"
+)
+
+var warningNames = []string{
+	WARN_TRUNCATED, WARN_NEGATIVE, WARN_LITERAL_DC, WARN_LITERAL_LOOP,
+	WARN_UNSIGNED_SIGNED, WARN_UNCHECKED_INDEX, WARN_INDEX_RANGE,
+}
+
+// Magic comments turning warnings off: ";@allow NAME,..." for its own
+// line and ";@allow-file NAME,..." for the whole file it is in. "all"
+// stands for every warning.
+var allowCommentPattern = regexp.MustCompile(`(?i)^;\s*@ALLOW(-FILE)?\s+([a-z0-9,\s-]+?)\s*$`)
+
+// commentIndex returns the position of the ; starting the comment of
+// line, or -1 when it has none. A ; inside a quoted string does not
+// start a comment.
+func commentIndex(line string) int {
+	quoted := false
+	for i := 0; i < len(line); i++ {
+		switch line[i] {
+		case '\'':
+			quoted = !quoted
+		case ';':
+			if !quoted {
+				return i
+			}
+		}
+	}
+	return -1
+}
+
+// collectAllows reads the ;@allow and ;@allow-file comments of all lines
+// before pass 1, so that ;@allow-file applies to warnings on any line of
+// its file, before or after it.
+func collectAllows(asmState *AssemblerState) error {
+	asmState.allowLines = make(map[int]map[string]bool)
+	asmState.allowFiles = make(map[string]map[string]bool)
+	for i, src := range asmState.lines {
+		idx := commentIndex(src.Text)
+		if idx < 0 {
+			continue
+		}
+		m := allowCommentPattern.FindStringSubmatch(strings.TrimSpace(src.Text[idx:]))
+		if m == nil {
+			continue
+		}
+		names := make(map[string]bool)
+		for _, name := range strings.Split(m[2], ",") {
+			name = strings.ToLower(strings.TrimSpace(name))
+			if name != "all" && !slices.Contains(warningNames, name) {
+				asmState.line = i + 1
+				return errorCasl2(asmState, fmt.Sprintf("Unknown warning \"%s\" in ;@allow (%s, all)", name, strings.Join(warningNames, ", ")))
+			}
+			names[name] = true
+		}
+		if m[1] != "" {
+			if asmState.allowFiles[src.File] == nil {
+				asmState.allowFiles[src.File] = make(map[string]bool)
+			}
+			for name := range names {
+				asmState.allowFiles[src.File][name] = true
+			}
+		} else {
+			asmState.allowLines[i+1] = names
+		}
+	}
+	return nil
+}
+
+// allowed reports whether the warning name on line pos is turned off.
+func allowed(asmState *AssemblerState, pos int, name string) bool {
+	if names := asmState.allowLines[pos]; names[name] || names["all"] {
+		return true
+	}
+	names := asmState.allowFiles[asmState.origin(pos).File]
+	return names[name] || names["all"]
+}
+
+// warning returns the warning name about line pos, or nothing when it is
+// turned off there.
+func warning(asmState *AssemblerState, pos int, name string, msg string) []Diagnostic {
+	if allowed(asmState, pos, name) {
+		return nil
+	}
+	return []Diagnostic{{Line: asmState.origin(pos).Line, Msg: msg, Severity: "warning", Code: name}}
+}
@@ -0,0 +1,43 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestAllowWarnings(t *testing.T) {
+	src := `MAIN	START
+	LAD	GR1,0
+LOOP	ADDA	GR1,=1	;@allow literal-in-loop
+	CPA	GR1,=10
+	JNZ	LOOP
+	RET
+	DC	40000,70000	;@allow negative-constant, truncated-number
+	DC	40000
+	END
+`
+	*optLint = true
+	defer func() { *optLint = false }()
+	asmState := newAssemblerState()
+	if _, err := assembleText("prog.cas", src, asmState); err != nil {
+		t.Fatal(err)
+	}
+	want := []Diagnostic{
+		{Line: 8, Msg: "\"40000\" is stored as #9C40, which reads as -25536 when signed", Severity: "warning", Code: WARN_NEGATIVE},
+		{Line: 4, Msg: "Literal =10 is inside the loop at LOOP (lines 3-5); a named DC constant would be clearer", Severity: "warning", Code: WARN_LITERAL_LOOP},
+	}
+	if !reflect.DeepEqual(asmState.warnings, want) {
+		t.Errorf("Expected %v, got %v", want, asmState.warnings)
+	}
+
+	asmState = newAssemblerState()
+	if _, err := assembleText("prog.cas", "; scaffold\n;@allow-file all\n"+src, asmState); err != nil || len(asmState.warnings) != 0 {
+		t.Errorf("Expected ;@allow-file all to turn every warning off, got %v %v", asmState.warnings, err)
+	}
+
+	_, err := assembleText("prog.cas", "MAIN\tSTART\n\tRET\t;@allow unused\n\tEND\n", newAssemblerState())
+	if err == nil || !strings.Contains(err.Error(), "Unknown warning \"unused\"") {
+		t.Errorf("Expected an unknown warning name to fail, got %v", err)
+	}
+}
@@ -0,0 +1,172 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// Diagnostic is a problem found in a source line by Analysis.
+type Diagnostic struct {
+	Line     int    `json:"line"`
+	Column   int    `json:"column,omitempty"`
+	Msg      string `json:"message"`
+	Severity string `json:"severity,omitempty"`
+	Code     string `json:"code,omitempty"`
+}
+
+// analysisLine is the cached parse of one source line.
+type analysisLine struct {
+	parsed      *ParsedLine
+	include     string
+	conditional string
+	err         *ParseError
+}
+
+// Analysis keeps every line of a source file parsed so that an editor can
+// re-check the file after each change. Update re-parses only the changed
+// lines; label resolution is then redone over the cached lines, which is
+// cheap compared with lexing and parsing.
+type Analysis struct {
+	lines       []analysisLine
+	Diagnostics []Diagnostic
+}
+
+// NewAnalysis parses source and resolves its labels.
+func NewAnalysis(source string) *Analysis {
+	a := &Analysis{}
+	a.Update(1, 0, strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n"))
+	return a
+}
+
+// Update replaces lines first..last (1-based, inclusive) with lines and
+// refreshes Diagnostics. Use last = first-1 to insert without replacing.
+func (a *Analysis) Update(first, last int, lines []string) {
+	first = max(first, 1)
+	last = min(max(last, first-1), len(a.lines))
+	first = min(first, last+1)
+
+	parsed := make([]analysisLine, len(lines))
+	for i, text := range lines {
+		parsed[i] = analyzeLine(text, first+i)
+	}
+
+	rest := append(parsed, a.lines[last:]...)
+	a.lines = append(a.lines[:first-1], rest...)
+	a.resolve()
+}
+
+// analyzeLine parses a single line, recognizing INCLUDE <STD/...>.
+func analyzeLine(text string, lineNum int) analysisLine {
+	code := text
+	if idx := strings.Index(code, ";"); idx >= 0 {
+		code = code[:idx]
+	}
+	if matches := includeStdPattern.FindStringSubmatch(code); matches != nil {
+		return analysisLine{parsed: &ParsedLine{Line: lineNum}, include: matches[1]}
+	}
+	if includeFilePattern.MatchString(code) {
+		return analysisLine{parsed: &ParsedLine{Line: lineNum}}
+	}
+	if matches := conditionalPattern.FindStringSubmatch(code); matches != nil {
+		return analysisLine{parsed: &ParsedLine{Line: lineNum}, conditional: matches[1]}
+	}
+
+	parsed, err := ParseLine(text, lineNum)
+	if err != nil {
+		perr, _ := err.(*ParseError)
+		return analysisLine{err: perr}
+	}
+	return analysisLine{parsed: parsed}
+}
+
+// resolve checks instructions and labels over the cached lines.
+func (a *Analysis) resolve() {
+	a.Diagnostics = nil
+	report := func(line int, format string, args ...interface{}) {
+		a.Diagnostics = append(a.Diagnostics, Diagnostic{Line: line, Msg: fmt.Sprintf(format, args...)})
+	}
+
+	// Labels are scoped by the START label of their program, except the
+	// START labels themselves, which are entry points visible everywhere.
+	defined := make(map[string]bool)
+	global := make(map[string]bool)
+	scopes := make([]string, len(a.lines))
+	scope := ""
+	depth := 0
+	for i, l := range a.lines {
+		line := i + 1
+		if l.err != nil {
+			a.Diagnostics = append(a.Diagnostics, Diagnostic{Line: line, Column: l.err.Column, Msg: l.err.Msg})
+			continue
+		}
+		if l.include != "" {
+			if _, ok := stdlib[strings.ToUpper(l.include)]; !ok {
+				report(line, "Unknown library \"<STD/%s>\"", l.include)
+			}
+			global[strings.ToUpper(l.include)] = true
+			continue
+		}
+		if l.conditional != "" {
+			switch l.conditional {
+			case "IFDEF", "IFEQ":
+				depth++
+			case "ENDIF":
+				depth = max(depth-1, 0)
+			}
+			continue
+		}
+
+		p := l.parsed
+		if p.Instruction == "" && len(p.Operands) > 0 {
+			report(line, "Illegal instruction \"%s\"", p.Operands[0])
+			continue
+		}
+		if p.Instruction == "START" {
+			scope = foldCase(p.Label)
+			if p.Label == "" {
+				report(line, "No label found at START")
+			}
+			global[scope] = true
+		} else if p.Instruction != "" && scope == "" {
+			report(line, "NO \"START\" instruction found")
+		}
+		scopes[i] = scope
+
+		if p.Label != "" {
+			key := scope + ":" + foldCase(p.Label)
+			// Both branches of IFDEF or IFEQ may define the same label
+			if defined[key] && depth == 0 {
+				report(line, "Label \"%s\" has already defined", p.Label)
+			}
+			defined[key] = true
+		}
+		if p.Instruction == "END" {
+			scope = ""
+		}
+	}
+
+	for i, l := range a.lines {
+		if l.parsed == nil || l.parsed.Instruction == "" {
+			continue
+		}
+		for _, opr := range l.parsed.Operands {
+			if !isLabelReference(opr) {
+				continue
+			}
+			if name := foldCase(opr); !defined[scopes[i]+":"+name] && !global[name] {
+				report(i+1, "Label \"%s\" is not defined", opr)
+			}
+		}
+	}
+
+	sort.SliceStable(a.Diagnostics, func(i, j int) bool {
+		return a.Diagnostics[i].Line < a.Diagnostics[j].Line
+	})
+}
+
+// isLabelReference reports whether an operand names a label rather than
+// a register, a constant or a literal.
+func isLabelReference(opr string) bool {
+	return IsValidLabel(opr) && !IsRegister(strings.ToUpper(opr))
+}
@@ -0,0 +1,42 @@
+package main
+
+import "testing"
+
+func TestAnalysisUpdate(t *testing.T) {
+	a := NewAnalysis(`MAIN	START
+	LD	GR1,X
+	CALL	OUTNUM
+	RET
+X	DC	1
+	INCLUDE	<STD/OUTNUM>
+	END
+`)
+	if len(a.Diagnostics) != 0 {
+		t.Fatalf("Unexpected diagnostics: %+v", a.Diagnostics)
+	}
+
+	// Rename X on line 5 so the reference on line 2 breaks
+	a.Update(5, 5, []string{"Y\tDC\t1"})
+	if len(a.Diagnostics) != 1 || a.Diagnostics[0].Line != 2 {
+		t.Fatalf("Expected undefined label on line 2, got %+v", a.Diagnostics)
+	}
+
+	// Insert a duplicate definition after line 5
+	a.Update(6, 5, []string{"X\tDC\t2", "X\tDC\t3"})
+	if len(a.Diagnostics) != 1 || a.Diagnostics[0].Line != 7 {
+		t.Fatalf("Expected duplicate label on line 7, got %+v", a.Diagnostics)
+	}
+
+	// Delete the duplicate
+	a.Update(7, 7, nil)
+	if len(a.Diagnostics) != 0 {
+		t.Fatalf("Unexpected diagnostics: %+v", a.Diagnostics)
+	}
+}
+
+func TestAnalysisIllegalInstruction(t *testing.T) {
+	a := NewAnalysis("MAIN\tSTART\n\tFOO\tGR1\n\tEND\n")
+	if len(a.Diagnostics) != 1 || a.Diagnostics[0].Line != 2 {
+		t.Fatalf("Expected one diagnostic on line 2, got %+v", a.Diagnostics)
+	}
+}
@@ -0,0 +1,151 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"slices"
+	"strings"
+)
+
+// anonymizeSource renames the labels defined in source to L1, L2, ... in
+// order of definition and drops comments and blank lines. References to
+// names the source does not define, such as library subroutines, are
+// kept. Preprocessor lines are kept, with names they test renamed.
+func anonymizeSource(source string) (string, error) {
+	texts := strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n")
+	lines := make([]*ParsedLine, len(texts))
+	used := make(map[string]bool)
+	var defined []string
+	for i, text := range texts {
+		code := text
+		if idx := strings.Index(code, ";"); idx >= 0 {
+			code = code[:idx]
+		}
+		if conditionalPattern.MatchString(code) || includeFilePattern.MatchString(code) || includeStdPattern.MatchString(code) {
+			continue
+		}
+		parsed, err := ParseLine(text, i+1)
+		if err != nil {
+			return "", fmt.Errorf("[CASL2 ERROR] %v", err)
+		}
+		lines[i] = parsed
+		if parsed.Label != "" && !slices.Contains(defined, parsed.Label) {
+			defined = append(defined, parsed.Label)
+		}
+		for _, opr := range parsed.Operands {
+			used[strings.TrimPrefix(opr, "=")] = true
+		}
+	}
+
+	// New names skip those referred to but not renamed
+	names := make(map[string]string)
+	n := 0
+	for _, label := range defined {
+		for {
+			n++
+			if name := fmt.Sprintf("L%d", n); !used[name] || slices.Contains(defined, name) {
+				names[label] = name
+				break
+			}
+		}
+	}
+	rename := func(s string) string {
+		if name, ok := names[s]; ok {
+			return name
+		}
+		return s
+	}
+
+	var b strings.Builder
+	for i, text := range texts {
+		parsed := lines[i]
+		if parsed == nil {
+			code := strings.TrimRight(text, " \t")
+			if idx := strings.Index(code, ";"); idx >= 0 {
+				code = strings.TrimRight(code[:idx], " \t")
+			}
+			if m := conditionalPattern.FindStringSubmatch(code); m != nil && m[3] != "" {
+				name, value, ok := strings.Cut(m[3], ",")
+				operands := rename(strings.TrimSpace(name))
+				if ok {
+					operands += "," + strings.TrimSpace(value)
+				}
+				code = "\t" + m[1] + "\t" + operands
+			}
+			b.WriteString(code + "\n")
+			continue
+		}
+		if parsed.Label == "" && parsed.Instruction == "" {
+			continue
+		}
+		operands := make([]string, len(parsed.Operands))
+		for j, opr := range parsed.Operands {
+			if strings.HasPrefix(opr, "=") {
+				operands[j] = "=" + rename(opr[1:])
+			} else {
+				operands[j] = rename(opr)
+			}
+		}
+		b.WriteString(strings.TrimRight(rename(parsed.Label)+"\t"+parsed.Instruction+"\t"+strings.Join(operands, ","), "\t") + "\n")
+	}
+	return b.String(), nil
+}
+
+// runAnonymizeCommand implements "c2c2 anonymize". It writes the source
+// with labels renamed and comments removed, after checking that both
+// assemble to the same image. It returns the exit status.
+func runAnonymizeCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("anonymize", flag.ContinueOnError)
+	fs.SetOutput(w)
+	output := fs.String("o", "", "write the anonymized source to the given file instead of the standard output")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 anonymize [-o OUTPUT] FILE.cas\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 1 {
+		fs.Usage()
+		return 2
+	}
+
+	path := fs.Arg(0)
+	source, err := os.ReadFile(path)
+	if err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] Cannot read file: %v\n", err)
+		return 1
+	}
+	original, err := assembleText(path, string(source), newAssemblerState())
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	anonymized, err := anonymizeSource(string(source))
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	// The renamed program must be the same machine code
+	renamed, err := assembleText(path, anonymized, newAssemblerState())
+	if err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] The anonymized source does not assemble: %v\n", err)
+		return 1
+	}
+	if renamed.Start != original.Start || !slices.Equal(renamed.Bin, original.Bin) {
+		fmt.Fprintln(w, "[CASL2 ERROR] The anonymized source assembles to a different program")
+		return 1
+	}
+
+	if *output == "" {
+		fmt.Fprint(w, anonymized)
+		return 0
+	}
+	if err := os.WriteFile(*output, []byte(anonymized), 0644); err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] Cannot write file: %v\n", err)
+		return 1
+	}
+	return 0
+}
@@ -0,0 +1,47 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestAnonymize(t *testing.T) {
+	source := `; homework 2: sum of inputs
+MAIN	START	BEGIN
+L1	DC	'a;b'
+BEGIN	LAD	GR1,0	; total
+LOOP	ADDA	GR1,=1
+	CPA	GR1,LIMIT
+	JMI	LOOP
+	CALL	OUTNUM
+	RET
+LIMIT	DC	3
+	INCLUDE	<STD/OUTNUM>	; library
+	END
+`
+	dir := t.TempDir()
+	path := filepath.Join(dir, "hw.cas")
+	os.WriteFile(path, []byte(source), 0644)
+
+	var out bytes.Buffer
+	if status := runAnonymizeCommand([]string{path}, &out); status != 0 {
+		t.Fatalf("Expected anonymize to succeed, got %d:\n%s", status, out.String())
+	}
+	// MAIN becomes L1, so the label L1 is renamed too
+	expected := "L1\tSTART\tL3\n" +
+		"L2\tDC\t'a;b'\n" +
+		"L3\tLAD\tGR1,0\n" +
+		"L4\tADDA\tGR1,=1\n" +
+		"\tCPA\tGR1,L5\n" +
+		"\tJMI\tL4\n" +
+		"\tCALL\tOUTNUM\n" +
+		"\tRET\n" +
+		"L5\tDC\t3\n" +
+		"\tINCLUDE\t<STD/OUTNUM>\n" +
+		"\tEND\n"
+	if out.String() != expected {
+		t.Errorf("Expected\n%s\ngot\n%s", expected, out.String())
+	}
+}
@@ -0,0 +1,50 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// First word of the program argument block written by -args. The block
+// holds the number of arguments, then the address and length of each
+// argument, then their characters one per word, like IN stores them. It
+// lies between the stack top and the SVC vector area, so the stack never
+// reaches it.
+const ARGS_BASE = STACK_TOP
+
+// parseArgs splits the -args option at commas and checks that the
+// argument block fits below SVC_VECTOR_BASE.
+func parseArgs(spec string) ([]string, error) {
+	if spec == "" {
+		return nil, nil
+	}
+	args := strings.Split(spec, ",")
+	size := 1
+	for _, arg := range args {
+		size += 2 + len(arg)
+	}
+	if size > SVC_VECTOR_BASE-ARGS_BASE {
+		return nil, fmt.Errorf("[COMET2 ERROR] -args needs %d words, only %d are available at #%s",
+			size, SVC_VECTOR_BASE-ARGS_BASE, hex(ARGS_BASE, 4))
+	}
+	return args, nil
+}
+
+// writeArgs stores the argument block for args at ARGS_BASE. Nothing is
+// written without arguments, so programs that do not use them see the
+// memory fill as before.
+func writeArgs(memory []uint16, args []string) {
+	if len(args) == 0 || len(memory) < SVC_VECTOR_BASE {
+		return
+	}
+	memory[ARGS_BASE] = uint16(len(args))
+	text := ARGS_BASE + 1 + 2*len(args)
+	for i, arg := range args {
+		memory[ARGS_BASE+1+2*i] = uint16(text)
+		memory[ARGS_BASE+2+2*i] = uint16(len(arg))
+		for j := 0; j < len(arg); j++ {
+			memory[text+j] = uint16(arg[j])
+		}
+		text += len(arg)
+	}
+}
@@ -0,0 +1,33 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestProgramArgs(t *testing.T) {
+	args, err := parseArgs("ab,,c")
+	if err != nil {
+		t.Fatalf("parseArgs failed: %v", err)
+	}
+	c := NewComet2Builder().WithObject(assembleObject(t, "MAIN\tSTART\n\tLD\tGR1,#FF00\n\tRET\n\tEND\n")).
+		WithArgs(args).WithOutput(func(string) {}).Build()
+	expected := []uint16{3, 0xff07, 2, 0xff09, 0, 0xff09, 1, 'a', 'b', 'c'}
+	if block := c.Memory[ARGS_BASE : ARGS_BASE+len(expected)]; !reflect.DeepEqual(block, expected) {
+		t.Errorf("Expected the argument block %v, got %v", expected, block)
+	}
+	c.Run()
+	if c.State[GR1] != 3 {
+		t.Errorf("Expected the program to read 3 arguments, got %d", c.State[GR1])
+	}
+
+	c.Memory[ARGS_BASE] = 0
+	c.Reset(assembleObject(t, "MAIN\tSTART\n\tRET\n\tEND\n"))
+	if c.Memory[ARGS_BASE] != 3 {
+		t.Errorf("Expected Reset to write the arguments again")
+	}
+	if _, err := parseArgs(strings.Repeat("x", 240)); err == nil {
+		t.Errorf("Expected arguments too long for the block to fail")
+	}
+}
@@ -0,0 +1,143 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+// Manifest of an -artifacts directory
+const ARTIFACTS_MANIFEST = "manifest.json"
+
+// artifactKind is a file an -artifacts directory may hold: the option
+// writing it and its name in the directory. Options given on the command
+// line keep their path and are listed in the manifest as they are.
+type artifactKind struct {
+	kind string
+	name string
+	// opt is the option writing the file, nil for the files -artifacts
+	// writes itself.
+	opt *string
+}
+
+// artifactKinds are the files of an -artifacts directory, in the order of
+// the manifest.
+var artifactKinds = []artifactKind{
+	{"object", "program" + OBJECT_EXT, optObject},
+	{"listing", "listing" + LISTING_EXT, nil},
+	{"symbols", "symbols.json", nil},
+	{"report", "report.md", optReportMd},
+	{"summary", "result.json", optReportJs},
+	{"svc-log", "svc.log", optSvcLog},
+	{"stack", "stack.txt", optStack},
+	{"trace", "samples.jsonl", optSamples},
+}
+
+// artifactEntry is a file listed in the manifest.
+type artifactEntry struct {
+	Kind string `json:"kind"`
+	// Path is relative to the directory for the files inside it.
+	Path   string `json:"path"`
+	Bytes  int64  `json:"bytes"`
+	Sha256 string `json:"sha256"`
+}
+
+// artifactManifest is manifest.json of an -artifacts directory.
+type artifactManifest struct {
+	Version  string          `json:"version"`
+	Source   string          `json:"source"`
+	Args     []string        `json:"args"`
+	Started  time.Time       `json:"started"`
+	Finished time.Time       `json:"finished"`
+	Halt     string          `json:"halt,omitempty"`
+	Files    []artifactEntry `json:"files"`
+}
+
+// artifactsDir collects the files of one run for -artifacts.
+type artifactsDir struct {
+	dir     string
+	source  string
+	started time.Time
+}
+
+// openArtifacts creates dir and points the output options that were not
+// given into it.
+func openArtifacts(dir string, source string) (*artifactsDir, error) {
+	if err := os.MkdirAll(dir, 0755); err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot create artifacts directory: %v", err)
+	}
+	for _, k := range artifactKinds {
+		if k.opt != nil && *k.opt == "" {
+			*k.opt = filepath.Join(dir, k.name)
+		}
+	}
+	return &artifactsDir{dir: dir, source: source, started: time.Now()}, nil
+}
+
+// writeAssembly writes the listing and the symbols of an assembled
+// program.
+func (a *artifactsDir) writeAssembly(asmState *AssemblerState, obj *Object) error {
+	listing := "CASL LISTING\n\n" + strings.Join(asmState.outdump, "\n") + "\n"
+	if err := os.WriteFile(filepath.Join(a.dir, "listing"+LISTING_EXT), []byte(listing), 0644); err != nil {
+		return fmt.Errorf("[CASL2 ERROR] Cannot write listing: %v", err)
+	}
+	data, err := json.MarshalIndent(obj.Symbols, "", "  ")
+	if err != nil {
+		return fmt.Errorf("[CASL2 ERROR] Cannot encode symbols: %v", err)
+	}
+	if err := os.WriteFile(filepath.Join(a.dir, "symbols.json"), append(data, '\n'), 0644); err != nil {
+		return fmt.Errorf("[CASL2 ERROR] Cannot write symbols: %v", err)
+	}
+	return nil
+}
+
+// writeManifest lists the files of the run that exist, with their size
+// and SHA-256, in manifest.json.
+func (a *artifactsDir) writeManifest(halt string) error {
+	m := artifactManifest{
+		Version:  VERSION,
+		Source:   a.source,
+		Args:     os.Args[1:],
+		Started:  a.started,
+		Finished: time.Now(),
+		Halt:     halt,
+		Files:    []artifactEntry{},
+	}
+	for _, k := range artifactKinds {
+		path := filepath.Join(a.dir, k.name)
+		if k.opt != nil {
+			// FILE[:FROM-TO] of -samples
+			path = *k.opt
+			if k.kind == "trace" {
+				if idx := strings.LastIndex(path, ":"); idx > 1 {
+					path = path[:idx]
+				}
+			}
+		}
+		data, err := os.ReadFile(path)
+		if err != nil {
+			continue
+		}
+		if rel, err := filepath.Rel(a.dir, path); err == nil && !strings.HasPrefix(rel, "..") {
+			path = rel
+		}
+		m.Files = append(m.Files, artifactEntry{
+			Kind:   k.kind,
+			Path:   filepath.ToSlash(path),
+			Bytes:  int64(len(data)),
+			Sha256: fmt.Sprintf("%x", sha256.Sum256(data)),
+		})
+	}
+	data, err := json.MarshalIndent(m, "", "  ")
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot encode manifest: %v", err)
+	}
+	if err := os.WriteFile(filepath.Join(a.dir, ARTIFACTS_MANIFEST), append(data, '\n'), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write manifest: %v", err)
+	}
+	return nil
+}
@@ -0,0 +1,82 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestArtifacts(t *testing.T) {
+	saved := []string{*optObject, *optReportMd, *optReportJs, *optSvcLog, *optStack, *optSamples}
+	defer func() {
+		*optObject, *optReportMd, *optReportJs, *optSvcLog, *optStack, *optSamples = saved[0], saved[1], saved[2], saved[3], saved[4], saved[5]
+	}()
+
+	dir := filepath.Join(t.TempDir(), "run")
+	own := filepath.Join(t.TempDir(), "report.json")
+	*optObject, *optReportMd, *optSvcLog, *optStack, *optSamples = "", "", "", "", ""
+	*optReportJs = own
+	artifacts, err := openArtifacts(dir, "prog.cas")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if *optObject != filepath.Join(dir, "program"+OBJECT_EXT) || *optReportMd != filepath.Join(dir, "report.md") {
+		t.Errorf("Expected the outputs in %s, got %s and %s", dir, *optObject, *optReportMd)
+	}
+	if *optReportJs != own {
+		t.Errorf("Expected -report-json to keep its path, got %s", *optReportJs)
+	}
+
+	src := "MAIN\tSTART\n\tLD\tGR1,=5\n\tRET\n\tEND\n"
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	if err := os.WriteFile(path, []byte(src), 0644); err != nil {
+		t.Fatal(err)
+	}
+	asmState := newAssemblerState()
+	obj, err := assemble(path, asmState)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if err := writeObjectFile(*optObject, obj); err != nil {
+		t.Fatal(err)
+	}
+	if err := artifacts.writeAssembly(asmState, obj); err != nil {
+		t.Fatal(err)
+	}
+	if err := os.WriteFile(own, []byte("{}\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	if err := artifacts.writeManifest("Program finished (RET)"); err != nil {
+		t.Fatal(err)
+	}
+
+	data, err := os.ReadFile(filepath.Join(dir, ARTIFACTS_MANIFEST))
+	if err != nil {
+		t.Fatal(err)
+	}
+	var manifest artifactManifest
+	if err := json.Unmarshal(data, &manifest); err != nil {
+		t.Fatalf("Invalid manifest: %v", err)
+	}
+	if manifest.Source != "prog.cas" || manifest.Halt != "Program finished (RET)" {
+		t.Errorf("Unexpected manifest source %q and halt %q", manifest.Source, manifest.Halt)
+	}
+	var kinds []string
+	for _, f := range manifest.Files {
+		kinds = append(kinds, f.Kind)
+	}
+	if strings.Join(kinds, ",") != "object,listing,symbols,summary" {
+		t.Fatalf("Expected the object, listing, symbols and summary, got %v", kinds)
+	}
+	symbols, _ := os.ReadFile(filepath.Join(dir, "symbols.json"))
+	if manifest.Files[2].Path != "symbols.json" || manifest.Files[2].Sha256 != fmt.Sprintf("%x", sha256.Sum256(symbols)) {
+		t.Errorf("Unexpected symbols entry %+v", manifest.Files[2])
+	}
+	if manifest.Files[3].Path != filepath.ToSlash(own) || manifest.Files[3].Bytes != 3 {
+		t.Errorf("Expected the summary outside the directory by its path, got %+v", manifest.Files[3])
+	}
+}
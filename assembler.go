@@ -1,62 +1,122 @@
 package main
 
 import (
+	"errors"
 	"fmt"
-	"io/ioutil"
 	"regexp"
 	"strconv"
 	"strings"
+	"unicode"
 )
 
-func assemble(inputFilepath string, asmState *AssemblerState) ([]uint16, string, error) {
+// Patterns used while assembling, compiled once rather than per line
+var (
+	instLinePattern        = regexp.MustCompile(`^(\S+)?\s+([A-Za-z]+)(\s+(.*))?$`)
+	labelLinePattern       = regexp.MustCompile(`^(\S+)\s*$`)
+	labelPattern           = regexp.MustCompile(`^[a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*$`)
+	scopedLabelPattern     = regexp.MustCompile(`:([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*)$`)
+	symbolPattern          = regexp.MustCompile(`^([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*):([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*)$`)
+	registerPattern        = regexp.MustCompile(`^GR[0-7]$`)
+	registerOperandPattern = regexp.MustCompile(`^(GR)?([0-7])$`)
+)
+
+func assemble(inputFilepath string, asmState *AssemblerState) (*Object, error) {
 	// Read source file
-	content, err := ioutil.ReadFile(inputFilepath)
+	content, err := asmState.sources.ReadSource(inputFilepath)
+	if err != nil {
+		return nil, fmt.Errorf("[CASL2 ERROR] Cannot read file: %v", err)
+	}
+	return assembleText(inputFilepath, content, asmState)
+}
+
+// assembleText assembles source as if it were read from the file name.
+func assembleText(name string, source string, asmState *AssemblerState) (*Object, error) {
+	lines, err := preprocess(name, source, asmState.sources)
+	if err != nil {
+		return nil, err
+	}
+	asmState.build = newBuildInfo(source)
+	return assembleLines(name, lines, asmState)
+}
+
+// assembleFiles assembles the files at paths as one program, as if their
+// sources were written one after another in this order. The program is
+// named after the first file; diagnostics point at the file of each line.
+func assembleFiles(paths []string, asmState *AssemblerState) (*Object, error) {
+	lines, err := readProgram(paths, asmState)
 	if err != nil {
-		return nil, "", fmt.Errorf("[CASL2 ERROR] Cannot read file: %v", err)
+		return nil, err
 	}
+	return assembleLines(paths[0], lines, asmState)
+}
+
+// readProgram reads and preprocesses the files at paths as one program.
+func readProgram(paths []string, asmState *AssemblerState) ([]SourceLine, error) {
+	var lines []SourceLine
+	var contents []string
+	for _, path := range paths {
+		content, err := asmState.sources.ReadSource(path)
+		if err != nil {
+			return nil, fmt.Errorf("[CASL2 ERROR] Cannot read file: %v", err)
+		}
+		lines = append(lines, sourceLines(path, content)...)
+		contents = append(contents, content)
+	}
+	lines, err := preprocessLines(lines, asmState.sources)
+	if err != nil {
+		return nil, err
+	}
+	asmState.build = newBuildInfo(contents...)
+	return lines, nil
+}
 
-	casl2code := string(content)
-	asmState.file = inputFilepath
+// assembleLines assembles preprocessed lines into the program name.
+func assembleLines(name string, lines []SourceLine, asmState *AssemblerState) (*Object, error) {
+	asmState.file = name
+	asmState.lines = lines
+	if err := collectAllows(asmState); err != nil {
+		return nil, err
+	}
 
 	// Pass 1: Build symbol table
-	startLabel, err := pass1(casl2code, asmState)
+	startLabel, err := pass1(asmState)
 	if err != nil {
-		return nil, "", err
+		return nil, err
 	}
 
 	// Pass 2: Generate binary
 	comet2bin, err := pass2(asmState)
 	if err != nil {
-		return nil, "", err
+		return nil, err
+	}
+
+	if *optLint {
+		asmState.warnings = append(asmState.warnings, lintLiterals(asmState)...)
+		asmState.warnings = append(asmState.warnings, lintSigns(asmState)...)
+		asmState.warnings = append(asmState.warnings, lintIndexes(asmState)...)
 	}
 
-	return comet2bin, startLabel, nil
+	return newObject(comet2bin, startLabel, asmState), nil
 }
 
-func pass1(source string, asmState *AssemblerState) (string, error) {
-	var inBlock bool
-	var address int
-	var literalStack []string
-	var comet2startLabel string
+func pass1(asmState *AssemblerState) (string, error) {
+	s := &pass1State{asm: asmState}
 
-	lines := strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n")
 	asmState.line = 0
+	asmState.written = make([]string, len(asmState.lines))
 
-	for i, line := range lines {
+	for i, src := range asmState.lines {
 		asmState.line = i + 1
+		line := src.Text
 
 		// Remove comments
-		if idx := strings.Index(line, ";"); idx >= 0 {
-			// Check if semicolon is inside quotes
-			hasQuote := false
-			for j := 0; j < idx; j++ {
-				if line[j] == '\'' {
-					hasQuote = !hasQuote
-				}
-			}
-			if !hasQuote {
-				line = line[:idx]
+		bounds := false
+		if idx := commentIndex(line); idx >= 0 {
+			if m := traceCommentPattern.FindStringSubmatch(strings.TrimSpace(line[idx:])); m != nil {
+				asmState.traceMarks = append(asmState.traceMarks, traceMark{asmState.line, strings.EqualFold(m[1], "ON")})
 			}
+			bounds = boundsCommentPattern.MatchString(strings.TrimSpace(line[idx:]))
+			line = line[:idx]
 		}
 
 		// Remove trailing spaces
@@ -69,41 +129,54 @@ func pass1(source string, asmState *AssemblerState) (string, error) {
 
 		// Extract label, instruction, and operands
 		var label, inst, opr string
-		re1 := regexp.MustCompile(`^(\S+)?\s+([A-Z]+)(\s+(.*))?$`)
-		re2 := regexp.MustCompile(`^(\S+)\s*$`)
-
-		if matches := re1.FindStringSubmatch(line); matches != nil {
+		if matches := instLinePattern.FindStringSubmatch(line); matches != nil {
 			label = matches[1]
 			inst = matches[2]
 			if len(matches) > 4 {
 				opr = matches[4]
 			}
-		} else if matches := re2.FindStringSubmatch(line); matches != nil {
+		} else if matches := labelLinePattern.FindStringSubmatch(line); matches != nil {
 			label = matches[1]
 		} else {
 			return "", errorCasl2(asmState, fmt.Sprintf("Syntax error: %s", line))
 		}
 
+		// The listing shows the line as written; the assembler works on
+		// the mnemonic in upper case and on labels folded by foldCase
+		asmState.written[i] = label + "\t" + inst + "\t" + opr
+		inst = strings.ToUpper(inst)
+		label = foldCase(label)
+		opr = foldCase(opr)
+
 		// Keep every line in buf
 		uniqLabel := ""
 		if label != "" {
 			uniqLabel = asmState.varScope + ":" + label
 		}
 		asmState.buf = append(asmState.buf, uniqLabel+"\t"+inst+"\t"+opr)
+		asmState.bufPos = append(asmState.bufPos, asmState.line)
+
+		if bounds {
+			if label == "" || (inst != "DS" && inst != "DC") {
+				return "", errorCasl2(asmState, ";@bounds must be on a DS or DC line with a label")
+			}
+			asmState.boundsMarks = append(asmState.boundsMarks, boundsMark{asmState.line, uniqLabel})
+		}
 
 		// Register label to symbol table
-		if label != "" && inBlock {
-			err := addLabel(asmState, label, address)
+		if label != "" && s.inBlock {
+			err := addLabel(asmState, label, s.address)
 			if err != nil {
 				return "", err
 			}
 
 			// Check if label is referred from START instruction
 			if label == asmState.actualLabel {
-				err := updateLabel(asmState, asmState.virtualLabel, address)
+				err := updateLabel(asmState, asmState.virtualLabel, s.address)
 				if err != nil {
 					return "", err
 				}
+				asmState.units[len(asmState.units)-1].Entry = s.address
 				asmState.actualLabel = ""
 			}
 		}
@@ -116,6 +189,7 @@ func pass1(source string, asmState *AssemblerState) (string, error) {
 			}
 
 			instType := instDef.Type
+			before := s.address
 
 			// Parse operands
 			var oprArray []string
@@ -124,41 +198,34 @@ func pass1(source string, asmState *AssemblerState) (string, error) {
 			}
 
 			// START must be the first instruction
-			if !inBlock && instType != START {
+			if !s.inBlock && instType != START {
 				return "", errorCasl2(asmState, "NO \"START\" instruction found")
 			}
 
-			// GR0 cannot be used as index register
-			if len(oprArray) > 2 {
-				if matched, _ := regexp.MatchString(`^(GR)?0$`, oprArray[2]); matched {
-					return "", errorCasl2(asmState, "Can't use GR0 as an index register")
-				}
+			if err := checkOperands(asmState, inst, opr, oprArray); err != nil {
+				return "", err
 			}
 
 			// Process each instruction type
 			switch instType {
 			case OP1:
-				if len(oprArray) < 2 || len(oprArray) > 3 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
 				if len(oprArray) == 2 {
 					oprArray = append(oprArray, "0")
 				}
 
 				// Handle literals
 				if strings.HasPrefix(oprArray[1], "=") {
-					oprArray[1] = handleLiteral(oprArray[1], &literalStack, &asmState.literalCounter)
+					oprArray[1] = handleLiteral(oprArray[1], &s.literals, asmState)
 				} else if isLabel(oprArray[1]) && !isRegister(oprArray[1]) {
 					oprArray[1] = asmState.varScope + ":" + oprArray[1]
+				} else if err := checkAddress(asmState, oprArray[1]); err != nil {
+					return "", err
 				}
 
-				genCode2(asmState.memory, address, int(instDef.Code), oprArray[0], oprArray[1], oprArray[2], asmState)
-				address += 2
+				genCode2(asmState.memory, s.address, int(instDef.Code), oprArray[0], oprArray[1], oprArray[2], asmState)
+				s.address += 2
 
 			case OP2:
-				if len(oprArray) < 1 || len(oprArray) > 2 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
 				if len(oprArray) == 1 {
 					oprArray = append(oprArray, "0")
 				}
@@ -169,208 +236,97 @@ func pass1(source string, asmState *AssemblerState) (string, error) {
 					} else {
 						oprArray[0] = asmState.varScope + ":" + oprArray[0]
 					}
+					if instDef.Code&0xf0 == 0x60 {
+						asmState.jumps = append(asmState.jumps, jumpEntry{Line: asmState.line, Target: oprArray[0]})
+					}
+				} else if err := checkAddress(asmState, oprArray[0]); err != nil {
+					return "", err
 				}
 
-				genCode2(asmState.memory, address, int(instDef.Code), "0", oprArray[0], oprArray[1], asmState)
-				address += 2
+				genCode2(asmState.memory, s.address, int(instDef.Code), "0", oprArray[0], oprArray[1], asmState)
+				s.address += 2
 
 			case OP3:
-				if len(oprArray) != 1 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				genCode3(asmState.memory, address, int(instDef.Code), oprArray[0], "0", asmState)
-				address++
+				genCode3(asmState.memory, s.address, int(instDef.Code), oprArray[0], "0", asmState)
+				s.address++
 
 			case OP4:
-				if len(oprArray) != 0 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				genCode1(asmState.memory, address, int(instDef.Code)<<8, asmState)
-				address++
+				genCode1(asmState.memory, s.address, int(instDef.Code)<<8, asmState)
+				asmState.memory[s.address].Code = true
+				s.address++
 
 			case OP5:
-				if len(oprArray) < 2 || len(oprArray) > 3 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
 				if len(oprArray) == 2 {
 					oprArray = append(oprArray, "0")
 				}
 
 				// Handle literals
 				if strings.HasPrefix(oprArray[1], "=") {
-					oprArray[1] = handleLiteral(oprArray[1], &literalStack, &asmState.literalCounter)
+					oprArray[1] = handleLiteral(oprArray[1], &s.literals, asmState)
 				} else if isLabel(oprArray[1]) && !isRegister(oprArray[1]) {
 					oprArray[1] = asmState.varScope + ":" + oprArray[1]
+				} else if !isRegister(oprArray[1]) {
+					if err := checkAddress(asmState, oprArray[1]); err != nil {
+						return "", err
+					}
 				}
 
 				// Check if GR,GR form
 				if isRegister(oprArray[1]) {
-					instCode := int(instDef.Code) + 4
-					genCode3(asmState.memory, address, instCode, oprArray[0], oprArray[1], asmState)
-					address++
+					instCode := int(instDef.Code) + REG_FORM_OFFSET
+					genCode3(asmState.memory, s.address, instCode, oprArray[0], oprArray[1], asmState)
+					s.address++
 				} else {
-					genCode2(asmState.memory, address, int(instDef.Code), oprArray[0], oprArray[1], oprArray[2], asmState)
-					address += 2
-				}
-
-			case START:
-				if label == "" {
-					return "", errorCasl2(asmState, "No label found at START")
+					genCode2(asmState.memory, s.address, int(instDef.Code), oprArray[0], oprArray[1], oprArray[2], asmState)
+					s.address += 2
 				}
 
-				if asmState.firstStart {
-					asmState.firstStart = false
-					if len(oprArray) > 0 {
-						comet2startLabel = label + ":" + oprArray[0]
-					} else {
-						comet2startLabel = label + ":" + label
-					}
-				} else {
-					if len(oprArray) > 0 {
-						asmState.actualLabel = oprArray[0]
-					} else {
-						asmState.actualLabel = ""
-					}
-					asmState.virtualLabel = label
+			default:
+				d, ok := directives[instType]
+				if !ok {
+					return "", errorCasl2(asmState, fmt.Sprintf("Instruction type \"%s\" is not implemented", instType))
 				}
-
-				asmState.varScope = label
-				err := addLabel(asmState, label, address)
-				if err != nil {
+				if err := d.Assemble(s, label, oprArray); err != nil {
 					return "", err
 				}
-				inBlock = true
-
-			case END:
-				if label != "" {
-					return "", errorCasl2(asmState, fmt.Sprintf("Can't use label \"%s\" at END", label))
-				}
-				if len(oprArray) != 0 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-
-				// Expand literals
-				for _, lit := range literalStack {
-					addLiteral(asmState, lit, address)
-					lit = strings.TrimPrefix(lit, "=")
-
-					if strings.HasPrefix(lit, "'") && strings.HasSuffix(lit, "'") {
-						str := lit[1 : len(lit)-1]
-						str = strings.ReplaceAll(str, "''", "'")
-						for _, ch := range str {
-							genCode1(asmState.memory, address, int(ch), asmState)
-							address++
-						}
-						genCode1(asmState.memory, address, 0, asmState)
-						address++
-					} else if matched, _ := regexp.MatchString(`^[+-]?\d+|^\#[\da-fA-F]+`, lit); matched {
-						genCode1(asmState.memory, address, lit, asmState)
-						address++
-					} else {
-						return "", errorCasl2(asmState, fmt.Sprintf("Invalid literal =%s", lit))
-					}
-				}
-
-				asmState.varScope = ""
-				inBlock = false
-
-			case DS:
-				if len(oprArray) != 1 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				count, err := strconv.Atoi(oprArray[0])
-				if err != nil {
-					return "", errorCasl2(asmState, fmt.Sprintf("\"%s\" must be decimal", oprArray[0]))
-				}
-				for j := 0; j < count; j++ {
-					genCode1(asmState.memory, address, 0, asmState)
-					address++
-				}
-
-			case DC:
-				if len(oprArray) < 1 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				for _, op := range oprArray {
-					if strings.HasPrefix(op, "'") && strings.HasSuffix(op, "'") {
-						str := op[1 : len(op)-1]
-						str = strings.ReplaceAll(str, "''", "'")
-						for _, ch := range str {
-							genCode1(asmState.memory, address, int(ch), asmState)
-							address++
-						}
-						genCode1(asmState.memory, address, 0, asmState)
-						address++
-					} else if isLabel(op) {
-						op = asmState.varScope + ":" + op
-						genCode1(asmState.memory, address, op, asmState)
-						address++
-					} else {
-						genCode1(asmState.memory, address, op, asmState)
-						address++
-					}
-				}
-
-			case IN, OUT:
-				if len(oprArray) != 2 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-
-				checkLabel(asmState, oprArray[0])
-				checkLabel(asmState, oprArray[1])
-
-				oprArray[0] = asmState.varScope + ":" + oprArray[0]
-				oprArray[1] = asmState.varScope + ":" + oprArray[1]
-
-				entry := SYS_IN
-				if instType == OUT {
-					entry = SYS_OUT
-				}
-
-				genCode2(asmState.memory, address, int(CASL2TBL["PUSH"].Code), "0", "0", "1", asmState)
-				genCode2(asmState.memory, address+2, int(CASL2TBL["PUSH"].Code), "0", "0", "2", asmState)
-				genCode2(asmState.memory, address+4, int(CASL2TBL["LAD"].Code), "1", oprArray[0], "0", asmState)
-				genCode2(asmState.memory, address+6, int(CASL2TBL["LAD"].Code), "2", oprArray[1], "0", asmState)
-				genCode2(asmState.memory, address+8, int(CASL2TBL["SVC"].Code), "0", strconv.Itoa(entry), "0", asmState)
-				genCode3(asmState.memory, address+10, int(CASL2TBL["POP"].Code), "2", "0", asmState)
-				genCode3(asmState.memory, address+11, int(CASL2TBL["POP"].Code), "1", "0", asmState)
-				address += 12
-
-			case RPUSH:
-				if len(oprArray) != 0 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				for j := 0; j < 7; j++ {
-					genCode2(asmState.memory, address+j*2, int(CASL2TBL["PUSH"].Code), "0", "0", strconv.Itoa(j+1), asmState)
-				}
-				address += 14
+			}
 
-			case RPOP:
-				if len(oprArray) != 0 {
-					return "", errorCasl2(asmState, fmt.Sprintf("Invalid operand \"%s\"", opr))
-				}
-				for j := 0; j < 7; j++ {
-					genCode3(asmState.memory, address+j, int(CASL2TBL["POP"].Code), strconv.Itoa(7-j), "0", asmState)
-				}
-				address += 7
+			countWords(asmState, instType, s.address-before)
+			if err := checkProgramSize(asmState, s.address); err != nil {
+				return "", err
+			}
+		}
 
-			default:
-				return "", errorCasl2(asmState, fmt.Sprintf("Instruction type \"%s\" is not implemented", instType))
+		// -literals-after places the pool after the labeled line
+		if label != "" && label == foldCase(*optLitAfter) && s.inBlock {
+			before := s.address
+			var err error
+			if s.address, err = flushLiterals(asmState, &s.literals, s.address); err != nil {
+				return "", err
+			}
+			countWords(asmState, LTORG, s.address-before)
+			if err := checkProgramSize(asmState, s.address); err != nil {
+				return "", err
 			}
 		}
 	}
 
-	if inBlock {
+	if s.inBlock {
 		return "", errorCasl2(asmState, "NO \"END\" instruction found")
 	}
 
-	addressMax = address
-	return comet2startLabel, nil
+	return s.startLabel, nil
 }
 
 func pass2(asmState *AssemblerState) ([]uint16, error) {
 	if *optAll {
 		caslPrint("CASL LISTING\n")
+		if asmState.build != nil {
+			for _, line := range formatBuildInfo(asmState.build) {
+				caslPrint("; " + line)
+			}
+			caslPrint("")
+		}
 	}
 
 	var lastLine = -1
@@ -397,69 +353,86 @@ func pass2(asmState *AssemblerState) ([]uint16, error) {
 		memEntry := asmState.memory[address]
 		asmState.line = memEntry.Line
 
-		val := expandLabel(asmState.symtbl, memEntry.Val)
+		val, ok := resolveLabel(asmState.symtbl, memEntry.Val)
+		if !ok {
+			name := fmt.Sprint(memEntry.Val)
+			if matches := scopedLabelPattern.FindStringSubmatch(name); matches != nil {
+				name = matches[1]
+			}
+			return nil, errorCasl2(asmState, fmt.Sprintf("Label \"%s\" is not defined", name))
+		}
 		comet2bin = append(comet2bin, uint16(val))
+		if relocatable(asmState, memEntry.Val) {
+			asmState.relocs = append(asmState.relocs, address)
+		}
 
-		if *optAll {
-			bufLine := strings.Split(asmState.buf[asmState.line-1], "\t")
-			if len(bufLine) > 0 {
-				re := regexp.MustCompile(`:([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*)$`)
-				if matches := re.FindStringSubmatch(bufLine[0]); matches != nil {
-					bufLine[0] = matches[1]
-				}
-			}
-			line := strings.Join(bufLine, "\t")
+		line := asmState.written[asmState.line-1]
 
-			if asmState.line != lastLine {
-				str := fmt.Sprintf("%4d %s %s\t%s", asmState.line, hex(address, 4), hex(val, 4), line)
-				asmState.outdump = append(asmState.outdump, str)
-				lastLine = asmState.line
-			} else {
-				str := fmt.Sprintf("%4d      %s", asmState.line, hex(val, 4))
-				asmState.outdump = append(asmState.outdump, str)
-			}
+		srcLine := asmState.origin(asmState.line).Line
+		if asmState.line != lastLine {
+			str := fmt.Sprintf("%4d %s %s\t%s", srcLine, hex(address, 4), hex(val, 4), line)
+			asmState.outdump = append(asmState.outdump, str)
+			lastLine = asmState.line
+		} else {
+			str := fmt.Sprintf("%4d      %s", srcLine, hex(val, 4))
+			asmState.outdump = append(asmState.outdump, str)
 		}
 	}
 
-	if *optAll {
-		asmState.outdump = append(asmState.outdump, "\nDEFINED SYMBOLS")
+	asmState.outdump = append(asmState.outdump, "\nDEFINED SYMBOLS")
 
-		// Sort symbols by line
-		type symInfo struct {
-			name string
-			line int
+	// Sort symbols by line
+	type symInfo struct {
+		name string
+		line int
+	}
+	var symbols []symInfo
+	for name, entry := range asmState.symtbl {
+		if !strings.HasPrefix(name, "=") {
+			symbols = append(symbols, symInfo{name, entry.Line})
 		}
-		var symbols []symInfo
-		for name, entry := range asmState.symtbl {
-			if !strings.HasPrefix(name, "=") {
-				symbols = append(symbols, symInfo{name, entry.Line})
+	}
+
+	// Sort by line, then by name so that the listing is reproducible
+	for i := 0; i < len(symbols); i++ {
+		for j := i + 1; j < len(symbols); j++ {
+			if symbols[i].line > symbols[j].line || (symbols[i].line == symbols[j].line && symbols[i].name > symbols[j].name) {
+				symbols[i], symbols[j] = symbols[j], symbols[i]
 			}
 		}
+	}
 
-		// Sort by line
-		for i := 0; i < len(symbols); i++ {
-			for j := i + 1; j < len(symbols); j++ {
-				if symbols[i].line > symbols[j].line {
-					symbols[i], symbols[j] = symbols[j], symbols[i]
-				}
+	for _, sym := range symbols {
+		label := sym.name
+		if matches := symbolPattern.FindStringSubmatch(label); matches != nil {
+			var labelView string
+			if matches[1] == matches[2] {
+				labelView = matches[2]
+			} else {
+				labelView = fmt.Sprintf("%s (%s)", matches[2], matches[1])
 			}
+			val := expandLabel(asmState.symtbl, label)
+			asmState.outdump = append(asmState.outdump, fmt.Sprintf("%d:\t%s\t%s", asmState.origin(sym.line).Line, hex(val, 4), labelView))
 		}
+	}
 
-		for _, sym := range symbols {
-			label := sym.name
-			re := regexp.MustCompile(`^([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*):([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*)$`)
-			if matches := re.FindStringSubmatch(label); matches != nil {
-				var labelView string
-				if matches[1] == matches[2] {
-					labelView = matches[2]
-				} else {
-					labelView = fmt.Sprintf("%s (%s)", matches[2], matches[1])
-				}
-				val := expandLabel(asmState.symtbl, label)
-				asmState.outdump = append(asmState.outdump, fmt.Sprintf("%d:\t%s\t%s", sym.line, hex(val, 4), labelView))
-			}
+	// Entry points of the units when there are several
+	if len(asmState.units) > 1 {
+		asmState.outdump = append(asmState.outdump, "\nENTRY POINTS")
+		for _, u := range asmState.units {
+			asmState.outdump = append(asmState.outdump, fmt.Sprintf("%s\t%s", hex(u.Entry, 4), u.Name))
 		}
+	}
 
+	// Literal pools: address, literal and the line referring to it
+	if len(asmState.literals) > 0 {
+		asmState.outdump = append(asmState.outdump, "\nLITERALS")
+		for _, lit := range asmState.literals {
+			asmState.outdump = append(asmState.outdump, fmt.Sprintf("%d:\t%s\t%s", asmState.origin(lit.Line).Line, hex(lit.Address, 4), lit.Text))
+		}
+	}
+
+	if *optAll {
 		for _, line := range asmState.outdump {
 			caslPrint(line)
 		}
@@ -503,22 +476,139 @@ func parseOperands(opr string) []string {
 }
 
 func isLabel(s string) bool {
-	matched, _ := regexp.MatchString(`^[a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*$`, s)
-	return matched
+	return labelPattern.MatchString(s)
+}
+
+// foldCase returns text as the assembler compares labels: in upper case
+// outside quoted strings, or unchanged with -case-sensitive.
+func foldCase(text string) string {
+	if *optCaseSens {
+		return text
+	}
+	var b strings.Builder
+	quoted := false
+	for _, r := range text {
+		if r == '\'' {
+			quoted = !quoted
+		}
+		if !quoted {
+			r = unicode.ToUpper(r)
+		}
+		b.WriteRune(r)
+	}
+	return b.String()
 }
 
 func isRegister(s string) bool {
-	matched, _ := regexp.MatchString(`^GR[0-7]$`, strings.ToUpper(s))
-	return matched
+	return registerPattern.MatchString(strings.ToUpper(s))
 }
 
-func handleLiteral(lit string, stack *[]string, counter *int) string {
-	newLit := fmt.Sprintf("%s_%d", lit, *counter)
-	*stack = append(*stack, newLit)
-	*counter++
+func handleLiteral(lit string, stack *[]LiteralEntry, asmState *AssemblerState) string {
+	newLit := fmt.Sprintf("%s_%d", lit, asmState.literalCounter)
+	*stack = append(*stack, LiteralEntry{Name: newLit, Text: lit, Line: asmState.line})
+	asmState.literalCounter++
 	return newLit
 }
 
+// numberOperand parses a numeric operand: a decimal number with an
+// optional sign, or # and hexadecimal digits. ok is false when s is not a
+// number. Digits too many to be any number are always an error. Values
+// outside a word (-32768 to 65535, or more than four hex digits) are an
+// error with -strict-numbers; otherwise they are truncated to 16 bits by
+// the caller, with a warning unless -lenient-numbers is given.
+func numberOperand(asmState *AssemblerState, s string) (val int, ok bool, err error) {
+	var num int64
+	if strings.HasPrefix(s, "#") {
+		n, perr := strconv.ParseUint(s[1:], 16, 32)
+		if perr != nil {
+			if errors.Is(perr, strconv.ErrRange) {
+				return 0, true, errorCasl2(asmState, fmt.Sprintf("\"%s\" is too large to be a number", s))
+			}
+			return 0, false, nil
+		}
+		if len(s) > 5 && *optStrict {
+			return 0, true, errorCasl2(asmState, fmt.Sprintf("\"%s\" has more than 4 hex digits", s))
+		}
+		num = int64(n)
+	} else {
+		n, perr := strconv.ParseInt(s, 10, 32)
+		if perr != nil {
+			if errors.Is(perr, strconv.ErrRange) {
+				return 0, true, errorCasl2(asmState, fmt.Sprintf("\"%s\" is too large to be a number", s))
+			}
+			return 0, false, nil
+		}
+		num = n
+	}
+	if num < -32768 || num > 0xffff {
+		if *optStrict {
+			return 0, true, errorCasl2(asmState, fmt.Sprintf("\"%s\" does not fit in a word (-32768 to 65535)", s))
+		}
+		if !*optLenient {
+			warnCasl2(asmState, WARN_TRUNCATED, fmt.Sprintf("\"%s\" does not fit in a word and is truncated to #%s", s, hex(int(num)&0xffff, 4)))
+		}
+	}
+	return int(num), true, nil
+}
+
+// checkConstant warns about a decimal constant from 32768 to 65535, whose
+// word is negative when read as a signed number (DC 40000 is -25536).
+// Writing it in hexadecimal or as a negative number is not ambiguous.
+func checkConstant(asmState *AssemblerState, s string, val int) {
+	if *optLenient || strings.HasPrefix(s, "#") {
+		return
+	}
+	if val >= 0x8000 && val <= 0xffff {
+		warnCasl2(asmState, WARN_NEGATIVE, fmt.Sprintf("\"%s\" is stored as #%s, which reads as %d when signed", s, hex(val, 4), val-0x10000))
+	}
+}
+
+// checkAddress checks an address operand that is not a label, literal or
+// register: it must be a number, which -strict-numbers also keeps within
+// a word.
+func checkAddress(asmState *AssemblerState, s string) error {
+	_, ok, err := numberOperand(asmState, s)
+	if err != nil {
+		return err
+	}
+	if !ok && *optStrict {
+		return errorCasl2(asmState, fmt.Sprintf("Invalid address \"%s\"", s))
+	}
+	return nil
+}
+
+// flushLiterals places the pending literals at address, as at END or
+// LTORG, and returns the address after them.
+func flushLiterals(asmState *AssemblerState, pending *[]LiteralEntry, address int) (int, error) {
+	for _, lit := range *pending {
+		addLiteral(asmState, lit.Name, address)
+		lit.Address = address
+		asmState.literals = append(asmState.literals, lit)
+		value := strings.TrimPrefix(lit.Text, "=")
+
+		if strings.HasPrefix(value, "'") && strings.HasSuffix(value, "'") {
+			str := value[1 : len(value)-1]
+			str = strings.ReplaceAll(str, "''", "'")
+			for _, ch := range str {
+				genCode1(asmState.memory, address, int(ch), asmState)
+				address++
+			}
+			genCode1(asmState.memory, address, 0, asmState)
+			address++
+		} else if val, ok, err := numberOperand(asmState, value); err != nil {
+			return address, err
+		} else if ok {
+			checkConstant(asmState, value, val)
+			genCode1(asmState.memory, address, val&0xffff, asmState)
+			address++
+		} else {
+			return address, errorCasl2(asmState, fmt.Sprintf("Invalid literal =%s", value))
+		}
+	}
+	*pending = (*pending)[:0]
+	return address, nil
+}
+
 func checkLabel(asmState *AssemblerState, label string) error {
 	if !isLabel(label) {
 		return errorCasl2(asmState, fmt.Sprintf("Invalid label \"%s\"", label))
@@ -538,7 +628,6 @@ func addLabel(asmState *AssemblerState, label string, val int) error {
 
 	asmState.symtbl[uniqLabel] = &SymbolEntry{
 		Val:  val,
-		File: asmState.file,
 		Line: asmState.line,
 	}
 
@@ -557,7 +646,6 @@ func updateLabel(asmState *AssemblerState, label string, val int) error {
 
 	asmState.symtbl[uniqLabel] = &SymbolEntry{
 		Val:  val,
-		File: asmState.file,
 		Line: asmState.line,
 	}
 
@@ -567,61 +655,66 @@ func updateLabel(asmState *AssemblerState, label string, val int) error {
 func addLiteral(asmState *AssemblerState, literal string, val int) {
 	asmState.symtbl[literal] = &SymbolEntry{
 		Val:  val,
-		File: asmState.file,
 		Line: asmState.line,
 	}
 }
 
 func expandLabel(symtbl map[string]*SymbolEntry, val interface{}) int {
+	v, _ := resolveLabel(symtbl, val)
+	return v
+}
+
+// resolveLabel returns the word of a memory entry or symbol: a number, or
+// a scoped label looked up in the symbol table once pass 1 has defined
+// every label, so references may come before the definition. A label that
+// is not in its own unit names another START unit, as for CALL SUB or
+// DC SUB. ok is false when the label is not defined.
+func resolveLabel(symtbl map[string]*SymbolEntry, val interface{}) (int, bool) {
 	switch v := val.(type) {
 	case int:
-		return v & 0xffff
+		return v & 0xffff, true
 	case string:
 		// Check if it's a hex number
 		if strings.HasPrefix(v, "#") {
 			num, err := strconv.ParseInt(v[1:], 16, 64)
 			if err == nil {
-				return int(num) & 0xffff
+				return int(num) & 0xffff, true
 			}
 		}
 
 		// Check if it's in symbol table
 		if entry, exists := symtbl[v]; exists {
-			return expandLabel(symtbl, entry.Val)
+			return resolveLabel(symtbl, entry.Val)
 		}
 
 		// Check for CALL_ prefix
-		if strings.HasPrefix(v, "CALL_") {
-			lbl := v[5:]
-			if entry, exists := symtbl[lbl]; exists {
-				return expandLabel(symtbl, entry.Val)
-			}
+		lbl := strings.TrimPrefix(v, "CALL_")
+		if entry, exists := symtbl[lbl]; exists {
+			return resolveLabel(symtbl, entry.Val)
+		}
 
-			// Try with scope
-			re := regexp.MustCompile(`:([a-zA-Z\$%_\.][0-9a-zA-Z\$%_\.]*)$`)
-			if matches := re.FindStringSubmatch(v); matches != nil {
-				k := matches[1] + ":" + matches[1]
-				if entry, exists := symtbl[k]; exists {
-					return expandLabel(symtbl, entry.Val)
-				}
+		// Try the unit of that name
+		if matches := scopedLabelPattern.FindStringSubmatch(lbl); matches != nil {
+			k := matches[1] + ":" + matches[1]
+			if entry, exists := symtbl[k]; exists {
+				return resolveLabel(symtbl, entry.Val)
 			}
 		}
 
 		// Try to parse as decimal
 		if num, err := strconv.ParseInt(v, 10, 64); err == nil {
-			return int(num) & 0xffff
+			return int(num) & 0xffff, true
 		}
 
-		// If all else fails, return 0
-		return 0
+		// If all else fails, the label is not defined
+		return 0, false
 	default:
-		return 0
+		return 0, false
 	}
 }
 
 func checkRegister(register string) (int, error) {
-	re := regexp.MustCompile(`^(GR)?([0-7])$`)
-	matches := re.FindStringSubmatch(strings.ToUpper(register))
+	matches := registerOperandPattern.FindStringSubmatch(strings.ToUpper(register))
 	if matches == nil {
 		return 0, fmt.Errorf("Invalid register \"%s\"", register)
 	}
@@ -632,22 +725,22 @@ func checkRegister(register string) (int, error) {
 func genCode1(memory map[int]*MemoryEntry, address int, val interface{}, asmState *AssemblerState) {
 	switch v := val.(type) {
 	case int:
-		memory[address] = &MemoryEntry{Val: v, File: asmState.file, Line: asmState.line}
+		memory[address] = &MemoryEntry{Val: v, Line: asmState.line}
 	case string:
 		// Check for hex
 		if strings.HasPrefix(v, "#") {
 			if num, err := strconv.ParseInt(v[1:], 16, 64); err == nil {
-				memory[address] = &MemoryEntry{Val: int(num), File: asmState.file, Line: asmState.line}
+				memory[address] = &MemoryEntry{Val: int(num), Line: asmState.line}
 				return
 			}
 		}
 		// Check for decimal
 		if num, err := strconv.ParseInt(v, 10, 64); err == nil {
-			memory[address] = &MemoryEntry{Val: int(num), File: asmState.file, Line: asmState.line}
+			memory[address] = &MemoryEntry{Val: int(num), Line: asmState.line}
 			return
 		}
 		// Store as string (will be resolved in pass2)
-		memory[address] = &MemoryEntry{Val: v, File: asmState.file, Line: asmState.line}
+		memory[address] = &MemoryEntry{Val: v, Line: asmState.line}
 	}
 }
 
@@ -656,17 +749,17 @@ func genCode2(memory map[int]*MemoryEntry, address int, code int, gr, adr, xr st
 	nxr, _ := checkRegister(xr)
 
 	val := (code << 8) + (ngr << 4) + nxr
-	memory[address] = &MemoryEntry{Val: val, File: asmState.file, Line: asmState.line}
+	memory[address] = &MemoryEntry{Val: val, Line: asmState.line, Code: true}
 
 	// Handle address operand
 	if strings.HasPrefix(adr, "#") {
 		if num, err := strconv.ParseInt(adr[1:], 16, 64); err == nil {
-			memory[address+1] = &MemoryEntry{Val: int(num), File: asmState.file, Line: asmState.line}
+			memory[address+1] = &MemoryEntry{Val: int(num), Line: asmState.line, Code: true}
 			return
 		}
 	}
 
-	memory[address+1] = &MemoryEntry{Val: adr, File: asmState.file, Line: asmState.line}
+	memory[address+1] = &MemoryEntry{Val: adr, Line: asmState.line, Code: true}
 }
 
 func genCode3(memory map[int]*MemoryEntry, address int, code int, gr1, gr2 string, asmState *AssemblerState) {
@@ -674,10 +767,15 @@ func genCode3(memory map[int]*MemoryEntry, address int, code int, gr1, gr2 strin
 	ngr2, _ := checkRegister(gr2)
 
 	val := (code << 8) + (ngr1 << 4) + ngr2
-	memory[address] = &MemoryEntry{Val: val, File: asmState.file, Line: asmState.line}
+	memory[address] = &MemoryEntry{Val: val, Line: asmState.line, Code: true}
 }
 
 func errorCasl2(asmState *AssemblerState, msg string) error {
-	return fmt.Errorf("%sLine %d: %s%s",
-		"\x1b[31;43m", asmState.line, msg, "\x1b[0m")
+	o := asmState.origin(asmState.line)
+	return &AssembleError{File: o.File, Line: o.Line, Msg: msg}
+}
+
+// warnCasl2 records a problem that does not stop the assembly.
+func warnCasl2(asmState *AssemblerState, name string, msg string) {
+	asmState.warnings = append(asmState.warnings, warning(asmState, asmState.line, name, msg)...)
 }
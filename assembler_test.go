@@ -0,0 +1,328 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestLiteralPool(t *testing.T) {
+	src := `MAIN	START
+	LD	GR1,=5
+	JUMP	NEXT
+	LTORG
+NEXT	ADDA	GR1,=#10
+	RET
+	END
+`
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	os.WriteFile(path, []byte(src), 0644)
+	asmState := newAssemblerState()
+	obj, err := assemble(path, asmState)
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+
+	// =5 is placed at LTORG, right after JUMP; =#10 at END
+	if obj.Bin[4] != 5 || obj.Bin[8] != 0x10 {
+		t.Errorf("Unexpected literal placement %v", obj.Bin)
+	}
+	want := []LiteralEntry{
+		{Name: "=5_0", Text: "=5", Address: 4, Line: 2},
+		{Name: "=#10_1", Text: "=#10", Address: 8, Line: 5},
+	}
+	if !reflect.DeepEqual(asmState.literals, want) {
+		t.Errorf("Expected %+v, got %+v", want, asmState.literals)
+	}
+	listing := strings.Join(asmState.outdump, "\n")
+	if !strings.Contains(listing, "LITERALS\n2:\t0004\t=5\n5:\t0008\t=#10") {
+		t.Errorf("Expected the literal pool in the listing, got\n%s", listing)
+	}
+
+	c := LoadObject(obj)
+	c.Output = func(string) {}
+	c.Run()
+	if c.State[GR1] != 0x15 {
+		t.Errorf("Expected GR1 = #0015, got #%04x", c.State[GR1])
+	}
+}
+
+func TestNumberOperands(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,-1
+	LAD	GR2,#10
+	RET
+BUF	DS	#0003
+	DC	-1,#FFFF,65535,-32768,70000
+	END
+`)
+	want := []uint16{0xffff, 0xffff, 0xffff, 0x8000, 70000 & 0xffff}
+	if obj.Bin[1] != 0xffff || obj.Bin[3] != 0x10 || len(obj.Bin) != 5+3+len(want) ||
+		!reflect.DeepEqual(obj.Bin[8:], want) {
+		t.Errorf("Unexpected words %v", obj.Bin)
+	}
+
+	*optStrict = true
+	defer func() { *optStrict = false }()
+	for src, msg := range map[string]string{
+		"MAIN\tSTART\n\tDC\t70000\n\tEND\n":       "does not fit in a word",
+		"MAIN\tSTART\n\tLAD\tGR1,#12345\n\tEND\n": "more than 4 hex digits",
+		"MAIN\tSTART\n\tLD\tGR1,=-40000\n\tEND\n": "does not fit in a word",
+		"MAIN\tSTART\n\tDC\t1X\n\tEND\n":          "Invalid constant",
+		"MAIN\tSTART\n\tDS\t-1\n\tEND\n":          "must be a number from 0 to 65535",
+	} {
+		path := filepath.Join(t.TempDir(), "prog.cas")
+		os.WriteFile(path, []byte(src), 0644)
+		if _, err := assemble(path, newAssemblerState()); err == nil || !strings.Contains(err.Error(), msg) {
+			t.Errorf("%q: expected %q, got %v", src, msg, err)
+		}
+	}
+}
+
+func TestNumberWarnings(t *testing.T) {
+	src := `MAIN	START
+	DC	40000,#9C40,-25536,70000
+	LD	GR1,=99999999999
+	END
+`
+	if _, err := assembleText("prog.cas", src, newAssemblerState()); err == nil || !strings.Contains(err.Error(), "too large to be a number") {
+		t.Errorf("Expected an error for an impossible number, got %v", err)
+	}
+
+	asmState := newAssemblerState()
+	src = strings.Replace(src, "99999999999", "1", 1)
+	if _, err := assembleText("prog.cas", src, asmState); err != nil {
+		t.Fatal(err)
+	}
+	want := []Diagnostic{
+		{Line: 2, Msg: "\"40000\" is stored as #9C40, which reads as -25536 when signed", Severity: "warning", Code: WARN_NEGATIVE},
+		{Line: 2, Msg: "\"70000\" does not fit in a word and is truncated to #1170", Severity: "warning", Code: WARN_TRUNCATED},
+	}
+	if !reflect.DeepEqual(asmState.warnings, want) {
+		t.Errorf("Unexpected warnings %v", asmState.warnings)
+	}
+
+	*optLenient = true
+	defer func() { *optLenient = false }()
+	asmState = newAssemblerState()
+	if _, err := assembleText("prog.cas", src, asmState); err != nil || len(asmState.warnings) != 0 {
+		t.Errorf("Expected no warnings with -lenient-numbers, got %v %v", asmState.warnings, err)
+	}
+}
+
+func TestStartEntry(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START	BEGIN
+DATA	DC	7
+BEGIN	LD	GR1,DATA
+	CALL	SUB
+	RET
+	END
+SUB	START	ENTRY
+	NOP
+ENTRY	LAD	GR2,1
+	RET
+	END
+`)
+	if obj.Start != 1 || obj.Entry != "MAIN:BEGIN" {
+		t.Errorf("Expected entry MAIN:BEGIN at #0001, got %s at #%s", obj.Entry, hex(obj.Start, 4))
+	}
+	if obj.Units[0].Entry != 1 || obj.Units[1].Start != 6 || obj.Units[1].Entry != 7 {
+		t.Errorf("Unexpected unit entries %+v", obj.Units)
+	}
+
+	c := LoadObject(obj)
+	c.Output = func(string) {}
+	c.Run()
+	if c.State[GR1] != 7 || c.State[GR2] != 1 {
+		t.Errorf("Expected GR1 = 7 and GR2 = 1, got %d and %d", c.State[GR1], c.State[GR2])
+	}
+
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	os.WriteFile(path, []byte("MAIN\tSTART\tNOPE\n\tRET\n\tEND\n"), 0644)
+	if _, err := assemble(path, newAssemblerState()); err == nil || !strings.Contains(err.Error(), "Entry label \"NOPE\" of \"MAIN\" is not defined") {
+		t.Errorf("Expected an undefined entry error, got %v", err)
+	}
+}
+
+func TestStableOutput(t *testing.T) {
+	src := `MAIN	START
+A	LAD	GR1,=3
+B	NOP
+C	NOP
+	CALL	SUB
+X	LD	GR2,=4
+	RET
+	END
+SUB	START
+X	LD	GR0,=5
+Y	RET
+	END
+`
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	os.WriteFile(path, []byte(src+"\tLD\tGR1,UNDEF\n\tFOO\n"), 0644)
+
+	var first []string
+	for i := 0; i < 20; i++ {
+		asmState := newAssemblerState()
+		obj, err := assembleText("prog.cas", src, asmState)
+		if err != nil {
+			t.Fatalf("Failed to assemble: %v", err)
+		}
+		data, _ := json.Marshal(obj)
+		var check bytes.Buffer
+		runCheck([]string{path}, "text", &check)
+		c := LoadObject(obj)
+		got := []string{strings.Join(asmState.outdump, "\n"), string(data), check.String(),
+			strings.Join(monitorCompletions(c, []string{"dump"}), " "), c.labelAt(1)}
+		if first == nil {
+			first = got
+		} else if !reflect.DeepEqual(got, first) {
+			t.Fatalf("Output changed between runs:\n%q\n%q", first, got)
+		}
+	}
+}
+
+func TestDuplicateUnit(t *testing.T) {
+	dir := t.TempDir()
+	files := [][2]string{
+		{"main.cas", "MAIN\tSTART\n\tCALL\tSUB\n\tRET\n\tEND\nSUB\tSTART\n\tRET\n\tEND\n"},
+		{"sub.cas", "; helpers\nSUB\tSTART\n\tLAD\tGR0,7\n\tRET\n\tEND\n"},
+	}
+	for _, f := range files {
+		if err := os.WriteFile(filepath.Join(dir, f[0]), []byte(f[1]), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", f[0], err)
+		}
+	}
+	t.Chdir(dir)
+
+	var aerr *AssembleError
+	_, err := assembleFiles([]string{"main.cas", "sub.cas"}, newAssemblerState())
+	if !errors.As(err, &aerr) || aerr.File != "sub.cas" || aerr.Line != 2 {
+		t.Fatalf("Expected an error at sub.cas line 2, got %v", err)
+	}
+	if want := `Program unit "SUB" is defined twice: main.cas line 5 and sub.cas line 2`; aerr.Msg != want {
+		t.Errorf("Expected %q, got %q", want, aerr.Msg)
+	}
+}
+
+func TestDCForwardReferences(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,=5
+	LTORG
+PTRS	DC	LATER,SUB,MAIN
+	RET
+LATER	DC	PTRS
+	END
+SUB	START	BODY
+	NOP
+BODY	LD	GR1,TBL
+	RET
+TBL	DC	MAIN,BODY
+	END
+`)
+	later, sub, ptrs, tbl := obj.Symbols["MAIN:LATER"], obj.Symbols["SUB:SUB"], obj.Symbols["MAIN:PTRS"], obj.Symbols["SUB:TBL"]
+	if sub != obj.Symbols["SUB:BODY"] {
+		t.Errorf("Expected unit SUB to start at BODY, got #%s", hex(sub, 4))
+	}
+	// The pool of =5 comes before PTRS
+	if ptrs != 3 {
+		t.Errorf("Expected PTRS after the literal pool at #0003, got #%s", hex(ptrs, 4))
+	}
+	for _, w := range []struct{ addr, want int }{
+		{ptrs, later},
+		{ptrs + 1, sub},
+		{ptrs + 2, 0},
+		{later, ptrs},
+		{tbl, 0},
+		{tbl + 1, sub},
+	} {
+		if int(obj.Bin[w.addr]) != w.want {
+			t.Errorf("Expected #%s at #%s, got #%s", hex(w.want, 4), hex(w.addr, 4), hex(int(obj.Bin[w.addr]), 4))
+		}
+	}
+
+	_, err := assembleText("prog.cas", "MAIN\tSTART\n\tRET\nP\tDC\tQ\n\tEND\n", newAssemblerState())
+	var aerr *AssembleError
+	if !errors.As(err, &aerr) || aerr.Line != 3 || !strings.Contains(aerr.Msg, "Label \"Q\" is not defined") {
+		t.Errorf("Expected an undefined label on line 3, got %v", err)
+	}
+}
+
+func TestProgramSizeLimit(t *testing.T) {
+	// RET and DS fill the address space exactly
+	obj, err := assembleText("prog.cas", "MAIN\tSTART\n\tRET\nBUF\tDS\t65535\n\tEND\n", newAssemblerState())
+	if err != nil {
+		t.Fatalf("Expected 65536 words to fit, got %v", err)
+	}
+	if len(obj.Bin) != ADDRESS_SPACE {
+		t.Errorf("Expected %d words, got %d", ADDRESS_SPACE, len(obj.Bin))
+	}
+
+	for _, tc := range []struct {
+		src  string
+		line int
+		size string
+	}{
+		{"MAIN\tSTART\n\tRET\nBUF\tDS\t65535\nX\tDC\t1\n\tEND\n", 4, "65537 words"},
+		{"MAIN\tSTART\n\tRET\nA\tDS\t40000\nB\tDS\t40000\nC\tDC\t1\n\tEND\n", 4, "80001 words"},
+		{"MAIN\tSTART\n\tLD\tGR1,=1\nBUF\tDS\t65534\n\tEND\n", 4, "65537 words"},
+	} {
+		_, err := assembleText("prog.cas", tc.src, newAssemblerState())
+		var aerr *AssembleError
+		if !errors.As(err, &aerr) || aerr.Line != tc.line || !strings.Contains(aerr.Msg, tc.size) {
+			t.Errorf("Expected the program to overflow at line %d with %s, got %v", tc.line, tc.size, err)
+		}
+	}
+}
+
+func TestMixedCaseSource(t *testing.T) {
+	src := `Main	start
+	lad	gr1,2
+Loop	suba	gr1,=1
+	jnz	LOOP
+	out	Msg,len
+	ret
+msg	dc	'Hi'
+Len	dc	2
+	end
+`
+	asmState := newAssemblerState()
+	obj, err := assembleText("prog.cas", src, asmState)
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+	var outputs []string
+	c := LoadObject(obj)
+	c.Output = func(text string) { outputs = append(outputs, text) }
+	c.Run()
+	if c.State[GR1] != 0 || len(outputs) != 1 || outputs[0] != "Hi" {
+		t.Errorf("Expected GR1 = 0 and OUT \"Hi\", got GR1 = %d and %q", c.State[GR1], outputs)
+	}
+	listing := strings.Join(asmState.outdump, "\n")
+	for _, want := range []string{"\tLoop\tsuba\tgr1,=1\n", "\t\tout\tMsg,len\n", "\tLOOP\n"} {
+		if !strings.Contains(listing, want) {
+			t.Errorf("Expected %q in the listing:\n%s", want, listing)
+		}
+	}
+	if a := NewAnalysis(src); len(a.Diagnostics) != 0 {
+		t.Errorf("Unexpected diagnostics %+v", a.Diagnostics)
+	}
+
+	if _, err := assembleText("prog.cas", "MAIN\tSTART\nx\tDC\t1\nX\tDC\t2\n\tEND\n", newAssemblerState()); err == nil || !strings.Contains(err.Error(), "already defined") {
+		t.Errorf("Expected x and X to be the same label, got %v", err)
+	}
+
+	*optCaseSens = true
+	defer func() { *optCaseSens = false }()
+	if _, err := assembleText("prog.cas", src, newAssemblerState()); err == nil || !strings.Contains(err.Error(), "Label \"LOOP\" is not defined") {
+		t.Errorf("Expected LOOP to differ from Loop, got %v", err)
+	}
+	if _, err := assembleText("prog.cas", "MAIN\tSTART\nx\tDC\t1\nX\tDC\t2\n\tEND\n", newAssemblerState()); err != nil {
+		t.Errorf("Expected x and X to be two labels, got %v", err)
+	}
+}
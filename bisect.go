@@ -0,0 +1,217 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"strings"
+)
+
+// runConfig is how one side of bisect-run configures its machine, given
+// as space separated KEY=VALUE settings of the options of the same name:
+// "engine=block trap=overflow,range mem-init=ones".
+type runConfig struct {
+	engine  string
+	trap    string
+	memInit string
+}
+
+// parseRunConfig parses the settings of a side of bisect-run.
+func parseRunConfig(spec string) (runConfig, error) {
+	var rc runConfig
+	for _, field := range strings.Fields(spec) {
+		key, value, ok := strings.Cut(field, "=")
+		if !ok {
+			return rc, fmt.Errorf("[COMET2 ERROR] Invalid setting \"%s\" (KEY=VALUE)", field)
+		}
+		switch key {
+		case "engine":
+			rc.engine = value
+		case "trap":
+			rc.trap = value
+		case "mem-init":
+			rc.memInit = value
+		default:
+			return rc, fmt.Errorf("[COMET2 ERROR] Unknown setting \"%s\" (engine, trap, mem-init)", key)
+		}
+	}
+	return rc, nil
+}
+
+// build creates a machine for obj with the configuration, failing on an
+// IN after the last of inputs.
+func (rc runConfig) build(obj *Object, inputs []string) (*Comet2, error) {
+	fill, err := parseMemInit(rc.memInit)
+	if err != nil {
+		return nil, err
+	}
+	c := NewComet2Builder().WithObject(obj).WithInputs(inputs).WithOutput(nil).WithMemInit(fill).Build()
+	c.FailOnInputWait = true
+	if err := c.SetEngine(rc.engine); err != nil {
+		return nil, err
+	}
+	if err := c.SetTraps(rc.trap); err != nil {
+		return nil, err
+	}
+	return c, nil
+}
+
+// stepWrites records the memory words an instruction writes.
+type stepWrites struct {
+	BaseObserver
+	writes []string
+}
+
+func (o *stepWrites) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	o.writes = append(o.writes, fmt.Sprintf("#%s=#%s", hex(addr, 4), hex(val, 4)))
+}
+
+// stepDivergence is the first step after which two machines differ: the
+// instruction each executed and what differs after it.
+type stepDivergence struct {
+	step  int
+	pc    [2]int
+	inst  [2]string
+	diffs []string
+}
+
+// bisectRun steps machines a and b together, up to maxSteps instructions,
+// and returns the first step after which their registers, FR, the memory
+// the step wrote or how they halted differ. When they run the same way to
+// the end it returns nil, the number of steps and how both halted.
+func bisectRun(a, b *Comet2, maxSteps int) (*stepDivergence, int, string) {
+	machines := [2]*Comet2{a, b}
+	var writes [2]*stepWrites
+	for i, c := range machines {
+		writes[i] = &stepWrites{}
+		c.AddObserver(writes[i])
+	}
+
+	for step := 1; step <= maxSteps; step++ {
+		d := &stepDivergence{step: step}
+		var halts [2]string
+		for i, c := range machines {
+			writes[i].writes = writes[i].writes[:0]
+			d.pc[i] = c.State[PC]
+			inst, opr, _ := parse(c.Memory, c.State)
+			d.inst[i] = strings.TrimSpace(inst + "\t" + opr)
+			if r := c.Step(); r.Stopped() {
+				halts[i] = r.Err.Error()
+			}
+		}
+
+		for reg := GR0; reg <= SP; reg++ {
+			if a.State[reg] != b.State[reg] {
+				d.diffs = append(d.diffs, fmt.Sprintf("%s: %s / %s", registerName(reg), formatWord(a.State[reg]), formatWord(b.State[reg])))
+			}
+		}
+		if a.State[FR] != b.State[FR] {
+			d.diffs = append(d.diffs, fmt.Sprintf("FR: %s / %s", formatFR(a.State[FR]), formatFR(b.State[FR])))
+		}
+		if halts[0] == "" && a.State[PC] != b.State[PC] {
+			d.diffs = append(d.diffs, fmt.Sprintf("PR: #%s / #%s", hex(a.State[PC], 4), hex(b.State[PC], 4)))
+		}
+		if wa, wb := strings.Join(writes[0].writes, " "), strings.Join(writes[1].writes, " "); wa != wb {
+			d.diffs = append(d.diffs, fmt.Sprintf("memory written: %s / %s", orNone(wa), orNone(wb)))
+		}
+		if halts[0] != halts[1] {
+			d.diffs = append(d.diffs, fmt.Sprintf("halt: %s / %s", orNone(halts[0]), orNone(halts[1])))
+		}
+		if len(d.diffs) > 0 {
+			return d, step, ""
+		}
+		if halts[0] != "" {
+			return nil, step, halts[0]
+		}
+	}
+	return nil, maxSteps, ErrStepLimit.Error()
+}
+
+// orNone renders an empty difference as "none".
+func orNone(s string) string {
+	if s == "" {
+		return "none"
+	}
+	return s
+}
+
+// runBisectRunCommand implements "c2c2 bisect-run A B": it runs two
+// programs, or one program under two configurations, on the same inputs
+// one instruction at a time and reports the first step after which their
+// registers, flags, memory writes or halts differ, to triage a change of
+// the assembler or of the emulator's semantics. It returns the exit
+// status: 0 when they run the same way, 1 when they diverge.
+func runBisectRunCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("bisect-run", flag.ContinueOnError)
+	fs.SetOutput(w)
+	specA := fs.String("a", "", "settings of the first machine (e.g. \"engine=block trap=overflow mem-init=ones\")")
+	specB := fs.String("b", "", "settings of the second machine")
+	inFile := fs.String("in-file", "", "read IN lines from FILE, one line per IN")
+	maxSteps := fs.Int("max-steps", LOCKSTEP_MAX_STEPS, "stop after N instructions")
+	var inputs []string
+	fs.Func("input", "a line for IN (repeatable)", func(s string) error {
+		inputs = append(inputs, s)
+		return nil
+	})
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 bisect-run [-a SETTINGS] [-b SETTINGS] [-input LINE ...] [-in-file FILE] A [B]\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() < 1 || fs.NArg() > 2 {
+		fs.Usage()
+		return 2
+	}
+	if *inFile != "" {
+		lines, err := readInputFile(*inFile)
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		inputs = append(inputs, lines...)
+	}
+
+	paths := [2]string{fs.Arg(0), fs.Arg(0)}
+	if fs.NArg() == 2 {
+		paths[1] = fs.Arg(1)
+	}
+	var machines [2]*Comet2
+	for i, spec := range []string{*specA, *specB} {
+		var obj *Object
+		var err error
+		if strings.HasSuffix(paths[i], OBJECT_EXT) {
+			obj, err = loadObjectFile(paths[i])
+		} else {
+			obj, err = assemble(paths[i], newAssemblerState())
+		}
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		rc, err := parseRunConfig(spec)
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		if machines[i], err = rc.build(obj, inputs); err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+	}
+
+	d, steps, halt := bisectRun(machines[0], machines[1], *maxSteps)
+	if d == nil {
+		fmt.Fprintf(w, "No divergence in %d steps: %s\n", steps, halt)
+		return 0
+	}
+	fmt.Fprintf(w, "Diverged at step %d\n", d.step)
+	for i, c := range machines {
+		fmt.Fprintf(w, "  %c: %s  %s\n", 'A'+i, c.formatAddr(d.pc[i]), d.inst[i])
+	}
+	for _, diff := range d.diffs {
+		fmt.Fprintf(w, "  %s\n", diff)
+	}
+	return 1
+}
@@ -0,0 +1,47 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestBisectRun(t *testing.T) {
+	dir := t.TempDir()
+	files := map[string]string{
+		"add.cas": "MAIN\tSTART\n\tLAD\tGR1,#7FFF\n\tADDA\tGR1,=1\n\tRET\n\tEND\n",
+		"old.cas": "MAIN\tSTART\n\tIN\tBUF,LEN\n\tLAD\tGR2,1\n\tRET\nBUF\tDS\t4\nLEN\tDS\t1\n\tEND\n",
+		"new.cas": "MAIN\tSTART\n\tIN\tBUF,LEN\n\tLAD\tGR2,2\n\tRET\nBUF\tDS\t4\nLEN\tDS\t1\n\tEND\n",
+	}
+	for name, content := range files {
+		if err := os.WriteFile(filepath.Join(dir, name), []byte(content), 0644); err != nil {
+			t.Fatal(err)
+		}
+	}
+
+	var out bytes.Buffer
+	if status := runBisectRunCommand([]string{"-b", "engine=block", filepath.Join(dir, "add.cas")}, &out); status != 0 ||
+		out.String() != "No divergence in 3 steps: Program finished (RET)\n" {
+		t.Errorf("Expected no divergence between the engines, got %d: %s", status, out.String())
+	}
+
+	out.Reset()
+	if status := runBisectRunCommand([]string{"-b", "trap=overflow", filepath.Join(dir, "add.cas")}, &out); status != 1 ||
+		!strings.HasPrefix(out.String(), "Diverged at step 2\n") || !strings.Contains(out.String(), "  halt: none / Overflow trap at #0002") {
+		t.Errorf("Expected the overflow trap to diverge, got %d: %s", status, out.String())
+	}
+
+	// The LAD after the 7 instructions of the IN macro
+	out.Reset()
+	if status := runBisectRunCommand([]string{"-input", "ab", filepath.Join(dir, "old.cas"), filepath.Join(dir, "new.cas")}, &out); status != 1 ||
+		!strings.HasPrefix(out.String(), "Diverged at step 8\n") || !strings.Contains(out.String(), "  GR2: #0001(     1) / #0002(     2)\n") {
+		t.Errorf("Expected GR2 to diverge, got %d: %s", status, out.String())
+	}
+
+	out.Reset()
+	if status := runBisectRunCommand([]string{"-a", "speed=fast", filepath.Join(dir, "add.cas")}, &out); status != 1 || !strings.Contains(out.String(), "Unknown setting") {
+		t.Errorf("Expected an unknown setting to be refused, got %d: %s", status, out.String())
+	}
+}
@@ -0,0 +1,167 @@
+package main
+
+import "fmt"
+
+// Maximum number of instructions decoded into one block
+const BLOCK_MAX_INSTS = 64
+
+// Number of times the block engine reaches an address before it decodes
+// the block starting there and keeps it
+const BLOCK_HOT_THRESHOLD = 8
+
+// Number of addresses counted towards BLOCK_HOT_THRESHOLD before the
+// counts are started afresh
+const BLOCK_COUNTS_MAX = 4096
+
+// decodedInst is a decoded instruction: its mnemonic and operands as
+// shown, its size, the handler it executes and the fields of its words.
+// The block engine decodes each instruction of a hot block once.
+type decodedInst struct {
+	inst    string
+	opr     string
+	size    int
+	exec    execFunc
+	grForm  bool
+	gr      int
+	xr      int
+	adr     int
+	memAddr bool
+	block   *decodedBlock
+}
+
+// decodedBlock is a straight run of instructions ending at the first
+// control transfer. Writing to any of its words drops the whole block.
+type decodedBlock struct {
+	start int
+	end   int
+}
+
+// blockCache is the "block" engine: once an address has been reached
+// BLOCK_HOT_THRESHOLD times, the block starting there is decoded, with
+// the handler and operand fields of every instruction, and stepExec runs
+// hot loops from it without decoding again. Code run only a few times is
+// decoded on every step as by the interpreter.
+type blockCache struct {
+	insts  map[int]*decodedInst
+	owner  map[int]*decodedBlock
+	counts map[int]int
+}
+
+func newBlockCache() *blockCache {
+	b := &blockCache{}
+	b.reset()
+	return b
+}
+
+func (b *blockCache) reset() {
+	b.insts = make(map[int]*decodedInst)
+	b.owner = make(map[int]*decodedBlock)
+	b.counts = make(map[int]int)
+}
+
+// endsBlock reports whether inst may continue anywhere but the next word.
+func endsBlock(inst string) bool {
+	return instructionUses[inst]&USE_BRANCH != 0
+}
+
+// lookup returns the instruction at pc, decoding a new block once pc
+// turns out to be hot.
+func (b *blockCache) lookup(memory []uint16, pc int) *decodedInst {
+	if d, ok := b.insts[pc]; ok {
+		return d
+	}
+
+	state := make([]int, SP+1)
+	// Addresses run only a few times must not pile up
+	if len(b.counts) >= BLOCK_COUNTS_MAX {
+		clear(b.counts)
+	}
+	b.counts[pc]++
+	if b.counts[pc] < BLOCK_HOT_THRESHOLD {
+		state[PC] = pc
+		d := decodeInst(memory, state)
+		return &d
+	}
+	delete(b.counts, pc)
+
+	block := &decodedBlock{start: pc, end: pc}
+	for n := 0; n < BLOCK_MAX_INSTS; n++ {
+		addr := block.end
+		if _, ok := b.owner[addr]; ok && n > 0 {
+			break
+		}
+		state[PC] = addr
+		d := decodeInst(memory, state)
+		d.block = block
+		b.insts[addr] = &d
+		block.end = addr + d.size
+		if endsBlock(d.inst) {
+			break
+		}
+	}
+	for addr := block.start; addr < block.end; addr++ {
+		if old, ok := b.owner[addr]; ok && old != block {
+			b.drop(old)
+		}
+		b.owner[addr] = block
+	}
+	return b.insts[pc]
+}
+
+// invalidate drops the block containing addr, if any.
+func (b *blockCache) invalidate(addr int) {
+	if block, ok := b.owner[addr]; ok {
+		b.drop(block)
+	}
+}
+
+func (b *blockCache) drop(block *decodedBlock) {
+	for addr := block.start; addr < block.end; addr++ {
+		if b.owner[addr] == block {
+			delete(b.owner, addr)
+		}
+		if d, ok := b.insts[addr]; ok && d.block == block {
+			delete(b.insts, addr)
+		}
+	}
+}
+
+// SetEngine selects how instructions are decoded: "interp" decodes every
+// step, "block" caches decoded blocks.
+func (c *Comet2) SetEngine(name string) error {
+	switch name {
+	case "", "interp":
+		c.blocks = nil
+	case "block":
+		c.blocks = newBlockCache()
+	default:
+		return fmt.Errorf("[COMET2 ERROR] Unknown engine \"%s\"", name)
+	}
+	return nil
+}
+
+// decodeInst decodes the instruction at the PR of state, with the
+// handler its opcode executes, or none for an illegal instruction.
+func decodeInst(memory []uint16, state []int) decodedInst {
+	inst, opr, size := parse(memory, state)
+	word := memGet(memory, state[PC])
+	d := decodedInst{inst: inst, opr: opr, size: size, gr: (word >> 4) & 0xf, xr: word & 0xf}
+	if size == 2 {
+		d.adr = memGet(memory, state[PC]+1)
+	}
+	if def, ok := COMET2TBL[word>>8]; ok {
+		d.exec = def.Exec
+		d.grForm = def.Type == OP5
+		d.memAddr = addressesMemory(inst)
+	}
+	return d
+}
+
+// decode returns the instruction at PR.
+func (c *Comet2) decode() *decodedInst {
+	if c.blocks == nil {
+		d := decodeInst(c.Memory, c.State)
+		return &d
+	}
+	return c.blocks.lookup(c.Memory, c.State[PC])
+}
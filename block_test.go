@@ -0,0 +1,175 @@
+package main
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// runWithEngine runs obj to completion on the given engine.
+func runWithEngine(t *testing.T, obj *Object, engine string, inputs []string) (*Comet2, string) {
+	t.Helper()
+
+	c := NewComet2Builder().
+		WithObject(obj).
+		WithInputs(inputs).
+		WithOutput(nil).
+		WithWarn(nil).
+		WithMaxSteps(1000000).
+		Build()
+	if err := c.SetEngine(engine); err != nil {
+		t.Fatalf("Failed to set engine: %v", err)
+	}
+
+	halt := ""
+	if err := c.Run(); err != nil {
+		halt = err.Error()
+	}
+	return c, halt
+}
+
+func TestBlockEngineMatchesInterpreter(t *testing.T) {
+	inputData, err := os.ReadFile("test/input.json")
+	if err != nil {
+		t.Fatalf("Failed to read input.json: %v", err)
+	}
+	var testInputs TestInput
+	if err := json.Unmarshal(inputData, &testInputs); err != nil {
+		t.Fatalf("Failed to parse input.json: %v", err)
+	}
+
+	casFiles, err := filepath.Glob("test/samples/**/*.cas")
+	if err != nil {
+		t.Fatalf("Failed to glob test files: %v", err)
+	}
+
+	for _, casFile := range casFiles {
+		t.Run(filepath.Base(casFile), func(t *testing.T) {
+			obj, err := assemble(casFile, newAssemblerState())
+			if err != nil {
+				t.Skipf("Does not assemble: %v", err)
+			}
+			inputs := testInputs[filepath.Base(casFile)].Inputs
+
+			ref, refHalt := runWithEngine(t, obj, "interp", inputs)
+			blk, blkHalt := runWithEngine(t, obj, "block", inputs)
+
+			if blkHalt != refHalt {
+				t.Errorf("Halt differs: interp %q, block %q", refHalt, blkHalt)
+			}
+			if blk.Steps != ref.Steps || !reflect.DeepEqual(blk.State, ref.State) {
+				t.Errorf("State differs: interp %v after %d steps, block %v after %d steps", ref.State, ref.Steps, blk.State, blk.Steps)
+			}
+			if !reflect.DeepEqual(blk.Memory, ref.Memory) {
+				t.Errorf("Memory differs")
+			}
+			if len(blk.Transcript) != len(ref.Transcript) {
+				t.Fatalf("Transcript length differs: interp %d, block %d", len(ref.Transcript), len(blk.Transcript))
+			}
+			for i := range ref.Transcript {
+				if blk.Transcript[i].Text != ref.Transcript[i].Text {
+					t.Errorf("Event %d differs: interp %q, block %q", i, ref.Transcript[i].Text, blk.Transcript[i].Text)
+				}
+			}
+		})
+	}
+}
+
+func TestBlockEngineSelfModifyingCode(t *testing.T) {
+	// Once its block is hot, the loop replaces its own "LAD GR1,1,GR1"
+	// with "LAD GR1,1", so a stale block would count to 20
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR2,20
+	LD	GR4,ADR
+LOOP	LAD	GR1,1,GR1
+	CPA	GR2,=10
+	JNZ	NEXT
+	LD	GR3,PATCH
+	ST	GR3,0,GR4
+NEXT	SUBA	GR2,=1
+	JNZ	LOOP
+	RET
+ADR	DC	LOOP
+PATCH	DC	#1210
+	END
+`)
+
+	ref, _ := runWithEngine(t, obj, "interp", nil)
+	blk, _ := runWithEngine(t, obj, "block", nil)
+	if ref.State[GR1] != 1 || blk.State[GR1] != 1 {
+		t.Errorf("Expected GR1 = 1, got %d (interp) and %d (block)", ref.State[GR1], blk.State[GR1])
+	}
+}
+
+func TestBlockEngineHotBlocks(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR2,20
+LOOP	SUBA	GR2,=1
+	JNZ	LOOP
+	RET
+	END
+`)
+	if err := c.SetEngine("block"); err != nil {
+		t.Fatalf("Failed to set engine: %v", err)
+	}
+	step := func(n int) {
+		for i := 0; i < n; i++ {
+			if _, err := c.stepExec(); err != nil {
+				t.Fatalf("Unexpected error: %v", err)
+			}
+		}
+	}
+
+	// LAD, then LOOP one time short of hot
+	step(1 + 2*(BLOCK_HOT_THRESHOLD-1))
+	if len(c.blocks.insts) != 0 {
+		t.Errorf("Expected no block before LOOP is hot, got %d instructions", len(c.blocks.insts))
+	}
+
+	step(1)
+	d, ok := c.blocks.insts[2]
+	if !ok || d.block.start != 2 || d.block.end != 6 {
+		t.Fatalf("Expected the block of SUBA and JNZ at #0002-#0005, got %+v", d)
+	}
+	if d.exec == nil || d.gr != 2 || d.adr != 7 || !d.memAddr {
+		t.Errorf("Expected SUBA GR2,#0007 to be decoded with its handler, got %+v", d)
+	}
+	if _, ok := c.blocks.insts[0]; ok {
+		t.Errorf("Expected LAD, run once, to stay undecoded")
+	}
+	if _, ok := c.blocks.counts[2]; ok || c.blocks.counts[0] != 1 {
+		t.Errorf("Expected only the count of LOOP to be dropped once it is hot, got %v", c.blocks.counts)
+	}
+
+	// Any write into the block drops it
+	c.poke(3, int(c.Memory[3]))
+	if _, ok := c.blocks.insts[2]; ok {
+		t.Errorf("Expected poke to drop the block of LOOP")
+	}
+
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if c.State[GR2] != 0 {
+		t.Errorf("Expected GR2 = 0, got %d", c.State[GR2])
+	}
+}
+
+func TestBlockCountsLimit(t *testing.T) {
+	b := newBlockCache()
+	memory := make([]uint16, BLOCK_COUNTS_MAX+1)
+	for pc := 0; pc < BLOCK_COUNTS_MAX; pc++ {
+		b.lookup(memory, pc)
+	}
+	if len(b.counts) != BLOCK_COUNTS_MAX {
+		t.Fatalf("Expected %d counts, got %d", BLOCK_COUNTS_MAX, len(b.counts))
+	}
+	// Past the limit, counting starts afresh
+	b.lookup(memory, BLOCK_COUNTS_MAX)
+	if len(b.counts) != 1 || b.counts[BLOCK_COUNTS_MAX] != 1 {
+		t.Errorf("Expected the counts to be cleared at the limit, got %d", len(b.counts))
+	}
+}
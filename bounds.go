@@ -0,0 +1,82 @@
+package main
+
+import (
+	"regexp"
+)
+
+// Magic comment on a labeled DS or DC line asking the emulator to check
+// the indexed accesses to it: "BUF DS 16 ;@bounds"
+var boundsCommentPattern = regexp.MustCompile(`(?i)^;\s*@BOUNDS\s*$`)
+
+// BoundedRegion is a DS or DC annotated with ;@bounds. An instruction
+// with adr = Start and an index register must stay from Start to End
+// (exclusive).
+type BoundedRegion struct {
+	Label string `json:"label"`
+	Start int    `json:"start"`
+	End   int    `json:"end"`
+}
+
+// boundsMark is a ;@bounds comment the assembler found on the line of
+// the scoped label.
+type boundsMark struct {
+	line  int
+	label string
+}
+
+// boundedRegions places the ;@bounds comments over the words their lines
+// generated.
+func boundedRegions(asmState *AssemblerState) []BoundedRegion {
+	var regions []BoundedRegion
+	for _, m := range asmState.boundsMarks {
+		start := expandLabel(asmState.symtbl, m.label)
+		size := 0
+		for _, entry := range asmState.memory {
+			if entry.Line == m.line && !entry.Code {
+				size++
+			}
+		}
+		name := m.label
+		if matches := scopedLabelPattern.FindStringSubmatch(name); matches != nil {
+			name = matches[1]
+		}
+		regions = append(regions, BoundedRegion{Label: name, Start: start, End: start + size})
+	}
+	return regions
+}
+
+// boundsChecker halts with Range Over when an indexed access such as
+// LD GR1,BUF,GR2 leaves BUF, a region annotated with ;@bounds. Accesses
+// that do not name the region itself, like LD GR1,0,GR3 with GR3 pointing
+// into it, are not checked.
+type boundsChecker struct {
+	BaseObserver
+	regions map[int]BoundedRegion
+}
+
+func newBoundsChecker(regions []BoundedRegion) *boundsChecker {
+	b := &boundsChecker{regions: make(map[int]BoundedRegion)}
+	for _, r := range regions {
+		b.regions[r.Start] = r
+	}
+	return b
+}
+
+func (b *boundsChecker) BeforeStep(c *Comet2, pc int) {
+	word := memGet(c.Memory, pc)
+	inst, ok := COMET2TBL[word>>8]
+	xr := word & 0xf
+	if !ok || inst.Type != OP1 || !addressesMemory(inst.ID) || xr < 1 || xr > 7 {
+		return
+	}
+	region, ok := b.regions[memGet(c.Memory, pc+1)]
+	if !ok {
+		return
+	}
+	index := c.State[GR0+xr]
+	if eadr := (region.Start + index) & 0xffff; eadr >= region.Start && eadr < region.End {
+		return
+	}
+	c.Halt(runtimeError(RUNTIME_RANGE_OVER, pc, "Range over at %s: %s index GR%d = %d is outside %s (%d words)%s",
+		c.formatAddr(pc), inst.ID, xr, signed(index), region.Label, region.End-region.Start, c.locate(pc)))
+}
@@ -0,0 +1,37 @@
+package main
+
+import (
+	"errors"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestBoundsAnnotation(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR2,0
+LOOP	LD	GR1,BUF,GR2
+	LAD	GR2,1,GR2
+	CPA	GR2,=5
+	JNZ	LOOP
+	RET
+BUF	DC	1,2,3,4	;@bounds
+	END
+`)
+	if want := []BoundedRegion{{Label: "BUF", Start: 11, End: 15}}; !reflect.DeepEqual(obj.Bounds, want) {
+		t.Fatalf("Expected %+v, got %+v", want, obj.Bounds)
+	}
+	c := NewComet2Builder().WithObject(obj).WithObserver(newBoundsChecker(obj.Bounds)).Build()
+	var rerr *RuntimeError
+	err := c.Run()
+	if !errors.As(err, &rerr) || rerr.Kind != RUNTIME_RANGE_OVER || rerr.PC != 2 {
+		t.Fatalf("Expected Range Over at #0002, got %v", err)
+	}
+	if !strings.Contains(err.Error(), "Range over at #0002 <LOOP>: LD index GR2 = 4 is outside BUF (4 words)") || !strings.Contains(err.Error(), "line 3") {
+		t.Errorf("Unexpected message %q", err.Error())
+	}
+
+	if _, err := assembleText("prog.cas", "MAIN\tSTART\n\tRET\t;@bounds\n\tEND\n", newAssemblerState()); err == nil || !strings.Contains(err.Error(), ";@bounds must be on a DS or DC line with a label") {
+		t.Errorf("Expected ;@bounds on RET to be rejected, got %v", err)
+	}
+}
@@ -0,0 +1,179 @@
+package main
+
+import (
+	"fmt"
+	"slices"
+	"strings"
+)
+
+// Breakpoint is one entry of a BreakpointTable.
+type Breakpoint struct {
+	// ID numbers the breakpoint from 1; it is not reused after a delete.
+	ID   int
+	Addr int
+	// Label is the label or LABEL+N the breakpoint was set at, or "" for
+	// a number. Such breakpoints follow the label when the program is
+	// reassembled.
+	Label string
+	// Enabled breakpoints stop the program; disabled ones are kept but
+	// neither stop it nor count hits.
+	Enabled bool
+	// Temporary breakpoints are deleted the first time they stop it.
+	Temporary bool
+	// Hits counts the times the program reached the breakpoint while it
+	// was enabled, including ignored ones.
+	Hits int
+	// Ignore is the number of further hits that do not stop the program.
+	Ignore int
+}
+
+// String describes the breakpoint as info lists it, without the address.
+func (bp *Breakpoint) String() string {
+	s := "enabled"
+	if !bp.Enabled {
+		s = "disabled"
+	}
+	if bp.Temporary {
+		s += ", temporary"
+	}
+	if bp.Hits == 1 {
+		s += ", hit 1 time"
+	} else if bp.Hits > 1 {
+		s += fmt.Sprintf(", hit %d times", bp.Hits)
+	}
+	if bp.Ignore > 0 {
+		s += fmt.Sprintf(", ignore next %d hits", bp.Ignore)
+	}
+	return s
+}
+
+// BreakpointTable holds the breakpoints of a machine. The -break flag, the
+// monitor commands and the control protocol all work on the table of
+// Comet2.Breakpoints, which is kept when a program is reloaded.
+type BreakpointTable struct {
+	list   []*Breakpoint
+	nextID int
+}
+
+func NewBreakpointTable() *BreakpointTable {
+	return &BreakpointTable{nextID: 1}
+}
+
+// Add sets an enabled breakpoint at addr and returns it.
+func (t *BreakpointTable) Add(addr int, temporary bool) *Breakpoint {
+	bp := &Breakpoint{ID: t.nextID, Addr: addr, Enabled: true, Temporary: temporary}
+	t.nextID++
+	t.list = append(t.list, bp)
+	return bp
+}
+
+// Get returns the breakpoint with the given ID, or nil.
+func (t *BreakpointTable) Get(id int) *Breakpoint {
+	for _, bp := range t.list {
+		if bp.ID == id {
+			return bp
+		}
+	}
+	return nil
+}
+
+// Delete removes the breakpoint with the given ID and reports whether it
+// existed.
+func (t *BreakpointTable) Delete(id int) bool {
+	n := len(t.list)
+	t.list = slices.DeleteFunc(t.list, func(bp *Breakpoint) bool { return bp.ID == id })
+	return len(t.list) < n
+}
+
+// Clear removes all breakpoints. IDs keep counting up.
+func (t *BreakpointTable) Clear() {
+	t.list = nil
+}
+
+// All returns the breakpoints in the order they were set.
+func (t *BreakpointTable) All() []*Breakpoint {
+	return t.list
+}
+
+func (t *BreakpointTable) Len() int {
+	return len(t.list)
+}
+
+// Relocate moves the breakpoints set at a label to the address of that
+// label in obj, a reassembled program. It returns the ones that moved and
+// the ones whose label obj no longer has; those are disabled and keep
+// their old address.
+func (t *BreakpointTable) Relocate(obj *Object) (moved []*Breakpoint, lost []*Breakpoint) {
+	for _, bp := range t.list {
+		if bp.Label == "" {
+			continue
+		}
+		addr, ok := lookupAddress(obj, bp.Label)
+		if !ok {
+			if bp.Enabled {
+				bp.Enabled = false
+				lost = append(lost, bp)
+			}
+			continue
+		}
+		if addr != bp.Addr {
+			bp.Addr = addr
+			moved = append(moved, bp)
+		}
+	}
+	return moved, lost
+}
+
+// breakpointLabel returns the label a breakpoint is set at with spec, an
+// address, a label or LABEL+N, or "" for an address.
+func breakpointLabel(spec string) string {
+	if _, ok := expandNumber(spec); ok {
+		return ""
+	}
+	return spec
+}
+
+// hit is called when the program reaches addr. It counts a hit on every
+// enabled breakpoint there and returns the first that stops the program,
+// or nil when all of them ignore this hit. A temporary breakpoint that
+// stops the program is deleted.
+func (t *BreakpointTable) hit(addr int) *Breakpoint {
+	if t == nil {
+		return nil
+	}
+	var stop *Breakpoint
+	for _, bp := range t.list {
+		if bp.Addr != addr || !bp.Enabled {
+			continue
+		}
+		bp.Hits++
+		if bp.Ignore > 0 {
+			bp.Ignore--
+			continue
+		}
+		if stop == nil {
+			stop = bp
+		}
+	}
+	if stop != nil && stop.Temporary {
+		t.Delete(stop.ID)
+	}
+	return stop
+}
+
+// setBreakpoints adds the breakpoints of -break: addresses, labels or
+// LABEL+N separated by commas.
+func setBreakpoints(c *Comet2, spec string) error {
+	for _, s := range strings.Split(spec, ",") {
+		s = strings.TrimSpace(s)
+		if s == "" {
+			continue
+		}
+		addr, ok := parseAddress(c, s)
+		if !ok {
+			return fmt.Errorf("[COMET2 ERROR] Invalid breakpoint address \"%s\"", s)
+		}
+		c.Breakpoints.Add(addr, false).Label = breakpointLabel(s)
+	}
+	return nil
+}
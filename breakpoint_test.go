@@ -0,0 +1,128 @@
+package main
+
+import (
+	"bytes"
+	"fmt"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestBreakpointRelocate(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "loop.cas")
+	write := func(source string) {
+		t.Helper()
+		if err := os.WriteFile(path, []byte(source), 0644); err != nil {
+			t.Fatalf("Failed to write source: %v", err)
+		}
+	}
+	write("MAIN\tSTART\n\tLAD\tGR1,0\nLOOP\tLAD\tGR1,1,GR1\n\tCPA\tGR1,=5\n\tJNZ\tLOOP\nGONE\tRET\n\tEND\n")
+	c := assembleSource(t, "MAIN\tSTART\n\tRET\n\tEND\n")
+	for _, cmd := range [][]string{{"file", path}, {"b", "LOOP"}, {"b", "LOOP+2"}, {"b", "GONE"}, {"b", "#0000"}} {
+		if err := executeCommand(cmd[0], cmd[1:], c); err != nil {
+			t.Fatalf("%s failed: %v", cmd[0], err)
+		}
+	}
+
+	// One more instruction before LOOP, and GONE renamed
+	write("MAIN\tSTART\n\tLAD\tGR1,0\n\tLAD\tGR2,0\nLOOP\tLAD\tGR1,1,GR1\n\tCPA\tGR1,=5\n\tJNZ\tLOOP\nDONE\tRET\n\tEND\n")
+	if err := executeCommand("file", []string{path}, c); err != nil {
+		t.Fatalf("file failed: %v", err)
+	}
+	var got []string
+	for _, bp := range c.Breakpoints.All() {
+		got = append(got, fmt.Sprintf("%s=#%s:%v", bp.Label, hex(bp.Addr, 4), bp.Enabled))
+	}
+	want := []string{"LOOP=#0004:true", "LOOP+2=#0006:true", "GONE=#0008:false", "=#0000:true"}
+	if !reflect.DeepEqual(got, want) {
+		t.Errorf("Expected %v, got %v", want, got)
+	}
+}
+
+func TestBreakpointTable(t *testing.T) {
+	source := `MAIN	START
+	LAD	GR1,0
+LOOP	LAD	GR1,1,GR1
+	CPA	GR1,=5
+	JNZ	LOOP
+	RET
+	END
+`
+	c := assembleSource(t, source)
+
+	// Stop in the third round of the loop, then once more at the temporary
+	// breakpoint on the RET
+	for _, cmd := range [][]string{{"b", "LOOP"}, {"ignore", "1", "2"}, {"tbreak", "LOOP+6"}} {
+		if err := executeCommand(cmd[0], cmd[1:], c); err != nil {
+			t.Fatalf("%s failed: %v", cmd[0], err)
+		}
+	}
+	runMonitor(t, c, "r")
+	if bp := c.Breakpoints.Get(1); c.State[PC] != 2 || c.State[GR1] != 2 || bp.Hits != 3 || bp.Ignore != 0 {
+		t.Errorf("Expected to stop in round 3 at #0002, got PC #%s, GR1 %d, %+v", hex(c.State[PC], 4), c.State[GR1], bp)
+	}
+	if err := executeCommand("disable", []string{"1"}, c); err != nil || c.Breakpoints.Get(1).Enabled {
+		t.Fatalf("disable failed: %v", err)
+	}
+	runMonitor(t, c, "r")
+	if c.State[PC] != 8 || c.State[GR1] != 5 || c.Breakpoints.Get(2) != nil {
+		t.Errorf("Expected to stop once at the RET, got PC #%s, GR1 %d", hex(c.State[PC], 4), c.State[GR1])
+	}
+	if err := executeCommand("enable", []string{"3"}, c); err == nil {
+		t.Errorf("Expected an error for a missing breakpoint")
+	}
+	if err := executeCommand("info", []string{"watch"}, c); err == nil {
+		t.Errorf("Expected an error for an unknown info command")
+	}
+
+	// The table survives LOAD in the control protocol
+	path := filepath.Join(t.TempDir(), "loop.cas")
+	if err := os.WriteFile(path, []byte(source), 0644); err != nil {
+		t.Fatalf("Failed to write source: %v", err)
+	}
+	requests := strings.Join([]string{
+		"LOAD " + path,
+		"BREAK LOOP",
+		"IGNORE 1 1",
+		"CONTINUE",
+		"DISABLE 1",
+		"LOAD " + path,
+		"CONTINUE",
+		"BREAKPOINTS",
+		"DELETE 2",
+	}, "\n")
+	var out bytes.Buffer
+	if err := runControl(strings.NewReader(requests), &out, "", "", "interp"); err != nil {
+		t.Fatalf("Control failed: %v", err)
+	}
+	want := []string{
+		"OK start=#0000 size=",
+		"OK 1 addr=#0002",
+		"OK",
+		"OK BREAK 1 pr=#0002 steps=4",
+		"OK",
+		"OK start=#0000 size=",
+		"OK HALTED Program finished",
+		"OK 1:#0002:d:2:0",
+		"ERR No breakpoint 2",
+	}
+	got := strings.Split(strings.TrimSuffix(out.String(), "\n"), "\n")
+	if len(got) != len(want) {
+		t.Fatalf("Expected %d replies, got %q", len(want), got)
+	}
+	for i := range want {
+		if !strings.HasPrefix(got[i], want[i]) {
+			t.Errorf("Reply %d: expected %q, got %q", i, want[i], got[i])
+		}
+	}
+
+	c = assembleSource(t, source)
+	if err := setBreakpoints(c, "LOOP, 8"); err != nil || c.Breakpoints.Len() != 2 {
+		t.Errorf("Expected two breakpoints, got %d (%v)", c.Breakpoints.Len(), err)
+	}
+	if err := setBreakpoints(c, "NOWHERE"); err == nil {
+		t.Errorf("Expected an error for an unknown label")
+	}
+}
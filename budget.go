@@ -0,0 +1,66 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// budgetRegion is the code a budget of a test case is charged for.
+type budgetRegion struct {
+	label      string
+	start, end int
+	limit      int
+	count      int
+}
+
+// budgetCounter counts the instructions executed in the region of each
+// label of the "budgets" of a test case.
+type budgetCounter struct {
+	BaseObserver
+	regions []*budgetRegion
+}
+
+// newBudgetCounter finds the region of every label of budgets in obj: the
+// whole unit for the name of a START unit, otherwise the words from the
+// label to the next one, as the -cost profile attributes them.
+func newBudgetCounter(obj *Object, budgets map[string]int) (*budgetCounter, error) {
+	labels := make([]string, 0, len(budgets))
+	for label := range budgets {
+		labels = append(labels, label)
+	}
+	sort.Strings(labels)
+
+	b := &budgetCounter{}
+	for _, label := range labels {
+		r, ok := labelRegion(obj, label)
+		if !ok {
+			return nil, fmt.Errorf("unknown label \"%s\" in budgets", label)
+		}
+		b.regions = append(b.regions, &budgetRegion{label: label, start: r[0], end: r[1] + 1, limit: budgets[label]})
+	}
+	return b, nil
+}
+
+func (b *budgetCounter) BeforeStep(c *Comet2, pc int) {
+	for _, r := range b.regions {
+		if pc >= r.start && pc < r.end {
+			r.count++
+		}
+	}
+}
+
+// check reports the labels that executed more instructions than their
+// budget, or nil.
+func (b *budgetCounter) check() error {
+	var over []string
+	for _, r := range b.regions {
+		if r.count > r.limit {
+			over = append(over, fmt.Sprintf("%s executed %d instructions, budget %d", r.label, r.count, r.limit))
+		}
+	}
+	if over != nil {
+		return fmt.Errorf("over budget: %s", strings.Join(over, "; "))
+	}
+	return nil
+}
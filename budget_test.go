@@ -0,0 +1,51 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestSpecBudgets(t *testing.T) {
+	program := `MAIN	START
+	LAD	GR1,3
+	CALL	COUNT
+	RET
+	END
+COUNT	START
+LOOP	SUBA	GR1,ONE
+	JNZ	LOOP
+	RET
+ONE	DC	1
+	END
+`
+	dir := t.TempDir()
+	files := [][2]string{
+		{"spec.json", `{
+			"ok.cas": {"budgets": {"MAIN": 3, "COUNT": 7}},
+			"over.cas": {"budgets": {"LOOP": 6}},
+			"unknown.cas": {"budgets": {"NOPE": 1}}
+		}`},
+	}
+	for _, name := range []string{"ok.cas", "over.cas", "unknown.cas"} {
+		files = append(files, [2]string{name, program}, [2]string{name + ".out", "Program finished (RET)\n"})
+	}
+	for _, f := range files {
+		if err := os.WriteFile(filepath.Join(dir, f[0]), []byte(f[1]), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", f[0], err)
+		}
+	}
+
+	var out bytes.Buffer
+	total, failed, err := runSpec(filepath.Join(dir, "spec.json"), &out, specOptions{jobs: 1})
+	if err != nil || total != 3 || failed != 2 {
+		t.Fatalf("Expected 2 of 3 tests to fail, got %d of %d (%v)\n%s", failed, total, err, out.String())
+	}
+	expected := "ok ok.cas\n" +
+		"FAIL over.cas: over budget: LOOP executed 7 instructions, budget 6\n" +
+		"FAIL unknown.cas: unknown label \"NOPE\" in budgets\n"
+	if out.String() != expected {
+		t.Errorf("Expected %q, got %q", expected, out.String())
+	}
+}
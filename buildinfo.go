@@ -0,0 +1,165 @@
+package main
+
+import (
+	"crypto/sha256"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// Environment variable with a Unix time to record as the assembly time
+// instead of the current time, so that rebuilding gives the same file
+const SOURCE_DATE_EPOCH_ENV = "SOURCE_DATE_EPOCH"
+
+// BuildInfo is the reproducibility header of an object file: which
+// toolchain and options produced it from which source, and when. It is not
+// covered by the checksum.
+type BuildInfo struct {
+	Tool string `json:"tool"`
+	// Options are the flags that change what the assembler produces, such
+	// as -strict-numbers and -define.
+	Options    []string `json:"options,omitempty"`
+	SourceHash string   `json:"sourceHash"`
+	Time       string   `json:"time"`
+}
+
+// newBuildInfo describes an assembly of the given sources with the current
+// flags.
+func newBuildInfo(sources ...string) *BuildInfo {
+	return &BuildInfo{
+		Tool:       "c2c2 " + VERSION,
+		Options:    assemblerOptions(),
+		SourceHash: sourceHash(sources...),
+		Time:       buildTime().Format(time.RFC3339),
+	}
+}
+
+// assemblerOptions lists the flags given that change the assembled program.
+func assemblerOptions() []string {
+	var opts []string
+	if *optStrict {
+		opts = append(opts, "-strict-numbers")
+	}
+	if *optLenient {
+		opts = append(opts, "-lenient-numbers")
+	}
+	if *optCaseSens {
+		opts = append(opts, "-case-sensitive")
+	}
+	if *optStdlib {
+		opts = append(opts, "-with-stdlib")
+	}
+	if *optDefine != "" {
+		opts = append(opts, "-define="+*optDefine)
+	}
+	if *optLitAfter != "" {
+		opts = append(opts, "-literals-after="+*optLitAfter)
+	}
+	return opts
+}
+
+// sourceHash returns the SHA-256 of the sources, one after another, as
+// "sha256:HEX". For a single file it is what sha256sum prints.
+func sourceHash(sources ...string) string {
+	h := sha256.New()
+	for _, s := range sources {
+		io.WriteString(h, s)
+	}
+	return fmt.Sprintf("sha256:%x", h.Sum(nil))
+}
+
+// buildTime is the time to record: $SOURCE_DATE_EPOCH when set, otherwise
+// now.
+func buildTime() time.Time {
+	if s := os.Getenv(SOURCE_DATE_EPOCH_ENV); s != "" {
+		if sec, err := strconv.ParseInt(s, 10, 64); err == nil {
+			return time.Unix(sec, 0).UTC()
+		}
+	}
+	return time.Now().UTC()
+}
+
+// formatBuildInfo renders the header as "Name: value" lines, as shown by
+// "c2c2 info" and at the top of the -a listing.
+func formatBuildInfo(b *BuildInfo) []string {
+	opts := strings.Join(b.Options, " ")
+	if opts == "" {
+		opts = "(defaults)"
+	}
+	return []string{
+		"Tool:      " + b.Tool,
+		"Options:   " + opts,
+		"Source:    " + b.SourceHash,
+		"Assembled: " + b.Time,
+	}
+}
+
+// runInfoCommand implements "c2c2 info". It prints the reproducibility
+// header of an object file and its checksum and, with -source, checks
+// that the source file is the one it was assembled from. It returns the
+// exit status.
+func runInfoCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("info", flag.ContinueOnError)
+	fs.SetOutput(w)
+	source := fs.String("source", "", "check that the object was assembled from the given casl2 source")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 info [-source FILE.cas] FILE%s\n\nOptions:\n", OBJECT_EXT)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 1 {
+		fs.Usage()
+		return 2
+	}
+
+	path := fs.Arg(0)
+	obj, err := readObjectFile(path)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+
+	status := 0
+	fmt.Fprintf(w, "File:      %s\n", path)
+	if obj.Source != "" {
+		fmt.Fprintf(w, "Program:   %s\n", obj.Source)
+	}
+	if obj.Build == nil {
+		fmt.Fprintln(w, "Build:     not recorded")
+	} else {
+		for _, line := range formatBuildInfo(obj.Build) {
+			fmt.Fprintln(w, line)
+		}
+	}
+	if err := verifyChecksum(obj); err != nil {
+		fmt.Fprintf(w, "Checksum:  %s (MISMATCH)\n", obj.Checksum)
+		status = 1
+	} else if obj.Checksum != "" {
+		fmt.Fprintf(w, "Checksum:  %s (OK)\n", obj.Checksum)
+	}
+
+	if *source != "" {
+		data, err := os.ReadFile(*source)
+		if err != nil {
+			fmt.Fprintf(w, "[CASL2 ERROR] Cannot read file: %v\n", err)
+			return 1
+		}
+		switch {
+		case obj.Build == nil:
+			fmt.Fprintf(w, "Source:    %s cannot be checked without a build header\n", *source)
+			status = 1
+		case sourceHash(string(data)) == obj.Build.SourceHash:
+			fmt.Fprintf(w, "Source:    %s matches\n", *source)
+		default:
+			fmt.Fprintf(w, "Source:    %s does NOT match (%s)\n", *source, sourceHash(string(data)))
+			status = 1
+		}
+	}
+	return status
+}
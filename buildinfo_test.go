@@ -0,0 +1,56 @@
+package main
+
+import (
+	"bytes"
+	"crypto/sha256"
+	"fmt"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestBuildInfo(t *testing.T) {
+	t.Setenv(SOURCE_DATE_EPOCH_ENV, "1700000000")
+	*optStrict = true
+	defer func() { *optStrict = false }()
+
+	dir := t.TempDir()
+	source := "MAIN\tSTART\n\tRET\n\tEND\n"
+	src := filepath.Join(dir, "prog.cas")
+	os.WriteFile(src, []byte(source), 0644)
+	obj, err := assemble(src, newAssemblerState())
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+	want := &BuildInfo{
+		Tool:       "c2c2 " + VERSION,
+		Options:    []string{"-strict-numbers"},
+		SourceHash: fmt.Sprintf("sha256:%x", sha256.Sum256([]byte(source))),
+		Time:       "2023-11-14T22:13:20Z",
+	}
+	if !reflect.DeepEqual(obj.Build, want) {
+		t.Errorf("Expected %+v, got %+v", want, obj.Build)
+	}
+
+	path := filepath.Join(dir, "prog.c2o")
+	if err := writeObjectFile(path, obj); err != nil {
+		t.Fatalf("writeObjectFile: %v", err)
+	}
+	var out bytes.Buffer
+	if status := runInfoCommand([]string{"-source", src, path}, &out); status != 0 {
+		t.Fatalf("Expected info to succeed, got %d:\n%s", status, out.String())
+	}
+	for _, line := range []string{"Options:   -strict-numbers\n", "Assembled: 2023-11-14T22:13:20Z\n", "Checksum:  sha256:", "matches\n"} {
+		if !strings.Contains(out.String(), line) {
+			t.Errorf("Expected %q in\n%s", line, out.String())
+		}
+	}
+
+	os.WriteFile(src, []byte(source+"; changed\n"), 0644)
+	out.Reset()
+	if status := runInfoCommand([]string{"-source", src, path}, &out); status != 1 || !strings.Contains(out.String(), "does NOT match") {
+		t.Errorf("Expected a source mismatch, got %d:\n%s", status, out.String())
+	}
+}
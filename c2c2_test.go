@@ -1,6 +1,7 @@
 package main
 
 import (
+	"context"
 	"encoding/json"
 	"io/ioutil"
 	"os"
@@ -10,8 +11,8 @@ import (
 	"testing"
 )
 
-// Test input configuration
-type TestInput map[string][]string
+// Test input configuration: the inputs and compare mode of each sample
+type TestInput map[string]TestCase
 
 func TestC2C2Samples(t *testing.T) {
 	// Read input.json
@@ -33,6 +34,7 @@ func TestC2C2Samples(t *testing.T) {
 
 	for _, casFile := range casFiles {
 		t.Run(filepath.Base(casFile), func(t *testing.T) {
+			t.Parallel()
 			testSample(t, casFile, testInputs)
 		})
 	}
@@ -57,13 +59,17 @@ func testSample(t *testing.T, casFile string, testInputs TestInput) {
 
 	// Build command arguments
 	args := []string{"-n", "-q", "-r", casFile}
-	if inputs, ok := testInputs[baseName]; ok {
-		args = append(args, inputs...)
-	}
+	tc := testInputs[baseName]
+	args = append(args, tc.Inputs...)
 
 	// Execute c2c2
-	cmd := exec.Command("./c2c2", args...)
+	ctx, cancel := context.WithTimeout(context.Background(), TEST_TIMEOUT)
+	defer cancel()
+	cmd := exec.CommandContext(ctx, "./c2c2", args...)
 	output, err := cmd.CombinedOutput()
+	if ctx.Err() != nil {
+		t.Fatalf("Timed out after %v", TEST_TIMEOUT)
+	}
 	
 	// Check for errors (but allow "Program finished" errors)
 	if err != nil {
@@ -75,7 +81,11 @@ func testSample(t *testing.T, casFile string, testInputs TestInput) {
 	actual := string(output)
 
 	// Compare outputs
-	if actual != expected {
+	matched, err := compareOutput(tc.Compare, expected, actual)
+	if err != nil {
+		t.Fatalf("Invalid test spec for %s: %v", baseName, err)
+	}
+	if !matched {
 		t.Errorf("Output mismatch for %s\nExpected:\n%s\nActual:\n%s", baseName, expected, actual)
 		
 		// Show diff
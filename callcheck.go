@@ -0,0 +1,74 @@
+package main
+
+// callFrame is a subroutine call that has not returned yet.
+type callFrame struct {
+	sp     int
+	target int
+}
+
+// callChecker reports RETs that do not match their CALL: a RET that pops
+// a word pushed by PUSH, or a subroutine returning with SP different from
+// its entry (the classic forgotten POP).
+type callChecker struct {
+	BaseObserver
+	strict   bool
+	inst     string
+	frames   []callFrame
+	pushedBy map[int]int
+}
+
+func newCallChecker(strict bool) *callChecker {
+	return &callChecker{strict: strict, pushedBy: make(map[int]int)}
+}
+
+func (t *callChecker) BeforeStep(c *Comet2, pc int) {
+	t.inst, _, _ = parse(c.Memory, c.State)
+	if t.inst == "RET" {
+		t.checkRet(c, pc)
+	}
+}
+
+func (t *callChecker) AfterStep(c *Comet2, pc int) {
+	sp := c.State[SP]
+	switch t.inst {
+	case "CALL":
+		t.frames = append(t.frames, callFrame{sp: sp, target: c.State[PC]})
+		delete(t.pushedBy, sp)
+	case "PUSH":
+		t.pushedBy[sp] = pc
+	}
+}
+
+// checkRet compares SP at a RET with the innermost open CALL and drops
+// the frames the RET leaves.
+func (t *callChecker) checkRet(c *Comet2, pc int) {
+	sp := c.State[SP]
+	name, entry := "The program", c.StackTop
+	if len(t.frames) > 0 {
+		frame := t.frames[len(t.frames)-1]
+		entry = frame.sp
+		name = "Subroutine " + c.formatAddr(frame.target)
+	}
+	for len(t.frames) > 0 && t.frames[len(t.frames)-1].sp <= sp {
+		t.frames = t.frames[:len(t.frames)-1]
+	}
+	if sp == entry {
+		return
+	}
+
+	var err error
+	if pusher, ok := t.pushedBy[sp]; ok {
+		err = runtimeError(RUNTIME_CALL_MISMATCH, pc,
+			"%s returns at %s to a word pushed by PUSH at %s%s (SP = #%s, expected #%s)",
+			name, c.formatAddr(pc), c.formatAddr(pusher), c.locate(pusher), hex(sp, 4), hex(entry, 4))
+	} else {
+		err = runtimeError(RUNTIME_CALL_MISMATCH, pc,
+			"%s returns at %s with SP = #%s, expected #%s%s",
+			name, c.formatAddr(pc), hex(sp, 4), hex(entry, 4), c.locate(pc))
+	}
+	if t.strict {
+		c.Halt(err)
+		return
+	}
+	c.warn(err.Error())
+}
@@ -0,0 +1,49 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+)
+
+func TestCallChecker(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	CALL	SUB
+	RET
+SUB	PUSH	0,GR1
+	RET
+	END
+`)
+	c.AddObserver(newCallChecker(true))
+
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_CALL_MISMATCH {
+		t.Fatalf("Expected call mismatch, got %v", err)
+	}
+	if !strings.Contains(rerr.Msg, "<SUB>") || !strings.Contains(rerr.Msg, "pushed by PUSH") {
+		t.Errorf("Unexpected message: %s", rerr.Msg)
+	}
+}
+
+func TestCallCheckerWarns(t *testing.T) {
+	// SUB returns through a pushed FIN, which then returns from SUB
+	c := assembleSource(t, `MAIN	START
+	CALL	SUB
+	RET
+SUB	LAD	GR1,FIN
+	PUSH	0,GR1
+	RET
+FIN	RET
+	END
+`)
+	c.AddObserver(newCallChecker(false))
+	var warnings []string
+	c.Warn = func(msg string) { warnings = append(warnings, msg) }
+
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if len(warnings) != 1 || !strings.Contains(warnings[0], "<SUB>") || !strings.Contains(warnings[0], "pushed by PUSH") {
+		t.Errorf("Expected one warning about SUB, got %v", warnings)
+	}
+}
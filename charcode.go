@@ -0,0 +1,93 @@
+package main
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+// Names of the ASCII control characters #00-#1F
+var controlNames = [32]string{
+	"NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL",
+	"BS", "HT", "LF", "VT", "FF", "CR", "SO", "SI",
+	"DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB",
+	"CAN", "EM", "SUB", "ESC", "FS", "GS", "RS", "US",
+}
+
+// charName names the character of a word as OUT would write its low byte:
+// 'A' when printable, the ASCII name of a control character, or "" above
+// #7F.
+func charName(val int) string {
+	ch := val & 0xff
+	switch {
+	case ch < 0x20:
+		return controlNames[ch]
+	case ch == 0x20:
+		return "' ' (SP)"
+	case ch == 0x7f:
+		return "DEL"
+	case ch > 0x7f:
+		return ""
+	}
+	return fmt.Sprintf("'%c'", rune(ch))
+}
+
+// cmdChar shows the character of each VALUE: a decimal number, #XXXX or
+// 0xXXXX.
+func cmdChar(c *Comet2, args []string) error {
+	if len(args) == 0 {
+		return fmt.Errorf("Usage: char VALUE ...")
+	}
+	for _, arg := range args {
+		val, ok := expandNumber(arg)
+		if !ok && (strings.HasPrefix(arg, "0x") || strings.HasPrefix(arg, "0X")) {
+			val, ok = expandNumber("#" + arg[2:])
+		}
+		if !ok {
+			return fmt.Errorf("Invalid value \"%s\".", arg)
+		}
+		name := charName(val)
+		if name == "" {
+			name = "not an ASCII character"
+		} else if val > 0xff {
+			name += " (low byte)"
+		}
+		cometPrint(fmt.Sprintf("%s: %s", formatWord(val), name))
+	}
+	return nil
+}
+
+// cmdCode shows the code of each character of TEXT, written like a DC
+// string ('A', with '' for a quote) or as is.
+func cmdCode(c *Comet2, args []string) error {
+	// The command line is split at spaces, which may be part of the text
+	text := strings.Join(args, " ")
+	if len(text) >= 2 && strings.HasPrefix(text, "'") && strings.HasSuffix(text, "'") {
+		text = strings.ReplaceAll(text[1:len(text)-1], "''", "'")
+	}
+	if text == "" {
+		return fmt.Errorf("Usage: code 'TEXT'")
+	}
+	for _, r := range text {
+		if r > 0x7e {
+			return fmt.Errorf("Not an ASCII character: %s.", strconv.QuoteRune(r))
+		}
+		cometPrint(fmt.Sprintf("%s: %s", charName(int(r)), formatWord(int(r))))
+	}
+	return nil
+}
+
+// cmdAscii prints the printable characters #20-#7E as a table with the
+// high digit of the code in rows and the low digit in columns.
+func cmdAscii(c *Comet2, args []string) error {
+	cometPrint("     0 1 2 3 4 5 6 7 8 9 A B C D E F")
+	for high := 0x20; high < 0x80; high += 0x10 {
+		var b strings.Builder
+		fmt.Fprintf(&b, "#%s ", hex(high, 2))
+		for ch := high; ch < high+0x10 && ch < 0x7f; ch++ {
+			fmt.Fprintf(&b, " %c", rune(ch))
+		}
+		cometPrint(strings.TrimRight(b.String(), " "))
+	}
+	return nil
+}
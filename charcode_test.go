@@ -0,0 +1,43 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestCharCommands(t *testing.T) {
+	c := assembleSource(t, "MAIN\tSTART\n\tRET\n\tEND\n")
+	for _, tc := range []struct {
+		val  int
+		want string
+	}{
+		{0x41, "'A'"},
+		{0x20, "' ' (SP)"},
+		{0x0a, "LF"},
+		{0x7f, "DEL"},
+		{0x141, "'A'"},
+		{0xa4, ""},
+	} {
+		if got := charName(tc.val); got != tc.want {
+			t.Errorf("charName(#%s): expected %q, got %q", hex(tc.val, 4), tc.want, got)
+		}
+	}
+	for _, args := range [][]string{{"65", "#41", "0x41"}, {"-1"}} {
+		if err := executeCommand("char", args, c); err != nil {
+			t.Errorf("char %v failed: %v", args, err)
+		}
+	}
+	if err := executeCommand("char", []string{"A"}, c); err == nil {
+		t.Errorf("Expected an error for an invalid value")
+	}
+	for _, args := range [][]string{{"'A", "B'"}, {"''''"}, {"xyz"}} {
+		if err := executeCommand("code", args, c); err != nil {
+			t.Errorf("code %v failed: %v", args, err)
+		}
+	}
+	if err := executeCommand("code", []string{"''"}, c); err == nil {
+		t.Errorf("Expected an error for an empty string")
+	}
+	if err := executeCommand("ascii", nil, c); err != nil {
+		t.Errorf("ascii failed: %v", err)
+	}
+}
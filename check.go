@@ -0,0 +1,109 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"os"
+	"sort"
+)
+
+// checkResult is the outcome of checking one source file.
+type checkResult struct {
+	File        string       `json:"file"`
+	OK          bool         `json:"ok"`
+	Diagnostics []Diagnostic `json:"diagnostics"`
+}
+
+// checkFile parses, resolves and assembles path without running or
+// writing anything. Problems in the source are returned as diagnostics;
+// the error is only for a file that cannot be read.
+func checkFile(path string) ([]Diagnostic, error) {
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("[CASL2 ERROR] Cannot read file: %v", err)
+	}
+	source := string(content)
+	diags := append([]Diagnostic{}, NewAnalysis(source).Diagnostics...)
+
+	// The assembler stops at its first error; keep it unless the analysis
+	// already reported that line
+	asmState := newAssemblerState()
+	if _, err := assembleText(path, source, asmState); err != nil {
+		var aerr *AssembleError
+		if !errors.As(err, &aerr) {
+			return nil, err
+		}
+		reported := false
+		for _, d := range diags {
+			reported = reported || d.Line == aerr.Line
+		}
+		if !reported {
+			diags = append(diags, Diagnostic{Line: aerr.Line, Msg: aerr.Msg, Code: aerr.Code})
+		}
+	}
+	diags = append(diags, asmState.warnings...)
+	sort.SliceStable(diags, func(i, j int) bool {
+		return diags[i].Line < diags[j].Line
+	})
+	return diags, nil
+}
+
+// runCheck implements -check for the given files and prints the
+// diagnostics as "FILE:LINE[:COLUMN]: MESSAGE" lines with a summary, or as
+// JSON. It returns the exit status: 0 when no file has an error; warnings
+// are printed but do not count.
+func runCheck(paths []string, format string, w io.Writer) int {
+	if format != "text" && format != "json" {
+		fmt.Fprintf(w, "[CASL2 ERROR] Invalid -check-format \"%s\"\n", format)
+		return 2
+	}
+
+	status := 0
+	errorCount := 0
+	results := make([]checkResult, 0, len(paths))
+	for _, path := range paths {
+		diags, err := checkFile(path)
+		if err != nil {
+			diags = []Diagnostic{{Msg: err.Error()}}
+		}
+		if diags == nil {
+			diags = []Diagnostic{}
+		}
+		fileErrors := 0
+		for _, d := range diags {
+			if d.Severity != "warning" {
+				fileErrors++
+			}
+		}
+		if fileErrors > 0 {
+			status = 1
+		}
+		errorCount += fileErrors
+		results = append(results, checkResult{File: path, OK: fileErrors == 0, Diagnostics: diags})
+	}
+
+	if format == "json" {
+		data, _ := json.MarshalIndent(results, "", "  ")
+		fmt.Fprintln(w, string(data))
+		return status
+	}
+	for _, r := range results {
+		for _, d := range r.Diagnostics {
+			if d.Severity == "warning" {
+				d.Msg = fmt.Sprintf("warning: %s [%s]", d.Msg, d.Code)
+			}
+			switch {
+			case d.Line == 0:
+				fmt.Fprintf(w, "%s: %s\n", r.File, d.Msg)
+			case d.Column > 0:
+				fmt.Fprintf(w, "%s:%d:%d: %s\n", r.File, d.Line, d.Column, d.Msg)
+			default:
+				fmt.Fprintf(w, "%s:%d: %s\n", r.File, d.Line, d.Msg)
+			}
+		}
+	}
+	fmt.Fprintf(w, "%d files checked, %d errors\n", len(results), errorCount)
+	return status
+}
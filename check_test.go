@@ -0,0 +1,36 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestCheck(t *testing.T) {
+	dir := t.TempDir()
+	good := filepath.Join(dir, "good.cas")
+	bad := filepath.Join(dir, "bad.cas")
+	os.WriteFile(good, []byte(echoProgram), 0644)
+	os.WriteFile(bad, []byte("MAIN\tSTART\n\tLD\tGR1,NOPE\n\tRET\n\tEND\n"), 0644)
+
+	var out bytes.Buffer
+	if status := runCheck([]string{good}, "text", &out); status != 0 || out.String() != "1 files checked, 0 errors\n" {
+		t.Errorf("Expected a clean check, got %d: %s", status, out.String())
+	}
+
+	out.Reset()
+	if status := runCheck([]string{good, bad}, "text", &out); status != 1 ||
+		!strings.Contains(out.String(), bad+":2: Label \"NOPE\" is not defined\n") {
+		t.Errorf("Expected an undefined label error, got %d: %s", status, out.String())
+	}
+
+	out.Reset()
+	runCheck([]string{bad}, "json", &out)
+	var results []checkResult
+	if err := json.Unmarshal(out.Bytes(), &results); err != nil || len(results) != 1 || results[0].OK || len(results[0].Diagnostics) != 1 {
+		t.Errorf("Unexpected JSON result %s (%v)", out.String(), err)
+	}
+}
@@ -0,0 +1,155 @@
+package main
+
+import (
+	"encoding/binary"
+	"encoding/json"
+	"fmt"
+	"maps"
+	"os"
+	"path/filepath"
+	"strings"
+)
+
+// Extension of checkpoint files written by the monitor
+const CHECKPOINT_EXT = ".c2cp"
+
+// checkpoint is a saved machine state. Symbols identify the program it
+// was taken from; the memory image is stored as big-endian words.
+type checkpoint struct {
+	Version     string         `json:"version"`
+	Steps       int            `json:"steps"`
+	State       []int          `json:"state"`
+	Memory      []byte         `json:"memory"`
+	Transcript  []IoEvent      `json:"transcript,omitempty"`
+	OutputBytes int            `json:"outputBytes,omitempty"`
+	OutputLines int            `json:"outputLines,omitempty"`
+	ExitStatus  *int           `json:"exitStatus,omitempty"`
+	StackDepth  int            `json:"stackDepth,omitempty"`
+	Symbols     map[string]int `json:"symbols,omitempty"`
+}
+
+// checkpointPath returns the file of the checkpoint NAME in the current
+// directory. CHECKPOINT_EXT is always added, so that "checkpoint prog.cas"
+// cannot overwrite the source; a NAME with a path separator is refused.
+func checkpointPath(name string) (string, error) {
+	if name == "" || strings.ContainsAny(name, `/\`) || name == "." || name == ".." {
+		return "", fmt.Errorf("Invalid checkpoint name %q: give a name without a directory.", name)
+	}
+	if strings.HasSuffix(name, CHECKPOINT_EXT) {
+		return name, nil
+	}
+	return name + CHECKPOINT_EXT, nil
+}
+
+// takeCheckpoint captures the state of c.
+func takeCheckpoint(c *Comet2) *checkpoint {
+	cp := &checkpoint{
+		Version:     VERSION,
+		Steps:       c.Steps,
+		State:       append([]int{}, c.State...),
+		Memory:      encodeWords(c.Memory, binary.BigEndian),
+		Transcript:  append([]IoEvent{}, c.Transcript...),
+		OutputBytes: c.outputBytes,
+		OutputLines: c.outputLines,
+		ExitStatus:  c.ExitStatus,
+		StackDepth:  c.stackDepth,
+		Symbols:     c.Symbols,
+	}
+	return cp
+}
+
+// restoreCheckpoint puts c back in the state of cp. Undo entries from
+// before the restore no longer apply and are dropped.
+func restoreCheckpoint(c *Comet2, cp *checkpoint) error {
+	if len(cp.Memory) != 2*len(c.Memory) || len(cp.State) != len(c.State) {
+		return fmt.Errorf("The checkpoint does not match the size of this machine.")
+	}
+	memory, err := decodeWords(cp.Memory, binary.BigEndian)
+	if err != nil {
+		return fmt.Errorf("The checkpoint memory is damaged: %v", err)
+	}
+	copy(c.Memory, memory)
+	copy(c.State, cp.State)
+	c.Steps = cp.Steps
+	c.Transcript = append(c.Transcript[:0], cp.Transcript...)
+	c.outputBytes = cp.OutputBytes
+	c.outputLines = cp.OutputLines
+	c.ExitStatus = cp.ExitStatus
+	c.stackDepth = cp.StackDepth
+	c.InputMode = INPUT_MODE_CMD
+	c.LastFlagChange = nil
+	c.OutputTruncated = false
+	c.haltErr = nil
+	if c.blocks != nil {
+		c.blocks.reset()
+	}
+	for _, o := range c.observers {
+		if log, ok := o.(*undoLog); ok {
+			log.entries = log.entries[:0]
+		}
+	}
+	return nil
+}
+
+// cmdCheckpoint saves the machine state to the file NAME.c2cp, or lists
+// the checkpoint files in the current directory.
+func cmdCheckpoint(c *Comet2, args []string) error {
+	if len(args) == 0 {
+		files, _ := filepath.Glob("*" + CHECKPOINT_EXT)
+		if len(files) == 0 {
+			cometPrint("No checkpoints.")
+		}
+		for _, f := range files {
+			cometPrint(strings.TrimSuffix(f, CHECKPOINT_EXT))
+		}
+		return nil
+	}
+	if len(args) != 1 {
+		return fmt.Errorf("Usage: checkpoint [NAME]")
+	}
+
+	path, err := checkpointPath(args[0])
+	if err != nil {
+		return err
+	}
+	data, err := json.Marshal(takeCheckpoint(c))
+	if err != nil {
+		return err
+	}
+	if err := os.WriteFile(path, data, 0644); err != nil {
+		return fmt.Errorf("Cannot write checkpoint: %v", err)
+	}
+	cometPrint(fmt.Sprintf("Checkpoint %s saved at step %d, PR=#%s.", path, c.Steps, hex(c.State[PC], 4)))
+	return nil
+}
+
+// cmdRestore loads the machine state saved by checkpoint NAME.
+func cmdRestore(c *Comet2, args []string) error {
+	if len(args) != 1 {
+		return fmt.Errorf("Usage: restore NAME")
+	}
+
+	path, err := checkpointPath(args[0])
+	if err != nil {
+		return err
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return fmt.Errorf("Cannot read checkpoint: %v", err)
+	}
+	var cp checkpoint
+	if err := json.Unmarshal(data, &cp); err != nil {
+		return fmt.Errorf("Invalid checkpoint %s: %v", path, err)
+	}
+	if err := restoreCheckpoint(c, &cp); err != nil {
+		return err
+	}
+	if !maps.Equal(cp.Symbols, c.Symbols) {
+		cometWarn("Warning: the checkpoint was saved with a different program; the labels may not match.")
+	}
+	cometPrint(fmt.Sprintf("Restored %s: step %d, PR=#%s.", path, c.Steps, hex(c.State[PC], 4)))
+	if verbosity.dump {
+		cmdPrint(c, []string{})
+	}
+	return nil
+}
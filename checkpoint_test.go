@@ -0,0 +1,110 @@
+package main
+
+import (
+	"os"
+	"testing"
+)
+
+func TestCheckpoint(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,5
+	ST	GR1,A
+	LAD	GR1,7
+	ST	GR1,A
+	RET
+A	DS	1
+	END
+`)
+	step := func(n int) {
+		for i := 0; i < n; i++ {
+			if _, err := c.stepExec(); err != nil {
+				t.Fatalf("step %d: %v", i, err)
+			}
+		}
+	}
+	t.Chdir(t.TempDir())
+	name := "demo"
+
+	step(2)
+	if err := executeCommand("checkpoint", []string{name}, c); err != nil {
+		t.Fatalf("checkpoint failed: %v", err)
+	}
+	if _, err := os.Stat(name + CHECKPOINT_EXT); err != nil {
+		t.Fatalf("Expected a checkpoint file: %v", err)
+	}
+	step(2)
+	if c.Memory[9] != 7 {
+		t.Fatalf("Expected A = 7, got %d", c.Memory[9])
+	}
+
+	if err := executeCommand("restore", []string{name}, c); err != nil {
+		t.Fatalf("restore failed: %v", err)
+	}
+	if c.State[PC] != 4 || c.State[GR1] != 5 || c.Memory[9] != 5 || c.Steps != 2 {
+		t.Errorf("Expected the state after the first ST, got PC #%s GR1 %d A %d steps %d",
+			hex(c.State[PC], 4), c.State[GR1], c.Memory[9], c.Steps)
+	}
+	if err := executeCommand("restore", []string{name + "-missing"}, c); err == nil {
+		t.Errorf("Expected a missing checkpoint to fail")
+	}
+}
+
+func TestCheckpointPath(t *testing.T) {
+	t.Chdir(t.TempDir())
+	if err := os.WriteFile("prog.cas", []byte("source"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	c := assembleSource(t, echoProgram)
+	if err := executeCommand("checkpoint", []string{"prog.cas"}, c); err != nil {
+		t.Fatalf("checkpoint failed: %v", err)
+	}
+	if data, _ := os.ReadFile("prog.cas"); string(data) != "source" {
+		t.Errorf("Expected prog.cas to be left alone, got %q", data)
+	}
+	if _, err := os.Stat("prog.cas" + CHECKPOINT_EXT); err != nil {
+		t.Errorf("Expected prog.cas%s: %v", CHECKPOINT_EXT, err)
+	}
+	if err := executeCommand("restore", []string{"prog.cas"}, c); err != nil {
+		t.Errorf("restore failed: %v", err)
+	}
+
+	for _, name := range []string{"../x", "sub/x", `sub\x`, ".."} {
+		if err := executeCommand("checkpoint", []string{name}, c); err == nil {
+			t.Errorf("Expected %q to be refused", name)
+		}
+	}
+}
+
+func TestCheckpointExitAndStack(t *testing.T) {
+	t.Chdir(t.TempDir())
+	c := assembleSource(t, echoProgram)
+	status := 3
+	c.ExitStatus = &status
+	c.stackDepth = 5
+	if err := executeCommand("checkpoint", []string{"done"}, c); err != nil {
+		t.Fatalf("checkpoint failed: %v", err)
+	}
+
+	c.ExitStatus = nil
+	c.stackDepth = 0
+	if err := executeCommand("restore", []string{"done"}, c); err != nil {
+		t.Fatalf("restore failed: %v", err)
+	}
+	if c.ExitStatus == nil || *c.ExitStatus != 3 || c.stackDepth != 5 {
+		t.Errorf("Expected exit status 3 and stack depth 5, got %v and %d", c.ExitStatus, c.stackDepth)
+	}
+
+	// A checkpoint before the exit SVC restores the lack of one
+	c.ExitStatus = nil
+	c.stackDepth = 0
+	if err := executeCommand("checkpoint", []string{"running"}, c); err != nil {
+		t.Fatalf("checkpoint failed: %v", err)
+	}
+	c.ExitStatus = &status
+	if err := executeCommand("restore", []string{"running"}, c); err != nil {
+		t.Fatalf("restore failed: %v", err)
+	}
+	if c.ExitStatus != nil {
+		t.Errorf("Expected no exit status, got %d", *c.ExitStatus)
+	}
+}
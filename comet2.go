@@ -0,0 +1,782 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"strings"
+	"sync/atomic"
+	"time"
+)
+
+// ErrCancelled is returned when execution is stopped through Cancel.
+var ErrCancelled = errors.New("Execution cancelled")
+
+// ErrStepLimit is returned when Run reaches MaxSteps.
+var ErrStepLimit = errors.New("Step limit exceeded")
+
+// ErrInputExhausted is returned by Run when FailOnInputWait is set and IN
+// finds no more lines in Input.
+var ErrInputExhausted = errors.New("Program requested more input than provided")
+
+// SvcHandler implements an SVC entry. It may change GR registers, FR and
+// memory; PR advances past the SVC afterwards.
+type SvcHandler func(c *Comet2) error
+
+// Comet2 holds the state of a single COMET2 machine.
+type Comet2 struct {
+	Memory     []uint16
+	State      []int
+	InputMode  int
+	AddressMax int
+	StackTop   int
+
+	// Output receives the text written by OUT. When nil, OUT is only
+	// recorded in Transcript.
+	Output func(text string)
+	// Input supplies lines to IN during Run. When nil, or when it runs
+	// dry, Run returns with InputMode set to INPUT_MODE_IN.
+	Input InputSource
+
+	// Steps is the number of instructions executed so far.
+	Steps int
+	// Transcript records every IN and OUT in the order they happened.
+	Transcript []IoEvent
+	// SourceMap maps addresses to source lines for error messages.
+	SourceMap []SourceLocation
+	// Regions tells code from data for the disassembler and memory dumps.
+	// It is empty for objects without that information.
+	Regions []Region
+	// Symbols maps scoped labels to addresses for annotating addresses.
+	Symbols map[string]int
+	// TrapOverflow halts execution when an instruction sets OF.
+	TrapOverflow bool
+	// TrapRange halts execution when adr + GR(x) of a memory access or
+	// jump wraps past #FFFF or falls outside Memory. Without it the
+	// address wraps silently.
+	TrapRange bool
+	// TrapProtect halts execution on ST into the SVC vector area
+	// (SVC_VECTOR_BASE to #FFFF); TrapProtectStack also protects the
+	// stack area between the program and StackTop.
+	TrapProtect      bool
+	TrapProtectStack bool
+	// MemInit gives the words of memory not loaded from the program.
+	MemInit MemFill
+	// Args are the program arguments written at ARGS_BASE on every load.
+	Args []string
+	// InOverflow is what IN does with a line longer than IN_MAX_LENGTH
+	// characters. The first IN_MAX_LENGTH are always stored; "" or
+	// "truncate" drops the rest silently, "warn" also reports it and
+	// "error" halts with a Range-Over error.
+	InOverflow string
+	// CheckIO is what IN and OUT do with a buffer and length word that
+	// make no sense: a length word inside the buffer or in the code, a
+	// buffer reaching the code, or an OUT length outside 0 to
+	// IN_MAX_LENGTH. "" ignores them, "warn" reports them and "error"
+	// halts with a Range-Over error.
+	CheckIO string
+	// FailOnInputWait makes Run and Continue fail with ErrInputExhausted
+	// when IN finds no line in Input, instead of returning to wait for
+	// one. Runs that are given all their input up front, such as tests,
+	// set it.
+	FailOnInputWait bool
+	// LastFlagChange is how the last instruction changed FR, or nil.
+	LastFlagChange *FlagChange
+	// MaxSteps stops Run with ErrStepLimit when nonzero.
+	MaxSteps int
+	// MaxTime stops Run with ErrTimeLimit once Run, Continue and Step have
+	// taken this long in total. Zero means no limit.
+	MaxTime time.Duration
+	// MaxStackDepth halts the program with a stack overflow when PUSH or
+	// CALL would put more words than this on the stack. Zero means no
+	// limit other than the program itself.
+	MaxStackDepth int
+	// Breakpoints are where Continue stops. Reset keeps them.
+	Breakpoints *BreakpointTable
+	// MaxOutputBytes and MaxOutputLines halt the program when OUT would
+	// write more than this in total. Zero means no limit.
+	MaxOutputBytes int
+	MaxOutputLines int
+	// OutputTruncated is set when an output limit stopped the program.
+	OutputTruncated bool
+	// ExitStatus is the status the program ended with through the exit
+	// SVC of -extended-svc, or nil when it ended otherwise.
+	ExitStatus *int
+	// OutEscape makes OUT write characters outside #20-#7E as \xNN (and
+	// \ as \\) and warn about words above #FF instead of keeping only
+	// their low byte. OutStopNul ends the OUT text at the first #0000.
+	OutEscape  bool
+	OutStopNul bool
+	// OutputFilters transform each OUT line, in order, before it is
+	// written and recorded.
+	OutputFilters []OutputFilter
+	// SvcHandlers adds or overrides SVC entries.
+	SvcHandlers map[int]SvcHandler
+	// Quiet suppresses diagnostics printed by the emulator itself.
+	Quiet bool
+	// Warn receives the emulator's own diagnostics, such as division by
+	// zero. When nil they are dropped.
+	Warn func(msg string)
+	// ReadFile loads source files for error messages. When nil, errors
+	// point at line numbers only. Output, Input, Warn and ReadFile are the
+	// only ways the machine itself reaches the host; observers added by
+	// the caller, such as the event server of -ws, may do more.
+	ReadFile func(name string) ([]byte, error)
+
+	observers   []ExecutionObserver
+	symbols     *Symbolizer
+	devices     []Device
+	blocks      *blockCache
+	outputBytes int
+	outputLines int
+	wallTime    time.Duration
+	stackDepth  int
+	cancelled   atomic.Bool
+	haltErr     error
+	sourceText  map[string][]string
+}
+
+// IoEventKind distinguishes transcript entries.
+type IoEventKind int
+
+const (
+	IO_IN IoEventKind = iota
+	IO_OUT
+)
+
+func (k IoEventKind) MarshalText() ([]byte, error) {
+	if k == IO_IN {
+		return []byte("in"), nil
+	}
+	return []byte("out"), nil
+}
+
+func (k *IoEventKind) UnmarshalText(text []byte) error {
+	switch string(text) {
+	case "in":
+		*k = IO_IN
+	case "out":
+		*k = IO_OUT
+	default:
+		return fmt.Errorf("Unknown I/O event kind \"%s\"", text)
+	}
+	return nil
+}
+
+// IoEvent is a single IN or OUT recorded in the transcript.
+type IoEvent struct {
+	Kind IoEventKind `json:"kind"`
+	Text string      `json:"text"`
+	Step int         `json:"step"`
+	Time time.Time   `json:"time"`
+	// PC is the address of the SVC, Buffer (GR1) and LengthAt (GR2) the
+	// addresses of its buffer and length word.
+	PC       int `json:"pc"`
+	Buffer   int `json:"buffer"`
+	LengthAt int `json:"lengthAt"`
+	// Truncated is the number of characters of an IN line that did not
+	// fit in the buffer.
+	Truncated int `json:"truncated,omitempty"`
+	// Synthesized is set on an IN line that was not typed but read as the
+	// default of -input-timeout.
+	Synthesized bool `json:"synthesized,omitempty"`
+}
+
+// ExecutionObserver receives callbacks from the emulator core.
+// Tracing, coverage, watchpoints and profiling are layered on top of
+// these hooks instead of being wired into stepExec one by one.
+type ExecutionObserver interface {
+	// BeforeStep is called before the instruction at pc is executed.
+	BeforeStep(c *Comet2, pc int)
+	// AfterStep is called after the instruction at pc has been executed.
+	AfterStep(c *Comet2, pc int)
+	// MemoryRead is called when an instruction reads a data word.
+	MemoryRead(c *Comet2, addr int, val int)
+	// MemoryWrite is called when an instruction writes a data word.
+	MemoryWrite(c *Comet2, addr int, old int, val int)
+	// Svc is called when SVC is executed with the given entry address.
+	Svc(c *Comet2, entry int)
+	// FlagChange is called when an instruction changes FR.
+	FlagChange(c *Comet2, change FlagChange)
+}
+
+// BaseObserver implements ExecutionObserver with no-op callbacks.
+// Embed it to override only the hooks you need.
+type BaseObserver struct{}
+
+func (BaseObserver) BeforeStep(c *Comet2, pc int)                      {}
+func (BaseObserver) AfterStep(c *Comet2, pc int)                       {}
+func (BaseObserver) MemoryRead(c *Comet2, addr int, val int)           {}
+func (BaseObserver) MemoryWrite(c *Comet2, addr int, old int, val int) {}
+func (BaseObserver) Svc(c *Comet2, entry int)                          {}
+func (BaseObserver) FlagChange(c *Comet2, change FlagChange)           {}
+
+// NewComet2 creates a machine with bin loaded at address 0.
+func NewComet2(bin []uint16, start int, addressMax int) *Comet2 {
+	c := NewComet2Builder().WithObject(&Object{Bin: bin, Start: start}).Build()
+	c.AddressMax = addressMax
+	return c
+}
+
+// LoadObject creates a machine for obj, keeping its source map so runtime
+// errors can point at source lines.
+func LoadObject(obj *Object) *Comet2 {
+	return NewComet2Builder().WithObject(obj).Build()
+}
+
+// Comet2Builder configures a Comet2 before it is created. The zero
+// configuration is a 64K machine with an empty program.
+type Comet2Builder struct {
+	obj        *Object
+	memorySize int
+	stackTop   int
+	input      InputSource
+	output     func(text string)
+	observers  []ExecutionObserver
+	maxSteps   int
+	maxTime    time.Duration
+	maxDepth   int
+	maxBytes   int
+	maxLines   int
+	svc        map[int]SvcHandler
+	quiet      bool
+	warn       func(msg string)
+	readFile   func(name string) ([]byte, error)
+	memInit    MemFill
+	args       []string
+	filters    []OutputFilter
+}
+
+// NewComet2Builder returns a builder with the default configuration. OUT
+// and diagnostics go to the standard output and source files are read
+// from disk; hosts without them replace these with WithOutput, WithWarn
+// and WithReadFile.
+func NewComet2Builder() *Comet2Builder {
+	return &Comet2Builder{
+		obj:        &Object{},
+		memorySize: 0x10000,
+		stackTop:   STACK_TOP,
+		output:     cometOut,
+		svc:        make(map[int]SvcHandler),
+		warn:       cometWarn,
+		readFile:   os.ReadFile,
+	}
+}
+
+// WithObject sets the program to load.
+func (b *Comet2Builder) WithObject(obj *Object) *Comet2Builder {
+	b.obj = obj
+	return b
+}
+
+// WithMemorySize sets the number of memory words.
+func (b *Comet2Builder) WithMemorySize(words int) *Comet2Builder {
+	b.memorySize = words
+	return b
+}
+
+// WithStackTop sets the initial SP.
+func (b *Comet2Builder) WithStackTop(addr int) *Comet2Builder {
+	b.stackTop = addr
+	return b
+}
+
+// WithInput sets the source of IN lines.
+func (b *Comet2Builder) WithInput(src InputSource) *Comet2Builder {
+	b.input = src
+	return b
+}
+
+// WithInputs supplies a fixed list of IN lines.
+func (b *Comet2Builder) WithInputs(lines []string) *Comet2Builder {
+	return b.WithInput(NewInputLines(lines))
+}
+
+// WithOutput sets the receiver of OUT text.
+func (b *Comet2Builder) WithOutput(fn func(text string)) *Comet2Builder {
+	b.output = fn
+	return b
+}
+
+// WithObserver registers an execution observer.
+func (b *Comet2Builder) WithObserver(o ExecutionObserver) *Comet2Builder {
+	b.observers = append(b.observers, o)
+	return b
+}
+
+// WithMaxSteps limits the number of instructions Run may execute.
+func (b *Comet2Builder) WithMaxSteps(n int) *Comet2Builder {
+	b.maxSteps = n
+	return b
+}
+
+// WithMaxTime limits the wall-clock time Run may take.
+func (b *Comet2Builder) WithMaxTime(d time.Duration) *Comet2Builder {
+	b.maxTime = d
+	return b
+}
+
+// WithMaxStackDepth limits the number of words on the stack.
+func (b *Comet2Builder) WithMaxStackDepth(words int) *Comet2Builder {
+	b.maxDepth = words
+	return b
+}
+
+// WithMaxOutput limits the total output in bytes and in OUT lines.
+func (b *Comet2Builder) WithMaxOutput(bytes, lines int) *Comet2Builder {
+	b.maxBytes = bytes
+	b.maxLines = lines
+	return b
+}
+
+// WithSvc installs a handler for an SVC entry.
+func (b *Comet2Builder) WithSvc(entry int, h SvcHandler) *Comet2Builder {
+	b.svc[entry] = h
+	return b
+}
+
+// WithQuiet suppresses the emulator's own diagnostics.
+func (b *Comet2Builder) WithQuiet(quiet bool) *Comet2Builder {
+	b.quiet = quiet
+	return b
+}
+
+// WithWarn sets the receiver of the emulator's own diagnostics.
+func (b *Comet2Builder) WithWarn(fn func(msg string)) *Comet2Builder {
+	b.warn = fn
+	return b
+}
+
+// WithReadFile sets how source files are loaded for error messages. Pass
+// nil on hosts without a file system.
+func (b *Comet2Builder) WithReadFile(fn func(name string) ([]byte, error)) *Comet2Builder {
+	b.readFile = fn
+	return b
+}
+
+// WithMemInit sets the contents of memory outside the loaded program.
+func (b *Comet2Builder) WithMemInit(f MemFill) *Comet2Builder {
+	b.memInit = f
+	return b
+}
+
+// WithOutputFilter adds a transformation of OUT lines, applied after the
+// ones added before.
+func (b *Comet2Builder) WithOutputFilter(f OutputFilter) *Comet2Builder {
+	b.filters = append(b.filters, f)
+	return b
+}
+
+// WithArgs sets the program arguments written at ARGS_BASE.
+func (b *Comet2Builder) WithArgs(args []string) *Comet2Builder {
+	b.args = args
+	return b
+}
+
+// Build creates the machine.
+func (b *Comet2Builder) Build() *Comet2 {
+	c := &Comet2{
+		Memory:         make([]uint16, b.memorySize),
+		InputMode:      INPUT_MODE_CMD,
+		AddressMax:     len(b.obj.Bin),
+		StackTop:       b.stackTop,
+		Output:         b.output,
+		Input:          b.input,
+		SourceMap:      b.obj.SourceMap,
+		Regions:        b.obj.Regions,
+		Symbols:        b.obj.Symbols,
+		MaxSteps:       b.maxSteps,
+		MaxTime:        b.maxTime,
+		MaxStackDepth:  b.maxDepth,
+		MaxOutputBytes: b.maxBytes,
+		MaxOutputLines: b.maxLines,
+		SvcHandlers:    make(map[int]SvcHandler),
+		Quiet:          b.quiet,
+		Warn:           b.warn,
+		ReadFile:       b.readFile,
+		MemInit:        b.memInit,
+		Args:           b.args,
+		OutputFilters:  b.filters,
+		Breakpoints:    NewBreakpointTable(),
+	}
+	c.MemInit.fill(c.Memory, len(b.obj.Bin))
+	c.MemInit.poisonStack(c.Memory, len(b.obj.Bin), b.stackTop)
+	copy(c.Memory, b.obj.Bin)
+	writeArgs(c.Memory, c.Args)
+	c.State = []int{b.obj.Start, FR_PLUS, 0, 0, 0, 0, 0, 0, 0, 0, b.stackTop}
+	for entry, h := range b.svc {
+		c.SvcHandlers[entry] = h
+	}
+	for _, o := range b.observers {
+		c.AddObserver(o)
+	}
+	return c
+}
+
+// Reset loads obj into the machine for a new run, reusing the memory and
+// transcript buffers. Configuration such as Output, Input, traps and
+// observers is kept; observers that hold per-run state must be reset by
+// the caller.
+func (c *Comet2) Reset(obj *Object) {
+	c.MemInit.fill(c.Memory, len(obj.Bin))
+	c.MemInit.poisonStack(c.Memory, len(obj.Bin), c.StackTop)
+	copy(c.Memory, obj.Bin)
+	writeArgs(c.Memory, c.Args)
+	c.State = append(c.State[:0], obj.Start, FR_PLUS, 0, 0, 0, 0, 0, 0, 0, 0, c.StackTop)
+	c.InputMode = INPUT_MODE_CMD
+	c.AddressMax = len(obj.Bin)
+	c.SourceMap = obj.SourceMap
+	c.Regions = obj.Regions
+	c.Symbols = obj.Symbols
+	c.symbols = nil
+	c.Steps = 0
+	c.Transcript = c.Transcript[:0]
+	c.OutputTruncated = false
+	c.ExitStatus = nil
+	c.outputBytes = 0
+	c.outputLines = 0
+	c.wallTime = 0
+	c.stackDepth = 0
+	c.LastFlagChange = nil
+	c.haltErr = nil
+	c.cancelled.Store(false)
+	clear(c.sourceText)
+	if c.blocks != nil {
+		c.blocks.reset()
+	}
+}
+
+// SetTraps enables the comma separated traps in spec.
+func (c *Comet2) SetTraps(spec string) error {
+	for _, name := range strings.Split(spec, ",") {
+		switch strings.TrimSpace(name) {
+		case "":
+		case "overflow":
+			c.TrapOverflow = true
+		case "range":
+			c.TrapRange = true
+		case "protect":
+			c.TrapProtect = true
+		case "protect-stack":
+			c.TrapProtect = true
+			c.TrapProtectStack = true
+		default:
+			return fmt.Errorf("[COMET2 ERROR] Unknown trap \"%s\"", name)
+		}
+	}
+	return c.checkLayout()
+}
+
+// SetOutChars enables the comma separated OUT options in spec: "raw"
+// (the default), "escape" and "stop-nul".
+func (c *Comet2) SetOutChars(spec string) error {
+	for _, name := range strings.Split(spec, ",") {
+		switch strings.TrimSpace(name) {
+		case "", "raw":
+		case "escape":
+			c.OutEscape = true
+		case "stop-nul":
+			c.OutStopNul = true
+		default:
+			return fmt.Errorf("[COMET2 ERROR] Unknown -out-chars option \"%s\"", name)
+		}
+	}
+	return nil
+}
+
+// SetInOverflow selects what IN does with a line that is too long:
+// "truncate", "warn" or "error".
+func (c *Comet2) SetInOverflow(mode string) error {
+	switch mode {
+	case "", "truncate", "warn", "error":
+		c.InOverflow = mode
+		return nil
+	}
+	return fmt.Errorf("[COMET2 ERROR] Invalid -in-overflow mode \"%s\"", mode)
+}
+
+// checkLayout reports a program loaded over a protected region.
+func (c *Comet2) checkLayout() error {
+	if c.TrapProtect && c.AddressMax > SVC_VECTOR_BASE {
+		return fmt.Errorf("[COMET2 ERROR] Program (#%s words) is loaded over the SVC vector area #%s-#ffff",
+			hex(c.AddressMax, 4), hex(SVC_VECTOR_BASE, 4))
+	}
+	if c.TrapProtectStack && c.AddressMax >= c.StackTop {
+		return fmt.Errorf("[COMET2 ERROR] Program (#%s words) is loaded over the stack below #%s",
+			hex(c.AddressMax, 4), hex(c.StackTop, 4))
+	}
+	return nil
+}
+
+// protectedRegion names the protected region containing addr, or returns
+// "" when writing to addr is allowed.
+func (c *Comet2) protectedRegion(addr int) string {
+	if c.TrapProtect && addr >= SVC_VECTOR_BASE {
+		return "SVC vector area"
+	}
+	if c.TrapProtectStack && addr >= c.AddressMax && addr < c.StackTop {
+		return "stack area"
+	}
+	return ""
+}
+
+// AddObserver registers an observer on the machine.
+func (c *Comet2) AddObserver(o ExecutionObserver) {
+	c.observers = append(c.observers, o)
+}
+
+// load reads a data word on behalf of the running program.
+func (c *Comet2) load(addr int) int {
+	if d, offset := c.deviceAt(addr); d != nil {
+		return d.Read(c, offset)
+	}
+	val := memGet(c.Memory, addr)
+	for _, o := range c.observers {
+		o.MemoryRead(c, addr, val)
+	}
+	return val
+}
+
+// poke writes a word of memory and drops the decoded block containing
+// it. Every write to memory goes through it, so that the block engine
+// does not run a stale decoding.
+func (c *Comet2) poke(addr int, val int) {
+	memPut(c.Memory, addr, val)
+	if c.blocks != nil {
+		c.blocks.invalidate(addr)
+	}
+}
+
+// store writes a data word on behalf of the running program.
+func (c *Comet2) store(addr int, val int) {
+	if d, offset := c.deviceAt(addr); d != nil {
+		d.Write(c, offset, val&0xffff)
+		return
+	}
+	old := memGet(c.Memory, addr)
+	c.poke(addr, val)
+	for _, o := range c.observers {
+		o.MemoryWrite(c, addr, old, memGet(c.Memory, addr))
+	}
+}
+
+// record appends an I/O event to the transcript.
+func (c *Comet2) record(kind IoEventKind, text string) {
+	c.Transcript = append(c.Transcript, IoEvent{
+		Kind:     kind,
+		Text:     text,
+		Step:     c.Steps,
+		Time:     time.Now(),
+		PC:       c.State[PC],
+		Buffer:   c.State[GR1],
+		LengthAt: c.State[GR2],
+	})
+}
+
+// Cancel asks the machine to stop at the next instruction boundary.
+// It is safe to call from another goroutine.
+func (c *Comet2) Cancel() {
+	c.cancelled.Store(true)
+}
+
+// warn reports a diagnostic unless the machine is quiet.
+func (c *Comet2) warn(msg string) {
+	if !c.Quiet && c.Warn != nil {
+		c.Warn(msg)
+	}
+}
+
+// Halt stops execution with err after the current instruction. It is
+// meant to be called by observers.
+func (c *Comet2) Halt(err error) {
+	if c.haltErr == nil {
+		c.haltErr = err
+	}
+}
+
+// takeHalt returns and clears the error passed to Halt.
+func (c *Comet2) takeHalt() error {
+	err := c.haltErr
+	c.haltErr = nil
+	return err
+}
+
+// checkCancelled reports and clears a pending cancellation request.
+func (c *Comet2) checkCancelled() error {
+	if c.cancelled.Swap(false) {
+		return ErrCancelled
+	}
+	return nil
+}
+
+// Run executes instructions until the program finishes, an error occurs,
+// the program waits for IN that Input cannot satisfy, it reaches one of
+// Breakpoints, or Cancel is called.
+func (c *Comet2) Run() error {
+	return c.Continue().Err
+}
+
+// Continue runs like Run and also stops before an instruction at one of
+// Breakpoints, other than the one it starts at, and tells why it stopped.
+// Disabled breakpoints and ignored hits do not stop it.
+func (c *Comet2) Continue() HaltReason {
+	return c.run(-1)
+}
+
+// RunFor runs like Continue for at most steps instructions and returns
+// HALT_BUDGET when it executed them all, so that a frontend without
+// threads (a web page, an event loop) can run a program in slices and
+// stay responsive. Calling RunFor or Continue again goes on from there; a
+// program waiting for input goes on after SupplyInput.
+func (c *Comet2) RunFor(steps int) HaltReason {
+	return c.run(max(steps, 0))
+}
+
+// run is Continue with a budget of instructions, none when negative.
+func (c *Comet2) run(budget int) HaltReason {
+	start := time.Now()
+	defer func() { c.wallTime += time.Since(start) }()
+	for n := 0; ; n++ {
+		if err := c.checkCancelled(); err != nil {
+			return classifyHalt(err, c.State[PC])
+		}
+		if c.MaxSteps > 0 && c.Steps >= c.MaxSteps {
+			return classifyHalt(ErrStepLimit, c.State[PC])
+		}
+		if n%TIME_CHECK_INTERVAL == 0 && c.timeUp(start) {
+			return classifyHalt(ErrTimeLimit, c.State[PC])
+		}
+		// The breakpoint comes before the budget: the next slice starts
+		// by executing the instruction, as after a breakpoint
+		if n > 0 {
+			if bp := c.Breakpoints.hit(c.State[PC]); bp != nil {
+				return HaltReason{Kind: HALT_BREAKPOINT, PC: c.State[PC], Breakpoint: bp.ID}
+			}
+		}
+		if n == budget {
+			return HaltReason{Kind: HALT_BUDGET, PC: c.State[PC]}
+		}
+		if !c.readInput() {
+			return c.waitInput()
+		}
+		if _, err := c.stepExec(); err != nil {
+			return classifyHalt(err, c.State[PC])
+		}
+	}
+}
+
+// Step executes one instruction, reading the line for a pending IN from
+// Input first, and tells whether the program can go on.
+func (c *Comet2) Step() HaltReason {
+	start := time.Now()
+	defer func() { c.wallTime += time.Since(start) }()
+	if !c.readInput() {
+		return c.waitInput()
+	}
+	if _, err := c.stepExec(); err != nil {
+		return classifyHalt(err, c.State[PC])
+	}
+	return HaltReason{Kind: HALT_RUNNING, PC: c.State[PC]}
+}
+
+// waitInput is the halt reason of an IN without input: waiting, or with
+// FailOnInputWait an error naming the IN.
+func (c *Comet2) waitInput() HaltReason {
+	r := HaltReason{Kind: HALT_WAITING_INPUT, PC: c.State[PC], Input: c.PendingInput()}
+	if c.FailOnInputWait {
+		r.Err = c.inputExhausted()
+	}
+	return r
+}
+
+// PendingInput returns the IN the program waits for, or nil when it does
+// not wait for input.
+func (c *Comet2) PendingInput() *InputRequest {
+	if c.InputMode != INPUT_MODE_IN {
+		return nil
+	}
+	return &InputRequest{PC: c.State[PC], Buffer: c.State[GR1], LengthAt: c.State[GR2], MaxLength: IN_MAX_LENGTH}
+}
+
+// SupplyInput completes the IN the program waits for with line, as if Input
+// had returned it, for frontends that ask for each line when the program
+// needs it. Continue or Step then go on after the IN.
+func (c *Comet2) SupplyInput(line string) error {
+	if c.InputMode != INPUT_MODE_IN {
+		return fmt.Errorf("[COMET2 ERROR] The program is not waiting for input")
+	}
+	c.execIn(line)
+	c.InputMode = INPUT_MODE_CMD
+	return nil
+}
+
+// inputExhausted is the ErrInputExhausted of the pending IN.
+func (c *Comet2) inputExhausted() error {
+	return fmt.Errorf("%w at %s%s", ErrInputExhausted, c.formatAddr(c.State[PC]), c.locate(c.State[PC]))
+}
+
+// readInput completes a pending IN with a line from Input. It returns
+// false when the program still waits for input.
+func (c *Comet2) readInput() bool {
+	if c.InputMode != INPUT_MODE_IN {
+		return true
+	}
+	if c.Input == nil {
+		return false
+	}
+	line, ok := c.Input.ReadLine()
+	if !ok {
+		return false
+	}
+	c.execIn(line)
+	c.InputMode = INPUT_MODE_CMD
+	return true
+}
+
+// sourceLines returns the lines of the source file, or nil when it cannot
+// be read.
+func (c *Comet2) sourceLines(file string) []string {
+	if c.sourceText == nil {
+		c.sourceText = make(map[string][]string)
+	}
+	lines, ok := c.sourceText[file]
+	if !ok {
+		// Library programs are not files, see expandIncludes
+		name := strings.TrimSuffix(strings.TrimPrefix(file, "<STD/"), ">")
+		if text, found := stdlib[name]; found && file == "<STD/"+name+">" {
+			lines = strings.Split(text, "\n")
+		} else if c.ReadFile != nil {
+			if content, err := c.ReadFile(file); err == nil {
+				lines = strings.Split(strings.ReplaceAll(string(content), "\r\n", "\n"), "\n")
+			}
+		}
+		c.sourceText[file] = lines
+	}
+	return lines
+}
+
+// sourceLine returns the trimmed source text of line in file.
+func (c *Comet2) sourceLine(file string, line int) string {
+	lines := c.sourceLines(file)
+	if line < 1 || line > len(lines) {
+		return ""
+	}
+	code := lines[line-1]
+	if idx := strings.Index(code, ";"); idx >= 0 {
+		code = code[:idx]
+	}
+	return strings.Join(strings.Fields(code), " ")
+}
+
+// locate describes the source line an address was assembled from, e.g.
+// " (prog.cas line 57: 'DC 0')", or returns "" when it is unknown.
+func (c *Comet2) locate(addr int) string {
+	if addr < 0 || addr >= len(c.SourceMap) || c.SourceMap[addr].Line == 0 {
+		return ""
+	}
+	loc := c.SourceMap[addr]
+	if text := c.sourceLine(loc.File, loc.Line); text != "" {
+		return fmt.Sprintf(" (%s line %d: '%s')", loc.File, loc.Line, text)
+	}
+	return fmt.Sprintf(" (%s line %d)", loc.File, loc.Line)
+}
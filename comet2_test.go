@@ -0,0 +1,200 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestBuilder(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	IN	BUF,LEN
+	SVC	5
+	ST	GR1,LEN
+	OUT	BUF,LEN
+LOOP	JUMP	LOOP
+BUF	DS	16
+LEN	DS	1
+	END
+`)
+
+	var outputs []string
+	c := NewComet2Builder().
+		WithObject(obj).
+		WithStackTop(0xf000).
+		WithInputs([]string{"hello"}).
+		WithOutput(func(text string) {
+			outputs = append(outputs, text)
+		}).
+		WithSvc(5, func(c *Comet2) error {
+			c.State[GR1] = 2
+			return nil
+		}).
+		WithMaxSteps(100).
+		Build()
+
+	if c.State[SP] != 0xf000 {
+		t.Errorf("Expected SP #f000, got #%s", hex(c.State[SP], 4))
+	}
+	if err := c.Run(); err != ErrStepLimit {
+		t.Fatalf("Expected step limit, got %v", err)
+	}
+	if len(outputs) != 1 || outputs[0] != "he" {
+		t.Errorf("Unexpected outputs: %q", outputs)
+	}
+}
+
+func TestBuilderWithoutHost(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,1
+	DIVA	GR1,ZERO
+	OUT	MSG,LEN
+	POP	GR1
+MSG	DC	'hi'
+LEN	DC	2
+ZERO	DC	0
+	END
+`)
+
+	var warnings []string
+	c := NewComet2Builder().
+		WithObject(obj).
+		WithOutput(nil).
+		WithReadFile(nil).
+		WithWarn(func(msg string) {
+			warnings = append(warnings, msg)
+		}).
+		Build()
+
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_STACK_UNDERFLOW {
+		t.Fatalf("Expected stack underflow, got %v", err)
+	}
+	if len(warnings) != 1 || !strings.Contains(warnings[0], "Division by zero") {
+		t.Errorf("Unexpected warnings: %q", warnings)
+	}
+	if len(c.Transcript) != 1 || c.Transcript[0].Text != "hi" {
+		t.Errorf("Unexpected transcript: %+v", c.Transcript)
+	}
+}
+
+func TestHeadlessRun(t *testing.T) {
+	// Modifies PATCH and returns from SUB through a pushed address
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,NEW
+	ST	GR1,PATCH+1
+PATCH	LAD	GR2,5
+	CALL	SUB
+	OUT	MSG,LEN
+	RET
+SUB	LAD	GR1,FIN
+	PUSH	0,GR1
+	RET
+FIN	RET
+MSG	DC	'hi'
+LEN	DC	2
+NEW	DC	9
+	END
+`)
+	var warnings []string
+	c := NewComet2Builder().
+		WithObject(obj).
+		WithOutput(nil).
+		WithReadFile(nil).
+		WithWarn(func(msg string) { warnings = append(warnings, msg) }).
+		WithObserver(newSmcTracker(false)).
+		WithObserver(newCallChecker(false)).
+		WithObserver(newLoopDetector()).
+		WithObserver(&explainer{}).
+		Build()
+
+	var err error
+	if out := captureStdout(t, func() { err = c.Run() }); out != "" {
+		t.Errorf("Expected nothing on the standard output, got %q", out)
+	}
+	if err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if len(warnings) != 2 {
+		t.Errorf("Expected the modified instruction and the RET to be reported, got %q", warnings)
+	}
+	if len(c.Transcript) != 1 || c.Transcript[0].Text != "hi" {
+		t.Errorf("Unexpected transcript: %+v", c.Transcript)
+	}
+}
+
+func TestCancel(t *testing.T) {
+	spin := "MAIN\tSTART\nLOOP\tJUMP\tLOOP\n\tEND\n"
+	c := assembleSource(t, spin)
+	done := make(chan HaltReason, 1)
+	go func() { done <- c.Continue() }()
+	time.Sleep(10 * time.Millisecond)
+	c.Cancel()
+
+	select {
+	case r := <-done:
+		if r.Kind != HALT_CANCELLED || !errors.Is(r.Err, ErrCancelled) {
+			t.Fatalf("Expected a cancelled halt, got %+v", r)
+		}
+	case <-time.After(5 * time.Second):
+		t.Fatalf("Run did not stop after Cancel")
+	}
+
+	// The request is used up, so the run goes on afterwards
+	c.MaxSteps = c.Steps + 10
+	if err := c.Run(); err != ErrStepLimit {
+		t.Errorf("Expected step limit after resuming, got %v", err)
+	}
+
+	// Reset drops a cancel that no run has seen
+	c.Cancel()
+	c.Reset(assembleObject(t, spin))
+	c.MaxSteps = 10
+	if err := c.Run(); err != ErrStepLimit {
+		t.Errorf("Expected step limit after Reset, got %v", err)
+	}
+}
+
+func TestReset(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+	c.Input = NewInputLines([]string{"first"})
+	if err := c.Run(); err == nil {
+		t.Fatalf("Expected program to finish")
+	}
+	mem := &c.Memory[0]
+
+	obj := assembleObject(t, echoProgram)
+	c.Reset(obj)
+	if &c.Memory[0] != mem {
+		t.Errorf("Expected memory to be reused")
+	}
+	if c.Steps != 0 || len(c.Transcript) != 0 || c.State[PC] != obj.Start || c.State[SP] != STACK_TOP {
+		t.Fatalf("Machine was not reset: steps %d, transcript %d, state %v", c.Steps, len(c.Transcript), c.State)
+	}
+
+	c.Input = NewInputLines([]string{"second"})
+	err := c.Run()
+	if err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if len(c.Transcript) != 2 || c.Transcript[1].Text != "second" {
+		t.Errorf("Unexpected transcript: %+v", c.Transcript)
+	}
+}
+
+func TestFailOnInputWait(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+	c.Input = NewInputLines(nil)
+	c.FailOnInputWait = true
+	r := c.Continue()
+	if r.Kind != HALT_WAITING_INPUT || !errors.Is(r.Err, ErrInputExhausted) || !r.Stopped() {
+		t.Errorf("Expected the input wait to fail, got %+v", r)
+	}
+
+	obj := assembleObject(t, echoProgram)
+	transcript, _ := runTranscript(obj, nil, TEST_MAX_STEPS, TEST_TIMEOUT)
+	if !strings.Contains(transcript, "Program requested more input than provided at #") {
+		t.Errorf("Expected the transcript to end with the input error, got %q", transcript)
+	}
+}
@@ -1,38 +1,126 @@
 package main
 
 import (
+	"bufio"
 	"fmt"
+	"os"
+	"path/filepath"
+	"slices"
+	"sort"
 	"strconv"
+	"strings"
 )
 
-func executeCommand(cmd string, args []string, memory []uint16, state []int) error {
-	commands := map[string]func([]uint16, []int, []string) error{
-		"r":    cmdRun,
-		"run":  cmdRun,
-		"s":    cmdStep,
-		"step": cmdStep,
-		"p":    cmdPrint,
-		"print": cmdPrint,
-		"h":    cmdHelp,
-		"help": cmdHelp,
-		"du":   cmdDump,
-		"dump": cmdDump,
-		"st":   cmdStack,
-		"stack": cmdStack,
-		"di":    cmdDisasm,
-		"disasm": cmdDisasm,
+// Name of the monitor startup file, read from the home directory and then
+// from the current directory
+const STARTUP_FILE = ".c2c2rc"
+
+// Monitor commands by name
+var monitorCommands = map[string]func(*Comet2, []string) error{
+	"r":      cmdRun,
+	"run":    cmdRun,
+	"ro":     cmdRunUntilOut,
+	"s":      cmdStep,
+	"step":   cmdStep,
+	"p":      cmdPrint,
+	"print":  cmdPrint,
+	"h":      cmdHelp,
+	"help":   cmdHelp,
+	"du":     cmdDump,
+	"dump":   cmdDump,
+	"st":     cmdStack,
+	"stack":  cmdStack,
+	"di":     cmdDisasm,
+	"disasm": cmdDisasm,
+	"b":      cmdBreak,
+	"break":  cmdBreak,
+	"tbreak": cmdTbreak,
+	"enable": cmdEnable,
+	"ignore": cmdIgnore,
+	"bf":     cmdBreakFlag,
+	"w":      cmdWatch,
+	"watch":  cmdWatch,
+	"mark":   cmdMark,
+	"u":      cmdUndo,
+	"undo":   cmdUndo,
+	"d":      cmdDelete,
+	"delete": cmdDelete,
+	"i":      cmdInfo,
+	"info":   cmdInfo,
+	"f":      cmdFile,
+	"file":   cmdFile,
+	"j":      cmdJump,
+	"jump":   cmdJump,
+	"m":      cmdMemory,
+	"memory": cmdMemory,
+	"mem":    cmdMem,
+	"set":    cmdSet,
+	"fill":   cmdFill,
+	"char":   cmdChar,
+	"code":   cmdCode,
+	"ascii":  cmdAscii,
+}
+
+// FR bits that stop run when an instruction sets them (break-flag)
+var flagBreaks int
+
+// Indices into State of the registers that stop run when they change
+var watches []int
+
+// Memory saved by mark for diffmem, or nil
+var memMark []uint16
+
+// monitorConfirm asks a yes/no question; main reads the answer from the
+// console.
+var monitorConfirm = func(prompt string) bool { return true }
+
+// User-defined aliases, from a name to the command line it stands for
+var monitorAliases = map[string]string{}
+
+// The alias commands refer to monitorCommands, so they are added here,
+// along with the names too long for the table
+func init() {
+	monitorCommands["alias"] = cmdAlias
+	monitorCommands["unalias"] = cmdUnalias
+	monitorCommands["run-until-out"] = cmdRunUntilOut
+	monitorCommands["break-flag"] = cmdBreakFlag
+	monitorCommands["diffmem"] = cmdDiffMem
+	monitorCommands["checkpoint"] = cmdCheckpoint
+	monitorCommands["restore"] = cmdRestore
+	monitorCommands["disable"] = cmdDisable
+}
+
+// activeCommands returns the command table of the monitor: that of the
+// original Perl c2c2 with -compat.
+func activeCommands() map[string]func(*Comet2, []string) error {
+	if *optCompat {
+		return compatCommands
+	}
+	return monitorCommands
+}
+
+func executeCommand(cmd string, args []string, c *Comet2) error {
+	if expansion, ok := monitorAliases[cmd]; ok {
+		parts := append(strings.Fields(expansion), args...)
+		cmd, args = parts[0], parts[1:]
 	}
 
-	if handler, ok := commands[cmd]; ok {
-		return handler(memory, state, args)
+	if handler, ok := activeCommands()[cmd]; ok {
+		return handler(c, args)
 	}
 
 	return fmt.Errorf("Undefined command \"%s\". Try \"help\".", cmd)
 }
 
-func cmdRun(memory []uint16, state []int, args []string) error {
+func cmdRun(c *Comet2, args []string) error {
+	if err := c.checkCancelled(); err != nil {
+		nextCmd = ""
+		return err
+	}
+
 	nextCmd = "run"
-	stopFlag, err := stepExec(memory, state)
+	before := append([]int(nil), c.State...)
+	stopFlag, err := c.stepExec()
 	if err != nil {
 		nextCmd = ""
 		return err
@@ -43,11 +131,90 @@ func cmdRun(memory []uint16, state []int, args []string) error {
 		return nil
 	}
 
-	// Check for breakpoints (not implemented in minimal version)
+	if stopAtBreak(c, before) {
+		nextCmd = ""
+		if verbosity.dump {
+			cmdPrint(c, []string{})
+		}
+	}
 	return nil
 }
 
-func cmdStep(memory []uint16, state []int, args []string) error {
+// stopAtBreak reports whether run should stop, because the last
+// instruction changed a watched register or set a flag selected with
+// break-flag, or because a breakpoint is at the next one, and says why.
+// before is the machine state before the last instruction.
+func stopAtBreak(c *Comet2, before []int) bool {
+	pc := before[PC]
+	changed := false
+	for _, reg := range watches {
+		if old, val := before[reg], c.State[reg]; old != val {
+			cometPrint(fmt.Sprintf("Watch %s: %s -> %s at %s%s", registerName(reg), formatWord(old), formatWord(val), c.formatAddr(pc), c.locate(pc)))
+			changed = true
+		}
+	}
+	if changed {
+		return true
+	}
+	if fc := c.LastFlagChange; fc != nil && fc.New&^fc.Old&flagBreaks != 0 {
+		cometPrint(fmt.Sprintf("Flag break at %s%s, %s", c.formatAddr(fc.PC), c.locate(fc.PC), fc))
+		return true
+	}
+	if bp := c.Breakpoints.hit(c.State[PC]); bp != nil {
+		cometPrint(fmt.Sprintf("Breakpoint %d, %s", bp.ID, c.formatAddr(bp.Addr)))
+		return true
+	}
+	return false
+}
+
+// cmdRunUntilOut runs until N (default 1) more OUT lines have been
+// written, then shows the last line and the machine state. Like step, it
+// continues through nextCmd with the lines still to go.
+func cmdRunUntilOut(c *Comet2, args []string) error {
+	count := 1
+	if len(args) > 0 {
+		if n, ok := expandNumber(args[0]); ok && n > 0 {
+			count = n
+		}
+	}
+	if err := c.checkCancelled(); err != nil {
+		nextCmd = ""
+		return err
+	}
+
+	nextCmd = fmt.Sprintf("run-until-out %d", count)
+	lines := c.outputLines
+	pc := c.State[PC]
+	before := append([]int(nil), c.State...)
+	stopFlag, err := c.stepExec()
+	if err != nil {
+		nextCmd = ""
+		return err
+	}
+
+	if c.outputLines > lines {
+		count--
+		if count == 0 {
+			nextCmd = ""
+			text := c.Transcript[len(c.Transcript)-1].Text
+			cometPrint(fmt.Sprintf("Output at %s%s: %s", c.formatAddr(pc), c.locate(pc), strconv.Quote(text)))
+			cmdPrint(c, []string{})
+			return nil
+		}
+		nextCmd = fmt.Sprintf("run-until-out %d", count)
+	}
+	if stopFlag {
+		return nil
+	}
+
+	if stopAtBreak(c, before) {
+		nextCmd = ""
+		cmdPrint(c, []string{})
+	}
+	return nil
+}
+
+func cmdStep(c *Comet2, args []string) error {
 	count := 1
 	if len(args) > 0 {
 		if n, ok := expandNumber(args[0]); ok {
@@ -62,19 +229,23 @@ func cmdStep(memory []uint16, state []int, args []string) error {
 		nextCmd = ""
 	}
 
-	_, err := stepExec(memory, state)
+	if verbosity.dump {
+		cometPrint(colorYellow(c.Preview().String()))
+	}
+	_, err := c.stepExec()
 	if err != nil {
 		return err
 	}
 
-	if !*optQuiet {
-		cmdPrint(memory, state, []string{})
+	if verbosity.dump {
+		cmdPrint(c, []string{})
 	}
 
 	return nil
 }
 
-func cmdPrint(memory []uint16, state []int, args []string) error {
+func cmdPrint(c *Comet2, args []string) error {
+	memory, state := c.Memory, c.State
 	pc := state[PC]
 	fr := state[FR]
 	sp := state[SP]
@@ -86,52 +257,38 @@ func cmdPrint(memory []uint16, state []int, args []string) error {
 	cometPrint("")
 	cometPrint(fmt.Sprintf("%s  %s [ %s ]",
 		colorBCyan("PR"),
-		colorRed("#"+hex(pc, 4)),
+		colorRed(c.formatAddr(pc)),
 		colorGreen(fmt.Sprintf("%s\t\t%s", inst, opr))))
 
-	frBin := fmt.Sprintf("%d%d%d", (fr>>2)%2, (fr>>1)%2, fr%2)
-	frStr := ""
-	if (fr>>2)%2 == 1 {
-		frStr += "O"
-	} else {
-		frStr += "-"
-	}
-	if (fr>>1)%2 == 1 {
-		frStr += "S"
-	} else {
-		frStr += "-"
-	}
-	if fr%2 == 1 {
-		frStr += "Z"
-	} else {
-		frStr += "-"
-	}
-
-	cometPrint(fmt.Sprintf("%s  %s(%s)  %s    %s(%s)[ %s ]",
+	cometPrint(fmt.Sprintf("%s  %s  %s    %s(%s)[ %s ]",
 		colorBCyan("SP"),
-		colorRed("#"+hex(sp, 4)),
-		spacePadding(signed(sp), 6),
+		colorRed(formatWord(sp)),
 		colorBCyan("FR"),
-		colorYellow(frBin),
+		colorYellow(formatFRBits(fr)),
 		spacePadding(fr, 6),
-		colorGreen(frStr)))
+		colorGreen(formatFR(fr))))
+
+	if c.LastFlagChange != nil {
+		cometPrint(fmt.Sprintf("%s  %s", colorBCyan("FR"), colorYellow(c.LastFlagChange.String())))
+	}
 
-	cometPrint(fmt.Sprintf("%s %s(%s)  %s %s(%s)  %s %s(%s)  %s %s(%s)",
-		colorBCyan("GR0"), colorRed("#"+hex(regs[0], 4)), spacePadding(signed(regs[0]), 6),
-		colorBCyan("GR1"), colorRed("#"+hex(regs[1], 4)), spacePadding(signed(regs[1]), 6),
-		colorBCyan("GR2"), colorRed("#"+hex(regs[2], 4)), spacePadding(signed(regs[2]), 6),
-		colorBCyan("GR3"), colorRed("#"+hex(regs[3], 4)), spacePadding(signed(regs[3]), 6)))
+	cometPrint(fmt.Sprintf("%s %s  %s %s  %s %s  %s %s",
+		colorBCyan("GR0"), colorRed(formatWord(regs[0])),
+		colorBCyan("GR1"), colorRed(formatWord(regs[1])),
+		colorBCyan("GR2"), colorRed(formatWord(regs[2])),
+		colorBCyan("GR3"), colorRed(formatWord(regs[3]))))
 
-	cometPrint(fmt.Sprintf("%s %s(%s)  %s %s(%s)  %s %s(%s)  %s %s(%s)",
-		colorBCyan("GR4"), colorRed("#"+hex(regs[4], 4)), spacePadding(signed(regs[4]), 6),
-		colorBCyan("GR5"), colorRed("#"+hex(regs[5], 4)), spacePadding(signed(regs[5]), 6),
-		colorBCyan("GR6"), colorRed("#"+hex(regs[6], 4)), spacePadding(signed(regs[6]), 6),
-		colorBCyan("GR7"), colorRed("#"+hex(regs[7], 4)), spacePadding(signed(regs[7]), 6)))
+	cometPrint(fmt.Sprintf("%s %s  %s %s  %s %s  %s %s",
+		colorBCyan("GR4"), colorRed(formatWord(regs[4])),
+		colorBCyan("GR5"), colorRed(formatWord(regs[5])),
+		colorBCyan("GR6"), colorRed(formatWord(regs[6])),
+		colorBCyan("GR7"), colorRed(formatWord(regs[7]))))
 
 	return nil
 }
 
-func cmdDump(memory []uint16, state []int, args []string) error {
+func cmdDump(c *Comet2, args []string) error {
+	memory, state := c.Memory, c.State
 	val := state[PC]
 	if len(args) > 0 {
 		if n, ok := expandNumber(args[0]); ok {
@@ -149,13 +306,16 @@ func cmdDump(memory []uint16, state []int, args []string) error {
 
 		line += " "
 		for col := 0; col < 8; col++ {
-			c := memGet(memory, base+col) & 0xff
-			if c >= 0x20 && c <= 0x7f {
-				line += string(rune(c))
+			ch := memGet(memory, base+col) & 0xff
+			if ch >= 0x20 && ch <= 0x7f {
+				line += string(rune(ch))
 			} else {
 				line += "."
 			}
 		}
+		if len(c.Regions) > 0 {
+			line += " " + regionMarks(c.Regions, base, 8)
+		}
 
 		cometPrint(line)
 	}
@@ -163,14 +323,88 @@ func cmdDump(memory []uint16, state []int, args []string) error {
 	return nil
 }
 
-func cmdStack(memory []uint16, state []int, args []string) error {
-	return cmdDump(memory, state, []string{strconv.Itoa(state[SP])})
+// regionMarks returns one character per word from base: "c" for code, "d"
+// for data and "." outside the program.
+func regionMarks(regions []Region, base int, n int) string {
+	marks := make([]byte, n)
+	for i := range marks {
+		marks[i] = '.'
+		for _, r := range regions {
+			if base+i < r.Start || base+i >= r.End {
+				continue
+			}
+			if r.Code {
+				marks[i] = 'c'
+			} else {
+				marks[i] = 'd'
+			}
+		}
+	}
+	return string(marks)
+}
+
+// cmdMark saves the memory for a later diffmem.
+func cmdMark(c *Comet2, args []string) error {
+	memMark = append(memMark[:0], c.Memory...)
+	cometPrint(fmt.Sprintf("Memory marked at step %d.", c.Steps))
+	return nil
+}
+
+// cmdDiffMem lists the words that changed since mark, in the whole memory
+// or in FROM-TO, where both ends are addresses or labels.
+func cmdDiffMem(c *Comet2, args []string) error {
+	if memMark == nil {
+		return fmt.Errorf("No memory mark. Use \"mark\" first.")
+	}
+	from, to := 0, len(memMark)-1
+	if len(args) > 0 {
+		bounds := strings.SplitN(args[0], "-", 2)
+		if len(bounds) != 2 {
+			return fmt.Errorf("Usage: diffmem [FROM-TO]")
+		}
+		obj := &Object{Symbols: c.Symbols}
+		var ok bool
+		if from, ok = lookupSymbol(obj, bounds[0]); !ok {
+			return fmt.Errorf("Invalid address \"%s\".", bounds[0])
+		}
+		if to, ok = lookupSymbol(obj, bounds[1]); !ok {
+			return fmt.Errorf("Invalid address \"%s\".", bounds[1])
+		}
+		if from > to {
+			from, to = to, from
+		}
+	}
+
+	changes := diffMemory(c, from, to)
+	for _, line := range changes {
+		cometPrint(line)
+	}
+	cometPrint(fmt.Sprintf("%d words changed.", len(changes)))
+	return nil
+}
+
+// diffMemory describes each word from from to to that differs from
+// memMark as "ADDRESS <LABEL>	OLD -> NEW".
+func diffMemory(c *Comet2, from, to int) []string {
+	var changes []string
+	for addr := from; addr <= to && addr < len(memMark); addr++ {
+		old, val := int(memMark[addr]), memGet(c.Memory, addr)
+		if old != val {
+			changes = append(changes, fmt.Sprintf("%s\t%s -> %s", c.formatAddr(addr), formatWord(old), formatWord(val)))
+		}
+	}
+	return changes
 }
 
-func cmdDisasm(memory []uint16, state []int, args []string) error {
+func cmdStack(c *Comet2, args []string) error {
+	return cmdDump(c, []string{strconv.Itoa(c.State[SP])})
+}
+
+func cmdDisasm(c *Comet2, args []string) error {
+	memory, state := c.Memory, c.State
 	val := state[PC]
 	if len(args) > 0 {
-		if n, ok := expandNumber(args[0]); ok {
+		if n, ok := parseAddress(c, args[0]); ok {
 			val = n
 		}
 	}
@@ -180,8 +414,14 @@ func cmdDisasm(memory []uint16, state []int, args []string) error {
 	state[PC] = val
 
 	for i := 0; i < 16; i++ {
+		// Words the assembler emitted as data are not decoded
+		if isData(c.Regions, state[PC]) {
+			cometPrint(fmt.Sprintf("%s\tDC\t#%s", c.formatAddr(state[PC]), hex(memGet(memory, state[PC]), 4)))
+			state[PC]++
+			continue
+		}
 		inst, opr, size := parse(memory, state)
-		cometPrint(fmt.Sprintf("#%s\t%s\t%s", hex(state[PC], 4), inst, opr))
+		cometPrint(fmt.Sprintf("%s\t%s\t%s", c.formatAddr(state[PC]), inst, opr))
 		state[PC] += size
 	}
 
@@ -191,16 +431,424 @@ func cmdDisasm(memory []uint16, state []int, args []string) error {
 	return nil
 }
 
-func cmdHelp(memory []uint16, state []int, args []string) error {
+func cmdHelp(c *Comet2, args []string) error {
 	cometPrint("List of commands:")
 	cometPrint("r,  run             \t\tStart execution of program.")
+	cometPrint("ro, run-until-out [N]\t\tRun until N (default 1) more OUT lines are written.")
 	cometPrint("s,  step  [N]       \t\tStep execution. Argument N means do this N times.")
 	cometPrint("p,  print           \t\tPrint status of PC/FR/SP/GR0..GR7 registers.")
 	cometPrint("du, dump [ADDRESS]  \t\tDump 128 words of memory image from specified ADDRESS.")
 	cometPrint("st, stack           \t\tDump 128 words of stack image.")
-	cometPrint("di, disasm [ADDRESS]\t\tDisassemble 32 words from specified ADDRESS.")
+	cometPrint("di, disasm [ADDRESS]\t\tDisassemble 16 instructions from ADDRESS or a label.")
+	cometPrint("b,  break ADDRESS   \t\tSet a breakpoint at specified ADDRESS.")
+	cometPrint("tbreak ADDRESS      \t\tSet a breakpoint deleted when it is first hit.")
+	cometPrint("enable/disable [N ...]\t\tEnable or disable breakpoints N (without N, all).")
+	cometPrint("ignore N COUNT      \t\tDo not stop at breakpoint N the next COUNT times.")
+	cometPrint("bf, break-flag [FLAG ...]\tStop run when an instruction sets FLAG (OF, SF, ZF).")
+	cometPrint("w,  watch [REGISTER ...]\tStop run when REGISTER (GR0-GR7, SP) changes.")
+	cometPrint("d,  delete [N ...]  \t\tDelete breakpoints (without N, also flag breaks and watches).")
+	cometPrint("i,  info [break]    \t\tPrint breakpoint information.")
+	cometPrint("f,  file FILE       \t\tUse FILE as program to be debugged.")
+	cometPrint("j,  jump ADDRESS [force]\tContinue program at ADDRESS, the start of an instruction.")
+	cometPrint("m,  memory ADDRESS VALUE\tChange the memory at ADDRESS to VALUE.")
+	cometPrint("mem [ADDRESS|LABEL] \t\tList the program by label, a page at a time.")
+	cometPrint("set REGISTER VALUE  \t\tChange GR0-GR7, SP, PR or FR; set MEM[ADDRESS] VALUE changes a word.")
+	cometPrint("fill FROM TO VALUE  \t\tSet the words from FROM to TO to VALUE.")
+	cometPrint("u,  undo [N]        \t\tRevert the last N (default 1) instructions or set/fill/jump edits.")
+	cometPrint("char VALUE ...      \t\tShow the character of each VALUE (65, #41, 0x41).")
+	cometPrint("code 'TEXT'         \t\tShow the code of each character of TEXT.")
+	cometPrint("ascii               \t\tPrint a table of the printable characters.")
+	cometPrint("mark                \t\tSave the memory for diffmem.")
+	cometPrint("diffmem [FROM-TO]   \t\tShow the words changed since mark.")
+	cometPrint("checkpoint [NAME]   \t\tSave the machine state to NAME.c2cp, or list checkpoints.")
+	cometPrint("restore NAME        \t\tLoad the machine state saved by checkpoint NAME.")
+	cometPrint("alias [NAME [COMMAND]]\t\tDefine NAME as COMMAND, or list aliases.")
+	cometPrint("unalias NAME        \t\tRemove alias NAME.")
 	cometPrint("h,  help            \t\tPrint list of commands.")
 	cometPrint("q,  quit            \t\tExit comet2.")
 
 	return nil
 }
+
+// cmdBreak sets a breakpoint at ADDRESS, a number, a label or LABEL+N.
+func cmdBreak(c *Comet2, args []string) error {
+	return addBreakpoint(c, args, false)
+}
+
+// cmdTbreak sets a breakpoint that is deleted when it first stops run.
+func cmdTbreak(c *Comet2, args []string) error {
+	return addBreakpoint(c, args, true)
+}
+
+func addBreakpoint(c *Comet2, args []string, temporary bool) error {
+	if len(args) != 1 {
+		if temporary {
+			return fmt.Errorf("Usage: tbreak ADDRESS")
+		}
+		return fmt.Errorf("Usage: break ADDRESS")
+	}
+	addr, ok := parseAddress(c, args[0])
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", args[0])
+	}
+	bp := c.Breakpoints.Add(addr, temporary)
+	bp.Label = breakpointLabel(args[0])
+	cometPrint(fmt.Sprintf("Breakpoint %d at %s", bp.ID, c.formatAddr(addr)))
+	return nil
+}
+
+// findBreakpoints returns the breakpoints numbered by args, or all of them
+// without arguments.
+func findBreakpoints(c *Comet2, args []string) ([]*Breakpoint, error) {
+	if len(args) == 0 {
+		return c.Breakpoints.All(), nil
+	}
+	var found []*Breakpoint
+	for _, arg := range args {
+		id, err := strconv.Atoi(arg)
+		bp := c.Breakpoints.Get(id)
+		if err != nil || bp == nil {
+			return nil, fmt.Errorf("No breakpoint number %s.", arg)
+		}
+		found = append(found, bp)
+	}
+	return found, nil
+}
+
+func cmdEnable(c *Comet2, args []string) error {
+	bps, err := findBreakpoints(c, args)
+	if err != nil {
+		return err
+	}
+	for _, bp := range bps {
+		bp.Enabled = true
+	}
+	return nil
+}
+
+func cmdDisable(c *Comet2, args []string) error {
+	bps, err := findBreakpoints(c, args)
+	if err != nil {
+		return err
+	}
+	for _, bp := range bps {
+		bp.Enabled = false
+	}
+	return nil
+}
+
+// cmdIgnore makes breakpoint N let the program through the next COUNT
+// times it is reached.
+func cmdIgnore(c *Comet2, args []string) error {
+	if len(args) != 2 {
+		return fmt.Errorf("Usage: ignore N COUNT")
+	}
+	bps, err := findBreakpoints(c, args[:1])
+	if err != nil {
+		return err
+	}
+	count, err := strconv.Atoi(args[1])
+	if err != nil || count < 0 {
+		return fmt.Errorf("Invalid count \"%s\".", args[1])
+	}
+	bps[0].Ignore = count
+	return nil
+}
+
+// cmdBreakFlag makes run stop when an instruction sets one of the given
+// flags (OF, SF, ZF). Without arguments it lists them.
+func cmdBreakFlag(c *Comet2, args []string) error {
+	for _, arg := range args {
+		found := false
+		for _, fb := range flagBits {
+			if strings.EqualFold(arg, fb.name) {
+				flagBreaks |= fb.bit
+				found = true
+			}
+		}
+		if !found {
+			return fmt.Errorf("Invalid flag \"%s\" (OF, SF, ZF).", arg)
+		}
+	}
+	if len(args) == 0 {
+		cmdInfo(c, nil)
+	}
+	return nil
+}
+
+// cmdWatch makes run stop when one of the given registers (GR0-GR7, SP)
+// changes. Without arguments it lists the watches.
+func cmdWatch(c *Comet2, args []string) error {
+	for _, arg := range args {
+		reg := -1
+		for i := GR0; i <= SP; i++ {
+			if strings.EqualFold(arg, registerName(i)) {
+				reg = i
+			}
+		}
+		if reg < 0 {
+			return fmt.Errorf("Invalid register \"%s\" (GR0-GR7, SP).", arg)
+		}
+		if !slices.Contains(watches, reg) {
+			watches = append(watches, reg)
+		}
+	}
+	if len(args) == 0 {
+		cmdInfo(c, nil)
+	}
+	return nil
+}
+
+// registerName returns the name of State[i] for a general register or SP.
+func registerName(i int) string {
+	if i == SP {
+		return "SP"
+	}
+	return fmt.Sprintf("GR%d", i-GR0)
+}
+
+// flagBreakNames lists the flags selected with break-flag.
+func flagBreakNames() string {
+	var names []string
+	for _, fb := range flagBits {
+		if flagBreaks&fb.bit != 0 {
+			names = append(names, fb.name)
+		}
+	}
+	return strings.Join(names, " ")
+}
+
+func cmdDelete(c *Comet2, args []string) error {
+	if len(args) == 0 {
+		if monitorConfirm("Delete all breakpoints? (y or n) ") {
+			c.Breakpoints.Clear()
+			flagBreaks = 0
+			watches = nil
+		}
+		return nil
+	}
+	bps, err := findBreakpoints(c, args)
+	if err != nil {
+		return err
+	}
+	for _, bp := range bps {
+		c.Breakpoints.Delete(bp.ID)
+	}
+	return nil
+}
+
+// cmdInfo lists the breakpoints with their state and hit counts, then the
+// flag breaks and watches. "info break" lists only the breakpoints.
+func cmdInfo(c *Comet2, args []string) error {
+	if len(args) > 0 && args[0] != "break" && args[0] != "b" {
+		return fmt.Errorf("Undefined info command \"%s\". Try \"info break\".", args[0])
+	}
+	for _, bp := range c.Breakpoints.All() {
+		cometPrint(fmt.Sprintf("%d: %s %s", bp.ID, c.formatAddr(bp.Addr), bp))
+	}
+	if len(args) > 0 {
+		if c.Breakpoints.Len() == 0 {
+			cometPrint("No breakpoints.")
+		}
+		return nil
+	}
+	if flagBreaks != 0 {
+		cometPrint("Break when set: " + flagBreakNames())
+	}
+	if len(watches) > 0 {
+		names := make([]string, len(watches))
+		for i, reg := range watches {
+			names[i] = registerName(reg)
+		}
+		cometPrint("Watch: " + strings.Join(names, " "))
+	}
+	return nil
+}
+
+func cmdFile(c *Comet2, args []string) error {
+	if len(args) != 1 {
+		return fmt.Errorf("Usage: file FILE")
+	}
+	var obj *Object
+	var err error
+	if strings.HasSuffix(args[0], OBJECT_EXT) {
+		obj, err = loadObjectFile(args[0])
+	} else {
+		obj, err = assemble(args[0], newAssemblerState())
+	}
+	if err != nil {
+		return err
+	}
+	c.Reset(obj)
+	cometPrint("Successfully loaded.")
+	// Breakpoints set at a label stay at it in the new program
+	moved, lost := c.Breakpoints.Relocate(obj)
+	for _, bp := range moved {
+		cometPrint(fmt.Sprintf("Breakpoint %d moved to %s", bp.ID, c.formatAddr(bp.Addr)))
+	}
+	for _, bp := range lost {
+		cometPrint(fmt.Sprintf("Breakpoint %d at %s no longer resolves and is disabled", bp.ID, bp.Label))
+	}
+	if verbosity.dump {
+		cmdPrint(c, []string{})
+	}
+	return nil
+}
+
+// cmdJump sets PR to ADDRESS, a number, a label or LABEL+N, so that a
+// broken part of the program can be skipped. The address must be the first
+// word of an instruction of the program unless "force" follows it. The jump
+// can be undone.
+func cmdJump(c *Comet2, args []string) error {
+	if len(args) < 1 || len(args) > 2 || (len(args) == 2 && args[1] != "force") {
+		return fmt.Errorf("Usage: jump ADDRESS [force]")
+	}
+	addr, ok := parseAddress(c, args[0])
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", args[0])
+	}
+	if len(args) == 1 {
+		if err := checkJumpTarget(c, addr); err != nil {
+			return err
+		}
+	}
+	if log := findUndoLog(c); log != nil {
+		log.beginEdit(c)
+	}
+	c.State[PC] = addr
+	if verbosity.dump {
+		cmdPrint(c, []string{})
+	}
+	return nil
+}
+
+// checkJumpTarget reports why addr is not the first word of an instruction
+// of the program, or nil. Instructions are decoded from the start of the
+// code region addr is in; without region information any word of the
+// program is accepted.
+func checkJumpTarget(c *Comet2, addr int) error {
+	if addr >= c.AddressMax {
+		return fmt.Errorf("#%s is outside the program (use \"jump ADDRESS force\" to jump anyway).", hex(addr, 4))
+	}
+	for _, r := range c.Regions {
+		if addr < r.Start || addr >= r.End {
+			continue
+		}
+		if !r.Code {
+			return fmt.Errorf("%s is data, not an instruction (use \"jump ADDRESS force\" to jump anyway).",
+				c.formatAddr(addr))
+		}
+		state := make([]int, len(c.State))
+		pc, inst := r.Start, r.Start
+		for pc < addr {
+			state[PC] = pc
+			_, _, size := parse(c.Memory, state)
+			inst, pc = pc, pc+size
+		}
+		if pc != addr {
+			return fmt.Errorf("%s is inside the instruction at %s (use \"jump ADDRESS force\" to jump anyway).",
+				c.formatAddr(addr), c.formatAddr(inst))
+		}
+	}
+	return nil
+}
+
+func cmdMemory(c *Comet2, args []string) error {
+	if len(args) != 2 {
+		return fmt.Errorf("Usage: memory ADDRESS VALUE")
+	}
+	addr, ok := expandNumber(args[0])
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", args[0])
+	}
+	val, ok := expandNumber(args[1])
+	if !ok {
+		return fmt.Errorf("Invalid value \"%s\".", args[1])
+	}
+	c.poke(addr, val)
+	return nil
+}
+
+func cmdAlias(c *Comet2, args []string) error {
+	if len(args) == 0 {
+		names := make([]string, 0, len(monitorAliases))
+		for name := range monitorAliases {
+			names = append(names, name)
+		}
+		sort.Strings(names)
+		for _, name := range names {
+			cometPrint(fmt.Sprintf("alias %s %s", name, monitorAliases[name]))
+		}
+		return nil
+	}
+
+	name := args[0]
+	if len(args) == 1 {
+		expansion, ok := monitorAliases[name]
+		if !ok {
+			return fmt.Errorf("Undefined alias \"%s\".", name)
+		}
+		cometPrint(fmt.Sprintf("alias %s %s", name, expansion))
+		return nil
+	}
+
+	if _, ok := monitorCommands[name]; ok || name == "q" || name == "quit" {
+		return fmt.Errorf("Can't redefine command \"%s\".", name)
+	}
+	if _, ok := monitorCommands[args[1]]; !ok {
+		return fmt.Errorf("Undefined command \"%s\". Try \"help\".", args[1])
+	}
+	monitorAliases[name] = strings.Join(args[1:], " ")
+	return nil
+}
+
+func cmdUnalias(c *Comet2, args []string) error {
+	if len(args) != 1 {
+		return fmt.Errorf("Usage: unalias NAME")
+	}
+	if _, ok := monitorAliases[args[0]]; !ok {
+		return fmt.Errorf("Undefined alias \"%s\".", args[0])
+	}
+	delete(monitorAliases, args[0])
+	return nil
+}
+
+// startupFiles returns the monitor command files to run at startup:
+// ~/.c2c2rc unless home is false, then extra when it is given. A .c2c2rc
+// in the current directory is not run by itself, since it may have come
+// with a downloaded program; "-rc .c2c2rc" opts in to it.
+func startupFiles(home bool, extra string) []string {
+	var files []string
+	if dir, err := os.UserHomeDir(); err == nil && home {
+		files = append(files, filepath.Join(dir, STARTUP_FILE))
+	}
+	if extra != "" {
+		files = append(files, extra)
+	}
+	return files
+}
+
+// runStartupFile executes the monitor commands in path, one per line.
+// Empty lines and lines starting with "#" are skipped. A missing file is
+// not an error; a failing command is reported and the rest still run.
+func runStartupFile(path string, c *Comet2) error {
+	f, err := os.Open(path)
+	if os.IsNotExist(err) {
+		return nil
+	} else if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot read startup file: %v", err)
+	}
+	defer f.Close()
+
+	scanner := bufio.NewScanner(f)
+	for line := 1; scanner.Scan(); line++ {
+		parts := strings.Fields(scanner.Text())
+		if len(parts) == 0 || strings.HasPrefix(parts[0], "#") {
+			continue
+		}
+		if err := executeCommand(parts[0], parts[1:], c); err != nil {
+			fmt.Fprintf(os.Stderr, "%s:%d: %v\n", path, line, err)
+		}
+	}
+	return scanner.Err()
+}
@@ -0,0 +1,220 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// saveMonitorState restores the monitor settings that commands change
+// when the test ends, and starts it without any.
+func saveMonitorState(t *testing.T) {
+	t.Helper()
+
+	aliases, breaks, watched, mark := monitorAliases, flagBreaks, watches, memMark
+	t.Cleanup(func() {
+		monitorAliases, flagBreaks, watches, memMark = aliases, breaks, watched, mark
+		nextCmd = ""
+	})
+	monitorAliases, flagBreaks, watches, memMark = map[string]string{}, 0, nil, nil
+}
+
+// runMonitor runs the monitor command line cmd on c and then the commands
+// it leaves in nextCmd, as the monitor loop does.
+func runMonitor(t *testing.T, c *Comet2, cmd string) {
+	t.Helper()
+
+	for nextCmd = cmd; nextCmd != ""; {
+		parts := strings.Fields(nextCmd)
+		if err := executeCommand(parts[0], parts[1:], c); err != nil {
+			t.Fatalf("%s failed: %v", cmd, err)
+		}
+	}
+}
+
+func TestMonitorAliases(t *testing.T) {
+	saveMonitorState(t)
+	c := assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,1\n\tLAD\tGR1,2,GR1\n\tRET\n\tEND\n")
+
+	path := filepath.Join(t.TempDir(), STARTUP_FILE)
+	rc := "# startup\nalias s2 step 2\n\nalias s print\nalias x bogus\n"
+	if err := os.WriteFile(path, []byte(rc), 0644); err != nil {
+		t.Fatalf("Failed to write startup file: %v", err)
+	}
+	if err := runStartupFile(path, c); err != nil {
+		t.Fatalf("Failed to run startup file: %v", err)
+	}
+	if want := map[string]string{"s2": "step 2"}; !reflect.DeepEqual(monitorAliases, want) {
+		t.Errorf("Expected aliases %v, got %v", want, monitorAliases)
+	}
+
+	if err := executeCommand("s2", nil, c); err != nil {
+		t.Fatalf("Alias failed: %v", err)
+	}
+	if c.State[PC] != 2 || nextCmd != "step 1" {
+		t.Errorf("Expected alias to run \"step 2\", got PC #%s and next %q", hex(c.State[PC], 4), nextCmd)
+	}
+	nextCmd = ""
+
+	if err := executeCommand("unalias", []string{"s2"}, c); err != nil || len(monitorAliases) != 0 {
+		t.Errorf("Expected alias to be removed, got %v", err)
+	}
+	if err := runStartupFile(filepath.Join(t.TempDir(), "missing"), c); err != nil {
+		t.Errorf("Expected missing startup file to be ignored, got %v", err)
+	}
+}
+
+func TestStartupFiles(t *testing.T) {
+	home := t.TempDir()
+	t.Setenv("HOME", home)
+	t.Setenv("USERPROFILE", home)
+	t.Chdir(t.TempDir())
+	if err := os.WriteFile(STARTUP_FILE, []byte("alias x print\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+
+	want := []string{filepath.Join(home, STARTUP_FILE)}
+	if got := startupFiles(true, ""); !reflect.DeepEqual(got, want) {
+		t.Errorf("Expected only the home startup file, got %v", got)
+	}
+	want = append(want, STARTUP_FILE)
+	if got := startupFiles(true, STARTUP_FILE); !reflect.DeepEqual(got, want) {
+		t.Errorf("Expected %v with -rc, got %v", want, got)
+	}
+	if got := startupFiles(false, ""); len(got) != 0 {
+		t.Errorf("Expected no startup files with -no-rc, got %v", got)
+	}
+}
+
+func TestRunUntilOut(t *testing.T) {
+	saveMonitorState(t)
+	c := assembleSource(t, `MAIN	START
+	LAD	GR3,3
+LOOP	OUT	MSG,LEN
+	SUBA	GR3,=1
+	JNZ	LOOP
+	RET
+MSG	DC	'hi'
+LEN	DC	2
+	END
+`)
+	runMonitor(t, c, "ro")
+	if len(c.Transcript) != 1 || c.State[GR3] != 3 {
+		t.Errorf("Expected to stop after the first OUT, got %d lines and GR3 = %d", len(c.Transcript), c.State[GR3])
+	}
+	runMonitor(t, c, "run-until-out 2")
+	if len(c.Transcript) != 3 || c.State[GR3] != 1 {
+		t.Errorf("Expected to stop after the third OUT, got %d lines and GR3 = %d", len(c.Transcript), c.State[GR3])
+	}
+}
+
+func TestFlagBreak(t *testing.T) {
+	saveMonitorState(t)
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,#7FFE
+	ADDA	GR1,=1
+	ADDA	GR1,=1
+	LAD	GR2,0
+	RET
+	END
+`)
+	if err := executeCommand("bf", []string{"of"}, c); err != nil || flagBreaks != FR_OVER {
+		t.Fatalf("Expected a break on OF, got %d (%v)", flagBreaks, err)
+	}
+	if err := executeCommand("break-flag", []string{"CF"}, c); err == nil {
+		t.Errorf("Expected an error for an unknown flag")
+	}
+
+	// The first ADDA gives #7FFF; the second one overflows
+	runMonitor(t, c, "r")
+	if c.State[PC] != 6 || c.LastFlagChange == nil || c.LastFlagChange.PC != 4 {
+		t.Errorf("Expected to stop after the second ADDA, got PC #%s", hex(c.State[PC], 4))
+	}
+}
+
+func TestWatchRegister(t *testing.T) {
+	saveMonitorState(t)
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,1
+	LAD	GR2,2
+	LAD	GR3,0
+	LAD	GR3,3
+	RET
+	END
+`)
+	if err := executeCommand("watch", []string{"gr3"}, c); err != nil || !reflect.DeepEqual(watches, []int{GR3}) {
+		t.Fatalf("Expected a watch on GR3, got %v (%v)", watches, err)
+	}
+	if err := executeCommand("w", []string{"GR8"}, c); err == nil {
+		t.Errorf("Expected an error for an unknown register")
+	}
+
+	// LAD GR3,0 leaves GR3 unchanged
+	runMonitor(t, c, "r")
+	if c.State[PC] != 8 || c.State[GR3] != 3 {
+		t.Errorf("Expected to stop after LAD GR3,3, got PC #%s", hex(c.State[PC], 4))
+	}
+}
+
+func TestDiffMem(t *testing.T) {
+	saveMonitorState(t)
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,5
+	ST	GR1,A
+	ST	GR1,C
+	RET
+A	DS	1
+B	DC	7
+C	DS	1
+	END
+`)
+	if err := executeCommand("diffmem", nil, c); err == nil {
+		t.Errorf("Expected an error without a mark")
+	}
+	if err := executeCommand("mark", nil, c); err != nil {
+		t.Fatalf("mark failed: %v", err)
+	}
+	c.Run()
+
+	want := []string{
+		"#0007 <A>\t#0000(     0) -> #0005(     5)",
+		"#0009 <C>\t#0000(     0) -> #0005(     5)",
+	}
+	if got := diffMemory(c, 0, 0xffff); !reflect.DeepEqual(got, want) {
+		t.Errorf("Expected %q, got %q", want, got)
+	}
+	if err := executeCommand("diffmem", []string{"B-C"}, c); err != nil {
+		t.Errorf("diffmem B-C failed: %v", err)
+	}
+	if err := executeCommand("diffmem", []string{"B"}, c); err == nil {
+		t.Errorf("Expected an error for a range without an end")
+	}
+}
+
+func TestMonitorBreakpoints(t *testing.T) {
+	c := assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,1\n\tLAD\tGR1,2,GR1\n\tRET\n\tEND\n")
+
+
+	if err := executeCommand("b", []string{"#0002"}, c); err != nil {
+		t.Fatalf("break failed: %v", err)
+	}
+	runMonitor(t, c, "r")
+	if c.State[PC] != 2 || c.State[GR1] != 1 {
+		t.Errorf("Expected to stop at #0002 with GR1 = 1, got PC #%s, GR1 %d", hex(c.State[PC], 4), c.State[GR1])
+	}
+
+	if err := executeCommand("m", []string{"#0010", "-1"}, c); err != nil || c.Memory[0x10] != 0xffff {
+		t.Errorf("Expected memory #0010 = #ffff, got #%s (%v)", hex(int(c.Memory[0x10]), 4), err)
+	}
+	if err := executeCommand("j", []string{"0"}, c); err != nil || c.State[PC] != 0 {
+		t.Errorf("Expected jump to #0000, got PC #%s (%v)", hex(c.State[PC], 4), err)
+	}
+	if err := executeCommand("d", []string{"2"}, c); err == nil {
+		t.Errorf("Expected error for a missing breakpoint")
+	}
+	if err := executeCommand("d", []string{"1"}, c); err != nil || c.Breakpoints.Len() != 0 {
+		t.Errorf("Expected breakpoint to be deleted, got %d (%v)", c.Breakpoints.Len(), err)
+	}
+}
@@ -0,0 +1,184 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"slices"
+	"sort"
+	"strings"
+)
+
+// normalizedLine is a statement reduced to its structure: labels renamed
+// L1, L2, ... in order of appearance, numbers written as N and strings as
+// 'S'. Two programs that differ only in names, constants and comments
+// normalize to the same lines.
+type normalizedLine struct {
+	// line is the line number in the source.
+	line int
+	text string
+}
+
+// normalizeProgram returns the normalized statements of source, leaving
+// out blank lines, comments and preprocessor lines.
+func normalizeProgram(source string) ([]normalizedLine, error) {
+	names := make(map[string]string)
+	label := func(s string) string {
+		s = foldCase(s)
+		if _, ok := names[s]; !ok {
+			names[s] = fmt.Sprintf("L%d", len(names)+1)
+		}
+		return names[s]
+	}
+	value := func(s string) string {
+		switch {
+		case strings.HasPrefix(s, "'"):
+			return "'S'"
+		case isRegister(s):
+			return strings.ToUpper(s)
+		}
+		if _, ok := expandNumber(s); ok {
+			return "N"
+		}
+		return label(s)
+	}
+
+	var lines []normalizedLine
+	for i, text := range strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n") {
+		code := text
+		if idx := commentIndex(code); idx >= 0 {
+			code = code[:idx]
+		}
+		if conditionalPattern.MatchString(code) || includeFilePattern.MatchString(code) || includeStdPattern.MatchString(code) {
+			continue
+		}
+		parsed, err := ParseLine(text, i+1)
+		if err != nil {
+			return nil, fmt.Errorf("[CASL2 ERROR] %v", err)
+		}
+		if parsed.Instruction == "" {
+			continue
+		}
+		parts := []string{"", strings.ToUpper(parsed.Instruction)}
+		if parsed.Label != "" {
+			parts[0] = label(parsed.Label)
+		}
+		operands := make([]string, len(parsed.Operands))
+		for j, opr := range parsed.Operands {
+			if lit, ok := strings.CutPrefix(opr, "="); ok {
+				operands[j] = "=" + value(lit)
+			} else {
+				operands[j] = value(opr)
+			}
+		}
+		parts = append(parts, strings.Join(operands, ","))
+		lines = append(lines, normalizedLine{line: i + 1, text: strings.Join(parts, "\t")})
+	}
+	return lines, nil
+}
+
+// matchedRegion is a run of length normalized lines that is the same in
+// both programs, from index a of the first and index b of the second.
+type matchedRegion struct {
+	a, b   int
+	length int
+}
+
+// tileMatches pairs up the lines of a and b by greedy string tiling: it
+// takes the longest run of equal lines not yet matched, then the next
+// longest, and so on down to minLength lines. Moved or reordered blocks
+// are found as separate regions.
+func tileMatches(a, b []normalizedLine, minLength int) []matchedRegion {
+	usedA := make([]bool, len(a))
+	usedB := make([]bool, len(b))
+	var regions []matchedRegion
+	for {
+		best := matchedRegion{}
+		for i := range a {
+			for j := range b {
+				n := 0
+				for i+n < len(a) && j+n < len(b) && !usedA[i+n] && !usedB[j+n] && a[i+n].text == b[j+n].text {
+					n++
+				}
+				if n > best.length {
+					best = matchedRegion{i, j, n}
+				}
+			}
+		}
+		if best.length < max(minLength, 1) {
+			break
+		}
+		for k := 0; k < best.length; k++ {
+			usedA[best.a+k] = true
+			usedB[best.b+k] = true
+		}
+		regions = append(regions, best)
+	}
+	return regions
+}
+
+// formatComparison renders the similarity score of two programs, the
+// share of their statements in matched regions, and the regions by their
+// lines in the first program.
+func formatComparison(pathA string, pathB string, first, second []normalizedLine, regions []matchedRegion) string {
+	var b strings.Builder
+	matched := 0
+	for _, r := range regions {
+		matched += r.length
+	}
+	score := 0
+	if len(first)+len(second) > 0 {
+		score = 200 * matched / (len(first) + len(second))
+	}
+	fmt.Fprintf(&b, "Similarity: %d%% (%d of %d statements of %s, %d of %d of %s)\n", score, matched, len(first), pathA, matched, len(second), pathB)
+	if len(regions) == 0 {
+		b.WriteString("No matched regions\n")
+		return b.String()
+	}
+	sorted := slices.Clone(regions)
+	sort.Slice(sorted, func(i, j int) bool {
+		return sorted[i].a < sorted[j].a
+	})
+	for _, r := range sorted {
+		fmt.Fprintf(&b, "%s:%d-%d  %s:%d-%d  (%d statements)\n", pathA, first[r.a].line, first[r.a+r.length-1].line,
+			pathB, second[r.b].line, second[r.b+r.length-1].line, r.length)
+	}
+	return b.String()
+}
+
+// runCompareCommand implements "c2c2 compare A.cas B.cas": how much of two
+// programs is the same apart from names, constants and comments, to help
+// screen submissions for copying. It returns the exit status.
+func runCompareCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("compare", flag.ContinueOnError)
+	fs.SetOutput(w)
+	minLength := fs.Int("min", 3, "smallest number of statements in a row that counts as a matched region")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 compare [-min N] A.cas B.cas\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 2 {
+		fs.Usage()
+		return 2
+	}
+
+	var programs [2][]normalizedLine
+	for i, path := range fs.Args() {
+		source, err := os.ReadFile(path)
+		if err != nil {
+			fmt.Fprintf(w, "[CASL2 ERROR] Cannot read file: %v\n", err)
+			return 1
+		}
+		if programs[i], err = normalizeProgram(string(source)); err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+	}
+	regions := tileMatches(programs[0], programs[1], *minLength)
+	fmt.Fprint(w, formatComparison(fs.Arg(0), fs.Arg(1), programs[0], programs[1], regions))
+	return 0
+}
@@ -0,0 +1,49 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestCompare(t *testing.T) {
+	a, err := normalizeProgram(`MAIN	START
+	LAD	GR1,0
+LOOP	ADDA	GR1,STEP
+	CPA	GR1,LIMIT
+	JMI	LOOP
+	RET
+STEP	DC	1
+LIMIT	DC	10
+	END
+`)
+	if err != nil {
+		t.Fatal(err)
+	}
+	// Renamed, with other constants, comments and one more statement
+	b, err := normalizeProgram(`; copied
+PROG	START
+	LAD	GR1,0
+AGAIN	ADDA	GR1,INC	; add
+	CPA	GR1,MAX
+	JMI	AGAIN
+	NOP
+	RET
+INC	DC	2
+MAX	DC	20
+	END
+`)
+	if err != nil {
+		t.Fatal(err)
+	}
+	regions := tileMatches(a, b, 3)
+	expected := "Similarity: 94% (9 of 9 statements of a.cas, 9 of 10 of b.cas)\n" +
+		"a.cas:1-5  b.cas:2-6  (5 statements)\n" +
+		"a.cas:6-9  b.cas:8-11  (4 statements)\n"
+	if got := formatComparison("a.cas", "b.cas", a, b, regions); got != expected {
+		t.Errorf("Expected\n%s\ngot\n%s", expected, got)
+	}
+
+	// Runs shorter than the minimum do not count
+	if regions := tileMatches(a, b, 6); len(regions) != 0 {
+		t.Errorf("Expected no regions of 6 statements, got %v", regions)
+	}
+}
@@ -0,0 +1,305 @@
+package main
+
+import (
+	"fmt"
+	"strconv"
+)
+
+// Monitor commands of -compat: the command set of the original Perl c2c2
+// monitor, with its output, so that transcripts in course handouts stay
+// accurate. Breakpoints are numbered by their position in the list, as
+// there, so deleting one renumbers those after it.
+var compatCommands = map[string]func(*Comet2, []string) error{
+	"r":      compatRun,
+	"run":    compatRun,
+	"g":      compatRun,
+	"go":     compatRun,
+	"s":      compatStep,
+	"step":   compatStep,
+	"t":      compatTrace,
+	"trace":  compatTrace,
+	"b":      compatBreak,
+	"break":  compatBreak,
+	"d":      compatDelete,
+	"del":    compatDelete,
+	"delete": compatDelete,
+	"i":      compatInfo,
+	"info":   compatInfo,
+	"p":      compatPrint,
+	"print":  compatPrint,
+	"du":     compatDump,
+	"dump":   compatDump,
+	"st":     compatStack,
+	"stack":  compatStack,
+	"di":     compatDisasm,
+	"disasm": compatDisasm,
+	"f":      compatFile,
+	"file":   compatFile,
+	"j":      compatJump,
+	"jump":   compatJump,
+	"m":      cmdMemory,
+	"memory": cmdMemory,
+	"h":      compatHelp,
+	"help":   compatHelp,
+}
+
+// compatArgument returns the number in args, or an error when there is
+// none.
+func compatArgument(args []string) (int, error) {
+	if len(args) == 0 {
+		return 0, fmt.Errorf("Invalid argument.")
+	}
+	n, ok := expandNumber(args[0])
+	if !ok {
+		return 0, fmt.Errorf("Invalid argument.")
+	}
+	return n, nil
+}
+
+// compatRun executes one instruction and goes on through nextCmd until
+// the program ends or reaches a breakpoint.
+func compatRun(c *Comet2, args []string) error {
+	if err := c.checkCancelled(); err != nil {
+		nextCmd = ""
+		return err
+	}
+
+	nextCmd = "run"
+	stopFlag, err := c.stepExec()
+	if err != nil {
+		nextCmd = ""
+		return err
+	}
+	if stopFlag {
+		return nil
+	}
+
+	for i, bp := range c.Breakpoints.All() {
+		if bp.Enabled && bp.Addr == c.State[PC] {
+			nextCmd = ""
+			cometPrint(fmt.Sprintf("Breakpoint %d, #%s", i+1, hex(bp.Addr, 4)))
+			if verbosity.dump {
+				compatPrint(c, nil)
+			}
+			return nil
+		}
+	}
+	return nil
+}
+
+// compatStep executes N instructions (default 1) and then prints the
+// registers once.
+func compatStep(c *Comet2, args []string) error {
+	count := 1
+	if len(args) > 0 {
+		if n, ok := expandNumber(args[0]); ok {
+			count = n
+		}
+	}
+
+	count--
+	if count > 0 {
+		nextCmd = fmt.Sprintf("step %d", count)
+	} else {
+		nextCmd = ""
+	}
+
+	if _, err := c.stepExec(); err != nil {
+		nextCmd = ""
+		return err
+	}
+	if nextCmd == "" && verbosity.dump {
+		compatPrint(c, nil)
+	}
+	return nil
+}
+
+// compatTrace executes N instructions (default 1), printing the registers
+// after each of them.
+func compatTrace(c *Comet2, args []string) error {
+	count := 1
+	if len(args) > 0 {
+		if n, ok := expandNumber(args[0]); ok {
+			count = n
+		}
+	}
+
+	count--
+	if count > 0 {
+		nextCmd = fmt.Sprintf("trace %d", count)
+	} else {
+		nextCmd = ""
+	}
+
+	if _, err := c.stepExec(); err != nil {
+		nextCmd = ""
+		return err
+	}
+	compatPrint(c, nil)
+	return nil
+}
+
+func compatBreak(c *Comet2, args []string) error {
+	addr, err := compatArgument(args)
+	if err != nil {
+		return err
+	}
+	c.Breakpoints.Add(addr, false)
+	return nil
+}
+
+// compatDelete deletes the Nth breakpoint, or all of them once confirmed.
+func compatDelete(c *Comet2, args []string) error {
+	if len(args) == 0 {
+		if monitorConfirm("Delete all breakpoints? (y or n) ") {
+			c.Breakpoints.Clear()
+		}
+		return nil
+	}
+	n, err := compatArgument(args)
+	if err != nil {
+		return err
+	}
+	bps := c.Breakpoints.All()
+	if n < 1 || n > len(bps) {
+		return fmt.Errorf("Invalid argument.")
+	}
+	c.Breakpoints.Delete(bps[n-1].ID)
+	return nil
+}
+
+func compatInfo(c *Comet2, args []string) error {
+	bps := c.Breakpoints.All()
+	if len(bps) == 0 {
+		cometPrint("No breakpoints.")
+		return nil
+	}
+	for i, bp := range bps {
+		cometPrint(fmt.Sprintf("%d: #%s", i+1, hex(bp.Addr, 4)))
+	}
+	return nil
+}
+
+// compatPrint prints PR, SP, FR and GR0-GR7 in plain hex and decimal,
+// without labels or colors.
+func compatPrint(c *Comet2, args []string) error {
+	state := c.State
+	fr := state[FR]
+	inst, opr, _ := parse(c.Memory, state)
+
+	flags := []byte("---")
+	for i, name := range "OSZ" {
+		if fr&(4>>i) != 0 {
+			flags[i] = byte(name)
+		}
+	}
+
+	cometPrint("")
+	cometPrint(fmt.Sprintf("PR  #%s [ %s\t\t%s ]", hex(state[PC], 4), inst, opr))
+	cometPrint(fmt.Sprintf("SP  %s  FR    %s(%s)[ %s ]", formatWord(state[SP]), formatFRBits(fr), spacePadding(fr, 6), flags))
+	for row := GR0; row <= GR4; row += 4 {
+		cometPrint(fmt.Sprintf("GR%d %s  GR%d %s  GR%d %s  GR%d %s",
+			row-GR0, formatWord(state[row]), row-GR0+1, formatWord(state[row+1]),
+			row-GR0+2, formatWord(state[row+2]), row-GR0+3, formatWord(state[row+3])))
+	}
+	return nil
+}
+
+// compatDump prints 128 words from ADDRESS (default PR), eight words and
+// their characters per line.
+func compatDump(c *Comet2, args []string) error {
+	base := c.State[PC]
+	if len(args) > 0 {
+		n, err := compatArgument(args)
+		if err != nil {
+			return err
+		}
+		base = n
+	}
+
+	for row := 0; row < 16; row++ {
+		addr := base + row*8
+		line := hex(addr, 4) + ":"
+		chars := ""
+		for col := 0; col < 8; col++ {
+			val := memGet(c.Memory, addr+col)
+			line += " " + hex(val, 4)
+			if ch := val & 0xff; ch >= 0x20 && ch <= 0x7f {
+				chars += string(rune(ch))
+			} else {
+				chars += "."
+			}
+		}
+		cometPrint(line + " " + chars)
+	}
+	return nil
+}
+
+func compatStack(c *Comet2, args []string) error {
+	return compatDump(c, []string{strconv.Itoa(c.State[SP])})
+}
+
+// compatDisasm disassembles 16 instructions from ADDRESS (default PR),
+// decoding data words as instructions too.
+func compatDisasm(c *Comet2, args []string) error {
+	state := make([]int, len(c.State))
+	state[PC] = c.State[PC]
+	if len(args) > 0 {
+		n, err := compatArgument(args)
+		if err != nil {
+			return err
+		}
+		state[PC] = n
+	}
+
+	for i := 0; i < 16; i++ {
+		inst, opr, size := parse(c.Memory, state)
+		cometPrint(fmt.Sprintf("#%s\t%s\t%s", hex(state[PC], 4), inst, opr))
+		state[PC] += size
+	}
+	return nil
+}
+
+func compatFile(c *Comet2, args []string) error {
+	if len(args) != 1 {
+		return fmt.Errorf("Invalid argument.")
+	}
+	obj, err := assemble(args[0], newAssemblerState())
+	if err != nil {
+		return err
+	}
+	c.Reset(obj)
+	cometPrint("Successfully loaded.")
+	return nil
+}
+
+// compatJump sets PR to ADDRESS without checking it.
+func compatJump(c *Comet2, args []string) error {
+	addr, err := compatArgument(args)
+	if err != nil {
+		return err
+	}
+	c.State[PC] = addr & 0xffff
+	return nil
+}
+
+func compatHelp(c *Comet2, args []string) error {
+	cometPrint("List of commands:")
+	cometPrint("r,  run             \t\tStart execution of program.")
+	cometPrint("g,  go              \t\tSame as run.")
+	cometPrint("s,  step  [N]       \t\tStep execution. Argument N means do this N times.")
+	cometPrint("t,  trace [N]       \t\tStep execution N times, printing registers each time.")
+	cometPrint("b,  break ADDRESS   \t\tSet a breakpoint at specified ADDRESS.")
+	cometPrint("d,  del   [N]       \t\tDelete breakpoint N, or all breakpoints.")
+	cometPrint("i,  info            \t\tPrint information of breakpoints.")
+	cometPrint("p,  print           \t\tPrint status of PC/FR/SP/GR0..GR7 registers.")
+	cometPrint("du, dump [ADDRESS]  \t\tDump 128 words of memory image from specified ADDRESS.")
+	cometPrint("st, stack           \t\tDump 128 words of stack image.")
+	cometPrint("f,  file FILE       \t\tUse FILE as program to be debugged.")
+	cometPrint("j,  jump ADDRESS    \t\tContinue program at specified ADDRESS.")
+	cometPrint("m,  memory ADDRESS VALUE\tChange the memory at ADDRESS to VALUE.")
+	cometPrint("di, disasm [ADDRESS]\t\tDisassemble 32 words from specified ADDRESS.")
+	cometPrint("h,  help            \t\tPrint list of commands.")
+	cometPrint("q,  quit            \t\tExit comet2.")
+	return nil
+}
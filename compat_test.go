@@ -0,0 +1,70 @@
+package main
+
+import (
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// captureStdout returns what f prints on the standard output.
+func captureStdout(t *testing.T, f func()) string {
+	t.Helper()
+
+	r, w, err := os.Pipe()
+	if err != nil {
+		t.Fatalf("Failed to create pipe: %v", err)
+	}
+	out := make(chan string, 1)
+	go func() {
+		b, _ := io.ReadAll(r)
+		out <- string(b)
+	}()
+
+	stdout := os.Stdout
+	os.Stdout = w
+	func() {
+		defer func() {
+			os.Stdout = stdout
+			w.Close()
+		}()
+		f()
+	}()
+	return <-out
+}
+
+func TestCompatMonitor(t *testing.T) {
+	saveMonitorState(t)
+	compat, dump, color := *optCompat, verbosity.dump, verbosity.color
+	t.Cleanup(func() { *optCompat, verbosity.dump, verbosity.color = compat, dump, color })
+	*optCompat, verbosity.dump, verbosity.color = true, true, false
+
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,3
+LOOP	SUBA	GR1,=1
+	JNZ	LOOP
+	RET
+	END
+`)
+	// The transcript of a session as a handout shows it
+	var transcript strings.Builder
+	for _, cmd := range []string{"b 4", "i", "r", "t 2", "d 1", "i", "du 0", "di 0", "j 0", "s 3"} {
+		transcript.WriteString("comet2> " + cmd + "\n")
+		transcript.WriteString(captureStdout(t, func() { runMonitor(t, c, cmd) }))
+	}
+	diff, err := compareGolden(filepath.Join("test", "compat", "monitor.txt"), transcript.String(), false)
+	if err != nil {
+		t.Fatalf("Failed to compare: %v", err)
+	}
+	if diff != "" {
+		t.Errorf("Transcript differs from the golden file:\n%s", diff)
+	}
+
+	if err := executeCommand("b", nil, c); err == nil || err.Error() != "Invalid argument." {
+		t.Errorf("Expected break without an address to be invalid, got %v", err)
+	}
+	if err := executeCommand("ro", nil, c); err == nil {
+		t.Errorf("Expected ro to be undefined with -compat")
+	}
+}
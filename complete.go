@@ -0,0 +1,110 @@
+package main
+
+import (
+	"slices"
+	"sort"
+	"strings"
+)
+
+// argumentCompletions give the words tab completion offers for the
+// arguments of monitor commands that take something other than an address
+// or a label. The arguments of the other commands complete to labels.
+var argumentCompletions = map[string]func(c *Comet2, n int) []string{
+	"w":          watchCompletions,
+	"watch":      watchCompletions,
+	"bf":         flagCompletions,
+	"break-flag": flagCompletions,
+	"i":          infoCompletions,
+	"info":       infoCompletions,
+	"set":        setCompletions,
+	"unalias":    aliasCompletions,
+}
+
+// monitorCompletions returns the words tab completion offers after the
+// words before on a monitor line, sorted and without duplicates: command
+// names and aliases for the first word, and for its arguments what the
+// command takes, by default the labels of the program in c.
+func monitorCompletions(c *Comet2, before []string) []string {
+	var words []string
+	if len(before) == 0 {
+		words = append(words, "quit")
+		for name := range activeCommands() {
+			words = append(words, name)
+		}
+		for name := range monitorAliases {
+			words = append(words, name)
+		}
+	} else {
+		cmd := before[0]
+		if alias, ok := monitorAliases[cmd]; ok {
+			if fields := strings.Fields(alias); len(fields) > 0 {
+				cmd = fields[0]
+				before = append(fields, before[1:]...)
+			}
+		}
+		if f, ok := argumentCompletions[cmd]; ok {
+			words = f(c, len(before)-1)
+		} else {
+			words = labelCompletions(c.Symbols)
+		}
+	}
+	sort.Strings(words)
+	return slices.Compact(words)
+}
+
+// labelCompletions returns the labels of symbols without their unit.
+func labelCompletions(symbols map[string]int) []string {
+	var words []string
+	for name := range symbols {
+		if idx := strings.LastIndex(name, ":"); idx >= 0 {
+			name = name[idx+1:]
+		}
+		words = append(words, name)
+	}
+	return words
+}
+
+// watchCompletions offers the registers watch takes.
+func watchCompletions(c *Comet2, n int) []string {
+	var words []string
+	for i := GR0; i <= SP; i++ {
+		words = append(words, registerName(i))
+	}
+	return words
+}
+
+// flagCompletions offers the flags break-flag takes.
+func flagCompletions(c *Comet2, n int) []string {
+	var words []string
+	for _, fb := range flagBits {
+		words = append(words, fb.name)
+	}
+	return words
+}
+
+func infoCompletions(c *Comet2, n int) []string {
+	if n > 0 {
+		return nil
+	}
+	return []string{"break"}
+}
+
+// setCompletions offers the registers set takes as its target.
+func setCompletions(c *Comet2, n int) []string {
+	if n > 0 {
+		return nil
+	}
+	var words []string
+	for name := range editRegisters {
+		words = append(words, name)
+	}
+	return words
+}
+
+func aliasCompletions(c *Comet2, n int) []string {
+	var words []string
+	for name := range monitorAliases {
+		words = append(words, name)
+	}
+	return words
+}
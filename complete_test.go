@@ -0,0 +1,33 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestMonitorCompletions(t *testing.T) {
+	c := assembleSource(t, "MAIN\tSTART\nLOOP\tNOP\n\tRET\n\tEND\nSUB\tSTART\nLEN\tDC\t1\n\tRET\n\tEND\n")
+	monitorAliases["sw"] = "watch GR1"
+	defer delete(monitorAliases, "sw")
+
+	for _, tt := range []struct {
+		before []string
+		prefix string
+		want   []string
+	}{
+		{nil, "sw", []string{"sw"}},
+		{nil, "ta", nil},
+		{[]string{"break"}, "L", []string{"LEN", "LOOP"}},
+		{[]string{"dump"}, "", []string{"LEN", "LOOP", "MAIN", "SUB"}},
+		{[]string{"watch"}, "S", []string{"SP"}},
+		{[]string{"sw"}, "GR", []string{"GR0", "GR1", "GR2", "GR3", "GR4", "GR5", "GR6", "GR7"}},
+		{[]string{"set"}, "P", []string{"PC", "PR"}},
+		{[]string{"set", "PR"}, "", nil},
+		{[]string{"bf"}, "", []string{"OF", "SF", "ZF"}},
+		{[]string{"info"}, "b", []string{"break"}},
+	} {
+		if got := completions(monitorCompletions(c, tt.before), tt.prefix); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("%v %q: expected %v, got %v", tt.before, tt.prefix, tt.want, got)
+		}
+	}
+}
@@ -0,0 +1,127 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"net"
+	"strconv"
+	"strings"
+)
+
+// Telnet command bytes (RFC 854) that are not part of the IN text
+const (
+	TELNET_SE   = 240
+	TELNET_SB   = 250
+	TELNET_WILL = 251
+	TELNET_DONT = 254
+	TELNET_IAC  = 255
+)
+
+// listenConsole opens the console port on host, the loopback interface
+// when host is empty, so that the program's IN and OUT are not exposed to
+// the network unless asked for.
+func listenConsole(host string, port int) (net.Listener, error) {
+	if host == "" {
+		host = "127.0.0.1"
+	}
+	ln, err := net.Listen("tcp", net.JoinHostPort(host, strconv.Itoa(port)))
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot open console port %d: %v", port, err)
+	}
+	return ln, nil
+}
+
+// openConsole waits for a single TCP client on host and port and returns
+// the connection. IN and OUT are bridged to it so stdio stays free.
+func openConsole(host string, port int) (net.Conn, error) {
+	ln, err := listenConsole(host, port)
+	if err != nil {
+		return nil, err
+	}
+	defer ln.Close()
+
+	cometPrint(fmt.Sprintf("[Console] Waiting for connection on %s", ln.Addr()))
+	conn, err := ln.Accept()
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Console connection failed: %v", err)
+	}
+	cometPrint(fmt.Sprintf("[Console] Connected from %s", conn.RemoteAddr()))
+
+	return conn, nil
+}
+
+// consoleWriter returns an OUT handler that writes lines to w telnet-style.
+func consoleWriter(w io.Writer) func(string) {
+	return func(text string) {
+		fmt.Fprint(w, strings.TrimSuffix(text, "\n")+"\r\n")
+	}
+}
+
+// newConsoleScanner reads IN lines from the console connection.
+func newConsoleScanner(r io.Reader) *bufio.Scanner {
+	return bufio.NewScanner(&telnetReader{r: r})
+}
+
+// States of telnetReader
+const (
+	TELNET_STATE_DATA = iota
+	TELNET_STATE_COMMAND
+	TELNET_STATE_OPTION
+	TELNET_STATE_SUB
+	TELNET_STATE_SUB_COMMAND
+)
+
+// telnetReader drops the option negotiation a telnet client sends, and
+// the NUL after a bare CR, so that only the typed text reaches IN. IAC IAC
+// stands for a #FF byte.
+type telnetReader struct {
+	r     io.Reader
+	state int
+}
+
+func (t *telnetReader) Read(p []byte) (int, error) {
+	for {
+		n, err := t.r.Read(p)
+		out := 0
+		for _, b := range p[:n] {
+			switch t.state {
+			case TELNET_STATE_DATA:
+				if b == TELNET_IAC {
+					t.state = TELNET_STATE_COMMAND
+				} else if b != 0 {
+					p[out] = b
+					out++
+				}
+			case TELNET_STATE_COMMAND:
+				switch {
+				case b == TELNET_IAC:
+					p[out] = b
+					out++
+					t.state = TELNET_STATE_DATA
+				case b >= TELNET_WILL && b <= TELNET_DONT:
+					t.state = TELNET_STATE_OPTION
+				case b == TELNET_SB:
+					t.state = TELNET_STATE_SUB
+				default:
+					t.state = TELNET_STATE_DATA
+				}
+			case TELNET_STATE_OPTION:
+				t.state = TELNET_STATE_DATA
+			case TELNET_STATE_SUB:
+				if b == TELNET_IAC {
+					t.state = TELNET_STATE_SUB_COMMAND
+				}
+			case TELNET_STATE_SUB_COMMAND:
+				if b == TELNET_SE {
+					t.state = TELNET_STATE_DATA
+				} else {
+					t.state = TELNET_STATE_SUB
+				}
+			}
+		}
+		if out > 0 || err != nil {
+			return out, err
+		}
+	}
+}
@@ -0,0 +1,65 @@
+package main
+
+import (
+	"bufio"
+	"net"
+	"strings"
+	"testing"
+)
+
+func TestConsoleListensOnLoopback(t *testing.T) {
+	ln, err := listenConsole("", 0)
+	if err != nil {
+		t.Fatal(err)
+	}
+	defer ln.Close()
+	if addr := ln.Addr().(*net.TCPAddr); !addr.IP.IsLoopback() {
+		t.Errorf("Expected a loopback address, got %s", addr)
+	}
+}
+
+func TestConsoleSession(t *testing.T) {
+	client, server := net.Pipe()
+	defer client.Close()
+	defer server.Close()
+
+	c := assembleSource(t, echoProgram)
+	c.Output = consoleWriter(server)
+	in := newConsoleScanner(server)
+
+	// A telnet client opens with option negotiation before the line
+	go client.Write([]byte("\xff\xfb\x1f\xff\xfa\x18\x00xterm\xff\xf0hello\r\n"))
+	if err := c.Run(); err != nil || c.InputMode != INPUT_MODE_IN {
+		t.Fatalf("Expected the program to wait for IN, got %v", err)
+	}
+	if !in.Scan() {
+		t.Fatalf("Expected an IN line: %v", in.Err())
+	}
+	if err := c.SupplyInput(in.Text()); err != nil {
+		t.Fatal(err)
+	}
+
+	out := make(chan string, 1)
+	go func() {
+		line, _ := bufio.NewReader(client).ReadString('\n')
+		out <- line
+	}()
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if got := <-out; got != "hello\r\n" {
+		t.Errorf("Expected OUT to echo the typed text, got %q", got)
+	}
+}
+
+func TestTelnetReader(t *testing.T) {
+	// IAC IAC is a #FF byte, and the NUL of a CR NUL is dropped
+	in := newConsoleScanner(strings.NewReader("a\xff\xffb\xff\xfd\x03c\r\x00\nd\n"))
+	var lines []string
+	for in.Scan() {
+		lines = append(lines, in.Text())
+	}
+	if len(lines) != 2 || lines[0] != "a\xffbc" || lines[1] != "d" {
+		t.Errorf("Unexpected lines %q", lines)
+	}
+}
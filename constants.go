@@ -0,0 +1,180 @@
+package main
+
+import (
+	"fmt"
+	"slices"
+	"strings"
+)
+
+// Character codes that a program comparing or converting characters
+// typically writes as numbers
+var charCodes = []int{' ', '0', '9', 'A', 'Z', 'a', 'z'}
+
+// constantUse is an instruction using a constant, by its line.
+type constantUse struct {
+	pos  int
+	inst string
+}
+
+// programConstant is a numeric word of the program written by a DC or a
+// literal: a number or a single character.
+type programConstant struct {
+	pos   int
+	label string
+	// text is the operand as written: "255", "#00FF" or "'A'".
+	text    string
+	literal bool
+	value   int
+	uses    []constantUse
+}
+
+// isChar reports whether the constant is written as a character.
+func (c programConstant) isChar() bool {
+	return strings.HasPrefix(c.text, "'")
+}
+
+// String writes the constant as the program does: "=#00FF", "DC 255" or
+// "MASK DC 255".
+func (c programConstant) String() string {
+	if c.literal {
+		return "=" + c.text
+	}
+	if c.label != "" {
+		return c.label[strings.Index(c.label, ":")+1:] + " DC " + c.text
+	}
+	return "DC " + c.text
+}
+
+// constantValue returns the word of a number or of a string of one
+// character.
+func constantValue(text string) (int, bool) {
+	if strings.HasPrefix(text, "'") && strings.HasSuffix(text, "'") && len(text) >= 3 {
+		chars := strings.ReplaceAll(text[1:len(text)-1], "''", "'")
+		if len(chars) != 1 {
+			return 0, false
+		}
+		return int(chars[0]), true
+	}
+	return expandNumber(text)
+}
+
+// collectConstants finds the numeric DC words and literals of the program
+// and the instructions using them: the line of a literal, and the lines
+// naming the label of a DC.
+func collectConstants(asmState *AssemblerState) []*programConstant {
+	var constants []*programConstant
+	labeled := make(map[string]*programConstant)
+	scope := ""
+	type reference struct {
+		key string
+		use constantUse
+	}
+	var refs []reference
+
+	for i, text := range asmState.buf {
+		parts := strings.SplitN(text, "\t", 3)
+		if len(parts) < 3 {
+			continue
+		}
+		label, inst := parts[0], parts[1]
+		pos := asmState.bufPos[i]
+		if inst == "START" {
+			scope = label[strings.Index(label, ":")+1:]
+		}
+		for k, opr := range parseOperands(parts[2]) {
+			if inst == "DC" {
+				if val, ok := constantValue(opr); ok {
+					c := &programConstant{pos: pos, text: opr, value: val}
+					if k == 0 && label != "" {
+						c.label = label
+						labeled[label] = c
+					}
+					constants = append(constants, c)
+				}
+				continue
+			}
+			if lit, ok := strings.CutPrefix(opr, "="); ok {
+				if val, ok := constantValue(lit); ok {
+					constants = append(constants, &programConstant{pos: pos, text: lit, literal: true, value: val,
+						uses: []constantUse{{pos, inst}}})
+				}
+			} else if isLabel(opr) && !isRegister(opr) {
+				refs = append(refs, reference{scope + ":" + opr, constantUse{pos, inst}})
+			}
+		}
+	}
+	for _, ref := range refs {
+		if c, ok := labeled[ref.key]; ok {
+			c.uses = append(c.uses, ref.use)
+		}
+	}
+	return constants
+}
+
+// constantNotes returns what looks wrong about a constant: a character
+// code written as a number but used as a character, a character used as a
+// number, a hexadecimal number that reads like a decimal one, and a
+// decimal bit mask.
+func constantNotes(asmState *AssemblerState, c *programConstant) []string {
+	var notes []string
+	hexText := strings.HasPrefix(c.text, "#")
+	for _, use := range c.uses {
+		line := asmState.origin(use.pos).Line
+		switch use.inst {
+		case "ADDA", "ADDL", "SUBA", "SUBL", "CPA", "CPL":
+			if !hexText && !c.isChar() && slices.Contains(charCodes, c.value) {
+				notes = append(notes, fmt.Sprintf("%d is the character code of %s (%s on line %d); write '%c' if it stands for the character",
+					c.value, charName(c.value), use.inst, line, rune(c.value)))
+			}
+		case "MULA", "MULL", "DIVA", "DIVL":
+			if c.isChar() {
+				notes = append(notes, fmt.Sprintf("Character %s is used as the number %d by %s on line %d", c.text, c.value, use.inst, line))
+			}
+		case "AND", "OR", "XOR":
+			if !hexText && !c.isChar() && c.value >= 16 {
+				notes = append(notes, fmt.Sprintf("%s is used as a bit mask by %s on line %d; #%s shows its bits", c.text, use.inst, line, hex(c.value, 4)))
+			}
+		}
+	}
+	if hexText && c.value >= 10 && strings.Trim(c.text[1:], "0123456789") == "" {
+		notes = append(notes, fmt.Sprintf("%s is %d in decimal, not %s", c.text, c.value, strings.TrimLeft(c.text[1:], "0")))
+	}
+	return notes
+}
+
+// formatConstants renders the numeric constants of the program with their
+// value in decimal, hexadecimal and as a character, the lines using them,
+// and notes on suspicious ones.
+func formatConstants(asmState *AssemblerState) string {
+	var b strings.Builder
+	var notes []string
+	constants := collectConstants(asmState)
+	b.WriteString("Line  Constant              Decimal         Hex    Char      Used on\n")
+	for _, c := range constants {
+		line := asmState.origin(c.pos).Line
+		decimal := fmt.Sprintf("%d", c.value)
+		if c.value&0x8000 != 0 {
+			decimal = fmt.Sprintf("%d (%d)", int(int16(c.value)), c.value)
+		}
+		char := "-"
+		if name := charName(c.value); name != "" && c.value <= 0xff {
+			char = name
+		}
+		var uses []string
+		for _, use := range c.uses {
+			uses = append(uses, fmt.Sprintf("%d (%s)", asmState.origin(use.pos).Line, use.inst))
+		}
+		if len(uses) == 0 {
+			uses = append(uses, "-")
+		}
+		fmt.Fprintf(&b, "%4d  %-20s  %-14s  #%s  %-8s  %s\n", line, c, decimal, hex(c.value, 4), char, strings.Join(uses, ", "))
+		for _, note := range constantNotes(asmState, c) {
+			notes = append(notes, fmt.Sprintf("note: line %d: %s", line, note))
+		}
+	}
+	fmt.Fprintf(&b, "%d constants, %d notes\n", len(constants), len(notes))
+	for _, note := range notes {
+		b.WriteString(note + "\n")
+	}
+	return b.String()
+}
@@ -0,0 +1,39 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestConstantReport(t *testing.T) {
+	src := `MAIN	START
+	LD	GR1,CH
+	SUBA	GR1,=48
+	AND	GR1,MASK
+	MULA	GR1,='A'
+	RET
+CH	DC	'7',#0100
+MASK	DC	255
+	END
+`
+	asmState := newAssemblerState()
+	if _, err := assembleText("prog.cas", src, asmState); err != nil {
+		t.Fatal(err)
+	}
+	report := formatConstants(asmState)
+	for _, want := range []string{
+		"   3  =48                   48              #0030  '0'       3 (SUBA)\n",
+		"   7  CH DC '7'             55              #0037  '7'       2 (LD)\n",
+		"   7  DC #0100              256             #0100  -         -\n",
+		"   8  MASK DC 255           255             #00ff  -         4 (AND)\n",
+		"5 constants, 4 notes\n",
+		"note: line 3: 48 is the character code of '0' (SUBA on line 3); write '0' if it stands for the character\n",
+		"note: line 5: Character 'A' is used as the number 65 by MULA on line 5\n",
+		"note: line 7: #0100 is 256 in decimal, not 100\n",
+		"note: line 8: 255 is used as a bit mask by AND on line 4; #00ff shows its bits\n",
+	} {
+		if !strings.Contains(report, want) {
+			t.Errorf("Expected %q in the constant report:\n%s", want, report)
+		}
+	}
+}
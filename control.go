@@ -0,0 +1,294 @@
+package main
+
+import (
+	"bufio"
+	"errors"
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+)
+
+// Maximum number of words returned by one READMEM
+const CONTROL_READMEM_MAX = 1024
+
+// controller runs the line-based control protocol of -control-stdio.
+// Each request is one line; each reply is one line starting with "OK" or
+// "ERR", preceded by an "OUT" or "WARN" line for every OUT or warning the
+// program produced while handling the request.
+type controller struct {
+	w       io.Writer
+	machine *Comet2
+	input   *InputLines
+	halted  string
+	traps   string
+	engine  string
+	// Breakpoints are kept across LOAD
+	breakpoints *BreakpointTable
+}
+
+func newController(w io.Writer, traps, engine string) *controller {
+	return &controller{w: w, traps: traps, engine: engine, breakpoints: NewBreakpointTable()}
+}
+
+// runControl serves requests from r until QUIT or the end of input. When
+// path is given, it is loaded first as if by LOAD.
+func runControl(r io.Reader, w io.Writer, path string, traps, engine string) error {
+	ctl := newController(w, traps, engine)
+	if path != "" {
+		ctl.reply(ctl.load([]string{path}))
+	}
+
+	scanner := bufio.NewScanner(r)
+	for scanner.Scan() {
+		line := strings.TrimSpace(scanner.Text())
+		if line == "" {
+			continue
+		}
+		cmd, rest, _ := strings.Cut(line, " ")
+		cmd = strings.ToUpper(cmd)
+		if cmd == "QUIT" {
+			ctl.reply("OK")
+			return nil
+		}
+		ctl.reply(ctl.execute(cmd, strings.TrimSpace(rest)))
+	}
+	return scanner.Err()
+}
+
+func (ctl *controller) reply(line string) {
+	fmt.Fprintln(ctl.w, line)
+}
+
+// execute handles one request and returns its reply.
+func (ctl *controller) execute(cmd string, rest string) string {
+	args := strings.Fields(rest)
+	if cmd == "LOAD" {
+		return ctl.load(args)
+	}
+	if ctl.machine == nil {
+		return "ERR No program loaded"
+	}
+
+	switch cmd {
+	case "STEP":
+		count := 1
+		if len(args) > 0 {
+			n, err := strconv.Atoi(args[0])
+			if err != nil || n < 1 {
+				return fmt.Sprintf("ERR Invalid count \"%s\"", args[0])
+			}
+			count = n
+		}
+		return ctl.advance(count, false)
+	case "CONTINUE":
+		count := -1
+		if len(args) > 0 {
+			n, err := strconv.Atoi(args[0])
+			if err != nil || n < 1 {
+				return fmt.Sprintf("ERR Invalid count \"%s\"", args[0])
+			}
+			count = n
+		}
+		return ctl.advance(count, true)
+	case "BREAK", "TBREAK":
+		return ctl.addBreak(args, cmd == "TBREAK")
+	case "DELETE", "ENABLE", "DISABLE", "IGNORE":
+		return ctl.changeBreak(cmd, args)
+	case "BREAKPOINTS":
+		return ctl.listBreaks()
+	case "READREG":
+		return ctl.readReg()
+	case "READMEM":
+		return ctl.readMem(args)
+	case "INPUT":
+		text := rest
+		if strings.HasPrefix(rest, "\"") {
+			unquoted, err := strconv.Unquote(rest)
+			if err != nil {
+				return "ERR Invalid string " + rest
+			}
+			text = unquoted
+		}
+		ctl.input.Push(text)
+		return "OK"
+	}
+	return fmt.Sprintf("ERR Unknown command \"%s\"", cmd)
+}
+
+// load assembles (or reads a .c2o object) and replaces the machine.
+func (ctl *controller) load(args []string) string {
+	if len(args) != 1 {
+		return "ERR Usage: LOAD FILE"
+	}
+
+	var obj *Object
+	var err error
+	if strings.HasSuffix(args[0], OBJECT_EXT) {
+		obj, err = loadObjectFile(args[0])
+	} else {
+		obj, err = assemble(args[0], newAssemblerState())
+	}
+	if err != nil {
+		var aerr *AssembleError
+		if errors.As(err, &aerr) {
+			return fmt.Sprintf("ERR Line %d: %s", aerr.Line, aerr.Msg)
+		}
+		return "ERR " + err.Error()
+	}
+
+	ctl.input = NewInputLines(nil)
+	machine := NewComet2Builder().
+		WithObject(obj).
+		WithInput(ctl.input).
+		WithOutput(func(text string) { ctl.reply("OUT " + strconv.Quote(text)) }).
+		WithWarn(func(msg string) { ctl.reply("WARN " + strconv.Quote(msg)) }).
+		Build()
+	if err := machine.SetTraps(ctl.traps); err != nil {
+		return "ERR " + err.Error()
+	}
+	if err := machine.SetEngine(ctl.engine); err != nil {
+		return "ERR " + err.Error()
+	}
+	machine.Breakpoints = ctl.breakpoints
+	_, lost := ctl.breakpoints.Relocate(obj)
+	for _, bp := range lost {
+		ctl.reply("WARN " + strconv.Quote(fmt.Sprintf("Breakpoint %d at %s no longer resolves and is disabled", bp.ID, bp.Label)))
+	}
+	ctl.machine = machine
+	ctl.halted = ""
+	return fmt.Sprintf("OK start=#%s size=%d", hex(obj.Start, 4), len(obj.Bin))
+}
+
+// advance executes up to count instructions, or until the program stops
+// when count is negative. IN takes lines queued by INPUT; without one the
+// program waits. With breaks, it also stops before an instruction at a
+// breakpoint, other than the first.
+func (ctl *controller) advance(count int, breaks bool) string {
+	c := ctl.machine
+	if ctl.halted != "" {
+		return "OK HALTED " + ctl.halted
+	}
+
+	for n := 0; count < 0 || n < count; n++ {
+		if c.InputMode == INPUT_MODE_IN {
+			line, ok := ctl.input.ReadLine()
+			if !ok {
+				return "OK WAITING"
+			}
+			c.execIn(line)
+			c.InputMode = INPUT_MODE_CMD
+		}
+		if breaks && n > 0 {
+			if bp := c.Breakpoints.hit(c.State[PC]); bp != nil {
+				return fmt.Sprintf("OK BREAK %d pr=#%s steps=%d", bp.ID, hex(c.State[PC], 4), c.Steps)
+			}
+		}
+		if _, err := c.stepExec(); err != nil {
+			var rerr *RuntimeError
+			if errors.As(err, &rerr) && !rerr.Halts() {
+				return "ERR " + err.Error()
+			}
+			ctl.halted = err.Error()
+			return "OK HALTED " + ctl.halted
+		}
+	}
+	if c.InputMode == INPUT_MODE_IN {
+		return "OK WAITING"
+	}
+	return fmt.Sprintf("OK RUNNING pr=#%s steps=%d", hex(c.State[PC], 4), c.Steps)
+}
+
+// addBreak sets a breakpoint at an address, a label or LABEL+N.
+func (ctl *controller) addBreak(args []string, temporary bool) string {
+	if len(args) != 1 {
+		return "ERR Usage: BREAK ADDRESS"
+	}
+	addr, ok := parseAddress(ctl.machine, args[0])
+	if !ok {
+		return fmt.Sprintf("ERR Invalid address \"%s\"", args[0])
+	}
+	bp := ctl.breakpoints.Add(addr, temporary)
+	bp.Label = breakpointLabel(args[0])
+	return fmt.Sprintf("OK %d addr=#%s", bp.ID, hex(addr, 4))
+}
+
+// changeBreak handles DELETE ID, ENABLE ID, DISABLE ID and IGNORE ID COUNT.
+func (ctl *controller) changeBreak(cmd string, args []string) string {
+	if len(args) != 1 && (cmd != "IGNORE" || len(args) != 2) {
+		if cmd == "IGNORE" {
+			return "ERR Usage: IGNORE ID COUNT"
+		}
+		return "ERR Usage: " + cmd + " ID"
+	}
+	id, err := strconv.Atoi(args[0])
+	bp := ctl.breakpoints.Get(id)
+	if err != nil || bp == nil {
+		return fmt.Sprintf("ERR No breakpoint %s", args[0])
+	}
+	switch cmd {
+	case "DELETE":
+		ctl.breakpoints.Delete(id)
+	case "ENABLE":
+		bp.Enabled = true
+	case "DISABLE":
+		bp.Enabled = false
+	case "IGNORE":
+		count, err := strconv.Atoi(args[1])
+		if err != nil || count < 0 {
+			return fmt.Sprintf("ERR Invalid count \"%s\"", args[1])
+		}
+		bp.Ignore = count
+	}
+	return "OK"
+}
+
+// listBreaks replies with one "ID:#ADDR:FLAGS:HITS:IGNORE" field per
+// breakpoint, FLAGS being e (enabled) or d (disabled), plus t when
+// temporary.
+func (ctl *controller) listBreaks() string {
+	var b strings.Builder
+	b.WriteString("OK")
+	for _, bp := range ctl.breakpoints.All() {
+		flags := "e"
+		if !bp.Enabled {
+			flags = "d"
+		}
+		if bp.Temporary {
+			flags += "t"
+		}
+		fmt.Fprintf(&b, " %d:#%s:%s:%d:%d", bp.ID, hex(bp.Addr, 4), flags, bp.Hits, bp.Ignore)
+	}
+	return b.String()
+}
+
+func (ctl *controller) readReg() string {
+	s := ctl.machine.State
+	var b strings.Builder
+	fmt.Fprintf(&b, "OK PR=#%s SP=#%s FR=%s", hex(s[PC], 4), hex(s[SP], 4), formatFRBits(s[FR]))
+	for i := 0; i < 8; i++ {
+		fmt.Fprintf(&b, " GR%d=#%s", i, hex(s[GR0+i], 4))
+	}
+	return b.String()
+}
+
+func (ctl *controller) readMem(args []string) string {
+	if len(args) != 2 {
+		return "ERR Usage: READMEM ADDRESS LENGTH"
+	}
+	addr, ok := expandNumber(args[0])
+	if !ok {
+		return fmt.Sprintf("ERR Invalid address \"%s\"", args[0])
+	}
+	length, ok := expandNumber(args[1])
+	if !ok || length > CONTROL_READMEM_MAX {
+		return fmt.Sprintf("ERR Invalid length \"%s\"", args[1])
+	}
+
+	var b strings.Builder
+	b.WriteString("OK")
+	for i := 0; i < length; i++ {
+		fmt.Fprintf(&b, " #%s", hex(memGet(ctl.machine.Memory, (addr+i)&0xffff), 4))
+	}
+	return b.String()
+}
@@ -0,0 +1,59 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestControlProtocol(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "echo.cas")
+	if err := os.WriteFile(path, []byte(echoProgram), 0644); err != nil {
+		t.Fatalf("Failed to write source: %v", err)
+	}
+
+	requests := strings.Join([]string{
+		"READREG",
+		"LOAD " + path,
+		"step 2",
+		"READREG",
+		"CONTINUE",
+		`INPUT "hi there"`,
+		"CONTINUE",
+		"STEP",
+		"READMEM 0 2",
+		"FOO",
+		"QUIT",
+		"STEP",
+	}, "\n")
+	var out bytes.Buffer
+	if err := runControl(strings.NewReader(requests), &out, "", "", "interp"); err != nil {
+		t.Fatalf("Control failed: %v", err)
+	}
+
+	want := []string{
+		"ERR No program loaded",
+		"OK start=#0000 size=",
+		"OK RUNNING pr=#0004 steps=2",
+		"OK PR=#0004 SP=#fefe FR=000 GR0=#0000 GR1=#0000",
+		"OK WAITING",
+		"OK",
+		`OUT "hi there"`,
+		"OK HALTED Program finished",
+		"OK HALTED Program finished",
+		"OK #",
+		`ERR Unknown command "FOO"`,
+		"OK",
+	}
+	got := strings.Split(strings.TrimSuffix(out.String(), "\n"), "\n")
+	if len(got) != len(want) {
+		t.Fatalf("Expected %d replies, got %q", len(want), got)
+	}
+	for i := range want {
+		if !strings.HasPrefix(got[i], want[i]) {
+			t.Errorf("Reply %d: expected %q, got %q", i, want[i], got[i])
+		}
+	}
+}
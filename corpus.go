@@ -0,0 +1,290 @@
+package main
+
+import (
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"runtime"
+	"sort"
+	"strings"
+	"sync"
+)
+
+// corpusResult is what one program of a corpus did: the transcript of
+// runTranscript (IN and OUT lines and the halt message) and the number of
+// executed instructions, or the error that stopped its assembly.
+type corpusResult struct {
+	Transcript string `json:"transcript,omitempty"`
+	Steps      int    `json:"steps"`
+	Error      string `json:"error,omitempty"`
+}
+
+// corpusBaseline is the file of -baseline: the results by path relative
+// to the corpus directory.
+type corpusBaseline struct {
+	Version  string                   `json:"version"`
+	Programs map[string]*corpusResult `json:"programs"`
+}
+
+// stepCounter keeps the number of executed instructions of a run.
+type stepCounter struct {
+	BaseObserver
+	steps int
+}
+
+func (s *stepCounter) AfterStep(c *Comet2, pc int) {
+	s.steps = c.Steps
+}
+
+// corpusSources returns the .cas files under dir, relative to it, with
+// slashes and sorted.
+func corpusSources(dir string) ([]string, error) {
+	var sources []string
+	err := filepath.WalkDir(dir, func(path string, d fs.DirEntry, err error) error {
+		if err != nil {
+			return err
+		}
+		if !d.IsDir() && strings.EqualFold(filepath.Ext(path), ".cas") {
+			rel, err := filepath.Rel(dir, path)
+			if err != nil {
+				return err
+			}
+			sources = append(sources, filepath.ToSlash(rel))
+		}
+		return nil
+	})
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read corpus: %v", err)
+	}
+	sort.Strings(sources)
+	return sources, nil
+}
+
+// loadCorpusInputs reads a test spec giving the IN lines of the programs,
+// by path relative to the corpus directory or by file name alone.
+func loadCorpusInputs(path string) (map[string]TestCase, error) {
+	if path == "" {
+		return nil, nil
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read corpus inputs: %v", err)
+	}
+	var inputs map[string]TestCase
+	if err := json.Unmarshal(data, &inputs); err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] %s: invalid corpus inputs: %v", path, err)
+	}
+	return inputs, nil
+}
+
+// runCorpusProgram assembles and runs one program of the corpus.
+func runCorpusProgram(dir string, source string, inputs map[string]TestCase, opts specOptions) *corpusResult {
+	tc, ok := inputs[source]
+	if !ok {
+		tc = inputs[filepath.Base(source)]
+	}
+	obj, err := assemble(filepath.Join(dir, filepath.FromSlash(source)), newAssemblerState())
+	if err != nil {
+		return &corpusResult{Error: err.Error()}
+	}
+	maxSteps := opts.maxSteps
+	if tc.MaxSteps > 0 {
+		maxSteps = tc.MaxSteps
+	}
+	steps := &stepCounter{}
+	transcript, timedOut := runTranscript(obj, tc.Inputs, maxSteps, opts.timeout, steps)
+	if timedOut {
+		transcript += fmt.Sprintf("Timed out after %v\n", opts.timeout)
+	}
+	return &corpusResult{Transcript: transcript, Steps: steps.steps}
+}
+
+// runCorpus runs every program under dir, opts.jobs at a time.
+func runCorpus(dir string, inputs map[string]TestCase, opts specOptions) (map[string]*corpusResult, error) {
+	sources, err := corpusSources(dir)
+	if err != nil {
+		return nil, err
+	}
+	results := make([]*corpusResult, len(sources))
+	indexes := make(chan int)
+	var wg sync.WaitGroup
+	for n := 0; n < max(opts.jobs, 1); n++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := range indexes {
+				results[i] = runCorpusProgram(dir, sources[i], inputs, opts)
+			}
+		}()
+	}
+	for i := range sources {
+		indexes <- i
+	}
+	close(indexes)
+	wg.Wait()
+
+	programs := make(map[string]*corpusResult)
+	for i, source := range sources {
+		programs[source] = results[i]
+	}
+	return programs, nil
+}
+
+// describeCorpusChange explains how a program behaves differently from
+// its baseline, or returns "" when it does not.
+func describeCorpusChange(was *corpusResult, now *corpusResult) string {
+	var b strings.Builder
+	if was.Error != now.Error {
+		fmt.Fprintf(&b, "  assembly: %q -> %q\n", was.Error, now.Error)
+	}
+	if was.Transcript != now.Transcript {
+		b.WriteString(lineDiff(was.Transcript, now.Transcript))
+		b.WriteString(formatFirstDifference(was.Transcript, now.Transcript))
+	}
+	if was.Steps != now.Steps {
+		fmt.Fprintf(&b, "  steps: %d -> %d\n", was.Steps, now.Steps)
+	}
+	return b.String()
+}
+
+// compareCorpus prints the programs that changed, appeared or went away
+// since the baseline and returns whether any changed or went away.
+func compareCorpus(w io.Writer, baseline *corpusBaseline, programs map[string]*corpusResult) bool {
+	var names []string
+	for name := range programs {
+		names = append(names, name)
+	}
+	for name := range baseline.Programs {
+		if _, ok := programs[name]; !ok {
+			names = append(names, name)
+		}
+	}
+	sort.Strings(names)
+
+	unchanged, changed, added, removed := 0, 0, 0, 0
+	for _, name := range names {
+		was, inBaseline := baseline.Programs[name]
+		now, inCorpus := programs[name]
+		switch {
+		case !inBaseline:
+			fmt.Fprintf(w, "new %s\n", name)
+			added++
+		case !inCorpus:
+			fmt.Fprintf(w, "removed %s\n", name)
+			removed++
+		default:
+			if diff := describeCorpusChange(was, now); diff != "" {
+				fmt.Fprintf(w, "changed %s\n%s", name, diff)
+				changed++
+			} else {
+				unchanged++
+			}
+		}
+	}
+	fmt.Fprintf(w, "%d unchanged, %d changed, %d new, %d removed\n", unchanged, changed, added, removed)
+	return changed > 0 || removed > 0
+}
+
+// writeCorpusBaseline saves the results as the baseline at path.
+func writeCorpusBaseline(path string, programs map[string]*corpusResult) error {
+	data, err := json.MarshalIndent(corpusBaseline{Version: VERSION, Programs: programs}, "", "  ")
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot encode baseline: %v", err)
+	}
+	if err := os.WriteFile(path, append(data, '\n'), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write baseline: %v", err)
+	}
+	return nil
+}
+
+// runCorpusCommand implements "c2c2 corpus run DIR": it runs every .cas
+// file under DIR and, with -baseline, records the results on the first
+// run and reports the programs that behave differently on later ones.
+func runCorpusCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("corpus", flag.ContinueOnError)
+	fs.SetOutput(w)
+	baselinePath := fs.String("baseline", "", "record the results in the given JSON file, or compare with it when it exists")
+	update := fs.Bool("update", false, "with -baseline, write the results over the baseline instead of comparing")
+	inputsPath := fs.String("inputs", "", "JSON test spec giving the IN lines of the programs, by path or file name")
+	jobs := fs.Int("jobs", runtime.NumCPU(), "number of programs run at the same time")
+	maxSteps := fs.Int("max-steps", TEST_MAX_STEPS, "instruction limit of each program")
+	timeout := fs.Duration("timeout", TEST_TIMEOUT, "wall-clock limit of each program")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 corpus run DIR [-baseline FILE [-update]] [-inputs FILE] [-jobs N]\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+
+	if len(args) == 0 || args[0] != "run" {
+		fs.Usage()
+		return 2
+	}
+	// The directory may come before or after the options
+	if err := fs.Parse(args[1:]); err != nil {
+		return 2
+	}
+	if fs.NArg() == 0 {
+		fs.Usage()
+		return 2
+	}
+	dir := fs.Arg(0)
+	if err := fs.Parse(fs.Args()[1:]); err != nil {
+		return 2
+	}
+	if fs.NArg() > 0 {
+		fs.Usage()
+		return 2
+	}
+
+	inputs, err := loadCorpusInputs(*inputsPath)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+	programs, err := runCorpus(dir, inputs, specOptions{jobs: *jobs, maxSteps: *maxSteps, timeout: *timeout})
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+
+	if *baselinePath == "" {
+		var names []string
+		for name := range programs {
+			names = append(names, name)
+		}
+		sort.Strings(names)
+		for _, name := range names {
+			if r := programs[name]; r.Error != "" {
+				fmt.Fprintf(w, "error %s: %s\n", name, r.Error)
+			} else {
+				fmt.Fprintf(w, "ran %s (%d steps)\n", name, r.Steps)
+			}
+		}
+		return 0
+	}
+
+	data, err := os.ReadFile(*baselinePath)
+	if os.IsNotExist(err) || *update {
+		if err := writeCorpusBaseline(*baselinePath, programs); err != nil {
+			fmt.Fprintln(w, err)
+			return 2
+		}
+		fmt.Fprintf(w, "Recorded %d programs in %s\n", len(programs), *baselinePath)
+		return 0
+	} else if err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] Cannot read baseline: %v\n", err)
+		return 2
+	}
+	var baseline corpusBaseline
+	if err := json.Unmarshal(data, &baseline); err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] %s: invalid baseline: %v\n", *baselinePath, err)
+		return 2
+	}
+	if compareCorpus(w, &baseline, programs) {
+		return 1
+	}
+	return 0
+}
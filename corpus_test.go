@@ -0,0 +1,52 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestCorpusBaseline(t *testing.T) {
+	dir := t.TempDir()
+	write := func(name string, src string) {
+		t.Helper()
+		path := filepath.Join(dir, filepath.FromSlash(name))
+		if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+			t.Fatal(err)
+		}
+		if err := os.WriteFile(path, []byte(src), 0644); err != nil {
+			t.Fatal(err)
+		}
+	}
+	write("a/echo.cas", echoProgram)
+	write("b.cas", "MAIN\tSTART\n\tOUT\tX,ONE\n\tRET\nX\tDC\t'x'\nONE\tDC\t1\n\tEND\n")
+	inputs := filepath.Join(dir, "inputs.json")
+	if err := os.WriteFile(inputs, []byte(`{"echo.cas": ["hi"]}`), 0644); err != nil {
+		t.Fatal(err)
+	}
+	baseline := filepath.Join(t.TempDir(), "results.json")
+
+	var out strings.Builder
+	if code := runCorpusCommand([]string{"run", dir, "--baseline", baseline, "-inputs", inputs}, &out); code != 0 {
+		t.Fatalf("Expected the baseline to be recorded, got %d: %s", code, out.String())
+	}
+	if !strings.Contains(out.String(), "Recorded 2 programs in") {
+		t.Errorf("Unexpected output %q", out.String())
+	}
+
+	write("b.cas", "MAIN\tSTART\n\tOUT\tX,ONE\n\tRET\nX\tDC\t'y'\nONE\tDC\t1\n\tEND\n")
+	write("c.cas", "MAIN\tSTART\n\tRET\n\tEND\n")
+	out.Reset()
+	if code := runCorpusCommand([]string{"run", dir, "-baseline", baseline, "-inputs", inputs}, &out); code != 1 {
+		t.Fatalf("Expected a changed program, got %d: %s", code, out.String())
+	}
+	for _, want := range []string{"changed b.cas\n-   1: OUT> x\n+   1: OUT> y\n", "new c.cas\n", "1 unchanged, 1 changed, 1 new, 0 removed\n"} {
+		if !strings.Contains(out.String(), want) {
+			t.Errorf("Expected %q in:\n%s", want, out.String())
+		}
+	}
+	if strings.Contains(out.String(), "echo.cas") {
+		t.Errorf("Expected a/echo.cas to be unchanged:\n%s", out.String())
+	}
+}
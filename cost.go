@@ -0,0 +1,169 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+// Instruction classes that a cost table can weigh as a whole
+var instructionClasses = map[string][]string{
+	"transfer": {"LD", "ST", "LAD"},
+	"arith":    {"ADDA", "SUBA", "ADDL", "SUBL"},
+	"muldiv":   {"MULA", "DIVA", "MULL", "DIVL"},
+	"logic":    {"AND", "OR", "XOR"},
+	"compare":  {"CPA", "CPL"},
+	"shift":    {"SLA", "SRA", "SLL", "SRL"},
+	"branch":   {"JMI", "JNZ", "JZE", "JUMP", "JPL", "JOV"},
+	"stack":    {"PUSH", "POP"},
+	"call":     {"CALL", "RET"},
+	"svc":      {"SVC"},
+	"nop":      {"NOP"},
+}
+
+// costTable gives the weight of each executed instruction for -cost.
+// Instructions not listed cost Default.
+type costTable struct {
+	Default int
+	weights map[string]int
+}
+
+// weight returns the cost of one execution of inst.
+func (t *costTable) weight(inst string) int {
+	if w, ok := t.weights[inst]; ok {
+		return w
+	}
+	return t.Default
+}
+
+// parseCostTable reads a cost table in a small subset of TOML: a top
+// level "default" key, a [classes] table keyed by the names in
+// instructionClasses and an [instructions] table keyed by mnemonics,
+// which takes precedence over the classes. Values are integers.
+//
+//	default = 1
+//	[classes]
+//	muldiv = 10
+//	[instructions]
+//	DIVA = 20
+func parseCostTable(text string) (*costTable, error) {
+	t := &costTable{Default: 1, weights: make(map[string]int)}
+	insts := make(map[string]int)
+	section := ""
+	for n, line := range strings.Split(text, "\n") {
+		if i := strings.Index(line, "#"); i >= 0 {
+			line = line[:i]
+		}
+		line = strings.TrimSpace(line)
+		if line == "" {
+			continue
+		}
+		if strings.HasPrefix(line, "[") && strings.HasSuffix(line, "]") {
+			section = strings.TrimSpace(line[1 : len(line)-1])
+			if section != "classes" && section != "instructions" {
+				return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: unknown table [%s]", n+1, section)
+			}
+			continue
+		}
+
+		key, value, ok := strings.Cut(line, "=")
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: expected KEY = VALUE", n+1)
+		}
+		key = strings.Trim(strings.TrimSpace(key), `"`)
+		w, err := strconv.Atoi(strings.ReplaceAll(strings.TrimSpace(value), "_", ""))
+		if err != nil || w < 0 {
+			return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: invalid cost \"%s\"", n+1, strings.TrimSpace(value))
+		}
+
+		switch section {
+		case "":
+			if key != "default" {
+				return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: unknown key \"%s\"", n+1, key)
+			}
+			t.Default = w
+		case "classes":
+			members, ok := instructionClasses[key]
+			if !ok {
+				return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: unknown instruction class \"%s\"", n+1, key)
+			}
+			for _, inst := range members {
+				t.weights[inst] = w
+			}
+		case "instructions":
+			inst := strings.ToUpper(key)
+			if !isMachineInstruction(inst) {
+				return nil, fmt.Errorf("[COMET2 ERROR] Cost table line %d: unknown instruction \"%s\"", n+1, key)
+			}
+			insts[inst] = w
+		}
+	}
+	for inst, w := range insts {
+		t.weights[inst] = w
+	}
+	return t, nil
+}
+
+// isMachineInstruction reports whether inst is a COMET2 mnemonic.
+func isMachineInstruction(inst string) bool {
+	for _, i := range COMET2TBL {
+		if i.ID == inst {
+			return true
+		}
+	}
+	return false
+}
+
+// loadCostTable reads the cost table file for -cost.
+func loadCostTable(path string) (*costTable, error) {
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read cost table: %v", err)
+	}
+	return parseCostTable(string(content))
+}
+
+// labelCost is the cost of the instructions after one label.
+type labelCost struct {
+	Label string `json:"label"`
+	Cost  int    `json:"cost"`
+}
+
+// costReport is the JSON form of the accumulated cost.
+type costReport struct {
+	Total  int         `json:"total"`
+	Labels []labelCost `json:"labels"`
+}
+
+// costOf attributes the cost of the instruction at pc to the nearest
+// label at or before it.
+func (s *statsCollector) costOf(c *Comet2, pc int, inst string) {
+	label := c.labelAt(pc)
+	if i := strings.Index(label, "+"); i >= 0 {
+		label = label[:i]
+	}
+	if label == "" {
+		label = "-"
+	}
+	w := s.cost.weight(inst)
+	s.costTotal += w
+	s.costs[label] += w
+}
+
+// costReport builds the per-label cost, most expensive label first.
+func (s *statsCollector) costReport() *costReport {
+	r := &costReport{Total: s.costTotal, Labels: []labelCost{}}
+	for label, cost := range s.costs {
+		r.Labels = append(r.Labels, labelCost{Label: label, Cost: cost})
+	}
+	sort.Slice(r.Labels, func(i, j int) bool {
+		a, b := r.Labels[i], r.Labels[j]
+		if a.Cost != b.Cost {
+			return a.Cost > b.Cost
+		}
+		return a.Label < b.Label
+	})
+	return r
+}
@@ -0,0 +1,48 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestCostTable(t *testing.T) {
+	table, err := parseCostTable(`# weights for the sorting assignment
+default = 1
+[classes]
+arith = 2   # ADDA, SUBA, ADDL, SUBL
+[instructions]
+SUBA = 5
+`)
+	if err != nil {
+		t.Fatalf("parseCostTable: %v", err)
+	}
+	if table.weight("ADDA") != 2 || table.weight("SUBA") != 5 || table.weight("LD") != 1 {
+		t.Errorf("Unexpected weights: %v", table.weights)
+	}
+
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,3
+LOOP	ADDA	GR2,GR1
+	SUBA	GR1,=1
+	JNZ	LOOP
+	RET
+	END
+`)
+	stats := newStatsCollector()
+	stats.cost = table
+	c.AddObserver(stats)
+	c.Run()
+
+	// MAIN: LAD 1; LOOP: 3 * (ADDA 2 + SUBA 5 + JNZ 1) + RET 1
+	r := stats.report().Cost
+	want := []labelCost{{"LOOP", 25}, {"MAIN", 1}}
+	if r == nil || r.Total != 26 || !reflect.DeepEqual(r.Labels, want) {
+		t.Errorf("Unexpected cost: %+v", r)
+	}
+
+	for _, bad := range []string{"[timing]", "fast = 1", "[classes]\nmemory = 1", "[instructions]\nMOVE = 1", "default = -1"} {
+		if _, err := parseCostTable(bad); err == nil {
+			t.Errorf("Expected an error for %q", bad)
+		}
+	}
+}
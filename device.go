@@ -0,0 +1,132 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// Address of the timer device unless another one is given
+const TIMER_ADDRESS = 0xffe0
+
+// Device is hardware mapped into the address space of a machine. Loads and
+// stores of the program within its range go to the device instead of
+// Memory, and Tick runs after every instruction. Device accesses are not
+// memory accesses, so observers do not see them and undo cannot revert
+// them.
+type Device interface {
+	// Name identifies the device in messages.
+	Name() string
+	// Range returns the first address and the number of words mapped.
+	Range() (base int, size int)
+	// Read returns the word at offset from the first address.
+	Read(c *Comet2, offset int) int
+	// Write stores val, a word, at offset from the first address.
+	Write(c *Comet2, offset int, val int)
+	// Tick is called after every executed instruction.
+	Tick(c *Comet2)
+}
+
+// DeviceFactory creates a device from the options given to -device, e.g.
+// the address in "timer:#FFE0". options is "" when none are given.
+type DeviceFactory func(options string) (Device, error)
+
+var deviceFactories = map[string]DeviceFactory{}
+
+// registerDevice makes a device available to -device as name.
+func registerDevice(name string, f DeviceFactory) {
+	deviceFactories[name] = f
+}
+
+func init() {
+	registerDevice("timer", newTimer)
+}
+
+// deviceNames returns the registered device names in sorted order.
+func deviceNames() []string {
+	names := make([]string, 0, len(deviceFactories))
+	for name := range deviceFactories {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// parseDevices creates the devices of the -device option: NAME[:OPTIONS]
+// separated by commas.
+func parseDevices(spec string) ([]Device, error) {
+	var devices []Device
+	for _, item := range strings.Split(spec, ",") {
+		item = strings.TrimSpace(item)
+		if item == "" {
+			continue
+		}
+		name, options, _ := strings.Cut(item, ":")
+		f, ok := deviceFactories[name]
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Unknown device \"%s\" (available: %s)",
+				name, strings.Join(deviceNames(), ", "))
+		}
+		d, err := f(options)
+		if err != nil {
+			return nil, err
+		}
+		devices = append(devices, d)
+	}
+	return devices, nil
+}
+
+// AddDevice maps d into the address space. Its range must lie within
+// Memory and not overlap another device.
+func (c *Comet2) AddDevice(d Device) error {
+	base, size := d.Range()
+	if base < 0 || size < 1 || base+size > len(c.Memory) {
+		return fmt.Errorf("[COMET2 ERROR] Device %s at #%s-#%s is outside the memory",
+			d.Name(), hex(base, 4), hex(base+size-1, 4))
+	}
+	for _, other := range c.devices {
+		b, s := other.Range()
+		if base < b+s && b < base+size {
+			return fmt.Errorf("[COMET2 ERROR] Device %s at #%s overlaps %s at #%s",
+				d.Name(), hex(base, 4), other.Name(), hex(b, 4))
+		}
+	}
+	c.devices = append(c.devices, d)
+	return nil
+}
+
+// deviceAt returns the device mapped at addr and the offset of addr in
+// it, or nil.
+func (c *Comet2) deviceAt(addr int) (Device, int) {
+	for _, d := range c.devices {
+		if base, size := d.Range(); addr >= base && addr < base+size {
+			return d, addr - base
+		}
+	}
+	return nil, 0
+}
+
+// timer counts executed instructions in one word, wrapping at #FFFF.
+// Writing a value sets the count, e.g. 0 to start measuring.
+type timer struct {
+	base  int
+	count int
+}
+
+func newTimer(options string) (Device, error) {
+	base := TIMER_ADDRESS
+	if options != "" {
+		n, ok := expandNumber(options)
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Invalid timer address \"%s\"", options)
+		}
+		base = n
+	}
+	return &timer{base: base}, nil
+}
+
+func (t *timer) Name() string                         { return "timer" }
+func (t *timer) Range() (int, int)                    { return t.base, 1 }
+func (t *timer) Read(c *Comet2, offset int) int       { return t.count }
+func (t *timer) Write(c *Comet2, offset int, val int) { t.count = val }
+func (t *timer) Tick(c *Comet2)                       { t.count = (t.count + 1) & 0xffff }
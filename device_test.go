@@ -0,0 +1,38 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestDevices(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR0,0
+	ST	GR0,#FFE0
+	NOP
+	NOP
+	LD	GR1,#FFE0
+	RET
+	END
+`)
+	devices, err := parseDevices("timer")
+	if err != nil {
+		t.Fatalf("parseDevices failed: %v", err)
+	}
+	if err := c.AddDevice(devices[0]); err != nil {
+		t.Fatalf("AddDevice failed: %v", err)
+	}
+	c.Memory[TIMER_ADDRESS] = 99
+	c.Run()
+	if c.State[GR1] != 3 || c.Memory[TIMER_ADDRESS] != 99 {
+		t.Errorf("Expected the timer to read 3 and memory to be untouched, got %d and %d", c.State[GR1], c.Memory[TIMER_ADDRESS])
+	}
+
+	other, _ := newTimer("#FFE0")
+	if err := c.AddDevice(other); err == nil || !strings.Contains(err.Error(), "overlaps timer") {
+		t.Errorf("Expected overlapping devices to fail, got %v", err)
+	}
+	if _, err := parseDevices("lamp"); err == nil || !strings.Contains(err.Error(), "available: timer") {
+		t.Errorf("Expected an unknown device to fail, got %v", err)
+	}
+}
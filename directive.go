@@ -0,0 +1,270 @@
+package main
+
+import (
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+// pass1State is what pass 1 carries from line to line: whether it is
+// inside a START/END unit, the address of the next word, the literals
+// waiting for a pool and the label execution starts at.
+type pass1State struct {
+	asm        *AssemblerState
+	inBlock    bool
+	address    int
+	literals   []LiteralEntry
+	startLabel string
+}
+
+// unitPart is the part of a unit's size in "-size" that the words of a
+// line count toward.
+type unitPart int
+
+const (
+	PART_CODE unitPart = iota
+	PART_DATA
+	PART_LITERALS
+)
+
+// directive is an assembler directive or macro. Operands is its operand
+// schema for checkOperands, the smallest and largest number of operands
+// (-1: no limit), and Part the part of the unit its words count toward.
+// Assemble handles a line of it in pass 1, given the label of the line
+// (or "") and its operands: it generates the words from s.address on and
+// moves s.address past them. Doc is its entry in "c2c2 doc".
+type directive struct {
+	Type     InstructionType
+	Operands [2]int
+	Part     unitPart
+	Assemble func(s *pass1State, label string, oprs []string) error
+	Doc      instructionDoc
+}
+
+// directives are the registered directives by type
+var directives = map[InstructionType]directive{}
+
+// registerDirective makes d an instruction of the assembler, named after
+// its type in upper case.
+func registerDirective(d directive) {
+	name := strings.ToUpper(string(d.Type))
+	directives[d.Type] = d
+	CASL2TBL[name] = Instruction{0x00, d.Type}
+	instructionDocs[name] = d.Doc
+	operandCounts[d.Type] = d.Operands
+}
+
+func init() {
+	registerDirective(directive{START, [2]int{0, 1}, PART_CODE, assembleStart, instructionDoc{summary: "start of a program unit", operands: "[entry]", flags: "-", detail: "The label names the unit; execution starts at entry, or at the first word.", example: "MAIN\tSTART\tBEGIN"}})
+	registerDirective(directive{END, [2]int{0, 0}, PART_LITERALS, assembleEnd, instructionDoc{summary: "end of a program unit", operands: "", flags: "-", detail: "Literals still pending are placed here.", example: "END"}})
+	registerDirective(directive{DS, [2]int{1, 1}, PART_DATA, assembleDS, instructionDoc{summary: "define storage", operands: "words", flags: "-", detail: "Reserves the given number of words, initially 0.", example: "BUF\tDS\t256"}})
+	registerDirective(directive{DC, [2]int{1, -1}, PART_DATA, assembleDC, instructionDoc{summary: "define constant", operands: "const[,const...]", flags: "-", detail: "Places decimal, #hex, 'string' or address constants.", example: "MSG\tDC\t'Hello',10,#FFFF"}})
+	registerDirective(directive{IN, [2]int{2, 2}, PART_CODE, assembleInOut(SYS_IN), instructionDoc{summary: "input a line (macro)", operands: "buf,len", flags: "-", detail: "Reads a line into buf, one character per word, and its length into len. GR1 and GR2 are preserved.", example: "IN\tBUF,LEN"}})
+	registerDirective(directive{OUT, [2]int{2, 2}, PART_CODE, assembleInOut(SYS_OUT), instructionDoc{summary: "output a line (macro)", operands: "buf,len", flags: "-", detail: "Writes len characters from buf. GR1 and GR2 are preserved.", example: "OUT\tBUF,LEN"}})
+	registerDirective(directive{RPUSH, [2]int{0, 0}, PART_CODE, assembleRpush, instructionDoc{summary: "push GR1-GR7 (macro)", operands: "", flags: "-", detail: "Pushes GR1 to GR7 in this order.", example: "RPUSH"}})
+	registerDirective(directive{RPOP, [2]int{0, 0}, PART_CODE, assembleRpop, instructionDoc{summary: "pop GR7-GR1 (macro)", operands: "", flags: "-", detail: "Pops GR7 to GR1, undoing RPUSH.", example: "RPOP"}})
+	registerDirective(directive{LTORG, [2]int{0, 0}, PART_LITERALS, assembleLtorg, instructionDoc{summary: "place pending literals (extension)", operands: "", flags: "-", detail: "Places the literals referenced since the last pool at this point.", example: "LTORG"}})
+	registerDirective(directive{EQU, [2]int{1, 1}, PART_CODE, assembleEqu, instructionDoc{summary: "define a named constant (extension)", operands: "value", flags: "-", detail: "The label stands for value instead of an address. IFDEF and IFEQ can test it.", example: "SIZE\tEQU\t10"}})
+}
+
+// opcode returns the code of the machine instruction id, for the words
+// macros expand to.
+func opcode(id string) int {
+	return int(CASL2TBL[id].Code)
+}
+
+// assembleStart begins a program unit named after the label, entered at
+// the label given as operand or at its first word.
+func assembleStart(s *pass1State, label string, oprs []string) error {
+	asmState := s.asm
+	if label == "" {
+		return errorCasl2(asmState, "No label found at START")
+	}
+
+	// Execution of the unit, and CALLs to its label, begin at the entry
+	// label when one is given
+	entry := label
+	asmState.actualLabel = ""
+	if len(oprs) > 0 && oprs[0] != label {
+		if err := checkLabel(asmState, oprs[0]); err != nil {
+			return err
+		}
+		entry = oprs[0]
+		asmState.actualLabel = entry
+	}
+	asmState.virtualLabel = label
+
+	if asmState.firstStart {
+		asmState.firstStart = false
+		s.startLabel = label + ":" + label
+		asmState.entry = label
+		if entry != label {
+			asmState.entry = label + ":" + entry
+		}
+	}
+
+	// Labels are scoped by unit name, so a second unit of the same name
+	// would collide with the first one
+	if prev, ok := asmState.symtbl[label+":"+label]; ok {
+		first, again := asmState.origin(prev.Line), asmState.origin(asmState.line)
+		return errorCasl2(asmState, fmt.Sprintf("Program unit \"%s\" is defined twice: %s line %d and %s line %d",
+			label, first.File, first.Line, again.File, again.Line))
+	}
+
+	asmState.varScope = label
+	asmState.units = append(asmState.units, UnitSize{Name: label, Start: s.address, Entry: s.address})
+	if err := addLabel(asmState, label, s.address); err != nil {
+		return err
+	}
+	s.inBlock = true
+	return nil
+}
+
+// assembleEnd ends the unit, placing the literals not yet in a pool.
+func assembleEnd(s *pass1State, label string, oprs []string) error {
+	asmState := s.asm
+	if label != "" {
+		return errorCasl2(asmState, fmt.Sprintf("Can't use label \"%s\" at END", label))
+	}
+	if asmState.actualLabel != "" {
+		return errorCasl2(asmState, fmt.Sprintf("Entry label \"%s\" of \"%s\" is not defined", asmState.actualLabel, asmState.virtualLabel))
+	}
+
+	// Expand literals
+	var err error
+	if s.address, err = flushLiterals(asmState, &s.literals, s.address); err != nil {
+		return err
+	}
+
+	asmState.varScope = ""
+	s.inBlock = false
+	return nil
+}
+
+// assembleDS reserves the given number of words.
+func assembleDS(s *pass1State, label string, oprs []string) error {
+	asmState := s.asm
+	count, ok, err := numberOperand(asmState, oprs[0])
+	if err != nil {
+		return err
+	}
+	if !ok || count < 0 || count > 0xffff {
+		return errorCasl2(asmState, fmt.Sprintf("\"%s\" must be a number from 0 to 65535", oprs[0]))
+	}
+	if err := checkProgramSize(asmState, s.address+count); err != nil {
+		return err
+	}
+	for j := 0; j < count; j++ {
+		genCode1(asmState.memory, s.address, 0, asmState)
+		s.address++
+	}
+	return nil
+}
+
+// assembleDC generates a word for each number or label and the
+// characters of each string.
+func assembleDC(s *pass1State, label string, oprs []string) error {
+	asmState := s.asm
+	for k, op := range oprs {
+		if strings.HasPrefix(op, "'") && strings.HasSuffix(op, "'") {
+			str := op[1 : len(op)-1]
+			str = strings.ReplaceAll(str, "''", "'")
+			for _, ch := range str {
+				genCode1(asmState.memory, s.address, int(ch), asmState)
+				s.address++
+			}
+			genCode1(asmState.memory, s.address, 0, asmState)
+			s.address++
+		} else if isLabel(op) {
+			op = asmState.varScope + ":" + op
+			genCode1(asmState.memory, s.address, op, asmState)
+			s.address++
+		} else {
+			val, ok, err := numberOperand(asmState, op)
+			if err != nil {
+				return err
+			}
+			if ok {
+				checkConstant(asmState, op, val)
+				genCode1(asmState.memory, s.address, val&0xffff, asmState)
+				if k == 0 && label != "" {
+					asmState.constants = append(asmState.constants, constantEntry{Label: label, Value: val & 0xffff, Address: s.address, Line: asmState.line})
+				}
+			} else if *optStrict {
+				return errorCasl2(asmState, fmt.Sprintf("Invalid constant \"%s\"", op))
+			} else {
+				genCode1(asmState.memory, s.address, op, asmState)
+			}
+			s.address++
+		}
+	}
+	return nil
+}
+
+// assembleInOut returns the handler of the IN or OUT macro calling the
+// SVC at entry with the buffer and length word in GR1 and GR2.
+func assembleInOut(entry int) func(s *pass1State, label string, oprs []string) error {
+	return func(s *pass1State, label string, oprs []string) error {
+		asmState := s.asm
+		checkLabel(asmState, oprs[0])
+		checkLabel(asmState, oprs[1])
+
+		buffer := asmState.varScope + ":" + oprs[0]
+		length := asmState.varScope + ":" + oprs[1]
+
+		address := s.address
+		genCode2(asmState.memory, address, opcode("PUSH"), "0", "0", "1", asmState)
+		genCode2(asmState.memory, address+2, opcode("PUSH"), "0", "0", "2", asmState)
+		genCode2(asmState.memory, address+4, opcode("LAD"), "1", buffer, "0", asmState)
+		genCode2(asmState.memory, address+6, opcode("LAD"), "2", length, "0", asmState)
+		genCode2(asmState.memory, address+8, opcode("SVC"), "0", strconv.Itoa(entry), "0", asmState)
+		genCode3(asmState.memory, address+10, opcode("POP"), "2", "0", asmState)
+		genCode3(asmState.memory, address+11, opcode("POP"), "1", "0", asmState)
+		s.address += 12
+		return nil
+	}
+}
+
+// assembleRpush pushes GR1 to GR7.
+func assembleRpush(s *pass1State, label string, oprs []string) error {
+	for j := 0; j < 7; j++ {
+		genCode2(s.asm.memory, s.address+j*2, opcode("PUSH"), "0", "0", strconv.Itoa(j+1), s.asm)
+	}
+	s.address += 14
+	return nil
+}
+
+// assembleRpop pops GR7 to GR1.
+func assembleRpop(s *pass1State, label string, oprs []string) error {
+	for j := 0; j < 7; j++ {
+		genCode3(s.asm.memory, s.address+j, opcode("POP"), strconv.Itoa(7-j), "0", s.asm)
+	}
+	s.address += 7
+	return nil
+}
+
+// assembleLtorg places the literals referenced so far.
+func assembleLtorg(s *pass1State, label string, oprs []string) error {
+	var err error
+	s.address, err = flushLiterals(s.asm, &s.literals, s.address)
+	return err
+}
+
+// assembleEqu makes the label stand for the value instead of an address;
+// the preprocessor also uses it for IFDEF and IFEQ.
+func assembleEqu(s *pass1State, label string, oprs []string) error {
+	asmState := s.asm
+	if label == "" {
+		return errorCasl2(asmState, "No label found at EQU")
+	}
+	val, ok, err := numberOperand(asmState, oprs[0])
+	if err != nil {
+		return err
+	}
+	if !ok {
+		return errorCasl2(asmState, fmt.Sprintf("\"%s\" must be a number", oprs[0]))
+	}
+	asmState.symtbl[asmState.varScope+":"+label].Val = val & 0xffff
+	asmState.equates[asmState.varScope+":"+label] = true
+	return nil
+}
@@ -0,0 +1,54 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestRegisterDirective(t *testing.T) {
+	// FILL n,v reserves n words holding v
+	fill := InstructionType("fill")
+	t.Cleanup(func() {
+		delete(directives, fill)
+		delete(CASL2TBL, "FILL")
+		delete(instructionDocs, "FILL")
+		delete(operandCounts, fill)
+	})
+	registerDirective(directive{fill, [2]int{2, 2}, PART_DATA, func(s *pass1State, label string, oprs []string) error {
+		count, _, err := numberOperand(s.asm, oprs[0])
+		if err != nil {
+			return err
+		}
+		val, _, err := numberOperand(s.asm, oprs[1])
+		if err != nil {
+			return err
+		}
+		for j := 0; j < count; j++ {
+			genCode1(s.asm.memory, s.address, val, s.asm)
+			s.address++
+		}
+		return nil
+	}, instructionDoc{summary: "fill words", operands: "count,value", flags: "-", detail: "Reserves count words holding value.", example: "BUF\tFILL\t3,#FFFF"}})
+	if text, ok := formatDoc("FILL"); !ok || !strings.Contains(text, "FILL - fill words") {
+		t.Errorf("Expected a reference entry for FILL, got %q", text)
+	}
+
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,BUF
+	RET
+BUF	FILL	3,#FFFF
+	END
+`)
+	if want := []uint16{0x1010, 0x0003, 0x8100, 0xffff, 0xffff, 0xffff}; !reflect.DeepEqual(obj.Bin, want) {
+		t.Errorf("Expected %v, got %v", want, obj.Bin)
+	}
+	if obj.Units[0].Code != 3 || obj.Units[0].Data != 3 {
+		t.Errorf("Expected 3 words of code and 3 of data, got %+v", obj.Units[0])
+	}
+
+	_, err := assembleText("fill.cas", "MAIN\tSTART\n\tFILL\t3\n\tEND\n", newAssemblerState())
+	if err == nil || !strings.Contains(err.Error(), DIAG_OPERAND_COUNT) {
+		t.Errorf("Expected an operand count error, got %v", err)
+	}
+}
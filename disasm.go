@@ -0,0 +1,100 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"strings"
+)
+
+// disasmLines renders the whole program of obj like the monitor mem
+// command, marking the words of literal pools. With source, the lines of
+// the source files are interleaved before the words generated from them,
+// like objdump -S, so that the words a macro or a literal pool became
+// follow the line that produced them. Source lines are shown with their
+// number; lines that generated nothing, such as comments, come along with
+// the next line that did.
+func disasmLines(obj *Object, source bool) []string {
+	c := LoadObject(obj)
+	inPool := make(map[int]bool)
+	for _, r := range obj.Literals {
+		for addr := r.Start; addr < r.End; addr++ {
+			inPool[addr] = true
+		}
+	}
+
+	var lines []string
+	shown := make(map[string]int)
+	var files []string
+	showSource := func(file string, upTo int) {
+		text := c.sourceLines(file)
+		if _, ok := shown[file]; !ok {
+			files = append(files, file)
+		}
+		for n := shown[file] + 1; n <= min(upTo, len(text)); n++ {
+			lines = append(lines, strings.TrimRight(fmt.Sprintf("%5d  %s", n, text[n-1]), " \t"))
+		}
+		shown[file] = max(shown[file], upTo)
+	}
+
+	for addr := 0; addr < c.AddressMax; {
+		if source && addr < len(obj.SourceMap) && obj.SourceMap[addr].Line > 0 {
+			showSource(obj.SourceMap[addr].File, obj.SourceMap[addr].Line)
+		}
+		words, next := memViewLines(c, addr, 1)
+		if inPool[addr] {
+			words[len(words)-1] += "  (literal)"
+		}
+		lines = append(lines, words...)
+		addr = next
+	}
+	// The rest of each file, such as END
+	for _, file := range files {
+		text := c.sourceLines(file)
+		if len(text) > 0 && text[len(text)-1] == "" {
+			text = text[:len(text)-1]
+		}
+		showSource(file, len(text))
+	}
+	return lines
+}
+
+// runDisasmCommand implements "c2c2 disasm [-S] FILE": the disassembly of
+// a whole program, assembled from a source or read from an object file. It
+// returns the exit status.
+func runDisasmCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("disasm", flag.ContinueOnError)
+	fs.SetOutput(w)
+	source := fs.Bool("S", false, "interleave the source lines with the words generated from them")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 disasm [-S] FILE.cas|FILE%s\n\nOptions:\n", OBJECT_EXT)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 1 {
+		fs.Usage()
+		return 2
+	}
+
+	var obj *Object
+	var err error
+	if path := fs.Arg(0); strings.HasSuffix(path, OBJECT_EXT) {
+		obj, err = loadObjectFile(path)
+	} else {
+		obj, err = assemble(path, newAssemblerState())
+	}
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	if *source && len(obj.SourceMap) == 0 {
+		fmt.Fprintln(w, "[COMET2 ERROR] The object has no source lines to interleave")
+		return 1
+	}
+	for _, line := range disasmLines(obj, *source) {
+		fmt.Fprintln(w, line)
+	}
+	return 0
+}
@@ -0,0 +1,39 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestDisasmCommand(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "add.cas")
+	source := "; Load five\nMAIN    START\n        LD      GR1,=5\n        RET\n        END\n"
+	if err := os.WriteFile(path, []byte(source), 0644); err != nil {
+		t.Fatal(err)
+	}
+
+	var out bytes.Buffer
+	if status := runDisasmCommand([]string{"-S", path}, &out); status != 0 {
+		t.Fatalf("Expected success, got %d: %s", status, out.String())
+	}
+	want := "    1  ; Load five\n" +
+		"    2  MAIN    START\n" +
+		"MAIN:\n" +
+		"    3          LD      GR1,=5\n" +
+		"  #0000  1010 0003  LD\tGR1,   #0003\n" +
+		"    4          RET\n" +
+		"  #0002  8100       RET\n" +
+		"    5          END\n" +
+		"  #0003  0005            5     5  (literal)\n"
+	if out.String() != want {
+		t.Errorf("Expected\n%s\ngot\n%s", want, out.String())
+	}
+
+	out.Reset()
+	if status := runDisasmCommand([]string{path}, &out); status != 0 || strings.Contains(out.String(), "START") {
+		t.Errorf("Expected no source lines without -S, got %d: %s", status, out.String())
+	}
+}
@@ -0,0 +1,119 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"sort"
+	"strings"
+)
+
+// instructionDoc is the reference entry of one instruction, macro or
+// directive shown by "c2c2 doc", given by machineInstructions or by the
+// directive. The operand forms and encodings of machine instructions come
+// from COMET2TBL; operands is only given for the others.
+type instructionDoc struct {
+	summary  string
+	operands string
+	flags    string
+	detail   string
+	example  string
+}
+
+// Reference entries for every name in CASL2TBL: those of machineInstructions
+// and, as they are registered, those of the directives
+var instructionDocs = machineInstructionDocs()
+
+// machineInstructionDocs returns the reference entries of machineInstructions.
+func machineInstructionDocs() map[string]instructionDoc {
+	docs := make(map[string]instructionDoc)
+	for _, inst := range machineInstructions {
+		docs[inst.ID] = inst.Doc
+	}
+	return docs
+}
+
+// docForms returns the operand forms of name with their encodings, one
+// per line.
+func docForms(name string) []string {
+	var codes []int
+	for code, inst := range COMET2TBL {
+		if inst.ID == name {
+			codes = append(codes, code)
+		}
+	}
+	sort.Ints(codes)
+
+	var forms []string
+	for _, code := range codes {
+		var operands, encoding string
+		switch COMET2TBL[code].Type {
+		case OP1:
+			operands, encoding = "r,adr[,x]", fmt.Sprintf("#%02Xrx adr  (2 words)", code)
+		case OP2:
+			operands, encoding = "adr[,x]", fmt.Sprintf("#%02X0x adr  (2 words)", code)
+		case OP3:
+			operands, encoding = "r", fmt.Sprintf("#%02Xr0      (1 word)", code)
+		case OP4:
+			operands, encoding = "", fmt.Sprintf("#%02X00      (1 word)", code)
+		case OP5:
+			operands, encoding = "r1,r2", fmt.Sprintf("#%02X r1 r2  (1 word)", code)
+		}
+		forms = append(forms, fmt.Sprintf("%-6s %-12s%s", name, operands, encoding))
+	}
+	if len(forms) == 0 {
+		forms = append(forms, strings.TrimSpace(fmt.Sprintf("%-6s %s", name, instructionDocs[name].operands)))
+	}
+	return forms
+}
+
+// formatDoc renders the reference entry of name.
+func formatDoc(name string) (string, bool) {
+	doc, ok := instructionDocs[name]
+	if !ok {
+		return "", false
+	}
+	var b strings.Builder
+	fmt.Fprintf(&b, "%s - %s\n", name, doc.summary)
+	for _, form := range docForms(name) {
+		fmt.Fprintf(&b, "  %s\n", form)
+	}
+	fmt.Fprintf(&b, "Flags: %s\n", doc.flags)
+	fmt.Fprintf(&b, "%s\n", doc.detail)
+	fmt.Fprintf(&b, "Example:\n\t%s\n", doc.example)
+	return b.String(), true
+}
+
+// runDocCommand implements "c2c2 doc". Without a name it lists every
+// instruction; a diagnostic code such as E101 is explained. It returns the
+// exit status.
+func runDocCommand(args []string, w io.Writer) int {
+	if len(args) == 0 {
+		names := make([]string, 0, len(CASL2TBL))
+		for name := range CASL2TBL {
+			names = append(names, name)
+		}
+		sort.Strings(names)
+		for _, name := range names {
+			fmt.Fprintf(w, "%-6s %s\n", name, instructionDocs[name].summary)
+		}
+		return 0
+	}
+
+	status := 0
+	for i, arg := range args {
+		text, ok := formatDoc(strings.ToUpper(arg))
+		if detail, isCode := diagnosticDocs[strings.ToUpper(arg)]; isCode {
+			text, ok = fmt.Sprintf("%s\n%s\n", strings.ToUpper(arg), detail), true
+		}
+		if !ok {
+			fmt.Fprintf(w, "[CASL2 ERROR] Unknown instruction \"%s\"; see \"c2c2 doc\"\n", arg)
+			status = 1
+			continue
+		}
+		if i > 0 {
+			fmt.Fprintln(w)
+		}
+		fmt.Fprint(w, text)
+	}
+	return status
+}
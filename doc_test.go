@@ -0,0 +1,30 @@
+package main
+
+import (
+	"bytes"
+	"strings"
+	"testing"
+)
+
+func TestInstructionDocs(t *testing.T) {
+	for name := range CASL2TBL {
+		if _, ok := instructionDocs[name]; !ok {
+			t.Errorf("No reference entry for %s", name)
+		}
+	}
+
+	var out bytes.Buffer
+	if status := runDocCommand([]string{"adda"}, &out); status != 0 {
+		t.Fatalf("doc ADDA failed: %s", out.String())
+	}
+	for _, want := range []string{"ADDA - add arithmetic", "ADDA   r,adr[,x]   #20rx adr", "ADDA   r1,r2       #24 r1 r2", "Flags: OF SF ZF"} {
+		if !strings.Contains(out.String(), want) {
+			t.Errorf("Expected %q in\n%s", want, out.String())
+		}
+	}
+
+	out.Reset()
+	if status := runDocCommand([]string{"MOVE"}, &out); status != 1 {
+		t.Errorf("Expected an unknown instruction to fail")
+	}
+}
@@ -0,0 +1,130 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// editRegisters are the registers set accepts, by name.
+var editRegisters = map[string]int{
+	"PR": PC, "PC": PC, "FR": FR, "SP": SP,
+	"GR0": GR0, "GR1": GR1, "GR2": GR2, "GR3": GR3,
+	"GR4": GR4, "GR5": GR5, "GR6": GR6, "GR7": GR7,
+}
+
+// parseAddress reads an address, a label or LABEL+N / LABEL-N.
+func parseAddress(c *Comet2, s string) (int, bool) {
+	return lookupAddress(&Object{Symbols: c.Symbols}, s)
+}
+
+// lookupAddress reads an address, a label of obj or LABEL+N / LABEL-N.
+func lookupAddress(obj *Object, s string) (int, bool) {
+	if addr, ok := lookupSymbol(obj, s); ok {
+		return addr, true
+	}
+	i := strings.LastIndexAny(s, "+-")
+	if i <= 0 {
+		return 0, false
+	}
+	base, ok := lookupSymbol(obj, s[:i])
+	if !ok {
+		return 0, false
+	}
+	offset, ok := expandNumber(s[i+1:])
+	if !ok {
+		return 0, false
+	}
+	if s[i] == '-' {
+		offset = -offset
+	}
+	return (base + offset) & 0xffff, true
+}
+
+// editMemory writes val at addr for the monitor, recording the old value
+// in the undo entry started by beginEdit, if any.
+func editMemory(c *Comet2, log *undoLog, addr int, val int) {
+	if log != nil {
+		e := &log.entries[len(log.entries)-1]
+		e.writes = append(e.writes, memoryDelta{addr, memGet(c.Memory, addr)})
+	}
+	c.poke(addr, val)
+}
+
+// cmdSet changes a register (set GR1 #000A) or a word of memory
+// (set MEM[BUF+2] 65). The change can be undone.
+func cmdSet(c *Comet2, args []string) error {
+	if len(args) != 2 {
+		return fmt.Errorf("Usage: set REGISTER|MEM[ADDRESS] VALUE")
+	}
+	val, ok := expandNumber(args[1])
+	if !ok {
+		return fmt.Errorf("Invalid value \"%s\".", args[1])
+	}
+
+	target := strings.ToUpper(args[0])
+	log := findUndoLog(c)
+	if reg, ok := editRegisters[target]; ok {
+		if reg == FR && val > FR_OVER|FR_MINUS|FR_ZERO {
+			return fmt.Errorf("Invalid FR value \"%s\" (0-7).", args[1])
+		}
+		if log != nil {
+			log.beginEdit(c)
+		}
+		old := c.State[reg]
+		c.State[reg] = val
+		cometPrint(fmt.Sprintf("%s: %s -> %s", target, formatWord(old), formatWord(val)))
+		return nil
+	}
+
+	if !strings.HasPrefix(target, "MEM[") || !strings.HasSuffix(target, "]") {
+		return fmt.Errorf("Invalid target \"%s\" (GR0-GR7, SP, PR, FR or MEM[ADDRESS]).", args[0])
+	}
+	expr := args[0][len("MEM[") : len(args[0])-1]
+	addr, ok := parseAddress(c, expr)
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", expr)
+	}
+	if log != nil {
+		log.beginEdit(c)
+	}
+	old := memGet(c.Memory, addr)
+	editMemory(c, log, addr, val)
+	cometPrint(fmt.Sprintf("%s: %s -> %s", c.formatAddr(addr), formatWord(old), formatWord(val)))
+	return nil
+}
+
+// cmdFill sets every word from FROM to TO, inclusive, to VALUE. The change
+// can be undone as a whole.
+func cmdFill(c *Comet2, args []string) error {
+	if len(args) != 3 {
+		return fmt.Errorf("Usage: fill FROM TO VALUE")
+	}
+	from, ok := parseAddress(c, args[0])
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", args[0])
+	}
+	to, ok := parseAddress(c, args[1])
+	if !ok {
+		return fmt.Errorf("Invalid address \"%s\".", args[1])
+	}
+	val, ok := expandNumber(args[2])
+	if !ok {
+		return fmt.Errorf("Invalid value \"%s\".", args[2])
+	}
+	if from > to {
+		from, to = to, from
+	}
+	if to >= len(c.Memory) {
+		return fmt.Errorf("Address #%s is outside the memory.", hex(to, 4))
+	}
+
+	log := findUndoLog(c)
+	if log != nil {
+		log.beginEdit(c)
+	}
+	for addr := from; addr <= to; addr++ {
+		editMemory(c, log, addr, val)
+	}
+	cometPrint(fmt.Sprintf("Filled %s-%s (%d words) with %s.", c.formatAddr(from), c.formatAddr(to), to-from+1, formatWord(val)))
+	return nil
+}
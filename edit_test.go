@@ -0,0 +1,60 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestSetFill(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,5
+	ST	GR1,A
+	RET
+A	DS	3
+	END
+`)
+	c.AddObserver(newUndoLog(3))
+	if _, err := c.stepExec(); err != nil {
+		t.Fatalf("step: %v", err)
+	}
+
+	if err := executeCommand("set", []string{"GR1", "#000A"}, c); err != nil {
+		t.Fatalf("set failed: %v", err)
+	}
+	if err := executeCommand("set", []string{"MEM[A+1]", "65"}, c); err != nil {
+		t.Fatalf("set failed: %v", err)
+	}
+	if err := executeCommand("fill", []string{"A", "#0007", "-1"}, c); err != nil {
+		t.Fatalf("fill failed: %v", err)
+	}
+	if c.State[GR1] != 10 || c.Memory[5] != 0xffff || c.Memory[6] != 0xffff || c.Memory[7] != 0xffff {
+		t.Errorf("Expected GR1 = 10 and A filled, got GR1 %d A %v", c.State[GR1], c.Memory[5:8])
+	}
+	if c.Steps != 1 {
+		t.Errorf("Expected edits not to count as steps, got %d", c.Steps)
+	}
+
+	for _, args := range [][]string{
+		{"FR", "8"},
+		{"GR8", "1"},
+		{"MEM[B]", "1"},
+		{"GR1"},
+	} {
+		if err := executeCommand("set", args, c); err == nil {
+			t.Errorf("Expected set %v to fail", args)
+		}
+	}
+
+	if err := executeCommand("undo", nil, c); err != nil {
+		t.Fatalf("undo failed: %v", err)
+	}
+	if c.State[GR1] != 10 || c.Memory[5] != 0 || c.Memory[6] != 65 || c.Memory[7] != 0 {
+		t.Errorf("Expected the fill undone, got GR1 %d A %v", c.State[GR1], c.Memory[5:8])
+	}
+	if err := executeCommand("undo", []string{"3"}, c); err != nil {
+		t.Fatalf("undo failed: %v", err)
+	}
+	if c.State[GR1] != 0 || c.Memory[6] != 0 || c.State[PC] != 0 || c.Steps != 0 {
+		t.Errorf("Expected the first instruction undone, got GR1 %d PC #%s steps %d",
+			c.State[GR1], hex(c.State[PC], 4), c.Steps)
+	}
+}
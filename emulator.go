@@ -9,56 +9,11 @@ import (
 type Comet2Instruction struct {
 	ID   string
 	Type InstructionType
+	Exec execFunc
 }
 
-var COMET2TBL = map[int]Comet2Instruction{
-	0x00: {"NOP", OP4},
-	0x10: {"LD", OP1},
-	0x11: {"ST", OP1},
-	0x12: {"LAD", OP1},
-	0x14: {"LD", OP5},
-	0x20: {"ADDA", OP1},
-	0x21: {"SUBA", OP1},
-	0x22: {"ADDL", OP1},
-	0x23: {"SUBL", OP1},
-	0x24: {"ADDA", OP5},
-	0x25: {"SUBA", OP5},
-	0x26: {"ADDL", OP5},
-	0x27: {"SUBL", OP5},
-	0x28: {"MULA", OP1},
-	0x29: {"DIVA", OP1},
-	0x2a: {"MULL", OP1},
-	0x2b: {"DIVL", OP1},
-	0x2c: {"MULA", OP5},
-	0x2d: {"DIVA", OP5},
-	0x2e: {"MULL", OP5},
-	0x2f: {"DIVL", OP5},
-	0x30: {"AND", OP1},
-	0x31: {"OR", OP1},
-	0x32: {"XOR", OP1},
-	0x34: {"AND", OP5},
-	0x35: {"OR", OP5},
-	0x36: {"XOR", OP5},
-	0x40: {"CPA", OP1},
-	0x41: {"CPL", OP1},
-	0x44: {"CPA", OP5},
-	0x45: {"CPL", OP5},
-	0x50: {"SLA", OP1},
-	0x51: {"SRA", OP1},
-	0x52: {"SLL", OP1},
-	0x53: {"SRL", OP1},
-	0x61: {"JMI", OP2},
-	0x62: {"JNZ", OP2},
-	0x63: {"JZE", OP2},
-	0x64: {"JUMP", OP2},
-	0x65: {"JPL", OP2},
-	0x66: {"JOV", OP2},
-	0x70: {"PUSH", OP2},
-	0x71: {"POP", OP3},
-	0x80: {"CALL", OP2},
-	0x81: {"RET", OP4},
-	0xf0: {"SVC", OP2},
-}
+// COMET2TBL decodes opcodes
+var COMET2TBL = comet2Table()
 
 func parse(memory []uint16, state []int) (string, string, int) {
 	pc := state[PC]
@@ -75,237 +30,488 @@ func parse(memory []uint16, state []int) (string, string, int) {
 		instSym = comet2Inst.ID
 		instType := comet2Inst.Type
 
+		size = instructionSize(instType)
 		switch instType {
 		case OP1:
 			oprSym = fmt.Sprintf("GR%d,   #%s", gr, hex(adr, 4))
 			if xr > 0 {
 				oprSym += fmt.Sprintf(", GR%d", xr)
 			}
-			size = 2
 		case OP2:
 			oprSym = fmt.Sprintf("#%s", hex(adr, 4))
 			if xr > 0 {
 				oprSym += fmt.Sprintf(", GR%d", xr)
 			}
-			size = 2
 		case OP3:
 			oprSym = fmt.Sprintf("GR%d", gr)
-			size = 1
 		case OP4:
 			oprSym = ""
-			size = 1
 		case OP5:
 			oprSym = fmt.Sprintf("GR%d, GR%d", gr, xr)
-			size = 1
 		}
 	}
 
 	return instSym, oprSym, size
 }
 
-func execIn(memory []uint16, state []int, text string) {
+func (c *Comet2) execIn(text string) {
 	text = strings.TrimSpace(text)
-	if len(text) > 256 {
-		text = text[:256]
+	dropped := 0
+	if len(text) > IN_MAX_LENGTH {
+		dropped = len(text) - IN_MAX_LENGTH
+		text = text[:IN_MAX_LENGTH]
 	}
 
-	lenp := state[GR2]
-	bufp := state[GR1]
+	c.record(IO_IN, text)
+	if dropped > 0 {
+		c.Transcript[len(c.Transcript)-1].Truncated = dropped
+		pc := c.State[PC]
+		switch c.InOverflow {
+		case "warn":
+			c.warn(fmt.Sprintf("IN at %s: line of %d characters truncated to %d%s",
+				c.formatAddr(pc), IN_MAX_LENGTH+dropped, IN_MAX_LENGTH, c.locate(pc)))
+		case "error":
+			c.Halt(runtimeError(RUNTIME_RANGE_OVER, pc, "Range over at %s: IN line of %d characters exceeds %d%s",
+				c.formatAddr(pc), IN_MAX_LENGTH+dropped, IN_MAX_LENGTH, c.locate(pc)))
+		}
+	}
+
+	lenp := c.State[GR2]
+	bufp := c.State[GR1]
+	if c.CheckIO != "" && c.reportIO(c.State[PC], c.ioBufferProblem("IN", bufp, lenp, len(text))) {
+		return
+	}
 
-	memPut(memory, lenp, len(text))
+	c.store(lenp, len(text))
 	for i, ch := range text {
-		memPut(memory, bufp+i, int(ch))
+		c.store(bufp+i, int(ch))
 	}
 
-	state[PC] += 2
+	c.State[PC] += 2
 }
 
-func execOut(memory []uint16, state []int) {
-	lenp := state[GR2]
-	bufp := state[GR1]
-	length := memGet(memory, lenp)
+func (c *Comet2) execOut() {
+	lenp := c.State[GR2]
+	bufp := c.State[GR1]
+	length := c.load(lenp)
+	var problem string
+	if n := signed(length); n < 0 || n > IN_MAX_LENGTH {
+		problem = fmt.Sprintf("OUT length %d in GR2 = %s is outside 0..%d", n, c.formatAddr(lenp), IN_MAX_LENGTH)
+	} else if c.CheckIO != "" {
+		problem = c.ioBufferProblem("OUT", bufp, lenp, length)
+	}
+	if c.reportIO(c.State[PC], problem) {
+		return
+	}
 
 	var outstr strings.Builder
+	wide := 0
 	for i := 0; i < length; i++ {
-		outstr.WriteByte(byte(memGet(memory, bufp+i) & 0xff))
+		ch := c.load(bufp + i)
+		if ch == 0 && c.OutStopNul {
+			break
+		}
+		if !c.OutEscape {
+			outstr.WriteByte(byte(ch & 0xff))
+			continue
+		}
+		switch {
+		case ch > 0xff:
+			wide++
+			fmt.Fprintf(&outstr, "\\u%04x", ch)
+		case ch == '\\':
+			outstr.WriteString(`\\`)
+		case ch < 0x20 || ch > 0x7e:
+			fmt.Fprintf(&outstr, "\\x%02x", ch)
+		default:
+			outstr.WriteByte(byte(ch))
+		}
+	}
+	if wide > 0 {
+		c.warn(fmt.Sprintf("OUT at %s: %d characters above #FF%s", c.formatAddr(c.State[PC]), wide, c.locate(c.State[PC])))
 	}
 
-	cometOut(outstr.String())
+	text := applyOutputFilters(c.OutputFilters, outstr.String())
+	if c.outputExceeded(text) {
+		c.OutputTruncated = true
+		c.Halt(runtimeError(RUNTIME_OUTPUT_LIMIT, c.State[PC], "Output limit exceeded at %s: %d bytes in %d lines written%s",
+			c.formatAddr(c.State[PC]), c.outputBytes, c.outputLines, c.locate(c.State[PC])))
+		return
+	}
+	c.outputBytes += len(text)
+	c.outputLines++
+
+	c.record(IO_OUT, text)
+	if c.Output != nil {
+		c.Output(text)
+	}
 }
 
-func stepExec(memory []uint16, state []int) (bool, error) {
-	inst, opr, _ := parse(memory, state)
+// outputExceeded reports whether writing text would go over an output limit.
+func (c *Comet2) outputExceeded(text string) bool {
+	if c.MaxOutputBytes > 0 && c.outputBytes+len(text) > c.MaxOutputBytes {
+		return true
+	}
+	return c.MaxOutputLines > 0 && c.outputLines+1 > c.MaxOutputLines
+}
 
-	pc := state[PC]
-	fr := state[FR]
-	sp := state[SP]
-	regs := state[GR0 : GR7+1]
+// execState is what the handler of an instruction works on: the
+// registers of the step, its decoded operands and whether the machine
+// stops for input. stepExec writes it back once the handler returns.
+type execState struct {
+	pc      int
+	startPC int
+	fr      int
+	sp      int
+	regs    []int
+	gr      int
+	xr      int
+	eadr    int
+	grForm  bool
+	stop    bool
+}
 
-	instVal := memGet(memory, pc)
-	gr := (instVal >> 4) & 0xf
-	xr := instVal & 0xf
-	adr := memGet(memory, pc+1)
-	eadr := adr
+// execFunc executes one instruction. machineInstructions gives the
+// handler of every instruction; an error halts the machine.
+type execFunc func(c *Comet2, x *execState) error
 
-	var val int
-	stopFlag := false
+func (c *Comet2) stepExec() (bool, error) {
+	state := c.State
+	d := c.decode()
+	inst, opr := d.inst, d.opr
 
-	if xr >= 1 && xr <= 7 {
-		eadr += regs[xr]
+	// A halt requested between instructions, such as by IN, stops the
+	// machine before it goes on
+	if err := c.takeHalt(); err != nil {
+		return false, err
 	}
-	eadr &= 0xffff
 
-	// Check if operand is GR,GR form
-	grIsGrForm := isGRGRForm(opr)
+	pc := state[PC]
+	startPC := pc
+	c.LastFlagChange = nil
+	for _, o := range c.observers {
+		o.BeforeStep(c, startPC)
+	}
+	// An observer that stops the machine before the instruction, such as
+	// -smc error, keeps it from running
+	if err := c.takeHalt(); err != nil {
+		return false, err
+	}
 
-	switch inst {
-	case "LD":
-		if !grIsGrForm {
-			regs[gr] = memGet(memory, eadr)
-			fr = getFlag(regs[gr])
-			pc += 2
-		} else {
-			regs[gr] = regs[xr]
-			fr = getFlag(regs[gr])
-			pc++
+	x := &execState{
+		pc:      pc,
+		startPC: startPC,
+		fr:      state[FR],
+		sp:      state[SP],
+		regs:    state[GR0 : GR7+1],
+		gr:      d.gr,
+		xr:      d.xr,
+		grForm:  d.grForm,
+	}
+
+	eadr := d.adr
+	if x.xr >= 1 && x.xr <= 7 {
+		eadr += x.regs[x.xr]
+	}
+	if c.TrapRange && !d.grForm && d.memAddr && (eadr > 0xffff || eadr >= len(c.Memory)) {
+		return false, runtimeError(RUNTIME_RANGE_OVER, pc, "Range over at %s: %s %s reaches #%s (SVC %d)%s",
+			c.formatAddr(pc), inst, opr, hex(eadr, 4), EXIT_ROV, c.locate(pc))
+	}
+	x.eadr = eadr & 0xffff
+
+	if d.exec == nil {
+		return false, runtimeError(RUNTIME_ILLEGAL_INSTRUCTION, pc, "Illegal instruction %s at %s%s", inst, c.formatAddr(pc), c.locate(pc))
+	}
+	if err := d.exec(c, x); err != nil {
+		return false, err
+	}
+	fr, regs := x.fr, x.regs
+
+	if fr != state[FR] {
+		change := newFlagChange(startPC, inst, state[FR], fr)
+		c.LastFlagChange = &change
+		for _, o := range c.observers {
+			o.FlagChange(c, change)
 		}
+	}
 
-	case "ST":
-		memPut(memory, eadr, regs[gr])
-		pc += 2
+	// Update state
+	state[PC] = x.pc
+	state[FR] = fr
+	state[SP] = x.sp
+	for i := 0; i < 8; i++ {
+		state[GR0+i] = regs[i]
+	}
 
-	case "LAD":
-		regs[gr] = eadr
-		pc += 2
+	c.Steps++
+	for _, dev := range c.devices {
+		dev.Tick(c)
+	}
+	for _, o := range c.observers {
+		o.AfterStep(c, startPC)
+	}
 
-	case "ADDA":
-		if !grIsGrForm {
-			regs[gr] = signed(regs[gr])
-			regs[gr] += memGet(memory, eadr)
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > MAX_SIGNED {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < MIN_SIGNED {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
-		} else {
-			regs[gr] = signed(regs[gr])
-			regs[xr] = signed(regs[xr])
-			regs[gr] += regs[xr]
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > MAX_SIGNED {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < MIN_SIGNED {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			regs[xr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
+	if err := c.takeHalt(); err != nil {
+		return false, err
+	}
+
+	if c.TrapOverflow && fr&FR_OVER != 0 {
+		return false, runtimeError(RUNTIME_OVERFLOW_TRAP, startPC, "Overflow trap at %s: %s %s%s", c.formatAddr(startPC), inst, opr, c.locate(startPC))
+	}
+
+	return x.stop, nil
+}
+
+// The handlers of the instructions, as machineInstructions lists them
+
+func execLD(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] = c.load(x.eadr)
+		x.fr = getFlag(regs[gr])
+		x.pc += 2
+	} else {
+		regs[gr] = regs[xr]
+		x.fr = getFlag(regs[gr])
+		x.pc++
+	}
+	return nil
+}
+
+func execST(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	if region := c.protectedRegion(x.eadr); region != "" {
+		return runtimeError(RUNTIME_PROTECTED, x.pc, "Write to the %s at %s: ST into #%s%s",
+			region, c.formatAddr(x.pc), hex(x.eadr, 4), c.locate(x.pc))
+	}
+	c.store(x.eadr, regs[gr])
+	x.pc += 2
+	return nil
+}
+
+func execLAD(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	regs[gr] = x.eadr
+	x.pc += 2
+	return nil
+}
+
+func execADDA(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] = signed(regs[gr])
+		regs[gr] += c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
 		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] = signed(regs[gr])
+		regs[xr] = signed(regs[xr])
+		regs[gr] += regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		regs[xr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
 
-	case "SUBA":
-		if !grIsGrForm {
-			regs[gr] = signed(regs[gr])
-			regs[gr] -= memGet(memory, eadr)
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > MAX_SIGNED {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < MIN_SIGNED {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
-		} else {
-			regs[gr] = signed(regs[gr])
-			regs[xr] = signed(regs[xr])
-			regs[gr] -= regs[xr]
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > MAX_SIGNED {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < MIN_SIGNED {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			regs[xr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
+func execSUBA(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] = signed(regs[gr])
+		regs[gr] -= c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
 		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] = signed(regs[gr])
+		regs[xr] = signed(regs[xr])
+		regs[gr] -= regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		regs[xr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
 
-	case "ADDL":
-		if !grIsGrForm {
-			regs[gr] += memGet(memory, eadr)
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > 0xffff {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < 0 {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
-		} else {
-			regs[gr] += regs[xr]
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > 0xffff {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < 0 {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
+func execADDL(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] += c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
 		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] += regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
 
-	case "SUBL":
-		if !grIsGrForm {
-			regs[gr] -= memGet(memory, eadr)
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > 0xffff {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < 0 {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
-		} else {
-			regs[gr] -= regs[xr]
-			ofr1 := 0
-			ofr2 := 0
-			if regs[gr] > 0xffff {
-				ofr1 = FR_OVER
-			}
-			if regs[gr] < 0 {
-				ofr2 = FR_OVER
-			}
-			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
+func execSUBL(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] -= c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
 		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] -= regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
+
+func execMULA(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] = signed(regs[gr])
+		regs[gr] *= c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] = signed(regs[gr])
+		regs[xr] = signed(regs[xr])
+		regs[gr] *= regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > MAX_SIGNED {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < MIN_SIGNED {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		regs[xr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
+
+func execMULL(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] *= c.load(x.eadr)
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc += 2
+	} else {
+		regs[gr] *= regs[xr]
+		ofr1 := 0
+		ofr2 := 0
+		if regs[gr] > 0xffff {
+			ofr1 = FR_OVER
+		}
+		if regs[gr] < 0 {
+			ofr2 = FR_OVER
+		}
+		regs[gr] &= 0xffff
+		regs[xr] &= 0xffff
+		x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+		x.pc++
+	}
+	return nil
+}
 
-	case "MULA":
-		if !grIsGrForm {
-			regs[gr] = signed(regs[gr])
-			regs[gr] *= memGet(memory, eadr)
+func execDIVA(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] = signed(regs[gr])
+		m := c.load(x.eadr)
+		if m == 0 {
+			x.fr = FR_OVER | FR_ZERO
+			c.warn(fmt.Sprintf("Error: Division by zero in DIVA at %s%s.", c.formatAddr(x.pc), c.locate(x.pc)))
+			x.pc += 2
+		} else {
+			regs[gr] /= m
 			ofr1 := 0
 			ofr2 := 0
 			if regs[gr] > MAX_SIGNED {
@@ -315,12 +521,18 @@ func stepExec(memory []uint16, state []int) (bool, error) {
 				ofr2 = FR_OVER
 			}
 			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
+			x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+			x.pc += 2
+		}
+	} else {
+		regs[gr] = signed(regs[gr])
+		regs[xr] = signed(regs[xr])
+		if regs[xr] == 0 {
+			x.fr = FR_OVER | FR_ZERO
+			c.warn(fmt.Sprintf("Error: Division by zero in DIVA at %s%s.", c.formatAddr(x.pc), c.locate(x.pc)))
+			x.pc++
 		} else {
-			regs[gr] = signed(regs[gr])
-			regs[xr] = signed(regs[xr])
-			regs[gr] *= regs[xr]
+			regs[gr] /= regs[xr]
 			ofr1 := 0
 			ofr2 := 0
 			if regs[gr] > MAX_SIGNED {
@@ -331,13 +543,23 @@ func stepExec(memory []uint16, state []int) (bool, error) {
 			}
 			regs[gr] &= 0xffff
 			regs[xr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
+			x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+			x.pc++
 		}
+	}
+	return nil
+}
 
-	case "MULL":
-		if !grIsGrForm {
-			regs[gr] *= memGet(memory, eadr)
+func execDIVL(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		m := c.load(x.eadr)
+		if m == 0 {
+			x.fr = FR_OVER | FR_ZERO
+			c.warn(fmt.Sprintf("Error: Division by zero in DIVL at %s%s.", c.formatAddr(x.pc), c.locate(x.pc)))
+			x.pc += 2
+		} else {
+			regs[gr] /= m
 			ofr1 := 0
 			ofr2 := 0
 			if regs[gr] > 0xffff {
@@ -347,10 +569,16 @@ func stepExec(memory []uint16, state []int) (bool, error) {
 				ofr2 = FR_OVER
 			}
 			regs[gr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc += 2
+			x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+			x.pc += 2
+		}
+	} else {
+		if regs[xr] == 0 {
+			x.fr = FR_OVER | FR_ZERO
+			c.warn(fmt.Sprintf("Error: Division by zero in DIVL at %s%s.", c.formatAddr(x.pc), c.locate(x.pc)))
+			x.pc++
 		} else {
-			regs[gr] *= regs[xr]
+			regs[gr] /= regs[xr]
 			ofr1 := 0
 			ofr2 := 0
 			if regs[gr] > 0xffff {
@@ -361,321 +589,300 @@ func stepExec(memory []uint16, state []int) (bool, error) {
 			}
 			regs[gr] &= 0xffff
 			regs[xr] &= 0xffff
-			fr = getFlag(regs[gr]) | ofr1 | ofr2
-			pc++
-		}
-
-	case "DIVA":
-		if !grIsGrForm {
-			regs[gr] = signed(regs[gr])
-			m := memGet(memory, eadr)
-			if m == 0 {
-				fr = FR_OVER | FR_ZERO
-				fmt.Println(colorRedYellow("Error: Division by zero in DIVA."))
-				pc += 2
-			} else {
-				regs[gr] /= m
-				ofr1 := 0
-				ofr2 := 0
-				if regs[gr] > MAX_SIGNED {
-					ofr1 = FR_OVER
-				}
-				if regs[gr] < MIN_SIGNED {
-					ofr2 = FR_OVER
-				}
-				regs[gr] &= 0xffff
-				fr = getFlag(regs[gr]) | ofr1 | ofr2
-				pc += 2
-			}
-		} else {
-			regs[gr] = signed(regs[gr])
-			regs[xr] = signed(regs[xr])
-			if regs[xr] == 0 {
-				fr = FR_OVER | FR_ZERO
-				fmt.Println(colorRedYellow("Error: Division by zero in DIVA."))
-				pc++
-			} else {
-				regs[gr] /= regs[xr]
-				ofr1 := 0
-				ofr2 := 0
-				if regs[gr] > MAX_SIGNED {
-					ofr1 = FR_OVER
-				}
-				if regs[gr] < MIN_SIGNED {
-					ofr2 = FR_OVER
-				}
-				regs[gr] &= 0xffff
-				regs[xr] &= 0xffff
-				fr = getFlag(regs[gr]) | ofr1 | ofr2
-				pc++
-			}
+			x.fr = getFlag(regs[gr]) | ofr1 | ofr2
+			x.pc++
 		}
+	}
+	return nil
+}
 
-	case "DIVL":
-		if !grIsGrForm {
-			m := memGet(memory, eadr)
-			if m == 0 {
-				fr = FR_OVER | FR_ZERO
-				fmt.Println(colorRedYellow("Error: Division by zero in DIVL."))
-				pc += 2
-			} else {
-				regs[gr] /= m
-				ofr1 := 0
-				ofr2 := 0
-				if regs[gr] > 0xffff {
-					ofr1 = FR_OVER
-				}
-				if regs[gr] < 0 {
-					ofr2 = FR_OVER
-				}
-				regs[gr] &= 0xffff
-				fr = getFlag(regs[gr]) | ofr1 | ofr2
-				pc += 2
-			}
-		} else {
-			if regs[xr] == 0 {
-				fr = FR_OVER | FR_ZERO
-				fmt.Println(colorRedYellow("Error: Division by zero in DIVL."))
-				pc++
-			} else {
-				regs[gr] /= regs[xr]
-				ofr1 := 0
-				ofr2 := 0
-				if regs[gr] > 0xffff {
-					ofr1 = FR_OVER
-				}
-				if regs[gr] < 0 {
-					ofr2 = FR_OVER
-				}
-				regs[gr] &= 0xffff
-				regs[xr] &= 0xffff
-				fr = getFlag(regs[gr]) | ofr1 | ofr2
-				pc++
-			}
-		}
+func execAND(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] &= c.load(x.eadr)
+		x.fr = getFlag(regs[gr])
+		x.pc += 2
+	} else {
+		regs[gr] &= regs[xr]
+		x.fr = getFlag(regs[gr])
+		x.pc++
+	}
+	return nil
+}
 
-	case "AND":
-		if !grIsGrForm {
-			regs[gr] &= memGet(memory, eadr)
-			fr = getFlag(regs[gr])
-			pc += 2
-		} else {
-			regs[gr] &= regs[xr]
-			fr = getFlag(regs[gr])
-			pc++
-		}
+func execOR(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] |= c.load(x.eadr)
+		x.fr = getFlag(regs[gr])
+		x.pc += 2
+	} else {
+		regs[gr] |= regs[xr]
+		x.fr = getFlag(regs[gr])
+		x.pc++
+	}
+	return nil
+}
 
-	case "OR":
-		if !grIsGrForm {
-			regs[gr] |= memGet(memory, eadr)
-			fr = getFlag(regs[gr])
-			pc += 2
-		} else {
-			regs[gr] |= regs[xr]
-			fr = getFlag(regs[gr])
-			pc++
-		}
+func execXOR(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	if !x.grForm {
+		regs[gr] ^= c.load(x.eadr)
+		x.fr = getFlag(regs[gr])
+		x.pc += 2
+	} else {
+		regs[gr] ^= regs[xr]
+		x.fr = getFlag(regs[gr])
+		x.pc++
+	}
+	return nil
+}
 
-	case "XOR":
-		if !grIsGrForm {
-			regs[gr] ^= memGet(memory, eadr)
-			fr = getFlag(regs[gr])
-			pc += 2
-		} else {
-			regs[gr] ^= regs[xr]
-			fr = getFlag(regs[gr])
-			pc++
+func execCPA(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	var val int
+	if !x.grForm {
+		val = signed(regs[gr]) - signed(c.load(x.eadr))
+		if val > MAX_SIGNED {
+			val = MAX_SIGNED
 		}
-
-	case "CPA":
-		if !grIsGrForm {
-			val = signed(regs[gr]) - signed(memGet(memory, eadr))
-			if val > MAX_SIGNED {
-				val = MAX_SIGNED
-			}
-			if val < MIN_SIGNED {
-				val = MIN_SIGNED
-			}
-			fr = getFlag(unsigned(val))
-			pc += 2
-		} else {
-			val = signed(regs[gr]) - signed(regs[xr])
-			if val > MAX_SIGNED {
-				val = MAX_SIGNED
-			}
-			if val < MIN_SIGNED {
-				val = MIN_SIGNED
-			}
-			fr = getFlag(unsigned(val))
-			pc++
+		if val < MIN_SIGNED {
+			val = MIN_SIGNED
 		}
-
-	case "CPL":
-		if !grIsGrForm {
-			val = regs[gr] - memGet(memory, eadr)
-			if val > MAX_SIGNED {
-				val = MAX_SIGNED
-			}
-			if val < MIN_SIGNED {
-				val = MIN_SIGNED
-			}
-			fr = getFlag(unsigned(val))
-			pc += 2
-		} else {
-			val = regs[gr] - regs[xr]
-			if val > MAX_SIGNED {
-				val = MAX_SIGNED
-			}
-			if val < MIN_SIGNED {
-				val = MIN_SIGNED
-			}
-			fr = getFlag(unsigned(val))
-			pc++
+		x.fr = getFlag(unsigned(val))
+		x.pc += 2
+	} else {
+		val = signed(regs[gr]) - signed(regs[xr])
+		if val > MAX_SIGNED {
+			val = MAX_SIGNED
 		}
-
-	case "SLA":
-		val = regs[gr] & 0x8000
-		regs[gr] <<= eadr
-		ofr := regs[gr] & 0x8000
-		ofr >>= 13
-		regs[gr] |= val
-		regs[gr] &= 0xffff
-		fr = getFlag(regs[gr]) | ofr
-		pc += 2
-
-	case "SRA":
-		val = regs[gr]
-		ofr := regs[gr] & (0x0001 << (eadr - 1))
-		ofr <<= (2 - (eadr - 1))
-		if val&0x8000 != 0 {
-			val &= 0x7fff
-			val >>= eadr
-			val += ((0x7fff >> eadr) ^ 0xffff)
-		} else {
-			val >>= eadr
+		if val < MIN_SIGNED {
+			val = MIN_SIGNED
 		}
-		regs[gr] = val
-		fr = getFlag(regs[gr]) | ofr
-		pc += 2
+		x.fr = getFlag(unsigned(val))
+		x.pc++
+	}
+	return nil
+}
 
-	case "SLL":
-		regs[gr] <<= eadr
-		ofr := regs[gr] & 0x10000
-		ofr >>= 14
-		regs[gr] &= 0xffff
-		fr = getFlag(regs[gr]) | ofr
-		pc += 2
-
-	case "SRL":
-		ofr := regs[gr] & (0x0001 << (eadr - 1))
-		ofr <<= 2 - (eadr - 1)
-		regs[gr] >>= eadr
-		fr = getFlag(regs[gr]) | ofr
-		pc += 2
-
-	case "JMI":
-		if (fr & FR_MINUS) == FR_MINUS {
-			pc = eadr
-		} else {
-			pc += 2
+func execCPL(c *Comet2, x *execState) error {
+	regs, gr, xr := x.regs, x.gr, x.xr
+	var val int
+	if !x.grForm {
+		val = regs[gr] - c.load(x.eadr)
+		if val > MAX_SIGNED {
+			val = MAX_SIGNED
 		}
-
-	case "JNZ":
-		if (fr & FR_ZERO) != FR_ZERO {
-			pc = eadr
-		} else {
-			pc += 2
+		if val < MIN_SIGNED {
+			val = MIN_SIGNED
 		}
-
-	case "JZE":
-		if (fr & FR_ZERO) == FR_ZERO {
-			pc = eadr
-		} else {
-			pc += 2
+		x.fr = getFlag(unsigned(val))
+		x.pc += 2
+	} else {
+		val = regs[gr] - regs[xr]
+		if val > MAX_SIGNED {
+			val = MAX_SIGNED
 		}
+		if val < MIN_SIGNED {
+			val = MIN_SIGNED
+		}
+		x.fr = getFlag(unsigned(val))
+		x.pc++
+	}
+	return nil
+}
 
-	case "JUMP":
-		pc = eadr
+func execSLA(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	val := regs[gr] & 0x8000
+	regs[gr] <<= x.eadr
+	ofr := regs[gr] & 0x8000
+	ofr >>= 13
+	regs[gr] |= val
+	regs[gr] &= 0xffff
+	x.fr = getFlag(regs[gr]) | ofr
+	x.pc += 2
+	return nil
+}
 
-	case "JPL":
-		if ((fr & FR_MINUS) != FR_MINUS) && ((fr & FR_ZERO) != FR_ZERO) {
-			pc = eadr
-		} else {
-			pc += 2
-		}
+func execSRA(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	val := regs[gr]
+	ofr := regs[gr] & (0x0001 << (x.eadr - 1))
+	ofr <<= (2 - (x.eadr - 1))
+	if val&0x8000 != 0 {
+		val &= 0x7fff
+		val >>= x.eadr
+		val += ((0x7fff >> x.eadr) ^ 0xffff)
+	} else {
+		val >>= x.eadr
+	}
+	regs[gr] = val
+	x.fr = getFlag(regs[gr]) | ofr
+	x.pc += 2
+	return nil
+}
 
-	case "JOV":
-		if (fr & FR_OVER) != 0 {
-			pc = eadr
-		} else {
-			pc += 2
-		}
+func execSLL(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	regs[gr] <<= x.eadr
+	ofr := regs[gr] & 0x10000
+	ofr >>= 14
+	regs[gr] &= 0xffff
+	x.fr = getFlag(regs[gr]) | ofr
+	x.pc += 2
+	return nil
+}
 
-	case "PUSH":
-		sp--
-		if sp <= addressMax {
-			return false, fmt.Errorf("Stack overflow at #%s: SP = #%s", hex(pc, 4), hex(sp, 4))
-		}
-		memPut(memory, sp, eadr)
-		pc += 2
+func execSRL(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	ofr := regs[gr] & (0x0001 << (x.eadr - 1))
+	ofr <<= 2 - (x.eadr - 1)
+	regs[gr] >>= x.eadr
+	x.fr = getFlag(regs[gr]) | ofr
+	x.pc += 2
+	return nil
+}
 
-	case "POP":
-		regs[gr] = memGet(memory, sp)
-		sp++
-		if sp > STACK_TOP {
-			return false, fmt.Errorf("Stack underflow at #%s: SP = #%s", hex(pc, 4), hex(sp, 4))
-		}
-		pc++
+func execJMI(c *Comet2, x *execState) error {
+	if (x.fr & FR_MINUS) == FR_MINUS {
+		x.pc = x.eadr
+	} else {
+		x.pc += 2
+	}
+	return nil
+}
 
-	case "CALL":
-		sp--
-		if sp <= addressMax {
-			return false, fmt.Errorf("Stack overflow at #%s: SP = #%s", hex(pc, 4), hex(sp, 4))
-		}
-		memPut(memory, sp, pc+2)
-		pc = eadr
+func execJNZ(c *Comet2, x *execState) error {
+	if (x.fr & FR_ZERO) != FR_ZERO {
+		x.pc = x.eadr
+	} else {
+		x.pc += 2
+	}
+	return nil
+}
 
-	case "RET":
-		pc = memGet(memory, sp)
-		sp++
-		if sp > STACK_TOP {
-			return false, fmt.Errorf("Program finished (RET)")
-		}
+func execJZE(c *Comet2, x *execState) error {
+	if (x.fr & FR_ZERO) == FR_ZERO {
+		x.pc = x.eadr
+	} else {
+		x.pc += 2
+	}
+	return nil
+}
 
-	case "SVC":
-		switch eadr {
-		case SYS_IN:
-			inputMode = INPUT_MODE_IN
-			stopFlag = true
-		case SYS_OUT:
-			execOut(memory, state)
-			pc += 2
-		case EXIT_USR:
-			return false, fmt.Errorf("Program finished (SVC %d)", EXIT_USR)
-		case EXIT_OVF:
-			return false, fmt.Errorf("Program finished (SVC %d)", EXIT_OVF)
-		case EXIT_DVZ:
-			return false, fmt.Errorf("Program finished (SVC %d)", EXIT_DVZ)
-		case EXIT_ROV:
-			return false, fmt.Errorf("Program finished (SVC %d)", EXIT_ROV)
-		}
+func execJUMP(c *Comet2, x *execState) error {
+	x.pc = x.eadr
+	return nil
+}
+
+func execJPL(c *Comet2, x *execState) error {
+	if ((x.fr & FR_MINUS) != FR_MINUS) && ((x.fr & FR_ZERO) != FR_ZERO) {
+		x.pc = x.eadr
+	} else {
+		x.pc += 2
+	}
+	return nil
+}
 
-	case "NOP":
-		pc++
+func execJOV(c *Comet2, x *execState) error {
+	if (x.fr & FR_OVER) != 0 {
+		x.pc = x.eadr
+	} else {
+		x.pc += 2
+	}
+	return nil
+}
 
-	default:
-		return false, fmt.Errorf("Illegal instruction %s at #%s", inst, hex(pc, 4))
+func execPUSH(c *Comet2, x *execState) error {
+	x.sp--
+	if x.sp <= c.AddressMax {
+		return runtimeError(RUNTIME_STACK_OVERFLOW, x.pc, "Stack overflow at %s: SP = #%s%s", c.formatAddr(x.pc), hex(x.sp, 4), c.locate(x.pc))
 	}
+	if err := c.pushed(x.pc, x.sp); err != nil {
+		return err
+	}
+	c.store(x.sp, x.eadr)
+	x.pc += 2
+	return nil
+}
 
-	// Update state
-	state[PC] = pc
-	state[FR] = fr
-	state[SP] = sp
-	for i := 0; i < 8; i++ {
-		state[GR0+i] = regs[i]
+func execPOP(c *Comet2, x *execState) error {
+	regs, gr := x.regs, x.gr
+	regs[gr] = c.load(x.sp)
+	x.sp++
+	if x.sp > c.StackTop {
+		return runtimeError(RUNTIME_STACK_UNDERFLOW, x.pc, "Stack underflow at %s: SP = #%s%s", c.formatAddr(x.pc), hex(x.sp, 4), c.locate(x.pc))
 	}
+	c.poisonPopped(x.sp - 1)
+	x.pc++
+	return nil
+}
+
+func execCALL(c *Comet2, x *execState) error {
+	x.sp--
+	if x.sp <= c.AddressMax {
+		return runtimeError(RUNTIME_STACK_OVERFLOW, x.pc, "Stack overflow at %s: SP = #%s%s", c.formatAddr(x.pc), hex(x.sp, 4), c.locate(x.pc))
+	}
+	if err := c.pushed(x.pc, x.sp); err != nil {
+		return err
+	}
+	c.store(x.sp, x.pc+2)
+	x.pc = x.eadr
+	return nil
+}
+
+func execRET(c *Comet2, x *execState) error {
+	x.pc = c.load(x.sp)
+	x.sp++
+	if x.sp > c.StackTop {
+		return runtimeError(RUNTIME_FINISHED, x.startPC, "Program finished (RET)")
+	}
+	c.poisonPopped(x.sp - 1)
+	return nil
+}
+
+func execSVC(c *Comet2, x *execState) error {
+	for _, o := range c.observers {
+		o.Svc(c, x.eadr)
+	}
+	if handler, ok := c.SvcHandlers[x.eadr]; ok {
+		err := handler(c)
+		if c.blocks != nil {
+			// The handler may have written memory directly
+			c.blocks.reset()
+		}
+		if err != nil {
+			return err
+		}
+		x.fr = c.State[FR]
+		x.pc += 2
+		return nil
+	}
+	switch x.eadr {
+	case SYS_IN:
+		c.InputMode = INPUT_MODE_IN
+		x.stop = true
+	case SYS_OUT:
+		c.execOut()
+		x.pc += 2
+	case EXIT_USR, EXIT_OVF, EXIT_DVZ, EXIT_ROV:
+		rerr := runtimeError(RUNTIME_FINISHED, x.pc, "Program finished (SVC %d)", x.eadr)
+		rerr.Code = x.eadr
+		return rerr
+	}
+	return nil
+}
+
+func execNOP(c *Comet2, x *execState) error {
+	x.pc++
+	return nil
+}
 
-	return stopFlag, nil
+// addressesMemory reports whether the effective address of inst is used
+// as a memory or jump address rather than as a value.
+func addressesMemory(inst string) bool {
+	return instructionUses[inst]&USE_MEM_ADDR != 0
 }
 
 // isGRGRForm checks if the operand string is in GR,GR format without regex
@@ -0,0 +1,257 @@
+package main
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// assembleSource assembles src and returns a machine ready to run it.
+func assembleSource(t *testing.T, src string) *Comet2 {
+	t.Helper()
+
+	c := LoadObject(assembleObject(t, src))
+	c.Output = func(string) {}
+	return c
+}
+
+// assembleObject assembles src into an object.
+func assembleObject(t *testing.T, src string) *Object {
+	t.Helper()
+
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	if err := os.WriteFile(path, []byte(src), 0644); err != nil {
+		t.Fatalf("Failed to write source: %v", err)
+	}
+
+	obj, err := assemble(path, newAssemblerState())
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+
+	return obj
+}
+
+const echoProgram = `MAIN	START
+	IN	BUF,LEN
+	OUT	BUF,LEN
+	RET
+BUF	DS	16
+LEN	DS	1
+	END
+`
+
+func TestTranscriptOrdering(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+
+	if err := c.Run(); err != nil {
+		t.Fatalf("Unexpected error before IN: %v", err)
+	}
+	if c.InputMode != INPUT_MODE_IN {
+		t.Fatalf("Expected machine to wait for IN")
+	}
+	c.execIn("abc")
+	c.InputMode = INPUT_MODE_CMD
+
+	err := c.Run()
+	if err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+
+	if len(c.Transcript) != 2 {
+		t.Fatalf("Expected 2 transcript events, got %d", len(c.Transcript))
+	}
+	in, out := c.Transcript[0], c.Transcript[1]
+	if in.Kind != IO_IN || in.Text != "abc" {
+		t.Errorf("Unexpected first event: %+v", in)
+	}
+	if out.Kind != IO_OUT || out.Text != "abc" {
+		t.Errorf("Unexpected second event: %+v", out)
+	}
+	if out.Step <= in.Step {
+		t.Errorf("OUT step %d should come after IN step %d", out.Step, in.Step)
+	}
+}
+
+func TestInOverflow(t *testing.T) {
+	src := `MAIN	START
+	IN	BUF,LEN
+	OUT	BUF,LEN
+	RET
+BUF	DS	256
+LEN	DS	1
+	END
+`
+	long := strings.Repeat("x", 300)
+
+	for _, mode := range []string{"truncate", "warn", "error"} {
+		c := assembleSource(t, src)
+		if err := c.SetInOverflow(mode); err != nil {
+			t.Fatalf("SetInOverflow(%s): %v", mode, err)
+		}
+		var warnings []string
+		c.Warn = func(msg string) { warnings = append(warnings, msg) }
+		c.Input = NewInputLines([]string{long})
+
+		err := c.Run()
+		in := c.Transcript[0]
+		if in.Text != long[:IN_MAX_LENGTH] || in.Truncated != 300-IN_MAX_LENGTH {
+			t.Errorf("%s: unexpected IN event: %d characters, %d truncated", mode, len(in.Text), in.Truncated)
+		}
+
+		var rerr *RuntimeError
+		switch mode {
+		case "truncate":
+			if len(warnings) != 0 || len(c.Transcript) != 2 {
+				t.Errorf("truncate: expected a silent run, got %v", warnings)
+			}
+		case "warn":
+			if len(warnings) != 1 || !strings.Contains(warnings[0], "300 characters truncated to 256") {
+				t.Errorf("warn: unexpected warnings %v", warnings)
+			}
+		case "error":
+			if !errors.As(err, &rerr) || rerr.Kind != RUNTIME_RANGE_OVER {
+				t.Fatalf("error: expected Range-Over, got %v", err)
+			}
+			if len(c.Transcript) != 1 {
+				t.Errorf("error: OUT should not run after the halt")
+			}
+		}
+	}
+
+	c := assembleSource(t, src)
+	if err := c.SetInOverflow("ignore"); err == nil {
+		t.Errorf("Expected an error for an unknown mode")
+	}
+}
+
+func TestTrapOverflow(t *testing.T) {
+	src := `MAIN	START
+	LD	GR1,BIG
+	ADDA	GR1,ONE
+	LAD	GR2,1
+	RET
+BIG	DC	32767
+ONE	DC	1
+	END
+`
+	// Without the trap OF is only set and the program goes on
+	c := assembleSource(t, src)
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if c.State[GR1] != 0x8000 || c.State[GR2] != 1 || c.State[FR]&FR_OVER == 0 {
+		t.Errorf("Unexpected state: GR1 #%s, GR2 %d, FR %d", hex(c.State[GR1], 4), c.State[GR2], c.State[FR])
+	}
+
+	c = assembleSource(t, src)
+	if err := c.SetTraps("overflow"); err != nil {
+		t.Fatalf("Failed to set trap: %v", err)
+	}
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_OVERFLOW_TRAP {
+		t.Fatalf("Expected overflow trap, got %v", err)
+	}
+	if rerr.PC != 2 || !strings.Contains(rerr.Msg, "ADDA") || !strings.Contains(rerr.Msg, "line 3") {
+		t.Errorf("Expected the trap at ADDA on line 3, got PC #%s: %s", hex(rerr.PC, 4), rerr.Msg)
+	}
+	if c.State[GR2] != 0 {
+		t.Errorf("Expected the program to stop after ADDA, got GR2 %d", c.State[GR2])
+	}
+}
+
+func TestTrapRange(t *testing.T) {
+	src := `MAIN	START
+	LAD	GR2,3
+	LAD	GR3,-1,GR2
+	LD	GR1,#FFFF,GR2
+	RET
+	END
+`
+	// Without the trap the address wraps to #0002
+	c := assembleSource(t, src)
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if c.State[GR3] != 2 || c.State[GR1] != int(c.Memory[2]) {
+		t.Errorf("Unexpected registers: GR1 #%s, GR3 #%s", hex(c.State[GR1], 4), hex(c.State[GR3], 4))
+	}
+
+	c = assembleSource(t, src)
+	if err := c.SetTraps("range"); err != nil {
+		t.Fatalf("Failed to set trap: %v", err)
+	}
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_RANGE_OVER {
+		t.Fatalf("Expected range over, got %v", err)
+	}
+	if rerr.PC != 4 || c.State[GR3] != 2 {
+		t.Errorf("Expected LD at #0004 to trap after LAD wrapped, got PC #%s", hex(rerr.PC, 4))
+	}
+
+	// #FFFF itself is still a valid address
+	c = assembleSource(t, "MAIN\tSTART\n\tLD\tGR1,#FFFF\n\tRET\n\tEND\n")
+	c.SetTraps("range")
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+}
+
+func TestTrapProtect(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,1
+	ST	GR1,#FFF0
+	RET
+	END
+`)
+	if err := c.SetTraps("protect"); err != nil {
+		t.Fatalf("Failed to set trap: %v", err)
+	}
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_PROTECTED {
+		t.Fatalf("Expected protected write, got %v", err)
+	}
+	if c.Memory[SVC_VECTOR_BASE] != 0 {
+		t.Errorf("Protected word was written")
+	}
+
+	c = NewComet2Builder().WithObject(&Object{Bin: make([]uint16, 0xff00)}).Build()
+	if err := c.SetTraps("protect-stack"); err == nil {
+		t.Errorf("Expected program over the stack to be rejected")
+	}
+}
+
+func TestJumpChecks(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,5
+SKIP	ST	GR1,A
+	RET
+A	DS	1
+	END
+`)
+	c.AddObserver(newUndoLog(3))
+
+	if err := executeCommand("jump", []string{"SKIP"}, c); err != nil || c.State[PC] != 2 {
+		t.Errorf("Expected jump to SKIP (#0002), got PC #%s (%v)", hex(c.State[PC], 4), err)
+	}
+	if err := executeCommand("j", []string{"MAIN+4"}, c); err != nil || c.State[PC] != 4 {
+		t.Errorf("Expected jump to RET (#0004), got PC #%s (%v)", hex(c.State[PC], 4), err)
+	}
+	for _, target := range []string{"#0001", "SKIP+1", "A", "#0100", "NOWHERE"} {
+		if err := executeCommand("jump", []string{target}, c); err == nil {
+			t.Errorf("Expected jump to %s to be refused", target)
+		}
+	}
+	if c.State[PC] != 4 {
+		t.Errorf("Expected a refused jump to leave PC alone, got #%s", hex(c.State[PC], 4))
+	}
+
+	if err := executeCommand("jump", []string{"A", "force"}, c); err != nil || c.State[PC] != 5 {
+		t.Errorf("Expected a forced jump to A (#0005), got PC #%s (%v)", hex(c.State[PC], 4), err)
+	}
+	if err := executeCommand("undo", []string{"3"}, c); err != nil || c.State[PC] != 0 {
+		t.Errorf("Expected undo to revert the jumps, got PC #%s (%v)", hex(c.State[PC], 4), err)
+	}
+}
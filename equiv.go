@@ -0,0 +1,169 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"slices"
+	"strings"
+)
+
+// equivWord is a word of an object as equiv compares it.
+type equivWord struct {
+	addr int
+	// key is the word with every address replaced by what it points at:
+	// "@N" for the Nth word of the program outside the literal pools,
+	// "=..." for a literal by its value, "#XXXX" for an address outside
+	// the program and the bare hexadecimal value for any other word.
+	key string
+}
+
+// equivProgram is an object reduced for comparing: its words outside the
+// literal pools in order, and its start address as a key.
+type equivProgram struct {
+	obj   *Object
+	words []equivWord
+	start string
+}
+
+// normalizeObject reduces obj so that objects of the same program compare
+// equal whatever the addresses of its labels and the order of its
+// literals. Which words are addresses is known from obj.Relocs.
+func normalizeObject(obj *Object) *equivProgram {
+	reloc := make(map[int]bool)
+	for _, addr := range obj.Relocs {
+		reloc[addr] = true
+	}
+	literalAt := make(map[int]Region)
+	inPool := make(map[int]bool)
+	for _, r := range obj.Literals {
+		literalAt[r.Start] = r
+		for addr := r.Start; addr < r.End; addr++ {
+			inPool[addr] = true
+		}
+	}
+
+	p := &equivProgram{obj: obj}
+	ordinal := make(map[int]int)
+	for addr := range obj.Bin {
+		if !inPool[addr] {
+			ordinal[addr] = len(p.words)
+			p.words = append(p.words, equivWord{addr: addr})
+		}
+	}
+	// A label on END points just past the program
+	ordinal[len(obj.Bin)] = len(p.words)
+
+	var target func(addr int) string
+	target = func(addr int) string {
+		if r, ok := literalAt[addr]; ok {
+			var words []string
+			for a := r.Start; a < min(r.End, len(obj.Bin)); a++ {
+				if reloc[a] && !inPool[int(obj.Bin[a])] {
+					words = append(words, target(int(obj.Bin[a])))
+				} else {
+					words = append(words, hex(int(obj.Bin[a]), 4))
+				}
+			}
+			return "=" + strings.Join(words, ",")
+		}
+		if n, ok := ordinal[addr]; ok {
+			return fmt.Sprintf("@%d", n)
+		}
+		return "#" + hex(addr, 4)
+	}
+	for i, w := range p.words {
+		if reloc[w.addr] {
+			p.words[i].key = target(int(obj.Bin[w.addr]))
+		} else {
+			p.words[i].key = hex(int(obj.Bin[w.addr]), 4)
+		}
+	}
+	p.start = target(obj.Start)
+	return p
+}
+
+// describeWord renders the word at addr of obj for a difference: its
+// address with the nearest label and source line, and its value.
+func describeWord(obj *Object, addr int) string {
+	s := NewSymbolizer(obj.Symbols, len(obj.Bin))
+	text := s.Format(addr)
+	if addr < len(obj.SourceMap) && obj.SourceMap[addr].Line > 0 {
+		text += fmt.Sprintf(" (%s:%d)", obj.SourceMap[addr].File, obj.SourceMap[addr].Line)
+	}
+	if slices.Contains(obj.Relocs, addr) {
+		return text + ": address " + s.Format(int(obj.Bin[addr]))
+	}
+	return text + ": #" + hex(int(obj.Bin[addr]), 4)
+}
+
+// compareObjects returns the differences between the programs of a and b
+// after normalizeObject, at most limit of them, and the total number.
+func compareObjects(pathA string, pathB string, a, b *equivProgram, limit int) ([]string, int) {
+	var diffs []string
+	total := 0
+	add := func(text string) {
+		total++
+		if total <= limit {
+			diffs = append(diffs, text)
+		}
+	}
+	if a.start != b.start {
+		add(fmt.Sprintf("start: %s #%s, %s #%s", pathA, hex(a.obj.Start, 4), pathB, hex(b.obj.Start, 4)))
+	}
+	for i := 0; i < min(len(a.words), len(b.words)); i++ {
+		if a.words[i].key != b.words[i].key {
+			add(fmt.Sprintf("%s %s\n%s %s", pathA, describeWord(a.obj, a.words[i].addr), pathB, describeWord(b.obj, b.words[i].addr)))
+		}
+	}
+	if len(a.words) != len(b.words) {
+		longer, path, n := a, pathA, len(b.words)
+		if len(b.words) > len(a.words) {
+			longer, path, n = b, pathB, len(a.words)
+		}
+		add(fmt.Sprintf("%s has %d more words from #%s", path, len(longer.words)-n, hex(longer.words[n].addr, 4)))
+	}
+	return diffs, total
+}
+
+// runEquivCommand implements "c2c2 equiv A.c2o B.c2o": whether two objects
+// hold the same program apart from the addresses of labels and the order
+// of literals, as after reformatting or reordering a source. It returns
+// the exit status: 0 when they are equivalent, 1 when not.
+func runEquivCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("equiv", flag.ContinueOnError)
+	fs.SetOutput(w)
+	limit := fs.Int("max", 10, "number of differences shown")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 equiv [-max N] A%s B%s\n\nOptions:\n", OBJECT_EXT, OBJECT_EXT)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 2 {
+		fs.Usage()
+		return 2
+	}
+
+	var programs [2]*equivProgram
+	for i, path := range fs.Args() {
+		obj, err := loadObjectFile(path)
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		programs[i] = normalizeObject(obj)
+	}
+	a, b := programs[0], programs[1]
+	diffs, total := compareObjects(fs.Arg(0), fs.Arg(1), a, b, max(*limit, 0))
+	if total == 0 {
+		fmt.Fprintf(w, "Equivalent: %d words and %d literals\n", len(a.words), len(a.obj.Literals))
+		return 0
+	}
+	for _, diff := range diffs {
+		fmt.Fprintln(w, diff)
+	}
+	fmt.Fprintf(w, "Not equivalent: %d differences\n", total)
+	return 1
+}
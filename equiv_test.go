@@ -0,0 +1,46 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestEquiv(t *testing.T) {
+	a := assembleObject(t, `MAIN	START
+	LD	GR1,=1
+	ADDA	GR1,=2
+	ST	GR1,BUF
+	RET
+BUF	DS	1
+	END
+`)
+	// The literal pool moves before BUF, which moves with it
+	b := assembleObject(t, `MAIN	START
+	LD	GR1,=1
+	ADDA	GR1,=2
+	ST	GR1,BUF
+	RET
+	LTORG
+BUF	DS	1
+	END
+`)
+	if a.Symbols["MAIN:BUF"] == b.Symbols["MAIN:BUF"] {
+		t.Fatalf("Expected BUF to move, got #%s in both", hex(a.Symbols["MAIN:BUF"], 4))
+	}
+	if _, total := compareObjects("a.c2o", "b.c2o", normalizeObject(a), normalizeObject(b), 10); total != 0 {
+		t.Errorf("Expected the objects to be equivalent, got %d differences", total)
+	}
+
+	c := assembleObject(t, `MAIN	START
+	LD	GR1,=1
+	ADDA	GR1,=3
+	ST	GR1,BUF
+	RET
+BUF	DS	1
+	END
+`)
+	diffs, total := compareObjects("a.c2o", "c.c2o", normalizeObject(a), normalizeObject(c), 10)
+	if total != 1 || !strings.HasPrefix(diffs[0], "a.c2o #0003") {
+		t.Errorf("Expected one difference at #0003, got %d: %q", total, diffs)
+	}
+}
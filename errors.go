@@ -0,0 +1,177 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+)
+
+// ParseError is returned by ParseLine for a line that cannot be parsed.
+type ParseError struct {
+	Line   int
+	Column int
+	Msg    string
+}
+
+func (e *ParseError) Error() string {
+	return fmt.Sprintf("Line %d: %s", e.Line, e.Msg)
+}
+
+// AssembleError is returned by the assembler. File and Line point at the
+// offending source line. Code is the diagnostic code of operand errors,
+// such as DIAG_OPERAND_COUNT, and also ends Msg.
+type AssembleError struct {
+	File string
+	Line int
+	Msg  string
+	Code string
+}
+
+func (e *AssembleError) Error() string {
+	return fmt.Sprintf("%sLine %d: %s%s", "\x1b[31;43m", e.Line, e.Msg, "\x1b[0m")
+}
+
+// RuntimeErrorKind classifies why the emulator stopped.
+type RuntimeErrorKind int
+
+const (
+	RUNTIME_FINISHED RuntimeErrorKind = iota
+	RUNTIME_STACK_OVERFLOW
+	RUNTIME_STACK_UNDERFLOW
+	RUNTIME_ILLEGAL_INSTRUCTION
+	RUNTIME_OVERFLOW_TRAP
+	RUNTIME_INFINITE_LOOP
+	RUNTIME_MODIFIED_INSTRUCTION
+	RUNTIME_OUTPUT_LIMIT
+	RUNTIME_CALL_MISMATCH
+	RUNTIME_RANGE_OVER
+	RUNTIME_PROTECTED
+)
+
+// RuntimeError is returned by Run and stepExec. PC is the address of the
+// instruction involved. Code is the SVC number when the program ended
+// with SVC.
+type RuntimeError struct {
+	Kind RuntimeErrorKind
+	PC   int
+	Code int
+	Msg  string
+}
+
+func (e *RuntimeError) Error() string {
+	return e.Msg
+}
+
+// Halts reports whether the error ends the program rather than just
+// rejecting the current instruction.
+func (e *RuntimeError) Halts() bool {
+	return e.Kind != RUNTIME_ILLEGAL_INSTRUCTION
+}
+
+// runtimeError creates a RuntimeError for the instruction at pc.
+func runtimeError(kind RuntimeErrorKind, pc int, format string, args ...interface{}) *RuntimeError {
+	return &RuntimeError{Kind: kind, PC: pc, Msg: fmt.Sprintf(format, args...)}
+}
+
+// HaltKind classifies why Continue or Step returned.
+type HaltKind int
+
+const (
+	HALT_RUNNING HaltKind = iota
+	HALT_NORMAL_EXIT
+	HALT_SVC_ERROR
+	HALT_STEP_LIMIT
+	HALT_CANCELLED
+	HALT_RUNTIME_ERROR
+	HALT_BREAKPOINT
+	HALT_WAITING_INPUT
+	HALT_TIME_LIMIT
+	HALT_BUDGET
+)
+
+var haltKindNames = []string{
+	"running", "normal exit", "SVC error", "step limit", "cancelled",
+	"runtime error", "breakpoint", "waiting for input", "time limit", "budget used",
+}
+
+func (k HaltKind) String() string {
+	if int(k) < 0 || int(k) >= len(haltKindNames) {
+		return fmt.Sprintf("HaltKind(%d)", int(k))
+	}
+	return haltKindNames[k]
+}
+
+// HaltReason tells a frontend why the machine stopped without matching
+// error messages. HALT_RUNNING is a step that completed normally, and
+// HALT_BUDGET a RunFor that executed all its steps.
+type HaltReason struct {
+	Kind HaltKind
+	// PC is the address of the instruction involved, or the PR the
+	// machine stopped at.
+	PC int
+	// SvcCode is the SVC number of HALT_SVC_ERROR (EXIT_OVF, EXIT_DVZ or
+	// EXIT_ROV).
+	SvcCode int
+	// RuntimeKind classifies HALT_RUNTIME_ERROR when it comes from a
+	// RuntimeError.
+	RuntimeKind RuntimeErrorKind
+	// Breakpoint is the ID in Comet2.Breakpoints of HALT_BREAKPOINT.
+	Breakpoint int
+	// Input is the IN that HALT_WAITING_INPUT waits for.
+	Input *InputRequest
+	// Err is the error behind the halt, as returned by Run.
+	Err error
+}
+
+// InputRequest describes an IN waiting for a line, so that a frontend can
+// show an input box and resume with Comet2.SupplyInput.
+type InputRequest struct {
+	// PC is the address of the IN's SVC.
+	PC int
+	// Buffer and LengthAt are the addresses the line and its length are
+	// stored at.
+	Buffer   int
+	LengthAt int
+	// MaxLength is the number of characters stored; InOverflow decides
+	// what happens to the rest of a longer line.
+	MaxLength int
+}
+
+// Stopped reports whether the program cannot go on as it is. Waiting for
+// input stops it only when the machine fails on input waits.
+func (r HaltReason) Stopped() bool {
+	switch r.Kind {
+	case HALT_RUNNING, HALT_BUDGET, HALT_BREAKPOINT, HALT_STEP_LIMIT, HALT_TIME_LIMIT, HALT_CANCELLED:
+		return false
+	case HALT_WAITING_INPUT:
+		return r.Err != nil
+	}
+	return true
+}
+
+// classifyHalt turns an error from the emulator into a HaltReason. pc is
+// used when the error does not name an instruction.
+func classifyHalt(err error, pc int) HaltReason {
+	r := HaltReason{Kind: HALT_RUNTIME_ERROR, PC: pc, Err: err}
+	var rerr *RuntimeError
+	switch {
+	case errors.Is(err, ErrCancelled):
+		r.Kind = HALT_CANCELLED
+	case errors.Is(err, ErrStepLimit):
+		r.Kind = HALT_STEP_LIMIT
+	case errors.Is(err, ErrTimeLimit):
+		r.Kind = HALT_TIME_LIMIT
+	case errors.As(err, &rerr):
+		r.PC = rerr.PC
+		r.RuntimeKind = rerr.Kind
+		if rerr.Kind == RUNTIME_FINISHED {
+			switch rerr.Code {
+			case EXIT_OVF, EXIT_DVZ, EXIT_ROV:
+				r.Kind = HALT_SVC_ERROR
+				r.SvcCode = rerr.Code
+			default:
+				r.Kind = HALT_NORMAL_EXIT
+			}
+		}
+	}
+	return r
+}
@@ -0,0 +1,129 @@
+package main
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestRuntimeErrorKind(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	POP	GR1
+	END
+`)
+
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_STACK_UNDERFLOW {
+		t.Fatalf("Expected stack underflow, got %v", err)
+	}
+	if rerr.PC != 0 {
+		t.Errorf("Expected PC #0000, got #%s", hex(rerr.PC, 4))
+	}
+}
+
+func TestAssembleErrorLine(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "prog.cas")
+	src := "MAIN\tSTART\n\tLD\tGR1,X\nX\tDC\t1\nX\tDC\t2\n\tEND\n"
+	if err := os.WriteFile(path, []byte(src), 0644); err != nil {
+		t.Fatalf("Failed to write source: %v", err)
+	}
+
+	_, err := assemble(path, newAssemblerState())
+	var aerr *AssembleError
+	if !errors.As(err, &aerr) {
+		t.Fatalf("Expected AssembleError, got %v", err)
+	}
+	if aerr.Line != 4 {
+		t.Errorf("Expected error on line 4, got %d", aerr.Line)
+	}
+}
+
+func TestHaltReasons(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,1
+LOOP	LAD	GR1,1,GR1
+	CPA	GR1,=3
+	JNZ	LOOP
+	RET
+	END
+`)
+	c.Breakpoints.Add(8, false)
+	if r := c.Continue(); r.Kind != HALT_BREAKPOINT || r.PC != 8 || r.Breakpoint != 1 || r.Stopped() {
+		t.Errorf("Expected to stop at the breakpoint, got %+v", r)
+	}
+	if r := c.Step(); r.Kind != HALT_NORMAL_EXIT || r.Err == nil || !r.Stopped() {
+		t.Errorf("Expected a normal exit, got %+v", r)
+	}
+
+	c = assembleSource(t, "MAIN\tSTART\n\tSVC\t2\n\tEND\n")
+	if r := c.Continue(); r.Kind != HALT_SVC_ERROR || r.SvcCode != EXIT_DVZ {
+		t.Errorf("Expected an SVC error, got %+v", r)
+	}
+
+	c = assembleSource(t, "MAIN\tSTART\n\tDC\t#FF00\n\tEND\n")
+	if r := c.Continue(); r.Kind != HALT_RUNTIME_ERROR || r.RuntimeKind != RUNTIME_ILLEGAL_INSTRUCTION {
+		t.Errorf("Expected an illegal instruction, got %+v", r)
+	}
+
+	c = assembleSource(t, echoProgram)
+	if r := c.Continue(); r.Kind != HALT_WAITING_INPUT {
+		t.Errorf("Expected to wait for input, got %+v", r)
+	}
+	c.Input = NewInputLines([]string{"abc"})
+	c.MaxSteps = c.Steps + 1
+	if r := c.Continue(); r.Kind != HALT_STEP_LIMIT || r.Err != ErrStepLimit {
+		t.Errorf("Expected the step limit, got %+v", r)
+	}
+
+	// A frontend asks for the line when the program needs it
+	c = assembleSource(t, echoProgram)
+	c.Output = func(string) {}
+	r := c.Continue()
+	want := InputRequest{PC: 8, Buffer: c.Symbols["MAIN:BUF"], LengthAt: c.Symbols["MAIN:LEN"], MaxLength: IN_MAX_LENGTH}
+	if r.Kind != HALT_WAITING_INPUT || r.Input == nil || *r.Input != want || r.Stopped() {
+		t.Fatalf("Expected to wait for input %+v, got %+v", want, r)
+	}
+	if err := c.SupplyInput("hi"); err != nil || c.PendingInput() != nil {
+		t.Fatalf("Expected the IN to complete, got %v", err)
+	}
+	if r := c.Continue(); r.Kind != HALT_NORMAL_EXIT || strings.TrimSuffix(c.Transcript[1].Text, "\n") != "hi" {
+		t.Errorf("Expected the line to be echoed, got %+v %+v", r, c.Transcript)
+	}
+	if err := c.SupplyInput("again"); err == nil {
+		t.Errorf("Expected an error without a pending IN")
+	}
+
+	// A frontend without threads runs the program in slices
+	c = assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,3\nLOOP\tSUBA\tGR1,=1\n\tJNZ\tLOOP\n\tRET\n\tEND\n")
+	if r := c.RunFor(3); r.Kind != HALT_BUDGET || r.Stopped() || c.Steps != 3 || r.PC != c.State[PC] {
+		t.Fatalf("Expected the budget to run out after 3 steps, got %+v after %d", r, c.Steps)
+	}
+	calls := 2
+	for r := c.RunFor(3); r.Kind != HALT_NORMAL_EXIT; r = c.RunFor(3) {
+		if r.Kind != HALT_BUDGET || calls > 3 {
+			t.Fatalf("Unexpected halt %+v", r)
+		}
+		calls++
+	}
+	// The final RET is not counted
+	if calls != 3 || c.Steps != 7 {
+		t.Errorf("Expected 7 steps in 3 calls, got %d in %d", c.Steps, calls)
+	}
+
+	// A slice ending on a breakpoint stops there, and the next one does
+	// not run past it
+	c = assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,3\nLOOP\tSUBA\tGR1,=1\n\tJNZ\tLOOP\n\tRET\n\tEND\n")
+	bp := c.Breakpoints.Add(4, false)
+	stops := 0
+	for r := c.RunFor(2); r.Kind != HALT_NORMAL_EXIT; r = c.RunFor(2) {
+		if r.Kind != HALT_BREAKPOINT || r.PC != 4 || stops > 3 {
+			t.Fatalf("Expected to stop at the JNZ, got %+v after %d steps", r, c.Steps)
+		}
+		stops++
+	}
+	if stops != 3 || bp.Hits != 3 {
+		t.Errorf("Expected 3 stops at the JNZ, got %d (%d hits)", stops, bp.Hits)
+	}
+}
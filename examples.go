@@ -0,0 +1,237 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"sort"
+)
+
+// example is a bundled sample program with the inputs it runs on by
+// default.
+type example struct {
+	desc   string
+	inputs []string
+	source string
+}
+
+// Sample programs compiled into the binary, listed with "c2c2 examples
+// list" and run with "c2c2 examples run NAME".
+var examples = map[string]example{
+	"hello": {
+		desc: "print a greeting with OUT",
+		source: `; Print a greeting
+HELLO	START
+	OUT	MSG,LEN
+	RET
+MSG	DC	'Hello World'
+LEN	DC	11
+	END
+`,
+	},
+
+	"sum": {
+		desc:   "read a count and that many numbers, print their sum",
+		inputs: []string{"4", "10", "20", "-5", "7"},
+		source: `; Read a count and that many numbers, then print their sum
+SUM	START
+	CALL	INNUM
+	LD	GR2,GR0		; GR2 = numbers left to read
+	LAD	GR1,0		; GR1 = sum
+	JMI	FIN
+	JZE	FIN
+LOOP	CALL	INNUM
+	ADDA	GR1,GR0
+	SUBA	GR2,=1
+	JNZ	LOOP
+FIN	CALL	OUTNUM
+	RET
+	INCLUDE	<STD/INNUM>
+	INCLUDE	<STD/OUTNUM>
+	END
+`,
+	},
+
+	"sort": {
+		desc:   "read up to 16 numbers and print them in ascending order (bubble sort)",
+		inputs: []string{"5", "3", "-1", "4", "1", "5"},
+		source: `; Read a count and that many numbers (at most 16), then print them
+; in ascending order
+SORT	START
+	CALL	INNUM
+	LD	GR7,GR0		; GR7 = number of values
+	JMI	FIN
+	CPA	GR7,MAX
+	JPL	FIN
+	LAD	GR1,0
+READ	CPA	GR1,GR7
+	JZE	BUBBLE
+	CALL	INNUM
+	ST	GR0,DATA,GR1
+	LAD	GR1,1,GR1
+	JUMP	READ
+; Bubble sort: each pass moves the largest unsorted value to the end
+BUBBLE	LD	GR2,GR7		; GR2 = length of the unsorted part
+OUTER	SUBA	GR2,=1
+	JMI	PRINT
+	JZE	PRINT
+	LAD	GR3,0
+INNER	CPA	GR3,GR2
+	JZE	OUTER
+	LAD	GR6,1,GR3
+	LD	GR4,DATA,GR3
+	LD	GR5,DATA,GR6
+	CPA	GR4,GR5
+	JMI	NEXT
+	JZE	NEXT
+	ST	GR5,DATA,GR3
+	ST	GR4,DATA,GR6
+NEXT	LAD	GR3,1,GR3
+	JUMP	INNER
+PRINT	LAD	GR3,0
+PLOOP	CPA	GR3,GR7
+	JZE	FIN
+	LD	GR1,DATA,GR3
+	CALL	OUTNUM
+	LAD	GR3,1,GR3
+	JUMP	PLOOP
+FIN	RET
+MAX	DC	16
+DATA	DS	16
+	INCLUDE	<STD/INNUM>
+	INCLUDE	<STD/OUTNUM>
+	END
+`,
+	},
+
+	"reverse": {
+		desc:   "read a line and print it reversed",
+		inputs: []string{"stressed"},
+		source: `; Read a line and print it reversed
+REVERSE	START
+	IN	BUF,LEN
+	LD	GR2,LEN		; GR2 = characters left to copy
+	JMI	FIN
+	LAD	GR3,0		; GR3 = index in OBUF
+LOOP	SUBA	GR2,=1
+	JMI	PRINT
+	LD	GR4,BUF,GR2
+	ST	GR4,OBUF,GR3
+	LAD	GR3,1,GR3
+	JUMP	LOOP
+PRINT	OUT	OBUF,LEN
+FIN	RET
+BUF	DS	256
+OBUF	DS	256
+LEN	DS	1
+	END
+`,
+	},
+
+	"subroutine": {
+		desc:   "call a subroutine in another program to pick the larger of two numbers",
+		inputs: []string{"12", "30"},
+		source: `; Read two numbers and print the larger one, computed by the
+; subroutine LARGER
+MAIN	START
+	CALL	INNUM
+	LD	GR1,GR0
+	CALL	INNUM
+	LD	GR2,GR0
+	CALL	LARGER
+	LD	GR1,GR0
+	CALL	OUTNUM
+	RET
+	INCLUDE	<STD/INNUM>
+	INCLUDE	<STD/OUTNUM>
+	END
+; LARGER: GR0 = the larger of GR1 and GR2
+LARGER	START
+	LD	GR0,GR1
+	CPA	GR1,GR2
+	JPL	FIN
+	LD	GR0,GR2
+FIN	RET
+	END
+`,
+	},
+}
+
+// exampleNames returns the names of the bundled examples in order.
+func exampleNames() []string {
+	names := make([]string, 0, len(examples))
+	for name := range examples {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// assembleExample assembles the bundled example name.
+func assembleExample(name string) (*Object, error) {
+	ex, ok := examples[name]
+	if !ok {
+		return nil, fmt.Errorf("[CASL2 ERROR] Unknown example \"%s\"", name)
+	}
+	return assembleText(name+".cas", ex.source, newAssemblerState())
+}
+
+// runExamplesCommand implements "c2c2 examples". It returns the exit
+// status.
+func runExamplesCommand(args []string, w io.Writer) int {
+	usage := func() int {
+		fmt.Fprintf(w, "Usage: c2c2 examples list\n")
+		fmt.Fprintf(w, "       c2c2 examples show NAME\n")
+		fmt.Fprintf(w, "       c2c2 examples run NAME [-inputs] [input1 ...]\n")
+		return 2
+	}
+	if len(args) == 0 {
+		return usage()
+	}
+
+	switch args[0] {
+	case "list":
+		for _, name := range exampleNames() {
+			fmt.Fprintf(w, "%-12s %s\n", name, examples[name].desc)
+		}
+		return 0
+	case "show", "run":
+		if len(args) < 2 {
+			return usage()
+		}
+	default:
+		return usage()
+	}
+
+	name := args[1]
+	ex, ok := examples[name]
+	if !ok {
+		fmt.Fprintf(w, "[CASL2 ERROR] Unknown example \"%s\"; see \"c2c2 examples list\"\n", name)
+		return 2
+	}
+	if args[0] == "show" {
+		fmt.Fprint(w, ex.source)
+		return 0
+	}
+
+	// Inputs follow the name, optionally after -inputs; without any the
+	// example runs on its own
+	inputs := args[2:]
+	if len(inputs) > 0 && (inputs[0] == "-inputs" || inputs[0] == "--inputs") {
+		inputs = inputs[1:]
+	}
+	if len(inputs) == 0 {
+		inputs = ex.inputs
+	}
+
+	obj, err := assembleExample(name)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	transcript, timedOut := runTranscript(obj, inputs, TEST_MAX_STEPS, TEST_TIMEOUT)
+	fmt.Fprint(w, transcript)
+	if timedOut {
+		return 1
+	}
+	return 0
+}
@@ -0,0 +1,36 @@
+package main
+
+import (
+	"bytes"
+	"strings"
+	"testing"
+)
+
+func TestExamples(t *testing.T) {
+	expected := map[string]string{
+		"hello":      "OUT> Hello World\n",
+		"sum":        "IN> 4\nIN> 10\nIN> 20\nIN> -5\nIN> 7\nOUT> 32\n",
+		"sort":       "IN> 5\nIN> 3\nIN> -1\nIN> 4\nIN> 1\nIN> 5\nOUT> -1\nOUT> 1\nOUT> 3\nOUT> 4\nOUT> 5\n",
+		"reverse":    "IN> stressed\nOUT> desserts\n",
+		"subroutine": "IN> 12\nIN> 30\nOUT> 30\n",
+	}
+	for _, name := range exampleNames() {
+		obj, err := assembleExample(name)
+		if err != nil {
+			t.Errorf("%s: %v", name, err)
+			continue
+		}
+		transcript, _ := runTranscript(obj, examples[name].inputs, TEST_MAX_STEPS, TEST_TIMEOUT)
+		if want := expected[name] + "Program finished (RET)\n"; transcript != want {
+			t.Errorf("%s: expected %q, got %q", name, want, transcript)
+		}
+	}
+
+	var out bytes.Buffer
+	if status := runExamplesCommand([]string{"run", "sort", "-inputs", "2", "9", "-3"}, &out); status != 0 {
+		t.Fatalf("Expected status 0, got %d\n%s", status, out.String())
+	}
+	if !strings.Contains(out.String(), "OUT> -3\nOUT> 9\n") {
+		t.Errorf("Unexpected output %s", out.String())
+	}
+}
@@ -0,0 +1,321 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"regexp"
+	"slices"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// Output comparison modes of expected-output tests
+const (
+	COMPARE_EXACT      = "exact"
+	COMPARE_WHITESPACE = "whitespace"
+	COMPARE_NO_PROMPT  = "no-prompt"
+	COMPARE_REGEX      = "regex"
+)
+
+// Default limits of one test: executed instructions and wall-clock time
+const (
+	TEST_MAX_STEPS = 10000000
+	TEST_TIMEOUT   = 10 * time.Second
+)
+
+// TestCase is one entry of a test spec: the IN lines for a program and
+// how its output is compared with the expectation. In JSON it is either
+// a list of inputs or an object:
+//
+//	{"inputs": ["3", "1"], "compare": "whitespace", "expect": "a.out",
+//	 "maxSteps": 100000, "timeout": "2s", "budgets": {"SORT": 5000},
+//	 "filters": ["trim", "mask:[0-9]+ms"]}
+//
+// Budgets limit the instructions executed in the region of a label.
+// Filters are applied to the OUT lines of both the expected and the
+// actual output before they are compared; a regex expectation is left
+// as it is.
+type TestCase struct {
+	Inputs   []string       `json:"inputs,omitempty"`
+	Compare  string         `json:"compare,omitempty"`
+	Expect   string         `json:"expect,omitempty"`
+	MaxSteps int            `json:"maxSteps,omitempty"`
+	Timeout  string         `json:"timeout,omitempty"`
+	Budgets  map[string]int `json:"budgets,omitempty"`
+	Filters  []string       `json:"filters,omitempty"`
+}
+
+// specOptions are the defaults of a spec run; tests can override the
+// limits.
+type specOptions struct {
+	jobs     int
+	maxSteps int
+	timeout  time.Duration
+	// project, when set, is assembled as a whole for the tests of its
+	// sources.
+	project *Project
+}
+
+func (tc *TestCase) UnmarshalJSON(data []byte) error {
+	var inputs []string
+	if err := json.Unmarshal(data, &inputs); err == nil {
+		*tc = TestCase{Inputs: inputs}
+		return nil
+	}
+	type plain TestCase
+	return json.Unmarshal(data, (*plain)(tc))
+}
+
+// promptPattern matches the IN>/OUT> prefix of a transcript line.
+var promptPattern = regexp.MustCompile(`(?m)^(IN|OUT)> `)
+
+// promptMismatch tells, for an output that differs under mode, whether
+// the expected output was written without the IN>/OUT> prefixes that test
+// output always has, e.g. from a plain run or by hand. It returns the
+// diagnosis, or "" when the prefixes are not the problem.
+func promptMismatch(mode string, expected string, actual string) string {
+	if mode != "" && mode != COMPARE_EXACT && mode != COMPARE_WHITESPACE {
+		return ""
+	}
+	if strings.TrimSpace(expected) == "" || promptPattern.MatchString(expected) || !promptPattern.MatchString(actual) {
+		return ""
+	}
+	return `expected output has no "IN> "/"OUT> " prefixes, which test output always has; ` +
+		`use "compare": "no-prompt" in the spec, or write the expected file with "c2c2 -n -q -r"`
+}
+
+// compareOutput reports whether actual matches expected under mode.
+func compareOutput(mode string, expected string, actual string) (bool, error) {
+	switch mode {
+	case "", COMPARE_EXACT:
+		return actual == expected, nil
+	case COMPARE_WHITESPACE:
+		return strings.Join(strings.Fields(actual), " ") == strings.Join(strings.Fields(expected), " "), nil
+	case COMPARE_NO_PROMPT:
+		return promptPattern.ReplaceAllString(actual, "") == promptPattern.ReplaceAllString(expected, ""), nil
+	case COMPARE_REGEX:
+		re, err := regexp.Compile(`(?s)^(?:` + strings.TrimSuffix(expected, "\n") + `)\n?$`)
+		if err != nil {
+			return false, fmt.Errorf("[COMET2 ERROR] Invalid expected pattern: %v", err)
+		}
+		return re.MatchString(actual), nil
+	}
+	return false, fmt.Errorf("[COMET2 ERROR] Unknown compare mode \"%s\"", mode)
+}
+
+// runTranscript runs obj on inputs and returns what "c2c2 -n -q -r" shows:
+// the IN and OUT lines followed by the halt message. An IN after the last
+// input fails the run instead of waiting. The run is cancelled after
+// timeout, if positive, and timedOut is then true. observers watch the run.
+func runTranscript(obj *Object, inputs []string, maxSteps int, timeout time.Duration, observers ...ExecutionObserver) (transcript string, timedOut bool) {
+	builder := NewComet2Builder().
+		WithObject(obj).
+		WithInputs(inputs).
+		WithOutput(nil).
+		WithWarn(nil).
+		WithMaxSteps(maxSteps)
+	for _, o := range observers {
+		builder.WithObserver(o)
+	}
+	c := builder.Build()
+	c.FailOnInputWait = true
+	if timeout > 0 {
+		timer := time.AfterFunc(timeout, c.Cancel)
+		defer timer.Stop()
+	}
+	err := c.Run()
+
+	var b strings.Builder
+	for _, ev := range c.Transcript {
+		kind := "OUT"
+		if ev.Kind == IO_IN {
+			kind = "IN"
+		}
+		fmt.Fprintf(&b, "%s> %s\n", kind, strings.TrimSuffix(ev.Text, "\n"))
+	}
+	if err != nil {
+		b.WriteString(err.Error() + "\n")
+	}
+	return b.String(), err == ErrCancelled
+}
+
+// ioStates records the machine at each IN and OUT of a run, one entry per
+// transcript line, so that a failed test can show the state in which the
+// first differing line was written.
+type ioStates struct {
+	BaseObserver
+	states []ioState
+}
+
+// ioState is the machine at the SVC of an IN or OUT.
+type ioState struct {
+	step int
+	pc   int
+	gr   [8]int
+}
+
+func (o *ioStates) Svc(c *Comet2, entry int) {
+	if entry != SYS_IN && entry != SYS_OUT {
+		return
+	}
+	s := ioState{step: c.Steps, pc: c.State[PC]}
+	for i := range s.gr {
+		s.gr[i] = c.State[GR0+i]
+	}
+	o.states = append(o.states, s)
+}
+
+// describe tells where transcript line n, from 1, was written, or "" for
+// the halt message after the IN and OUT lines.
+func (o *ioStates) describe(obj *Object, n int) string {
+	if n < 1 || n > len(o.states) {
+		return ""
+	}
+	s := o.states[n-1]
+	where := NewSymbolizer(obj.Symbols, len(obj.Bin)).Format(s.pc)
+	if s.pc < len(obj.SourceMap) && obj.SourceMap[s.pc].Line > 0 {
+		where += fmt.Sprintf(" (line %d)", obj.SourceMap[s.pc].Line)
+	}
+	regs := make([]string, len(s.gr))
+	for i, v := range s.gr {
+		regs[i] = fmt.Sprintf("GR%d=#%s", i, hex(v, 4))
+	}
+	return fmt.Sprintf("  written at step %d by the SVC at %s\n  %s\n", s.step, where, strings.Join(regs, " "))
+}
+
+// runSpec runs the tests of the spec file at path, opts.jobs at a time,
+// each on its own machine. Sources and expected output files are relative
+// to the spec; the expectation defaults to SOURCE.out. Results are
+// reported in source order once all tests are done. It returns the number
+// of tests and of failed tests.
+func runSpec(path string, w io.Writer, opts specOptions) (int, int, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return 0, 0, fmt.Errorf("[COMET2 ERROR] Cannot read test spec: %v", err)
+	}
+	var spec map[string]TestCase
+	if err := json.Unmarshal(data, &spec); err != nil {
+		return 0, 0, fmt.Errorf("[COMET2 ERROR] %s: invalid test spec: %v", path, err)
+	}
+
+	sources := make([]string, 0, len(spec))
+	for source := range spec {
+		sources = append(sources, source)
+	}
+	sort.Strings(sources)
+
+	dir := filepath.Dir(path)
+	results := make([]error, len(sources))
+	indexes := make(chan int)
+	var wg sync.WaitGroup
+	for n := 0; n < max(opts.jobs, 1); n++ {
+		wg.Add(1)
+		go func() {
+			defer wg.Done()
+			for i := range indexes {
+				results[i] = runSpecCase(dir, sources[i], spec[sources[i]], opts)
+			}
+		}()
+	}
+	for i := range sources {
+		indexes <- i
+	}
+	close(indexes)
+	wg.Wait()
+
+	failed := 0
+	for i, source := range sources {
+		if results[i] != nil {
+			fmt.Fprintf(w, "FAIL %s: %v\n", source, results[i])
+			failed++
+		} else {
+			fmt.Fprintf(w, "ok %s\n", source)
+		}
+	}
+	return len(sources), failed, nil
+}
+
+func runSpecCase(dir string, source string, tc TestCase, opts specOptions) error {
+	maxSteps := opts.maxSteps
+	if tc.MaxSteps > 0 {
+		maxSteps = tc.MaxSteps
+	}
+	timeout := opts.timeout
+	if tc.Timeout != "" {
+		d, err := time.ParseDuration(tc.Timeout)
+		if err != nil {
+			return fmt.Errorf("invalid timeout \"%s\"", tc.Timeout)
+		}
+		timeout = d
+	}
+	expectPath := tc.Expect
+	if expectPath == "" {
+		expectPath = source + ".out"
+	}
+	expectPath = filepath.Join(dir, expectPath)
+
+	expected, err := os.ReadFile(expectPath)
+	if err != nil {
+		return fmt.Errorf("cannot read expected output: %v", err)
+	}
+	var obj *Object
+	path := filepath.Join(dir, source)
+	if opts.project != nil && slices.Contains(opts.project.Sources, path) {
+		obj, err = assembleProject(opts.project, newAssemblerState())
+	} else {
+		obj, err = assemble(path, newAssemblerState())
+	}
+	if err != nil {
+		return err
+	}
+	budgets, err := newBudgetCounter(obj, tc.Budgets)
+	if err != nil {
+		return err
+	}
+	filters, err := parseOutputFilters(tc.Filters)
+	if err != nil {
+		return err
+	}
+	states := &ioStates{}
+	// Grading halts a program stuck in a loop as soon as it is detected
+	actual, timedOut := runTranscript(obj, tc.Inputs, maxSteps, timeout, budgets, states, newLoopDetector())
+	if timedOut {
+		return fmt.Errorf("timed out after %v", timeout)
+	}
+	actual = filterTranscript(filters, actual)
+	want := string(expected)
+	if tc.Compare != COMPARE_REGEX {
+		want = filterTranscript(filters, want)
+	}
+
+	ok, err := compareOutput(tc.Compare, want, actual)
+	if err != nil {
+		return err
+	}
+	if !ok {
+		reason := "output differs"
+		// Without the prefixes every line would differ; compare the rest
+		// so that the diagnosis comes with the lines that really differ
+		if hint := promptMismatch(tc.Compare, want, actual); hint != "" {
+			actual = promptPattern.ReplaceAllString(actual, "")
+			if same, _ := compareOutput(tc.Compare, want, actual); same {
+				return fmt.Errorf("%s (%s)\n  the output matches apart from the prefixes", hint, expectPath)
+			}
+			reason = hint
+		}
+		diff := lineDiff(want, actual)
+		if tc.Compare != COMPARE_REGEX {
+			diff += formatFirstDifference(want, actual)
+			if line, _, _ := firstDifference(want, actual); line > 0 {
+				diff += states.describe(obj, line)
+			}
+		}
+		return fmt.Errorf("%s (%s)\n%s", reason, expectPath, strings.TrimSuffix(diff, "\n"))
+	}
+	return budgets.check()
+}
@@ -0,0 +1,139 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestCompareOutput(t *testing.T) {
+	actual := "OUT> Sum of data =  6\nProgram finished (RET)\n"
+	tests := []struct {
+		mode     string
+		expected string
+		want     bool
+	}{
+		{"exact", actual, true},
+		{"", "OUT> Sum of data = 6\nProgram finished (RET)\n", false},
+		{"whitespace", "OUT> Sum of data = 6\n\nProgram finished (RET)", true},
+		{"no-prompt", "Sum of data =  6\nProgram finished (RET)\n", true},
+		{"no-prompt", "Sum of data = 6\nProgram finished (RET)\n", false},
+		{"regex", `OUT> Sum of data = +\d+\nProgram finished .*`, true},
+		{"regex", `OUT> Sum of data = 7.*`, false},
+	}
+	for _, tt := range tests {
+		got, err := compareOutput(tt.mode, tt.expected, actual)
+		if err != nil || got != tt.want {
+			t.Errorf("Mode %q with %q: expected %v, got %v (%v)", tt.mode, tt.expected, tt.want, got, err)
+		}
+	}
+	if _, err := compareOutput("fuzzy", "", actual); err == nil {
+		t.Errorf("Expected error for an unknown mode")
+	}
+}
+
+func TestRunSpec(t *testing.T) {
+	dir := t.TempDir()
+	files := map[string]string{
+		"echo.cas":   echoProgram,
+		"echo.out":   "IN> hello\nOUT> hello\nProgram finished (RET)\n",
+		"echo.regex": "OUT> h.*\n.*",
+		"spec.json": `{
+			"echo.cas": {"inputs": ["hello"], "compare": "no-prompt", "expect": "echo.out"},
+			"echo2.cas": {"inputs": ["hello"], "compare": "regex", "expect": "echo.regex"},
+			"loop.cas": {"timeout": "50ms", "expect": "echo.out"},
+			"spin.cas": {"expect": "echo.out"}
+		}`,
+		// Counting through 2^32 states, too many to repeat in time
+		"loop.cas": "MAIN\tSTART\nLOOP\tADDA\tGR1,=1\n\tJNZ\tLOOP\n\tADDA\tGR2,=1\n\tJUMP\tLOOP\n\tEND\n",
+		"spin.cas": "MAIN\tSTART\nLOOP\tJUMP\tLOOP\n\tEND\n",
+	}
+	files["echo2.cas"] = echoProgram
+	for name, content := range files {
+		if err := os.WriteFile(filepath.Join(dir, name), []byte(content), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", name, err)
+		}
+	}
+
+	var out bytes.Buffer
+	total, failed, err := runSpec(filepath.Join(dir, "spec.json"), &out, specOptions{jobs: 2})
+	if err != nil || total != 4 || failed != 3 {
+		t.Fatalf("Expected 3 of 4 tests to fail, got %d of %d (%v)\n%s", failed, total, err, out.String())
+	}
+	// Results are reported in source order whatever order the tests finish in
+	if !strings.HasPrefix(out.String(), "ok echo.cas\nFAIL echo2.cas: output differs") || !strings.Contains(out.String(), "FAIL loop.cas: timed out after 50ms") {
+		t.Errorf("Unexpected result %s", out.String())
+	}
+	// The loop detector is on when grading
+	if !strings.Contains(out.String(), "FAIL spin.cas: output differs") || !strings.Contains(out.String(), "Probable infinite loop at") {
+		t.Errorf("Expected spin.cas to halt as a loop, got %s", out.String())
+	}
+}
+
+func TestRunSpecFirstDifference(t *testing.T) {
+	dir := t.TempDir()
+	files := map[string]string{
+		"count.cas": "MAIN\tSTART\n\tLAD\tGR3,5\n\tOUT\tA,LEN\nSECOND\tOUT\tB,LEN\n\tRET\nA\tDC\t'1'\nB\tDC\t'3'\nLEN\tDC\t1\n\tEND\n",
+		"count.out": "OUT> 1\nOUT> 2\nProgram finished (RET)\n",
+		"spec.json": `{"count.cas": {"expect": "count.out"}}`,
+	}
+	for name, content := range files {
+		if err := os.WriteFile(filepath.Join(dir, name), []byte(content), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", name, err)
+		}
+	}
+
+	var out bytes.Buffer
+	if _, failed, err := runSpec(filepath.Join(dir, "spec.json"), &out, specOptions{jobs: 1}); err != nil || failed != 1 {
+		t.Fatalf("Expected the test to fail, got %d (%v)\n%s", failed, err, out.String())
+	}
+	for _, want := range []string{
+		"first difference at line 2, column 6:\n  expected: OUT> 2\n  actual:   OUT> 3\n",
+		"by the SVC at #0016 <SECOND+8> (line 4)",
+		"GR3=#0005",
+	} {
+		if !strings.Contains(out.String(), want) {
+			t.Errorf("Expected %q in\n%s", want, out.String())
+		}
+	}
+}
+
+func TestRunSpecPromptMismatch(t *testing.T) {
+	dir := t.TempDir()
+	files := map[string]string{
+		"hello.cas": "MAIN\tSTART\n\tOUT\tA,LEN\n\tOUT\tB,LEN\n\tRET\nA\tDC\t'1'\nB\tDC\t'3'\nLEN\tDC\t1\n\tEND\n",
+		"same.out":  "1\n3\nProgram finished (RET)\n",
+		"other.out": "1\n2\nProgram finished (RET)\n",
+		"spec.json": `{"hello.cas": {"expect": "same.out"}}`,
+	}
+	for name, content := range files {
+		if err := os.WriteFile(filepath.Join(dir, name), []byte(content), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", name, err)
+		}
+	}
+
+	var out bytes.Buffer
+	if _, failed, err := runSpec(filepath.Join(dir, "spec.json"), &out, specOptions{jobs: 1}); err != nil || failed != 1 {
+		t.Fatalf("Expected the test to fail, got %d (%v)\n%s", failed, err, out.String())
+	}
+	if !strings.Contains(out.String(), `"compare": "no-prompt"`) || !strings.Contains(out.String(), "matches apart from the prefixes") {
+		t.Errorf("Expected the missing prefixes to be diagnosed, got\n%s", out.String())
+	}
+
+	if err := os.WriteFile(filepath.Join(dir, "spec.json"), []byte(`{"hello.cas": {"expect": "other.out"}}`), 0644); err != nil {
+		t.Fatal(err)
+	}
+	out.Reset()
+	if _, failed, err := runSpec(filepath.Join(dir, "spec.json"), &out, specOptions{jobs: 1}); err != nil || failed != 1 {
+		t.Fatalf("Expected the test to fail, got %d (%v)\n%s", failed, err, out.String())
+	}
+	if !strings.Contains(out.String(), "first difference at line 2, column 1:\n  expected: 2\n  actual:   3\n") {
+		t.Errorf("Expected the difference without prefixes, got\n%s", out.String())
+	}
+
+	if hint := promptMismatch(COMPARE_NO_PROMPT, "1\n", "OUT> 1\n"); hint != "" {
+		t.Errorf("Expected no diagnosis with no-prompt, got %q", hint)
+	}
+}
@@ -0,0 +1,297 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+)
+
+// Message templates used by explain mode. They are kept in one place so
+// they can be translated.
+var explainCatalog = map[string]string{
+	"load":     "%s <- %s",
+	"store":    "%s <- %s",
+	"arith":    "%s %s %s = %s",
+	"compare":  "compare %s with %s",
+	"shift":    "%s %s by %d = %s",
+	"jump":     "jump to %s",
+	"nojump":   "no jump",
+	"push":     "push %s (SP=#%s)",
+	"pop":      "%s <- pop (SP=#%s)",
+	"call":     "call %s, return address %s pushed",
+	"ret":      "return to %s",
+	"svc":      "system call %d",
+	"svcin":    "read a line into %s, its length into %s",
+	"svcout":   "write the characters at %s, as many as mem[#%s](%d)",
+	"svcexit":  "end the program with code %d (%s)",
+	"nop":      "do nothing",
+	"flags":    "; %s",
+	"mem":      "mem[#%s](%d)",
+	"reg":      "GR%d(%d)",
+	"shiftl":   "shifted left",
+	"shiftr":   "shifted right",
+	"unknown":  "%s",
+	"register": "GR%d",
+}
+
+// Operator symbols for arithmetic and logical instructions
+var explainOperators = map[string]string{
+	"ADDA": "+", "ADDL": "+", "SUBA": "-", "SUBL": "-",
+	"MULA": "*", "MULL": "*", "DIVA": "/", "DIVL": "/",
+	"AND": "and", "OR": "or", "XOR": "xor",
+}
+
+// Magic comment turning explain mode on or off from the next instruction
+// on: ";@TRACE ON" or ";@TRACE OFF"
+var traceCommentPattern = regexp.MustCompile(`(?i)^;\s*@TRACE\s+(ON|OFF)\s*$`)
+
+// TracePoint is where a ;@TRACE comment turns explain mode on or off: the
+// address of the first instruction after it.
+type TracePoint struct {
+	Addr int  `json:"addr"`
+	On   bool `json:"on"`
+}
+
+// traceMark is a ;@TRACE comment the assembler found on a line.
+type traceMark struct {
+	line int
+	on   bool
+}
+
+// tracePoints places the ;@TRACE comments at the first instruction on or
+// after their line. Comments after the last instruction are dropped.
+func tracePoints(asmState *AssemblerState) []TracePoint {
+	var points []TracePoint
+	for _, m := range asmState.traceMarks {
+		addr, line := -1, 0
+		for a, entry := range asmState.memory {
+			if a < 0 || !entry.Code || entry.Line < m.line {
+				continue
+			}
+			if addr < 0 || entry.Line < line || (entry.Line == line && a < addr) {
+				addr, line = a, entry.Line
+			}
+		}
+		if addr >= 0 {
+			points = append(points, TracePoint{Addr: addr, On: m.on})
+		}
+	}
+	return points
+}
+
+// explainer prints a one-line description of every executed instruction,
+// or of those inside filter when it is set. Instructions executed while a
+// trace point has turned it off are skipped.
+type explainer struct {
+	BaseObserver
+	// print receives each explanation. When nil they are dropped.
+	print  func(msg string)
+	filter *traceFilter
+	points map[int]bool
+	off    bool
+	inst   string
+	opr    string
+	grForm bool
+	gr     int
+	xr     int
+	eadr   int
+	regs   [8]int
+	mem    int
+	sp     int
+}
+
+// setTracePoints makes the trace points of a program switch the
+// explainer. When one of them turns it on, it starts off.
+func (e *explainer) setTracePoints(points []TracePoint) {
+	e.points = make(map[int]bool)
+	for _, p := range points {
+		e.points[p.Addr] = p.On
+		if p.On {
+			e.off = true
+		}
+	}
+}
+
+func (e *explainer) BeforeStep(c *Comet2, pc int) {
+	if on, ok := e.points[pc]; ok {
+		e.off = !on
+	}
+	e.inst, e.opr, _ = parse(c.Memory, c.State)
+	e.grForm = isGRGRForm(e.opr)
+
+	word := memGet(c.Memory, pc)
+	e.gr = (word >> 4) & 0x7
+	e.xr = word & 0x7
+	e.eadr = memGet(c.Memory, pc+1)
+	if e.xr >= 1 && e.xr <= 7 {
+		e.eadr += c.State[GR0+e.xr]
+	}
+	e.eadr &= 0xffff
+
+	copy(e.regs[:], c.State[GR0:GR7+1])
+	e.mem = memGet(c.Memory, e.eadr)
+	e.sp = c.State[SP]
+}
+
+// value formats a register or word as signed or unsigned.
+func explainValue(val int, logical bool) int {
+	if logical {
+		return val
+	}
+	return signed(val)
+}
+
+// source describes the second operand before execution.
+func (e *explainer) source(logical bool) string {
+	if e.grForm {
+		return fmt.Sprintf(explainCatalog["reg"], e.xr, explainValue(e.regs[e.xr], logical))
+	}
+	return fmt.Sprintf(explainCatalog["mem"], hex(e.eadr, 4), explainValue(e.mem, logical))
+}
+
+func (e *explainer) AfterStep(c *Comet2, pc int) {
+	if e.off || e.filter != nil && !e.filter.contains(pc) {
+		return
+	}
+	logical := e.inst == "ADDL" || e.inst == "SUBL" || e.inst == "MULL" || e.inst == "DIVL" ||
+		e.inst == "CPL" || e.inst == "SLL" || e.inst == "SRL"
+	reg := func(n int, val int) string {
+		return fmt.Sprintf(explainCatalog["reg"], n, explainValue(val, logical))
+	}
+	after := c.State[GR0+e.gr]
+
+	var text string
+	switch e.inst {
+	case "LD":
+		text = fmt.Sprintf(explainCatalog["load"], fmt.Sprintf(explainCatalog["register"], e.gr), e.source(logical))
+	case "ST":
+		text = fmt.Sprintf(explainCatalog["store"], fmt.Sprintf("mem[#%s]", hex(e.eadr, 4)), reg(e.gr, e.regs[e.gr]))
+	case "LAD":
+		text = fmt.Sprintf(explainCatalog["load"], fmt.Sprintf(explainCatalog["register"], e.gr), "#"+hex(e.eadr, 4))
+	case "ADDA", "ADDL", "SUBA", "SUBL", "MULA", "MULL", "DIVA", "DIVL", "AND", "OR", "XOR":
+		text = fmt.Sprintf(explainCatalog["arith"], reg(e.gr, e.regs[e.gr]), explainOperators[e.inst],
+			e.source(logical), fmt.Sprint(explainValue(after, logical)))
+	case "CPA", "CPL":
+		text = fmt.Sprintf(explainCatalog["compare"], reg(e.gr, e.regs[e.gr]), e.source(logical))
+	case "SLA", "SLL":
+		text = fmt.Sprintf(explainCatalog["shift"], reg(e.gr, e.regs[e.gr]), explainCatalog["shiftl"], e.eadr,
+			fmt.Sprint(explainValue(after, logical)))
+	case "SRA", "SRL":
+		text = fmt.Sprintf(explainCatalog["shift"], reg(e.gr, e.regs[e.gr]), explainCatalog["shiftr"], e.eadr,
+			fmt.Sprint(explainValue(after, logical)))
+	case "JMI", "JNZ", "JZE", "JUMP", "JPL", "JOV":
+		if c.State[PC] == e.eadr {
+			text = fmt.Sprintf(explainCatalog["jump"], c.formatAddr(e.eadr))
+		} else {
+			text = explainCatalog["nojump"]
+		}
+	case "PUSH":
+		text = fmt.Sprintf(explainCatalog["push"], "#"+hex(e.eadr, 4), hex(c.State[SP], 4))
+	case "POP":
+		text = fmt.Sprintf(explainCatalog["pop"], fmt.Sprintf(explainCatalog["register"], e.gr), hex(c.State[SP], 4))
+	case "CALL":
+		text = fmt.Sprintf(explainCatalog["call"], c.formatAddr(e.eadr), c.formatAddr(pc+2))
+	case "RET":
+		text = fmt.Sprintf(explainCatalog["ret"], c.formatAddr(c.State[PC]))
+	case "SVC":
+		switch name, exit := svcExitNames[e.eadr]; {
+		case e.eadr == SYS_IN:
+			text = fmt.Sprintf(explainCatalog["svcin"], c.formatAddr(e.regs[1]), c.formatAddr(e.regs[2]))
+		case e.eadr == SYS_OUT:
+			text = fmt.Sprintf(explainCatalog["svcout"], c.formatAddr(e.regs[1]), hex(e.regs[2], 4), memGet(c.Memory, e.regs[2]))
+		case exit:
+			text = fmt.Sprintf(explainCatalog["svcexit"], e.eadr, name)
+		default:
+			text = fmt.Sprintf(explainCatalog["svc"], e.eadr)
+		}
+	case "NOP":
+		text = explainCatalog["nop"]
+	default:
+		text = fmt.Sprintf(explainCatalog["unknown"], e.inst)
+	}
+
+	fr := c.State[FR]
+	text += fmt.Sprintf(explainCatalog["flags"], formatFR(fr))
+	if e.print != nil {
+		e.print(fmt.Sprintf("%s %s: %s", e.inst, e.opr, text))
+	}
+}
+
+// traceFilter is a set of address ranges, each with inclusive bounds.
+type traceFilter struct {
+	ranges [][2]int
+}
+
+// parseTraceFilter parses the -trace-filter specification: items
+// separated by commas, each "FROM..TO" with addresses or labels as bounds,
+// or "label:NAME" for the START unit NAME or the code from the label NAME
+// up to the next label.
+func parseTraceFilter(spec string, obj *Object) (*traceFilter, error) {
+	f := &traceFilter{}
+	for _, item := range strings.Split(spec, ",") {
+		item = strings.TrimSpace(item)
+		if name, ok := strings.CutPrefix(item, "label:"); ok {
+			r, ok := labelRegion(obj, name)
+			if !ok {
+				return nil, fmt.Errorf("[COMET2 ERROR] Unknown label \"%s\" in -trace-filter", name)
+			}
+			f.ranges = append(f.ranges, r)
+			continue
+		}
+
+		lo, hi, ok := strings.Cut(item, "..")
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Invalid -trace-filter item \"%s\" (FROM..TO or label:NAME)", item)
+		}
+		from, ok := lookupSymbol(obj, lo)
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Invalid address \"%s\" in -trace-filter", lo)
+		}
+		to, ok := lookupSymbol(obj, hi)
+		if !ok {
+			return nil, fmt.Errorf("[COMET2 ERROR] Invalid address \"%s\" in -trace-filter", hi)
+		}
+		if from > to {
+			from, to = to, from
+		}
+		f.ranges = append(f.ranges, [2]int{from, to})
+	}
+	return f, nil
+}
+
+// labelRegion returns the words of the START unit name, or from the label
+// name up to the next label.
+func labelRegion(obj *Object, name string) ([2]int, bool) {
+	for _, u := range obj.Units {
+		if u.Name == name {
+			return [2]int{u.Start, u.Start + u.Total() - 1}, true
+		}
+	}
+	start, ok := lookupSymbol(obj, name)
+	if !ok || !labelPattern.MatchString(name) {
+		return [2]int{}, false
+	}
+	var addrs []int
+	for _, addr := range obj.Symbols {
+		if addr > start {
+			addrs = append(addrs, addr)
+		}
+	}
+	sort.Ints(addrs)
+	end := len(obj.Bin)
+	if len(addrs) > 0 {
+		end = addrs[0]
+	}
+	return [2]int{start, end - 1}, true
+}
+
+// contains reports whether addr is in one of the ranges.
+func (f *traceFilter) contains(addr int) bool {
+	for _, r := range f.ranges {
+		if addr >= r[0] && addr <= r[1] {
+			return true
+		}
+	}
+	return false
+}
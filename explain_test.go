@@ -0,0 +1,92 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestTraceFilter(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,1
+	CALL	SWAP
+	RET
+SWAP	LD	GR2,GR1
+	LD	GR1,GR0
+	RET
+TAIL	NOP
+	END
+SUB	START
+	RET
+	END
+`)
+	tests := []struct {
+		spec string
+		want [][2]int
+	}{
+		{"label:SWAP", [][2]int{{5, 7}}},
+		{"label:SUB", [][2]int{{9, 9}}},
+		{"SWAP..TAIL,#0000..2", [][2]int{{5, 8}, {0, 2}}},
+	}
+	for _, tt := range tests {
+		f, err := parseTraceFilter(tt.spec, obj)
+		if err != nil {
+			t.Fatalf("%s: %v", tt.spec, err)
+		}
+		if !reflect.DeepEqual(f.ranges, tt.want) {
+			t.Errorf("%s: expected %v, got %v", tt.spec, tt.want, f.ranges)
+		}
+	}
+	for _, bad := range []string{"label:NONE", "SWAP", "SWAP..NONE"} {
+		if _, err := parseTraceFilter(bad, obj); err == nil {
+			t.Errorf("Expected an error for %q", bad)
+		}
+	}
+}
+
+func TestTraceComments(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,0
+;@TRACE ON
+LOOP	LAD	GR1,1,GR1
+	CPA	GR1,=2	;@trace off
+	JNZ	LOOP
+	RET
+	DC	';@TRACE ON'
+;@TRACE ON
+	END
+`)
+	want := []TracePoint{{Addr: 2, On: true}, {Addr: 4, On: false}}
+	if !reflect.DeepEqual(obj.Traces, want) {
+		t.Errorf("Expected %+v, got %+v", want, obj.Traces)
+	}
+
+	e := &explainer{}
+	e.setTracePoints(obj.Traces)
+	if !e.off {
+		t.Errorf("Expected explaining to start off")
+	}
+	c := LoadObject(obj)
+	c.Output = func(string) {}
+	c.AddObserver(e)
+	var offAt []int
+	for i := 0; i < 4; i++ {
+		c.Step()
+		if e.off {
+			offAt = append(offAt, i)
+		}
+	}
+	if !reflect.DeepEqual(offAt, []int{0, 2, 3}) {
+		t.Errorf("Expected explaining off after steps 0, 2 and 3, got %v", offAt)
+	}
+}
+
+func TestExplainerPrint(t *testing.T) {
+	c := assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,3\n\tRET\n\tEND\n")
+	var lines []string
+	c.AddObserver(&explainer{print: func(msg string) { lines = append(lines, msg) }})
+	c.Step()
+	if len(lines) != 1 || !strings.HasPrefix(lines[0], "LAD ") || !strings.Contains(lines[0], ": GR1 <- #0003") {
+		t.Errorf("Unexpected explanations %q", lines)
+	}
+}
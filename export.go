@@ -0,0 +1,111 @@
+package main
+
+import (
+	"encoding/csv"
+	"fmt"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+// symbolView formats a "scope:label" symbol the way the listing does.
+func symbolView(name string) string {
+	parts := strings.SplitN(name, ":", 2)
+	if len(parts) != 2 || parts[0] == parts[1] {
+		return parts[len(parts)-1]
+	}
+	return fmt.Sprintf("%s (%s)", parts[1], parts[0])
+}
+
+// addressLabels maps each address to the labels defined there.
+func addressLabels(obj *Object) map[int][]string {
+	labels := make(map[int][]string)
+	for name, addr := range obj.Symbols {
+		labels[addr] = append(labels[addr], symbolView(name))
+	}
+	for addr := range labels {
+		sort.Strings(labels[addr])
+	}
+	return labels
+}
+
+// lookupSymbol resolves a number or a label (with or without scope).
+func lookupSymbol(obj *Object, name string) (int, bool) {
+	if n, ok := expandNumber(name); ok {
+		return n, true
+	}
+	if addr, ok := obj.Symbols[name]; ok {
+		return addr, true
+	}
+	if addr, ok := obj.Symbols[name+":"+name]; ok {
+		return addr, true
+	}
+
+	var found []string
+	for sym := range obj.Symbols {
+		if strings.HasSuffix(sym, ":"+name) {
+			found = append(found, sym)
+		}
+	}
+	if len(found) == 0 {
+		return 0, false
+	}
+	sort.Strings(found)
+	return obj.Symbols[found[0]], true
+}
+
+// parseExportSpec splits "FILE[:FROM-TO]" into a path and address range.
+// Without a range the whole program image is exported.
+func parseExportSpec(spec string, obj *Object) (string, int, int, error) {
+	from, to := 0, len(obj.Bin)-1
+
+	idx := strings.LastIndex(spec, ":")
+	if idx < 0 || !strings.Contains(spec[idx+1:], "-") {
+		return spec, from, to, nil
+	}
+
+	path, rng := spec[:idx], spec[idx+1:]
+	bounds := strings.SplitN(rng, "-", 2)
+	var ok bool
+	if from, ok = lookupSymbol(obj, bounds[0]); !ok {
+		return "", 0, 0, fmt.Errorf("[COMET2 ERROR] Invalid address \"%s\" in export range", bounds[0])
+	}
+	if to, ok = lookupSymbol(obj, bounds[1]); !ok {
+		return "", 0, 0, fmt.Errorf("[COMET2 ERROR] Invalid address \"%s\" in export range", bounds[1])
+	}
+	if from > to {
+		from, to = to, from
+	}
+	return path, from, to, nil
+}
+
+// exportMemoryCSV writes address, label, hex and decimal value rows.
+func exportMemoryCSV(spec string, obj *Object, c *Comet2) error {
+	path, from, to, err := parseExportSpec(spec, obj)
+	if err != nil {
+		return err
+	}
+
+	f, err := os.Create(path)
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write file: %v", err)
+	}
+	defer f.Close()
+
+	labels := addressLabels(obj)
+	w := csv.NewWriter(f)
+	w.Write([]string{"address", "label", "hex", "decimal"})
+	for addr := from; addr <= to; addr++ {
+		val := memGet(c.Memory, addr)
+		w.Write([]string{
+			"#" + hex(addr, 4),
+			strings.Join(labels[addr], " "),
+			"#" + hex(val, 4),
+			strconv.Itoa(signed(val)),
+		})
+	}
+	w.Flush()
+
+	return w.Error()
+}
@@ -0,0 +1,67 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+const exportProgram = `MAIN	START
+	LD	GR1,A
+	RET
+A	DC	7
+B	DC	-2
+	END
+`
+
+func TestParseExportSpec(t *testing.T) {
+	obj := assembleObject(t, exportProgram)
+	tests := []struct {
+		spec     string
+		path     string
+		from, to int
+	}{
+		{"mem.csv", "mem.csv", 0, 4},
+		{"mem.csv:A-B", "mem.csv", 3, 4},
+		{"mem.csv:MAIN-#0002", "mem.csv", 0, 2},
+		{"mem.csv:#0004-1", "mem.csv", 1, 4},
+		// A colon without a range is part of the path
+		{`C:\out\mem.csv`, `C:\out\mem.csv`, 0, 4},
+	}
+	for _, tt := range tests {
+		path, from, to, err := parseExportSpec(tt.spec, obj)
+		if err != nil {
+			t.Errorf("%s: %v", tt.spec, err)
+			continue
+		}
+		if path != tt.path || from != tt.from || to != tt.to {
+			t.Errorf("%s: expected %s #%s-#%s, got %s #%s-#%s", tt.spec, tt.path, hex(tt.from, 4), hex(tt.to, 4), path, hex(from, 4), hex(to, 4))
+		}
+	}
+	for _, bad := range []string{"mem.csv:A-NONE", "mem.csv:NONE-B", "mem.csv:-B"} {
+		if _, _, _, err := parseExportSpec(bad, obj); err == nil {
+			t.Errorf("Expected an error for %q", bad)
+		}
+	}
+}
+
+func TestExportMemoryCSV(t *testing.T) {
+	obj := assembleObject(t, exportProgram)
+	c := LoadObject(obj)
+	if err := c.Run(); err == nil {
+		t.Fatalf("Expected program to finish")
+	}
+
+	path := filepath.Join(t.TempDir(), "mem.csv")
+	if err := exportMemoryCSV(path+":A-B", obj, c); err != nil {
+		t.Fatal(err)
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	want := "address,label,hex,decimal\n#0003,A,#0007,7\n#0004,B,#fffe,-2\n"
+	if string(data) != want {
+		t.Errorf("Expected %q, got %q", want, data)
+	}
+}
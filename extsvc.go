@@ -0,0 +1,72 @@
+package main
+
+import (
+	"strconv"
+	"strings"
+)
+
+// SVC entries of -extended-svc
+const (
+	SYS_ITOA = 0xfff4
+	SYS_ATOI = 0xfff6
+	SYS_EXIT = 0xfff8
+)
+
+// extendedSvcs are the services -extended-svc installs. Like IN and OUT,
+// the conversions take the text at GR1 with its length in mem[GR2], one
+// character per word.
+var extendedSvcs = map[int]SvcHandler{
+	SYS_ITOA: svcItoa,
+	SYS_ATOI: svcAtoi,
+	SYS_EXIT: svcExit,
+}
+
+// svcItoa writes GR0 as a signed decimal number to the buffer at GR1 and
+// its length to mem[GR2], so that OUT can print it right away.
+func svcItoa(c *Comet2) error {
+	text := strconv.Itoa(signed(c.State[GR0]))
+	bufp := c.State[GR1]
+	for i, ch := range text {
+		c.store(bufp+i, int(ch))
+	}
+	c.store(c.State[GR2], len(text))
+	return nil
+}
+
+// svcAtoi reads the decimal number at GR1, mem[GR2] characters long, into
+// GR0 and sets FR as LD does. Spaces around the number and a sign are
+// allowed. When the text is not a number from -32768 to 32767, GR0 is 0
+// and OF is set.
+func svcAtoi(c *Comet2) error {
+	bufp := c.State[GR1]
+	length := c.load(c.State[GR2])
+	var text strings.Builder
+	for i := 0; i < length; i++ {
+		text.WriteByte(byte(c.load(bufp+i) & 0xff))
+	}
+	n, err := strconv.ParseInt(strings.TrimSpace(text.String()), 10, 16)
+	if err != nil {
+		c.State[GR0] = 0
+		c.State[FR] = FR_OVER | FR_ZERO
+		return nil
+	}
+	c.State[GR0] = int(n) & 0xffff
+	c.State[FR] = getFlag(c.State[GR0])
+	return nil
+}
+
+// svcExit ends the program with GR0 as its exit status, so that a harness
+// can read a result without parsing the output.
+func svcExit(c *Comet2) error {
+	status := c.State[GR0]
+	c.ExitStatus = &status
+	rerr := runtimeError(RUNTIME_FINISHED, c.State[PC], "Program finished (exit status %d)", status)
+	rerr.Code = SYS_EXIT
+	return rerr
+}
+
+// processExitCode maps an exit status to the exit code of c2c2: statuses
+// above 255 become 255, since the system only keeps the low byte.
+func processExitCode(status int) int {
+	return min(status, 255)
+}
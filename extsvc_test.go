@@ -0,0 +1,77 @@
+package main
+
+import (
+	"errors"
+	"reflect"
+	"testing"
+)
+
+func TestExtendedSvc(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+LOOP	IN	BUF,LEN
+	LAD	GR1,BUF
+	LAD	GR2,LEN
+	SVC	#FFF6
+	JOV	BAD
+	ADDA	GR0,GR0
+	SVC	#FFF4
+	OUT	BUF,LEN
+	JUMP	LOOP
+BAD	OUT	MSG,MSGLEN
+	JUMP	LOOP
+BUF	DS	256
+LEN	DS	1
+MSG	DC	'bad'
+MSGLEN	DC	3
+	END
+`)
+	var outputs []string
+	builder := NewComet2Builder().
+		WithObject(obj).
+		WithInputs([]string{"21", " -16000 ", "+0", "x1", "40000"}).
+		WithOutput(func(text string) { outputs = append(outputs, text) })
+	for entry, h := range extendedSvcs {
+		builder.WithSvc(entry, h)
+	}
+	c := builder.Build()
+	c.FailOnInputWait = true
+	if err := c.Run(); !errors.Is(err, ErrInputExhausted) {
+		t.Fatalf("Expected the input to run out, got %v", err)
+	}
+	want := []string{"42", "-32000", "0", "bad", "bad"}
+	if !reflect.DeepEqual(outputs, want) {
+		t.Errorf("Expected %q, got %q", want, outputs)
+	}
+}
+
+func TestExitStatusSvc(t *testing.T) {
+	obj := assembleObject(t, "MAIN\tSTART\n\tLAD\tGR0,7\n\tSVC\t#FFF8\n\tLAD\tGR0,1\n\tRET\n\tEND\n")
+	builder := NewComet2Builder().WithObject(obj)
+	for entry, h := range extendedSvcs {
+		builder.WithSvc(entry, h)
+	}
+	c := builder.Build()
+	err := c.Run()
+	if err == nil || err.Error() != "Program finished (exit status 7)" {
+		t.Fatalf("Expected the program to exit with status 7, got %v", err)
+	}
+	if c.ExitStatus == nil || *c.ExitStatus != 7 {
+		t.Fatalf("Expected ExitStatus 7, got %v", c.ExitStatus)
+	}
+	if r := classifyHalt(err, c.State[PC]); r.Kind != HALT_NORMAL_EXIT || r.PC != 2 {
+		t.Errorf("Expected a normal exit at #0002, got %v at %d", r.Kind, r.PC)
+	}
+	if r := newRunResult(obj, c, err.Error()); r.ExitStatus == nil || *r.ExitStatus != 7 {
+		t.Errorf("Expected exitStatus 7 in the run result, got %v", r.ExitStatus)
+	}
+	for status, want := range map[int]int{0: 0, 255: 255, 256: 255, 0xffff: 255} {
+		if got := processExitCode(status); got != want {
+			t.Errorf("processExitCode(%d): expected %d, got %d", status, want, got)
+		}
+	}
+
+	c.Reset(obj)
+	if c.ExitStatus != nil {
+		t.Errorf("Expected Reset to clear ExitStatus")
+	}
+}
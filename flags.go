@@ -0,0 +1,98 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// FlagChange describes how one instruction changed FR and why.
+type FlagChange struct {
+	PC      int
+	Inst    string
+	Old     int
+	New     int
+	Reasons []string
+}
+
+// FR bits in display order
+var flagBits = []struct {
+	bit  int
+	name string
+}{
+	{FR_OVER, "OF"},
+	{FR_MINUS, "SF"},
+	{FR_ZERO, "ZF"},
+}
+
+// newFlagChange records the FR bits that differ between oldFR and newFR.
+func newFlagChange(pc int, inst string, oldFR, newFR int) FlagChange {
+	change := FlagChange{PC: pc, Inst: inst, Old: oldFR, New: newFR}
+	for _, fb := range flagBits {
+		if oldFR&fb.bit == newFR&fb.bit {
+			continue
+		}
+		set := newFR&fb.bit != 0
+		state := "cleared"
+		if set {
+			state = "set"
+		}
+		change.Reasons = append(change.Reasons,
+			fmt.Sprintf("%s %s: %s", fb.name, state, flagReason(inst, fb.bit, set)))
+	}
+	return change
+}
+
+// flagReason explains the rule that gave a FR bit its new value.
+func flagReason(inst string, bit int, set bool) string {
+	compare := inst == "CPA" || inst == "CPL"
+
+	switch bit {
+	case FR_OVER:
+		if !set {
+			switch inst {
+			case "LD", "AND", "OR", "XOR", "CPA", "CPL":
+				return "always cleared by " + inst
+			}
+			return "no overflow"
+		}
+		switch inst {
+		case "ADDA", "SUBA", "MULA":
+			return "signed result out of range"
+		case "ADDL", "SUBL", "MULL":
+			return "unsigned result out of range"
+		case "DIVA", "DIVL":
+			return "division by zero"
+		case "SLA", "SRA", "SLL", "SRL":
+			return "last bit shifted out was 1"
+		}
+		return "overflow"
+	case FR_MINUS:
+		if compare {
+			if set {
+				return "first operand is smaller"
+			}
+			return "first operand is not smaller"
+		}
+		if set {
+			return "result is negative"
+		}
+		return "result is not negative"
+	case FR_ZERO:
+		if compare {
+			if set {
+				return "operands are equal"
+			}
+			return "operands differ"
+		}
+		if set {
+			return "result is zero"
+		}
+		return "result is not zero"
+	}
+	return ""
+}
+
+// String formats the change for the monitor.
+func (fc FlagChange) String() string {
+	return fmt.Sprintf("%s: %s", fc.Inst, strings.Join(fc.Reasons, ", "))
+}
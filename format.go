@@ -0,0 +1,30 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// formatFR renders FR as "OF=0 SF=1 ZF=0".
+func formatFR(fr int) string {
+	parts := make([]string, len(flagBits))
+	for i, fb := range flagBits {
+		bit := 0
+		if fr&fb.bit != 0 {
+			bit = 1
+		}
+		parts[i] = fmt.Sprintf("%s=%d", fb.name, bit)
+	}
+	return strings.Join(parts, " ")
+}
+
+// formatFRBits renders FR as three binary digits in OF, SF, ZF order.
+func formatFRBits(fr int) string {
+	return fmt.Sprintf("%d%d%d", (fr>>2)%2, (fr>>1)%2, fr%2)
+}
+
+// formatWord renders a register or memory word as hex with its signed
+// decimal value.
+func formatWord(val int) string {
+	return fmt.Sprintf("#%s(%s)", hex(val, 4), spacePadding(signed(val), 6))
+}
@@ -0,0 +1,30 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestFormatHelpers(t *testing.T) {
+	if s := formatFR(FR_MINUS); s != "OF=0 SF=1 ZF=0" {
+		t.Errorf("Unexpected FR: %q", s)
+	}
+	if s := formatFRBits(FR_OVER | FR_ZERO); s != "101" {
+		t.Errorf("Unexpected FR bits: %q", s)
+	}
+	if s := formatWord(0xffff); s != "#ffff(    -1)" {
+		t.Errorf("Unexpected word: %q", s)
+	}
+
+	c := assembleSource(t, `MAIN	START
+	NOP
+LOOP	LAD	GR1,1,GR1
+	JUMP	LOOP
+	END
+`)
+	if s := c.formatAddr(3); s != "#0003 <LOOP+2>" {
+		t.Errorf("Unexpected address: %q", s)
+	}
+	if s := c.formatAddr(0xff00); s != "#ff00" {
+		t.Errorf("Unexpected address: %q", s)
+	}
+}
@@ -0,0 +1,282 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"math/rand"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// inputGen produces one input line.
+type inputGen func(r *rand.Rand) string
+
+// parseInputSpec parses an input specification. Items are separated by
+// spaces and each produces one line:
+//
+//	LO..HI    random integer in the range (both bounds are integers)
+//	A|B|C     one of the choices
+//	word:N    random lowercase word of 1 to N letters
+//	TEXT      TEXT itself, such as "..." or "a..z"
+//
+// An item may be prefixed with "K*" to repeat it K times, so
+// "3 3*0..100" is a count followed by three integers.
+func parseInputSpec(spec string) ([]inputGen, error) {
+	var gens []inputGen
+
+	for _, item := range strings.Fields(spec) {
+		repeat := 1
+		if idx := strings.Index(item, "*"); idx > 0 {
+			n, err := strconv.Atoi(item[:idx])
+			if err != nil || n < 0 {
+				return nil, fmt.Errorf("invalid repeat count in \"%s\"", item)
+			}
+			repeat = n
+			item = item[idx+1:]
+		}
+
+		gen, err := parseInputItem(item)
+		if err != nil {
+			return nil, err
+		}
+		for i := 0; i < repeat; i++ {
+			gens = append(gens, gen)
+		}
+	}
+
+	return gens, nil
+}
+
+func parseInputItem(item string) (inputGen, error) {
+	if lo, hi, ok := parseIntRange(item); ok {
+		if lo > hi {
+			return nil, fmt.Errorf("invalid range \"%s\"", item)
+		}
+		return func(r *rand.Rand) string {
+			return strconv.Itoa(lo + r.Intn(hi-lo+1))
+		}, nil
+	}
+
+	if strings.Contains(item, "|") {
+		choices := strings.Split(item, "|")
+		return func(r *rand.Rand) string {
+			return choices[r.Intn(len(choices))]
+		}, nil
+	}
+
+	if strings.HasPrefix(item, "word:") {
+		n, err := strconv.Atoi(item[5:])
+		if err != nil || n < 1 {
+			return nil, fmt.Errorf("invalid word length in \"%s\"", item)
+		}
+		return func(r *rand.Rand) string {
+			word := make([]byte, 1+r.Intn(n))
+			for i := range word {
+				word[i] = byte('a' + r.Intn(26))
+			}
+			return string(word)
+		}, nil
+	}
+
+	return func(r *rand.Rand) string {
+		return item
+	}, nil
+}
+
+// parseIntRange splits "LO..HI" into its bounds. ok is false unless both
+// are integers, so that other text with ".." is taken literally.
+func parseIntRange(item string) (lo int, hi int, ok bool) {
+	from, to, found := strings.Cut(item, "..")
+	if !found {
+		return 0, 0, false
+	}
+	lo, err1 := strconv.Atoi(from)
+	hi, err2 := strconv.Atoi(to)
+	return lo, hi, err1 == nil && err2 == nil
+}
+
+// runToHalt runs obj on inputs and returns how it halted.
+func runToHalt(obj *Object, inputs []string) lockstepEvent {
+	s := newLockstepSide(LoadObject(obj), inputs)
+	for {
+		ev := s.next(LOCKSTEP_MAX_STEPS)
+		if ev.Kind == LOCKSTEP_HALT {
+			return ev
+		}
+	}
+}
+
+// fuzzCase is one run of -fuzz as saved in a -fuzz-corpus directory, so
+// that a failure can be replayed exactly with -fuzz-case.
+type fuzzCase struct {
+	ID     string   `json:"id"`
+	Spec   string   `json:"spec"`
+	Seed   int64    `json:"seed"`
+	Run    int      `json:"run"`
+	Inputs []string `json:"inputs"`
+	Passed bool     `json:"passed"`
+	// Report describes how the run failed, as printed.
+	Report []string `json:"report,omitempty"`
+}
+
+// fuzzCaseID names run number run of the given seed.
+func fuzzCaseID(seed int64, run int) string {
+	return fmt.Sprintf("%d-%d", seed, run)
+}
+
+// checkFuzzInputs runs obj on inputs, in lockstep with refObj when given,
+// and fills in the outcome of fc.
+func checkFuzzInputs(fc *fuzzCase, obj *Object, refObj *Object) {
+	fc.Passed, fc.Report = true, nil
+	if refObj != nil {
+		d := runLockstep(LoadObject(refObj), LoadObject(obj), fc.Inputs, LOCKSTEP_MAX_STEPS)
+		if d == nil {
+			return
+		}
+		fc.Passed = false
+		fc.Report = []string{
+			fmt.Sprintf("diverged with inputs %q", fc.Inputs),
+			fmt.Sprintf("  reference: %s %q", d.Left.Kind, d.Left.Text),
+			fmt.Sprintf("  program:   %s %q", d.Right.Kind, d.Right.Text),
+		}
+		for _, line := range d.State {
+			fc.Report = append(fc.Report, fmt.Sprintf("  final state differs: %s", line))
+		}
+		return
+	}
+
+	ev := runToHalt(obj, fc.Inputs)
+	if !strings.Contains(ev.Text, "Program finished") {
+		fc.Passed = false
+		fc.Report = []string{fmt.Sprintf("failed with inputs %q: %s", fc.Inputs, ev.Text)}
+	}
+}
+
+// printFuzzFailure reports a failed case with its ID.
+func printFuzzFailure(fc *fuzzCase) {
+	cometPrint(colorRedYellow(fmt.Sprintf("[Fuzz] Run %d (case %s) %s", fc.Run, fc.ID, fc.Report[0])))
+	for _, line := range fc.Report[1:] {
+		cometPrint(line)
+	}
+}
+
+// saveFuzzCase writes fc as ID.json in dir.
+func saveFuzzCase(dir string, fc *fuzzCase) error {
+	data, err := json.MarshalIndent(fc, "", "  ")
+	if err != nil {
+		return err
+	}
+	return os.WriteFile(filepath.Join(dir, fc.ID+".json"), append(data, '\n'), 0644)
+}
+
+// loadFuzzCase reads the case ID from dir.
+func loadFuzzCase(dir string, id string) (*fuzzCase, error) {
+	data, err := os.ReadFile(filepath.Join(dir, id+".json"))
+	if err != nil {
+		return nil, err
+	}
+	fc := &fuzzCase{}
+	if err := json.Unmarshal(data, fc); err != nil {
+		return nil, fmt.Errorf("%s: %v", id, err)
+	}
+	return fc, nil
+}
+
+// loadFuzzReference assembles the -diff reference program, if any.
+func loadFuzzReference(refFilepath string) *Object {
+	if refFilepath == "" {
+		return nil
+	}
+	refObj, err := assemble(refFilepath, newAssemblerState())
+	if err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	return refObj
+}
+
+// runFuzzMode runs the program on randomly generated inputs. With a
+// reference program each run is compared in lockstep; otherwise runs that
+// do not finish normally are reported. With a corpus directory every case
+// is saved there.
+func runFuzzMode(spec string, runs int, seed int64, obj *Object, refFilepath string, corpus string) {
+	gens, err := parseInputSpec(spec)
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Invalid input specification: %v\n", err)
+		os.Exit(1)
+	}
+	refObj := loadFuzzReference(refFilepath)
+	if corpus != "" {
+		if err := os.MkdirAll(corpus, 0755); err != nil {
+			fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Cannot create fuzz corpus: %v\n", err)
+			os.Exit(1)
+		}
+	}
+
+	if seed == 0 {
+		seed = time.Now().UnixNano()
+	}
+	r := rand.New(rand.NewSource(seed))
+	cometPrint(fmt.Sprintf("[Fuzz] %d runs, seed %d", runs, seed))
+
+	failures := 0
+	for run := 1; run <= runs; run++ {
+		fc := &fuzzCase{ID: fuzzCaseID(seed, run), Spec: spec, Seed: seed, Run: run, Inputs: make([]string, len(gens))}
+		for i, gen := range gens {
+			fc.Inputs[i] = gen(r)
+		}
+
+		checkFuzzInputs(fc, obj, refObj)
+		if !fc.Passed {
+			failures++
+			printFuzzFailure(fc)
+		}
+		if corpus != "" {
+			if err := saveFuzzCase(corpus, fc); err != nil {
+				fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Cannot save fuzz case: %v\n", err)
+				os.Exit(1)
+			}
+		}
+	}
+
+	cometPrint(fmt.Sprintf("[Fuzz] %d / %d runs passed", runs-failures, runs))
+	if corpus != "" {
+		cometPrint(fmt.Sprintf("[Fuzz] Cases saved in %s; replay one with -fuzz-corpus %s -fuzz-case ID", corpus, corpus))
+	}
+	if failures > 0 {
+		os.Exit(1)
+	}
+	os.Exit(0)
+}
+
+// runFuzzCase replays the case id saved in corpus on the program and
+// reports whether it passes now, and whether that changed.
+func runFuzzCase(corpus string, id string, obj *Object, refFilepath string) {
+	if corpus == "" {
+		fmt.Fprintln(os.Stderr, "[COMET2 ERROR] -fuzz-case needs -fuzz-corpus")
+		os.Exit(1)
+	}
+	fc, err := loadFuzzCase(corpus, id)
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Cannot read fuzz case: %v\n", err)
+		os.Exit(1)
+	}
+	refObj := loadFuzzReference(refFilepath)
+
+	before := fc.Passed
+	checkFuzzInputs(fc, obj, refObj)
+	cometPrint(fmt.Sprintf("[Fuzz] Case %s: spec %q, seed %d, run %d, inputs %q", fc.ID, fc.Spec, fc.Seed, fc.Run, fc.Inputs))
+	if !fc.Passed {
+		printFuzzFailure(fc)
+		os.Exit(1)
+	}
+	if before {
+		cometPrint("[Fuzz] Passed")
+	} else {
+		cometPrint("[Fuzz] Passed (failed when it was saved)")
+	}
+	os.Exit(0)
+}
@@ -0,0 +1,105 @@
+package main
+
+import (
+	"math/rand"
+	"reflect"
+	"strconv"
+	"testing"
+)
+
+func TestFuzzCorpus(t *testing.T) {
+	obj := assembleObject(t, echoProgram)
+	ref := assembleObject(t, "MAIN\tSTART\n\tIN\tBUF,LEN\n\tOUT\tX,ONE\n\tRET\nBUF\tDS\t16\nLEN\tDS\t1\nX\tDC\t'x'\nONE\tDC\t1\n\tEND\n")
+
+	fc := &fuzzCase{ID: fuzzCaseID(42, 3), Spec: "a..z", Seed: 42, Run: 3, Inputs: []string{"x"}}
+	checkFuzzInputs(fc, obj, ref)
+	if !fc.Passed || fc.Report != nil {
+		t.Fatalf("Expected the case to pass, got %q", fc.Report)
+	}
+	fc.Inputs = []string{"y"}
+	checkFuzzInputs(fc, obj, ref)
+	if fc.Passed || len(fc.Report) != 3 {
+		t.Fatalf("Expected a divergence, got %v %q", fc.Passed, fc.Report)
+	}
+
+	dir := t.TempDir()
+	if err := saveFuzzCase(dir, fc); err != nil {
+		t.Fatalf("Failed to save case: %v", err)
+	}
+	loaded, err := loadFuzzCase(dir, "42-3")
+	if err != nil {
+		t.Fatalf("Failed to load case: %v", err)
+	}
+	if !reflect.DeepEqual(loaded, fc) {
+		t.Errorf("Expected %+v, got %+v", fc, loaded)
+	}
+	if _, err := loadFuzzCase(dir, "42-4"); err == nil {
+		t.Errorf("Expected an error for a missing case")
+	}
+}
+
+func TestParseInputSpec(t *testing.T) {
+	between := func(lo, hi int) func(string) bool {
+		return func(line string) bool {
+			n, err := strconv.Atoi(line)
+			return err == nil && n >= lo && n <= hi
+		}
+	}
+	equals := func(want string) func(string) bool {
+		return func(line string) bool { return line == want }
+	}
+	tests := []struct {
+		spec  string
+		lines int
+		valid func(string) bool
+	}{
+		{"0..100", 1, between(0, 100)},
+		{"-10..-5", 1, between(-10, -5)},
+		{"-3..3", 1, between(-3, 3)},
+		{"7..7", 1, equals("7")},
+		{"yes|no", 1, func(line string) bool { return line == "yes" || line == "no" }},
+		{"word:3", 1, func(line string) bool {
+			for _, ch := range line {
+				if ch < 'a' || ch > 'z' {
+					return false
+				}
+			}
+			return len(line) >= 1 && len(line) <= 3
+		}},
+		{"3*0..9", 3, between(0, 9)},
+		{"0*x", 0, nil},
+		{"hello", 1, equals("hello")},
+		// ".." between anything but two integers is literal text
+		{"...", 1, equals("...")},
+		{"a..z", 1, equals("a..z")},
+		{"1..x", 1, equals("1..x")},
+	}
+	r := rand.New(rand.NewSource(1))
+	for _, tt := range tests {
+		gens, err := parseInputSpec(tt.spec)
+		if err != nil {
+			t.Errorf("%s: %v", tt.spec, err)
+			continue
+		}
+		if len(gens) != tt.lines {
+			t.Errorf("%s: expected %d lines, got %d", tt.spec, tt.lines, len(gens))
+			continue
+		}
+		for i := 0; i < 50; i++ {
+			for _, gen := range gens {
+				if line := gen(r); !tt.valid(line) {
+					t.Errorf("%s: unexpected line %q", tt.spec, line)
+				}
+			}
+		}
+	}
+
+	if gens, err := parseInputSpec("3 3*0..100"); err != nil || len(gens) != 4 {
+		t.Errorf("Expected a count and three integers, got %d lines (%v)", len(gens), err)
+	}
+	for _, bad := range []string{"5..1", "word:0", "word:x", "x*3", "-1*3"} {
+		if _, err := parseInputSpec(bad); err == nil {
+			t.Errorf("Expected an error for %q", bad)
+		}
+	}
+}
@@ -0,0 +1,159 @@
+package main
+
+import (
+	"bufio"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"runtime"
+	"sort"
+	"strings"
+)
+
+// expectPathFor returns the expected output file of source in expects,
+// named after the file alone as in test/test_expects, or as the expect
+// of its test case says.
+func expectPathFor(expects string, source string, tc TestCase) string {
+	if tc.Expect != "" {
+		return filepath.Join(expects, tc.Expect)
+	}
+	return filepath.Join(expects, filepath.Base(source)+".out")
+}
+
+// confirmUpdate asks on w whether to overwrite path and reads the answer
+// from answers. Anything but y or yes, or no answer, keeps the file.
+func confirmUpdate(w io.Writer, answers *bufio.Scanner, path string) bool {
+	fmt.Fprintf(w, "Update %s? [y/N] ", path)
+	if !answers.Scan() {
+		fmt.Fprintln(w)
+		return false
+	}
+	answer := strings.ToLower(strings.TrimSpace(answers.Text()))
+	return answer == "y" || answer == "yes"
+}
+
+// runGenExpectCommand implements "c2c2 genexpect DIR": it runs every .cas
+// file under DIR, the reference solutions, on its inputs and writes what
+// "c2c2 -n -q -r" shows into the expected output files, so that a corpus
+// of expectations is kept up to date by running the references again.
+// Files that exist and differ are shown as a diff and only overwritten
+// once confirmed on in, or with -yes. It returns the exit status: 1 when
+// a program could not be run, or with -dry-run when a file would change.
+func runGenExpectCommand(args []string, in io.Reader, w io.Writer) int {
+	fs := flag.NewFlagSet("genexpect", flag.ContinueOnError)
+	fs.SetOutput(w)
+	inputsPath := fs.String("inputs", "", "JSON test spec giving the IN lines of the programs, by path or file name")
+	expectsDir := fs.String("expects", "", "directory of the expected output files (default: test_expects next to DIR)")
+	yes := fs.Bool("yes", false, "overwrite changed files without asking")
+	dryRun := fs.Bool("dry-run", false, "show what would change without writing anything")
+	jobs := fs.Int("jobs", runtime.NumCPU(), "number of programs run at the same time")
+	maxSteps := fs.Int("max-steps", TEST_MAX_STEPS, "instruction limit of each program")
+	timeout := fs.Duration("timeout", TEST_TIMEOUT, "wall-clock limit of each program")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 genexpect DIR [-inputs FILE] [-expects DIR] [-yes | -dry-run] [-jobs N]\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	// The directory may come before or after the options
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() == 0 {
+		fs.Usage()
+		return 2
+	}
+	dir := fs.Arg(0)
+	if err := fs.Parse(fs.Args()[1:]); err != nil {
+		return 2
+	}
+	if fs.NArg() > 0 {
+		fs.Usage()
+		return 2
+	}
+	expects := *expectsDir
+	if expects == "" {
+		expects = filepath.Join(filepath.Dir(filepath.Clean(dir)), "test_expects")
+	}
+
+	inputs, err := loadCorpusInputs(*inputsPath)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+	programs, err := runCorpus(dir, inputs, specOptions{jobs: *jobs, maxSteps: *maxSteps, timeout: *timeout})
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+
+	var names []string
+	for name := range programs {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+
+	answers := bufio.NewScanner(in)
+	written, unchanged, kept, skipped, failed := 0, 0, 0, 0, 0
+	for _, name := range names {
+		r := programs[name]
+		tc, ok := inputs[name]
+		if !ok {
+			tc = inputs[filepath.Base(name)]
+		}
+		path := expectPathFor(expects, name, tc)
+		switch {
+		case r.Error != "":
+			fmt.Fprintf(w, "error %s: %s\n", name, r.Error)
+			failed++
+			continue
+		case strings.HasSuffix(r.Transcript, fmt.Sprintf("Timed out after %v\n", *timeout)):
+			fmt.Fprintf(w, "error %s: timed out after %v\n", name, *timeout)
+			failed++
+			continue
+		case tc.Compare == COMPARE_REGEX:
+			fmt.Fprintf(w, "skip %s: the expectation is a regular expression\n", name)
+			skipped++
+			continue
+		}
+
+		old, err := os.ReadFile(path)
+		switch {
+		case err == nil && string(old) == r.Transcript:
+			unchanged++
+			continue
+		case err == nil:
+			fmt.Fprintf(w, "changed %s\n%s", path, lineDiff(string(old), r.Transcript))
+			if *dryRun || (!*yes && !confirmUpdate(w, answers, path)) {
+				kept++
+				continue
+			}
+		case os.IsNotExist(err):
+			fmt.Fprintf(w, "new %s\n", path)
+			if *dryRun {
+				kept++
+				continue
+			}
+		default:
+			fmt.Fprintf(w, "error %s: %v\n", name, err)
+			failed++
+			continue
+		}
+		err = os.MkdirAll(filepath.Dir(path), 0755)
+		if err == nil {
+			err = os.WriteFile(path, []byte(r.Transcript), 0644)
+		}
+		if err != nil {
+			fmt.Fprintf(w, "error %s: cannot write %s: %v\n", name, path, err)
+			failed++
+			continue
+		}
+		written++
+	}
+
+	fmt.Fprintf(w, "%d written, %d unchanged, %d kept, %d skipped, %d failed\n", written, unchanged, kept, skipped, failed)
+	if failed > 0 || (*dryRun && kept > 0) {
+		return 1
+	}
+	return 0
+}
@@ -0,0 +1,70 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestGenExpect(t *testing.T) {
+	root := t.TempDir()
+	samples := filepath.Join(root, "samples")
+	write := func(name string, src string) {
+		t.Helper()
+		path := filepath.Join(samples, filepath.FromSlash(name))
+		if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+			t.Fatal(err)
+		}
+		if err := os.WriteFile(path, []byte(src), 0644); err != nil {
+			t.Fatal(err)
+		}
+	}
+	write("a/echo.cas", echoProgram)
+	write("b.cas", "MAIN\tSTART\n\tOUT\tX,ONE\n\tRET\nX\tDC\t'x'\nONE\tDC\t1\n\tEND\n")
+	inputs := filepath.Join(root, "input.json")
+	if err := os.WriteFile(inputs, []byte(`{"echo.cas": ["hi"]}`), 0644); err != nil {
+		t.Fatal(err)
+	}
+	expectB := filepath.Join(root, "test_expects", "b.cas.out")
+	readB := func() string {
+		t.Helper()
+		data, err := os.ReadFile(expectB)
+		if err != nil {
+			t.Fatal(err)
+		}
+		return string(data)
+	}
+
+	var out strings.Builder
+	if code := runGenExpectCommand([]string{samples, "-inputs", inputs}, strings.NewReader(""), &out); code != 0 {
+		t.Fatalf("Expected the files to be written, got %d: %s", code, out.String())
+	}
+	if got := readB(); got != "OUT> x\nProgram finished (RET)\n" {
+		t.Errorf("Unexpected expected output %q", got)
+	}
+	if _, err := os.Stat(filepath.Join(root, "test_expects", "echo.cas.out")); err != nil {
+		t.Errorf("Expected echo.cas.out named after the file alone: %v", err)
+	}
+
+	// A changed file is only overwritten once confirmed
+	write("b.cas", "MAIN\tSTART\n\tOUT\tX,ONE\n\tRET\nX\tDC\t'y'\nONE\tDC\t1\n\tEND\n")
+	out.Reset()
+	if code := runGenExpectCommand([]string{samples, "-inputs", inputs}, strings.NewReader("n\n"), &out); code != 0 {
+		t.Fatalf("Expected success, got %d: %s", code, out.String())
+	}
+	want := "changed " + expectB + "\n-   1: OUT> x\n+   1: OUT> y\nUpdate " + expectB + "? [y/N] 0 written, 1 unchanged, 1 kept, 0 skipped, 0 failed\n"
+	if out.String() != want || readB() != "OUT> x\nProgram finished (RET)\n" {
+		t.Errorf("Expected\n%s\ngot\n%s", want, out.String())
+	}
+
+	out.Reset()
+	if code := runGenExpectCommand([]string{"-dry-run", "-inputs", inputs, samples}, strings.NewReader("y\n"), &out); code != 1 || strings.Contains(out.String(), "Update") {
+		t.Errorf("Expected -dry-run to report the change without asking, got %d: %s", code, out.String())
+	}
+
+	out.Reset()
+	if code := runGenExpectCommand([]string{samples, "-inputs", inputs}, strings.NewReader("y\n"), &out); code != 0 || readB() != "OUT> y\nProgram finished (RET)\n" {
+		t.Errorf("Expected the file to be updated, got %d: %s", code, out.String())
+	}
+}
@@ -0,0 +1,236 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"runtime"
+	"strings"
+)
+
+// Extension of golden listing files
+const LISTING_EXT = ".lst"
+
+// assembleListing assembles path and returns the listing that -a prints.
+func assembleListing(path string) (string, error) {
+	asmState := newAssemblerState()
+	if _, err := assemble(path, asmState); err != nil {
+		return "", err
+	}
+	return "CASL LISTING\n\n" + strings.Join(asmState.outdump, "\n") + "\n", nil
+}
+
+// goldenPath returns the golden listing file for source in dir, or next
+// to the source when dir is empty.
+func goldenPath(source string, dir string) string {
+	name := strings.TrimSuffix(filepath.Base(source), filepath.Ext(source)) + LISTING_EXT
+	if dir == "" {
+		dir = filepath.Dir(source)
+	}
+	return filepath.Join(dir, name)
+}
+
+// compareGolden compares actual with the golden file. With update, the
+// golden file is rewritten instead. It returns a readable diff, which is
+// empty when they match.
+func compareGolden(golden string, actual string, update bool) (string, error) {
+	if update {
+		if err := os.WriteFile(golden, []byte(actual), 0644); err != nil {
+			return "", fmt.Errorf("[CASL2 ERROR] Cannot write golden file: %v", err)
+		}
+		return "", nil
+	}
+
+	expected, err := os.ReadFile(golden)
+	if err != nil {
+		return "", fmt.Errorf("[CASL2 ERROR] Cannot read golden file: %v", err)
+	}
+	if string(expected) == actual {
+		return "", nil
+	}
+	return lineDiff(string(expected), actual), nil
+}
+
+// lineDiff shows the lines of want missing from got as "-" and the extra
+// lines of got as "+", with the line number in want and got. Matching
+// lines are left out.
+func lineDiff(want string, got string) string {
+	a := strings.Split(strings.TrimSuffix(want, "\n"), "\n")
+	b := strings.Split(strings.TrimSuffix(got, "\n"), "\n")
+
+	// lcs[i][j] is the length of the longest common subsequence of a[i:] and b[j:]
+	lcs := make([][]int, len(a)+1)
+	for i := range lcs {
+		lcs[i] = make([]int, len(b)+1)
+	}
+	for i := len(a) - 1; i >= 0; i-- {
+		for j := len(b) - 1; j >= 0; j-- {
+			if a[i] == b[j] {
+				lcs[i][j] = lcs[i+1][j+1] + 1
+			} else {
+				lcs[i][j] = max(lcs[i+1][j], lcs[i][j+1])
+			}
+		}
+	}
+
+	var out strings.Builder
+	i, j := 0, 0
+	for i < len(a) || j < len(b) {
+		switch {
+		case i < len(a) && j < len(b) && a[i] == b[j]:
+			i++
+			j++
+		case i < len(a) && (j == len(b) || lcs[i+1][j] >= lcs[i][j+1]):
+			fmt.Fprintf(&out, "-%4d: %s\n", i+1, a[i])
+			i++
+		default:
+			fmt.Fprintf(&out, "+%4d: %s\n", j+1, b[j])
+			j++
+		}
+	}
+	return out.String()
+}
+
+// firstDifference returns the line and column, both from 1, of the first
+// character where got differs from want. ok is false when they are equal.
+// A line missing from one side differs at column 1.
+func firstDifference(want string, got string) (line int, col int, ok bool) {
+	a := strings.Split(strings.TrimSuffix(want, "\n"), "\n")
+	b := strings.Split(strings.TrimSuffix(got, "\n"), "\n")
+	for i := 0; i < max(len(a), len(b)); i++ {
+		if i >= len(a) || i >= len(b) {
+			return i + 1, 1, true
+		}
+		if a[i] == b[i] {
+			continue
+		}
+		ra, rb := []rune(a[i]), []rune(b[i])
+		col := 0
+		for col < len(ra) && col < len(rb) && ra[col] == rb[col] {
+			col++
+		}
+		return i + 1, col + 1, true
+	}
+	return 0, 0, false
+}
+
+// formatFirstDifference shows the first differing line of want and got
+// one above the other with a caret under the first differing character,
+// or "" when they are equal.
+func formatFirstDifference(want string, got string) string {
+	line, col, ok := firstDifference(want, got)
+	if !ok {
+		return ""
+	}
+	text := func(s string) string {
+		lines := strings.Split(strings.TrimSuffix(s, "\n"), "\n")
+		if line > len(lines) {
+			return "(no line)"
+		}
+		return lines[line-1]
+	}
+	return fmt.Sprintf("first difference at line %d, column %d:\n  expected: %s\n  actual:   %s\n  %s^\n",
+		line, col, text(want), text(got), strings.Repeat(" ", len("actual:   ")+col-1))
+}
+
+// runTestCommand implements "c2c2 test". It returns the exit status.
+func runTestCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("test", flag.ContinueOnError)
+	fs.SetOutput(w)
+	golden := fs.Bool("golden", false, "compare the listing of each file with its "+LISTING_EXT+" golden file")
+	update := fs.Bool("update-golden", false, "write the golden files instead of comparing")
+	dir := fs.String("golden-dir", "", "directory of the golden files (default: next to each source)")
+	spec := fs.String("spec", "", "run the expected-output tests of the given JSON test spec")
+	jobs := fs.Int("jobs", runtime.NumCPU(), "number of -spec tests run at the same time")
+	maxSteps := fs.Int("max-steps", TEST_MAX_STEPS, "default instruction limit of each -spec test")
+	timeout := fs.Duration("timeout", TEST_TIMEOUT, "default wall-clock limit of each -spec test")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 test                (test specs of %s)\n", PROJECT_FILE)
+		fmt.Fprintf(w, "       c2c2 test -golden [-update-golden] [-golden-dir DIR] <casl2file> ...\n")
+		fmt.Fprintf(w, "       c2c2 test -spec FILE [-jobs N] [-max-steps N] [-timeout D]\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+
+	opts := specOptions{jobs: *jobs, maxSteps: *maxSteps, timeout: *timeout}
+	specs := []string{*spec}
+	if *spec == "" && !*golden && !*update && fs.NArg() == 0 {
+		// Without arguments, run the tests of the project manifest
+		if _, err := os.Stat(PROJECT_FILE); err != nil {
+			fs.Usage()
+			return 2
+		}
+		p, err := loadProject(".")
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 2
+		}
+		if len(p.Tests) == 0 {
+			fmt.Fprintf(w, "[COMET2 ERROR] %s lists no tests\n", PROJECT_FILE)
+			return 2
+		}
+		opts.project = p
+		specs = p.Tests
+	}
+
+	if specs[0] != "" {
+		total, failed := 0, 0
+		for _, path := range specs {
+			n, f, err := runSpec(path, w, opts)
+			if err != nil {
+				fmt.Fprintln(w, err)
+				return 2
+			}
+			total += n
+			failed += f
+		}
+		fmt.Fprintf(w, "%d passed, %d failed\n", total-failed, failed)
+		if failed > 0 {
+			return 1
+		}
+		return 0
+	}
+
+	if !*golden && !*update {
+		fs.Usage()
+		return 2
+	}
+	if fs.NArg() == 0 {
+		fmt.Fprintln(w, "[CASL2 ERROR] No casl2 source file is specified.")
+		return 2
+	}
+
+	failed := 0
+	for _, source := range fs.Args() {
+		path := goldenPath(source, *dir)
+		listing, err := assembleListing(source)
+		if err == nil {
+			var diff string
+			diff, err = compareGolden(path, listing, *update)
+			if err == nil && diff != "" {
+				fmt.Fprintf(w, "FAIL %s (%s)\n%s", source, path, diff)
+				failed++
+				continue
+			}
+		}
+		switch {
+		case err != nil:
+			fmt.Fprintf(w, "FAIL %s: %v\n", source, err)
+			failed++
+		case *update:
+			fmt.Fprintf(w, "UPDATED %s\n", path)
+		default:
+			fmt.Fprintf(w, "ok %s\n", source)
+		}
+	}
+
+	fmt.Fprintf(w, "%d passed, %d failed\n", fs.NArg()-failed, failed)
+	if failed > 0 {
+		return 1
+	}
+	return 0
+}
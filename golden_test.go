@@ -0,0 +1,74 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestGoldenListing(t *testing.T) {
+	dir := t.TempDir()
+	source := filepath.Join(dir, "prog.cas")
+	write := func(src string) {
+		t.Helper()
+		if err := os.WriteFile(source, []byte(src), 0644); err != nil {
+			t.Fatalf("Failed to write source: %v", err)
+		}
+	}
+
+	write("MAIN\tSTART\n\tLAD\tGR1,1\n\tRET\n\tEND\n")
+	var out bytes.Buffer
+	if status := runTestCommand([]string{"-update-golden", source}, &out); status != 0 {
+		t.Fatalf("Update failed: %s", out.String())
+	}
+	golden, err := os.ReadFile(filepath.Join(dir, "prog"+LISTING_EXT))
+	if err != nil || !strings.Contains(string(golden), "   2 0000 1210\t\tLAD\tGR1,1") {
+		t.Fatalf("Unexpected golden file %q (%v)", golden, err)
+	}
+
+	out.Reset()
+	if status := runTestCommand([]string{"-golden", source}, &out); status != 0 {
+		t.Errorf("Expected listing to match, got %s", out.String())
+	}
+
+	write("MAIN\tSTART\n\tLAD\tGR1,2\n\tRET\n\tEND\n")
+	out.Reset()
+	if status := runTestCommand([]string{"-golden", source}, &out); status != 1 {
+		t.Errorf("Expected listing to differ, got %s", out.String())
+	}
+	if !strings.Contains(out.String(), "-   4:    2      0001") || !strings.Contains(out.String(), "+   4:    2      0002") {
+		t.Errorf("Expected a diff of the operand word, got %s", out.String())
+	}
+}
+
+func TestLineDiff(t *testing.T) {
+	got := lineDiff("a\nb\nc\n", "a\nx\nc\nd\n")
+	want := "-   2: b\n+   2: x\n+   4: d\n"
+	if got != want {
+		t.Errorf("Expected %q, got %q", want, got)
+	}
+}
+
+func TestFirstDifference(t *testing.T) {
+	for _, tc := range []struct {
+		want, got string
+		line, col int
+	}{
+		{"a\nabcd\n", "a\nabXd\n", 2, 3},
+		{"a\n", "a\nb\n", 2, 1},
+		{"abc\n", "ab\n", 1, 3},
+		{"a\nb\n", "a\nb\n", 0, 0},
+	} {
+		line, col, _ := firstDifference(tc.want, tc.got)
+		if line != tc.line || col != tc.col {
+			t.Errorf("%q, %q: expected line %d column %d, got %d %d", tc.want, tc.got, tc.line, tc.col, line, col)
+		}
+	}
+
+	want := "first difference at line 1, column 3:\n  expected: abc\n  actual:   abX\n              ^\n"
+	if got := formatFirstDifference("abc\n", "abX\n"); got != want {
+		t.Errorf("Expected %q, got %q", want, got)
+	}
+}
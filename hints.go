@@ -0,0 +1,201 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+// defaultHintRules are the rules of -hints, in the syntax of a
+// -hint-rules file.
+const defaultHintRules = `
+max-repeat:MULA >= 50: MULA at {at} ran {n} times; in a loop, a multiplication by a power of two is cheaper as a shift (SLA)
+max-repeat:MULL >= 50: MULL at {at} ran {n} times; in a loop, a multiplication by a power of two is cheaper as a shift (SLL)
+max-repeat:DIVA >= 50: DIVA at {at} ran {n} times; in a loop, a division by a power of two is cheaper as a shift (SRA)
+max-repeat:DIVL >= 50: DIVL at {at} ran {n} times; in a loop, a division by a power of two is cheaper as a shift (SRL)
+max-stack >= 200: The stack reached {n} words; check the base case of the recursion and that every PUSH has its POP
+svcs >= 100: The program made {n} IN/OUT calls; collecting a line in a buffer and writing it with one OUT needs fewer
+`
+
+// hintMetrics are the measures a hint rule can test, with whether they
+// take a mnemonic after a colon (count:MULA).
+var hintMetrics = map[string]bool{
+	"steps":      false,
+	"reads":      false,
+	"writes":     false,
+	"svcs":       false,
+	"max-stack":  false,
+	"count":      true,
+	"max-repeat": true,
+}
+
+var hintRulePattern = regexp.MustCompile(`^(\S+)\s*>=\s*(\d+)\s*:\s*(.+)$`)
+
+// hintRule shows Message when Metric is at least Min after the run. The
+// message may refer to the value as {n} and, for max-repeat, to the
+// address of the instruction as {at}.
+type hintRule struct {
+	Metric  string
+	Min     int
+	Message string
+}
+
+// parseHintRules reads rules, one per line as "METRIC >= N: MESSAGE".
+// Lines starting with # are comments.
+func parseHintRules(text string) ([]hintRule, error) {
+	var rules []hintRule
+	for n, line := range strings.Split(text, "\n") {
+		line = strings.TrimSpace(line)
+		if line == "" || strings.HasPrefix(line, "#") {
+			continue
+		}
+		m := hintRulePattern.FindStringSubmatch(line)
+		if m == nil {
+			return nil, fmt.Errorf("[COMET2 ERROR] Hint rule line %d: expected METRIC >= N: MESSAGE", n+1)
+		}
+		name, inst, hasInst := strings.Cut(m[1], ":")
+		takesInst, ok := hintMetrics[name]
+		if !ok || takesInst != hasInst || (hasInst && !isMachineInstruction(strings.ToUpper(inst))) {
+			return nil, fmt.Errorf("[COMET2 ERROR] Hint rule line %d: unknown metric \"%s\"", n+1, m[1])
+		}
+		metric := name
+		if hasInst {
+			metric += ":" + strings.ToUpper(inst)
+		}
+		threshold, err := strconv.Atoi(m[2])
+		if err != nil {
+			return nil, fmt.Errorf("[COMET2 ERROR] Hint rule line %d: invalid number \"%s\"", n+1, m[2])
+		}
+		rules = append(rules, hintRule{Metric: metric, Min: threshold, Message: m[3]})
+	}
+	return rules, nil
+}
+
+// loadHintRules returns the rules of -hints with those of the file at
+// path, if any, added. A rule of the file on the same metric as a
+// built-in one replaces it.
+func loadHintRules(path string) ([]hintRule, error) {
+	rules, err := parseHintRules(defaultHintRules)
+	if err != nil {
+		return nil, err
+	}
+	if path == "" {
+		return rules, nil
+	}
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read hint rules: %v", err)
+	}
+	extra, err := parseHintRules(string(content))
+	if err != nil {
+		return nil, err
+	}
+	for _, r := range extra {
+		replaced := false
+		for i := range rules {
+			if rules[i].Metric == r.Metric {
+				rules[i] = r
+				replaced = true
+			}
+		}
+		if !replaced {
+			rules = append(rules, r)
+		}
+	}
+	return rules, nil
+}
+
+// hintStats collects the measures of the hint rules during the run.
+type hintStats struct {
+	BaseObserver
+	rules  []hintRule
+	steps  int
+	reads  int
+	writes int
+	svcs   int
+	counts map[string]int
+	// runs counts the executions of the instruction at each address.
+	runs  map[int]int
+	insts map[int]string
+	// sp0 is SP at the first step and minSP the lowest SP since.
+	sp0     int
+	minSP   int
+	started bool
+}
+
+func newHintStats(rules []hintRule) *hintStats {
+	return &hintStats{rules: rules, counts: make(map[string]int), runs: make(map[int]int), insts: make(map[int]string)}
+}
+
+func (h *hintStats) BeforeStep(c *Comet2, pc int) {
+	if !h.started {
+		h.sp0, h.minSP, h.started = c.State[SP], c.State[SP], true
+	}
+	inst, _, _ := parse(c.Memory, c.State)
+	h.steps++
+	h.counts[inst]++
+	h.runs[pc]++
+	h.insts[pc] = inst
+}
+
+func (h *hintStats) AfterStep(c *Comet2, pc int) {
+	h.minSP = min(h.minSP, c.State[SP])
+}
+
+func (h *hintStats) MemoryRead(c *Comet2, addr int, val int) {
+	h.reads++
+}
+
+func (h *hintStats) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	h.writes++
+}
+
+func (h *hintStats) Svc(c *Comet2, entry int) {
+	h.svcs++
+}
+
+// value returns the measure of metric and, for max-repeat, the address
+// of the instruction that ran the most.
+func (h *hintStats) value(metric string) (int, int) {
+	name, inst, _ := strings.Cut(metric, ":")
+	switch name {
+	case "steps":
+		return h.steps, 0
+	case "reads":
+		return h.reads, 0
+	case "writes":
+		return h.writes, 0
+	case "svcs":
+		return h.svcs, 0
+	case "max-stack":
+		return h.sp0 - h.minSP, 0
+	case "count":
+		return h.counts[inst], 0
+	case "max-repeat":
+		n, at := 0, 0
+		for addr, runs := range h.runs {
+			if h.insts[addr] == inst && (runs > n || (runs == n && addr < at)) {
+				n, at = runs, addr
+			}
+		}
+		return n, at
+	}
+	return 0, 0
+}
+
+// render returns the hints of the rules that apply, in the order of the
+// rules.
+func (h *hintStats) render() string {
+	var b strings.Builder
+	for _, r := range h.rules {
+		n, at := h.value(r.Metric)
+		if n < r.Min || n == 0 {
+			continue
+		}
+		msg := strings.NewReplacer("{n}", strconv.Itoa(n), "{at}", "#"+hex(at, 4)).Replace(r.Message)
+		fmt.Fprintf(&b, "Hint: %s\n", msg)
+	}
+	return b.String()
+}
@@ -0,0 +1,53 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestHints(t *testing.T) {
+	program := `MAIN	START
+	LAD	GR1,60
+LOOP	MULA	GR2,GR3
+	SUBA	GR1,=1
+	JNZ	LOOP
+	RET
+	END
+`
+	rules, err := loadHintRules("")
+	if err != nil {
+		t.Fatal(err)
+	}
+	c := assembleSource(t, program)
+	hints := newHintStats(rules)
+	c.AddObserver(hints)
+	c.Run()
+	want := "Hint: MULA at #0002 ran 60 times; in a loop, a multiplication by a power of two is cheaper as a shift (SLA)\n"
+	if got := hints.render(); got != want {
+		t.Errorf("Expected %q, got %q", want, got)
+	}
+
+	// Rules of a file replace the built-in one on the same metric
+	path := filepath.Join(t.TempDir(), "hints.txt")
+	if err := os.WriteFile(path, []byte("# course rules\nmax-repeat:mula >= 10: use SLA at {at} ({n})\ncount:JNZ >= 100: {n} jumps\nsteps >= 1: {n} steps\n"), 0644); err != nil {
+		t.Fatal(err)
+	}
+	if rules, err = loadHintRules(path); err != nil {
+		t.Fatal(err)
+	}
+	c = assembleSource(t, program)
+	hints = newHintStats(rules)
+	c.AddObserver(hints)
+	c.Run()
+	want = "Hint: use SLA at #0002 (60)\nHint: 182 steps\n"
+	if got := hints.render(); got != want {
+		t.Errorf("Expected %q, got %q", want, got)
+	}
+
+	for _, bad := range []string{"steps > 1: x", "depth >= 1: x", "count >= 1: x", "count:MOVE >= 1: x", "steps:LD >= 1: x"} {
+		if _, err := parseHintRules(bad); err == nil {
+			t.Errorf("Expected an error for %q", bad)
+		}
+	}
+}
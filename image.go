@@ -0,0 +1,428 @@
+package main
+
+import (
+	"encoding/binary"
+	hexenc "encoding/hex"
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+// Largest image in bytes: every word of COMET2 memory
+const IMAGE_BYTES = 2 * ADDRESS_SPACE
+
+// ImageFormat reads and writes memory images in one file format. The image
+// is an Object whose Bin is loaded at address 0 and started at Start.
+//
+// Formats that store bytes keep each word as two bytes in the given byte
+// order, and their addresses are byte addresses: word address N is at byte
+// address 2N.
+type ImageFormat interface {
+	// Name is the format name of -from, -to and -import.
+	Name() string
+	// Extensions are the file extensions that select the format.
+	Extensions() []string
+	Encode(obj *Object, order binary.ByteOrder) ([]byte, error)
+	Decode(data []byte, order binary.ByteOrder) (*Object, error)
+}
+
+var imageFormats = map[string]ImageFormat{}
+
+// registerImageFormat makes f available to "c2c2 image" and, reading
+// words in big-endian order, to -import.
+func registerImageFormat(f ImageFormat) {
+	imageFormats[f.Name()] = f
+	registerImporter(imageImporter{f})
+}
+
+func init() {
+	registerImageFormat(rawFormat{})
+	registerImageFormat(ihexFormat{})
+	registerImageFormat(srecFormat{})
+	registerImageFormat(objectFormat{})
+}
+
+// imageFormatNames returns the registered format names in sorted order.
+func imageFormatNames() []string {
+	names := make([]string, 0, len(imageFormats))
+	for name := range imageFormats {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// findImageFormat returns the format called name or, when name is "",
+// the one the extension of path selects.
+func findImageFormat(name string, path string) (ImageFormat, error) {
+	if name != "" {
+		if f, ok := imageFormats[name]; ok {
+			return f, nil
+		}
+		return nil, fmt.Errorf("[COMET2 ERROR] Unknown image format \"%s\" (available: %s)", name, strings.Join(imageFormatNames(), ", "))
+	}
+	ext := strings.ToLower(filepath.Ext(path))
+	for _, f := range imageFormats {
+		for _, e := range f.Extensions() {
+			if e == ext {
+				return f, nil
+			}
+		}
+	}
+	return nil, fmt.Errorf("[COMET2 ERROR] Cannot tell the image format of \"%s\"; give it with -from or -to (%s)", path, strings.Join(imageFormatNames(), ", "))
+}
+
+// parseByteOrder reads "big" or "little".
+func parseByteOrder(name string) (binary.ByteOrder, error) {
+	switch name {
+	case "big":
+		return binary.BigEndian, nil
+	case "little":
+		return binary.LittleEndian, nil
+	}
+	return nil, fmt.Errorf("[COMET2 ERROR] Unknown byte order \"%s\" (big, little)", name)
+}
+
+// encodeWords stores each word as two bytes in order.
+func encodeWords(words []uint16, order binary.ByteOrder) []byte {
+	data := make([]byte, 2*len(words))
+	for i, w := range words {
+		order.PutUint16(data[2*i:], w)
+	}
+	return data
+}
+
+// decodeWords reads two bytes per word in order.
+func decodeWords(data []byte, order binary.ByteOrder) ([]uint16, error) {
+	if len(data)%2 != 0 {
+		return nil, fmt.Errorf("odd number of bytes (%d); each word is two bytes", len(data))
+	}
+	words := make([]uint16, len(data)/2)
+	for i := range words {
+		words[i] = order.Uint16(data[2*i:])
+	}
+	return words, nil
+}
+
+// imageBytes collects the bytes of a format with addresses, where records
+// may come in any order and leave gaps, which read as zero.
+type imageBytes struct {
+	data []byte
+}
+
+func (b *imageBytes) store(addr int, bytes []byte) error {
+	if addr+len(bytes) > IMAGE_BYTES {
+		return fmt.Errorf("data at byte address #%X is past the end of memory", addr)
+	}
+	if n := addr + len(bytes); n > len(b.data) {
+		b.data = append(b.data, make([]byte, n-len(b.data))...)
+	}
+	copy(b.data[addr:], bytes)
+	return nil
+}
+
+// object decodes the collected bytes, padding a trailing half word.
+func (b *imageBytes) object(start int, order binary.ByteOrder) (*Object, error) {
+	if start%2 != 0 {
+		return nil, fmt.Errorf("start address #%X is not the first byte of a word", start)
+	}
+	if start/2 >= ADDRESS_SPACE {
+		return nil, fmt.Errorf("start address #%X is past the end of memory", start/2)
+	}
+	if len(b.data)%2 != 0 {
+		b.data = append(b.data, 0)
+	}
+	words, err := decodeWords(b.data, order)
+	if err != nil {
+		return nil, err
+	}
+	return &Object{Bin: words, Start: start / 2}, nil
+}
+
+// checksum8 is the two's complement of the sum of bytes, as Intel HEX
+// records end with; S-records end with its ones' complement.
+func checksum8(bytes []byte) byte {
+	var sum byte
+	for _, b := range bytes {
+		sum += b
+	}
+	return -sum
+}
+
+// rawFormat is the words alone, from address 0, starting at 0.
+type rawFormat struct{}
+
+func (rawFormat) Name() string         { return "raw" }
+func (rawFormat) Extensions() []string { return []string{".bin", ".raw"} }
+
+func (rawFormat) Encode(obj *Object, order binary.ByteOrder) ([]byte, error) {
+	return encodeWords(obj.Bin, order), nil
+}
+
+func (rawFormat) Decode(data []byte, order binary.ByteOrder) (*Object, error) {
+	if len(data) > IMAGE_BYTES {
+		return nil, fmt.Errorf("%d bytes do not fit in the %d words of memory", len(data), ADDRESS_SPACE)
+	}
+	words, err := decodeWords(data, order)
+	if err != nil {
+		return nil, err
+	}
+	return &Object{Bin: words}, nil
+}
+
+// ihexFormat is Intel HEX: data records (00) of 16 bytes, extended linear
+// address records (04) above byte #FFFF, a start linear address record
+// (05) when Start is not 0, and the end of file record (01). Reading also
+// accepts extended segment (02) and start segment (03) records.
+type ihexFormat struct{}
+
+func (ihexFormat) Name() string         { return "ihex" }
+func (ihexFormat) Extensions() []string { return []string{".hex", ".ihex"} }
+
+// ihexRecord writes one record with its checksum.
+func ihexRecord(b *strings.Builder, addr int, kind byte, data []byte) {
+	record := append([]byte{byte(len(data)), byte(addr >> 8), byte(addr), kind}, data...)
+	record = append(record, checksum8(record))
+	fmt.Fprintf(b, ":%X\n", record)
+}
+
+func (ihexFormat) Encode(obj *Object, order binary.ByteOrder) ([]byte, error) {
+	var b strings.Builder
+	data := encodeWords(obj.Bin, order)
+	upper := 0
+	for addr := 0; addr < len(data); addr += 16 {
+		if addr>>16 != upper {
+			upper = addr >> 16
+			ihexRecord(&b, 0, 0x04, []byte{byte(upper >> 8), byte(upper)})
+		}
+		ihexRecord(&b, addr&0xffff, 0x00, data[addr:min(addr+16, len(data))])
+	}
+	if obj.Start != 0 {
+		start := make([]byte, 4)
+		binary.BigEndian.PutUint32(start, uint32(2*obj.Start))
+		ihexRecord(&b, 0, 0x05, start)
+	}
+	ihexRecord(&b, 0, 0x01, nil)
+	return []byte(b.String()), nil
+}
+
+func (ihexFormat) Decode(data []byte, order binary.ByteOrder) (*Object, error) {
+	var image imageBytes
+	base, start := 0, 0
+	for i, line := range strings.Split(strings.ReplaceAll(string(data), "\r\n", "\n"), "\n") {
+		line = strings.TrimSpace(line)
+		if line == "" {
+			continue
+		}
+		record, err := hexenc.DecodeString(strings.TrimPrefix(line, ":"))
+		if !strings.HasPrefix(line, ":") || err != nil || len(record) < 5 || len(record) != int(record[0])+5 {
+			return nil, fmt.Errorf("line %d: invalid Intel HEX record", i+1)
+		}
+		if checksum8(record[:len(record)-1]) != record[len(record)-1] {
+			return nil, fmt.Errorf("line %d: checksum mismatch", i+1)
+		}
+		addr, fields := int(record[1])<<8|int(record[2]), record[4:len(record)-1]
+		switch kind := record[3]; {
+		case kind == 0x00:
+			if err := image.store(base+addr, fields); err != nil {
+				return nil, fmt.Errorf("line %d: %v", i+1, err)
+			}
+		case kind == 0x01:
+			return image.object(start, order)
+		case kind == 0x02 && len(fields) == 2:
+			base = (int(fields[0])<<8 | int(fields[1])) << 4
+		case kind == 0x04 && len(fields) == 2:
+			base = (int(fields[0])<<8 | int(fields[1])) << 16
+		case kind == 0x03 && len(fields) == 4:
+			start = (int(fields[0])<<8|int(fields[1]))<<4 + (int(fields[2])<<8 | int(fields[3]))
+		case kind == 0x05 && len(fields) == 4:
+			start = int(binary.BigEndian.Uint32(fields))
+		default:
+			return nil, fmt.Errorf("line %d: unsupported record type %02X", i+1, kind)
+		}
+	}
+	return nil, fmt.Errorf("missing end of file record")
+}
+
+// srecFormat is Motorola S-records: an S0 header, S1 data records with
+// 16-bit addresses (S2 with 24-bit ones for images over #FFFF bytes) and
+// the S9 (S8) record holding Start. Reading also accepts S3 and S7
+// records and skips S5 and S6 counts.
+type srecFormat struct{}
+
+func (srecFormat) Name() string         { return "srec" }
+func (srecFormat) Extensions() []string { return []string{".srec", ".s19", ".s28", ".mot"} }
+
+// srecRecord writes one record of kind with an address of size bytes.
+func srecRecord(b *strings.Builder, kind byte, addr int, size int, data []byte) {
+	record := []byte{byte(size + len(data) + 1)}
+	for i := size - 1; i >= 0; i-- {
+		record = append(record, byte(addr>>(8*i)))
+	}
+	record = append(record, data...)
+	record = append(record, ^(-checksum8(record)))
+	fmt.Fprintf(b, "S%c%X\n", kind, record)
+}
+
+func (srecFormat) Encode(obj *Object, order binary.ByteOrder) ([]byte, error) {
+	var b strings.Builder
+	data := encodeWords(obj.Bin, order)
+	kind, end, size := byte('1'), byte('9'), 2
+	if len(data) > 0x10000 || 2*obj.Start > 0xffff {
+		kind, end, size = '2', '8', 3
+	}
+	srecRecord(&b, '0', 0, 2, []byte("c2c2"))
+	for addr := 0; addr < len(data); addr += 16 {
+		srecRecord(&b, kind, addr, size, data[addr:min(addr+16, len(data))])
+	}
+	srecRecord(&b, end, 2*obj.Start, size, nil)
+	return []byte(b.String()), nil
+}
+
+func (srecFormat) Decode(data []byte, order binary.ByteOrder) (*Object, error) {
+	var image imageBytes
+	start := 0
+	sizes := map[byte]int{'0': 2, '1': 2, '2': 3, '3': 4, '5': 2, '6': 3, '7': 4, '8': 3, '9': 2}
+	for i, line := range strings.Split(strings.ReplaceAll(string(data), "\r\n", "\n"), "\n") {
+		line = strings.TrimSpace(line)
+		if line == "" {
+			continue
+		}
+		if len(line) < 4 || line[0] != 'S' {
+			return nil, fmt.Errorf("line %d: invalid S-record", i+1)
+		}
+		kind := line[1]
+		size, ok := sizes[kind]
+		record, err := hexenc.DecodeString(line[2:])
+		if !ok || err != nil || len(record) < size+2 || len(record) != int(record[0])+1 {
+			return nil, fmt.Errorf("line %d: invalid S-record", i+1)
+		}
+		if ^(-checksum8(record[:len(record)-1])) != record[len(record)-1] {
+			return nil, fmt.Errorf("line %d: checksum mismatch", i+1)
+		}
+		addr := 0
+		for _, b := range record[1 : 1+size] {
+			addr = addr<<8 | int(b)
+		}
+		switch kind {
+		case '1', '2', '3':
+			if err := image.store(addr, record[1+size:len(record)-1]); err != nil {
+				return nil, fmt.Errorf("line %d: %v", i+1, err)
+			}
+		case '7', '8', '9':
+			start = addr
+		}
+	}
+	return image.object(start, order)
+}
+
+// objectFormat is the .c2o object of c2c2, JSON with the image, symbols
+// and source map. Words are numbers, so the byte order does not apply.
+type objectFormat struct{}
+
+func (objectFormat) Name() string         { return "c2o" }
+func (objectFormat) Extensions() []string { return []string{OBJECT_EXT} }
+
+func (objectFormat) Encode(obj *Object, order binary.ByteOrder) ([]byte, error) {
+	obj.Checksum = objectChecksum(obj)
+	data, err := json.MarshalIndent(obj, "", "  ")
+	if err != nil {
+		return nil, fmt.Errorf("Cannot encode object: %v", err)
+	}
+	return data, nil
+}
+
+func (objectFormat) Decode(data []byte, order binary.ByteOrder) (*Object, error) {
+	obj := &Object{}
+	if err := json.Unmarshal(data, obj); err != nil {
+		return nil, fmt.Errorf("invalid object file: %v", err)
+	}
+	if len(obj.Bin) > ADDRESS_SPACE {
+		return nil, fmt.Errorf("image of %d words is larger than memory", len(obj.Bin))
+	}
+	if obj.Start < 0 || obj.Start >= ADDRESS_SPACE {
+		return nil, fmt.Errorf("start address %d is outside memory", obj.Start)
+	}
+	return obj, nil
+}
+
+// imageImporter reads an image format for -import, words in big-endian
+// order.
+type imageImporter struct {
+	format ImageFormat
+}
+
+func (imp imageImporter) Name() string {
+	return imp.format.Name()
+}
+
+func (imp imageImporter) Import(data []byte) (*Object, error) {
+	return imp.format.Decode(data, binary.BigEndian)
+}
+
+// runImageCommand implements "c2c2 image INPUT OUTPUT": it converts a
+// memory image between formats, chosen by -from and -to or by the file
+// extensions. It returns the exit status.
+func runImageCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("image", flag.ContinueOnError)
+	fs.SetOutput(w)
+	from := fs.String("from", "", "format of INPUT (default: by its extension)")
+	to := fs.String("to", "", "format of OUTPUT (default: by its extension)")
+	orderName := fs.String("byte-order", "big", "order of the two bytes of a word in raw, ihex and srec images (big, little)")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 image [-from FORMAT] [-to FORMAT] [-byte-order big|little] INPUT OUTPUT\n\nFormats: %s\n\nOptions:\n", strings.Join(imageFormatNames(), ", "))
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 2 {
+		fs.Usage()
+		return 2
+	}
+	order, err := parseByteOrder(*orderName)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+	input, output := fs.Arg(0), fs.Arg(1)
+	inFormat, err := findImageFormat(*from, input)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+	outFormat, err := findImageFormat(*to, output)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+
+	data, err := os.ReadFile(input)
+	if err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] Cannot read file: %v\n", err)
+		return 1
+	}
+	obj, err := inFormat.Decode(data, order)
+	if err == nil && inFormat.Name() == "c2o" {
+		err = verifyChecksum(obj)
+	}
+	if err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] %s: %v\n", input, err)
+		return 1
+	}
+	if data, err = outFormat.Encode(obj, order); err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] %s: %v\n", output, err)
+		return 1
+	}
+	if err := os.WriteFile(output, data, 0644); err != nil {
+		fmt.Fprintf(w, "[COMET2 ERROR] Cannot write file: %v\n", err)
+		return 1
+	}
+	fmt.Fprintf(w, "Converted %d words from %s to %s\n", len(obj.Bin), inFormat.Name(), outFormat.Name())
+	return 0
+}
@@ -0,0 +1,169 @@
+package main
+
+import (
+	"bytes"
+	"encoding/binary"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// testImages are images covering the edge cases of the formats: no words,
+// one word, words crossing the 16-byte records, a start address, and an
+// image larger than the 16-bit byte addresses of ihex and srec records.
+func testImages() map[string]*Object {
+	words := func(n int) []uint16 {
+		bin := make([]uint16, n)
+		for i := range bin {
+			bin[i] = uint16(i*0x0101 + 0x8001)
+		}
+		return bin
+	}
+	return map[string]*Object{
+		"empty":   {Bin: []uint16{}},
+		"one":     {Bin: []uint16{0x1234}},
+		"records": {Bin: words(17), Start: 3},
+		"large":   {Bin: words(0x9000), Start: 0x8800},
+	}
+}
+
+func TestImageRoundTrip(t *testing.T) {
+	orders := map[string]binary.ByteOrder{"big": binary.BigEndian, "little": binary.LittleEndian}
+	for _, name := range imageFormatNames() {
+		format := imageFormats[name]
+		for orderName, order := range orders {
+			for imageName, obj := range testImages() {
+				if name == "raw" {
+					obj.Start = 0
+				}
+				data, err := format.Encode(obj, order)
+				if err != nil {
+					t.Fatalf("%s/%s/%s: %v", name, orderName, imageName, err)
+				}
+				got, err := format.Decode(data, order)
+				if err != nil {
+					t.Fatalf("%s/%s/%s: %v", name, orderName, imageName, err)
+				}
+				if got.Start != obj.Start || len(got.Bin) != len(obj.Bin) || (len(obj.Bin) > 0 && !reflect.DeepEqual(got.Bin, obj.Bin)) {
+					t.Errorf("%s/%s/%s: got %d words from #%s, expected %d words from #%s",
+						name, orderName, imageName, len(got.Bin), hex(got.Start, 4), len(obj.Bin), hex(obj.Start, 4))
+				}
+			}
+		}
+	}
+}
+
+func TestImageVectors(t *testing.T) {
+	obj := &Object{Bin: []uint16{0x1234}}
+	tests := []struct {
+		format string
+		order  binary.ByteOrder
+		want   string
+	}{
+		{"raw", binary.BigEndian, "\x12\x34"},
+		{"raw", binary.LittleEndian, "\x34\x12"},
+		{"ihex", binary.BigEndian, ":020000001234B8\n:00000001FF\n"},
+		{"srec", binary.BigEndian, "S007000063326332CE\nS10500001234B4\nS9030000FC\n"},
+	}
+	for _, tt := range tests {
+		data, err := imageFormats[tt.format].Encode(obj, tt.order)
+		if err != nil {
+			t.Fatal(err)
+		}
+		if string(data) != tt.want {
+			t.Errorf("%s: expected %q, got %q", tt.format, tt.want, data)
+		}
+	}
+
+	// Extended linear address and start records of a large image
+	large, _ := imageFormats["ihex"].Encode(testImages()["large"], binary.BigEndian)
+	for _, record := range []string{":020000040001F9\n", ":0400000500011000E6\n"} {
+		if !strings.Contains(string(large), record) {
+			t.Errorf("Expected ihex record %q", strings.TrimSpace(record))
+		}
+	}
+	large, _ = imageFormats["srec"].Encode(testImages()["large"], binary.BigEndian)
+	if !strings.Contains(string(large), "\nS2") || !strings.Contains(string(large), "\nS804011000EA\n") {
+		t.Errorf("Expected S2 records and an S8 record for a large image")
+	}
+}
+
+func TestImageDecodeErrors(t *testing.T) {
+	tests := []struct {
+		format string
+		data   string
+		want   string
+	}{
+		{"raw", "\x12", "odd number of bytes"},
+		{"ihex", ":020000001234B9\n:00000001FF\n", "checksum mismatch"},
+		{"ihex", ":020000001234B8\n", "missing end of file record"},
+		{"ihex", ":00000006FA\n", "unsupported record type 06"},
+		{"srec", "S10500001234B5\n", "checksum mismatch"},
+		{"srec", "X10500001234B4\n", "invalid S-record"},
+		{"ihex", ":0400000500020000F5\n:00000001FF\n", "start address #10000 is past the end of memory"},
+		{"srec", "S804020000F9\n", "start address #10000 is past the end of memory"},
+		{"c2o", `{"start":-1,"bin":[0]}`, "start address -1 is outside memory"},
+		{"c2o", `{"start":65536,"bin":[]}`, "start address 65536 is outside memory"},
+		{"c2o", `{"start":0,"bin":[` + strings.Repeat("0,", ADDRESS_SPACE) + `0]}`, "image of 65537 words is larger than memory"},
+	}
+	for _, tt := range tests {
+		_, err := imageFormats[tt.format].Decode([]byte(tt.data), binary.BigEndian)
+		if err == nil || !strings.Contains(err.Error(), tt.want) {
+			t.Errorf("%s %q: expected error containing %q, got %v", tt.format, tt.data, tt.want, err)
+		}
+	}
+
+	// Records may leave gaps, which read as zero
+	obj, err := imageFormats["ihex"].Decode([]byte(":020004001234B4\n:00000001FF\n"), binary.BigEndian)
+	if err != nil || !reflect.DeepEqual(obj.Bin, []uint16{0, 0, 0x1234}) {
+		t.Errorf("Expected a gap before #0002, got %v, %v", obj, err)
+	}
+}
+
+func TestImageCommand(t *testing.T) {
+	dir := t.TempDir()
+	obj := assembleObject(t, `MAIN	START	BEGIN
+BUF	DS	2
+BEGIN	LAD	GR1,1
+	RET
+	END
+`)
+	input := filepath.Join(dir, "prog.c2o")
+	if err := writeObjectFile(input, obj); err != nil {
+		t.Fatal(err)
+	}
+
+	var out bytes.Buffer
+	hexPath := filepath.Join(dir, "prog.hex")
+	if status := runImageCommand([]string{input, hexPath}, &out); status != 0 {
+		t.Fatalf("Expected status 0, got %d: %s", status, out.String())
+	}
+	if !strings.Contains(out.String(), "Converted 5 words from c2o to ihex") {
+		t.Errorf("Unexpected output %q", out.String())
+	}
+	loaded, err := importObject("ihex", hexPath)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if loaded.Start != obj.Start || !reflect.DeepEqual(loaded.Bin, obj.Bin) {
+		t.Errorf("Expected %v from #%s, got %v from #%s", obj.Bin, hex(obj.Start, 4), loaded.Bin, hex(loaded.Start, 4))
+	}
+
+	out.Reset()
+	rawPath := filepath.Join(dir, "prog.bin")
+	if status := runImageCommand([]string{"-byte-order", "little", input, rawPath}, &out); status != 0 {
+		t.Fatalf("Expected status 0, got %d: %s", status, out.String())
+	}
+	raw, _ := os.ReadFile(rawPath)
+	if !bytes.Equal(raw, encodeWords(obj.Bin, binary.LittleEndian)) {
+		t.Errorf("Unexpected raw image % x", raw)
+	}
+
+	out.Reset()
+	if status := runImageCommand([]string{input, filepath.Join(dir, "prog.txt")}, &out); status != 2 ||
+		!strings.Contains(out.String(), "Cannot tell the image format") {
+		t.Errorf("Expected an unknown format error, got %d: %s", status, out.String())
+	}
+}
@@ -0,0 +1,155 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// What lintIndexes knows about the value of a register
+type indexKind int
+
+const (
+	INDEX_UNKNOWN indexKind = iota
+	// INDEX_UNCHECKED is a value loaded from memory that no CPA or CPL has
+	// compared since.
+	INDEX_UNCHECKED
+	INDEX_CHECKED
+	// INDEX_CONSTANT is a number set by LAD.
+	INDEX_CONSTANT
+)
+
+// indexValue is what is known about a register, and the line that set it.
+type indexValue struct {
+	kind indexKind
+	pos  int
+	val  int
+}
+
+// dsSizes returns the number of words of each labeled DS, by scoped label.
+func dsSizes(asmState *AssemblerState) map[string]int {
+	sizes := make(map[string]int)
+	for _, text := range asmState.buf {
+		parts := strings.SplitN(text, "\t", 3)
+		if len(parts) < 3 || parts[0] == "" || parts[1] != "DS" {
+			continue
+		}
+		if n, ok := expandNumber(strings.TrimSpace(parts[2])); ok {
+			sizes[parts[0]] = n
+		}
+	}
+	return sizes
+}
+
+// lintIndexes warns about indexed accesses such as LD GR1,BUF,GR2 to a DS
+// buffer where GR2 was loaded from memory and not compared with CPA or CPL
+// before, so nothing keeps it within BUF, and where GR2 is a constant from
+// LAD past the end of BUF. Like lintSigns it follows straight-line code
+// and forgets the registers at labels, calls and SVCs.
+func lintIndexes(asmState *AssemblerState) []Diagnostic {
+	var diags []Diagnostic
+	warn := func(pos int, name string, format string, args ...interface{}) {
+		diags = append(diags, warning(asmState, pos, name, fmt.Sprintf(format, args...))...)
+	}
+	sizes := dsSizes(asmState)
+	var regs [8]indexValue
+	scope := ""
+
+	for i, text := range asmState.buf {
+		parts := strings.SplitN(text, "\t", 3)
+		if len(parts) < 3 {
+			continue
+		}
+		label, inst := parts[0], parts[1]
+		pos := asmState.bufPos[i]
+		if label != "" {
+			regs = [8]indexValue{}
+		}
+		def, ok := CASL2TBL[inst]
+		if !ok {
+			continue
+		}
+		oprs := parseOperands(parts[2])
+
+		switch def.Type {
+		case START:
+			scope = label[strings.Index(label, ":")+1:]
+			continue
+		case RPOP:
+			regs = [8]indexValue{}
+			continue
+		case OP1, OP2, OP5:
+		default:
+			continue
+		}
+
+		adr := 1
+		if def.Type == OP2 {
+			adr = 0
+		}
+		if len(oprs) == adr+2 {
+			if x, err := checkRegister(oprs[adr+1]); err == nil && x > 0 {
+				if size, ok := sizes[scope+":"+oprs[adr]]; ok {
+					switch v := regs[x]; {
+					case v.kind == INDEX_UNCHECKED:
+						warn(pos, WARN_UNCHECKED_INDEX, "Possible out-of-range index: GR%d was loaded on line %d and not compared with CPA or CPL before indexing %s (%d words)",
+							x, asmState.origin(v.pos).Line, oprs[adr], size)
+						regs[x].kind = INDEX_UNKNOWN
+					case v.kind == INDEX_CONSTANT && (v.val < 0 || v.val >= size):
+						warn(pos, WARN_INDEX_RANGE, "Out-of-range index: GR%d is %d from line %d, outside %s (%d words)",
+							x, v.val, asmState.origin(v.pos).Line, oprs[adr], size)
+					}
+				}
+			}
+		}
+
+		if def.Type == OP2 {
+			if inst == "CALL" || inst == "SVC" {
+				// The subroutine or service may change any register
+				regs = [8]indexValue{}
+			}
+			continue
+		}
+
+		r, r2 := operandRegisters(oprs)
+		if r < 0 {
+			continue
+		}
+		// A value derived from an unchecked one is still unchecked
+		unchecked := indexValue{}
+		for _, reg := range []int{r, r2} {
+			if reg >= 0 && regs[reg].kind == INDEX_UNCHECKED {
+				unchecked = regs[reg]
+			}
+		}
+		switch {
+		case inst == "CPA" || inst == "CPL":
+			for _, reg := range []int{r, r2} {
+				if reg >= 0 && regs[reg].kind == INDEX_UNCHECKED {
+					regs[reg].kind = INDEX_CHECKED
+				}
+			}
+		case inst == "ST":
+		case inst == "LD" && r2 >= 0:
+			regs[r] = regs[r2]
+		case inst == "LD":
+			regs[r] = indexValue{kind: INDEX_UNCHECKED, pos: pos}
+		case inst == "LAD" && len(oprs) == 2:
+			if n, ok := expandNumber(oprs[1]); ok {
+				regs[r] = indexValue{kind: INDEX_CONSTANT, pos: pos, val: signed(n & 0xffff)}
+			} else {
+				regs[r] = indexValue{}
+			}
+		case inst == "LAD":
+			regs[r] = indexValue{}
+			if x, err := checkRegister(oprs[2]); err == nil && regs[x].kind == INDEX_UNCHECKED {
+				regs[r] = regs[x]
+			}
+		case inst == "AND":
+			// A mask bounds the value
+			regs[r] = indexValue{}
+		default:
+			regs[r] = unchecked
+		}
+	}
+	return diags
+}
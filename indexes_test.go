@@ -0,0 +1,44 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestIndexLint(t *testing.T) {
+	src := `MAIN	START
+	IN	BUF,LEN
+	LD	GR2,LEN
+	LD	GR1,BUF,GR2
+	LD	GR3,LEN
+	CPA	GR3,=16
+	JPL	FIN
+	ST	GR1,BUF,GR3
+	LAD	GR4,16
+	LD	GR0,BUF,GR4
+	LAD	GR4,15
+	LD	GR0,BUF,GR4
+	LD	GR5,LEN
+	CALL	SUB
+	LD	GR0,BUF,GR5
+LOOP	LD	GR0,BUF,GR2
+FIN	RET
+SUB	RET
+BUF	DS	16
+LEN	DS	1
+	END
+`
+	*optLint = true
+	defer func() { *optLint = false }()
+	asmState := newAssemblerState()
+	if _, err := assembleText("prog.cas", src, asmState); err != nil {
+		t.Fatal(err)
+	}
+	want := []Diagnostic{
+		{Line: 4, Msg: "Possible out-of-range index: GR2 was loaded on line 3 and not compared with CPA or CPL before indexing BUF (16 words)", Severity: "warning", Code: WARN_UNCHECKED_INDEX},
+		{Line: 10, Msg: "Out-of-range index: GR4 is 16 from line 9, outside BUF (16 words)", Severity: "warning", Code: WARN_INDEX_RANGE},
+	}
+	if !reflect.DeepEqual(asmState.warnings, want) {
+		t.Errorf("Expected %v, got %v", want, asmState.warnings)
+	}
+}
@@ -0,0 +1,127 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"strings"
+	"time"
+)
+
+// InputSource supplies lines to IN on demand.
+// ReadLine returns false when no more input is available.
+type InputSource interface {
+	ReadLine() (string, bool)
+}
+
+// InputFunc adapts a function to an InputSource.
+type InputFunc func() (string, bool)
+
+func (f InputFunc) ReadLine() (string, bool) {
+	return f()
+}
+
+// InputChan adapts a channel to an InputSource. ReadLine blocks until a
+// line is sent; closing the channel ends the input.
+type InputChan <-chan string
+
+func (ch InputChan) ReadLine() (string, bool) {
+	line, ok := <-ch
+	return line, ok
+}
+
+// InputLines is an InputSource over a fixed list of lines.
+type InputLines struct {
+	lines []string
+}
+
+func NewInputLines(lines []string) *InputLines {
+	return &InputLines{lines: append([]string(nil), lines...)}
+}
+
+func (in *InputLines) ReadLine() (string, bool) {
+	if len(in.lines) == 0 {
+		return "", false
+	}
+	line := in.lines[0]
+	in.lines = in.lines[1:]
+	return line, true
+}
+
+// Push appends a line after the remaining ones.
+func (in *InputLines) Push(line string) {
+	in.lines = append(in.lines, line)
+}
+
+// readInputFile returns the lines of path, one per IN. A final newline
+// does not add an empty line.
+func readInputFile(path string) ([]string, error) {
+	content, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read input file: %v", err)
+	}
+	text := strings.ReplaceAll(string(content), "\r\n", "\n")
+	if text == "" {
+		return nil, nil
+	}
+	return strings.Split(strings.TrimSuffix(text, "\n"), "\n"), nil
+}
+
+// inputTimeout is the -input-timeout setting: how long an IN typed at the
+// console may take before the default line is read instead.
+type inputTimeout struct {
+	wait time.Duration
+	line string
+}
+
+// parseInputTimeout reads "DURATION=DEFAULT", e.g. "10s=0". DEFAULT may
+// be empty.
+func parseInputTimeout(spec string) (*inputTimeout, error) {
+	waitText, line, ok := strings.Cut(spec, "=")
+	wait, err := time.ParseDuration(strings.TrimSpace(waitText))
+	if !ok || err != nil || wait <= 0 {
+		return nil, fmt.Errorf("[COMET2 ERROR] Invalid -input-timeout \"%s\" (expected DURATION=DEFAULT, e.g. 10s=0)", spec)
+	}
+	return &inputTimeout{wait: wait, line: line}, nil
+}
+
+// timedLine is the result of one Scan of a timedScanner.
+type timedLine struct {
+	text string
+	ok   bool
+}
+
+// timedScanner reads lines with a time limit. A line that comes after the
+// limit is kept for the next read, so it is neither lost nor read twice.
+type timedScanner struct {
+	scanner *bufio.Scanner
+	// pending receives the line of a read that timed out, nil when no
+	// read is in progress.
+	pending chan timedLine
+}
+
+// read returns the next line, waiting at most wait (0: no limit). ok is
+// false at the end of input; timedOut is true when no line came in time.
+func (s *timedScanner) read(wait time.Duration) (line string, ok bool, timedOut bool) {
+	if s.pending == nil {
+		pending := make(chan timedLine, 1)
+		go func() {
+			ok := s.scanner.Scan()
+			pending <- timedLine{s.scanner.Text(), ok}
+		}()
+		s.pending = pending
+	}
+	var timeout <-chan time.Time
+	if wait > 0 {
+		timer := time.NewTimer(wait)
+		defer timer.Stop()
+		timeout = timer.C
+	}
+	select {
+	case l := <-s.pending:
+		s.pending = nil
+		return l.text, l.ok, false
+	case <-timeout:
+		return "", true, true
+	}
+}
@@ -0,0 +1,75 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestRunWithInputChan(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+
+	lines := make(chan string, 1)
+	lines <- "hello"
+	c.Input = InputChan(lines)
+
+	var outputs []string
+	c.Output = func(text string) {
+		outputs = append(outputs, text)
+	}
+
+	err := c.Run()
+	if err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if len(outputs) != 1 || outputs[0] != "hello" {
+		t.Errorf("Unexpected outputs: %q", outputs)
+	}
+}
+
+func TestReadInputFile(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "input.txt")
+	if err := os.WriteFile(path, []byte("1, 2\r\nhello world\n"), 0644); err != nil {
+		t.Fatalf("Failed to write input: %v", err)
+	}
+
+	lines, err := readInputFile(path)
+	if err != nil {
+		t.Fatalf("Failed to read input: %v", err)
+	}
+	if len(lines) != 2 || lines[0] != "1, 2" || lines[1] != "hello world" {
+		t.Errorf("Unexpected lines: %q", lines)
+	}
+}
+
+func TestInputTimeout(t *testing.T) {
+	timeout, err := parseInputTimeout("50ms=42")
+	if err != nil || timeout.wait != 50*time.Millisecond || timeout.line != "42" {
+		t.Fatalf("Unexpected timeout %+v, %v", timeout, err)
+	}
+	for _, spec := range []string{"10s", "ten=0", "0s=0"} {
+		if _, err := parseInputTimeout(spec); err == nil {
+			t.Errorf("Expected an error for %q", spec)
+		}
+	}
+
+	r, w := io.Pipe()
+	in := &timedScanner{scanner: bufio.NewScanner(r)}
+	if _, ok, timedOut := in.read(timeout.wait); !ok || !timedOut {
+		t.Fatalf("Expected the read to time out")
+	}
+	// The line typed late is read next, not lost
+	go fmt.Fprintln(w, "late")
+	if line, ok, timedOut := in.read(time.Second); !ok || timedOut || line != "late" {
+		t.Errorf("Expected the late line, got %q %v %v", line, ok, timedOut)
+	}
+	w.Close()
+	if _, ok, _ := in.read(time.Second); ok {
+		t.Errorf("Expected the end of input")
+	}
+}
@@ -0,0 +1,50 @@
+package main
+
+import "fmt"
+
+// ioBufferProblem describes what makes no sense in the buffer at GR1 and
+// the length word at GR2 of an IN or OUT that uses n words of the buffer,
+// or returns "". The code of the program is only known for objects with
+// regions.
+func (c *Comet2) ioBufferProblem(svc string, bufp int, lenp int, n int) string {
+	if lenp >= bufp && lenp < bufp+n {
+		return fmt.Sprintf("%s length word GR2 = %s lies inside the buffer GR1 = %s of %d words",
+			svc, c.formatAddr(lenp), c.formatAddr(bufp), n)
+	}
+	if isCode(c.Regions, lenp) {
+		return fmt.Sprintf("%s length word GR2 = %s is in the code of the program", svc, c.formatAddr(lenp))
+	}
+	for i := 0; i < n; i++ {
+		if addr := (bufp + i) & 0xffff; isCode(c.Regions, addr) {
+			return fmt.Sprintf("%s buffer GR1 = %s of %d words reaches the code of the program at %s",
+				svc, c.formatAddr(bufp), n, c.formatAddr(addr))
+		}
+	}
+	return ""
+}
+
+// reportIO reports a problem of the IN or OUT at pc as CheckIO asks. It
+// returns whether the machine halts.
+func (c *Comet2) reportIO(pc int, problem string) bool {
+	if problem == "" || c.CheckIO == "" {
+		return false
+	}
+	err := runtimeError(RUNTIME_RANGE_OVER, pc, "Range over at %s: %s%s", c.formatAddr(pc), problem, c.locate(pc))
+	if c.CheckIO == "error" {
+		c.Halt(err)
+		return true
+	}
+	c.warn(err.Error())
+	return false
+}
+
+// SetCheckIO selects what IN and OUT do with nonsensical GR1/GR2: "" to
+// ignore them, "warn" or "error".
+func (c *Comet2) SetCheckIO(mode string) error {
+	switch mode {
+	case "", "warn", "error":
+		c.CheckIO = mode
+		return nil
+	}
+	return fmt.Errorf("[COMET2 ERROR] Invalid -check-io mode \"%s\"", mode)
+}
@@ -0,0 +1,55 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+)
+
+func TestCheckIO(t *testing.T) {
+	tests := []struct {
+		src   string
+		input string
+		want  string
+	}{
+		{"MAIN\tSTART\n\tOUT\tMSG,MSG\n\tRET\nMSG\tDC\t2\n\tDC\t'A'\n\tEND\n", "",
+			"OUT length word GR2 = #000D <MSG> lies inside the buffer GR1 = #000D <MSG> of 2 words"},
+		{"MAIN\tSTART\n\tIN\tMAIN,LEN\n\tRET\nLEN\tDS\t1\n\tEND\n", "abc",
+			"IN buffer GR1 = #0000 <MAIN> of 3 words reaches the code of the program at #0000 <MAIN>"},
+		{"MAIN\tSTART\n\tOUT\tMSG,LEN\n\tRET\nMSG\tDC\t'A'\nLEN\tDC\t-1\n\tEND\n", "",
+			"OUT length -1 in GR2 = #000E <LEN> is outside 0..256"},
+	}
+	for _, tt := range tests {
+		for _, mode := range []string{"", "warn", "error"} {
+			c := assembleSource(t, tt.src)
+			if err := c.SetCheckIO(mode); err != nil {
+				t.Fatal(err)
+			}
+			var warnings []string
+			c.Warn = func(msg string) { warnings = append(warnings, msg) }
+			c.Input = NewInputLines([]string{tt.input})
+			err := c.Run()
+
+			var rerr *RuntimeError
+			switch mode {
+			case "":
+				if len(warnings) != 0 {
+					t.Errorf("Expected no report without -check-io, got %v", warnings)
+				}
+			case "warn":
+				if len(warnings) != 1 || !strings.Contains(warnings[0], tt.want) {
+					t.Errorf("Expected a warning %q, got %v", tt.want, warnings)
+				}
+			case "error":
+				if !errors.As(err, &rerr) || rerr.Kind != RUNTIME_RANGE_OVER || !strings.Contains(err.Error(), tt.want) {
+					t.Errorf("Expected Range-Over %q, got %v", tt.want, err)
+				}
+			}
+		}
+	}
+
+	c := assembleSource(t, echoProgram)
+	if err := c.SetCheckIO("ignore"); err == nil {
+		t.Errorf("Expected an error for an unknown mode")
+	}
+}
@@ -0,0 +1,115 @@
+package main
+
+// Offset from the opcode of the r,adr[,x] form of an OP5 instruction to
+// its r1,r2 form, e.g. LD #10 and #14
+const REG_FORM_OFFSET = 0x04
+
+// instructionUse tells the tools what an instruction does with its
+// effective address and where it may continue.
+type instructionUse uint8
+
+const (
+	// The effective address is a memory or jump address, not a value
+	USE_MEM_ADDR instructionUse = 1 << iota
+	// Execution may continue anywhere but the next instruction
+	USE_BRANCH
+)
+
+// machineInstruction is one COMET2 instruction: its mnemonic, opcode,
+// operand form and use, the handler stepExec runs for it and its reference
+// entry. OP5 instructions have two encodings, r,adr[,x] at Code and r1,r2
+// at Code+REG_FORM_OFFSET; the handler tells them apart by
+// execState.grForm.
+type machineInstruction struct {
+	ID   string
+	Code uint8
+	Type InstructionType
+	Use  instructionUse
+	Exec execFunc
+	Doc  instructionDoc
+}
+
+// machineInstructions is the instruction set. The assembler table
+// CASL2TBL, the decoder table COMET2TBL with the handlers stepExec
+// dispatches to, instruction sizes, the uses the block engine and checkers
+// go by and the entries and encodings shown by "c2c2 doc" are all derived
+// from it.
+var machineInstructions = []machineInstruction{
+	{"NOP", 0x00, OP4, 0, execNOP, instructionDoc{summary: "no operation", flags: "-", detail: "Does nothing.", example: "NOP"}},
+	{"LD", 0x10, OP5, USE_MEM_ADDR, execLD, instructionDoc{summary: "load", flags: "SF ZF, OF=0", detail: "r <- operand.", example: "LD\tGR1,DATA,GR2"}},
+	{"ST", 0x11, OP1, USE_MEM_ADDR, execST, instructionDoc{summary: "store", flags: "-", detail: "mem[adr+x] <- r.", example: "ST\tGR1,RESULT"}},
+	{"LAD", 0x12, OP1, 0, execLAD, instructionDoc{summary: "load address", flags: "-", detail: "r <- adr+x. Reads no memory, so it also loads constants and adds to registers.", example: "LAD\tGR1,1,GR1"}},
+	{"ADDA", 0x20, OP5, USE_MEM_ADDR, execADDA, instructionDoc{summary: "add arithmetic", flags: "OF SF ZF", detail: "r <- r + operand as signed numbers; OF when the result is outside -32768..32767.", example: "ADDA\tGR1,=1"}},
+	{"SUBA", 0x21, OP5, USE_MEM_ADDR, execSUBA, instructionDoc{summary: "subtract arithmetic", flags: "OF SF ZF", detail: "r <- r - operand as signed numbers; OF when the result is outside -32768..32767.", example: "SUBA\tGR2,GR3"}},
+	{"ADDL", 0x22, OP5, USE_MEM_ADDR, execADDL, instructionDoc{summary: "add logical", flags: "OF SF ZF", detail: "r <- r + operand as unsigned numbers; OF on a carry past 65535.", example: "ADDL\tGR1,GR2"}},
+	{"SUBL", 0x23, OP5, USE_MEM_ADDR, execSUBL, instructionDoc{summary: "subtract logical", flags: "OF SF ZF", detail: "r <- r - operand as unsigned numbers; OF on a borrow below 0.", example: "SUBL\tGR1,=1"}},
+	{"MULA", 0x28, OP5, USE_MEM_ADDR, execMULA, instructionDoc{summary: "multiply arithmetic (extension)", flags: "OF SF ZF", detail: "r <- r * operand as signed numbers; OF when the product does not fit in a word.", example: "MULA\tGR1,=10"}},
+	{"DIVA", 0x29, OP5, USE_MEM_ADDR, execDIVA, instructionDoc{summary: "divide arithmetic (extension)", flags: "OF SF ZF", detail: "r <- r / operand as signed numbers; dividing by zero sets OF and ZF.", example: "DIVA\tGR1,GR2"}},
+	{"MULL", 0x2a, OP5, USE_MEM_ADDR, execMULL, instructionDoc{summary: "multiply logical (extension)", flags: "OF SF ZF", detail: "r <- r * operand as unsigned numbers; OF when the product exceeds 65535.", example: "MULL\tGR1,=2"}},
+	{"DIVL", 0x2b, OP5, USE_MEM_ADDR, execDIVL, instructionDoc{summary: "divide logical (extension)", flags: "OF SF ZF", detail: "r <- r / operand as unsigned numbers; dividing by zero sets OF and ZF.", example: "DIVL\tGR1,=10"}},
+	{"AND", 0x30, OP5, USE_MEM_ADDR, execAND, instructionDoc{summary: "logical and", flags: "SF ZF, OF=0", detail: "r <- r AND operand, bit by bit.", example: "AND\tGR1,=#00FF"}},
+	{"OR", 0x31, OP5, USE_MEM_ADDR, execOR, instructionDoc{summary: "logical or", flags: "SF ZF, OF=0", detail: "r <- r OR operand, bit by bit.", example: "OR\tGR1,=#0030"}},
+	{"XOR", 0x32, OP5, USE_MEM_ADDR, execXOR, instructionDoc{summary: "exclusive or", flags: "SF ZF, OF=0", detail: "r <- r XOR operand, bit by bit.", example: "XOR\tGR1,=#FFFF"}},
+	{"CPA", 0x40, OP5, USE_MEM_ADDR, execCPA, instructionDoc{summary: "compare arithmetic", flags: "SF ZF, OF=0", detail: "Compares r with the operand as signed numbers: SF=1 when smaller, ZF=1 when equal. r is unchanged.", example: "CPA\tGR1,LIMIT"}},
+	{"CPL", 0x41, OP5, USE_MEM_ADDR, execCPL, instructionDoc{summary: "compare logical", flags: "SF ZF, OF=0", detail: "Compares r with the operand as unsigned numbers: SF=1 when smaller, ZF=1 when equal. r is unchanged.", example: "CPL\tGR1,=#0041"}},
+	{"SLA", 0x50, OP1, 0, execSLA, instructionDoc{summary: "shift left arithmetic", flags: "OF SF ZF", detail: "Shifts r left by adr+x bits keeping the sign bit; OF is the last bit shifted out.", example: "SLA\tGR1,1"}},
+	{"SRA", 0x51, OP1, 0, execSRA, instructionDoc{summary: "shift right arithmetic", flags: "OF SF ZF", detail: "Shifts r right by adr+x bits filling with the sign bit; OF is the last bit shifted out.", example: "SRA\tGR1,2"}},
+	{"SLL", 0x52, OP1, 0, execSLL, instructionDoc{summary: "shift left logical", flags: "OF SF ZF", detail: "Shifts r left by adr+x bits filling with 0; OF is the last bit shifted out.", example: "SLL\tGR1,8"}},
+	{"SRL", 0x53, OP1, 0, execSRL, instructionDoc{summary: "shift right logical", flags: "OF SF ZF", detail: "Shifts r right by adr+x bits filling with 0; OF is the last bit shifted out.", example: "SRL\tGR1,8"}},
+	{"JMI", 0x61, OP2, USE_MEM_ADDR | USE_BRANCH, execJMI, instructionDoc{summary: "jump on minus", flags: "-", detail: "Jumps to adr+x when SF=1.", example: "JMI\tNEG"}},
+	{"JNZ", 0x62, OP2, USE_MEM_ADDR | USE_BRANCH, execJNZ, instructionDoc{summary: "jump on non zero", flags: "-", detail: "Jumps to adr+x when ZF=0.", example: "JNZ\tLOOP"}},
+	{"JZE", 0x63, OP2, USE_MEM_ADDR | USE_BRANCH, execJZE, instructionDoc{summary: "jump on zero", flags: "-", detail: "Jumps to adr+x when ZF=1.", example: "JZE\tFIN"}},
+	{"JUMP", 0x64, OP2, USE_MEM_ADDR | USE_BRANCH, execJUMP, instructionDoc{summary: "unconditional jump", flags: "-", detail: "Jumps to adr+x.", example: "JUMP\tLOOP"}},
+	{"JPL", 0x65, OP2, USE_MEM_ADDR | USE_BRANCH, execJPL, instructionDoc{summary: "jump on plus", flags: "-", detail: "Jumps to adr+x when SF=0 and ZF=0.", example: "JPL\tBIG"}},
+	{"JOV", 0x66, OP2, USE_MEM_ADDR | USE_BRANCH, execJOV, instructionDoc{summary: "jump on overflow", flags: "-", detail: "Jumps to adr+x when OF=1.", example: "JOV\tERROR"}},
+	{"PUSH", 0x70, OP2, 0, execPUSH, instructionDoc{summary: "push", flags: "-", detail: "SP <- SP-1, mem[SP] <- adr+x.", example: "PUSH\t0,GR1"}},
+	{"POP", 0x71, OP3, 0, execPOP, instructionDoc{summary: "pop", flags: "-", detail: "r <- mem[SP], SP <- SP+1.", example: "POP\tGR1"}},
+	{"CALL", 0x80, OP2, USE_MEM_ADDR | USE_BRANCH, execCALL, instructionDoc{summary: "call subroutine", flags: "-", detail: "Pushes the address of the next instruction and jumps to adr+x.", example: "CALL\tSUB"}},
+	{"RET", 0x81, OP4, USE_BRANCH, execRET, instructionDoc{summary: "return from subroutine", flags: "-", detail: "Pops the return address into PR. At the top level it ends the program.", example: "RET"}},
+	{"SVC", 0xf0, OP2, USE_BRANCH, execSVC, instructionDoc{summary: "supervisor call", flags: "-", detail: "Calls the system function adr+x; IN and OUT expand to SVC.", example: "SVC\t0"}},
+}
+
+// casl2Table builds CASL2TBL: the machine instructions by mnemonic. The
+// directives are added as they are registered, see registerDirective.
+func casl2Table() map[string]Instruction {
+	tbl := make(map[string]Instruction)
+	for _, inst := range machineInstructions {
+		tbl[inst.ID] = Instruction{inst.Code, inst.Type}
+	}
+	return tbl
+}
+
+// instructionUses are the uses of the machine instructions by mnemonic.
+var instructionUses = machineInstructionUses()
+
+func machineInstructionUses() map[string]instructionUse {
+	uses := make(map[string]instructionUse)
+	for _, inst := range machineInstructions {
+		uses[inst.ID] = inst.Use
+	}
+	return uses
+}
+
+// comet2Table builds COMET2TBL: every opcode with the form it decodes to
+// and its handler. The memory form of an OP5 instruction decodes as OP1.
+func comet2Table() map[int]Comet2Instruction {
+	tbl := make(map[int]Comet2Instruction)
+	for _, inst := range machineInstructions {
+		if inst.Type == OP5 {
+			tbl[int(inst.Code)] = Comet2Instruction{inst.ID, OP1, inst.Exec}
+			tbl[int(inst.Code)+REG_FORM_OFFSET] = Comet2Instruction{inst.ID, OP5, inst.Exec}
+		} else {
+			tbl[int(inst.Code)] = Comet2Instruction{inst.ID, inst.Type, inst.Exec}
+		}
+	}
+	return tbl
+}
+
+// instructionSize returns the number of words of a decoded form: two for
+// the forms with an address word, one for the others.
+func instructionSize(t InstructionType) int {
+	if t == OP1 || t == OP2 {
+		return 2
+	}
+	return 1
+}
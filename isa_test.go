@@ -0,0 +1,64 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestInstructionTable(t *testing.T) {
+	operands := map[InstructionType][]string{
+		OP1: {"GR1,#0010,GR2"},
+		OP2: {"#0010,GR2"},
+		OP3: {"GR1"},
+		OP4: {""},
+		OP5: {"GR1,#0010,GR2", "GR1,GR2"},
+	}
+	decoded := 0
+	for _, inst := range machineInstructions {
+		for _, opr := range operands[inst.Type] {
+			obj, err := assembleText("isa.cas", "MAIN\tSTART\n\t"+inst.ID+"\t"+opr+"\n\tEND\n", newAssemblerState())
+			if err != nil {
+				t.Errorf("%s %s: %v", inst.ID, opr, err)
+				continue
+			}
+			id, _, size := parse(obj.Bin, make([]int, SP+1))
+			if id != inst.ID || size != len(obj.Bin) {
+				t.Errorf("%s %s: decoded as %s in %d words, assembled to %d", inst.ID, opr, id, size, len(obj.Bin))
+			}
+			decoded++
+
+			// An instruction not marked as a branch goes on with the next one
+			c := LoadObject(obj)
+			if _, err := c.stepExec(); err == nil && inst.Use&USE_BRANCH == 0 && c.State[PC] != size {
+				t.Errorf("%s %s: continued at #%s but is not marked USE_BRANCH", inst.ID, opr, hex(c.State[PC], 4))
+			}
+		}
+	}
+	// Only instructions with an address operand use it as an address, and
+	// the helpers of the block engine and checkers follow the table
+	for _, inst := range machineInstructions {
+		if inst.Use&USE_MEM_ADDR != 0 && inst.Type != OP1 && inst.Type != OP2 && inst.Type != OP5 {
+			t.Errorf("%s has no address operand but is marked USE_MEM_ADDR", inst.ID)
+		}
+		if addressesMemory(inst.ID) != (inst.Use&USE_MEM_ADDR != 0) || endsBlock(inst.ID) != (inst.Use&USE_BRANCH != 0) {
+			t.Errorf("%s: addressesMemory and endsBlock do not follow the table", inst.ID)
+		}
+	}
+	// Every opcode is reached from exactly one assembler form
+	if decoded != len(COMET2TBL) {
+		t.Errorf("Expected %d opcodes, assembled %d forms", len(COMET2TBL), decoded)
+	}
+	// Every opcode executes, and every name has a reference entry
+	for code, inst := range COMET2TBL {
+		if inst.Exec == nil {
+			t.Errorf("No handler for %s (#%02X)", inst.ID, code)
+		}
+	}
+	for name := range CASL2TBL {
+		if instructionDocs[name].summary == "" {
+			t.Errorf("No doc entry for %s", name)
+		}
+	}
+	if len(CASL2TBL) != len(machineInstructions)+len(directives) {
+		t.Errorf("Expected mnemonics and directives to be unique, got %d entries", len(CASL2TBL))
+	}
+}
@@ -8,6 +8,7 @@ package main
 
 import (
 	"fmt"
+	"iter"
 	"strings"
 )
 
@@ -29,12 +30,42 @@ const (
 	TOKEN_COMMENT
 )
 
-// Token represents a lexical token
+var tokenTypeNames = []string{
+	"eof", "newline", "label", "instruction", "register", "number",
+	"hexnum", "string", "comma", "equals", "whitespace", "comment",
+}
+
+func (t TokenType) String() string {
+	if int(t) < 0 || int(t) >= len(tokenTypeNames) {
+		return fmt.Sprintf("TokenType(%d)", int(t))
+	}
+	return tokenTypeNames[t]
+}
+
+// MarshalText encodes the type by name so JSON output stays readable.
+func (t TokenType) MarshalText() ([]byte, error) {
+	return []byte(t.String()), nil
+}
+
+func (t *TokenType) UnmarshalText(text []byte) error {
+	for i, name := range tokenTypeNames {
+		if name == string(text) {
+			*t = TokenType(i)
+			return nil
+		}
+	}
+	return fmt.Errorf("Unknown token type \"%s\"", text)
+}
+
+// Token represents a lexical token. Value is a substring of the input and
+// Start/End are its byte offsets, so no text is copied while lexing.
 type Token struct {
-	Type   TokenType
-	Value  string
-	Line   int
-	Column int
+	Type   TokenType `json:"type"`
+	Value  string    `json:"value"`
+	Line   int       `json:"line"`
+	Column int       `json:"column"`
+	Start  int       `json:"start"`
+	End    int       `json:"end"`
 }
 
 // Lexer tokenizes CASL2 source code
@@ -118,6 +149,34 @@ func isWhitespace(ch byte) bool {
 
 // NextToken returns the next token from the input
 func (l *Lexer) NextToken() Token {
+	start := l.pos
+	tok := l.scanToken()
+	tok.Start, tok.End = start, l.pos
+	return tok
+}
+
+// Tokens returns an iterator over the remaining tokens, stopping before
+// TOKEN_EOF. An unexpected character is yielded as a ParseError and ends
+// the sequence.
+func (l *Lexer) Tokens() iter.Seq2[Token, error] {
+	return func(yield func(Token, error) bool) {
+		for {
+			tok := l.NextToken()
+			if tok.Type == TOKEN_EOF {
+				if tok.Start != tok.End {
+					yield(tok, &ParseError{Line: tok.Line, Column: tok.Column, Msg: tok.Value})
+				}
+				return
+			}
+			if !yield(tok, nil) {
+				return
+			}
+		}
+	}
+}
+
+// scanToken scans the token at the current position
+func (l *Lexer) scanToken() Token {
 	ch := l.peek()
 
 	// Skip whitespace but track it
@@ -350,10 +409,10 @@ func (l *Lexer) scanIdentifier() Token {
 
 // ParsedLine represents a parsed line of CASL2 code
 type ParsedLine struct {
-	Label       string
-	Instruction string
-	Operands    []string
-	Line        int
+	Label       string   `json:"label,omitempty"`
+	Instruction string   `json:"instruction,omitempty"`
+	Operands    []string `json:"operands,omitempty"`
+	Line        int      `json:"line"`
 }
 
 // ParseLine parses a single line using the lexer
@@ -361,14 +420,15 @@ func ParseLine(line string, lineNum int) (*ParsedLine, error) {
 	lexer := NewLexer(line)
 	result := &ParsedLine{Line: lineNum}
 
-	tokens := []Token{}
+	// A CASL2 line rarely has more than a label, an instruction and three
+	// operands with commas
+	tokens := make([]Token, 0, 8)
 	hasLeadingWhitespace := false
 	firstToken := true
 	
-	for {
-		tok := lexer.NextToken()
-		if tok.Type == TOKEN_EOF {
-			break
+	for tok, err := range lexer.Tokens() {
+		if err != nil {
+			return nil, &ParseError{Line: lineNum, Column: tok.Column, Msg: tok.Value}
 		}
 		if tok.Type == TOKEN_COMMENT {
 			break
@@ -392,34 +452,27 @@ func ParseLine(line string, lineNum int) (*ParsedLine, error) {
 
 	pos := 0
 
-	// If line starts with whitespace, first token is instruction
-	// Otherwise, first token could be label or instruction
+	// The label field starts in the first column, so a token there is a
+	// label even when it is spelled like an instruction (IN, OUT, LD...).
+	// Only the operation field after it holds a mnemonic.
 	if !hasLeadingWhitespace && pos < len(tokens) && tokens[pos].Type == TOKEN_LABEL {
-		// Check if this is an instruction by checking CASL2TBL
-		if isInstruction(tokens[pos].Value) {
-			// It's an instruction (no label)
-			result.Instruction = tokens[pos].Value
-			pos++
-		} else {
-			// It's a label
-			result.Label = tokens[pos].Value
-			pos++
-			
-			// Next token should be instruction if present
-			if pos < len(tokens) && tokens[pos].Type == TOKEN_LABEL {
-				if isInstruction(tokens[pos].Value) {
-					result.Instruction = tokens[pos].Value
-					pos++
-				}
+		result.Label = tokens[pos].Value
+		pos++
+
+		// Next token should be instruction if present
+		if pos < len(tokens) && tokens[pos].Type == TOKEN_LABEL {
+			if isInstruction(tokens[pos].Value) {
+				result.Instruction = strings.ToUpper(tokens[pos].Value)
+				pos++
 			}
 		}
 	} else if hasLeadingWhitespace && pos < len(tokens) && tokens[pos].Type == TOKEN_LABEL {
 		// Leading whitespace means first token must be instruction
 		if isInstruction(tokens[pos].Value) {
-			result.Instruction = tokens[pos].Value
+			result.Instruction = strings.ToUpper(tokens[pos].Value)
 			pos++
 		} else {
-			return nil, fmt.Errorf("expected instruction after leading whitespace, got %s", tokens[pos].Value)
+			return nil, &ParseError{Line: lineNum, Column: tokens[pos].Column, Msg: "expected instruction after leading whitespace, got " + tokens[pos].Value}
 		}
 	}
 
@@ -430,7 +483,7 @@ func ParseLine(line string, lineNum int) (*ParsedLine, error) {
 		// Handle literals (=...)
 		if tok.Type == TOKEN_EQUALS {
 			if pos+1 >= len(tokens) {
-				return nil, fmt.Errorf("expected value after =")
+				return nil, &ParseError{Line: lineNum, Column: tok.Column, Msg: "expected value after ="}
 			}
 			nextTok := tokens[pos+1]
 			var literal string
@@ -441,7 +494,7 @@ func ParseLine(line string, lineNum int) (*ParsedLine, error) {
 				literal = "=" + nextTok.Value
 				pos += 2
 			} else {
-				return nil, fmt.Errorf("invalid literal value")
+				return nil, &ParseError{Line: lineNum, Column: nextTok.Column, Msg: "invalid literal value"}
 			}
 			result.Operands = append(result.Operands, literal)
 		} else if tok.Type == TOKEN_COMMA {
@@ -452,16 +505,17 @@ func ParseLine(line string, lineNum int) (*ParsedLine, error) {
 			result.Operands = append(result.Operands, tok.Value)
 			pos++
 		} else {
-			return nil, fmt.Errorf("unexpected token: %s", tok.Value)
+			return nil, &ParseError{Line: lineNum, Column: tok.Column, Msg: "unexpected token: " + tok.Value}
 		}
 	}
 
 	return result, nil
 }
 
-// isInstruction checks if a string is a known CASL2 instruction
+// isInstruction checks if a string is a known CASL2 instruction, in any
+// case
 func isInstruction(s string) bool {
-	_, exists := CASL2TBL[s]
+	_, exists := CASL2TBL[strings.ToUpper(s)]
 	return exists
 }
 
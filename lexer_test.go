@@ -0,0 +1,90 @@
+package main
+
+import (
+	"encoding/json"
+	"reflect"
+	"testing"
+)
+
+func TestLexerTokens(t *testing.T) {
+	src := "LOOP\tLD\tGR1,=#00FF ; load\n"
+	var got []Token
+	for tok, err := range NewLexer(src).Tokens() {
+		if err != nil {
+			t.Fatalf("Unexpected error: %v", err)
+		}
+		if tok.Type != TOKEN_WHITESPACE {
+			got = append(got, tok)
+		}
+	}
+
+	want := []TokenType{TOKEN_LABEL, TOKEN_LABEL, TOKEN_REGISTER, TOKEN_COMMA, TOKEN_EQUALS, TOKEN_HEXNUM, TOKEN_COMMENT, TOKEN_NEWLINE}
+	if len(got) != len(want) {
+		t.Fatalf("Expected %d tokens, got %d: %+v", len(want), len(got), got)
+	}
+	for i, tok := range got {
+		if tok.Type != want[i] {
+			t.Errorf("Token %d: expected %s, got %s", i, want[i], tok.Type)
+		}
+		if tok.Type != TOKEN_NEWLINE && src[tok.Start:tok.End] != tok.Value {
+			t.Errorf("Token %d: span %d-%d does not match %q", i, tok.Start, tok.End, tok.Value)
+		}
+	}
+}
+
+func TestLexerTokensError(t *testing.T) {
+	var err error
+	for _, e := range NewLexer("\tLD\tGR1,?").Tokens() {
+		if e != nil {
+			err = e
+		}
+	}
+	perr, ok := err.(*ParseError)
+	if !ok {
+		t.Fatalf("Expected ParseError, got %v", err)
+	}
+	if perr.Column != 9 {
+		t.Errorf("Expected column 9, got %d", perr.Column)
+	}
+}
+
+func TestParseLineInstructionNamesAsLabels(t *testing.T) {
+	for src, want := range map[string]ParsedLine{
+		"IN\tIN\tBUF,LEN": {Label: "IN", Instruction: "IN", Operands: []string{"BUF", "LEN"}, Line: 1},
+		"LD\tLD\tGR1,OUT": {Label: "LD", Instruction: "LD", Operands: []string{"GR1", "OUT"}, Line: 1},
+		"OUT\tDS\t1":      {Label: "OUT", Instruction: "DS", Operands: []string{"1"}, Line: 1},
+		"RET":             {Label: "RET", Line: 1},
+		"\tJUMP\tIN":      {Instruction: "JUMP", Operands: []string{"IN"}, Line: 1},
+	} {
+		got, err := ParseLine(src, 1)
+		if err != nil {
+			t.Errorf("%q: %v", src, err)
+			continue
+		}
+		if !reflect.DeepEqual(*got, want) {
+			t.Errorf("%q: expected %+v, got %+v", src, want, *got)
+		}
+	}
+
+	src := "MAIN\tSTART\nIN\tIN\tOUT,LD\n\tOUT\tOUT,LD\n\tRET\nOUT\tDS\t8\nLD\tDC\t8\n\tEND\n"
+	if diags := NewAnalysis(src).Diagnostics; len(diags) != 0 {
+		t.Errorf("Unexpected diagnostics %v", diags)
+	}
+	if _, err := assembleText("prog.cas", src, newAssemblerState()); err != nil {
+		t.Errorf("Unexpected error: %v", err)
+	}
+}
+
+func TestTokenJSON(t *testing.T) {
+	data, err := json.Marshal(Token{Type: TOKEN_REGISTER, Value: "GR1", Line: 2, Column: 5})
+	if err != nil {
+		t.Fatalf("Failed to encode: %v", err)
+	}
+	var tok Token
+	if err := json.Unmarshal(data, &tok); err != nil {
+		t.Fatalf("Failed to decode: %v", err)
+	}
+	if tok.Type != TOKEN_REGISTER || tok.Value != "GR1" || tok.Column != 5 {
+		t.Errorf("Unexpected token: %+v", tok)
+	}
+}
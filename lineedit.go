@@ -0,0 +1,414 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"unicode/utf8"
+)
+
+// Name of the monitor history file in the home directory
+const HISTORY_FILE = ".c2c2_history"
+
+// Maximum number of commands kept in the history
+const HISTORY_MAX = 1000
+
+// Control keys understood by the line editor
+const (
+	KEY_CTRL_A    = 1
+	KEY_CTRL_B    = 2
+	KEY_CTRL_C    = 3
+	KEY_CTRL_D    = 4
+	KEY_CTRL_E    = 5
+	KEY_CTRL_F    = 6
+	KEY_CTRL_G    = 7
+	KEY_BACKSPACE = 8
+	KEY_TAB       = 9
+	KEY_CTRL_K    = 11
+	KEY_ENTER     = 13
+	KEY_CTRL_N    = 14
+	KEY_CTRL_P    = 16
+	KEY_CTRL_R    = 18
+	KEY_CTRL_U    = 21
+	KEY_ESC       = 27
+	KEY_DELETE    = 127
+)
+
+// lineEditor reads monitor commands. On a terminal it edits the line in
+// raw mode with history (up/down, Ctrl-R search) and tab completion;
+// otherwise it reads plain lines from the scanner.
+type lineEditor struct {
+	scanner  *bufio.Scanner
+	history  []string
+	histFile string
+	complete func(before []string) []string
+}
+
+// newLineEditor creates an editor reading from scanner when stdin is not
+// a terminal. History is loaded from and saved to histFile unless it is
+// empty; complete returns the words offered by tab completion after the
+// words before the one being completed.
+func newLineEditor(scanner *bufio.Scanner, histFile string, complete func(before []string) []string) *lineEditor {
+	e := &lineEditor{scanner: scanner, histFile: histFile, complete: complete}
+	if histFile != "" {
+		if data, err := os.ReadFile(histFile); err == nil {
+			for _, line := range strings.Split(string(data), "\n") {
+				if line = strings.TrimSpace(line); line != "" {
+					e.history = append(e.history, line)
+				}
+			}
+		}
+		e.trimHistory()
+	}
+	return e
+}
+
+// defaultHistoryFile returns ~/.c2c2_history, or "" without a home directory.
+func defaultHistoryFile() string {
+	home, err := os.UserHomeDir()
+	if err != nil {
+		return ""
+	}
+	return filepath.Join(home, HISTORY_FILE)
+}
+
+// ReadLine shows prompt and returns the next line. ok is false at the end
+// of input. Only lines typed on a terminal are added to the history.
+func (e *lineEditor) ReadLine(prompt string) (line string, ok bool) {
+	restore, err := enableRawMode(os.Stdin)
+	if err != nil {
+		fmt.Print(prompt)
+		if !e.scanner.Scan() {
+			return "", false
+		}
+		return e.scanner.Text(), true
+	}
+
+	line, ok = e.edit(os.Stdin, os.Stdout, prompt)
+	restore()
+	if ok {
+		e.addHistory(line)
+	}
+	return line, ok
+}
+
+// addHistory records line unless it is empty or repeats the last entry,
+// and appends it to the history file.
+func (e *lineEditor) addHistory(line string) {
+	line = strings.TrimSpace(line)
+	if line == "" || (len(e.history) > 0 && e.history[len(e.history)-1] == line) {
+		return
+	}
+	e.history = append(e.history, line)
+	e.trimHistory()
+
+	if e.histFile == "" {
+		return
+	}
+	if f, err := os.OpenFile(e.histFile, os.O_APPEND|os.O_CREATE|os.O_WRONLY, 0600); err == nil {
+		fmt.Fprintln(f, line)
+		f.Close()
+	}
+}
+
+func (e *lineEditor) trimHistory() {
+	if len(e.history) > HISTORY_MAX {
+		e.history = e.history[len(e.history)-HISTORY_MAX:]
+	}
+}
+
+// editState is the line being edited.
+type editState struct {
+	w      io.Writer
+	prompt string
+	buf    []rune
+	pos    int
+}
+
+// refresh redraws the prompt and the line and puts the cursor at pos.
+func (s *editState) refresh() {
+	fmt.Fprintf(s.w, "\r%s%s\x1b[K", s.prompt, string(s.buf))
+	if n := len(s.buf) - s.pos; n > 0 {
+		fmt.Fprintf(s.w, "\x1b[%dD", n)
+	}
+}
+
+func (s *editState) set(line string) {
+	s.buf = []rune(line)
+	s.pos = len(s.buf)
+}
+
+func (s *editState) insert(text []rune) {
+	buf := make([]rune, 0, len(s.buf)+len(text))
+	buf = append(buf, s.buf[:s.pos]...)
+	buf = append(buf, text...)
+	s.buf = append(buf, s.buf[s.pos:]...)
+	s.pos += len(text)
+}
+
+// readRune reads one UTF-8 encoded character byte by byte, so that
+// nothing after the line is consumed from r.
+func readRune(r io.Reader) (rune, error) {
+	var b [utf8.UTFMax]byte
+	n := 0
+	for {
+		if _, err := io.ReadFull(r, b[n:n+1]); err != nil {
+			return 0, err
+		}
+		n++
+		if utf8.FullRune(b[:n]) || n == utf8.UTFMax {
+			ch, _ := utf8.DecodeRune(b[:n])
+			return ch, nil
+		}
+	}
+}
+
+// edit reads keys from r in raw mode, echoing to w, until Enter.
+func (e *lineEditor) edit(r io.Reader, w io.Writer, prompt string) (string, bool) {
+	s := &editState{w: w, prompt: prompt}
+	histPos := len(e.history)
+	saved := ""
+	s.refresh()
+
+	for {
+		ch, err := readRune(r)
+		if err != nil {
+			fmt.Fprint(w, "\r\n")
+			return "", false
+		}
+
+		switch ch {
+		case KEY_ENTER, '\n':
+			fmt.Fprint(w, "\r\n")
+			return string(s.buf), true
+		case KEY_CTRL_D:
+			if len(s.buf) == 0 {
+				fmt.Fprint(w, "\r\n")
+				return "", false
+			}
+			if s.pos < len(s.buf) {
+				s.buf = append(s.buf[:s.pos], s.buf[s.pos+1:]...)
+			}
+		case KEY_CTRL_C:
+			fmt.Fprint(w, "^C\r\n")
+			s.set("")
+			histPos = len(e.history)
+		case KEY_BACKSPACE, KEY_DELETE:
+			if s.pos > 0 {
+				s.buf = append(s.buf[:s.pos-1], s.buf[s.pos:]...)
+				s.pos--
+			}
+		case KEY_CTRL_A:
+			s.pos = 0
+		case KEY_CTRL_E:
+			s.pos = len(s.buf)
+		case KEY_CTRL_B:
+			if s.pos > 0 {
+				s.pos--
+			}
+		case KEY_CTRL_F:
+			if s.pos < len(s.buf) {
+				s.pos++
+			}
+		case KEY_CTRL_K:
+			s.buf = s.buf[:s.pos]
+		case KEY_CTRL_U:
+			s.buf = s.buf[s.pos:]
+			s.pos = 0
+		case KEY_CTRL_P, KEY_CTRL_N:
+			histPos, saved = e.moveHistory(s, histPos, saved, ch == KEY_CTRL_P)
+		case KEY_TAB:
+			e.completeWord(s)
+		case KEY_CTRL_R:
+			line, accept, ok := e.search(r, s)
+			if !ok {
+				return "", false
+			}
+			if accept {
+				fmt.Fprint(w, "\r\n")
+				return line, true
+			}
+		case KEY_ESC:
+			seq, err := readEscape(r)
+			if err != nil {
+				return "", false
+			}
+			switch seq {
+			case "[A", "OA":
+				histPos, saved = e.moveHistory(s, histPos, saved, true)
+			case "[B", "OB":
+				histPos, saved = e.moveHistory(s, histPos, saved, false)
+			case "[C", "OC":
+				if s.pos < len(s.buf) {
+					s.pos++
+				}
+			case "[D", "OD":
+				if s.pos > 0 {
+					s.pos--
+				}
+			case "[H", "OH", "[1~":
+				s.pos = 0
+			case "[F", "OF", "[4~":
+				s.pos = len(s.buf)
+			case "[3~":
+				if s.pos < len(s.buf) {
+					s.buf = append(s.buf[:s.pos], s.buf[s.pos+1:]...)
+				}
+			}
+		default:
+			if ch >= ' ' {
+				s.insert([]rune{ch})
+			}
+		}
+		s.refresh()
+	}
+}
+
+// readEscape reads the rest of an escape sequence such as "[A" or "[3~".
+func readEscape(r io.Reader) (string, error) {
+	ch, err := readRune(r)
+	if err != nil {
+		return "", err
+	}
+	seq := string(ch)
+	if ch != '[' && ch != 'O' {
+		return seq, nil
+	}
+	for {
+		ch, err := readRune(r)
+		if err != nil {
+			return "", err
+		}
+		seq += string(ch)
+		if ch >= '@' && ch <= '~' {
+			return seq, nil
+		}
+	}
+}
+
+// moveHistory replaces the line with the previous (up) or next history
+// entry. The line being typed is kept in saved while browsing.
+func (e *lineEditor) moveHistory(s *editState, histPos int, saved string, up bool) (int, string) {
+	if histPos == len(e.history) {
+		saved = string(s.buf)
+	}
+	if up && histPos > 0 {
+		histPos--
+	} else if !up && histPos < len(e.history) {
+		histPos++
+	} else {
+		return histPos, saved
+	}
+	if histPos == len(e.history) {
+		s.set(saved)
+	} else {
+		s.set(e.history[histPos])
+	}
+	return histPos, saved
+}
+
+// search runs a Ctrl-R reverse incremental search. accept is true when
+// Enter was pressed on a match; otherwise the match is left in the line.
+func (e *lineEditor) search(r io.Reader, s *editState) (line string, accept bool, ok bool) {
+	query := []rune{}
+	idx := len(e.history)
+	match := ""
+
+	find := func(from int) {
+		for i := from; i >= 0; i-- {
+			if i < len(e.history) && strings.Contains(e.history[i], string(query)) {
+				idx = i
+				match = e.history[i]
+				return
+			}
+		}
+	}
+	show := func() {
+		fmt.Fprintf(s.w, "\r(reverse-i-search)`%s': %s\x1b[K", string(query), match)
+	}
+
+	show()
+	for {
+		ch, err := readRune(r)
+		if err != nil {
+			return "", false, false
+		}
+		switch ch {
+		case KEY_ENTER, '\n':
+			return match, true, true
+		case KEY_CTRL_R:
+			find(idx - 1)
+		case KEY_BACKSPACE, KEY_DELETE:
+			if len(query) > 0 {
+				query = query[:len(query)-1]
+				find(len(e.history) - 1)
+			}
+		case KEY_CTRL_G, KEY_CTRL_C:
+			return "", false, true
+		default:
+			if ch >= ' ' {
+				query = append(query, ch)
+				find(idx)
+				break
+			}
+			if match != "" {
+				s.set(match)
+			}
+			return "", false, true
+		}
+		show()
+	}
+}
+
+// completeWord completes the word before the cursor from the words
+// complete offers there. With several candidates it inserts their common
+// prefix, or lists them when there is nothing more to insert.
+func (e *lineEditor) completeWord(s *editState) {
+	if e.complete == nil {
+		return
+	}
+	start := s.pos
+	for start > 0 && s.buf[start-1] != ' ' {
+		start--
+	}
+	prefix := string(s.buf[start:s.pos])
+
+	candidates := completions(e.complete(strings.Fields(string(s.buf[:start]))), prefix)
+	switch len(candidates) {
+	case 0:
+		return
+	case 1:
+		s.insert([]rune(strings.TrimPrefix(candidates[0], prefix) + " "))
+		return
+	}
+
+	common := candidates[0]
+	for _, c := range candidates[1:] {
+		for !strings.HasPrefix(c, common) {
+			common = common[:len(common)-1]
+		}
+	}
+	if len(common) > len(prefix) {
+		s.insert([]rune(common[len(prefix):]))
+		return
+	}
+	fmt.Fprintf(s.w, "\r\n%s\r\n", strings.Join(candidates, "  "))
+}
+
+// completions returns the sorted, unique words starting with prefix.
+func completions(words []string, prefix string) []string {
+	seen := make(map[string]bool)
+	var result []string
+	for _, w := range words {
+		if strings.HasPrefix(w, prefix) && !seen[w] {
+			seen[w] = true
+			result = append(result, w)
+		}
+	}
+	sort.Strings(result)
+	return result
+}
@@ -0,0 +1,97 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// editKeys feeds keys to the line editor and returns the line it read.
+func editKeys(t *testing.T, e *lineEditor, keys string) string {
+	t.Helper()
+
+	line, ok := e.edit(strings.NewReader(keys), &bytes.Buffer{}, "> ")
+	if !ok {
+		t.Fatalf("Unexpected end of input for %q", keys)
+	}
+	return line
+}
+
+func TestLineEditorEditing(t *testing.T) {
+	e := newLineEditor(nil, "", nil)
+	e.history = []string{"step 5", "print", "dump #0010"}
+
+	tests := []struct {
+		keys string
+		want string
+	}{
+		{"run\r", "run"},
+		{"rnu\x08\x08un\r", "run"},
+		{"un\x01r\r", "run"},
+		{"tep\x1b[D\x1b[D\x1b[Ds\x05 2\r", "step 2"},
+		{"\x1b[A\r", "dump #0010"},
+		{"\x1b[A\x1b[A\x1b[A\x1b[B\r", "print"},
+		{"xx\x1b[A\x1b[B\r", "xx"},
+		{"\x12step\r", "step 5"},
+		{"\x12t\x12\r", "step 5"},
+		{"\x12pri\x05 1\r", "print 1"},
+		{"abc\x03run\r", "run"},
+	}
+	for _, tt := range tests {
+		if got := editKeys(t, e, tt.keys); got != tt.want {
+			t.Errorf("Keys %q: expected %q, got %q", tt.keys, tt.want, got)
+		}
+	}
+
+	if _, ok := e.edit(strings.NewReader("\x04"), &bytes.Buffer{}, "> "); ok {
+		t.Errorf("Expected Ctrl-D on an empty line to end input")
+	}
+}
+
+func TestLineEditorCompletion(t *testing.T) {
+	e := newLineEditor(nil, "", func(before []string) []string {
+		if len(before) > 0 {
+			return []string{"LOOP", "LEN"}
+		}
+		return []string{"step", "stack", "st", "run"}
+	})
+
+	if got := editKeys(t, e, "ru\t\r"); got != "run " {
+		t.Errorf("Expected \"run \", got %q", got)
+	}
+	if got := editKeys(t, e, "sta\t\r"); got != "stack " {
+		t.Errorf("Expected \"stack \", got %q", got)
+	}
+	if got := editKeys(t, e, "dump LO\t\r"); got != "dump LOOP " {
+		t.Errorf("Expected \"dump LOOP \", got %q", got)
+	}
+	if got := editKeys(t, e, "LO\t\r"); got != "LO" {
+		t.Errorf("Expected no label as a command, got %q", got)
+	}
+
+	if got := completions([]string{"step", "stack", "st", "run"}, "st"); !reflect.DeepEqual(got, []string{"st", "stack", "step"}) {
+		t.Errorf("Unexpected completions %v", got)
+	}
+}
+
+func TestLineEditorHistoryFile(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "history")
+	if err := os.WriteFile(path, []byte("run\nprint\n"), 0600); err != nil {
+		t.Fatalf("Failed to write history: %v", err)
+	}
+
+	e := newLineEditor(nil, path, nil)
+	e.addHistory("print")
+	e.addHistory("step 3")
+	e.addHistory("")
+
+	if want := []string{"run", "print", "step 3"}; !reflect.DeepEqual(e.history, want) {
+		t.Errorf("Expected history %v, got %v", want, e.history)
+	}
+	if e2 := newLineEditor(nil, path, nil); !reflect.DeepEqual(e2.history, e.history) {
+		t.Errorf("Expected saved history %v, got %v", e.history, e2.history)
+	}
+}
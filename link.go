@@ -0,0 +1,252 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"path/filepath"
+	"slices"
+	"sort"
+	"strings"
+)
+
+// linkItem is a region of the image in the link map: a program unit, a
+// labeled DS or DC, or a literal pool.
+type linkItem struct {
+	start int
+	words int
+	unit  string
+	what  string
+	// pos is the line of the item, 0 for literal pools.
+	pos int
+}
+
+// unresolvedLabel is a label that no unit defines and no START names, and
+// the line referring to it.
+type unresolvedLabel struct {
+	name string
+	pos  int
+}
+
+// unitAt returns the name of the unit holding addr.
+func unitAt(units []UnitSize, addr int) string {
+	for _, u := range units {
+		if addr >= u.Start && addr < u.Start+u.Total() {
+			return u.Name
+		}
+	}
+	return ""
+}
+
+// linkMap lays out the program of asmState after pass 1, when every
+// address is known but no label has been resolved: its units, data blocks
+// and literal pools by address as p places them, and the labels pass 2
+// would fail to resolve.
+func linkMap(asmState *AssemblerState, p *unitPlacement) ([]linkItem, []unresolvedLabel) {
+	var items []linkItem
+	for _, u := range asmState.units {
+		items = append(items, linkItem{start: u.Start, words: u.Total(), unit: u.Name, what: "unit " + u.Name,
+			pos: asmState.symtbl[u.Name+":"+u.Name].Line})
+	}
+
+	// The words of a DS or DC are the memory entries of its line
+	words := make(map[int]int)
+	for _, entry := range asmState.memory {
+		words[entry.Line]++
+	}
+	for i, text := range asmState.buf {
+		parts := strings.SplitN(text, "\t", 3)
+		if len(parts) < 3 || parts[0] == "" || (parts[1] != "DS" && parts[1] != "DC") {
+			continue
+		}
+		start := expandLabel(asmState.symtbl, parts[0])
+		name := parts[0][strings.Index(parts[0], ":")+1:]
+		items = append(items, linkItem{start: start, words: words[asmState.bufPos[i]], unit: unitAt(asmState.units, start),
+			what: strings.ToLower(parts[1]) + " " + name, pos: asmState.bufPos[i]})
+	}
+
+	// Literals placed one after another make one pool
+	literals := slices.Clone(asmState.literals)
+	sort.Slice(literals, func(i, j int) bool {
+		return literals[i].Address < literals[j].Address
+	})
+	for i, lit := range literals {
+		if i > 0 && lit.Address == literals[i-1].Address+1 {
+			items[len(items)-1].words++
+			continue
+		}
+		items = append(items, linkItem{start: lit.Address, words: 1, unit: unitAt(asmState.units, lit.Address), what: "literal pool"})
+	}
+	for i := range items {
+		items[i].start = p.move(items[i].start)
+	}
+	sort.SliceStable(items, func(i, j int) bool {
+		return items[i].start < items[j].start
+	})
+
+	var unresolved []unresolvedLabel
+	seen := make(map[unresolvedLabel]bool)
+	var addresses []int
+	for address := range asmState.memory {
+		addresses = append(addresses, address)
+	}
+	sort.Ints(addresses)
+	for _, address := range addresses {
+		entry := asmState.memory[address]
+		if _, ok := resolveLabel(asmState.symtbl, entry.Val); ok {
+			continue
+		}
+		name := fmt.Sprint(entry.Val)
+		if matches := scopedLabelPattern.FindStringSubmatch(name); matches != nil {
+			name = matches[1]
+		}
+		label := unresolvedLabel{name, entry.Line}
+		if !seen[label] {
+			seen[label] = true
+			unresolved = append(unresolved, label)
+		}
+	}
+	return items, unresolved
+}
+
+// formatLinkMap renders the link map of asmState.
+func formatLinkMap(asmState *AssemblerState, p *unitPlacement) string {
+	var b strings.Builder
+	items, unresolved := linkMap(asmState, p)
+	b.WriteString("Address       Words  Unit      Item\n")
+	for _, item := range items {
+		end := item.start + max(item.words, 1) - 1
+		// Data blocks and pools are indented under their unit
+		what := "  " + item.what
+		if strings.HasPrefix(item.what, "unit ") {
+			what = item.what
+		}
+		if item.pos > 0 {
+			src := asmState.origin(item.pos)
+			what += fmt.Sprintf(" (%s:%d)", src.File, src.Line)
+		} else {
+			what += fmt.Sprintf(" (%d literals)", item.words)
+		}
+		fmt.Fprintf(&b, "#%s-#%s  %5d  %-8s  %s\n", hex(item.start, 4), hex(end, 4), item.words, item.unit, what)
+	}
+	total := 0
+	for _, u := range asmState.units {
+		total += u.Total()
+	}
+	fmt.Fprintf(&b, "%d units, %d words\n", len(asmState.units), total)
+	if len(unresolved) == 0 {
+		b.WriteString("No unresolved labels\n")
+		return b.String()
+	}
+	fmt.Fprintf(&b, "%d unresolved labels:\n", len(unresolved))
+	for _, label := range unresolved {
+		src := asmState.origin(label.pos)
+		fmt.Fprintf(&b, "  %s (%s:%d)\n", label.name, src.File, src.Line)
+	}
+	return b.String()
+}
+
+// runLinkCommand implements "c2c2 link": it assembles FILE.cas ... or the
+// sources of c2c2.toml as one program, or reads FILE.c2o, places its units
+// at -base and -unit-base and writes the object file, or with -dry-run
+// only prints where everything would be placed and the labels that would
+// not resolve. It returns the exit status.
+func runLinkCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("link", flag.ContinueOnError)
+	fs.SetOutput(w)
+	dryRun := fs.Bool("dry-run", false, "print the link map and the unresolved labels without writing anything")
+	output := fs.String("o", "", "write the object to the given file (default: the first source with "+OBJECT_EXT+")")
+	baseText := fs.String("base", "0", "load address of the first unit; the others follow it")
+	unitBases := fs.String("unit-base", "", "load addresses of units, as NAME=ADDRESS separated by commas; the units after one follow it")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 link [-dry-run] [-base ADDRESS] [-unit-base NAME=ADDRESS,...] [-o OUTPUT] [FILE.cas ... | FILE%s]\n"+
+			"       (default: the sources of %s)\n\nOptions:\n", OBJECT_EXT, PROJECT_FILE)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	base, ok := expandNumber(*baseText)
+	if !ok {
+		fmt.Fprintf(w, "[CASL2 ERROR] Invalid base address \"%s\"\n", *baseText)
+		return 2
+	}
+	bases, err := parseUnitBases(*unitBases)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 2
+	}
+
+	paths := fs.Args()
+	entry := ""
+	if len(paths) == 0 {
+		p, err := loadProject(".")
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		paths, entry = p.Sources, p.Entry
+	}
+	relocating := len(paths) == 1 && strings.HasSuffix(paths[0], OBJECT_EXT)
+	if *output == "" {
+		*output = strings.TrimSuffix(paths[0], filepath.Ext(paths[0])) + OBJECT_EXT
+	}
+
+	asmState := newAssemblerState()
+	if *dryRun && !relocating {
+		lines, err := readProgram(paths, asmState)
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		asmState.file = paths[0]
+		asmState.lines = lines
+		if err := collectAllows(asmState); err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		if _, err := pass1(asmState); err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		placement, err := placeUnits(asmState.units, base, bases)
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		fmt.Fprint(w, formatLinkMap(asmState, placement))
+		if _, unresolved := linkMap(asmState, placement); len(unresolved) > 0 {
+			return 1
+		}
+		return 0
+	}
+
+	var obj *Object
+	if relocating {
+		obj, err = loadObjectFile(paths[0])
+	} else {
+		obj, err = assembleProject(&Project{Sources: paths, Entry: entry}, asmState)
+	}
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	placement, err := placeUnits(obj.Units, base, bases)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	obj = relocateObject(obj, placement)
+	if *dryRun {
+		for i, u := range obj.Units {
+			fmt.Fprintf(w, "#%s-#%s  %5d  unit %s\n", hex(placement.starts[i], 4), hex(placement.starts[i]+max(u.Total(), 1)-1, 4), u.Total(), u.Name)
+		}
+		return 0
+	}
+	if err := writeObjectFile(*output, obj); err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	fmt.Fprintf(w, "Linked %d units into %s (%d words)\n", len(obj.Units), *output, len(obj.Bin))
+	return 0
+}
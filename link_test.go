@@ -0,0 +1,46 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestLinkDryRun(t *testing.T) {
+	dir := t.TempDir()
+	mainPath := filepath.Join(dir, "main.cas")
+	subPath := filepath.Join(dir, "sub.cas")
+	os.WriteFile(mainPath, []byte("MAIN\tSTART\n\tLD\tGR1,=5\n\tCALL\tSUB\n\tCALL\tPRINT\n\tRET\nBUF\tDS\t3\n\tEND\n"), 0644)
+	os.WriteFile(subPath, []byte("SUB\tSTART\n\tLD\tGR0,ONE\n\tRET\nONE\tDC\t1\n\tEND\n"), 0644)
+
+	var out bytes.Buffer
+	if status := runLinkCommand([]string{"-dry-run", mainPath, subPath}, &out); status != 1 {
+		t.Errorf("Expected status 1 for an unresolved label, got %d", status)
+	}
+	expected := "Address       Words  Unit      Item\n" +
+		"#0000-#000a     11  MAIN      unit MAIN (" + mainPath + ":1)\n" +
+		"#0007-#0009      3  MAIN        ds BUF (" + mainPath + ":6)\n" +
+		"#000a-#000a      1  MAIN        literal pool (1 literals)\n" +
+		"#000b-#000e      4  SUB       unit SUB (" + subPath + ":1)\n" +
+		"#000e-#000e      1  SUB         dc ONE (" + subPath + ":4)\n" +
+		"2 units, 15 words\n" +
+		"1 unresolved labels:\n" +
+		"  PRINT (" + mainPath + ":4)\n"
+	if out.String() != expected {
+		t.Errorf("Expected\n%s\ngot\n%s", expected, out.String())
+	}
+	if _, err := os.Stat(filepath.Join(dir, "main"+OBJECT_EXT)); err == nil {
+		t.Errorf("Expected -dry-run to write nothing")
+	}
+
+	// Without the unresolved call the program links
+	os.WriteFile(mainPath, []byte("MAIN\tSTART\n\tCALL\tSUB\n\tRET\n\tEND\n"), 0644)
+	out.Reset()
+	if status := runLinkCommand([]string{mainPath, subPath}, &out); status != 0 {
+		t.Fatalf("Expected link to succeed, got %d:\n%s", status, out.String())
+	}
+	if _, err := loadObjectFile(filepath.Join(dir, "main"+OBJECT_EXT)); err != nil {
+		t.Errorf("Expected an object file, got %v", err)
+	}
+}
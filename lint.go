@@ -0,0 +1,100 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// constantEntry is a labeled DC whose first word is a number, which the
+// program can refer to instead of writing the number as a literal.
+type constantEntry struct {
+	Label   string
+	Value   int
+	Address int
+	Line    int
+}
+
+// jumpEntry is a jump to a label. Line is its position in the
+// preprocessed source and Target the scoped label.
+type jumpEntry struct {
+	Line   int
+	Target string
+}
+
+// literalValue returns the word of a numeric literal such as =5 or
+// =#FFFF; ok is false for character literals.
+func literalValue(text string) (int, bool) {
+	return expandNumber(strings.TrimPrefix(text, "="))
+}
+
+// lintLiterals warns about literals that repeat the value of a labeled DC
+// in the same program unit, which costs a word the label would save, and
+// about literals inside a loop, where a named DC says more about what the
+// number is for.
+func lintLiterals(asmState *AssemblerState) []Diagnostic {
+	var diags []Diagnostic
+	warn := func(pos int, name string, format string, args ...interface{}) {
+		diags = append(diags, warning(asmState, pos, name, fmt.Sprintf(format, args...))...)
+	}
+	unitOf := func(addr int) int {
+		unit := -1
+		for i, u := range asmState.units {
+			if u.Start <= addr {
+				unit = i
+			}
+		}
+		return unit
+	}
+
+	for _, lit := range asmState.literals {
+		if val, ok := literalValue(lit.Text); ok {
+			for _, c := range asmState.constants {
+				if c.Value == val && unitOf(c.Address) == unitOf(lit.Address) {
+					warn(lit.Line, WARN_LITERAL_DC, "Literal %s repeats DC %s on line %d; use %s to save a word", lit.Text, c.Label, asmState.origin(c.Line).Line, c.Label)
+					break
+				}
+			}
+		}
+
+		for _, j := range asmState.jumps {
+			target, ok := asmState.symtbl[j.Target]
+			if !ok || target.Line > lit.Line || lit.Line > j.Line {
+				continue
+			}
+			label := j.Target[strings.Index(j.Target, ":")+1:]
+			warn(lit.Line, WARN_LITERAL_LOOP, "Literal %s is inside the loop at %s (lines %d-%d); a named DC constant would be clearer", lit.Text, label, asmState.origin(target.Line).Line, asmState.origin(j.Line).Line)
+			break
+		}
+	}
+
+	sort.SliceStable(diags, func(i, j int) bool {
+		return diags[i].Line < diags[j].Line
+	})
+	return diags
+}
+
+// formatLiterals renders the literals of the program grouped by their
+// text, with the address of each copy in the pools and the line using it.
+func formatLiterals(asmState *AssemblerState) string {
+	var b strings.Builder
+	var texts []string
+	uses := make(map[string][]LiteralEntry)
+	for _, lit := range asmState.literals {
+		if _, ok := uses[lit.Text]; !ok {
+			texts = append(texts, lit.Text)
+		}
+		uses[lit.Text] = append(uses[lit.Text], lit)
+	}
+
+	b.WriteString("Literal       Uses  Addresses\n")
+	for _, text := range texts {
+		var places []string
+		for _, lit := range uses[text] {
+			places = append(places, fmt.Sprintf("#%s (line %d)", hex(lit.Address, 4), asmState.origin(lit.Line).Line))
+		}
+		fmt.Fprintf(&b, "%-12s %5d  %s\n", text, len(uses[text]), strings.Join(places, ", "))
+	}
+	fmt.Fprintf(&b, "%d literals, %d distinct\n", len(asmState.literals), len(texts))
+	return b.String()
+}
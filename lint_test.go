@@ -0,0 +1,42 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestLintLiterals(t *testing.T) {
+	src := `MAIN	START
+	LAD	GR1,0
+LOOP	ADDA	GR1,=1
+	CPA	GR1,=10
+	JNZ	LOOP
+	ADDA	GR2,=1
+	RET
+ONE	DC	1
+	END
+`
+	*optLint = true
+	defer func() { *optLint = false }()
+	asmState := newAssemblerState()
+	if _, err := assembleText("prog.cas", src, asmState); err != nil {
+		t.Fatal(err)
+	}
+	want := []Diagnostic{
+		{Line: 3, Msg: "Literal =1 repeats DC ONE on line 8; use ONE to save a word", Severity: "warning", Code: WARN_LITERAL_DC},
+		{Line: 3, Msg: "Literal =1 is inside the loop at LOOP (lines 3-5); a named DC constant would be clearer", Severity: "warning", Code: WARN_LITERAL_LOOP},
+		{Line: 4, Msg: "Literal =10 is inside the loop at LOOP (lines 3-5); a named DC constant would be clearer", Severity: "warning", Code: WARN_LITERAL_LOOP},
+		{Line: 6, Msg: "Literal =1 repeats DC ONE on line 8; use ONE to save a word", Severity: "warning", Code: WARN_LITERAL_DC},
+	}
+	if !reflect.DeepEqual(asmState.warnings, want) {
+		t.Errorf("Unexpected warnings %v", asmState.warnings)
+	}
+
+	report := "Literal       Uses  Addresses\n" +
+		"=1               2  #000C (line 3), #000E (line 6)\n" +
+		"=10              1  #000D (line 4)\n" +
+		"3 literals, 2 distinct\n"
+	if got := formatLiterals(asmState); got != report {
+		t.Errorf("Unexpected literal report:\n%s", got)
+	}
+}
@@ -0,0 +1,69 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// formatSourceListing renders the source files of the program as they are
+// written, comments and spacing included, like the print-out of a classic
+// assembler: each line with its number and the address and word of the
+// first word generated from it, the other words of the line on their own
+// lines below, and then the warnings of the line. Files the program
+// includes are listed after it under their names.
+func formatSourceListing(asmState *AssemblerState, obj *Object) string {
+	words := make(map[SourceLocation][]int)
+	for addr, loc := range obj.SourceMap {
+		if loc.Line > 0 {
+			words[loc] = append(words[loc], addr)
+		}
+	}
+	// Diagnostics keep the line but not the file, so warnings are shown
+	// in the program file
+	warnings := make(map[int][]Diagnostic)
+	for _, w := range asmState.warnings {
+		warnings[w.Line] = append(warnings[w.Line], w)
+	}
+
+	var files []string
+	seen := make(map[string]bool)
+	for _, l := range asmState.lines {
+		if l.File != "" && !seen[l.File] {
+			seen[l.File] = true
+			files = append(files, l.File)
+		}
+	}
+
+	var b strings.Builder
+	for _, file := range files {
+		content, err := asmState.sources.ReadSource(file)
+		if err != nil {
+			continue
+		}
+		if len(files) > 1 {
+			fmt.Fprintf(&b, "; %s\n", file)
+		}
+		text := strings.Split(strings.ReplaceAll(content, "\r\n", "\n"), "\n")
+		if text[len(text)-1] == "" {
+			text = text[:len(text)-1]
+		}
+		for i, line := range text {
+			loc := SourceLocation{File: file, Line: i + 1}
+			addrs := words[loc]
+			if len(addrs) == 0 {
+				fmt.Fprintln(&b, strings.TrimRight(fmt.Sprintf("%4d %9s  %s", i+1, "", line), " \t"))
+			} else {
+				fmt.Fprintln(&b, strings.TrimRight(fmt.Sprintf("%4d %s %s  %s", i+1, hex(addrs[0], 4), hex(int(obj.Bin[addrs[0]]), 4), line), " \t"))
+				for _, addr := range addrs[1:] {
+					fmt.Fprintf(&b, "     %s %s\n", hex(addr, 4), hex(int(obj.Bin[addr]), 4))
+				}
+			}
+			if file == asmState.file {
+				for _, w := range warnings[i+1] {
+					fmt.Fprintf(&b, "     *** WARNING: %s [%s]\n", w.Msg, w.Code)
+				}
+			}
+		}
+	}
+	return b.String()
+}
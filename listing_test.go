@@ -0,0 +1,33 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"testing"
+)
+
+func TestSourceListing(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "big.cas")
+	source := "; Load five\nMAIN    START\n        LD      GR1,=5  ; five\n\n        RET\nBIG     DC      70000\n        END\n"
+	if err := os.WriteFile(path, []byte(source), 0644); err != nil {
+		t.Fatal(err)
+	}
+	asmState := newAssemblerState()
+	obj, err := assemble(path, asmState)
+	if err != nil {
+		t.Fatal(err)
+	}
+
+	want := "   1            ; Load five\n" +
+		"   2            MAIN    START\n" +
+		"   3 0000 1010          LD      GR1,=5  ; five\n" +
+		"     0001 0004\n" +
+		"   4\n" +
+		"   5 0002 8100          RET\n" +
+		"   6 0003 1170  BIG     DC      70000\n" +
+		"     *** WARNING: \"70000\" does not fit in a word and is truncated to #1170 [truncated-number]\n" +
+		"   7 0004 0005          END\n"
+	if got := formatSourceListing(asmState, obj); got != want {
+		t.Errorf("Expected\n%s\ngot\n%s", want, got)
+	}
+}
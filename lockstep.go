@@ -0,0 +1,186 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"slices"
+)
+
+// Maximum number of instructions each machine may execute in lockstep mode
+const LOCKSTEP_MAX_STEPS = 1000000
+
+// Maximum number of differing memory words listed in a divergence
+const LOCKSTEP_MAX_MEMORY_DIFFS = 8
+
+// Lockstep event kinds
+const (
+	LOCKSTEP_OUT  = "OUT"
+	LOCKSTEP_HALT = "HALT"
+)
+
+// lockstepEvent is an observable action of one machine. Err is the error
+// a HALT event stopped with, if any.
+type lockstepEvent struct {
+	Kind string
+	Text string
+	Step int
+	PC   int
+	Err  error
+}
+
+// sameEvent reports whether a and b are the same observable action. Two
+// runtime errors are the same when their kind and code are: their
+// messages name addresses and source lines, which differ between
+// equivalent programs.
+func sameEvent(a, b lockstepEvent) bool {
+	if a.Kind != b.Kind {
+		return false
+	}
+	var aerr, berr *RuntimeError
+	if errors.As(a.Err, &aerr) && errors.As(b.Err, &berr) {
+		return aerr.Kind == berr.Kind && aerr.Code == berr.Code
+	}
+	return a.Text == b.Text
+}
+
+// Divergence describes the first observable difference between two
+// machines. When they halted alike, State lists the registers and memory
+// words that differ in the end.
+type Divergence struct {
+	Left  lockstepEvent
+	Right lockstepEvent
+	State []string
+}
+
+// lockstepSide drives one machine and collects its output.
+type lockstepSide struct {
+	c      *Comet2
+	inputs []string
+	out    []string
+	steps  int
+}
+
+func newLockstepSide(c *Comet2, inputs []string) *lockstepSide {
+	s := &lockstepSide{c: c, inputs: append([]string(nil), inputs...)}
+	c.Output = func(text string) {
+		s.out = append(s.out, text)
+	}
+	return s
+}
+
+// next runs the machine until it writes with OUT or halts.
+func (s *lockstepSide) next(maxSteps int) lockstepEvent {
+	for s.steps < maxSteps {
+		pc := s.c.State[PC]
+
+		if s.c.InputMode == INPUT_MODE_IN {
+			if len(s.inputs) == 0 {
+				return lockstepEvent{LOCKSTEP_HALT, "Input exhausted", s.steps, pc, nil}
+			}
+			s.c.execIn(s.inputs[0])
+			s.inputs = s.inputs[1:]
+			s.c.InputMode = INPUT_MODE_CMD
+			continue
+		}
+
+		_, err := s.c.stepExec()
+		s.steps++
+		if err != nil {
+			return lockstepEvent{LOCKSTEP_HALT, err.Error(), s.steps, pc, err}
+		}
+
+		if len(s.out) > 0 {
+			text := s.out[0]
+			s.out = s.out[1:]
+			return lockstepEvent{LOCKSTEP_OUT, text, s.steps, pc, nil}
+		}
+	}
+
+	return lockstepEvent{LOCKSTEP_HALT, "Step limit exceeded", s.steps, s.c.State[PC], nil}
+}
+
+// runLockstep executes two machines on the same inputs and returns the
+// first point where their OUT text or halt reason differ, or where their
+// final state does, or nil.
+func runLockstep(left, right *Comet2, inputs []string, maxSteps int) *Divergence {
+	// Memory is only compared between runs of the same program: in two
+	// different ones everything down to the return addresses left on the
+	// stack is laid out differently
+	sameProgram := slices.Equal(left.Memory, right.Memory)
+	l := newLockstepSide(left, inputs)
+	r := newLockstepSide(right, inputs)
+
+	for {
+		le := l.next(maxSteps)
+		re := r.next(maxSteps)
+
+		if !sameEvent(le, re) {
+			return &Divergence{Left: le, Right: re}
+		}
+		if le.Kind == LOCKSTEP_HALT {
+			if state := diffFinalState(left, right, sameProgram); len(state) > 0 {
+				return &Divergence{Left: le, Right: re, State: state}
+			}
+			return nil
+		}
+	}
+}
+
+// diffFinalState lists the general registers and FR, and with memory the
+// memory words, that differ between left and right.
+func diffFinalState(left, right *Comet2, memory bool) []string {
+	var diffs []string
+	for i := 0; i < 8; i++ {
+		if l, r := left.State[GR0+i], right.State[GR0+i]; l != r {
+			diffs = append(diffs, fmt.Sprintf("GR%d #%s / #%s", i, hex(l, 4), hex(r, 4)))
+		}
+	}
+	if l, r := left.State[FR], right.State[FR]; l != r {
+		diffs = append(diffs, fmt.Sprintf("FR %s / %s", formatFR(l), formatFR(r)))
+	}
+
+	if !memory {
+		return diffs
+	}
+	words := 0
+	for addr := 0; addr < len(left.Memory) && addr < len(right.Memory); addr++ {
+		if left.Memory[addr] == right.Memory[addr] {
+			continue
+		}
+		if words < LOCKSTEP_MAX_MEMORY_DIFFS {
+			diffs = append(diffs, fmt.Sprintf("#%s #%s / #%s", hex(addr, 4), hex(int(left.Memory[addr]), 4), hex(int(right.Memory[addr]), 4)))
+		}
+		words++
+	}
+	if words > LOCKSTEP_MAX_MEMORY_DIFFS {
+		diffs = append(diffs, fmt.Sprintf("... and %d more memory words", words-LOCKSTEP_MAX_MEMORY_DIFFS))
+	}
+	return diffs
+}
+
+// runDiffMode assembles the reference program, runs it in lockstep with
+// the program under test and exits with the result.
+func runDiffMode(refFilepath string, machine *Comet2) {
+	refObj, err := assemble(refFilepath, newAssemblerState())
+	if err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	ref := LoadObject(refObj)
+
+	d := runLockstep(ref, machine, inputBuffer, LOCKSTEP_MAX_STEPS)
+	if d == nil {
+		cometPrint(colorWhiteGreen("[Lockstep] No divergence found."))
+		os.Exit(0)
+	}
+
+	cometPrint(colorRedYellow(fmt.Sprintf("[Lockstep] Diverged at step %d (#%s) / step %d (#%s)",
+		d.Left.Step, hex(d.Left.PC, 4), d.Right.Step, hex(d.Right.PC, 4))))
+	cometPrint(fmt.Sprintf("  reference: %s %q", d.Left.Kind, d.Left.Text))
+	cometPrint(fmt.Sprintf("  program:   %s %q", d.Right.Kind, d.Right.Text))
+	for _, line := range d.State {
+		cometPrint(fmt.Sprintf("  final state differs: %s", line))
+	}
+	os.Exit(1)
+}
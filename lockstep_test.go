@@ -0,0 +1,62 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestLockstep(t *testing.T) {
+	run := func(left, right string, inputs []string) *Divergence {
+		t.Helper()
+		return runLockstep(assembleSource(t, left), assembleSource(t, right), inputs, LOCKSTEP_MAX_STEPS)
+	}
+
+	// The same output from programs laid out differently
+	ref := "MAIN\tSTART\n\tIN\tBUF,LEN\n\tOUT\tBUF,LEN\n\tRET\nBUF\tDS\t16\nLEN\tDS\t1\n\tEND\n"
+	padded := "MAIN\tSTART\n\tNOP\n\tNOP\n\tIN\tBUF,LEN\n\tOUT\tBUF,LEN\n\tRET\nBUF\tDS\t16\nLEN\tDS\t1\n\tEND\n"
+	if d := run(ref, padded, []string{"abc"}); d != nil {
+		t.Errorf("Expected no divergence, got %+v", d)
+	}
+
+	// Different output
+	other := "MAIN\tSTART\n\tIN\tBUF,LEN\n\tOUT\tX,ONE\n\tRET\nBUF\tDS\t16\nLEN\tDS\t1\nX\tDC\t'x'\nONE\tDC\t1\n\tEND\n"
+	d := run(ref, other, []string{"abc"})
+	if d == nil || d.Left.Kind != LOCKSTEP_OUT || d.Left.Text != "abc" || d.Right.Text != "x" {
+		t.Fatalf("Expected OUT to diverge, got %+v", d)
+	}
+
+	// Stack overflows at different addresses halt alike; a different exit
+	// code does not
+	overflow := "MAIN\tSTART\nLOOP\tPUSH\t0\n\tJUMP\tLOOP\n\tEND\n"
+	if d := run(overflow, "MAIN\tSTART\n\tNOP\n"+overflow[len("MAIN\tSTART\n"):], nil); d != nil {
+		t.Errorf("Expected the same stack overflow, got %q and %q", d.Left.Text, d.Right.Text)
+	}
+	exit1 := "MAIN\tSTART\n\tSVC\t1\n\tEND\n"
+	exit2 := "MAIN\tSTART\n\tSVC\t2\n\tEND\n"
+	if d := run(exit1, exit2, nil); d == nil || d.Left.Kind != LOCKSTEP_HALT {
+		t.Errorf("Expected exit codes 1 and 2 to diverge, got %+v", d)
+	}
+
+	// The same output and halt, but different registers in the end
+	d = run("MAIN\tSTART\n\tLAD\tGR1,1\n\tRET\n\tEND\n", "MAIN\tSTART\n\tLAD\tGR1,2\n\tRET\n\tEND\n", nil)
+	if d == nil || len(d.State) != 1 || d.State[0] != "GR1 #0001 / #0002" {
+		t.Fatalf("Expected GR1 to differ, got %+v", d)
+	}
+
+	// Memory is compared between runs of the same program, as on two engines
+	src := "MAIN\tSTART\n\tLD\tGR1,A\n\tST\tGR1,B\n\tRET\nA\tDC\t5\nB\tDS\t1\n\tEND\n"
+	left, right := assembleSource(t, src), assembleSource(t, src)
+	if err := right.SetEngine("block"); err != nil {
+		t.Fatalf("Failed to set engine: %v", err)
+	}
+	if d := runLockstep(left, right, nil, LOCKSTEP_MAX_STEPS); d != nil {
+		t.Errorf("Expected the engines to agree, got %+v", d)
+	}
+	store := "MAIN\tSTART\n\tST\tGR2,B\n\tRET\nB\tDS\t1\n\tEND\n"
+	left, right = assembleSource(t, store), assembleSource(t, store)
+	right.State[GR2] = 7
+	d = runLockstep(left, right, nil, LOCKSTEP_MAX_STEPS)
+	if d == nil || strings.Join(d.State, ", ") != "GR2 #0000 / #0007, #0003 #0000 / #0007" {
+		t.Fatalf("Expected GR2 and B to differ, got %+v", d)
+	}
+}
@@ -0,0 +1,81 @@
+package main
+
+// Number of remembered loop states before the history is cleared
+const LOOP_HISTORY_MAX = 1 << 20
+
+// loopDetector halts the machine when the same state is seen twice at a
+// loop head with no IN/OUT in between. The state is PR, FR, SP, the
+// registers and the contents of every word written since the last I/O;
+// since a program without input is deterministic, a repeated state means
+// it will never finish. States are compared by hash, so a collision may
+// produce a false report. The hash of the written words is kept up to date
+// on each write, so a step costs the same however much was written.
+type loopDetector struct {
+	BaseObserver
+	written map[int]bool
+	memHash uint64
+	seen    map[uint64]bool
+}
+
+func newLoopDetector() *loopDetector {
+	d := &loopDetector{}
+	d.reset()
+	return d
+}
+
+func (d *loopDetector) reset() {
+	d.written = make(map[int]bool)
+	d.memHash = 0
+	d.seen = make(map[uint64]bool)
+}
+
+// wordHash is the contribution of the word at addr holding val to memHash.
+func wordHash(addr int, val int) uint64 {
+	return mix64(uint64(addr)<<16 | uint64(val&0xffff))
+}
+
+func (d *loopDetector) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	// A word not written since the last I/O has no contribution yet
+	if d.written[addr] {
+		d.memHash ^= wordHash(addr, old)
+	}
+	d.written[addr] = true
+	d.memHash ^= wordHash(addr, val)
+}
+
+func (d *loopDetector) Svc(c *Comet2, entry int) {
+	if entry == SYS_IN || entry == SYS_OUT {
+		d.reset()
+	}
+}
+
+func (d *loopDetector) AfterStep(c *Comet2, pc int) {
+	// Only backward jumps can close a loop
+	if c.State[PC] > pc {
+		return
+	}
+
+	var h uint64
+	for _, v := range c.State {
+		h = mix64(h + uint64(v))
+	}
+	h ^= d.memHash
+
+	if d.seen[h] {
+		head := c.State[PC]
+		c.Halt(runtimeError(RUNTIME_INFINITE_LOOP, head, "Probable infinite loop at %s%s", c.formatAddr(head), c.locate(head)))
+		return
+	}
+	if len(d.seen) >= LOOP_HISTORY_MAX {
+		d.seen = make(map[uint64]bool)
+	}
+	d.seen[h] = true
+}
+
+// mix64 is the splitmix64 finalizer.
+func mix64(x uint64) uint64 {
+	x += 0x9e3779b97f4a7c15
+	x = (x ^ (x >> 30)) * 0xbf58476d1ce4e5b9
+	x = (x ^ (x >> 27)) * 0x94d049bb133111eb
+	return x ^ (x >> 31)
+}
@@ -0,0 +1,59 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestLoopDetector(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,0
+LOOP	LAD	GR1,1,GR1
+	AND	GR1,MASK
+	ST	GR1,VAR
+	JUMP	LOOP
+MASK	DC	3
+VAR	DS	1
+	END
+`)
+	c.AddObserver(newLoopDetector())
+
+	err := c.Run()
+	if err == nil || !strings.Contains(err.Error(), "Probable infinite loop") {
+		t.Fatalf("Expected infinite loop to be detected, got %v", err)
+	}
+}
+
+func TestLoopDetectorMemoryHash(t *testing.T) {
+	d := newLoopDetector()
+	memory := make(map[int]int)
+	write := func(addr int, val int) {
+		d.MemoryWrite(nil, addr, memory[addr], val)
+		memory[addr] = val
+	}
+	// The running hash matches one computed over the written words
+	check := func() {
+		t.Helper()
+		var h uint64
+		for addr := range d.written {
+			h ^= wordHash(addr, memory[addr])
+		}
+		if d.memHash != h {
+			t.Errorf("Expected memory hash %x, got %x", h, d.memHash)
+		}
+	}
+
+	memory[5] = 9
+	write(5, 1)
+	write(6, 2)
+	write(5, 3)
+	write(6, 2)
+	check()
+
+	d.Svc(nil, SYS_OUT)
+	if d.memHash != 0 {
+		t.Errorf("Expected I/O to reset the memory hash")
+	}
+	write(5, 4)
+	check()
+}
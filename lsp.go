@@ -0,0 +1,272 @@
+package main
+
+import (
+	"bufio"
+	"encoding/json"
+	"fmt"
+	"io"
+	"strconv"
+	"strings"
+)
+
+// JSON-RPC error code of a request the server does not handle
+const LSP_METHOD_NOT_FOUND = -32601
+
+// LSP error code of a valid request that could not be carried out
+const LSP_REQUEST_FAILED = -32803
+
+// lspRequest is a JSON-RPC request or notification of the language server
+// protocol. Notifications have no ID.
+type lspRequest struct {
+	ID     json.RawMessage `json:"id,omitempty"`
+	Method string          `json:"method"`
+	Params json.RawMessage `json:"params,omitempty"`
+}
+
+type lspResponse struct {
+	JSONRPC string          `json:"jsonrpc"`
+	ID      json.RawMessage `json:"id"`
+	Result  any             `json:"result"`
+}
+
+type lspErrorResponse struct {
+	JSONRPC string          `json:"jsonrpc"`
+	ID      json.RawMessage `json:"id"`
+	Error   lspError        `json:"error"`
+}
+
+type lspError struct {
+	Code    int    `json:"code"`
+	Message string `json:"message"`
+}
+
+type lspPosition struct {
+	Line      int `json:"line"`
+	Character int `json:"character"`
+}
+
+type lspRange struct {
+	Start lspPosition `json:"start"`
+	End   lspPosition `json:"end"`
+}
+
+type lspTextDocument struct {
+	URI  string `json:"uri"`
+	Text string `json:"text"`
+}
+
+// lspParams holds the parameters of the requests the server handles.
+type lspParams struct {
+	TextDocument   lspTextDocument `json:"textDocument"`
+	Position       lspPosition     `json:"position"`
+	ContentChanges []struct {
+		Text string `json:"text"`
+	} `json:"contentChanges"`
+	NewName string `json:"newName"`
+}
+
+type lspHover struct {
+	Contents struct {
+		Kind  string `json:"kind"`
+		Value string `json:"value"`
+	} `json:"contents"`
+	Range lspRange `json:"range"`
+}
+
+type lspTextEdit struct {
+	Range   lspRange `json:"range"`
+	NewText string   `json:"newText"`
+}
+
+type lspWorkspaceEdit struct {
+	Changes map[string][]lspTextEdit `json:"changes"`
+}
+
+// lspServer is the language server of "c2c2 lsp": it keeps the text of
+// the open documents, shows the "c2c2 doc" entry of the instruction under
+// the cursor on hover and renames labels as "c2c2 rename" does.
+type lspServer struct {
+	w        io.Writer
+	texts    map[string]string
+	shutdown bool
+}
+
+// runLspCommand implements "c2c2 lsp": it serves the language server
+// protocol on r and w until exit or the end of input, reporting broken
+// messages on errw. It returns the exit status: 0 when the client asked
+// to shut down first, 1 otherwise.
+func runLspCommand(r io.Reader, w io.Writer, errw io.Writer) int {
+	s := &lspServer{w: w, texts: make(map[string]string)}
+	br := bufio.NewReader(r)
+	for {
+		req, err := readLspMessage(br)
+		if err == io.EOF {
+			break
+		}
+		if err != nil {
+			fmt.Fprintf(errw, "[CASL2 ERROR] %v\n", err)
+			return 1
+		}
+		if req.Method == "exit" {
+			break
+		}
+		s.handle(req)
+	}
+	if s.shutdown {
+		return 0
+	}
+	return 1
+}
+
+// readLspMessage reads one message: headers, a blank line and a body of
+// Content-Length bytes.
+func readLspMessage(br *bufio.Reader) (lspRequest, error) {
+	var req lspRequest
+	length := -1
+	for {
+		line, err := br.ReadString('\n')
+		if err == io.EOF && line == "" && length < 0 {
+			return req, io.EOF
+		}
+		if err != nil {
+			return req, fmt.Errorf("Failed to read a message header: %v", err)
+		}
+		line = strings.TrimRight(line, "\r\n")
+		if line == "" {
+			break
+		}
+		name, value, _ := strings.Cut(line, ":")
+		if strings.EqualFold(name, "Content-Length") {
+			if length, err = strconv.Atoi(strings.TrimSpace(value)); err != nil {
+				return req, fmt.Errorf("Invalid Content-Length \"%s\"", strings.TrimSpace(value))
+			}
+		}
+	}
+	if length < 0 {
+		return req, fmt.Errorf("Message without Content-Length")
+	}
+	body := make([]byte, length)
+	if _, err := io.ReadFull(br, body); err != nil {
+		return req, fmt.Errorf("Failed to read a message: %v", err)
+	}
+	if err := json.Unmarshal(body, &req); err != nil {
+		return req, fmt.Errorf("Invalid message: %v", err)
+	}
+	return req, nil
+}
+
+func (s *lspServer) send(msg any) {
+	body, _ := json.Marshal(msg)
+	fmt.Fprintf(s.w, "Content-Length: %d\r\n\r\n%s", len(body), body)
+}
+
+// handle answers a request, or updates the documents on a notification.
+func (s *lspServer) handle(req lspRequest) {
+	var params lspParams
+	if len(req.Params) > 0 {
+		json.Unmarshal(req.Params, &params)
+	}
+
+	var result any
+	switch req.Method {
+	case "initialize":
+		result = map[string]any{
+			"capabilities": map[string]any{
+				// Documents are sent whole on every change
+				"textDocumentSync": 1,
+				"hoverProvider":    true,
+				"renameProvider":   map[string]bool{"prepareProvider": true},
+			},
+			"serverInfo": map[string]string{"name": "c2c2", "version": VERSION},
+		}
+	case "shutdown":
+		s.shutdown = true
+	case "textDocument/didOpen":
+		s.texts[params.TextDocument.URI] = params.TextDocument.Text
+	case "textDocument/didChange":
+		if n := len(params.ContentChanges); n > 0 {
+			s.texts[params.TextDocument.URI] = params.ContentChanges[n-1].Text
+		}
+	case "textDocument/didClose":
+		delete(s.texts, params.TextDocument.URI)
+	case "textDocument/hover":
+		if hover, ok := s.hover(params.TextDocument.URI, params.Position); ok {
+			result = hover
+		}
+	case "textDocument/prepareRename":
+		if _, tok, ok := labelAt(s.texts[params.TextDocument.URI], params.Position.Line, params.Position.Character); ok {
+			result = lspRange{lspPosition{params.Position.Line, tok.Start}, lspPosition{params.Position.Line, tok.End}}
+		}
+	case "textDocument/rename":
+		edit, err := s.rename(params.TextDocument.URI, params.Position, params.NewName)
+		if err != nil {
+			if req.ID != nil {
+				s.send(lspErrorResponse{"2.0", req.ID, lspError{LSP_REQUEST_FAILED, strings.TrimPrefix(err.Error(), "[CASL2 ERROR] ")}})
+			}
+			return
+		}
+		result = edit
+	default:
+		if req.ID != nil {
+			s.send(lspErrorResponse{"2.0", req.ID, lspError{LSP_METHOD_NOT_FOUND, fmt.Sprintf("Unknown method \"%s\"", req.Method)}})
+		}
+		return
+	}
+	if req.ID != nil {
+		s.send(lspResponse{"2.0", req.ID, result})
+	}
+}
+
+// hover returns the reference entry of the instruction at pos, when pos
+// is on the instruction field of its line.
+func (s *lspServer) hover(uri string, pos lspPosition) (lspHover, bool) {
+	var hover lspHover
+	lines := strings.Split(s.texts[uri], "\n")
+	if pos.Line < 0 || pos.Line >= len(lines) {
+		return hover, false
+	}
+	start, end := instructionField(strings.TrimRight(lines[pos.Line], "\r"))
+	if pos.Character < start || pos.Character > end || start == end {
+		return hover, false
+	}
+	text, ok := formatDoc(strings.ToUpper(lines[pos.Line][start:end]))
+	if !ok {
+		return hover, false
+	}
+	hover.Contents.Kind = "markdown"
+	hover.Contents.Value = "```\n" + text + "```"
+	hover.Range = lspRange{lspPosition{pos.Line, start}, lspPosition{pos.Line, end}}
+	return hover, true
+}
+
+// rename returns the edits renaming the label at pos to name, everywhere
+// in the document.
+func (s *lspServer) rename(uri string, pos lspPosition, name string) (lspWorkspaceEdit, error) {
+	sites, err := renameAt(s.texts[uri], pos.Line, pos.Character, name)
+	if err != nil {
+		return lspWorkspaceEdit{}, err
+	}
+	edits := make([]lspTextEdit, len(sites))
+	for i, site := range sites {
+		edits[i] = lspTextEdit{lspRange{lspPosition{site.line, site.tok.Start}, lspPosition{site.line, site.tok.End}}, name}
+	}
+	return lspWorkspaceEdit{Changes: map[string][]lspTextEdit{uri: edits}}, nil
+}
+
+// instructionField returns the columns of the instruction field of line:
+// the first field, or the second when the line starts with a label.
+func instructionField(line string) (int, int) {
+	i := 0
+	skip := func(inField bool) {
+		for i < len(line) && line[i] != ';' && isWhitespace(line[i]) != inField {
+			i++
+		}
+	}
+	if i < len(line) && !isWhitespace(line[i]) {
+		skip(true)
+	}
+	skip(false)
+	start := i
+	skip(true)
+	return start, i
+}
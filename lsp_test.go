@@ -0,0 +1,184 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"strings"
+	"testing"
+)
+
+func TestLspHover(t *testing.T) {
+	var in bytes.Buffer
+	send := func(msg string) {
+		fmt.Fprintf(&in, "Content-Length: %d\r\n\r\n%s", len(msg), msg)
+	}
+	send(`{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}`)
+	send(`{"jsonrpc":"2.0","method":"initialized","params":{}}`)
+	send(`{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.cas","text":"MAIN\tSTART\nLOOP\tadda\tGR1,=1 ; ADDA\n\tRET\n\tEND\n"}}}`)
+	// On the mnemonic, on a label, in a comment and past the last line
+	send(`{"jsonrpc":"2.0","id":2,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///a.cas"},"position":{"line":1,"character":6}}}`)
+	send(`{"jsonrpc":"2.0","id":3,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///a.cas"},"position":{"line":1,"character":1}}}`)
+	send(`{"jsonrpc":"2.0","id":4,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///a.cas"},"position":{"line":1,"character":20}}}`)
+	send(`{"jsonrpc":"2.0","id":5,"method":"textDocument/hover","params":{"textDocument":{"uri":"file:///a.cas"},"position":{"line":9,"character":0}}}`)
+	send(`{"jsonrpc":"2.0","id":6,"method":"textDocument/definition","params":{}}`)
+	send(`{"jsonrpc":"2.0","id":7,"method":"shutdown"}`)
+	send(`{"jsonrpc":"2.0","method":"exit"}`)
+
+	var out, errs bytes.Buffer
+	if status := runLspCommand(&in, &out, &errs); status != 0 {
+		t.Fatalf("Expected exit status 0, got %d: %s", status, errs.String())
+	}
+
+	// The responses by ID
+	responses := make(map[string]lspTestResponse)
+	rest := out.String()
+	for rest != "" {
+		header, body, ok := strings.Cut(rest, "\r\n\r\n")
+		var length int
+		if _, err := fmt.Sscanf(header, "Content-Length: %d", &length); !ok || err != nil || length > len(body) {
+			t.Fatalf("Malformed response %q", rest)
+		}
+		var r lspTestResponse
+		if err := json.Unmarshal([]byte(body[:length]), &r); err != nil {
+			t.Fatalf("Failed to decode a response: %v", err)
+		}
+		responses[string(r.ID)] = r
+		rest = body[length:]
+	}
+	if len(responses) != 7 {
+		t.Fatalf("Expected 7 responses, got %d: %s", len(responses), out.String())
+	}
+
+	if !strings.Contains(string(responses["1"].Result), `"hoverProvider":true`) {
+		t.Errorf("Expected hover in the capabilities, got %s", responses["1"].Result)
+	}
+	var hover lspHover
+	if err := json.Unmarshal(responses["2"].Result, &hover); err != nil {
+		t.Fatalf("Failed to decode the hover: %v", err)
+	}
+	if !strings.Contains(hover.Contents.Value, "ADDA - add arithmetic") || !strings.Contains(hover.Contents.Value, "#24 r1 r2") {
+		t.Errorf("Expected the ADDA entry, got %q", hover.Contents.Value)
+	}
+	if want := (lspRange{lspPosition{1, 5}, lspPosition{1, 9}}); hover.Range != want {
+		t.Errorf("Expected range %+v, got %+v", want, hover.Range)
+	}
+	for _, id := range []string{"3", "4", "5", "7"} {
+		if r := responses[id]; string(r.Result) != "null" || r.Error != nil {
+			t.Errorf("Expected an empty result for %s, got %s", id, r.Result)
+		}
+	}
+	if r := responses["6"]; r.Error == nil || r.Error.Code != LSP_METHOD_NOT_FOUND {
+		t.Errorf("Expected an unknown method to fail, got %+v", r)
+	}
+
+	// Exiting without shutdown fails
+	in.Reset()
+	send(`{"jsonrpc":"2.0","method":"exit"}`)
+	if status := runLspCommand(&in, &out, &errs); status != 1 {
+		t.Errorf("Expected exit status 1 without shutdown, got %d", status)
+	}
+}
+
+type lspTestResponse struct {
+	ID     json.RawMessage `json:"id"`
+	Result json.RawMessage `json:"result"`
+	Error  *lspError       `json:"error"`
+}
+
+// runLspSession runs the server over the messages msgs, ending with
+// shutdown and exit, and returns the responses by ID.
+func runLspSession(t *testing.T, msgs ...string) map[string]lspTestResponse {
+	t.Helper()
+
+	var in bytes.Buffer
+	for _, msg := range append(msgs, `{"jsonrpc":"2.0","id":"end","method":"shutdown"}`, `{"jsonrpc":"2.0","method":"exit"}`) {
+		fmt.Fprintf(&in, "Content-Length: %d\r\n\r\n%s", len(msg), msg)
+	}
+	var out, errs bytes.Buffer
+	if status := runLspCommand(&in, &out, &errs); status != 0 {
+		t.Fatalf("Expected exit status 0, got %d: %s", status, errs.String())
+	}
+	responses := make(map[string]lspTestResponse)
+	rest := out.String()
+	for rest != "" {
+		header, body, ok := strings.Cut(rest, "\r\n\r\n")
+		var length int
+		if _, err := fmt.Sscanf(header, "Content-Length: %d", &length); !ok || err != nil || length > len(body) {
+			t.Fatalf("Malformed response %q", rest)
+		}
+		var r lspTestResponse
+		if err := json.Unmarshal([]byte(body[:length]), &r); err != nil {
+			t.Fatalf("Failed to decode a response: %v", err)
+		}
+		responses[string(r.ID)] = r
+		rest = body[length:]
+	}
+	return responses
+}
+
+func TestLspRename(t *testing.T) {
+	source := "MAIN\tSTART\n\tLD\tGR1,CNT\nLOOP\tSUBA\tGR1,=1\n\tST\tGR1,CNT ; CNT\n\tJNZ\tLOOP\n\tRET\nCNT\tDC\t3\n\tEND\n"
+	open, _ := json.Marshal(map[string]any{
+		"jsonrpc": "2.0",
+		"method":  "textDocument/didOpen",
+		"params":  map[string]any{"textDocument": map[string]string{"uri": "file:///a.cas", "text": source}},
+	})
+	at := `"textDocument":{"uri":"file:///a.cas"},"position":{"line":%d,"character":%d}`
+	responses := runLspSession(t,
+		`{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}`,
+		string(open),
+		// On the reference in line 2, on the mnemonic of line 3
+		`{"jsonrpc":"2.0","id":2,"method":"textDocument/prepareRename","params":{`+fmt.Sprintf(at, 1, 8)+`}}`,
+		`{"jsonrpc":"2.0","id":3,"method":"textDocument/prepareRename","params":{`+fmt.Sprintf(at, 2, 6)+`}}`,
+		`{"jsonrpc":"2.0","id":4,"method":"textDocument/rename","params":{`+fmt.Sprintf(at, 6, 0)+`,"newName":"COUNT"}}`,
+		`{"jsonrpc":"2.0","id":5,"method":"textDocument/rename","params":{`+fmt.Sprintf(at, 6, 0)+`,"newName":"LOOP"}}`,
+		`{"jsonrpc":"2.0","id":6,"method":"textDocument/rename","params":{`+fmt.Sprintf(at, 2, 6)+`,"newName":"X"}}`,
+	)
+
+	if !strings.Contains(string(responses["1"].Result), `"renameProvider":{"prepareProvider":true}`) {
+		t.Errorf("Expected rename in the capabilities, got %s", responses["1"].Result)
+	}
+	var rng lspRange
+	if err := json.Unmarshal(responses["2"].Result, &rng); err != nil {
+		t.Fatalf("Failed to decode the prepared range: %v", err)
+	}
+	if want := (lspRange{lspPosition{1, 8}, lspPosition{1, 11}}); rng != want {
+		t.Errorf("Expected range %+v, got %+v", want, rng)
+	}
+	if r := responses["3"]; string(r.Result) != "null" {
+		t.Errorf("Expected no range on a mnemonic, got %s", r.Result)
+	}
+
+	// Applying the edits gives what c2c2 rename writes
+	var edit lspWorkspaceEdit
+	if err := json.Unmarshal(responses["4"].Result, &edit); err != nil {
+		t.Fatalf("Failed to decode the rename: %v", err)
+	}
+	edits := edit.Changes["file:///a.cas"]
+	if len(edits) != 3 {
+		t.Fatalf("Expected 3 edits, got %+v", edit)
+	}
+	lines := strings.Split(source, "\n")
+	for i := len(edits) - 1; i >= 0; i-- {
+		e := edits[i]
+		if e.Range.Start.Line != e.Range.End.Line {
+			t.Fatalf("Expected an edit within a line, got %+v", e)
+		}
+		text := lines[e.Range.Start.Line]
+		lines[e.Range.Start.Line] = text[:e.Range.Start.Character] + e.NewText + text[e.Range.End.Character:]
+	}
+	want, _, err := renameSource(source, "CNT", "COUNT")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if got := strings.Join(lines, "\n"); got != want {
+		t.Errorf("Expected\n%s\ngot\n%s", want, got)
+	}
+
+	for _, id := range []string{"5", "6"} {
+		if r := responses[id]; r.Error == nil || r.Error.Code != LSP_REQUEST_FAILED {
+			t.Errorf("Expected rename %s to fail, got %+v", id, r)
+		}
+	}
+}
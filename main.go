@@ -2,8 +2,10 @@ package main
 
 import (
 	"bufio"
+	"errors"
 	"flag"
 	"fmt"
+	"io"
 	"os"
 	"strconv"
 	"strings"
@@ -29,9 +31,21 @@ const (
 	FR_OVER  = 4
 )
 
+// Number of words COMET2 can address
+const ADDRESS_SPACE = 0x10000
+
 // Stack configuration
 const STACK_TOP = 0xff00
 
+// First word of the SVC vector area, which runs to #FFFF
+const SVC_VECTOR_BASE = 0xfff0
+
+// Prompt shown before IN lines unless -in-prompt is given
+const DEFAULT_IN_PROMPT = "IN> "
+
+// Longest line IN stores; see -in-overflow for longer ones
+const IN_MAX_LENGTH = 256
+
 // Register indices
 const (
 	PC = iota
@@ -63,23 +77,108 @@ const (
 var (
 	optAll      = flag.Bool("a", false, "[casl2] show detailed info")
 	optCasl     = flag.Bool("c", false, "[casl2] apply casl2 only")
+	optCheck    = flag.Bool("check", false, "[casl2] check the given files without running or writing anything; exit status 1 on any error")
+	optCheckFmt = flag.String("check-format", "text", "[casl2] output of -check (text, json)")
 	optRun      = flag.Bool("r", false, "[comet2] run immediately")
-	optNoColor  = flag.Bool("n", false, "[casl2/comet2] disable color messages")
-	optQuiet    = flag.Bool("q", false, "[casl2/comet2] be quiet")
-	optQuietRun = flag.Bool("Q", false, "[comet2] be QUIET! (implies -q and -r)")
+	optNoColor  = flag.Bool("n", false, "[casl2/comet2] disable color messages (same as -color never)")
+	optQuiet    = flag.Bool("q", false, "[casl2/comet2] be quiet (same as -no-banner -no-progress -no-dump)")
+	optQuietRun = flag.Bool("Q", false, "[comet2] be QUIET! (implies -q, -no-io-prompt and -r)")
+	optColor    = flag.String("color", "auto", "[casl2/comet2] color messages (auto, always, never)")
+	optNoBanner = flag.Bool("no-banner", false, "[casl2/comet2] do not show the startup banners")
+	optNoProg   = flag.Bool("no-progress", false, "[casl2/comet2] do not show progress messages")
+	optNoPrompt = flag.Bool("no-io-prompt", false, "[comet2] do not prefix IN and OUT with prompts")
+	optNoEcho   = flag.Bool("no-in-echo", false, "[comet2] do not echo IN lines given as arguments or read from -in-file")
+	optInPrompt = flag.String("in-prompt", DEFAULT_IN_PROMPT, "[comet2] text shown before each IN line")
+	optNoDump   = flag.Bool("no-dump", false, "[comet2] do not show the machine state at startup and after each step")
+	optNoHist   = flag.Bool("no-history", false, "[comet2] do not load or save the monitor command history")
+	optNoRc     = flag.Bool("no-rc", false, "[comet2] do not run the monitor commands in ~/"+STARTUP_FILE)
+	optRc       = flag.String("rc", "", "[comet2] also run the monitor commands in the given file, e.g. ./"+STARTUP_FILE)
+	optProfile  = flag.String("profile", "", "[casl2/comet2] apply the options of the given profile (exam, lenient, research, or a [profile.NAME] table of "+PROJECT_FILE+"); options given on the command line take precedence")
 	optVersion  = flag.Bool("V", false, "output the version number")
+	optConsole  = flag.Int("console-port", 0, "[comet2] bridge IN/OUT to a TCP console on the given port")
+	optConHost  = flag.String("console-host", "127.0.0.1", "[comet2] with -console-port, the address to listen on (0.0.0.0 for every interface)")
+	optStdlib   = flag.Bool("with-stdlib", false, "[casl2] link all bundled library subroutines")
+	optObject   = flag.String("o", "", "[casl2] write the assembled program to a "+OBJECT_EXT+" object file")
+	optImport   = flag.String("import", "", "[comet2] load <casl2file> as an object in the given format (c2o, hexdump, ihex, raw, srec, words, yacasl2)")
+	optReportMd = flag.String("report-md", "", "[comet2] write a Markdown run report to the given file")
+	optReportJs = flag.String("report-json", "", "[comet2] write the run result as JSON to the given file")
+	optWebhook  = flag.String("webhook", "", "[comet2] POST the run result as JSON to the given URL after the run (signed with $"+WEBHOOK_SECRET_ENV+")")
+	optWebRetry = flag.Int("webhook-retries", 3, "[comet2] retries of -webhook after a network error or a 429/5xx answer")
+	optExport   = flag.String("export-csv", "", "[comet2] write memory as CSV after the run (FILE[:FROM-TO])")
+	optTrap     = flag.String("trap", "", "[comet2] halt when the given condition occurs (overflow, range, protect, protect-stack; comma separated)")
+	optLoops    = flag.Bool("detect-loops", false, "[comet2] halt on a probable infinite loop")
+	optSmc      = flag.String("smc", "", "[comet2] report execution of self-modified code (warn, error)")
+	optCalls    = flag.String("check-calls", "", "[comet2] report RETs that do not match their CALL (warn, error)")
+	optSigns    = flag.Bool("check-signs", false, "[comet2] warn when ADDA, SUBA, MULA, DIVA or CPA uses a register that ADDL, SUBL, MULL, DIVL or CPL treated as unsigned")
+	optBreak    = flag.String("break", "", "[comet2] set breakpoints at the given addresses, labels or LABEL+N, separated by commas")
+	optExplain  = flag.Bool("explain", false, "[comet2] describe each executed instruction in plain words")
+	optTraceFlt = flag.String("trace-filter", "", "[comet2] with -explain, only describe instructions in the given ranges (FROM..TO, label:NAME)")
+	optSvcLog   = flag.String("svc-log", "", "[comet2] write every SVC with its decoded arguments and I/O text to the given file after the run")
+	optTraceStk = flag.Bool("trace-stack", false, "[comet2] print every PUSH, POP, CALL and RET with SP before and after and the word pushed or popped")
+	optStack    = flag.String("stack-history", "", "[comet2] write the stack at every CALL/RET to the given file after the run")
+	optSamples  = flag.String("samples", "", "[comet2] write the registers and changed memory words every -sample-every steps as JSON lines for visualizers (FILE[:FROM-TO])")
+	optSampleN  = flag.Int("sample-every", SAMPLE_EVERY, "[comet2] number of steps between two samples of -samples")
+	optFuzz     = flag.String("fuzz", "", "[comet2] run on random inputs generated from the given specification")
+	optFuzzRuns = flag.Int("fuzz-runs", 10, "[comet2] number of runs for -fuzz")
+	optFuzzSeed = flag.Int64("fuzz-seed", 0, "[comet2] random seed for -fuzz (0: time based)")
+	optFuzzDir  = flag.String("fuzz-corpus", "", "[comet2] save every -fuzz case (seed, inputs and outcome) as JSON in the given directory")
+	optFuzzCase = flag.String("fuzz-case", "", "[comet2] replay the case with the given ID from -fuzz-corpus")
+	optDiff     = flag.String("diff", "", "[comet2] run in lockstep with a reference program and report the first divergence")
+	optEngine   = flag.String("engine", "interp", "[comet2] execution engine (interp, block)")
+	optMaxOut   = flag.Int("max-output", 0, "[comet2] halt when the program writes more than the given number of bytes (0: no limit)")
+	optMaxLines = flag.Int("max-output-lines", 0, "[comet2] halt when the program writes more than the given number of OUT lines (0: no limit)")
+	optSession  = flag.String("log-session", "", "[comet2] record every monitor command and its output with timestamps in the given file")
+	optOutFile  = flag.String("out-file", "", "[comet2] write OUT text to the given file instead of the console")
+	optOutChars = flag.String("out-chars", "raw", "[comet2] how OUT writes characters: raw, or escape and/or stop-nul separated by commas")
+	optOutFilt  = flag.String("out-filter", "", "[comet2] transform OUT lines with the given filters, separated by commas (trim, squeeze, numbers, mask:REGEX)")
+	optTee      = flag.Bool("tee", false, "[comet2] with -out-file, also show OUT text on the console")
+	optInFile   = flag.String("in-file", "", "[comet2] read IN lines from the given file, after any inputs given as arguments")
+	optCheckIO  = flag.String("check-io", "", "[comet2] report IN and OUT whose length word lies inside the buffer or the code, whose buffer reaches the code, or whose OUT length is outside 0..256 (warn, error)")
+	optInWait   = flag.String("input-timeout", "", "[comet2] when an IN typed at the console gets no line within DURATION, read DEFAULT instead (DURATION=DEFAULT, e.g. 10s=0)")
+	optArtifact = flag.String("artifacts", "", "[casl2/comet2] write the object, listing, symbols, reports, SVC log, stack history and samples of the run with a "+ARTIFACTS_MANIFEST+" into the given directory")
+	optInOver   = flag.String("in-overflow", "truncate", "[comet2] what IN does with a line over 256 characters (truncate, warn, error)")
+	optStrict   = flag.Bool("strict-numbers", false, "[casl2] reject numbers that do not fit in a word instead of truncating them")
+	optCaseSens = flag.Bool("case-sensitive", false, "[casl2] tell labels apart by case (LOOP and Loop are different labels); mnemonics and registers are accepted in any case either way")
+	optLenient  = flag.Bool("lenient-numbers", false, "[casl2] truncate numbers that do not fit in a word without a warning")
+	optDefine   = flag.String("define", "", "[casl2] define names for IFDEF and IFEQ (NAME or NAME=VALUE, separated by commas)")
+	optEntry    = flag.String("entry", "", "[casl2/comet2] start execution at the entry point of the given START unit")
+	optLitAfter = flag.String("literals-after", "", "[casl2] place the literals referenced so far right after the line with the given label")
+	optSize     = flag.Bool("size", false, "[casl2] show the size of code, data and literals of each program unit")
+	optLiterals = flag.Bool("literals", false, "[casl2] show the literals of the program with their addresses in the literal pools")
+	optConsts   = flag.Bool("constants", false, "[casl2] show the numeric DC constants and literals with their decimal, hex and character values and the lines using them, with notes on suspicious ones")
+	optSrcList  = flag.Bool("source-listing", false, "[casl2] show the source as written with line numbers, the address and words of each line and its warnings inline")
+	optLint     = flag.Bool("lint", false, "[casl2] warn about literals that repeat a DC constant or are used inside loops, about unsigned values used as signed, and about unchecked indexes")
+	optControl  = flag.Bool("control-stdio", false, "[comet2] serve line-based control commands (LOAD, STEP, CONTINUE, ...) on stdin/stdout")
+	optEvents   = flag.Int("events-port", 0, "[comet2] stream execution events as JSON over WebSocket (ws://localhost:PORT/events)")
+	optEvOrigin = flag.String("events-origin", "", "[comet2] with -events-port, also accept WebSocket clients from pages of the given origins (separated by commas), e.g. the web debugger's http://HOST:PORT")
+	optArgs     = flag.String("args", "", "[comet2] write the given arguments, separated by commas, to the argument block at #FF00")
+	optExtSvc   = flag.Bool("extended-svc", false, "[comet2] enable the conversion SVCs #FFF4 (GR0 to a decimal string), #FFF6 (a decimal string to GR0) and #FFF8 (exit with status GR0)")
+	optDevice   = flag.String("device", "", "[comet2] map the given devices into memory (NAME[:ADDRESS], separated by commas; available: timer)")
+	optMemInit  = flag.String("mem-init", "zero", "[comet2] contents of memory outside the program (zero, ones, #XXXX, #XX, random[:SEED]), with ,poison[:#XXXX] to poison the stack and popped words (default #DEAD)")
+	optStats    = flag.String("stats", "", "[comet2] show an instruction histogram and memory/SVC totals after the run (text, json)")
+	optRegUse   = flag.Bool("reg-usage", false, "[comet2] show which registers each subroutine reads, writes and returns changed after the run")
+	optHints    = flag.Bool("hints", false, "[comet2] after the run, show teaching hints on what the program executed, such as MULA in a loop or a deep stack")
+	optHintFile = flag.String("hint-rules", "", "[comet2] add the hint rules of the given file to those of -hints (implies -hints)")
+	optCost     = flag.String("cost", "", "[comet2] add up instruction costs per label from the given cost table (TOML) and show them with -stats")
+	optCompat   = flag.Bool("compat", false, "[comet2] use the monitor commands and output of the original Perl c2c2 (r, g, s, t, b, d, i, p, du, st, di, f, j, m, h)")
 )
 
+// What is shown on the console, set from the command line options
+var verbosity struct {
+	banner   bool
+	progress bool
+	ioPrompt bool
+	inEcho   bool
+	dump     bool
+	color    bool
+}
+
 // Global variables
 var (
-	comet2mem          []uint16
 	comet2startAddress uint16
-	state              []int
-	inputMode          int
 	inputBuffer        []string
 	lastCmd            string
 	nextCmd            string
-	addressMax         int
 )
 
 // Instruction table for CASL2
@@ -99,6 +198,8 @@ const (
 	OUT   InstructionType = "out"
 	RPUSH InstructionType = "rpush"
 	RPOP  InstructionType = "rpop"
+	LTORG InstructionType = "ltorg"
+	EQU   InstructionType = "equ"
 )
 
 type Instruction struct {
@@ -106,60 +207,33 @@ type Instruction struct {
 	Type InstructionType
 }
 
-var CASL2TBL = map[string]Instruction{
-	"NOP":   {0x00, OP4},
-	"LD":    {0x10, OP5},
-	"ST":    {0x11, OP1},
-	"LAD":   {0x12, OP1},
-	"ADDA":  {0x20, OP5},
-	"SUBA":  {0x21, OP5},
-	"ADDL":  {0x22, OP5},
-	"SUBL":  {0x23, OP5},
-	"MULA":  {0x28, OP5},
-	"DIVA":  {0x29, OP5},
-	"MULL":  {0x2A, OP5},
-	"DIVL":  {0x2B, OP5},
-	"AND":   {0x30, OP5},
-	"OR":    {0x31, OP5},
-	"XOR":   {0x32, OP5},
-	"CPA":   {0x40, OP5},
-	"CPL":   {0x41, OP5},
-	"SLA":   {0x50, OP1},
-	"SRA":   {0x51, OP1},
-	"SLL":   {0x52, OP1},
-	"SRL":   {0x53, OP1},
-	"JMI":   {0x61, OP2},
-	"JNZ":   {0x62, OP2},
-	"JZE":   {0x63, OP2},
-	"JUMP":  {0x64, OP2},
-	"JPL":   {0x65, OP2},
-	"JOV":   {0x66, OP2},
-	"PUSH":  {0x70, OP2},
-	"POP":   {0x71, OP3},
-	"CALL":  {0x80, OP2},
-	"RET":   {0x81, OP4},
-	"SVC":   {0xf0, OP2},
-	"START": {0x00, START},
-	"END":   {0x00, END},
-	"DS":    {0x00, DS},
-	"DC":    {0x00, DC},
-	"IN":    {0x00, IN},
-	"OUT":   {0x00, OUT},
-	"RPUSH": {0x00, RPUSH},
-	"RPOP":  {0x00, RPOP},
-}
-
-// Symbol table entry
+// CASL2TBL holds the instructions and directives the assembler accepts,
+// by name
+var CASL2TBL = casl2Table()
+
+// Symbol table entry. Line, like the Line of LiteralEntry and
+// MemoryEntry, is the position in the preprocessed source; see
+// AssemblerState.origin.
 type SymbolEntry struct {
 	Val  interface{}
-	File string
 	Line int
 }
 
+// LiteralEntry is a literal operand placed in a literal pool. Line is the
+// position of the line referring to it.
+type LiteralEntry struct {
+	Name    string
+	Text    string
+	Address int
+	Line    int
+}
+
 type MemoryEntry struct {
 	Val  interface{}
-	File string
 	Line int
+	// Code is set for the words of machine instructions, as opposed to
+	// DC, DS and literal data.
+	Code bool
 }
 
 // Assembler state
@@ -173,8 +247,38 @@ type AssemblerState struct {
 	firstStart     bool
 	varScope       string
 	literalCounter int
+	literals       []LiteralEntry
+	constants      []constantEntry
+	jumps          []jumpEntry
+	entry          string
+	units          []UnitSize
 	file           string
+	lines          []SourceLine
 	line           int
+	warnings       []Diagnostic
+	// sources reads the program and the files it includes.
+	sources SourceProvider
+	// build is the reproducibility header of the object.
+	build *BuildInfo
+	// traceMarks are the ;@TRACE comments in the order of their lines.
+	traceMarks []traceMark
+	// bufPos is the position in lines of each line of buf, which skips
+	// empty lines.
+	bufPos []int
+	// boundsMarks are the ;@bounds comments in the order of their lines.
+	boundsMarks []boundsMark
+	// allowLines are the warnings ;@allow turns off by line, and
+	// allowFiles those ;@allow-file turns off by file.
+	allowLines map[int]map[string]bool
+	allowFiles map[string]map[string]bool
+	// written is the label, mnemonic and operands of each line of lines
+	// as the source spells them, for the listing.
+	written []string
+	// equates are the scoped labels defined by EQU, which stand for a
+	// number rather than an address.
+	equates map[string]bool
+	// relocs are the addresses of the words holding an address.
+	relocs []int
 }
 
 func newAssemblerState() *AssemblerState {
@@ -184,12 +288,38 @@ func newAssemblerState() *AssemblerState {
 		buf:        make([]string, 0),
 		outdump:    make([]string, 0),
 		firstStart: true,
+		sources:    FileSources{},
+		equates:    make(map[string]bool),
 	}
 }
 
 func main() {
 	flag.Usage = func() {
-		fmt.Fprintf(os.Stderr, "Usage: c2c2 [options] <casl2file> [input1 ...]\n\n")
+		fmt.Fprintf(os.Stderr, "Usage: c2c2 [options] <casl2file> [input1 ...]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 [options] quiz <casl2file> [input1 ...]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 [options] tutorial\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 [options] run [input1 ...]   (program of %s)\n", PROJECT_FILE)
+		fmt.Fprintf(os.Stderr, "       c2c2 test                        (test specs of %s)\n", PROJECT_FILE)
+		fmt.Fprintf(os.Stderr, "       c2c2 test -golden [-update-golden] [-golden-dir DIR] <casl2file> ...\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 test -spec FILE [-jobs N] [-max-steps N] [-timeout D]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 new NAME [-template io|subroutine|array] [-force]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 examples list | show NAME | run NAME [-inputs] [input1 ...]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 corpus run DIR [-baseline FILE [-update]] [-inputs FILE]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 genexpect DIR [-inputs FILE] [-expects DIR] [-yes | -dry-run]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 verify [-source FILE.cas] FILE.c2o\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 info [-source FILE.cas] FILE.c2o\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 patch [-p PATCHFILE] [-o OUTPUT] FILE.c2o [ADDRESS=VALUE ...]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 anonymize [-o OUTPUT] FILE.cas\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 rename [-o OUTPUT] FILE.cas OLD NEW\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 refs [-C N] FILE.cas LABEL\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 compare [-min N] A.cas B.cas\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 equiv [-max N] A.c2o B.c2o\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 link [-dry-run] [-base ADDRESS] [-unit-base NAME=ADDRESS,...] [-o OUTPUT] [FILE.cas ... | FILE.c2o]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 image [-from FORMAT] [-to FORMAT] [-byte-order big|little] INPUT OUTPUT\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 bisect-run [-a SETTINGS] [-b SETTINGS] [-input LINE ...] A [B]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 disasm [-S] FILE.cas|FILE.c2o\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 doc [INSTRUCTION | CODE ...]\n")
+		fmt.Fprintf(os.Stderr, "       c2c2 lsp                         (language server on stdin/stdout)\n\n")
 		fmt.Fprintf(os.Stderr, "Options:\n")
 		flag.PrintDefaults()
 	}
@@ -200,21 +330,147 @@ func main() {
 		os.Exit(0)
 	}
 
+	if *optProfile != "" {
+		if err := applyProfile(flag.CommandLine, *optProfile, "."); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
 	if *optQuietRun {
 		*optQuiet = true
 		*optRun = true
 	}
+	if err := configureVerbosity(); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
 
 	args := flag.Args()
+
+	// Subcommands
+	if len(args) > 0 && args[0] == "test" {
+		os.Exit(runTestCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "examples" {
+		os.Exit(runExamplesCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "corpus" {
+		os.Exit(runCorpusCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "genexpect" {
+		os.Exit(runGenExpectCommand(args[1:], os.Stdin, os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "new" {
+		os.Exit(runNewCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "verify" {
+		os.Exit(runVerifyCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "info" {
+		os.Exit(runInfoCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "patch" {
+		os.Exit(runPatchCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "anonymize" {
+		os.Exit(runAnonymizeCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "rename" {
+		os.Exit(runRenameCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "refs" {
+		os.Exit(runRefsCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "compare" {
+		os.Exit(runCompareCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "equiv" {
+		os.Exit(runEquivCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "link" {
+		os.Exit(runLinkCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "image" {
+		os.Exit(runImageCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "bisect-run" {
+		os.Exit(runBisectRunCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "disasm" {
+		os.Exit(runDisasmCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "doc" {
+		os.Exit(runDocCommand(args[1:], os.Stdout))
+	}
+	if len(args) > 0 && args[0] == "lsp" {
+		os.Exit(runLspCommand(os.Stdin, os.Stdout, os.Stderr))
+	}
+	if len(args) > 0 && args[0] == "tutorial" {
+		runTutorial(bufio.NewScanner(os.Stdin))
+		os.Exit(0)
+	}
+	var project *Project
+	if len(args) > 0 && args[0] == "run" {
+		p, err := loadProject(".")
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		project = p
+		if len(args) == 1 {
+			args = append(args, p.Inputs...)
+		}
+		args = append([]string{p.Sources[0]}, args[1:]...)
+	}
+	quizMode := false
+	if len(args) > 1 && args[0] == "quiz" {
+		quizMode = true
+		args = args[1:]
+	}
+
+	if *optControl {
+		path := ""
+		if len(args) > 0 {
+			path = args[0]
+		}
+		if err := runControl(os.Stdin, os.Stdout, path, *optTrap, *optEngine); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		os.Exit(0)
+	}
+
 	if len(args) < 1 {
 		fmt.Fprintln(os.Stderr, "[CASL2 ERROR] No casl2 source file is specified.")
 		os.Exit(1)
 	}
 
+	if *optCheck {
+		os.Exit(runCheck(args, *optCheckFmt, os.Stdout))
+	}
+
 	inputFilepath := args[0]
 	inputBuffer = args[1:]
+	if *optInFile != "" {
+		lines, err := readInputFile(*optInFile)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		inputBuffer = append(inputBuffer, lines...)
+	}
 
-	if !*optQuiet {
+	var artifacts *artifactsDir
+	if *optArtifact != "" {
+		var err error
+		if artifacts, err = openArtifacts(*optArtifact, inputFilepath); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if verbosity.banner {
 		printGreen(`   _________   _____ __       ________
   / ____/   | / ___// /      /  _/  _/
  / /   / /| | \__ \/ /       / / / /  
@@ -223,35 +479,299 @@ func main() {
 		fmt.Printf("This is CASL II, version %s.\n(c) 2001-2023, Osamu Mizuno.\n\n", VERSION)
 	}
 
-	// Assemble the code
-	asmState := newAssemblerState()
-	comet2bin, startLabel, err := assemble(inputFilepath, asmState)
+	var obj *Object
+	var err error
+	if *optImport != "" {
+		// Load an object produced elsewhere
+		obj, err = importObject(*optImport, inputFilepath)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		caslPrint("Successfully imported.")
+	} else if strings.HasSuffix(inputFilepath, OBJECT_EXT) {
+		// Load a previously assembled object
+		obj, err = loadObjectFile(inputFilepath)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		caslPrint("Successfully loaded.")
+		if *optEntry != "" {
+			if err := selectEntry(obj, *optEntry); err != nil {
+				fmt.Fprintln(os.Stderr, err)
+				os.Exit(1)
+			}
+		}
+	} else {
+		// Assemble the code
+		asmState := newAssemblerState()
+		if project != nil {
+			obj, err = assembleProject(project, asmState)
+		} else {
+			obj, err = assemble(inputFilepath, asmState)
+		}
+		if err != nil {
+			// Lines of a project can come from any of its files
+			var aerr *AssembleError
+			if project != nil && errors.As(err, &aerr) && aerr.File != "" {
+				fmt.Fprintf(os.Stderr, "%s: ", aerr.File)
+			}
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		for _, w := range asmState.warnings {
+			caslWarn(fmt.Sprintf("[CASL2 WARNING] Line %d: %s [%s]", w.Line, w.Msg, w.Code))
+		}
+
+		caslPrint("Successfully assembled.")
+		if *optEntry != "" {
+			if err := selectEntry(obj, *optEntry); err != nil {
+				fmt.Fprintln(os.Stderr, err)
+				os.Exit(1)
+			}
+		}
+		if obj.Entry != "" {
+			caslPrint(fmt.Sprintf("Entry point: %s (#%s)", obj.Entry, hex(obj.Start, 4)))
+		}
+
+		if *optObject != "" {
+			if err := writeObjectFile(*optObject, obj); err != nil {
+				fmt.Fprintln(os.Stderr, err)
+				os.Exit(1)
+			}
+		}
+
+		if *optSize {
+			fmt.Print(formatSizes(obj))
+		}
+		if *optLiterals {
+			fmt.Print(formatLiterals(asmState))
+		}
+		if *optConsts {
+			fmt.Print(formatConstants(asmState))
+		}
+		if *optSrcList {
+			fmt.Print(formatSourceListing(asmState, obj))
+		}
+
+		if artifacts != nil {
+			if err := artifacts.writeAssembly(asmState, obj); err != nil {
+				fmt.Fprintln(os.Stderr, err)
+				os.Exit(1)
+			}
+		}
+
+		if *optCasl {
+			if artifacts != nil {
+				if err := artifacts.writeManifest(""); err != nil {
+					fmt.Fprintln(os.Stderr, err)
+					os.Exit(1)
+				}
+			}
+			os.Exit(0)
+		}
+	}
+
+	// Initialize COMET2
+	comet2startAddress = uint16(obj.Start)
+	builder := NewComet2Builder().WithObject(obj).WithMaxOutput(*optMaxOut, *optMaxLines)
+	memInit, err := parseMemInit(*optMemInit)
 	if err != nil {
 		fmt.Fprintln(os.Stderr, err)
 		os.Exit(1)
 	}
+	builder.WithMemInit(memInit)
+	args, err := parseArgs(*optArgs)
+	if err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	builder.WithArgs(args)
+	if *optExtSvc {
+		for entry, h := range extendedSvcs {
+			builder.WithSvc(entry, h)
+		}
+	}
+	builder.WithObserver(newUndoLog(UNDO_DEPTH))
+	if *optLoops {
+		builder.WithObserver(newLoopDetector())
+	}
+	var svcLog *svcLogger
+	if *optSvcLog != "" {
+		svcLog = newSvcLogger()
+		builder.WithObserver(svcLog)
+	}
+	var stackHistory *stackRecorder
+	if *optStack != "" {
+		stackHistory = newStackRecorder()
+		builder.WithObserver(stackHistory)
+	}
+	if *optTraceStk {
+		builder.WithObserver(newStackTracer(cometPrint))
+	}
+	var samples *stateSampler
+	var samplesPath string
+	if *optSamples != "" {
+		path, from, to, err := parseExportSpec(*optSamples, obj)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		samples, samplesPath = newStateSampler(*optSampleN, from, to), path
+		builder.WithObserver(samples)
+	}
+	if *optExplain {
+		e := &explainer{print: cometPrint}
+		e.setTracePoints(obj.Traces)
+		if *optTraceFlt != "" {
+			if e.filter, err = parseTraceFilter(*optTraceFlt, obj); err != nil {
+				fmt.Fprintln(os.Stderr, err)
+				os.Exit(1)
+			}
+		}
+		builder.WithObserver(e)
+	}
+	var stats *statsCollector
+	statsFormat := *optStats
+	if *optCost != "" && statsFormat == "" {
+		statsFormat = "text"
+	}
+	switch statsFormat {
+	case "":
+	case "text", "json":
+		stats = newStatsCollector()
+		builder.WithObserver(stats)
+	default:
+		fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Invalid -stats format \"%s\"\n", *optStats)
+		os.Exit(1)
+	}
+	if *optCost != "" {
+		table, err := loadCostTable(*optCost)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		stats.cost = table
+	}
+	var hints *hintStats
+	if *optHints || *optHintFile != "" {
+		rules, err := loadHintRules(*optHintFile)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		hints = newHintStats(rules)
+		builder.WithObserver(hints)
+	}
+	var regs *regUsage
+	if *optRegUse {
+		regs = newRegUsage()
+		builder.WithObserver(regs)
+	}
+	switch *optSmc {
+	case "":
+	case "warn", "error":
+		builder.WithObserver(newSmcTracker(*optSmc == "error"))
+	default:
+		fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Invalid -smc mode \"%s\"\n", *optSmc)
+		os.Exit(1)
+	}
+	switch *optCalls {
+	case "":
+	case "warn", "error":
+		builder.WithObserver(newCallChecker(*optCalls == "error"))
+	default:
+		fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Invalid -check-calls mode \"%s\"\n", *optCalls)
+		os.Exit(1)
+	}
+	if *optSigns {
+		builder.WithObserver(newSignChecker())
+	}
+	if len(obj.Bounds) > 0 {
+		builder.WithObserver(newBoundsChecker(obj.Bounds))
+	}
+	var events *eventStreamer
+	if *optEvents > 0 {
+		events = newEventStreamer()
+		if *optEvOrigin != "" {
+			events.origins = strings.Split(*optEvOrigin, ",")
+		}
+		builder.WithObserver(events)
+		if err := startEventServer(*optEvents, events); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+	machine := builder.Build()
+	devices, err := parseDevices(*optDevice)
+	if err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	for _, d := range devices {
+		if err := machine.AddDevice(d); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+	if err := machine.SetTraps(*optTrap); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if err := setBreakpoints(machine, *optBreak); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if err := machine.SetEngine(*optEngine); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if err := machine.SetInOverflow(*optInOver); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if err := machine.SetCheckIO(*optCheckIO); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if err := machine.SetOutChars(*optOutChars); err != nil {
+		fmt.Fprintln(os.Stderr, err)
+		os.Exit(1)
+	}
+	if *optOutFilt != "" {
+		if machine.OutputFilters, err = parseOutputFilters(strings.Split(*optOutFilt, ",")); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
 
-	caslPrint("Successfully assembled.")
-
-	if *optCasl {
-		os.Exit(0)
+	if *optFuzzCase != "" {
+		runFuzzCase(*optFuzzDir, *optFuzzCase, obj, *optDiff)
+	}
+	if *optFuzz != "" {
+		runFuzzMode(*optFuzz, *optFuzzRuns, *optFuzzSeed, obj, *optDiff, *optFuzzDir)
 	}
 
-	// Initialize COMET2
-	comet2mem = make([]uint16, 0x10000) // Full 64K memory space
-	copy(comet2mem, comet2bin)
-	comet2startAddress = uint16(expandLabel(asmState.symtbl, startLabel))
+	if *optDiff != "" {
+		runDiffMode(*optDiff, machine)
+	}
 
-	state = []int{int(comet2startAddress), FR_PLUS, 0, 0, 0, 0, 0, 0, 0, 0, STACK_TOP}
+	if quizMode {
+		runQuiz(machine, inputBuffer, os.Stdin, os.Stdout)
+		os.Exit(0)
+	}
 
-	if !*optQuiet {
+	if verbosity.banner {
 		printGreen(`   __________  __  _______________   ________
   / ____/ __ \/  |/  / ____/_  __/  /  _/  _/
  / /   / / / / /|_/ / __/   / /     / / / /  
 / /___/ /_/ / /  / / /___  / /    _/ /_/ /   
 \____/\____/_/  /_/_____/ /_/    /___/___/  `)
 		fmt.Printf("This is COMET II, version %s.\n(c) 2001-2023, Osamu Mizuno.\n\n", VERSION)
-		cmdPrint(comet2mem, state, []string{})
+	}
+	if verbosity.dump {
+		cmdPrint(machine, []string{})
 	}
 
 	if *optRun {
@@ -259,22 +779,106 @@ func main() {
 	}
 
 	// Main loop
-	inputMode = INPUT_MODE_CMD
 	scanner := bufio.NewScanner(os.Stdin)
 
+	// IN reads from stdin unless a TCP console is attached
+	inScanner := scanner
+	var inWriter io.Writer = os.Stdout
+	if *optConsole > 0 {
+		conn, err := openConsole(*optConHost, *optConsole)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		defer conn.Close()
+		machine.Output = consoleWriter(conn)
+		inScanner = newConsoleScanner(conn)
+		inWriter = conn
+	}
+
+	// With -input-timeout, IN lines are read in the background so that
+	// the wait can end; a line typed late goes to the next reader
+	var inTimeout *inputTimeout
+	var timedIn *timedScanner
+	if *optInWait != "" {
+		if inTimeout, err = parseInputTimeout(*optInWait); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		timedIn = &timedScanner{scanner: inScanner}
+	}
+
+	if *optOutFile != "" {
+		f, err := os.Create(*optOutFile)
+		if err != nil {
+			fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Cannot open output file: %v\n", err)
+			os.Exit(1)
+		}
+		defer f.Close()
+		if *optTee {
+			machine.Output = teeOutput(machine.Output, lineWriter(f))
+		} else {
+			machine.Output = lineWriter(f)
+		}
+	}
+
+	if *optSession != "" {
+		s, f, err := openSessionLog(*optSession, obj.Source)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		defer f.Close()
+		session = s
+	}
+
+	historyFile := ""
+	if !*optNoHist {
+		historyFile = defaultHistoryFile()
+	}
+	editor := newLineEditor(scanner, historyFile, func(before []string) []string {
+		return monitorCompletions(machine, before)
+	})
+	monitorConfirm = func(prompt string) bool {
+		answer, ok := editor.ReadLine(prompt)
+		return ok && strings.HasPrefix(strings.TrimSpace(answer), "y")
+	}
+
+	if *optRc != "" {
+		if _, err := os.Stat(*optRc); err != nil {
+			fmt.Fprintf(os.Stderr, "[COMET2 ERROR] Cannot read startup file: %v\n", err)
+		}
+	}
+	for _, path := range startupFiles(!*optNoRc && !*optQuietRun, *optRc) {
+		if err := runStartupFile(path, machine); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+		}
+	}
+
+	haltReason := "Not finished"
 	for {
 		var cmd string
 
-		if inputMode == INPUT_MODE_CMD {
+		if machine.InputMode == INPUT_MODE_CMD {
+			typed := false
 			if nextCmd != "" {
 				cmd = nextCmd
 				nextCmd = ""
+			} else if timedIn != nil && timedIn.pending != nil && inScanner == scanner {
+				// A line typed after an IN timed out
+				line, ok, _ := timedIn.read(0)
+				if !ok {
+					break
+				}
+				cmd = strings.TrimSpace(line)
+				typed = true
 			} else {
-				fmt.Print(colorYellow("comet2") + "> ")
-				if !scanner.Scan() {
+				line, ok := editor.ReadLine(colorYellow("comet2") + "> ")
+				if !ok {
 					break
 				}
-				cmd = strings.TrimSpace(scanner.Text())
+				cmd = strings.TrimSpace(line)
+				typed = true
 			}
 
 			if cmd == "" {
@@ -282,6 +886,9 @@ func main() {
 			} else {
 				lastCmd = cmd
 			}
+			if typed && session != nil {
+				session.command(cmd)
+			}
 
 			parts := strings.Fields(cmd)
 			if len(parts) == 0 {
@@ -293,57 +900,195 @@ func main() {
 
 			if cmd2 == "quit" || cmd2 == "q" {
 				cometPrint("[Comet2 finished]")
+				haltReason = "Quit"
 				break
 			}
 
-			err := executeCommand(cmd2, args, comet2mem, state)
+			err := executeCommand(cmd2, args, machine)
 			if err != nil {
-				if strings.Contains(err.Error(), "Program finished") ||
-					strings.Contains(err.Error(), "Stack overflow") ||
-					strings.Contains(err.Error(), "Stack underflow") {
+				var rerr *RuntimeError
+				if errors.As(err, &rerr) && rerr.Halts() {
 					fmt.Println(colorWhiteGreen(err.Error()))
+					logOutput(err.Error())
+					haltReason = err.Error()
 					break
 				}
 				fmt.Fprintln(os.Stderr, colorRedYellow(err.Error()))
+				logOutput(err.Error())
 			}
 
-		} else if inputMode == INPUT_MODE_IN {
+		} else if machine.InputMode == INPUT_MODE_IN {
 			var input string
+			timedOut := false
 			prompt := ""
-			if !*optQuietRun {
-				prompt = colorIGreen("IN") + "> "
+			if verbosity.ioPrompt {
+				prompt = inPrompt()
 			}
 
 			if len(inputBuffer) > 0 {
 				input = inputBuffer[0]
 				inputBuffer = inputBuffer[1:]
-				// Always print the input value when using buffered input
-				fmt.Printf("%s%s\n", prompt, input)
+				// Print the input value when using buffered input, as the
+				// console would have echoed it
+				if verbosity.inEcho {
+					fmt.Fprintf(inWriter, "%s%s\n", prompt, input)
+				}
+				if session != nil {
+					session.input(input, false)
+				}
 			} else {
 				if prompt != "" {
-					fmt.Print(prompt)
+					fmt.Fprint(inWriter, prompt)
 				}
-				if !scanner.Scan() {
+				ok := true
+				if timedIn != nil {
+					input, ok, timedOut = timedIn.read(inTimeout.wait)
+				} else if ok = inScanner.Scan(); ok {
+					input = inScanner.Text()
+				}
+				if !ok {
+					// The input ended while the program waits for a line
+					err := machine.inputExhausted()
+					fmt.Fprintln(os.Stderr, colorRedYellow(err.Error()))
+					logOutput(err.Error())
+					haltReason = err.Error()
 					break
 				}
-				input = scanner.Text()
+				if timedOut {
+					input = inTimeout.line
+					fmt.Fprintf(inWriter, "(no input for %v, reading \"%s\")\n", inTimeout.wait, input)
+				}
+				if session != nil && timedOut {
+					session.defaultInput(input)
+				} else if session != nil {
+					session.input(input, true)
+				}
 			}
 
-			execIn(comet2mem, state, input)
-			inputMode = INPUT_MODE_CMD
+			machine.execIn(input)
+			if timedOut {
+				machine.Transcript[len(machine.Transcript)-1].Synthesized = true
+			}
+			machine.InputMode = INPUT_MODE_CMD
 
-			if !*optQuiet {
+			if verbosity.dump {
 				if lastCmd == "s" || lastCmd == "step" {
-					cmdPrint(comet2mem, state, []string{})
+					cmdPrint(machine, []string{})
 				}
 			}
 		}
 	}
+
+	if events != nil {
+		events.halt(machine, haltReason)
+	}
+
+	if *optReportMd != "" {
+		if err := writeMarkdownReport(*optReportMd, obj, machine, haltReason); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if *optReportJs != "" {
+		if err := writeJsonReport(*optReportJs, obj, machine, haltReason); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if *optWebhook != "" {
+		if err := newWebhookClient(*optWebhook, *optWebRetry).deliver(newRunResult(obj, machine, haltReason)); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if stackHistory != nil {
+		if err := stackHistory.writeStackHistory(*optStack); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if samples != nil {
+		if err := samples.writeSamples(samplesPath, machine); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if svcLog != nil {
+		if err := svcLog.writeSvcLog(*optSvcLog, machine); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if *optExport != "" {
+		if err := exportMemoryCSV(*optExport, obj, machine); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if stats != nil {
+		text, err := stats.render(statsFormat)
+		if err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+		fmt.Print(text)
+	}
+	if regs != nil {
+		fmt.Print(regs.render(machine))
+	}
+	if hints != nil {
+		fmt.Print(hints.render())
+	}
+
+	if artifacts != nil {
+		if err := artifacts.writeManifest(haltReason); err != nil {
+			fmt.Fprintln(os.Stderr, err)
+			os.Exit(1)
+		}
+	}
+
+	if machine.ExitStatus != nil {
+		os.Exit(processExitCode(*machine.ExitStatus))
+	}
+}
+
+// configureVerbosity sets verbosity from the individual options and the
+// older -q, -Q and -n flags, which act as aliases.
+func configureVerbosity() error {
+	verbosity.banner = !*optNoBanner && !*optQuiet
+	verbosity.progress = !*optNoProg && !*optQuiet
+	verbosity.dump = !*optNoDump && !*optQuiet
+	verbosity.ioPrompt = !*optNoPrompt && !*optQuietRun
+	verbosity.inEcho = !*optNoEcho
+
+	color := *optColor
+	if *optNoColor {
+		color = "never"
+	}
+	switch color {
+	case "always":
+		verbosity.color = true
+	case "never":
+		verbosity.color = false
+	case "auto":
+		info, err := os.Stdout.Stat()
+		verbosity.color = err == nil && info.Mode()&os.ModeCharDevice != 0
+	default:
+		return fmt.Errorf("[CASL2 ERROR] Invalid -color mode \"%s\"", *optColor)
+	}
+	return nil
 }
 
 // Color functions
 func strColor(code, str string) string {
-	if *optNoColor {
+	if !verbosity.color {
 		return str
 	}
 	return code + str + "\x1b[0m"
@@ -383,27 +1128,49 @@ func colorBCyan(str string) string {
 
 func printGreen(str string) {
 	fmt.Println(colorGreen(str))
+	logOutput(str)
 }
 
 func caslPrint(msg string) {
-	if !*optQuiet {
+	if verbosity.progress {
 		fmt.Println(msg)
 	}
 }
 
+// caslWarn prints a diagnostic from the assembler that does not stop it.
+func caslWarn(msg string) {
+	fmt.Println(colorRedYellow(msg))
+}
+
 func cometPrint(msg string) {
 	fmt.Println(msg)
+	logOutput(msg)
+}
+
+// cometWarn prints a diagnostic from the emulator.
+func cometWarn(msg string) {
+	fmt.Println(colorRedYellow(msg))
+	logOutput(msg)
+}
+
+// inPrompt returns the text shown before an IN line.
+func inPrompt() string {
+	if *optInPrompt == DEFAULT_IN_PROMPT {
+		return colorIGreen("IN") + "> "
+	}
+	return *optInPrompt
 }
 
 func cometOut(msg string) {
 	prefix := ""
-	if !*optQuietRun {
+	if verbosity.ioPrompt {
 		prefix = colorIRed("OUT") + "> "
 	}
 	if !strings.HasSuffix(msg, "\n") {
 		msg += "\n"
 	}
 	fmt.Print(prefix + msg)
+	logOutput("OUT> " + msg)
 }
 
 // Utility functions
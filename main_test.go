@@ -0,0 +1,18 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestInPrompt(t *testing.T) {
+	defer func(prompt string, color bool) { *optInPrompt, verbosity.color = prompt, color }(*optInPrompt, verbosity.color)
+	verbosity.color = false
+
+	if p := inPrompt(); p != "IN> " {
+		t.Errorf("Expected the default prompt, got %q", p)
+	}
+	*optInPrompt = "? "
+	if p := inPrompt(); p != "? " {
+		t.Errorf("Expected the configured prompt, got %q", p)
+	}
+}
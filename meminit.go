@@ -0,0 +1,128 @@
+package main
+
+import (
+	"fmt"
+	"math/rand"
+	"strconv"
+	"strings"
+)
+
+// MemFill describes the contents of the memory words that are not loaded
+// from the program. The zero value fills them with #0000.
+type MemFill struct {
+	// Pattern is the value of every word unless Random is set.
+	Pattern uint16
+	// Random fills each word with a pseudo-random value from Seed, so a
+	// run can be repeated.
+	Random bool
+	Seed   int64
+	// PoisonStack fills the stack area, from the end of the program to
+	// the stack top, with Poison on every load, and overwrites each word
+	// POP and RET take off the stack with it, so that a program reading
+	// stale stack data gets an obviously wrong value.
+	PoisonStack bool
+	Poison      uint16
+}
+
+// Default sentinel of -mem-init poison
+const STACK_POISON = 0xdead
+
+// parseMemInit parses the -mem-init specification: a fill, optionally
+// followed by ",poison" or ",poison:#XXXX" to poison the stack (see
+// MemFill.PoisonStack). The fill is "zero", "ones", a word "#XXXX", a
+// byte "#XX" repeated in both halves of each word, or "random" with an
+// optional ":SEED" (default 1).
+func parseMemInit(spec string) (MemFill, error) {
+	fill, poison, hasPoison := strings.Cut(strings.ToLower(strings.TrimSpace(spec)), ",")
+	if !hasPoison && strings.HasPrefix(fill, "poison") {
+		fill, poison, hasPoison = "", fill, true
+	}
+	f, err := parseMemFill(fill)
+	if err != nil {
+		return f, fmt.Errorf("[COMET2 ERROR] Invalid -mem-init \"%s\" (zero, ones, #XXXX, #XX, random[:SEED], then ,poison[:#XXXX])", spec)
+	}
+	if !hasPoison {
+		return f, nil
+	}
+	f.PoisonStack = true
+	f.Poison = STACK_POISON
+	switch value, ok := strings.CutPrefix(poison, "poison"); {
+	case value == "":
+	case ok && strings.HasPrefix(value, ":#") && len(value) == 6:
+		n, err := strconv.ParseUint(value[2:], 16, 16)
+		if err != nil {
+			return f, fmt.Errorf("[COMET2 ERROR] Invalid -mem-init poison \"%s\" (poison or poison:#XXXX)", poison)
+		}
+		f.Poison = uint16(n)
+	default:
+		return f, fmt.Errorf("[COMET2 ERROR] Invalid -mem-init poison \"%s\" (poison or poison:#XXXX)", poison)
+	}
+	return f, nil
+}
+
+// parseMemFill parses the fill of a -mem-init specification.
+func parseMemFill(s string) (MemFill, error) {
+	switch {
+	case s == "" || s == "zero":
+		return MemFill{}, nil
+	case s == "ones":
+		return MemFill{Pattern: 0xffff}, nil
+	case s == "random" || strings.HasPrefix(s, "random:"):
+		f := MemFill{Random: true, Seed: 1}
+		if _, seed, ok := strings.Cut(s, ":"); ok {
+			n, err := strconv.ParseInt(seed, 10, 64)
+			if err != nil {
+				return f, err
+			}
+			f.Seed = n
+		}
+		return f, nil
+	case strings.HasPrefix(s, "#") && (len(s) == 3 || len(s) == 5):
+		n, err := strconv.ParseUint(s[1:], 16, 16)
+		if err != nil {
+			break
+		}
+		if len(s) == 3 {
+			n |= n << 8
+		}
+		return MemFill{Pattern: uint16(n)}, nil
+	}
+	return MemFill{}, fmt.Errorf("invalid fill \"%s\"", s)
+}
+
+// fill sets memory from the address from to the end.
+func (f MemFill) fill(memory []uint16, from int) {
+	if from >= len(memory) {
+		return
+	}
+	rest := memory[from:]
+	if f.Random {
+		r := rand.New(rand.NewSource(f.Seed))
+		for i := range rest {
+			rest[i] = uint16(r.Intn(0x10000))
+		}
+		return
+	}
+	for i := range rest {
+		rest[i] = f.Pattern
+	}
+}
+
+// poisonStack fills the stack area, from the address from up to below
+// stackTop, with the poison value when the stack is poisoned.
+func (f MemFill) poisonStack(memory []uint16, from int, stackTop int) {
+	if !f.PoisonStack {
+		return
+	}
+	for addr := from; addr < min(stackTop, len(memory)); addr++ {
+		memory[addr] = f.Poison
+	}
+}
+
+// poisonPopped overwrites the stack word at addr that POP or RET has just
+// taken off the stack when the stack is poisoned.
+func (c *Comet2) poisonPopped(addr int) {
+	if c.MemInit.PoisonStack && addr < c.StackTop {
+		c.store(addr, int(c.MemInit.Poison))
+	}
+}
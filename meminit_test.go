@@ -0,0 +1,83 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestMemInit(t *testing.T) {
+	for spec, want := range map[string]MemFill{
+		"zero":      {},
+		"ones":      {Pattern: 0xffff},
+		"#1234":     {Pattern: 0x1234},
+		"#a5":       {Pattern: 0xa5a5},
+		"random":    {Random: true, Seed: 1},
+		"random:42": {Random: true, Seed: 42},
+	} {
+		if got, err := parseMemInit(spec); err != nil || got != want {
+			t.Errorf("%s: expected %+v, got %+v (%v)", spec, want, got, err)
+		}
+	}
+	for _, spec := range []string{"#123", "#xyz", "random:x", "garbage"} {
+		if _, err := parseMemInit(spec); err == nil {
+			t.Errorf("%s: expected an error", spec)
+		}
+	}
+
+	obj := assembleObject(t, "MAIN\tSTART\n\tLD\tGR1,FREE\n\tRET\nFREE\tDS\t1\n\tEND\n")
+	c := NewComet2Builder().WithObject(obj).WithOutput(nil).WithMemInit(MemFill{Pattern: 0xbeef}).Build()
+	if c.Memory[len(obj.Bin)] != 0xbeef || c.Memory[0] != obj.Bin[0] || c.Memory[len(obj.Bin)-1] != 0 {
+		t.Errorf("Expected #BEEF outside the program only, got %04x %04x %04x",
+			c.Memory[0], c.Memory[len(obj.Bin)-1], c.Memory[len(obj.Bin)])
+	}
+	c.Memory[len(obj.Bin)] = 0
+	c.Reset(obj)
+	if c.Memory[len(obj.Bin)] != 0xbeef {
+		t.Errorf("Expected Reset to fill memory again, got %04x", c.Memory[len(obj.Bin)])
+	}
+
+	a := NewComet2Builder().WithObject(obj).WithMemInit(MemFill{Random: true, Seed: 7}).Build()
+	b := NewComet2Builder().WithObject(obj).WithMemInit(MemFill{Random: true, Seed: 7}).Build()
+	if !reflect.DeepEqual(a.Memory, b.Memory) {
+		t.Errorf("Expected the same seed to give the same memory")
+	}
+}
+
+func TestStackPoison(t *testing.T) {
+	for spec, want := range map[string]MemFill{
+		"poison":            {PoisonStack: true, Poison: STACK_POISON},
+		"random:3,poison":   {Random: true, Seed: 3, PoisonStack: true, Poison: STACK_POISON},
+		"ones,poison:#BAD0": {Pattern: 0xffff, PoisonStack: true, Poison: 0xbad0},
+	} {
+		if got, err := parseMemInit(spec); err != nil || got != want {
+			t.Errorf("%s: expected %+v, got %+v (%v)", spec, want, got, err)
+		}
+	}
+	for _, spec := range []string{"zero,poison:#12", "zero,stack", "poison,zero"} {
+		if _, err := parseMemInit(spec); err == nil {
+			t.Errorf("%s: expected an error", spec)
+		}
+	}
+
+	// The popped word is read again after POP
+	obj := assembleObject(t, "MAIN\tSTART\n\tLAD\tGR1,7\n\tPUSH\t0,GR1\n\tPOP\tGR2\n\tLD\tGR3,#FEFF\n\tRET\n\tEND\n")
+	for _, poison := range []bool{false, true} {
+		fill := MemFill{Pattern: 0x1111, PoisonStack: poison, Poison: STACK_POISON}
+		c := NewComet2Builder().WithObject(obj).WithOutput(nil).WithMemInit(fill).Build()
+		if poison && (c.Memory[len(obj.Bin)] != STACK_POISON || c.Memory[STACK_TOP-1] != STACK_POISON || c.Memory[STACK_TOP+0x10] != 0x1111) {
+			t.Errorf("Expected the stack area only to be poisoned, got %04x %04x %04x",
+				c.Memory[len(obj.Bin)], c.Memory[STACK_TOP-1], c.Memory[STACK_TOP+0x10])
+		}
+		if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+			t.Fatalf("Expected the program to finish, got %v", err)
+		}
+		want := 7
+		if poison {
+			want = STACK_POISON
+		}
+		if c.State[GR2] != 7 || c.State[GR3] != want {
+			t.Errorf("poison %v: expected GR2 = 7 and GR3 = #%04x, got #%04x #%04x", poison, want, c.State[GR2], c.State[GR3])
+		}
+	}
+}
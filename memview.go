@@ -0,0 +1,87 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// Number of words shown by one page of the mem command
+const MEM_VIEW_PAGE = 20
+
+// Address where mem without an argument continues
+var memViewNext int
+
+// cmdMem lists a page of the program memory from ADDRESS or LABEL, or from
+// where the previous page ended, grouped by the labels defined there.
+func cmdMem(c *Comet2, args []string) error {
+	if c.AddressMax == 0 {
+		return fmt.Errorf("No program is loaded.")
+	}
+	from := memViewNext
+	if len(args) > 0 {
+		addr, ok := lookupSymbol(&Object{Symbols: c.Symbols}, args[0])
+		if !ok {
+			return fmt.Errorf("Invalid address \"%s\".", args[0])
+		}
+		if addr < 0 || addr >= c.AddressMax {
+			return fmt.Errorf("Address #%s is outside the program (#0000-#%s).", hex(addr, 4), hex(c.AddressMax-1, 4))
+		}
+		from = addr
+	}
+	if from >= c.AddressMax {
+		from = 0
+	}
+
+	lines, next := memViewLines(c, from, MEM_VIEW_PAGE)
+	for _, line := range lines {
+		cometPrint(line)
+	}
+	if next >= c.AddressMax {
+		cometPrint("(end of program)")
+		next = 0
+	}
+	memViewNext = next
+	return nil
+}
+
+// memViewLines renders up to count words of the program from from, with a
+// "LABEL:" line before each word a label is defined at. Code is shown
+// disassembled and data as hex, signed, unsigned and character. It returns
+// the lines and the address after the last word shown.
+func memViewLines(c *Comet2, from, count int) ([]string, int) {
+	var lines []string
+	state := make([]int, len(c.State))
+	addr := from
+	for shown := 0; shown < count && addr < c.AddressMax; shown++ {
+		if label := c.labelAt(addr); label != "" && !strings.Contains(label, "+") {
+			lines = append(lines, label+":")
+		}
+
+		val := memGet(c.Memory, addr)
+		if isData(c.Regions, addr) {
+			lines = append(lines, strings.TrimRight(fmt.Sprintf("  #%s  %s       %6d %5d  %s",
+				hex(addr, 4), hex(val, 4), signed(val), val, memViewChar(val)), " "))
+			addr++
+			continue
+		}
+
+		state[PC] = addr
+		inst, opr, size := parse(c.Memory, state)
+		words := hex(val, 4) + "     "
+		if size == 2 {
+			words = hex(val, 4) + " " + hex(memGet(c.Memory, addr+1), 4)
+		}
+		lines = append(lines, strings.TrimRight(fmt.Sprintf("  #%s  %s  %s\t%s", hex(addr, 4), words, inst, opr), " \t"))
+		addr += size
+	}
+	return lines, addr
+}
+
+// memViewChar renders a word as a quoted character when it is printable
+// ASCII.
+func memViewChar(val int) string {
+	if val < 0x20 || val > 0x7e {
+		return ""
+	}
+	return fmt.Sprintf("'%c'", rune(val))
+}
@@ -0,0 +1,34 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestMemView(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LD	GR1,A
+	RET
+A	DC	-1,65
+	END
+`)
+	lines, next := memViewLines(c, 0, MEM_VIEW_PAGE)
+	expected := []string{
+		"MAIN:",
+		"  #0000  1010 0003  LD\tGR1,   #0003",
+		"  #0002  8100       RET",
+		"A:",
+		"  #0003  ffff           -1 65535",
+		"  #0004  0041           65    65  'A'",
+	}
+	if !reflect.DeepEqual(lines, expected) || next != 5 {
+		t.Errorf("Expected %q up to 5, got %q up to %d", expected, lines, next)
+	}
+
+	if lines, next := memViewLines(c, 0, 2); len(lines) != 3 || next != 3 {
+		t.Errorf("Expected a page of two words to end at 3, got %q up to %d", lines, next)
+	}
+	if err := executeCommand("mem", []string{"B"}, c); err == nil {
+		t.Errorf("Expected an unknown label to fail")
+	}
+}
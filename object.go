@@ -0,0 +1,374 @@
+package main
+
+import (
+	"encoding/binary"
+	"fmt"
+	"os"
+	"sort"
+	"strconv"
+	"strings"
+	"unicode/utf8"
+)
+
+// Extension of self-describing object files
+const OBJECT_EXT = ".c2o"
+
+// Object is an executable memory image for COMET2 together with the
+// symbols and source locations it was assembled from. It is the format
+// of .c2o files.
+type Object struct {
+	Version   string           `json:"version,omitempty"`
+	Source    string           `json:"source,omitempty"`
+	Start     int              `json:"start"`
+	Entry     string           `json:"entry,omitempty"`
+	Bin       []uint16         `json:"bin"`
+	Symbols   map[string]int   `json:"symbols,omitempty"`
+	SourceMap []SourceLocation `json:"sourceMap,omitempty"`
+	Units     []UnitSize       `json:"units,omitempty"`
+	Regions   []Region         `json:"regions,omitempty"`
+	Traces    []TracePoint     `json:"traces,omitempty"`
+	Bounds    []BoundedRegion  `json:"bounds,omitempty"`
+	Build     *BuildInfo       `json:"build,omitempty"`
+	// Relocs are the addresses of the words holding an address, which
+	// move with the program when it is relocated, and Absolute the
+	// symbols standing for a number (EQU), which do not.
+	Relocs   []int    `json:"relocs,omitempty"`
+	Absolute []string `json:"absolute,omitempty"`
+	// Literals are the words of each literal in the literal pools.
+	Literals []Region `json:"literals,omitempty"`
+	Checksum string   `json:"checksum,omitempty"`
+}
+
+// Region is a run of words that are all machine instructions (code) or
+// all data (DC, DS and literals). End is exclusive.
+type Region struct {
+	Start int  `json:"start"`
+	End   int  `json:"end"`
+	Code  bool `json:"code"`
+}
+
+// SourceLocation is the source line a memory word was generated from.
+type SourceLocation struct {
+	File string `json:"file,omitempty"`
+	Line int    `json:"line,omitempty"`
+}
+
+// newObject bundles the result of assembling into an Object.
+func newObject(bin []uint16, startLabel string, asmState *AssemblerState) *Object {
+	obj := &Object{
+		Version:   VERSION,
+		Source:    asmState.file,
+		Start:     expandLabel(asmState.symtbl, startLabel),
+		Entry:     asmState.entry,
+		Bin:       bin,
+		Symbols:   make(map[string]int),
+		SourceMap: make([]SourceLocation, len(bin)),
+		Units:     asmState.units,
+		Build:     asmState.build,
+		Traces:    tracePoints(asmState),
+		Bounds:    boundedRegions(asmState),
+		Relocs:    asmState.relocs,
+	}
+	for name := range asmState.equates {
+		obj.Absolute = append(obj.Absolute, name)
+	}
+	sort.Strings(obj.Absolute)
+	for _, lit := range asmState.literals {
+		obj.Literals = append(obj.Literals, Region{Start: lit.Address, End: lit.Address + literalSize(lit.Text)})
+	}
+	sort.Slice(obj.Literals, func(i, j int) bool {
+		return obj.Literals[i].Start < obj.Literals[j].Start
+	})
+
+	for name := range asmState.symtbl {
+		if !strings.HasPrefix(name, "=") {
+			obj.Symbols[name] = expandLabel(asmState.symtbl, name)
+		}
+	}
+	for addr, entry := range asmState.memory {
+		if addr >= 0 && addr < len(bin) {
+			src := asmState.origin(entry.Line)
+			obj.SourceMap[addr] = SourceLocation{File: src.File, Line: src.Line}
+		}
+	}
+	for addr := range bin {
+		code := asmState.memory[addr] != nil && asmState.memory[addr].Code
+		if n := len(obj.Regions); n > 0 && obj.Regions[n-1].Code == code {
+			obj.Regions[n-1].End = addr + 1
+		} else {
+			obj.Regions = append(obj.Regions, Region{Start: addr, End: addr + 1, Code: code})
+		}
+	}
+
+	return obj
+}
+
+// literalSize returns the number of words of the literal text ("=10",
+// "='AB'"): one for a number, the characters and a 0 for a string.
+func literalSize(text string) int {
+	value := strings.TrimPrefix(text, "=")
+	if len(value) >= 2 && strings.HasPrefix(value, "'") && strings.HasSuffix(value, "'") {
+		return utf8.RuneCountInString(strings.ReplaceAll(value[1:len(value)-1], "''", "'")) + 1
+	}
+	return 1
+}
+
+// isData reports whether addr lies in a data region of regions. Without
+// region information every word may be code.
+func isData(regions []Region, addr int) bool {
+	for _, r := range regions {
+		if addr >= r.Start && addr < r.End {
+			return !r.Code
+		}
+	}
+	return false
+}
+
+// isCode reports whether addr lies in a code region of regions. Without
+// region information no word is known to be code.
+func isCode(regions []Region, addr int) bool {
+	for _, r := range regions {
+		if addr >= r.Start && addr < r.End {
+			return r.Code
+		}
+	}
+	return false
+}
+
+// selectEntry makes obj start at the entry point of the START unit named
+// unit instead of the first one.
+func selectEntry(obj *Object, unit string) error {
+	for _, u := range obj.Units {
+		if u.Name == foldCase(unit) {
+			obj.Start = u.Entry
+			obj.Entry = u.Name
+			return nil
+		}
+	}
+	names := make([]string, len(obj.Units))
+	for i, u := range obj.Units {
+		names[i] = u.Name
+	}
+	return fmt.Errorf("[CASL2 ERROR] No START unit \"%s\" (units: %s)", unit, strings.Join(names, ", "))
+}
+
+// writeObjectFile saves obj as a .c2o file with the checksum of its image.
+func writeObjectFile(filepath string, obj *Object) error {
+	data, err := objectFormat{}.Encode(obj, binary.BigEndian)
+	if err != nil {
+		return fmt.Errorf("[CASL2 ERROR] %v", err)
+	}
+	if err := os.WriteFile(filepath, data, 0644); err != nil {
+		return fmt.Errorf("[CASL2 ERROR] Cannot write file: %v", err)
+	}
+	return nil
+}
+
+// loadObjectFile reads a .c2o file and verifies its checksum.
+func loadObjectFile(filepath string) (*Object, error) {
+	obj, err := readObjectFile(filepath)
+	if err != nil {
+		return nil, err
+	}
+	if err := verifyChecksum(obj); err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] %s: %v", filepath, err)
+	}
+	return obj, nil
+}
+
+// readObjectFile reads a .c2o file without verifying it.
+func readObjectFile(filepath string) (*Object, error) {
+	data, err := os.ReadFile(filepath)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read file: %v", err)
+	}
+
+	obj, err := objectFormat{}.Decode(data, binary.BigEndian)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] %s: %v", filepath, err)
+	}
+	return obj, nil
+}
+
+// ObjectImporter reads an object produced by another CASL2 simulator.
+// WCASL-II has none: its object layout is undocumented and no sample was
+// available to write one against.
+type ObjectImporter interface {
+	// Name is the format name selected with -import.
+	Name() string
+	// Import converts the file contents into an Object.
+	Import(data []byte) (*Object, error)
+}
+
+var objectImporters = map[string]ObjectImporter{}
+
+// registerImporter makes an importer available to -import.
+func registerImporter(imp ObjectImporter) {
+	objectImporters[imp.Name()] = imp
+}
+
+func init() {
+	registerImporter(hexDumpImporter{})
+	registerImporter(wordListImporter{})
+	registerImporter(yacasl2Importer{})
+}
+
+// importerNames returns the registered format names in sorted order.
+func importerNames() []string {
+	names := make([]string, 0, len(objectImporters))
+	for name := range objectImporters {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// importObject reads filepath with the importer registered as format.
+func importObject(format, filepath string) (*Object, error) {
+	imp, ok := objectImporters[format]
+	if !ok {
+		return nil, fmt.Errorf("[COMET2 ERROR] Unknown object format \"%s\" (available: %s)",
+			format, strings.Join(importerNames(), ", "))
+	}
+
+	data, err := os.ReadFile(filepath)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] Cannot read file: %v", err)
+	}
+
+	obj, err := imp.Import(data)
+	if err != nil {
+		return nil, fmt.Errorf("[COMET2 ERROR] %s: %v", filepath, err)
+	}
+	return obj, nil
+}
+
+// parseHexWord parses a 16-bit word written in hex, with optional '#'.
+func parseHexWord(s string) (uint16, bool) {
+	s = strings.TrimPrefix(s, "#")
+	if len(s) == 0 || len(s) > 4 {
+		return 0, false
+	}
+	num, err := strconv.ParseUint(s, 16, 16)
+	if err != nil {
+		return 0, false
+	}
+	return uint16(num), true
+}
+
+// objectLines splits an object text into lines without ';' comments.
+func objectLines(data []byte) []string {
+	lines := strings.Split(strings.ReplaceAll(string(data), "\r\n", "\n"), "\n")
+	for i, line := range lines {
+		if idx := strings.Index(line, ";"); idx >= 0 {
+			line = line[:idx]
+		}
+		lines[i] = strings.TrimSpace(line)
+	}
+	return lines
+}
+
+// storeWord writes val at addr, growing the image as needed. Addresses
+// past the end of memory are refused.
+func (o *Object) storeWord(addr int, val uint16) error {
+	if addr >= ADDRESS_SPACE {
+		return fmt.Errorf("word at #%X is past the end of memory", addr)
+	}
+	for len(o.Bin) <= addr {
+		o.Bin = append(o.Bin, 0)
+	}
+	o.Bin[addr] = val
+	return nil
+}
+
+// hexDumpImporter reads address-prefixed hex dumps as printed by the
+// dump command of c2c2 and by most classroom simulators:
+//
+//	START 0000
+//	0000: 1210 0005 1220 0001 ...
+//
+// Words after the address are read until the first non-hex field, so a
+// trailing character column is ignored.
+type hexDumpImporter struct{}
+
+func (hexDumpImporter) Name() string {
+	return "hexdump"
+}
+
+func (hexDumpImporter) Import(data []byte) (*Object, error) {
+	obj := &Object{}
+
+	for i, line := range objectLines(data) {
+		if line == "" {
+			continue
+		}
+		fields := strings.Fields(line)
+
+		if strings.ToUpper(fields[0]) == "START" {
+			if len(fields) != 2 {
+				return nil, fmt.Errorf("line %d: invalid START", i+1)
+			}
+			start, ok := parseHexWord(fields[1])
+			if !ok {
+				return nil, fmt.Errorf("line %d: invalid start address \"%s\"", i+1, fields[1])
+			}
+			obj.Start = int(start)
+			continue
+		}
+
+		addr, ok := parseHexWord(strings.TrimSuffix(fields[0], ":"))
+		if !ok || !strings.HasSuffix(fields[0], ":") {
+			return nil, fmt.Errorf("line %d: expected \"ADDR:\" but got \"%s\"", i+1, fields[0])
+		}
+		for j, field := range fields[1:] {
+			val, ok := parseHexWord(field)
+			if !ok || len(strings.TrimPrefix(field, "#")) != 4 {
+				break
+			}
+			if err := obj.storeWord(int(addr)+j, val); err != nil {
+				return nil, fmt.Errorf("line %d: %v", i+1, err)
+			}
+		}
+	}
+
+	return obj, nil
+}
+
+// wordListImporter reads one hex word per line starting at address 0.
+type wordListImporter struct{}
+
+func (wordListImporter) Name() string {
+	return "words"
+}
+
+func (wordListImporter) Import(data []byte) (*Object, error) {
+	obj := &Object{}
+
+	for i, line := range objectLines(data) {
+		if line == "" {
+			continue
+		}
+		val, ok := parseHexWord(line)
+		if !ok {
+			return nil, fmt.Errorf("line %d: invalid word \"%s\"", i+1, line)
+		}
+		if err := obj.storeWord(len(obj.Bin), val); err != nil {
+			return nil, fmt.Errorf("line %d: %v", i+1, err)
+		}
+	}
+
+	return obj, nil
+}
+
+// yacasl2Importer reads the object files of YACASL2 (casl2 -O): the words
+// of memory from address 0 as its host stores them, little-endian on the
+// PCs it runs on, without a header. Execution starts at address 0.
+type yacasl2Importer struct{}
+
+func (yacasl2Importer) Name() string {
+	return "yacasl2"
+}
+
+func (yacasl2Importer) Import(data []byte) (*Object, error) {
+	return rawFormat{}.Decode(data, binary.LittleEndian)
+}
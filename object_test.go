@@ -0,0 +1,118 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestSelectEntry(t *testing.T) {
+	src := `LIB	START
+	RET
+	END
+DRIVER	START	GO
+	NOP
+GO	LAD	GR1,5
+	RET
+	END
+`
+	path := filepath.Join(t.TempDir(), "lib.cas")
+	os.WriteFile(path, []byte(src), 0644)
+	asmState := newAssemblerState()
+	obj, err := assemble(path, asmState)
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+	if !strings.Contains(strings.Join(asmState.outdump, "\n"), "ENTRY POINTS\n0000\tLIB\n0002\tDRIVER") {
+		t.Errorf("Expected the entry points in the listing, got\n%s", strings.Join(asmState.outdump, "\n"))
+	}
+
+	if err := selectEntry(obj, "DRIVER"); err != nil || obj.Start != 2 || obj.Entry != "DRIVER" {
+		t.Fatalf("Expected to start at DRIVER (#0002), got #%s (%v)", hex(obj.Start, 4), err)
+	}
+	c := LoadObject(obj)
+	c.Output = func(string) {}
+	c.Run()
+	if c.State[GR1] != 5 {
+		t.Errorf("Expected GR1 = 5, got %d", c.State[GR1])
+	}
+
+	if err := selectEntry(obj, "NONE"); err == nil || !strings.Contains(err.Error(), "units: LIB, DRIVER") {
+		t.Errorf("Expected an unknown unit error, got %v", err)
+	}
+}
+
+func TestCodeRegions(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,=3
+	RET
+BUF	DS	2
+MSG	DC	'ab'
+	END
+`)
+	// LD (2 words) and RET, then BUF, MSG and the literal =3
+	want := []Region{{Start: 0, End: 3, Code: true}, {Start: 3, End: 8, Code: false}}
+	if !reflect.DeepEqual(obj.Regions, want) {
+		t.Errorf("Expected regions %+v, got %+v", want, obj.Regions)
+	}
+	if isData(obj.Regions, 1) || !isData(obj.Regions, 3) || isData(obj.Regions, 100) {
+		t.Errorf("Unexpected isData results")
+	}
+	if s := regionMarks(obj.Regions, 0, 10); s != "cccddddd.." {
+		t.Errorf("Unexpected region marks %q", s)
+	}
+}
+
+func TestImporters(t *testing.T) {
+	dir := t.TempDir()
+	write := func(name string, data []byte) string {
+		t.Helper()
+		path := filepath.Join(dir, name)
+		if err := os.WriteFile(path, data, 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", name, err)
+		}
+		return path
+	}
+
+	// LAD GR1,5 / RET, as YACASL2 writes it on a little-endian host
+	obj, err := importObject("yacasl2", write("prog.o", []byte{0x10, 0x12, 0x05, 0x00, 0x00, 0x81}))
+	if err != nil {
+		t.Fatalf("yacasl2: %v", err)
+	}
+	if want := []uint16{0x1210, 0x0005, 0x8100}; !reflect.DeepEqual(obj.Bin, want) || obj.Start != 0 {
+		t.Errorf("yacasl2: expected %04x from 0, got %04x from %d", want, obj.Bin, obj.Start)
+	}
+	c := LoadObject(obj)
+	c.Output = func(string) {}
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") || c.State[GR1] != 5 {
+		t.Errorf("yacasl2: expected the program to set GR1 to 5, got %d (%v)", c.State[GR1], err)
+	}
+	if _, err := importObject("yacasl2", write("odd.o", []byte{0x10, 0x12, 0x05})); err == nil {
+		t.Errorf("yacasl2: expected an odd number of bytes to be refused")
+	}
+
+	obj, err = importObject("hexdump", write("dump.txt", []byte("START 0002\n0000: 0000 0000 1210 0005 ..`.\n0004: 8100\n")))
+	if err != nil {
+		t.Fatalf("hexdump: %v", err)
+	}
+	if want := []uint16{0, 0, 0x1210, 0x0005, 0x8100}; !reflect.DeepEqual(obj.Bin, want) || obj.Start != 2 {
+		t.Errorf("hexdump: expected %04x from 2, got %04x from %d", want, obj.Bin, obj.Start)
+	}
+
+	// Nothing may reach past the 64K words of memory
+	if _, err := importObject("hexdump", write("over.txt", []byte("FFFE: 0001 0002 0003\n"))); err == nil || !strings.Contains(err.Error(), "line 1: word at #10000 is past the end of memory") {
+		t.Errorf("hexdump: expected a word past #FFFF to be refused, got %v", err)
+	}
+	words := strings.Repeat("0000\n", ADDRESS_SPACE)
+	if obj, err := importObject("words", write("full.txt", []byte(words))); err != nil || len(obj.Bin) != ADDRESS_SPACE {
+		t.Errorf("words: expected a full memory to load, got %v", err)
+	}
+	if _, err := importObject("words", write("over.txt", []byte(words+"0001\n"))); err == nil {
+		t.Errorf("words: expected a word past #FFFF to be refused")
+	}
+	if _, err := importObject("yacasl2", write("over.o", make([]byte, IMAGE_BYTES+2))); err == nil {
+		t.Errorf("yacasl2: expected an image past #FFFF to be refused")
+	}
+}
@@ -0,0 +1,133 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// Codes of the operand diagnostics. The assembler ends each message with
+// its code, -check reports it as "code", and "c2c2 doc CODE" explains it.
+const (
+	DIAG_OPERAND_COUNT = "E101"
+	DIAG_EMPTY_OPERAND = "E102"
+	DIAG_NOT_REGISTER  = "E103"
+	DIAG_BAD_INDEX     = "E104"
+	DIAG_NOT_ADDRESS   = "E105"
+)
+
+// Explanations shown by "c2c2 doc CODE"
+var diagnosticDocs = map[string]string{
+	DIAG_OPERAND_COUNT: "Wrong number of operands. Each form takes a fixed list, e.g. ADDA r,adr[,x] takes two or three and RET none; see \"c2c2 doc\" for the forms of an instruction.",
+	DIAG_EMPTY_OPERAND: "An operand is empty: the list starts or ends with a comma or has two in a row, as in \"LD GR1,ADR,\". Remove the comma or write the missing operand.",
+	DIAG_NOT_REGISTER:  "The operand must be a register GR0-GR7, as the r of LD r,adr[,x] and POP r.",
+	DIAG_BAD_INDEX:     "The index register x of adr,x must be GR1-GR7. GR0 cannot be used, since x=0 in the code means no index, and the r1,r2 form takes no index at all.",
+	DIAG_NOT_ADDRESS:   "A register is written where an address is expected, as in \"LAD GR1,GR2\". Only ADDA, LD and the other r1,r2 instructions take a register there.",
+}
+
+// operandCounts are the smallest and largest number of operands of each
+// instruction type; -1 is no limit. Directives add theirs when they are
+// registered.
+var operandCounts = map[InstructionType][2]int{
+	OP1: {2, 3},
+	OP2: {1, 2},
+	OP3: {1, 1},
+	OP4: {0, 0},
+	OP5: {2, 3},
+}
+
+// operandSyntax describes the accepted operand forms of inst, e.g.
+// "ADDA r,adr[,x] or ADDA r1,r2".
+func operandSyntax(inst string) string {
+	var forms []string
+	switch CASL2TBL[inst].Type {
+	case OP1:
+		forms = []string{"r,adr[,x]"}
+	case OP2:
+		forms = []string{"adr[,x]"}
+	case OP3:
+		forms = []string{"r"}
+	case OP4:
+		forms = []string{""}
+	case OP5:
+		forms = []string{"r,adr[,x]", "r1,r2"}
+	default:
+		forms = []string{instructionDocs[inst].operands}
+	}
+	for i, f := range forms {
+		forms[i] = strings.TrimSpace(inst + " " + f)
+	}
+	return strings.Join(forms, " or ")
+}
+
+// errorOperand returns an assembly error with the given code about the
+// operands of inst. The message goes on with the forms inst accepts.
+func errorOperand(asmState *AssemblerState, code string, inst string, msg string) error {
+	o := asmState.origin(asmState.line)
+	msg = fmt.Sprintf("%s; expected %s [%s]", msg, operandSyntax(inst), code)
+	return &AssembleError{File: o.File, Line: o.Line, Msg: msg, Code: code}
+}
+
+// checkOperands checks the operand list opr of inst, split into oprs, for
+// the number of operands and for registers where the form needs them,
+// before the line is assembled.
+func checkOperands(asmState *AssemblerState, inst string, opr string, oprs []string) error {
+	instType := CASL2TBL[inst].Type
+	opr = strings.TrimSpace(opr)
+
+	// parseOperands drops a trailing empty operand
+	if strings.HasSuffix(opr, ",") {
+		return errorOperand(asmState, DIAG_EMPTY_OPERAND, inst, fmt.Sprintf("Missing operand after the last \",\" in \"%s\"", opr))
+	}
+	for i, op := range oprs {
+		if op == "" {
+			return errorOperand(asmState, DIAG_EMPTY_OPERAND, inst, fmt.Sprintf("Operand %d of \"%s\" is empty", i+1, opr))
+		}
+	}
+
+	counts := operandCounts[instType]
+	switch {
+	case len(oprs) < counts[0] && len(oprs) == 0:
+		return errorOperand(asmState, DIAG_OPERAND_COUNT, inst, "Missing operands")
+	case len(oprs) < counts[0]:
+		return errorOperand(asmState, DIAG_OPERAND_COUNT, inst, fmt.Sprintf("Too few operands in \"%s\"", opr))
+	case counts[1] == 0 && len(oprs) > 0:
+		return errorOperand(asmState, DIAG_OPERAND_COUNT, inst, fmt.Sprintf("Unexpected operand \"%s\"", opr))
+	case counts[1] >= 0 && len(oprs) > counts[1]:
+		return errorOperand(asmState, DIAG_OPERAND_COUNT, inst, fmt.Sprintf("Too many operands in \"%s\"", opr))
+	}
+
+	// Where the register, address and index operands are in the list
+	reg, adr, index := -1, -1, -1
+	switch instType {
+	case OP1, OP5:
+		reg, adr, index = 0, 1, 2
+	case OP2:
+		adr, index = 0, 1
+	case OP3:
+		reg = 0
+	}
+
+	if reg >= 0 {
+		if _, err := checkRegister(oprs[reg]); err != nil {
+			return errorOperand(asmState, DIAG_NOT_REGISTER, inst, fmt.Sprintf("\"%s\" is not a register", oprs[reg]))
+		}
+	}
+	if adr >= 0 && isRegister(oprs[adr]) {
+		if instType != OP5 {
+			return errorOperand(asmState, DIAG_NOT_ADDRESS, inst, fmt.Sprintf("\"%s\" is a register, not an address", oprs[adr]))
+		}
+		if len(oprs) > index {
+			return errorOperand(asmState, DIAG_BAD_INDEX, inst, fmt.Sprintf("The r1,r2 form takes no index register, got \"%s\"", oprs[index]))
+		}
+	}
+	if index >= 0 && len(oprs) > index {
+		n, err := checkRegister(oprs[index])
+		if err != nil {
+			return errorOperand(asmState, DIAG_BAD_INDEX, inst, fmt.Sprintf("\"%s\" is not an index register", oprs[index]))
+		}
+		if n == 0 {
+			return errorOperand(asmState, DIAG_BAD_INDEX, inst, "Can't use GR0 as an index register")
+		}
+	}
+	return nil
+}
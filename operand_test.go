@@ -0,0 +1,44 @@
+package main
+
+import (
+	"bytes"
+	"errors"
+	"strings"
+	"testing"
+)
+
+func TestOperandValidation(t *testing.T) {
+	for _, tc := range []struct {
+		line string
+		code string
+		msg  string
+	}{
+		{"\tADDA\tGR1,DATA,", DIAG_EMPTY_OPERAND, "expected ADDA r,adr[,x] or ADDA r1,r2"},
+		{"\tLD\tGR1,,GR2", DIAG_EMPTY_OPERAND, "Operand 2 of \"GR1,,GR2\" is empty"},
+		{"\tST\tGR1", DIAG_OPERAND_COUNT, "Too few operands in \"GR1\"; expected ST r,adr[,x]"},
+		{"\tRET\tGR1", DIAG_OPERAND_COUNT, "Unexpected operand \"GR1\"; expected RET"},
+		{"\tPOP", DIAG_OPERAND_COUNT, "Missing operands; expected POP r"},
+		{"\tLD\tDATA,GR1", DIAG_NOT_REGISTER, "\"DATA\" is not a register"},
+		{"\tLAD\tGR1,DATA,GR0", DIAG_BAD_INDEX, "Can't use GR0 as an index register"},
+		{"\tJUMP\tDATA,FOO", DIAG_BAD_INDEX, "\"FOO\" is not an index register"},
+		{"\tADDA\tGR1,GR2,GR3", DIAG_BAD_INDEX, "The r1,r2 form takes no index register"},
+		{"\tLAD\tGR1,GR2", DIAG_NOT_ADDRESS, "\"GR2\" is a register, not an address"},
+		{"\tOUT\tDATA", DIAG_OPERAND_COUNT, "expected OUT buf,len"},
+	} {
+		src := "MAIN\tSTART\n" + tc.line + "\n\tRET\nDATA\tDC\t1,2,0\n\tEND\n"
+		_, err := assembleText("prog.cas", src, newAssemblerState())
+		var aerr *AssembleError
+		if !errors.As(err, &aerr) {
+			t.Errorf("%q: expected AssembleError, got %v", tc.line, err)
+			continue
+		}
+		if aerr.Line != 2 || aerr.Code != tc.code || !strings.Contains(aerr.Msg, tc.msg) || !strings.HasSuffix(aerr.Msg, "["+tc.code+"]") {
+			t.Errorf("%q: expected %s %q on line 2, got %s line %d: %s", tc.line, tc.code, tc.msg, aerr.Code, aerr.Line, aerr.Msg)
+		}
+	}
+
+	var out bytes.Buffer
+	if status := runDocCommand([]string{"e102"}, &out); status != 0 || !strings.Contains(out.String(), "empty") {
+		t.Errorf("Expected doc E102 to explain the code, got %d %q", status, out.String())
+	}
+}
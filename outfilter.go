@@ -0,0 +1,98 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+// OutputFilter transforms the text of one OUT line before it is written
+// and recorded.
+type OutputFilter func(text string) string
+
+// Text that "mask:REGEX" puts in place of each match
+const OUTPUT_MASK = "*"
+
+var (
+	spacesPattern  = regexp.MustCompile(`[ \t]+`)
+	integerPattern = regexp.MustCompile(`[+-]?[0-9]+`)
+)
+
+// Output filters by name, for -out-filter and the "filters" of tests
+var outputFilters = map[string]OutputFilter{
+	// trim drops trailing spaces and tabs.
+	"trim": func(text string) string {
+		return strings.TrimRight(text, " \t")
+	},
+	// squeeze turns each run of spaces and tabs into one space.
+	"squeeze": func(text string) string {
+		return spacesPattern.ReplaceAllString(text, " ")
+	},
+	// numbers writes integers without leading zeros and + sign, and -0
+	// as 0.
+	"numbers": func(text string) string {
+		return integerPattern.ReplaceAllStringFunc(text, func(s string) string {
+			n, err := strconv.Atoi(s)
+			if err != nil {
+				return s
+			}
+			return strconv.Itoa(n)
+		})
+	},
+}
+
+// parseOutputFilter returns the filter named spec: one of outputFilters,
+// or "mask:REGEX", which replaces every match of REGEX with OUTPUT_MASK.
+func parseOutputFilter(spec string) (OutputFilter, error) {
+	if pattern, ok := strings.CutPrefix(spec, "mask:"); ok {
+		re, err := regexp.Compile(pattern)
+		if err != nil {
+			return nil, fmt.Errorf("[COMET2 ERROR] Invalid output filter \"%s\": %v", spec, err)
+		}
+		return func(text string) string {
+			return re.ReplaceAllString(text, OUTPUT_MASK)
+		}, nil
+	}
+	if f, ok := outputFilters[spec]; ok {
+		return f, nil
+	}
+	return nil, fmt.Errorf("[COMET2 ERROR] Unknown output filter \"%s\" (trim, squeeze, numbers, mask:REGEX)", spec)
+}
+
+// parseOutputFilters parses a list of filter names, applied in order.
+func parseOutputFilters(specs []string) ([]OutputFilter, error) {
+	var filters []OutputFilter
+	for _, spec := range specs {
+		f, err := parseOutputFilter(strings.TrimSpace(spec))
+		if err != nil {
+			return nil, err
+		}
+		filters = append(filters, f)
+	}
+	return filters, nil
+}
+
+// applyOutputFilters passes text through filters in order.
+func applyOutputFilters(filters []OutputFilter, text string) string {
+	for _, f := range filters {
+		text = f(text)
+	}
+	return text
+}
+
+// filterTranscript applies filters to the OUT lines of a transcript as
+// runTranscript writes it, so that an expected output and an actual one
+// are compared after the same filters.
+func filterTranscript(filters []OutputFilter, transcript string) string {
+	if len(filters) == 0 {
+		return transcript
+	}
+	lines := strings.Split(transcript, "\n")
+	for i, line := range lines {
+		if text, ok := strings.CutPrefix(line, "OUT> "); ok {
+			lines[i] = "OUT> " + applyOutputFilters(filters, text)
+		}
+	}
+	return strings.Join(lines, "\n")
+}
@@ -0,0 +1,34 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestOutputFilters(t *testing.T) {
+	filters, err := parseOutputFilters([]string{"numbers", "squeeze", "trim", "mask:t=[0-9]+"})
+	if err != nil {
+		t.Fatal(err)
+	}
+	if got := applyOutputFilters(filters, "+007  -0\tx t=12 "); got != "7 0 x *" {
+		t.Errorf("Unexpected filtered text %q", got)
+	}
+	if _, err := parseOutputFilters([]string{"upper"}); err == nil {
+		t.Errorf("Expected an unknown filter to be rejected")
+	}
+	if got := filterTranscript(filters, "IN> 01\nOUT> 01 \nProgram finished (RET)\n"); got != "IN> 01\nOUT> 1\nProgram finished (RET)\n" {
+		t.Errorf("Unexpected filtered transcript %q", got)
+	}
+
+	var outputs []string
+	c := NewComet2Builder().WithObject(assembleObject(t, echoProgram)).
+		WithInputs([]string{"abc  "}).
+		WithOutput(func(text string) { outputs = append(outputs, text) }).
+		WithOutputFilter(outputFilters["trim"]).
+		WithOutputFilter(strings.ToUpper).Build()
+	c.FailOnInputWait = true
+	c.Run()
+	if len(outputs) == 0 || outputs[0] != "ABC" || c.Transcript[1].Text != "ABC" {
+		t.Errorf("Expected the filtered line to be written and recorded, got %q", outputs)
+	}
+}
@@ -0,0 +1,26 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"strings"
+)
+
+// lineWriter returns an OUT handler that writes each line to w.
+func lineWriter(w io.Writer) func(string) {
+	return func(text string) {
+		fmt.Fprint(w, strings.TrimSuffix(text, "\n")+"\n")
+	}
+}
+
+// teeOutput returns an OUT handler that passes text to every non-nil
+// handler in outs.
+func teeOutput(outs ...func(string)) func(string) {
+	return func(text string) {
+		for _, out := range outs {
+			if out != nil {
+				out(text)
+			}
+		}
+	}
+}
@@ -0,0 +1,84 @@
+package main
+
+import (
+	"bytes"
+	"errors"
+	"testing"
+)
+
+func TestOutChars(t *testing.T) {
+	src := `MAIN	START
+	OUT	BUF,LEN
+	RET
+BUF	DC	'a',7,#5C,#1234,0,'b'
+LEN	DC	6
+	END
+`
+	tests := []struct {
+		spec  string
+		want  string
+		warns int
+	}{
+		{"raw", "a\x07\\\x34\x00b", 0},
+		{"escape", `a\x07\\\u1234\x00b`, 1},
+		{"escape,stop-nul", `a\x07\\\u1234`, 1},
+		{"stop-nul", "a\x07\\\x34", 0},
+	}
+	for _, tt := range tests {
+		c := assembleSource(t, src)
+		if err := c.SetOutChars(tt.spec); err != nil {
+			t.Fatalf("SetOutChars(%s): %v", tt.spec, err)
+		}
+		var warnings []string
+		c.Warn = func(msg string) { warnings = append(warnings, msg) }
+		c.Run()
+		if len(c.Transcript) != 1 || c.Transcript[0].Text != tt.want {
+			t.Errorf("%s: got %q, want %q", tt.spec, c.Transcript, tt.want)
+		}
+		if len(warnings) != tt.warns {
+			t.Errorf("%s: got warnings %v", tt.spec, warnings)
+		}
+	}
+
+	c := assembleSource(t, src)
+	if err := c.SetOutChars("hex"); err == nil {
+		t.Errorf("Expected an error for an unknown option")
+	}
+}
+
+func TestMaxOutputLines(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+LOOP	OUT	MSG,LEN
+	JUMP	LOOP
+MSG	DC	'spam'
+LEN	DC	4
+	END
+`)
+	c := NewComet2Builder().WithObject(obj).WithOutput(nil).WithMaxOutput(0, 3).Build()
+
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_OUTPUT_LIMIT {
+		t.Fatalf("Expected output limit, got %v", err)
+	}
+	if !c.OutputTruncated || len(c.Transcript) != 3 {
+		t.Errorf("Expected 3 lines and truncation, got %d lines (truncated %v)", len(c.Transcript), c.OutputTruncated)
+	}
+}
+
+func TestTeeOutput(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+	c.Input = NewInputLines([]string{"abc"})
+
+	var file bytes.Buffer
+	var console []string
+	c.Output = teeOutput(func(text string) {
+		console = append(console, text)
+	}, lineWriter(&file))
+
+	if err := c.Run(); err == nil {
+		t.Fatalf("Expected program to finish")
+	}
+	if file.String() != "abc\n" || len(console) != 1 || console[0] != "abc" {
+		t.Errorf("Unexpected output: file %q, console %q", file.String(), console)
+	}
+}
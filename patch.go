@@ -0,0 +1,133 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"strings"
+)
+
+// objectPatch is one word to change: ADDRESS=VALUE, where ADDRESS is a
+// number, a label of the object or LABEL+N.
+type objectPatch struct {
+	Addr  int
+	Value int
+}
+
+// parsePatch reads ADDRESS=VALUE for obj. origin tells where it was given,
+// for messages.
+func parsePatch(obj *Object, text string, origin string) (objectPatch, error) {
+	addrText, valueText, ok := strings.Cut(text, "=")
+	if !ok {
+		return objectPatch{}, fmt.Errorf("%s: expected ADDRESS=VALUE, got \"%s\"", origin, text)
+	}
+	addrText, valueText = strings.TrimSpace(addrText), strings.TrimSpace(valueText)
+	addr, ok := lookupAddress(obj, addrText)
+	if !ok {
+		return objectPatch{}, fmt.Errorf("%s: invalid address \"%s\"", origin, addrText)
+	}
+	if addr >= len(obj.Bin) {
+		return objectPatch{}, fmt.Errorf("%s: address #%s is outside the image (%d words)", origin, hex(addr, 4), len(obj.Bin))
+	}
+	value, ok := expandNumber(valueText)
+	if !ok {
+		return objectPatch{}, fmt.Errorf("%s: invalid value \"%s\"", origin, valueText)
+	}
+	return objectPatch{Addr: addr, Value: value}, nil
+}
+
+// readPatchFile reads one ADDRESS=VALUE per line; ';' starts a comment.
+func readPatchFile(obj *Object, path string) ([]objectPatch, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return nil, fmt.Errorf("Cannot read patch file: %v", err)
+	}
+	var patches []objectPatch
+	for n, line := range strings.Split(string(data), "\n") {
+		if i := strings.Index(line, ";"); i >= 0 {
+			line = line[:i]
+		}
+		line = strings.TrimSpace(line)
+		if line == "" {
+			continue
+		}
+		p, err := parsePatch(obj, line, fmt.Sprintf("%s line %d", path, n+1))
+		if err != nil {
+			return nil, err
+		}
+		patches = append(patches, p)
+	}
+	return patches, nil
+}
+
+// runPatchCommand implements "c2c2 patch". It changes words of the image
+// of an object file, given as ADDRESS=VALUE arguments or in a patch file,
+// and writes the object with a new checksum, which is verified by reading
+// it back. The object must verify before it is patched. It returns the
+// exit status.
+func runPatchCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("patch", flag.ContinueOnError)
+	fs.SetOutput(w)
+	patchFile := fs.String("p", "", "read ADDRESS=VALUE lines from the given file")
+	output := fs.String("o", "", "write the patched object to the given file instead of FILE")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 patch [-p PATCHFILE] [-o OUTPUT] FILE%s [ADDRESS=VALUE ...]\n\nOptions:\n", OBJECT_EXT)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() < 1 || (fs.NArg() == 1 && *patchFile == "") {
+		fs.Usage()
+		return 2
+	}
+
+	path := fs.Arg(0)
+	obj, err := loadObjectFile(path)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	var patches []objectPatch
+	if *patchFile != "" {
+		patches, err = readPatchFile(obj, *patchFile)
+		if err != nil {
+			fmt.Fprintf(w, "[COMET2 ERROR] %v\n", err)
+			return 1
+		}
+	}
+	for i, arg := range fs.Args()[1:] {
+		p, err := parsePatch(obj, arg, fmt.Sprintf("argument %d", i+1))
+		if err != nil {
+			fmt.Fprintf(w, "[COMET2 ERROR] %v\n", err)
+			return 1
+		}
+		patches = append(patches, p)
+	}
+
+	before := obj.Checksum
+	symbols := NewSymbolizer(obj.Symbols, len(obj.Bin))
+	for _, p := range patches {
+		old := int(obj.Bin[p.Addr])
+		obj.Bin[p.Addr] = uint16(p.Value)
+		fmt.Fprintf(w, "%s: %s -> %s\n", symbols.Format(p.Addr), formatWord(old), formatWord(p.Value))
+	}
+
+	if *output == "" {
+		*output = path
+	}
+	if err := writeObjectFile(*output, obj); err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	if _, err := loadObjectFile(*output); err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	if before == "" {
+		before = "not recorded"
+	}
+	fmt.Fprintf(w, "Patched %d words of %s, checksum %s (was %s)\n", len(patches), *output, obj.Checksum, before)
+	return 0
+}
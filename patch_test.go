@@ -0,0 +1,58 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestPatchObject(t *testing.T) {
+	dir := t.TempDir()
+	obj, err := assembleText("prog.cas", "MAIN\tSTART\n\tLD\tGR1,A\n\tRET\nA\tDC\t5\n\tEND\n", newAssemblerState())
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+	path := filepath.Join(dir, "prog.c2o")
+	if err := writeObjectFile(path, obj); err != nil {
+		t.Fatalf("writeObjectFile: %v", err)
+	}
+	patchFile := filepath.Join(dir, "bug.patch")
+	os.WriteFile(patchFile, []byte("; off by one\nMAIN+1=#0004\n"), 0644)
+
+	var out bytes.Buffer
+	buggy := filepath.Join(dir, "buggy.c2o")
+	if status := runPatchCommand([]string{"-p", patchFile, "-o", buggy, path, "A=-1"}, &out); status != 0 {
+		t.Fatalf("Expected the patch to apply, got %d:\n%s", status, out.String())
+	}
+	patched, err := loadObjectFile(buggy)
+	if err != nil {
+		t.Fatalf("loadObjectFile: %v", err)
+	}
+	if patched.Bin[1] != 4 || patched.Bin[3] != 0xffff || patched.Checksum == obj.Checksum {
+		t.Errorf("Expected words 1 and 3 patched with a new checksum, got %v %s", patched.Bin, patched.Checksum)
+	}
+	if !strings.HasPrefix(out.String(), "#0001 <MAIN+1>: #0003(     3) -> #0004(     4)\n") {
+		t.Errorf("Unexpected report:\n%s", out.String())
+	}
+
+	for _, args := range [][]string{
+		{path, "#0010=1"},
+		{path, "NOWHERE=1"},
+		{path, "A"},
+	} {
+		out.Reset()
+		if status := runPatchCommand(args, &out); status != 1 {
+			t.Errorf("Expected %v to fail, got %d:\n%s", args, status, out.String())
+		}
+	}
+
+	// A modified object is not patched
+	data, _ := os.ReadFile(buggy)
+	os.WriteFile(buggy, bytes.Replace(data, []byte(`"sha256:`), []byte(`"sha256:0`), 1), 0644)
+	out.Reset()
+	if status := runPatchCommand([]string{buggy, "A=1"}, &out); status != 1 || !strings.Contains(out.String(), "checksum mismatch") {
+		t.Errorf("Expected a checksum mismatch, got %d:\n%s", status, out.String())
+	}
+}
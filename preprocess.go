@@ -0,0 +1,185 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+)
+
+// SourceLine is one line of the preprocessed source with the file and line
+// it was written at. Lines added by preprocessing, such as included library
+// programs, point at their own text rather than at the line that pulled
+// them in.
+type SourceLine struct {
+	Text string
+	File string
+	Line int
+}
+
+// Conditional assembly directives and EQU definitions seen by the
+// preprocessor
+var (
+	conditionalPattern = regexp.MustCompile(`^\s+(IFDEF|IFEQ|ELSE|ENDIF)(\s+(.*?))?\s*$`)
+	equPattern         = regexp.MustCompile(`^(\S+)\s+EQU\s+(\S+)\s*$`)
+)
+
+// preprocess turns the source of file name into the stream of lines the
+// assembler reads. Every stage that adds, removes or rewrites lines works
+// on this stream and keeps the origin of each line, so diagnostics,
+// listings and source maps point at what the user wrote. Lines are never
+// removed, only blanked, so the position of a line in the stream stays a
+// stable key for the passes. Included files are read from sources.
+func preprocess(name string, source string, sources SourceProvider) ([]SourceLine, error) {
+	return preprocessLines(sourceLines(name, source), sources)
+}
+
+// sourceLines splits the source of file name into lines.
+func sourceLines(name string, source string) []SourceLine {
+	texts := strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n")
+	lines := make([]SourceLine, len(texts))
+	for i, text := range texts {
+		lines[i] = SourceLine{Text: text, File: name, Line: i + 1}
+	}
+	return lines
+}
+
+// preprocessLines runs the preprocessing stages on lines, which may come
+// from several files.
+func preprocessLines(lines []SourceLine, sources SourceProvider) ([]SourceLine, error) {
+	defines, err := parseDefines(*optDefine)
+	if err != nil {
+		return nil, err
+	}
+	if err := assembleConditionals(lines, defines); err != nil {
+		return nil, err
+	}
+	lines, err = includeFiles(lines, defines, sources)
+	if err != nil {
+		return nil, err
+	}
+	return expandIncludes(lines)
+}
+
+// parseDefines reads the -define option: NAME or NAME=VALUE separated by
+// commas. A NAME without a value is defined as 1.
+func parseDefines(spec string) (map[string]string, error) {
+	defines := make(map[string]string)
+	for _, def := range strings.Split(spec, ",") {
+		def = strings.TrimSpace(def)
+		if def == "" {
+			continue
+		}
+		name, value, ok := strings.Cut(def, "=")
+		if !ok {
+			value = "1"
+		}
+		if !isLabel(name) {
+			return nil, fmt.Errorf("[CASL2 ERROR] Invalid -define \"%s\"", def)
+		}
+		defines[name] = value
+	}
+	return defines, nil
+}
+
+// conditional is an IFDEF or IFEQ block being preprocessed.
+type conditional struct {
+	line     SourceLine
+	active   bool
+	taken    bool
+	seenElse bool
+}
+
+// assembleConditionals blanks the lines excluded by IFDEF NAME, IFEQ
+// NAME,VALUE, ELSE and ENDIF, and the directive lines themselves. Blocks
+// may be nested. Names come from -define and from EQU lines included so
+// far; IFEQ compares numbers by value and anything else as text.
+func assembleConditionals(lines []SourceLine, defines map[string]string) error {
+	var stack []conditional
+	active := func() bool {
+		return len(stack) == 0 || stack[len(stack)-1].active
+	}
+	fail := func(l SourceLine, msg string) error {
+		return &AssembleError{File: l.File, Line: l.Line, Msg: msg}
+	}
+
+	for i, l := range lines {
+		code := l.Text
+		if idx := strings.Index(code, ";"); idx >= 0 {
+			code = code[:idx]
+		}
+
+		matches := conditionalPattern.FindStringSubmatch(code)
+		if matches == nil {
+			if !active() {
+				lines[i].Text = ""
+			} else if equ := equPattern.FindStringSubmatch(code); equ != nil {
+				defines[equ[1]] = equ[2]
+			}
+			continue
+		}
+		lines[i].Text = ""
+
+		directive, operands := matches[1], matches[3]
+		switch directive {
+		case "IFDEF", "IFEQ":
+			cond := false
+			if directive == "IFDEF" {
+				if !isLabel(operands) {
+					return fail(l, fmt.Sprintf("Invalid operand \"%s\"", operands))
+				}
+				_, cond = defines[operands]
+			} else {
+				name, value, ok := strings.Cut(operands, ",")
+				name, value = strings.TrimSpace(name), strings.TrimSpace(value)
+				if !ok || !isLabel(name) || value == "" {
+					return fail(l, fmt.Sprintf("Invalid operand \"%s\"", operands))
+				}
+				defined, ok := defines[name]
+				cond = ok && sameValue(defined, value)
+			}
+			stack = append(stack, conditional{line: l, active: active() && cond, taken: cond})
+		case "ELSE", "ENDIF":
+			if operands != "" {
+				return fail(l, fmt.Sprintf("Invalid operand \"%s\"", operands))
+			}
+			if len(stack) == 0 {
+				return fail(l, fmt.Sprintf("%s without IFDEF or IFEQ", directive))
+			}
+			top := &stack[len(stack)-1]
+			if directive == "ENDIF" {
+				stack = stack[:len(stack)-1]
+				continue
+			}
+			if top.seenElse {
+				return fail(l, "ELSE already given for this block")
+			}
+			top.seenElse = true
+			top.active = !top.taken && (len(stack) == 1 || stack[len(stack)-2].active)
+		}
+	}
+	if len(stack) > 0 {
+		return fail(stack[len(stack)-1].line, "No ENDIF for this block")
+	}
+	return nil
+}
+
+// sameValue compares an IFEQ value with a definition: numbers (decimal or
+// #hex) by the word they make, anything else as text.
+func sameValue(a, b string) bool {
+	x, okx := expandNumber(a)
+	y, oky := expandNumber(b)
+	if okx && oky {
+		return x == y
+	}
+	return a == b
+}
+
+// origin returns where the line at position pos (1-based) of the
+// preprocessed source was written. Positions outside the stream are
+// taken as lines of the file being assembled.
+func (asmState *AssemblerState) origin(pos int) SourceLine {
+	if pos < 1 || pos > len(asmState.lines) {
+		return SourceLine{File: asmState.file, Line: pos}
+	}
+	return asmState.lines[pos-1]
+}
@@ -0,0 +1,89 @@
+package main
+
+import (
+	"errors"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestPreprocessOrigins(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,-123
+	CALL	OUTNUM
+	LAD	GR1,0
+	CALL	OUTNUM
+	RET
+	INCLUDE	<STD/OUTNUM>
+	END
+`)
+
+	// RET is the last word of MAIN, RPUSH the first of the library program
+	if loc := c.SourceMap[8]; loc.Line != 6 || strings.HasPrefix(loc.File, "<STD/") {
+		t.Errorf("Unexpected location of RET: %+v", loc)
+	}
+	if got := c.locate(9); got != " (<STD/OUTNUM> line 2: 'RPUSH')" {
+		t.Errorf("Unexpected location of RPUSH: %q", got)
+	}
+
+	_, err := assembleText("prog.cas", "MAIN\tSTART\n\tINCLUDE\t<STD/NOPE>\n\tEND\n", newAssemblerState())
+	var aerr *AssembleError
+	if !errors.As(err, &aerr) || aerr.File != "prog.cas" || aerr.Line != 2 {
+		t.Errorf("Unexpected error %v", err)
+	}
+}
+
+func TestConditionalAssembly(t *testing.T) {
+	src := `MAIN	START
+DEBUG	EQU	1
+LEVEL	EQU	2
+	IFDEF	DEBUG
+	LAD	GR1,1
+	ELSE
+	LAD	GR1,2
+	ENDIF
+	IFEQ	LEVEL,#2
+	LAD	GR2,LEVEL
+	ENDIF
+	IFDEF	TRACE
+	IFEQ	LEVEL,2
+	NOP
+	ENDIF
+	ELSE
+	LAD	GR3,3
+	ENDIF
+	RET
+	END
+`
+	obj := assembleObject(t, src)
+	want := []uint16{0x1210, 1, 0x1220, 2, 0x1230, 3, 0x8100}
+	if !reflect.DeepEqual(obj.Bin, want) {
+		t.Errorf("Unexpected words %v", obj.Bin)
+	}
+	if obj.SourceMap[0].Line != 5 || obj.SourceMap[6].Line != 19 {
+		t.Errorf("Unexpected source map %v", obj.SourceMap)
+	}
+	if diags := NewAnalysis(src).Diagnostics; len(diags) != 0 {
+		t.Errorf("Unexpected diagnostics %v", diags)
+	}
+
+	*optDefine = "TRACE"
+	defer func() { *optDefine = "" }()
+	obj = assembleObject(t, src)
+	want = []uint16{0x1210, 1, 0x1220, 2, 0x0000, 0x8100}
+	if !reflect.DeepEqual(obj.Bin, want) {
+		t.Errorf("Unexpected words with -define TRACE: %v", obj.Bin)
+	}
+
+	for src, line := range map[string]int{
+		"MAIN\tSTART\n\tELSE\n\tEND\n":                     2,
+		"MAIN\tSTART\n\tIFDEF\tX\n\tRET\n\tEND\n":          2,
+		"MAIN\tSTART\n\tIFDEF\tX\n\tELSE\n\tELSE\n\tEND\n": 4,
+	} {
+		_, err := assembleText("prog.cas", src, newAssemblerState())
+		var aerr *AssembleError
+		if !errors.As(err, &aerr) || aerr.Line != line {
+			t.Errorf("%q: expected an error on line %d, got %v", src, line, err)
+		}
+	}
+}
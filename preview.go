@@ -0,0 +1,129 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// OperandPreview is what the instruction at PR is about to work on,
+// decoded before it is executed: its effective address, the memory word
+// there and the registers it uses.
+type OperandPreview struct {
+	PC   int
+	Inst string
+	// Operands are written as in the source, with adr as a label when
+	// one is defined at it: "GR1,TBL,GR2".
+	Operands string
+	// EA is the effective address adr + GR(x), or -1 for instructions
+	// without adr. For POP and RET it is SP, the word they pop.
+	EA int
+	// Mem is the word at EA, for instructions that read or write it.
+	Mem    int
+	HasMem bool
+	// Regs are the registers the instruction uses, in operand order, and
+	// Values what they hold.
+	Regs   []int
+	Values []int
+	// Logical is set for instructions that treat values as unsigned.
+	Logical bool
+}
+
+// Preview decodes the instruction at PR without executing it.
+func (c *Comet2) Preview() OperandPreview {
+	pc := c.State[PC]
+	word := memGet(c.Memory, pc)
+	p := OperandPreview{PC: pc, EA: -1}
+	inst, ok := COMET2TBL[word>>8]
+	if !ok {
+		p.Inst, p.Operands, _ = parse(c.Memory, c.State)
+		return p
+	}
+	p.Inst = inst.ID
+	switch inst.ID {
+	case "ADDL", "SUBL", "MULL", "DIVL", "CPL", "SLL", "SRL":
+		p.Logical = true
+	}
+
+	r, x := (word>>4)&0x7, word&0xf
+	adr := memGet(c.Memory, pc+1)
+	ea := adr
+	if x >= 1 && x <= 7 {
+		ea += c.State[GR0+x]
+	}
+	ea &= 0xffff
+
+	switch inst.Type {
+	case OP1, OP2:
+		var parts []string
+		if inst.Type == OP1 {
+			parts = append(parts, fmt.Sprintf("GR%d", r))
+			p.Regs = append(p.Regs, r)
+		}
+		parts = append(parts, c.previewAddr(adr))
+		if x >= 1 && x <= 7 {
+			parts = append(parts, fmt.Sprintf("GR%d", x))
+			p.Regs = append(p.Regs, x)
+		}
+		p.Operands = strings.Join(parts, ",")
+		p.EA = ea
+		if addressesMemory(inst.ID) && !strings.HasPrefix(inst.ID, "J") && inst.ID != "CALL" {
+			p.Mem, p.HasMem = memGet(c.Memory, ea), true
+		}
+	case OP3:
+		p.Operands = fmt.Sprintf("GR%d", r)
+		p.Regs = append(p.Regs, r)
+		if inst.ID == "POP" {
+			p.EA = c.State[SP]
+			p.Mem, p.HasMem = memGet(c.Memory, p.EA), true
+		}
+	case OP4:
+		if inst.ID == "RET" {
+			p.EA = c.State[SP]
+			p.Mem, p.HasMem = memGet(c.Memory, p.EA), true
+		}
+	case OP5:
+		p.Operands = fmt.Sprintf("GR%d,GR%d", r, word&0x7)
+		p.Regs = append(p.Regs, r, word&0x7)
+	}
+	for _, n := range p.Regs {
+		p.Values = append(p.Values, c.State[GR0+n])
+	}
+	return p
+}
+
+// previewAddr writes adr as the label defined at it, or in hex.
+func (c *Comet2) previewAddr(adr int) string {
+	if label := c.labelAt(adr); label != "" && !strings.Contains(label, "+") {
+		return label
+	}
+	return "#" + hex(adr, 4)
+}
+
+// String renders the preview as "ADDA GR1,TBL,GR2 -> EA=#0105 [=7],
+// GR1=3, GR2=5".
+func (p OperandPreview) String() string {
+	var parts []string
+	name := "EA"
+	if p.Inst == "POP" || p.Inst == "RET" {
+		name = "SP"
+	}
+	if p.EA >= 0 {
+		ea := fmt.Sprintf("%s=#%s", name, hex(p.EA, 4))
+		if p.HasMem {
+			ea += fmt.Sprintf(" [=%d]", explainValue(p.Mem, p.Logical))
+		}
+		parts = append(parts, ea)
+	}
+	for i, n := range p.Regs {
+		if i > 0 && n == p.Regs[0] {
+			// ADDA GR1,GR1 uses GR1 once
+			continue
+		}
+		parts = append(parts, fmt.Sprintf("GR%d=%d", n, explainValue(p.Values[i], p.Logical)))
+	}
+	text := strings.TrimSpace(p.Inst + " " + p.Operands)
+	if len(parts) == 0 {
+		return text
+	}
+	return text + " -> " + strings.Join(parts, ", ")
+}
@@ -0,0 +1,33 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestOperandPreview(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,3
+	LAD	GR2,1
+	ADDA	GR1,TBL,GR2
+	ADDA	GR1,GR2
+	RET
+TBL	DC	5,7
+	END
+`)
+	for _, want := range []string{
+		"LAD GR1,#0003 -> EA=#0003, GR1=0",
+		"LAD GR2,#0001 -> EA=#0001, GR2=0",
+		"ADDA GR1,TBL,GR2 -> EA=#0009 [=7], GR1=3, GR2=1",
+		"ADDA GR1,GR2 -> GR1=10, GR2=1",
+	} {
+		if got := c.Preview().String(); got != want {
+			t.Errorf("Expected preview %q, got %q", want, got)
+		}
+		if r := c.Step(); r.Kind != HALT_RUNNING {
+			t.Fatalf("Unexpected halt %+v", r)
+		}
+	}
+	if c.State[GR1] != 11 {
+		t.Errorf("Expected the preview not to change the machine, GR1 = %d", c.State[GR1])
+	}
+}
@@ -0,0 +1,101 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+// Built-in profiles of -profile: option values by option name. A
+// [profile.NAME] table of c2c2.toml adds a profile or replaces one of
+// these.
+var builtinProfiles = map[string]map[string]string{
+	// exam rejects what a grader would mark wrong and stops runaway
+	// programs.
+	"exam": {
+		"strict-numbers":   "true",
+		"check-calls":      "error",
+		"check-io":         "error",
+		"in-overflow":      "error",
+		"detect-loops":     "true",
+		"max-output-lines": "1000",
+	},
+	// lenient accepts what the original c2c2 accepts, silently.
+	"lenient": {
+		"lenient-numbers": "true",
+		"in-overflow":     "truncate",
+		"out-chars":       "raw",
+	},
+	// research reports everything worth a look without stopping.
+	"research": {
+		"lint":        "true",
+		"check-calls": "warn",
+		"check-io":    "warn",
+		"check-signs": "true",
+		"in-overflow": "warn",
+		"out-chars":   "escape",
+		"stats":       "text",
+	},
+}
+
+// findProfile returns the profile name from the c2c2.toml in dir, if it
+// has one, or else the built-in one.
+func findProfile(name string, dir string) (map[string]string, error) {
+	data, err := os.ReadFile(filepath.Join(dir, PROJECT_FILE))
+	if err != nil && !os.IsNotExist(err) {
+		return nil, fmt.Errorf("[CASL2 ERROR] Cannot read project manifest: %v", err)
+	}
+	if err == nil {
+		p, err := parseManifest(string(data))
+		if err != nil {
+			return nil, err
+		}
+		if profile, ok := p.Profiles[name]; ok {
+			return profile, nil
+		}
+	}
+	if profile, ok := builtinProfiles[name]; ok {
+		return profile, nil
+	}
+	var names []string
+	for n := range builtinProfiles {
+		names = append(names, n)
+	}
+	sort.Strings(names)
+	return nil, fmt.Errorf("[CASL2 ERROR] Unknown profile \"%s\" (%s, or a [profile.%s] table in %s)",
+		name, strings.Join(names, ", "), name, PROJECT_FILE)
+}
+
+// applyProfile sets the options of the profile name on fs, except those
+// given on the command line, which take precedence.
+func applyProfile(fs *flag.FlagSet, name string, dir string) error {
+	profile, err := findProfile(name, dir)
+	if err != nil {
+		return err
+	}
+	given := make(map[string]bool)
+	fs.Visit(func(f *flag.Flag) {
+		given[f.Name] = true
+	})
+
+	var keys []string
+	for key := range profile {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	for _, key := range keys {
+		if key == "profile" || fs.Lookup(key) == nil {
+			return fmt.Errorf("[CASL2 ERROR] Profile \"%s\": unknown option \"%s\"", name, key)
+		}
+		if given[key] {
+			continue
+		}
+		if err := fs.Set(key, profile[key]); err != nil {
+			return fmt.Errorf("[CASL2 ERROR] Profile \"%s\": invalid value \"%s\" of %s: %v", name, profile[key], key, err)
+		}
+	}
+	return nil
+}
@@ -0,0 +1,45 @@
+package main
+
+import (
+	"flag"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestProfiles(t *testing.T) {
+	for name, profile := range builtinProfiles {
+		for key := range profile {
+			if flag.Lookup(key) == nil {
+				t.Errorf("Profile %s sets unknown option %s", name, key)
+			}
+		}
+	}
+
+	fs := flag.NewFlagSet("test", flag.ContinueOnError)
+	strict := fs.Bool("strict-numbers", false, "")
+	calls := fs.String("check-calls", "", "")
+	lines := fs.Int("max-output-lines", 0, "")
+	fs.Parse([]string{"-check-calls", "warn"})
+
+	dir := t.TempDir()
+	os.WriteFile(filepath.Join(dir, PROJECT_FILE), []byte("[profile.course] # no [program]\nstrict-numbers = true\ncheck-calls = \"error\"\nmax-output-lines = 200\n"), 0644)
+	if err := applyProfile(fs, "course", dir); err != nil {
+		t.Fatal(err)
+	}
+	if !*strict || *calls != "warn" || *lines != 200 {
+		t.Errorf("Expected the profile under the command line, got %v %q %d", *strict, *calls, *lines)
+	}
+
+	if err := applyProfile(fs, "exam", dir); err == nil || !strings.Contains(err.Error(), "unknown option") {
+		t.Errorf("Expected the options of exam to be missing from the flag set, got %v", err)
+	}
+	if err := applyProfile(fs, "nosuch", dir); err == nil || !strings.Contains(err.Error(), "exam, lenient, research") {
+		t.Errorf("Expected an unknown profile error, got %v", err)
+	}
+	os.WriteFile(filepath.Join(dir, PROJECT_FILE), []byte("[profile.course]\nmax-output-lines = many\n"), 0644)
+	if err := applyProfile(fs, "course", dir); err == nil {
+		t.Errorf("Expected an invalid value to fail")
+	}
+}
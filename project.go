@@ -0,0 +1,182 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"strconv"
+	"strings"
+)
+
+// Project manifest read by "c2c2 run" and "c2c2 test" from the current
+// directory
+const PROJECT_FILE = "c2c2.toml"
+
+// Project is the [program] section of a project manifest. Paths are
+// written relative to the manifest; loadProject makes them relative to the
+// current directory.
+type Project struct {
+	// Sources are assembled as one program in this order.
+	Sources []string
+	// Entry is the START unit to run, or "" for the first one.
+	Entry string
+	// Inputs are the IN lines of "c2c2 run" without inputs.
+	Inputs []string
+	// Tests are the test specs run by "c2c2 test".
+	Tests []string
+	// Profiles are the [profile.NAME] tables for -profile: option values
+	// by option name.
+	Profiles map[string]map[string]string
+}
+
+// parseProject reads a project manifest in a small subset of TOML: a
+// [program] table with string or string array values on one line.
+//
+//	[program]
+//	sources = ["main.cas", "sub.cas"]
+//	entry = "MAIN"
+//	inputs = ["3", "5"]
+//	tests = ["tests.json"]
+//
+// Profiles of -profile may follow in [profile.NAME] tables.
+func parseProject(text string) (*Project, error) {
+	p, err := parseManifest(text)
+	if err != nil {
+		return nil, err
+	}
+	if len(p.Sources) == 0 {
+		return nil, fmt.Errorf("[CASL2 ERROR] %s lists no sources", PROJECT_FILE)
+	}
+	return p, nil
+}
+
+// parseManifest reads a manifest that may have no [program] table, as
+// one giving only profiles.
+func parseManifest(text string) (*Project, error) {
+	p := &Project{Profiles: make(map[string]map[string]string)}
+	section := ""
+	for n, line := range strings.Split(text, "\n") {
+		line = strings.TrimSpace(stripTomlComment(line))
+		if line == "" {
+			continue
+		}
+		if strings.HasPrefix(line, "[") && strings.HasSuffix(line, "]") {
+			section = strings.TrimSpace(line[1 : len(line)-1])
+			if name, ok := strings.CutPrefix(section, "profile."); ok && name != "" {
+				p.Profiles[name] = make(map[string]string)
+			} else if section != "program" {
+				return nil, fmt.Errorf("[CASL2 ERROR] %s line %d: unknown table [%s]", PROJECT_FILE, n+1, section)
+			}
+			continue
+		}
+		if section == "" {
+			return nil, fmt.Errorf("[CASL2 ERROR] %s line %d: expected [program]", PROJECT_FILE, n+1)
+		}
+
+		key, value, ok := strings.Cut(line, "=")
+		if !ok {
+			return nil, fmt.Errorf("[CASL2 ERROR] %s line %d: expected KEY = VALUE", PROJECT_FILE, n+1)
+		}
+		key, value = strings.TrimSpace(key), strings.TrimSpace(value)
+		if name, ok := strings.CutPrefix(section, "profile."); ok {
+			// Strings are quoted; booleans and numbers are not
+			if unquoted, err := strconv.Unquote(value); err == nil {
+				value = unquoted
+			}
+			p.Profiles[name][key] = value
+			continue
+		}
+		var err error
+		switch key {
+		case "sources":
+			p.Sources, err = parseTomlStrings(value)
+		case "inputs":
+			p.Inputs, err = parseTomlStrings(value)
+		case "tests":
+			p.Tests, err = parseTomlStrings(value)
+		case "entry":
+			p.Entry, err = strconv.Unquote(value)
+		default:
+			return nil, fmt.Errorf("[CASL2 ERROR] %s line %d: unknown key \"%s\"", PROJECT_FILE, n+1, key)
+		}
+		if err != nil {
+			return nil, fmt.Errorf("[CASL2 ERROR] %s line %d: invalid value of \"%s\"", PROJECT_FILE, n+1, key)
+		}
+	}
+	return p, nil
+}
+
+// stripTomlComment removes a # comment that is not inside a string.
+func stripTomlComment(line string) string {
+	quoted := false
+	for i := 0; i < len(line); i++ {
+		switch {
+		case line[i] == '\\' && quoted:
+			i++
+		case line[i] == '"':
+			quoted = !quoted
+		case line[i] == '#' && !quoted:
+			return line[:i]
+		}
+	}
+	return line
+}
+
+// parseTomlStrings reads an array of strings, ["a", "b"].
+func parseTomlStrings(value string) ([]string, error) {
+	if !strings.HasPrefix(value, "[") || !strings.HasSuffix(value, "]") {
+		return nil, fmt.Errorf("not an array")
+	}
+	rest := strings.TrimSpace(value[1 : len(value)-1])
+	var items []string
+	for rest != "" {
+		s, err := strconv.QuotedPrefix(rest)
+		if err != nil {
+			return nil, err
+		}
+		item, _ := strconv.Unquote(s)
+		items = append(items, item)
+		rest = strings.TrimSpace(rest[len(s):])
+		if rest != "" {
+			if rest[0] != ',' {
+				return nil, fmt.Errorf("expected a comma")
+			}
+			rest = strings.TrimSpace(rest[1:])
+		}
+	}
+	return items, nil
+}
+
+// loadProject reads the manifest in dir and makes its paths relative to
+// the current directory.
+func loadProject(dir string) (*Project, error) {
+	data, err := os.ReadFile(filepath.Join(dir, PROJECT_FILE))
+	if err != nil {
+		return nil, fmt.Errorf("[CASL2 ERROR] Cannot read project manifest: %v", err)
+	}
+	p, err := parseProject(string(data))
+	if err != nil {
+		return nil, err
+	}
+	for i := range p.Sources {
+		p.Sources[i] = filepath.Join(dir, p.Sources[i])
+	}
+	for i := range p.Tests {
+		p.Tests[i] = filepath.Join(dir, p.Tests[i])
+	}
+	return p, nil
+}
+
+// assembleProject assembles the sources of p and selects its entry unit.
+func assembleProject(p *Project, asmState *AssemblerState) (*Object, error) {
+	obj, err := assembleFiles(p.Sources, asmState)
+	if err != nil {
+		return nil, err
+	}
+	if p.Entry != "" {
+		if err := selectEntry(obj, p.Entry); err != nil {
+			return nil, err
+		}
+	}
+	return obj, nil
+}
@@ -0,0 +1,61 @@
+package main
+
+import (
+	"bytes"
+	"errors"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestProject(t *testing.T) {
+	dir := t.TempDir()
+	files := [][2]string{
+		{"c2c2.toml", "# homework 3\n[program]\nsources = [\"main.cas\", \"sub.cas\"]\nentry = \"MAIN\"\ninputs = [\"#1\"]\ntests = [\"tests.json\"]\n"},
+		{"main.cas", "MAIN\tSTART\n\tCALL\tSUB\n\tOUT\tMSG,LEN\n\tRET\nMSG\tDC\t'ok'\nLEN\tDC\t2\n\tEND\n"},
+		{"sub.cas", "SUB\tSTART\n\tLAD\tGR0,7\n\tRET\n\tEND\n"},
+		{"tests.json", `{"main.cas": []}`},
+		{"main.cas.out", "OUT> ok\nProgram finished (RET)\n"},
+	}
+	for _, f := range files {
+		if err := os.WriteFile(filepath.Join(dir, f[0]), []byte(f[1]), 0644); err != nil {
+			t.Fatalf("Failed to write %s: %v", f[0], err)
+		}
+	}
+	t.Chdir(dir)
+
+	p, err := loadProject(".")
+	if err != nil {
+		t.Fatalf("loadProject failed: %v", err)
+	}
+	expected := &Project{Sources: []string{"main.cas", "sub.cas"}, Entry: "MAIN", Inputs: []string{"#1"}, Tests: []string{"tests.json"}}
+	if !reflect.DeepEqual(p, expected) {
+		t.Errorf("Expected %+v, got %+v", expected, p)
+	}
+	obj, err := assembleProject(p, newAssemblerState())
+	if err != nil {
+		t.Fatalf("assembleProject failed: %v", err)
+	}
+	c := LoadObject(obj)
+	c.Output = nil
+	c.Run()
+	if c.State[GR0] != 7 || len(c.Transcript) != 1 {
+		t.Errorf("Expected SUB to be linked, got GR0 %d and %+v", c.State[GR0], c.Transcript)
+	}
+
+	var out bytes.Buffer
+	if status := runTestCommand(nil, &out); status != 0 || !strings.Contains(out.String(), "1 passed, 0 failed") {
+		t.Errorf("Expected the project tests to pass, got %d:\n%s", status, out.String())
+	}
+
+	os.WriteFile("sub.cas", []byte("SUB\tSTART\n\tLAD\tGR9,7\n\tEND\n"), 0644)
+	var aerr *AssembleError
+	if _, err := assembleProject(p, newAssemblerState()); !errors.As(err, &aerr) || aerr.File != "sub.cas" || aerr.Line != 2 {
+		t.Errorf("Expected an error at sub.cas line 2, got %v", err)
+	}
+	if _, err := parseProject("[build]\n"); err == nil {
+		t.Errorf("Expected an unknown table to fail")
+	}
+}
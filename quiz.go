@@ -0,0 +1,127 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"io"
+	"math/rand"
+	"strings"
+)
+
+// Maximum number of instructions skipped between quiz questions
+const QUIZ_MAX_SKIP = 4
+
+// quizStep executes one instruction, feeding IN from c.Input.
+func quizStep(c *Comet2) error {
+	if c.InputMode == INPUT_MODE_IN {
+		line, ok := c.Input.ReadLine()
+		if !ok {
+			return runtimeError(RUNTIME_FINISHED, c.State[PC], "Program finished (input exhausted)")
+		}
+		c.execIn(line)
+		c.InputMode = INPUT_MODE_CMD
+		return nil
+	}
+	_, err := c.stepExec()
+	return err
+}
+
+// quizTarget returns the name of the location the next instruction
+// writes, and a function reading its value.
+func quizTarget(c *Comet2) (string, func() int) {
+	inst, _, _ := parse(c.Memory, c.State)
+	word := memGet(c.Memory, c.State[PC])
+	gr := (word >> 4) & 0x7
+	xr := word & 0x7
+
+	switch inst {
+	case "LD", "LAD", "ADDA", "SUBA", "ADDL", "SUBL", "MULA", "MULL", "DIVA", "DIVL",
+		"AND", "OR", "XOR", "SLA", "SRA", "SLL", "SRL", "POP":
+		return fmt.Sprintf("GR%d", gr), func() int { return c.State[GR0+gr] }
+	case "ST":
+		eadr := memGet(c.Memory, c.State[PC]+1)
+		if xr > 0 {
+			eadr += c.State[GR0+xr]
+		}
+		eadr &= 0xffff
+		return fmt.Sprintf("mem[#%s]", hex(eadr, 4)), func() int { return memGet(c.Memory, eadr) }
+	case "CPA", "CPL":
+		return "FR", func() int { return c.State[FR] }
+	}
+	return "PR", func() int { return c.State[PC] }
+}
+
+// quizAsk asks on w for the value the next instruction writes, reads the
+// answer from scanner, then executes the instruction and tells whether the
+// prediction was right. ok is false when the user stopped instead of
+// answering.
+func quizAsk(c *Comet2, scanner *bufio.Scanner, w io.Writer) (right bool, ok bool, err error) {
+	inst, opr, _ := parse(c.Memory, c.State)
+	name, value := quizTarget(c)
+	fmt.Fprintf(w, "%s %s\t%s\t%s? ", colorYellow("Q"), inst, opr, name)
+	if !scanner.Scan() {
+		return false, false, nil
+	}
+	answer := strings.TrimSpace(scanner.Text())
+	if answer == "q" || answer == "quit" {
+		return false, false, nil
+	}
+
+	err = quizStep(c)
+	actual := value()
+	if guess, ok := expandNumber(answer); ok && guess == actual&0xffff {
+		fmt.Fprintln(w, colorGreen(fmt.Sprintf("Correct! %s = #%s (%d)", name, hex(actual, 4), signed(actual))))
+		return true, true, err
+	}
+	fmt.Fprintln(w, colorRed(fmt.Sprintf("Wrong. %s = #%s (%d)", name, hex(actual, 4), signed(actual))))
+	return false, true, err
+}
+
+// runQuiz steps through the program and asks the user to predict the
+// result of randomly chosen instructions. The questions are written to w
+// and the answers read from r. It returns the score.
+func runQuiz(c *Comet2, inputs []string, r io.Reader, w io.Writer) (correct int, asked int) {
+	c.Input = NewInputLines(inputs)
+	scanner := bufio.NewScanner(r)
+
+	fmt.Fprintln(w, "Predict the value after the shown instruction is executed.")
+	fmt.Fprintln(w, "Answer in decimal or #hex. Enter \"q\" to stop.")
+
+	for {
+		// Skip a few instructions silently
+		var err error
+		for n := rand.Intn(QUIZ_MAX_SKIP + 1); n > 0 && err == nil; n-- {
+			err = quizStep(c)
+		}
+		if err != nil {
+			fmt.Fprintln(w, colorWhiteGreen(err.Error()))
+			break
+		}
+
+		inst, _, _ := parse(c.Memory, c.State)
+		if inst == "SVC" || c.InputMode == INPUT_MODE_IN {
+			if err := quizStep(c); err != nil {
+				fmt.Fprintln(w, colorWhiteGreen(err.Error()))
+				break
+			}
+			continue
+		}
+
+		cmdPrint(c, []string{})
+		right, ok, err := quizAsk(c, scanner, w)
+		if !ok {
+			break
+		}
+		asked++
+		if right {
+			correct++
+		}
+		if err != nil {
+			fmt.Fprintln(w, colorWhiteGreen(err.Error()))
+			break
+		}
+	}
+
+	fmt.Fprintf(w, "Score: %d / %d\n", correct, asked)
+	return correct, asked
+}
@@ -0,0 +1,27 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestQuiz(t *testing.T) {
+	// Every instruction loads 5, so the questions do not depend on how
+	// many instructions are skipped between them
+	src := "MAIN\tSTART\n" + strings.Repeat("\tLAD\tGR1,5\n", 20) + "\tRET\n\tEND\n"
+	c := assembleSource(t, src)
+
+	var out strings.Builder
+	var correct, asked int
+	captureStdout(t, func() {
+		correct, asked = runQuiz(c, nil, strings.NewReader("5\n#0006\nq\n"), &out)
+	})
+	if correct != 1 || asked != 2 {
+		t.Errorf("Expected a score of 1 / 2, got %d / %d", correct, asked)
+	}
+	for _, want := range []string{"LAD\tGR1,   #0005\tGR1? ", "Correct! GR1 = #0005 (5)", "Wrong. GR1 = #0005 (5)", "Score: 1 / 2\n"} {
+		if !strings.Contains(out.String(), want) {
+			t.Errorf("Expected %q in the session, got %q", want, out.String())
+		}
+	}
+}
@@ -0,0 +1,14 @@
+//go:build !linux
+
+package main
+
+import (
+	"errors"
+	"os"
+)
+
+// enableRawMode is only implemented on Linux; elsewhere the monitor reads
+// plain lines.
+func enableRawMode(f *os.File) (func(), error) {
+	return nil, errors.New("raw mode is not supported on this platform")
+}
@@ -0,0 +1,96 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"strings"
+)
+
+// writeRefs prints the definition of the symbol name in the source at
+// path and the lines referring to it, as "FILE:LINE:COLUMN: TEXT" with
+// context lines around each as "FILE-LINE- TEXT".
+func writeRefs(w io.Writer, path string, source string, name string, context int) error {
+	texts := strings.Split(strings.ReplaceAll(source, "\r\n", "\n"), "\n")
+	lines, err := readRenameLines(texts)
+	if err != nil {
+		return err
+	}
+	symbols := resolveRenameLines(lines, func(line int, tok Token) string {
+		return foldCase(tok.Value)
+	})
+	target, err := renameTarget(symbols, name)
+	if err != nil {
+		return err
+	}
+	sites := symbolSites(lines, symbols, target)
+
+	// One entry per line, the first token of each giving the column
+	var definitions, references []symbolSite
+	for i, site := range sites {
+		if i > 0 && sites[i-1].line == site.line {
+			continue
+		}
+		if site.definition {
+			definitions = append(definitions, site)
+		} else {
+			references = append(references, site)
+		}
+	}
+
+	write := func(group []symbolSite) {
+		last := -1
+		for _, site := range group {
+			first := max(site.line-context, last+1, 0)
+			if last >= 0 && first > last+1 {
+				fmt.Fprintln(w, "--")
+			}
+			for i := first; i < site.line; i++ {
+				fmt.Fprintf(w, "%s-%d- %s\n", path, i+1, texts[i])
+			}
+			fmt.Fprintf(w, "%s:%d:%d: %s\n", path, site.line+1, site.tok.Column, texts[site.line])
+			last = site.line
+			for i := site.line + 1; i <= min(site.line+context, len(texts)-1); i++ {
+				fmt.Fprintf(w, "%s-%d- %s\n", path, i+1, texts[i])
+				last = i
+			}
+		}
+	}
+	fmt.Fprintf(w, "Definition of %s:\n", target)
+	write(definitions)
+	fmt.Fprintf(w, "%d references:\n", len(references))
+	write(references)
+	return nil
+}
+
+// runRefsCommand implements "c2c2 refs FILE.cas LABEL": where a label or
+// program is defined and where it is used. It returns the exit status.
+func runRefsCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("refs", flag.ContinueOnError)
+	fs.SetOutput(w)
+	context := fs.Int("C", 0, "number of lines printed before and after each line")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 refs [-C N] FILE.cas LABEL\n\nLABEL is a label, a program (START label) or PROGRAM:LABEL.\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 2 {
+		fs.Usage()
+		return 2
+	}
+
+	path := fs.Arg(0)
+	source, err := os.ReadFile(path)
+	if err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] Cannot read file: %v\n", err)
+		return 1
+	}
+	if err := writeRefs(w, path, string(source), fs.Arg(1), max(*context, 0)); err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	return 0
+}
@@ -0,0 +1,51 @@
+package main
+
+import (
+	"bytes"
+	"testing"
+)
+
+func TestRefs(t *testing.T) {
+	source := `MAIN	START
+LOOP	LD	GR1,LIMIT
+	CALL	SUB
+	JMI	LOOP
+	RET
+LIMIT	DC	3
+	END
+SUB	START
+	RET
+	END
+`
+	var out bytes.Buffer
+	if err := writeRefs(&out, "hw.cas", source, "LIMIT", 0); err != nil {
+		t.Fatalf("Expected refs to succeed, got %v", err)
+	}
+	expected := "Definition of MAIN:LIMIT:\n" +
+		"hw.cas:6:1: LIMIT\tDC\t3\n" +
+		"1 references:\n" +
+		"hw.cas:2:13: LOOP\tLD\tGR1,LIMIT\n"
+	if out.String() != expected {
+		t.Errorf("Expected\n%s\ngot\n%s", expected, out.String())
+	}
+
+	out.Reset()
+	if err := writeRefs(&out, "hw.cas", source, "SUB", 1); err != nil {
+		t.Fatalf("Expected refs to succeed, got %v", err)
+	}
+	expected = "Definition of SUB:\n" +
+		"hw.cas-7- \tEND\n" +
+		"hw.cas:8:1: SUB\tSTART\n" +
+		"hw.cas-9- \tRET\n" +
+		"1 references:\n" +
+		"hw.cas-2- LOOP\tLD\tGR1,LIMIT\n" +
+		"hw.cas:3:7: \tCALL\tSUB\n" +
+		"hw.cas-4- \tJMI\tLOOP\n"
+	if out.String() != expected {
+		t.Errorf("Expected\n%s\ngot\n%s", expected, out.String())
+	}
+
+	if err := writeRefs(&out, "hw.cas", source, "NOWHERE", 0); err == nil {
+		t.Errorf("Expected an undefined label to be an error")
+	}
+}
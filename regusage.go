@@ -0,0 +1,166 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// regFrame is a subroutine call being followed by regUsage. read and
+// written are register bit sets: the registers the subroutine read before
+// writing them, and the ones it wrote.
+type regFrame struct {
+	target  int
+	entry   [8]int
+	read    uint8
+	written uint8
+}
+
+// subroutineRegs is what regUsage found about one subroutine over all its
+// calls. clobberedAt holds, per register, 1 + the address of the first RET
+// that returned with the register changed, or 0.
+type subroutineRegs struct {
+	calls       int
+	reads       uint8
+	writes      uint8
+	clobbers    uint8
+	clobberedAt [8]int
+}
+
+// regUsage records for -reg-usage which registers each subroutine reads,
+// writes and returns changed, so that a missing PUSH/POP or RPUSH/RPOP
+// shows up. Instructions of nested calls count for the callee only, but
+// a register a callee clobbers is clobbered by its callers too.
+type regUsage struct {
+	BaseObserver
+	inst   string
+	frames []regFrame
+	subs   map[int]*subroutineRegs
+}
+
+func newRegUsage() *regUsage {
+	return &regUsage{subs: make(map[int]*subroutineRegs)}
+}
+
+// instRegisters returns the registers the instruction word reads and
+// writes as bit sets. PUSH 0,GRx saves a register rather than using it,
+// so it reads nothing.
+func instRegisters(inst Comet2Instruction, word int, adr int) (uint8, uint8) {
+	var reads, writes uint8
+	r, x := (word>>4)&0x7, word&0xf
+	if (inst.Type == OP1 || inst.Type == OP2) && x >= 1 && x <= 7 && !(inst.ID == "PUSH" && adr == 0) {
+		reads |= 1 << x
+	}
+	switch inst.Type {
+	case OP1, OP5:
+		if inst.Type == OP5 {
+			reads |= 1 << (word & 0x7)
+		}
+		switch inst.ID {
+		case "LD", "LAD":
+			writes |= 1 << r
+		case "ST", "CPA", "CPL":
+			reads |= 1 << r
+		default:
+			reads |= 1 << r
+			writes |= 1 << r
+		}
+	case OP2:
+		if inst.ID == "SVC" && (adr == SYS_IN || adr == SYS_OUT) {
+			reads |= 1<<1 | 1<<2
+		}
+	case OP3:
+		writes |= 1 << r
+	}
+	return reads, writes
+}
+
+func (u *regUsage) BeforeStep(c *Comet2, pc int) {
+	word := memGet(c.Memory, pc)
+	inst, ok := COMET2TBL[word>>8]
+	if !ok {
+		u.inst = ""
+		return
+	}
+	u.inst = inst.ID
+	if len(u.frames) == 0 {
+		return
+	}
+	f := &u.frames[len(u.frames)-1]
+	reads, writes := instRegisters(inst, word, memGet(c.Memory, pc+1))
+	f.read |= reads &^ f.written
+	f.written |= writes
+
+	if inst.ID == "RET" {
+		sub := u.subs[f.target]
+		sub.reads |= f.read
+		sub.writes |= f.written
+		for i := 0; i < 8; i++ {
+			if c.State[GR0+i] != f.entry[i] {
+				sub.clobbers |= 1 << i
+				if sub.clobberedAt[i] == 0 {
+					sub.clobberedAt[i] = pc + 1
+				}
+			}
+		}
+		u.frames = u.frames[:len(u.frames)-1]
+	}
+}
+
+func (u *regUsage) AfterStep(c *Comet2, pc int) {
+	if u.inst != "CALL" {
+		return
+	}
+	f := regFrame{target: c.State[PC]}
+	copy(f.entry[:], c.State[GR0:GR7+1])
+	u.frames = append(u.frames, f)
+	sub, ok := u.subs[f.target]
+	if !ok {
+		sub = &subroutineRegs{}
+		u.subs[f.target] = sub
+	}
+	sub.calls++
+}
+
+// regNames lists the registers of a bit set, e.g. "GR1 GR3", or "-".
+func regNames(set uint8) string {
+	var names []string
+	for i := 0; i < 8; i++ {
+		if set&(1<<i) != 0 {
+			names = append(names, fmt.Sprintf("GR%d", i))
+		}
+	}
+	if len(names) == 0 {
+		return "-"
+	}
+	return strings.Join(names, " ")
+}
+
+// render lists the subroutines by address with their registers, followed
+// by the registers each one returned changed.
+func (u *regUsage) render(c *Comet2) string {
+	var targets []int
+	for target := range u.subs {
+		targets = append(targets, target)
+	}
+	sort.Ints(targets)
+
+	var b strings.Builder
+	b.WriteString("Register usage per subroutine:\n")
+	if len(targets) == 0 {
+		b.WriteString("  (no CALL executed)\n")
+	}
+	for _, target := range targets {
+		sub := u.subs[target]
+		fmt.Fprintf(&b, "  %s: %d calls, reads %s, writes %s, restores %s, clobbers %s\n",
+			c.formatAddr(target), sub.calls, regNames(sub.reads), regNames(sub.writes),
+			regNames(sub.writes&^sub.clobbers), regNames(sub.clobbers))
+		for i := 0; i < 8; i++ {
+			if at := sub.clobberedAt[i] - 1; at >= 0 {
+				fmt.Fprintf(&b, "    GR%d is changed at RET %s; save it with PUSH/POP or RPUSH/RPOP unless it holds a result\n",
+					i, c.formatAddr(at))
+			}
+		}
+	}
+	return b.String()
+}
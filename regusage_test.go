@@ -0,0 +1,38 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestRegUsage(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,3
+	CALL	GOOD
+	CALL	BAD
+	CALL	GOOD
+	RET
+GOOD	RPUSH
+	LD	GR0,GR1
+	LAD	GR2,1
+	RPOP
+	RET
+BAD	ADDA	GR1,GR1
+	LAD	GR3,5
+	RET
+	END
+`)
+	u := newRegUsage()
+	c := NewComet2Builder().WithObject(obj).WithObserver(u).Build()
+	c.Run()
+	text := u.render(c)
+	for _, want := range []string{
+		"  #0009 <GOOD>: 2 calls, reads GR1, writes GR0 GR1 GR2 GR3 GR4 GR5 GR6 GR7, restores GR1 GR2 GR3 GR4 GR5 GR6 GR7, clobbers GR0\n",
+		"  #0022 <BAD>: 1 calls, reads GR1, writes GR1 GR3, restores -, clobbers GR1 GR3\n",
+		"    GR3 is changed at RET #0025 <BAD+3>;",
+	} {
+		if !strings.Contains(text, want) {
+			t.Errorf("Expected %q in:\n%s", want, text)
+		}
+	}
+}
@@ -0,0 +1,168 @@
+package main
+
+import (
+	"fmt"
+	"slices"
+	"sort"
+	"strconv"
+	"strings"
+)
+
+// relocatable reports whether the word of a memory entry is an address in
+// the program: a label other than an EQU, or a literal.
+func relocatable(asmState *AssemblerState, val interface{}) bool {
+	v, ok := val.(string)
+	if !ok || strings.HasPrefix(v, "#") {
+		return false
+	}
+	if _, err := strconv.ParseInt(v, 10, 64); err == nil {
+		return false
+	}
+	return !asmState.equates[v] && !asmState.equates[strings.TrimPrefix(v, "CALL_")]
+}
+
+// parseUnitBases reads "NAME=ADDRESS,..." for -unit-base.
+func parseUnitBases(spec string) (map[string]int, error) {
+	bases := make(map[string]int)
+	for _, item := range strings.Split(spec, ",") {
+		item = strings.TrimSpace(item)
+		if item == "" {
+			continue
+		}
+		name, addrText, ok := strings.Cut(item, "=")
+		addr, valid := expandNumber(strings.TrimSpace(addrText))
+		if !ok || !valid {
+			return nil, fmt.Errorf("[CASL2 ERROR] Invalid unit base \"%s\" (expected NAME=ADDRESS)", item)
+		}
+		bases[foldCase(strings.TrimSpace(name))] = addr
+	}
+	return bases, nil
+}
+
+// unitPlacement is where relocation puts each program unit.
+type unitPlacement struct {
+	units  []UnitSize
+	starts []int
+	// size is the end of the last unit, the length of the image.
+	size int
+}
+
+// placeUnits loads units from base on, one after another, except those
+// given their own address in unitBases, and checks that they neither
+// overlap nor run past the end of memory.
+func placeUnits(units []UnitSize, base int, unitBases map[string]int) (*unitPlacement, error) {
+	if len(units) == 0 {
+		return nil, fmt.Errorf("[CASL2 ERROR] No program units to relocate; assemble the object again")
+	}
+	names := make([]string, len(units))
+	for i, u := range units {
+		names[i] = u.Name
+	}
+	for name := range unitBases {
+		if !slices.Contains(names, name) {
+			return nil, fmt.Errorf("[CASL2 ERROR] No START unit \"%s\" (units: %s)", name, strings.Join(names, ", "))
+		}
+	}
+
+	p := &unitPlacement{units: units, starts: make([]int, len(units))}
+	next := base
+	for i, u := range units {
+		if b, ok := unitBases[u.Name]; ok {
+			next = b
+		}
+		p.starts[i] = next
+		next += u.Total()
+		p.size = max(p.size, next)
+	}
+	if p.size > ADDRESS_SPACE {
+		return nil, fmt.Errorf("[CASL2 ERROR] The relocated program ends at #%s, past the end of memory", hex(p.size-1, 4))
+	}
+	order := make([]int, len(units))
+	for i := range order {
+		order[i] = i
+	}
+	sort.Slice(order, func(a, b int) bool {
+		return p.starts[order[a]] < p.starts[order[b]]
+	})
+	for k := 1; k < len(order); k++ {
+		prev, cur := order[k-1], order[k]
+		if end := p.starts[prev] + units[prev].Total(); end > p.starts[cur] {
+			return nil, fmt.Errorf("[CASL2 ERROR] Units %s (#%s-#%s) and %s (#%s) overlap", units[prev].Name,
+				hex(p.starts[prev], 4), hex(end-1, 4), units[cur].Name, hex(p.starts[cur], 4))
+		}
+	}
+	return p, nil
+}
+
+// move maps an address of the program as assembled to the relocated one,
+// by the unit holding it; an address past a unit, such as a label on its
+// END, moves with the last unit starting before it.
+func (p *unitPlacement) move(addr int) int {
+	unit := 0
+	for i, u := range p.units {
+		if u.Start <= addr {
+			unit = i
+		}
+	}
+	return (addr - p.units[unit].Start + p.starts[unit]) & 0xffff
+}
+
+// relocateObject returns obj placed as p says. The words listed in Relocs
+// and the addresses of the symbols, units, source map and regions move
+// with their unit; words between the units are zero.
+func relocateObject(obj *Object, p *unitPlacement) *Object {
+	move, size := p.move, p.size
+	moved := &Object{
+		Version:   obj.Version,
+		Source:    obj.Source,
+		Start:     move(obj.Start),
+		Entry:     obj.Entry,
+		Bin:       make([]uint16, size),
+		Symbols:   make(map[string]int),
+		SourceMap: make([]SourceLocation, size),
+		Build:     obj.Build,
+		Absolute:  obj.Absolute,
+	}
+	code := make([]bool, size)
+	for addr, word := range obj.Bin {
+		moved.Bin[move(addr)] = word
+		code[move(addr)] = isCode(obj.Regions, addr)
+		if addr < len(obj.SourceMap) {
+			moved.SourceMap[move(addr)] = obj.SourceMap[addr]
+		}
+	}
+	for _, addr := range obj.Relocs {
+		moved.Bin[move(addr)] = uint16(move(int(obj.Bin[addr])))
+		moved.Relocs = append(moved.Relocs, move(addr))
+	}
+	sort.Ints(moved.Relocs)
+	for addr := range moved.Bin {
+		if n := len(moved.Regions); n > 0 && moved.Regions[n-1].Code == code[addr] {
+			moved.Regions[n-1].End = addr + 1
+		} else {
+			moved.Regions = append(moved.Regions, Region{Start: addr, End: addr + 1, Code: code[addr]})
+		}
+	}
+
+	for name, addr := range obj.Symbols {
+		if slices.Contains(obj.Absolute, name) {
+			moved.Symbols[name] = addr
+		} else {
+			moved.Symbols[name] = move(addr)
+		}
+	}
+	for i, u := range obj.Units {
+		u.Start, u.Entry = p.starts[i], move(u.Entry)
+		moved.Units = append(moved.Units, u)
+	}
+	for _, t := range obj.Traces {
+		moved.Traces = append(moved.Traces, TracePoint{Addr: move(t.Addr), On: t.On})
+	}
+	for _, r := range obj.Literals {
+		moved.Literals = append(moved.Literals, Region{Start: move(r.Start), End: move(r.Start) + r.End - r.Start})
+	}
+	for _, b := range obj.Bounds {
+		moved.Bounds = append(moved.Bounds, BoundedRegion{Label: b.Label, Start: move(b.Start), End: move(b.Start) + b.End - b.Start})
+	}
+	return moved
+}
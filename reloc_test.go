@@ -0,0 +1,52 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestRelocation(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,VAL
+	CALL	SUB
+	RET
+VAL	DC	SIZE
+SIZE	EQU	5
+	END
+SUB	START
+	LAD	GR1,1,GR1
+	RET
+	END
+`)
+	placement, err := placeUnits(obj.Units, 0x1000, map[string]int{"SUB": 0x2000})
+	if err != nil {
+		t.Fatal(err)
+	}
+	moved := relocateObject(obj, placement)
+	if len(moved.Bin) != 0x2003 || moved.Start != 0x1000 {
+		t.Fatalf("Expected the image to end at #2002 and start at #1000, got %d words from #%s", len(moved.Bin), hex(moved.Start, 4))
+	}
+	// Addresses move with their unit; the EQU value does not
+	if moved.Bin[0x1001] != 0x1005 || moved.Bin[0x1003] != 0x2000 || moved.Bin[0x1005] != 5 {
+		t.Errorf("Unexpected relocated words #%s #%s #%s", hex(int(moved.Bin[0x1001]), 4), hex(int(moved.Bin[0x1003]), 4), hex(int(moved.Bin[0x1005]), 4))
+	}
+	if moved.Symbols["MAIN:VAL"] != 0x1005 || moved.Symbols["MAIN:SIZE"] != 5 || moved.Units[1].Start != 0x2000 {
+		t.Errorf("Unexpected symbols %v and units %v", moved.Symbols, moved.Units)
+	}
+
+	c := LoadObject(moved)
+	c.Output = func(string) {}
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected the relocated program to finish, got %v", err)
+	}
+	if c.State[GR1] != 6 {
+		t.Errorf("Expected GR1 = 6, got %d", c.State[GR1])
+	}
+
+	if _, err := placeUnits(obj.Units, 0, map[string]int{"SUB": 3}); err == nil || !strings.Contains(err.Error(), "overlap") {
+		t.Errorf("Expected overlapping units to be refused, got %v", err)
+	}
+	if _, err := placeUnits(obj.Units, 0xfffe, nil); err == nil {
+		t.Errorf("Expected a program past the end of memory to be refused")
+	}
+}
@@ -0,0 +1,390 @@
+package main
+
+import (
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"slices"
+	"sort"
+	"strings"
+)
+
+// renameLine is a source line as rename sees it: the instruction, the
+// token of the label field and the operand tokens naming labels.
+type renameLine struct {
+	inst  string
+	label *Token
+	refs  []Token
+}
+
+// renameSymbols is what the labels of a source resolve to. A label is
+// "PROGRAM:LABEL" and a program, the START label, is "PROGRAM"; targets
+// holds the symbol of each reference by line, "" for names the source
+// does not define, such as library subroutines.
+type renameSymbols struct {
+	units   map[string]bool
+	defined map[string]bool
+	targets [][]string
+}
+
+// readRenameLines parses the lines of source. Preprocessor lines are nil:
+// the names they test are not labels.
+func readRenameLines(texts []string) ([]*renameLine, error) {
+	lines := make([]*renameLine, len(texts))
+	for i, text := range texts {
+		code := text
+		if idx := commentIndex(code); idx >= 0 {
+			code = code[:idx]
+		}
+		if conditionalPattern.MatchString(code) || includeFilePattern.MatchString(code) || includeStdPattern.MatchString(code) {
+			continue
+		}
+		parsed, err := ParseLine(text, i+1)
+		if err != nil {
+			return nil, fmt.Errorf("[CASL2 ERROR] %v", err)
+		}
+		var tokens []Token
+		for tok := range NewLexer(text).Tokens() {
+			if tok.Type == TOKEN_COMMENT || tok.Type == TOKEN_NEWLINE {
+				break
+			}
+			if tok.Type != TOKEN_WHITESPACE {
+				tokens = append(tokens, tok)
+			}
+		}
+		l := &renameLine{inst: parsed.Instruction}
+		if parsed.Label != "" {
+			l.label = &tokens[0]
+			tokens = tokens[1:]
+		}
+		if parsed.Instruction != "" {
+			tokens = tokens[1:]
+			for _, tok := range tokens {
+				if tok.Type == TOKEN_LABEL && isLabelReference(tok.Value) {
+					l.refs = append(l.refs, tok)
+				}
+			}
+		}
+		lines[i] = l
+	}
+	return lines, nil
+}
+
+// resolveRenameLines resolves the labels of lines the way the assembler
+// does, spelling each label token as name returns it: a reference is to
+// the label of its own program if there is one, else to a program.
+func resolveRenameLines(lines []*renameLine, name func(line int, tok Token) string) renameSymbols {
+	s := renameSymbols{
+		units:   make(map[string]bool),
+		defined: make(map[string]bool),
+		targets: make([][]string, len(lines)),
+	}
+	scopes := make([]string, len(lines))
+	scope := ""
+	for i, l := range lines {
+		if l == nil {
+			continue
+		}
+		if l.inst == "START" && l.label != nil {
+			scope = name(i, *l.label)
+			s.units[scope] = true
+		}
+		scopes[i] = scope
+		if l.label != nil {
+			s.defined[scope+":"+name(i, *l.label)] = true
+		}
+		if l.inst == "END" {
+			scope = ""
+		}
+	}
+	for i, l := range lines {
+		if l == nil {
+			continue
+		}
+		for _, tok := range l.refs {
+			n := name(i, tok)
+			target := ""
+			if s.defined[scopes[i]+":"+n] && n != scopes[i] {
+				target = scopes[i] + ":" + n
+			} else if s.units[n] {
+				target = n
+			}
+			s.targets[i] = append(s.targets[i], target)
+		}
+	}
+	return s
+}
+
+// symbolOf returns the symbol a label defined in scope stands for: the
+// program itself for its START label.
+func symbolOf(scope string, label string) string {
+	if label == scope {
+		return scope
+	}
+	return scope + ":" + label
+}
+
+// renameTarget finds the symbol from names: "PROGRAM:LABEL", a program,
+// or a label defined in only one program.
+func renameTarget(s renameSymbols, from string) (string, error) {
+	if unit, label, ok := strings.Cut(from, ":"); ok {
+		unit, label = foldCase(unit), foldCase(label)
+		if !s.defined[unit+":"+label] {
+			return "", fmt.Errorf("[CASL2 ERROR] Label \"%s\" is not defined", from)
+		}
+		return symbolOf(unit, label), nil
+	}
+	name := foldCase(from)
+	if s.units[name] {
+		return name, nil
+	}
+	var found []string
+	for key := range s.defined {
+		if _, label, _ := strings.Cut(key, ":"); label == name {
+			found = append(found, key)
+		}
+	}
+	sort.Strings(found)
+	switch len(found) {
+	case 0:
+		return "", fmt.Errorf("[CASL2 ERROR] Label \"%s\" is not defined", from)
+	case 1:
+		return found[0], nil
+	}
+	return "", fmt.Errorf("[CASL2 ERROR] Label \"%s\" is defined in several programs (%s); write PROGRAM:LABEL", from, strings.Join(found, ", "))
+}
+
+// symbolSite is a token naming a symbol, in its definition or in a
+// reference, and the index of its line.
+type symbolSite struct {
+	line       int
+	tok        Token
+	definition bool
+}
+
+// symbolSites returns the tokens of lines naming target, in source order.
+func symbolSites(lines []*renameLine, s renameSymbols, target string) []symbolSite {
+	var sites []symbolSite
+	scope := ""
+	for i, l := range lines {
+		if l == nil {
+			continue
+		}
+		if l.inst == "START" && l.label != nil {
+			scope = foldCase(l.label.Value)
+		}
+		if l.label != nil && symbolOf(scope, foldCase(l.label.Value)) == target {
+			sites = append(sites, symbolSite{line: i, tok: *l.label, definition: true})
+		}
+		for j, tok := range l.refs {
+			if s.targets[i][j] == target {
+				sites = append(sites, symbolSite{line: i, tok: tok})
+			}
+		}
+		if l.inst == "END" {
+			scope = ""
+		}
+	}
+	return sites
+}
+
+// renameSource renames the label or program from to to in source, with
+// every reference to it, and returns the new source and the number of
+// names changed. Everything else, comments and layout included, is kept.
+// A rename that would make a reference mean another label, or two labels
+// one, is refused.
+func renameSource(source string, from string, to string) (string, int, error) {
+	if !IsValidLabel(to) {
+		return "", 0, fmt.Errorf("[CASL2 ERROR] Invalid label \"%s\"", to)
+	}
+	if IsRegister(to) || isInstruction(to) {
+		return "", 0, fmt.Errorf("[CASL2 ERROR] \"%s\" is a reserved name", to)
+	}
+	texts := strings.Split(source, "\n")
+	lines, err := readRenameLines(texts)
+	if err != nil {
+		return "", 0, err
+	}
+	before := resolveRenameLines(lines, func(line int, tok Token) string {
+		return foldCase(tok.Value)
+	})
+	target, err := renameTarget(before, from)
+	if err != nil {
+		return "", 0, err
+	}
+
+	// What target and the labels of a renamed program become
+	unit, label, local := strings.Cut(target, ":")
+	toName := foldCase(to)
+	if (local && label == toName) || (!local && unit == toName) {
+		return "", 0, fmt.Errorf("[CASL2 ERROR] \"%s\" is already named \"%s\"", from, to)
+	}
+	renamed := func(symbol string) string {
+		if local {
+			if symbol == target {
+				return unit + ":" + toName
+			}
+			return symbol
+		}
+		if symbol == unit {
+			return toName
+		}
+		if rest, ok := strings.CutPrefix(symbol, unit+":"); ok {
+			return toName + ":" + rest
+		}
+		return symbol
+	}
+
+	// The tokens to rewrite: definitions of target and references to it
+	found := symbolSites(lines, before, target)
+	sites := make(map[[2]int]bool)
+	for _, site := range found {
+		sites[[2]int{site.line, site.tok.Start}] = true
+	}
+	after := resolveRenameLines(lines, func(line int, tok Token) string {
+		if sites[[2]int{line, tok.Start}] {
+			return toName
+		}
+		return foldCase(tok.Value)
+	})
+
+	if len(after.defined) != len(before.defined) || len(after.units) != len(before.units) {
+		return "", 0, fmt.Errorf("[CASL2 ERROR] \"%s\" is already defined", to)
+	}
+	for i := range lines {
+		for j, was := range before.targets[i] {
+			now := after.targets[i][j]
+			if was == "" && now != "" || was != "" && now != renamed(was) {
+				return "", 0, fmt.Errorf("[CASL2 ERROR] Line %d: renaming to \"%s\" would change what \"%s\" refers to", i+1, to, lines[i].refs[j].Value)
+			}
+		}
+	}
+
+	// From the end, so that the positions before stay valid
+	for _, site := range slices.Backward(found) {
+		text := texts[site.line]
+		texts[site.line] = text[:site.tok.Start] + to + text[site.tok.End:]
+	}
+	return strings.Join(texts, "\n"), len(found), nil
+}
+
+// labelAt returns the symbol named by the label token of source at line
+// and column (both from 0), as an editor points at it, and the token. A
+// reference to a name the source does not define is not a label.
+func labelAt(source string, line int, col int) (string, Token, bool) {
+	texts := strings.Split(source, "\n")
+	if line < 0 || line >= len(texts) {
+		return "", Token{}, false
+	}
+	lines, err := readRenameLines(texts)
+	if err != nil || lines[line] == nil {
+		return "", Token{}, false
+	}
+	s := resolveRenameLines(lines, func(line int, tok Token) string {
+		return foldCase(tok.Value)
+	})
+	scope := ""
+	for i, l := range lines[:line+1] {
+		if l == nil {
+			continue
+		}
+		if l.inst == "START" && l.label != nil {
+			scope = foldCase(l.label.Value)
+		}
+		if l.inst == "END" && i < line {
+			scope = ""
+		}
+	}
+	on := func(tok Token) bool {
+		return col >= tok.Start && col <= tok.End
+	}
+	l := lines[line]
+	if l.label != nil && on(*l.label) {
+		return symbolOf(scope, foldCase(l.label.Value)), *l.label, true
+	}
+	for j, tok := range l.refs {
+		if on(tok) && s.targets[line][j] != "" {
+			return s.targets[line][j], tok, true
+		}
+	}
+	return "", Token{}, false
+}
+
+// renameAt renames the label at line and column of source to to, with the
+// checks of renameSource, and returns the tokens to rewrite.
+func renameAt(source string, line int, col int, to string) ([]symbolSite, error) {
+	symbol, _, ok := labelAt(source, line, col)
+	if !ok {
+		return nil, fmt.Errorf("[CASL2 ERROR] There is no label to rename here")
+	}
+	if _, _, err := renameSource(source, symbol, to); err != nil {
+		return nil, err
+	}
+	lines, err := readRenameLines(strings.Split(source, "\n"))
+	if err != nil {
+		return nil, err
+	}
+	s := resolveRenameLines(lines, func(line int, tok Token) string {
+		return foldCase(tok.Value)
+	})
+	return symbolSites(lines, s, symbol), nil
+}
+
+// runRenameCommand implements "c2c2 rename FILE.cas OLD NEW". It renames
+// the label and the references to it, checks that the program assembles
+// to the same image and writes the source back, or to -o OUTPUT. It
+// returns the exit status.
+func runRenameCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("rename", flag.ContinueOnError)
+	fs.SetOutput(w)
+	output := fs.String("o", "", "write the renamed source to the given file instead of FILE.cas")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 rename [-o OUTPUT] FILE.cas OLD NEW\n\nOLD is a label, a program (START label) or PROGRAM:LABEL.\n\nOptions:\n")
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 3 {
+		fs.Usage()
+		return 2
+	}
+
+	path, from, to := fs.Arg(0), fs.Arg(1), fs.Arg(2)
+	source, err := os.ReadFile(path)
+	if err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] Cannot read file: %v\n", err)
+		return 1
+	}
+	original, err := assembleText(path, string(source), newAssemblerState())
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	text, count, err := renameSource(string(source), from, to)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	// Labels of included files are not seen by renameSource
+	renamed, err := assembleText(path, text, newAssemblerState())
+	if err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] The renamed source does not assemble: %v\n", err)
+		return 1
+	}
+	if renamed.Start != original.Start || !slices.Equal(renamed.Bin, original.Bin) {
+		fmt.Fprintln(w, "[CASL2 ERROR] The renamed source assembles to a different program")
+		return 1
+	}
+
+	if *output == "" {
+		*output = path
+	}
+	if err := os.WriteFile(*output, []byte(text), 0644); err != nil {
+		fmt.Fprintf(w, "[CASL2 ERROR] Cannot write file: %v\n", err)
+		return 1
+	}
+	fmt.Fprintf(w, "Renamed %s to %s in %d places\n", from, to, count)
+	return 0
+}
@@ -0,0 +1,78 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestRename(t *testing.T) {
+	source := `MAIN	START
+LOOP	LD	GR1,LIMIT	; LOOP: keep the comment
+	CALL	SUB
+	JMI	LOOP
+	CALL	OUTNUM
+	RET
+LIMIT	DC	3
+	INCLUDE	<STD/OUTNUM>
+	END
+SUB	START
+LOOP	SUBA	GR1,=1
+	JPL	loop
+	RET
+	END
+`
+	renamed, count, err := renameSource(source, "MAIN:LOOP", "NEXT")
+	if err != nil {
+		t.Fatalf("Expected rename to succeed, got %v", err)
+	}
+	if count != 2 || !strings.Contains(renamed, "NEXT\tLD\tGR1,LIMIT\t; LOOP: keep the comment\n") ||
+		!strings.Contains(renamed, "\tJMI\tNEXT\n") || !strings.Contains(renamed, "\tJPL\tloop\n") {
+		t.Errorf("Expected only the LOOP of MAIN renamed, got %d:\n%s", count, renamed)
+	}
+
+	// A program is renamed in the programs calling it too
+	renamed, _, err = renameSource(source, "SUB", "WORK")
+	if err != nil || !strings.Contains(renamed, "\tCALL\tWORK\n") || !strings.Contains(renamed, "WORK\tSTART\n") {
+		t.Errorf("Expected SUB renamed to WORK, got %v:\n%s", err, renamed)
+	}
+
+	for _, tc := range []struct {
+		from, to, message string
+	}{
+		{"LOOP", "NEXT", "several programs"},
+		{"MISSING", "NEXT", "not defined"},
+		{"LIMIT", "GR1", "reserved"},
+		{"LIMIT", "LD", "reserved"},
+		{"LIMIT", "1ST", "Invalid label"},
+		{"MAIN:LOOP", "LIMIT", "already defined"},
+		{"SUB", "MAIN", "already defined"},
+		// The CALL would then go to the label instead of the library
+		{"LIMIT", "OUTNUM", "would change what \"OUTNUM\" refers to"},
+		// In SUB, a label named like a program hides it
+		{"SUB:LOOP", "MAIN", ""},
+	} {
+		_, _, err := renameSource(source, tc.from, tc.to)
+		if tc.message == "" {
+			if err != nil {
+				t.Errorf("Expected %s -> %s to succeed, got %v", tc.from, tc.to, err)
+			}
+		} else if err == nil || !strings.Contains(err.Error(), tc.message) {
+			t.Errorf("Expected %s -> %s to fail with %q, got %v", tc.from, tc.to, tc.message, err)
+		}
+	}
+
+	dir := t.TempDir()
+	path := filepath.Join(dir, "hw.cas")
+	os.WriteFile(path, []byte(source), 0644)
+	var out bytes.Buffer
+	if status := runRenameCommand([]string{path, "LIMIT", "MAX"}, &out); status != 0 {
+		t.Fatalf("Expected rename to succeed, got %d:\n%s", status, out.String())
+	}
+	data, _ := os.ReadFile(path)
+	if !strings.Contains(string(data), "MAX\tDC\t3\n") || !strings.Contains(out.String(), "in 2 places") {
+		t.Errorf("Expected LIMIT renamed to MAX in the file, got %s:\n%s", out.String(), data)
+	}
+}
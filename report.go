@@ -0,0 +1,202 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"strings"
+)
+
+// Number of memory words shown in the listing excerpt of a report
+const REPORT_LISTING_WORDS = 64
+
+// writeMarkdownReport writes a summary of the run as Markdown so it can be
+// pasted into a homework submission.
+func writeMarkdownReport(filepath string, obj *Object, c *Comet2, halt string) error {
+	var b strings.Builder
+
+	source := ""
+	if obj.Source != "" {
+		if content, err := os.ReadFile(obj.Source); err == nil {
+			source = strings.ReplaceAll(string(content), "\r\n", "\n")
+		}
+	}
+	sourceLines := strings.Split(source, "\n")
+
+	fmt.Fprintf(&b, "# Run report: %s\n\n", obj.Source)
+	fmt.Fprintf(&b, "- c2c2 version: %s\n", VERSION)
+	fmt.Fprintf(&b, "- Result: %s\n", halt)
+	if c.OutputTruncated {
+		b.WriteString("- Output: truncated at the output limit\n")
+	}
+	if c.ExitStatus != nil {
+		fmt.Fprintf(&b, "- Exit status: %d\n", *c.ExitStatus)
+	}
+	b.WriteString("\n")
+
+	if source != "" {
+		text := strings.TrimRight(source, "\n")
+		fence := markdownFence(text)
+		fmt.Fprintf(&b, "## Source\n\n%scasl2\n%s\n%s\n\n", fence, text, fence)
+	}
+
+	var listing strings.Builder
+	for addr := 0; addr < len(obj.Bin) && addr < REPORT_LISTING_WORDS; addr++ {
+		line := fmt.Sprintf("#%s  %s", hex(addr, 4), hex(int(obj.Bin[addr]), 4))
+		if addr < len(obj.SourceMap) {
+			loc := obj.SourceMap[addr]
+			if loc.File == obj.Source && loc.Line > 0 && loc.Line <= len(sourceLines) {
+				line += fmt.Sprintf("  %4d  %s", loc.Line, strings.TrimSpace(sourceLines[loc.Line-1]))
+			}
+		}
+		listing.WriteString(line + "\n")
+	}
+	if len(obj.Bin) > REPORT_LISTING_WORDS {
+		fmt.Fprintf(&listing, "... (%d words in total)\n", len(obj.Bin))
+	}
+	fence := markdownFence(listing.String())
+	fmt.Fprintf(&b, "## Listing (excerpt)\n\n%s\n%s%s\n\n", fence, listing.String(), fence)
+
+	b.WriteString("## Input / Output\n\n")
+	if len(c.Transcript) == 0 {
+		b.WriteString("(none)\n\n")
+	} else {
+		b.WriteString("| Step | | Text |\n|---:|---|---|\n")
+		for _, ev := range c.Transcript {
+			kind := "OUT"
+			if ev.Kind == IO_IN {
+				kind = "IN"
+			}
+			if ev.Synthesized {
+				kind = "IN (default)"
+			}
+			fmt.Fprintf(&b, "| %d | %s | %s |\n", ev.Step, kind, markdownCode(ev.Text))
+		}
+		b.WriteString("\n")
+	}
+
+	b.WriteString("## Final registers\n\n| Register | Hex | Decimal |\n|---|---|---:|\n")
+	fmt.Fprintf(&b, "| PR | %s | %d |\n", c.formatAddr(c.State[PC]), c.State[PC])
+	fmt.Fprintf(&b, "| SP | #%s | %d |\n", hex(c.State[SP], 4), c.State[SP])
+	fmt.Fprintf(&b, "| FR | %s | %s |\n", formatFRBits(c.State[FR]), formatFR(c.State[FR]))
+	for i := 0; i < 8; i++ {
+		val := c.State[GR0+i]
+		fmt.Fprintf(&b, "| GR%d | #%s | %d |\n", i, hex(val, 4), signed(val))
+	}
+	b.WriteString("\n")
+
+	b.WriteString("## Statistics\n\n")
+	fmt.Fprintf(&b, "- Program size: %d words\n", len(obj.Bin))
+	for _, u := range obj.Units {
+		fmt.Fprintf(&b, "  - %s: %d code, %d data, %d literal words\n", u.Name, u.Code, u.Data, u.Literals)
+	}
+	if len(obj.Bin) > 0 {
+		fmt.Fprintf(&b, "- Highest address: #%s\n", hex(len(obj.Bin)-1, 4))
+	}
+	fmt.Fprintf(&b, "- Executed instructions: %d\n", c.Steps)
+	fmt.Fprintf(&b, "- IN / OUT: %d / %d\n", countIoEvents(c, IO_IN), countIoEvents(c, IO_OUT))
+
+	if err := os.WriteFile(filepath, []byte(b.String()), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write report: %v", err)
+	}
+	return nil
+}
+
+// markdownCode renders text as a code span that fits in a table cell: the
+// fence is longer than any run of backticks in text, and | is escaped.
+// Empty text is shown as "(empty)", since an empty code span is not one.
+func markdownCode(text string) string {
+	if text == "" {
+		return "*(empty)*"
+	}
+	text = strings.ReplaceAll(text, "|", "\\|")
+	fence := "`"
+	for strings.Contains(text, fence) {
+		fence += "`"
+	}
+	// A space on each side keeps a backtick at either end from joining
+	// the fence; one space on both sides is stripped when rendered
+	if strings.HasPrefix(text, "`") || strings.HasSuffix(text, "`") ||
+		(strings.HasPrefix(text, " ") && strings.HasSuffix(text, " ") && strings.TrimSpace(text) != "") {
+		text = " " + text + " "
+	}
+	return fence + text + fence
+}
+
+// markdownFence returns a code block fence for text: three backticks, or
+// more than the longest run of backticks in text, so that a comment such
+// as "; ```" does not end the block.
+func markdownFence(text string) string {
+	fence := "```"
+	for strings.Contains(text, fence) {
+		fence += "`"
+	}
+	return fence
+}
+
+// countIoEvents counts transcript entries of the given kind.
+func countIoEvents(c *Comet2, kind IoEventKind) int {
+	n := 0
+	for _, ev := range c.Transcript {
+		if ev.Kind == kind {
+			n++
+		}
+	}
+	return n
+}
+
+// RunResult is the outcome of a run in a form that can be serialized.
+type RunResult struct {
+	Source     string    `json:"source,omitempty"`
+	Halt       string    `json:"halt"`
+	Steps      int       `json:"steps"`
+	PR         int       `json:"pr"`
+	FR         int       `json:"fr"`
+	SP         int       `json:"sp"`
+	GR         [8]int    `json:"gr"`
+	Transcript []IoEvent `json:"transcript"`
+	Truncated  bool      `json:"truncated,omitempty"`
+	// ExitStatus is set when the program ended with the exit SVC of
+	// -extended-svc.
+	ExitStatus *int `json:"exitStatus,omitempty"`
+	// Usage is what the run consumed and the limits it ran under.
+	Usage ResourceUsage `json:"usage"`
+	// Memory and Symbols are kept for the Assert methods but not
+	// serialized.
+	Memory  []uint16       `json:"-"`
+	Symbols map[string]int `json:"-"`
+}
+
+// newRunResult captures the final state of c.
+func newRunResult(obj *Object, c *Comet2, halt string) *RunResult {
+	r := &RunResult{
+		Source:     obj.Source,
+		Halt:       halt,
+		Steps:      c.Steps,
+		PR:         c.State[PC],
+		FR:         c.State[FR],
+		SP:         c.State[SP],
+		Transcript: append([]IoEvent{}, c.Transcript...),
+		Truncated:  c.OutputTruncated,
+		ExitStatus: c.ExitStatus,
+		Usage:      c.Usage(),
+		Memory:     append([]uint16{}, c.Memory...),
+		Symbols:    c.Symbols,
+	}
+	for i := 0; i < 8; i++ {
+		r.GR[i] = c.State[GR0+i]
+	}
+	return r
+}
+
+// writeJsonReport writes the run result as JSON for external tools.
+func writeJsonReport(filepath string, obj *Object, c *Comet2, halt string) error {
+	data, err := json.MarshalIndent(newRunResult(obj, c, halt), "", "  ")
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot encode report: %v", err)
+	}
+	if err := os.WriteFile(filepath, append(data, '\n'), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write report: %v", err)
+	}
+	return nil
+}
@@ -0,0 +1,86 @@
+package main
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestRunResultJSON(t *testing.T) {
+	c := assembleSource(t, echoProgram)
+	c.Input = NewInputLines([]string{"abc"})
+	err := c.Run()
+
+	data, jerr := json.Marshal(newRunResult(&Object{}, c, err.Error()))
+	if jerr != nil {
+		t.Fatalf("Failed to encode: %v", jerr)
+	}
+	var r RunResult
+	if jerr := json.Unmarshal(data, &r); jerr != nil {
+		t.Fatalf("Failed to decode: %v", jerr)
+	}
+	if r.Halt != "Program finished (RET)" || r.Steps != c.Steps {
+		t.Errorf("Unexpected result: %+v", r)
+	}
+	if len(r.Transcript) != 2 || r.Transcript[0].Kind != IO_IN || r.Transcript[1].Kind != IO_OUT {
+		t.Errorf("Unexpected transcript: %+v", r.Transcript)
+	}
+	if !strings.Contains(string(data), `"kind":"in"`) {
+		t.Errorf("Expected I/O kind to be encoded by name: %s", data)
+	}
+}
+
+func TestMarkdownReport(t *testing.T) {
+	src := "test/report/prog.cas"
+	obj := &Object{
+		Source:    src,
+		Bin:       []uint16{0x1210, 0x0005, 0x8100},
+		SourceMap: []SourceLocation{{File: src, Line: 2}, {File: src, Line: 2}, {File: src, Line: 3}},
+		Units:     []UnitSize{{Name: "MAIN", Code: 3}},
+	}
+	c := LoadObject(obj)
+	// Text that would break a table row or a plain code span
+	c.Transcript = []IoEvent{
+		{Kind: IO_IN, Text: "a|b", Step: 1},
+		{Kind: IO_OUT, Text: "x`y", Step: 2},
+		{Kind: IO_OUT, Text: "", Step: 3},
+		{Kind: IO_IN, Text: "0", Step: 4, Synthesized: true},
+		{Kind: IO_OUT, Text: "`q`", Step: 5},
+	}
+
+	path := filepath.Join(t.TempDir(), "report.md")
+	if err := writeMarkdownReport(path, obj, c, "Program finished (RET)"); err != nil {
+		t.Fatal(err)
+	}
+	report, err := os.ReadFile(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	actual := strings.Replace(string(report), VERSION, "VERSION", 1)
+	diff, err := compareGolden(filepath.Join("test", "report", "report.md"), actual, false)
+	if err != nil {
+		t.Fatalf("Failed to compare: %v", err)
+	}
+	if diff != "" {
+		t.Errorf("Report differs from the golden file:\n%s", diff)
+	}
+}
+
+func TestMarkdownFence(t *testing.T) {
+	tests := []struct {
+		text string
+		want string
+	}{
+		{"LD\tGR1,A", "```"},
+		{"; `a` ``b``", "```"},
+		{"; ```", "````"},
+		{"; ``` and `````", "``````"},
+	}
+	for _, tt := range tests {
+		if got := markdownFence(tt.text); got != tt.want {
+			t.Errorf("%q: expected %s, got %s", tt.text, tt.want, got)
+		}
+	}
+}
@@ -0,0 +1,67 @@
+package main
+
+import (
+	"errors"
+	"time"
+)
+
+// ErrTimeLimit is returned when Run has run for MaxTime in total.
+var ErrTimeLimit = errors.New("Time limit exceeded")
+
+// Number of instructions between two checks of MaxTime, so that reading
+// the clock does not slow every instruction down
+const TIME_CHECK_INTERVAL = 1024
+
+// ResourceUsage is what a run has consumed, next to the limits it runs
+// under, so that an embedder can enforce and report a fair-use policy. A
+// zero limit means none. Durations are in nanoseconds in JSON.
+type ResourceUsage struct {
+	Steps          int           `json:"steps"`
+	MaxSteps       int           `json:"maxSteps,omitempty"`
+	WallTime       time.Duration `json:"wallTime"`
+	MaxTime        time.Duration `json:"maxTime,omitempty"`
+	OutputBytes    int           `json:"outputBytes"`
+	MaxOutputBytes int           `json:"maxOutputBytes,omitempty"`
+	OutputLines    int           `json:"outputLines"`
+	MaxOutputLines int           `json:"maxOutputLines,omitempty"`
+	InputLines     int           `json:"inputLines"`
+	// StackDepth is the largest number of words on the stack at once.
+	StackDepth    int `json:"stackDepth"`
+	MaxStackDepth int `json:"maxStackDepth,omitempty"`
+}
+
+// Usage returns the resources consumed since the program was loaded.
+// WallTime counts the time spent in Run, Continue and Step.
+func (c *Comet2) Usage() ResourceUsage {
+	return ResourceUsage{
+		Steps:          c.Steps,
+		MaxSteps:       c.MaxSteps,
+		WallTime:       c.wallTime,
+		MaxTime:        c.MaxTime,
+		OutputBytes:    c.outputBytes,
+		MaxOutputBytes: c.MaxOutputBytes,
+		OutputLines:    c.outputLines,
+		MaxOutputLines: c.MaxOutputLines,
+		InputLines:     countIoEvents(c, IO_IN),
+		StackDepth:     c.stackDepth,
+		MaxStackDepth:  c.MaxStackDepth,
+	}
+}
+
+// pushed records that the stack grew to sp at the instruction at pc and
+// fails when that is deeper than MaxStackDepth.
+func (c *Comet2) pushed(pc int, sp int) error {
+	depth := c.StackTop - sp
+	if c.MaxStackDepth > 0 && depth > c.MaxStackDepth {
+		return runtimeError(RUNTIME_STACK_OVERFLOW, pc, "Stack depth limit of %d words exceeded at %s: SP = #%s%s",
+			c.MaxStackDepth, c.formatAddr(pc), hex(sp, 4), c.locate(pc))
+	}
+	c.stackDepth = max(c.stackDepth, depth)
+	return nil
+}
+
+// timeUp reports whether Run has used up MaxTime, counting the time since
+// start on top of the earlier calls.
+func (c *Comet2) timeUp(start time.Time) bool {
+	return c.MaxTime > 0 && c.wallTime+time.Since(start) >= c.MaxTime
+}
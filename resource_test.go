@@ -0,0 +1,50 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestResourceUsage(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	IN	BUF,LEN
+	CALL	SUB
+	OUT	BUF,LEN
+	RET
+BUF	DS	8
+LEN	DS	1
+	END
+SUB	START
+	PUSH	0,GR1
+	POP	GR1
+	RET
+	END
+`)
+	c := NewComet2Builder().WithObject(obj).WithInputs([]string{"hey"}).WithOutput(nil).WithMaxSteps(100).Build()
+	c.Run()
+	usage := c.Usage()
+	if usage.Steps != c.Steps || usage.MaxSteps != 100 || usage.InputLines != 1 || usage.OutputBytes != 3 || usage.OutputLines != 1 {
+		t.Errorf("Unexpected usage %+v", usage)
+	}
+	// IN and OUT push GR1 and GR2, CALL and PUSH one word each
+	if usage.StackDepth != 2 || usage.WallTime <= 0 {
+		t.Errorf("Expected a stack depth of 2 and some wall time, got %+v", usage)
+	}
+	if r := newRunResult(obj, c, ""); r.Usage != usage {
+		t.Errorf("Expected the run result to carry the usage, got %+v", r.Usage)
+	}
+
+	c = NewComet2Builder().WithObject(obj).WithInputs([]string{"hey"}).WithOutput(nil).WithMaxStackDepth(1).Build()
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_STACK_OVERFLOW || !strings.Contains(err.Error(), "Stack depth limit of 1 words") {
+		t.Errorf("Expected the stack depth limit, got %v", err)
+	}
+
+	loop := assembleObject(t, "MAIN\tSTART\nLOOP\tJUMP\tLOOP\n\tEND\n")
+	c = NewComet2Builder().WithObject(loop).WithMaxTime(20 * time.Millisecond).Build()
+	if r := c.Continue(); r.Kind != HALT_TIME_LIMIT || !errors.Is(r.Err, ErrTimeLimit) || c.Usage().WallTime < 20*time.Millisecond {
+		t.Errorf("Expected the time limit, got %v after %v", r.Kind, c.Usage().WallTime)
+	}
+}
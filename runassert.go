@@ -0,0 +1,69 @@
+package main
+
+import "fmt"
+
+// Output returns the text written by OUT during the run, each OUT ending
+// a line.
+func (r *RunResult) Output() string {
+	var out string
+	for _, ev := range r.Transcript {
+		if ev.Kind == IO_OUT {
+			out += ev.Text + "\n"
+		}
+	}
+	return out
+}
+
+// AssertOutput checks that the run wrote exactly expected with OUT. The
+// error shows the differing lines.
+func (r *RunResult) AssertOutput(expected string) error {
+	if out := r.Output(); out != expected {
+		return fmt.Errorf("output differs from the expectation:\n%s%s", lineDiff(expected, out), formatFirstDifference(expected, out))
+	}
+	return nil
+}
+
+// AssertReg checks the final value of a register, given by its index into
+// State (PC, FR, GR0-GR7 or SP). Values are compared as words, so -1 and
+// 65535 are the same.
+func (r *RunResult) AssertReg(reg int, expected int) error {
+	var val int
+	switch {
+	case reg == PC:
+		val = r.PR
+	case reg == FR:
+		val = r.FR
+	case reg == SP:
+		val = r.SP
+	case reg >= GR0 && reg <= GR7:
+		val = r.GR[reg-GR0]
+	default:
+		return fmt.Errorf("invalid register index %d", reg)
+	}
+	name := "PR"
+	if reg == FR {
+		name = "FR"
+	} else if reg != PC {
+		name = registerName(reg)
+	}
+	if val&0xffff != expected&0xffff {
+		return fmt.Errorf("%s is %s, expected %s", name, formatWord(val), formatWord(expected&0xffff))
+	}
+	return nil
+}
+
+// AssertMemLabel checks the final value of the word at label, which is
+// looked up like the monitor does, or of an address given as a number.
+func (r *RunResult) AssertMemLabel(label string, expected int) error {
+	addr, ok := lookupSymbol(&Object{Symbols: r.Symbols}, label)
+	if !ok {
+		return fmt.Errorf("unknown label \"%s\"", label)
+	}
+	if addr < 0 || addr >= len(r.Memory) {
+		return fmt.Errorf("address #%s of \"%s\" is outside the memory", hex(addr, 4), label)
+	}
+	if val := int(r.Memory[addr]); val != expected&0xffff {
+		return fmt.Errorf("%s (#%s) is %s, expected %s", label, hex(addr, 4), formatWord(val), formatWord(expected&0xffff))
+	}
+	return nil
+}
@@ -0,0 +1,47 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestRunResultAssertions(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,-1
+	ST	GR1,SUM
+	OUT	MSG,LEN
+	LAD	GR0,42
+	RET
+SUM	DS	1
+MSG	DC	'hi'
+LEN	DC	2
+	END
+`)
+	err := c.Run()
+	r := newRunResult(&Object{}, c, err.Error())
+
+	if err := r.AssertOutput("hi\n"); err != nil {
+		t.Errorf("Expected the output to match: %v", err)
+	}
+	if err := r.AssertOutput("ho\n"); err == nil || !strings.Contains(err.Error(), "+   1: hi") {
+		t.Errorf("Expected a diff of the output, got %v", err)
+	}
+	if err := r.AssertReg(GR0, 42); err != nil {
+		t.Errorf("Expected GR0 to match: %v", err)
+	}
+	if err := r.AssertReg(GR1, 65535); err != nil {
+		t.Errorf("Expected GR1 to match as a word: %v", err)
+	}
+	if err := r.AssertReg(GR0, 41); err == nil || !strings.HasPrefix(err.Error(), "GR0 is #002a") {
+		t.Errorf("Expected GR0 to differ, got %v", err)
+	}
+	if err := r.AssertMemLabel("SUM", -1); err != nil {
+		t.Errorf("Expected SUM to match: %v", err)
+	}
+	if err := r.AssertMemLabel("SUM", 0); err == nil {
+		t.Errorf("Expected SUM to differ")
+	}
+	if err := r.AssertMemLabel("NONE", 0); err == nil {
+		t.Errorf("Expected an unknown label to fail")
+	}
+}
@@ -0,0 +1,104 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"os"
+)
+
+// Default number of steps between two samples of -samples
+const SAMPLE_EVERY = 100
+
+// Register names of a sample, in the order of the header
+var sampleRegisters = []string{"PR", "FR", "SP", "GR0", "GR1", "GR2", "GR3", "GR4", "GR5", "GR6", "GR7"}
+
+// sampleHeader is the first line of a -samples file.
+type sampleHeader struct {
+	Version int `json:"version"`
+	Every   int `json:"every"`
+	// From and To bound the memory words that are sampled.
+	From int `json:"from"`
+	To   int `json:"to"`
+}
+
+// stateSample is one line after the header: the registers and memory
+// words that changed since the previous sample, as [address, value]
+// pairs. The first sample holds every register and the words that are
+// not 0.
+type stateSample struct {
+	Step int            `json:"step"`
+	Regs map[string]int `json:"r,omitempty"`
+	Mem  [][2]int       `json:"m,omitempty"`
+}
+
+// stateSampler samples the machine every few steps for -samples, so that
+// a visualizer can animate a run. It only runs as an observer, so a run
+// without -samples does not pay for it.
+type stateSampler struct {
+	BaseObserver
+	every    int
+	from, to int
+	regs     []int
+	mem      []int
+	last     int
+	out      bytes.Buffer
+}
+
+func newStateSampler(every int, from int, to int) *stateSampler {
+	s := &stateSampler{every: max(every, 1), from: from, to: to, last: -1}
+	s.regs = make([]int, len(sampleRegisters))
+	for i := range s.regs {
+		s.regs[i] = -1
+	}
+	s.mem = make([]int, max(to-from+1, 0))
+	data, _ := json.Marshal(sampleHeader{Version: 1, Every: s.every, From: from, To: to})
+	s.out.Write(append(data, '\n'))
+	return s
+}
+
+func (s *stateSampler) BeforeStep(c *Comet2, pc int) {
+	if s.last < 0 {
+		s.sample(c)
+	}
+}
+
+func (s *stateSampler) AfterStep(c *Comet2, pc int) {
+	if c.Steps%s.every == 0 {
+		s.sample(c)
+	}
+}
+
+// sample appends the changes since the previous sample.
+func (s *stateSampler) sample(c *Comet2) {
+	smp := stateSample{Step: c.Steps, Regs: make(map[string]int)}
+	regs := []int{c.State[PC], c.State[FR], c.State[SP]}
+	regs = append(regs, c.State[GR0:GR7+1]...)
+	for i, v := range regs {
+		if v != s.regs[i] {
+			smp.Regs[sampleRegisters[i]] = v
+			s.regs[i] = v
+		}
+	}
+	for i := range s.mem {
+		if v := memGet(c.Memory, s.from+i); v != s.mem[i] {
+			smp.Mem = append(smp.Mem, [2]int{s.from + i, v})
+			s.mem[i] = v
+		}
+	}
+	data, _ := json.Marshal(smp)
+	s.out.Write(append(data, '\n'))
+	s.last = c.Steps
+}
+
+// writeSamples adds the final state unless it was just sampled and saves
+// the samples to path as JSON lines.
+func (s *stateSampler) writeSamples(path string, c *Comet2) error {
+	if s.last != c.Steps {
+		s.sample(c)
+	}
+	if err := os.WriteFile(path, s.out.Bytes(), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write samples: %v", err)
+	}
+	return nil
+}
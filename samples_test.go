@@ -0,0 +1,60 @@
+package main
+
+import (
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestStateSamples(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LAD	GR1,0
+LOOP	LAD	GR1,1,GR1
+	ST	GR1,CNT
+	CPA	GR1,=5
+	JMI	LOOP
+	RET
+CNT	DS	1
+	END
+`)
+	cnt := obj.Symbols["MAIN:CNT"]
+	samples := newStateSampler(4, cnt, cnt)
+	c := NewComet2Builder().WithObject(obj).WithOutput(func(string) {}).WithObserver(samples).Build()
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected the program to finish, got %v", err)
+	}
+	path := filepath.Join(t.TempDir(), "samples.jsonl")
+	if err := samples.writeSamples(path, c); err != nil {
+		t.Fatalf("Failed to write samples: %v", err)
+	}
+
+	data, err := os.ReadFile(path)
+	if err != nil {
+		t.Fatalf("Failed to read samples: %v", err)
+	}
+	lines := strings.Split(strings.TrimSpace(string(data)), "\n")
+	var header sampleHeader
+	if err := json.Unmarshal([]byte(lines[0]), &header); err != nil || header.Every != 4 || header.From != cnt {
+		t.Fatalf("Unexpected header %s (%v)", lines[0], err)
+	}
+	var first, second, last stateSample
+	json.Unmarshal([]byte(lines[1]), &first)
+	json.Unmarshal([]byte(lines[2]), &second)
+	json.Unmarshal([]byte(lines[len(lines)-1]), &last)
+	if first.Step != 0 || len(first.Regs) != len(sampleRegisters) || first.Mem != nil {
+		t.Errorf("Expected every register and no memory at step 0, got %+v", first)
+	}
+	// LAD, LAD, ST, CPA: GR1 is 1 and CNT was written
+	if second.Step != 4 || second.Regs["GR1"] != 1 || !reflect.DeepEqual(second.Mem, [][2]int{{cnt, 1}}) {
+		t.Errorf("Unexpected sample at step 4: %+v", second)
+	}
+	if _, ok := second.Regs["SP"]; ok {
+		t.Errorf("Expected SP to be left out while it does not change, got %+v", second)
+	}
+	if last.Step != c.Steps || len(lines) != 2+(c.Steps+3)/4 {
+		t.Errorf("Expected %d samples ending with the final state at step %d, got %d ending with %+v", (c.Steps+3)/4+1, c.Steps, len(lines)-1, last)
+	}
+}
@@ -0,0 +1,85 @@
+package main
+
+import (
+	"fmt"
+	"io"
+	"os"
+	"regexp"
+	"strings"
+	"time"
+)
+
+// Escape sequences of the console colors, which the session log drops
+var colorPattern = regexp.MustCompile("\x1b\\[[0-9;]*m")
+
+// sessionLog records the monitor interaction for -log-session. Commands
+// and IN lines typed at the console are written as they are, after a
+// comment with the time, and everything the monitor prints as comments,
+// so that the log without its "#" lines can be piped back into c2c2 to
+// replay the session.
+type sessionLog struct {
+	w   io.Writer
+	now func() time.Time
+}
+
+// The log of -log-session, or nil
+var session *sessionLog
+
+// openSessionLog creates the log at path for a session on source.
+func openSessionLog(path string, source string) (*sessionLog, *os.File, error) {
+	f, err := os.Create(path)
+	if err != nil {
+		return nil, nil, fmt.Errorf("[COMET2 ERROR] Cannot write session log: %v", err)
+	}
+	s := newSessionLog(f, time.Now)
+	fmt.Fprintf(f, "# c2c2 session log: %s, %s\n", source, s.now().Format(time.RFC3339))
+	return s, f, nil
+}
+
+func newSessionLog(w io.Writer, now func() time.Time) *sessionLog {
+	return &sessionLog{w: w, now: now}
+}
+
+// stamp returns the time of an entry.
+func (s *sessionLog) stamp() string {
+	return s.now().Format("15:04:05.000")
+}
+
+// command records a monitor command typed at the console.
+func (s *sessionLog) command(cmd string) {
+	fmt.Fprintf(s.w, "# %s command\n%s\n", s.stamp(), cmd)
+}
+
+// input records an IN line. Lines given on the command line are only
+// noted, since a replay gets them from its own command line.
+func (s *sessionLog) input(text string, typed bool) {
+	if typed {
+		fmt.Fprintf(s.w, "# %s input\n%s\n", s.stamp(), text)
+	} else {
+		fmt.Fprintf(s.w, "# %s input (argument): %s\n", s.stamp(), text)
+	}
+}
+
+// defaultInput records the default line of -input-timeout read by IN
+// when nothing was typed in time. It is written as a typed line, so a
+// replay reads it without waiting.
+func (s *sessionLog) defaultInput(text string) {
+	fmt.Fprintf(s.w, "# %s input (default after timeout)\n%s\n", s.stamp(), text)
+}
+
+// output records text the monitor printed, one comment per line.
+func (s *sessionLog) output(text string) {
+	stamp := s.stamp()
+	text = colorPattern.ReplaceAllString(strings.TrimSuffix(text, "\n"), "")
+	for _, line := range strings.Split(text, "\n") {
+		fmt.Fprintf(s.w, "# %s | %s\n", stamp, line)
+	}
+}
+
+// logOutput passes text printed on the console to the session log, if
+// there is one.
+func logOutput(text string) {
+	if session != nil {
+		session.output(text)
+	}
+}
@@ -0,0 +1,49 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+	"time"
+)
+
+func TestSessionLog(t *testing.T) {
+	var b strings.Builder
+	at := time.Date(2026, 1, 2, 10, 15, 2, 341000000, time.UTC)
+	session = newSessionLog(&b, func() time.Time { return at })
+	defer func() { session = nil }()
+
+	c := assembleSource(t, echoProgram)
+	c.Output = func(string) {}
+	session.command("step")
+	if err := executeCommand("step", nil, c); err != nil {
+		t.Fatal(err)
+	}
+	session.input("abc", true)
+	session.input("10", false)
+	cometWarn("line 1\nline 2")
+
+	log := b.String()
+	for _, want := range []string{
+		"# 10:15:02.341 command\nstep\n",
+		"# 10:15:02.341 input\nabc\n",
+		"# 10:15:02.341 input (argument): 10\n",
+		"# 10:15:02.341 | line 1\n# 10:15:02.341 | line 2\n",
+	} {
+		if !strings.Contains(log, want) {
+			t.Errorf("Expected %q in the log:\n%s", want, log)
+		}
+	}
+	if strings.Contains(log, "\x1b") {
+		t.Errorf("Expected no color codes in the log:\n%s", log)
+	}
+	var replay []string
+	for _, line := range strings.Split(strings.TrimSuffix(log, "\n"), "\n") {
+		if !strings.HasPrefix(line, "#") {
+			replay = append(replay, line)
+		}
+	}
+	if !reflect.DeepEqual(replay, []string{"step", "abc"}) {
+		t.Errorf("Expected the commands and typed inputs to replay, got %q", replay)
+	}
+}
@@ -0,0 +1,148 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// Instructions that treat registers as unsigned numbers, and the ones that
+// treat them as signed numbers
+var (
+	logicalOps    = map[string]bool{"ADDL": true, "SUBL": true, "MULL": true, "DIVL": true, "CPL": true}
+	arithmeticOps = map[string]bool{"ADDA": true, "SUBA": true, "MULA": true, "DIVA": true, "CPA": true}
+)
+
+// signUse is where a register was last treated as unsigned.
+type signUse struct {
+	inst string
+	pos  int
+}
+
+// operandRegisters returns the register of the r operand of an
+// instruction and, in the r1,r2 form, r2; -1 when there is none.
+func operandRegisters(oprs []string) (int, int) {
+	r, r2 := -1, -1
+	if len(oprs) > 0 {
+		if n, err := checkRegister(oprs[0]); err == nil {
+			r = n
+		}
+	}
+	if len(oprs) == 2 && isRegister(oprs[1]) {
+		r2, _ = checkRegister(oprs[1])
+	}
+	return r, r2
+}
+
+// lintSigns warns when a register that ADDL, SUBL, MULL, DIVL or CPL
+// treated as unsigned is then used by ADDA, SUBA, MULA, DIVA or CPA as
+// signed without being loaded again, within straight-line code. A label
+// may be jumped to, so what is known about the registers is dropped there
+// and after jumps, calls and SVCs; -check-signs follows the actual run.
+func lintSigns(asmState *AssemblerState) []Diagnostic {
+	var diags []Diagnostic
+	unsigned := make(map[int]signUse)
+	for i, text := range asmState.buf {
+		parts := strings.SplitN(text, "\t", 3)
+		if len(parts) < 3 {
+			continue
+		}
+		label, inst := parts[0], parts[1]
+		if label != "" {
+			clear(unsigned)
+		}
+		def, ok := CASL2TBL[inst]
+		if !ok {
+			continue
+		}
+		if def.Type != OP1 && def.Type != OP3 && def.Type != OP5 {
+			// Jumps, CALL, SVC and the macros change the flow or the
+			// registers out of sight
+			if inst != "PUSH" && inst != "NOP" {
+				clear(unsigned)
+			}
+			continue
+		}
+
+		r, r2 := operandRegisters(parseOperands(parts[2]))
+		pos := asmState.bufPos[i]
+		if arithmeticOps[inst] {
+			for _, reg := range []int{r, r2} {
+				if use, ok := unsigned[reg]; ok && reg >= 0 {
+					diags = append(diags, warning(asmState, pos, WARN_UNSIGNED_SIGNED, fmt.Sprintf(
+						"GR%d was treated as unsigned by %s on line %d and is now used as signed by %s; load it again or use %s",
+						reg, use.inst, asmState.origin(use.pos).Line, inst, logicalCounterpart(inst)))...)
+					delete(unsigned, reg)
+				}
+			}
+		}
+		switch {
+		case logicalOps[inst]:
+			unsigned[r] = signUse{inst, pos}
+		case inst != "ST":
+			// Anything else that writes r loads it again
+			delete(unsigned, r)
+		}
+	}
+
+	sort.SliceStable(diags, func(i, j int) bool {
+		return diags[i].Line < diags[j].Line
+	})
+	return diags
+}
+
+// logicalCounterpart names the unsigned instruction doing what the
+// arithmetic instruction inst does, e.g. CPL for CPA.
+func logicalCounterpart(inst string) string {
+	return strings.TrimSuffix(inst, "A") + "L"
+}
+
+// signChecker is the run-time side of lintSigns for -check-signs: it
+// follows the registers through jumps and calls, and reports each
+// instruction that uses a register as signed after a logical instruction
+// left it there.
+type signChecker struct {
+	BaseObserver
+	// unsigned holds, per register, 1 + the address of the instruction
+	// that last treated it as unsigned, or 0.
+	unsigned [8]int
+	reported map[int]bool
+}
+
+func newSignChecker() *signChecker {
+	return &signChecker{reported: make(map[int]bool)}
+}
+
+func (s *signChecker) BeforeStep(c *Comet2, pc int) {
+	word := memGet(c.Memory, pc)
+	inst, ok := COMET2TBL[word>>8]
+	if !ok || (inst.Type != OP1 && inst.Type != OP3 && inst.Type != OP5) {
+		return
+	}
+	r, r2 := (word>>4)&0x7, -1
+	if inst.Type == OP5 {
+		r2 = word & 0x7
+	}
+
+	if arithmeticOps[inst.ID] {
+		for _, reg := range []int{r, r2} {
+			if reg < 0 || s.unsigned[reg] == 0 {
+				continue
+			}
+			producer := s.unsigned[reg] - 1
+			s.unsigned[reg] = 0
+			if s.reported[pc] {
+				continue
+			}
+			s.reported[pc] = true
+			c.warn(fmt.Sprintf("Warning: GR%d was treated as unsigned by %s at %s%s and is now used as signed by %s at %s%s.",
+				reg, COMET2TBL[memGet(c.Memory, producer)>>8].ID, c.formatAddr(producer), c.locate(producer), inst.ID, c.formatAddr(pc), c.locate(pc)))
+		}
+	}
+	switch {
+	case logicalOps[inst.ID]:
+		s.unsigned[r] = pc + 1
+	case inst.ID != "ST":
+		s.unsigned[r] = 0
+	}
+}
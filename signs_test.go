@@ -0,0 +1,51 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestSignMixups(t *testing.T) {
+	src := `MAIN	START
+; GR1 = A + B
+	LD	GR1,A
+	ADDL	GR1,B
+	CPA	GR1,=0
+	CPL	GR2,B
+	LD	GR2,A
+	ADDA	GR2,GR1
+	SUBL	GR3,B
+	CALL	SUB
+	RET
+SUB	CPA	GR3,=1
+	RET
+A	DC	1
+B	DC	2
+	END
+`
+	*optLint = true
+	defer func() { *optLint = false }()
+	asmState := newAssemblerState()
+	obj, err := assembleText("prog.cas", src, asmState)
+	if err != nil {
+		t.Fatal(err)
+	}
+	want := []Diagnostic{
+		{Line: 5, Msg: "GR1 was treated as unsigned by ADDL on line 4 and is now used as signed by CPA; load it again or use CPL", Severity: "warning", Code: WARN_UNSIGNED_SIGNED},
+	}
+	if !reflect.DeepEqual(asmState.warnings, want) {
+		t.Errorf("Expected %v, got %v", want, asmState.warnings)
+	}
+
+	// The run also follows GR3 into SUB
+	var warnings []string
+	c := NewComet2Builder().WithObject(obj).WithOutput(func(string) {}).
+		WithWarn(func(msg string) { warnings = append(warnings, msg) }).
+		WithObserver(newSignChecker()).Build()
+	c.Run()
+	if len(warnings) != 2 || !strings.Contains(warnings[0], "GR1 was treated as unsigned by ADDL at #0002 <MAIN+2>") ||
+		!strings.Contains(warnings[1], "GR3 was treated as unsigned by SUBL at #000b <MAIN+11>") || !strings.Contains(warnings[1], "CPA at #0010 <SUB>") {
+		t.Errorf("Unexpected warnings %q", warnings)
+	}
+}
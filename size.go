@@ -0,0 +1,70 @@
+package main
+
+import (
+	"fmt"
+	"strings"
+)
+
+// UnitSize is the number of words one START..END program unit occupies,
+// split into instructions, DC/DS areas and its literal pool. Entry is the
+// address where the unit is entered.
+type UnitSize struct {
+	Name     string `json:"name"`
+	Start    int    `json:"start"`
+	Entry    int    `json:"entry"`
+	Code     int    `json:"code"`
+	Data     int    `json:"data"`
+	Literals int    `json:"literals"`
+}
+
+// Total is the size of the unit in words.
+func (u UnitSize) Total() int {
+	return u.Code + u.Data + u.Literals
+}
+
+// countWords adds the words generated by one line to the current unit.
+func countWords(asmState *AssemblerState, instType InstructionType, words int) {
+	if len(asmState.units) == 0 || words == 0 {
+		return
+	}
+	unit := &asmState.units[len(asmState.units)-1]
+	switch directives[instType].Part {
+	case PART_DATA:
+		unit.Data += words
+	case PART_LITERALS:
+		unit.Literals += words
+	default:
+		unit.Code += words
+	}
+}
+
+// checkProgramSize stops the assembly on the line where the program, now
+// address words long, grows past the memory of COMET2. Later labels would
+// otherwise get addresses no instruction can reach.
+func checkProgramSize(asmState *AssemblerState, address int) error {
+	if address > ADDRESS_SPACE {
+		return errorCasl2(asmState, fmt.Sprintf("Program does not fit in memory: it is %d words long up to this line, %d more than the %d words COMET2 can address",
+			address, address-ADDRESS_SPACE, ADDRESS_SPACE))
+	}
+	return nil
+}
+
+// formatSizes renders the size of each program unit, the totals and the
+// highest used address with the room left below the stack.
+func formatSizes(obj *Object) string {
+	var b strings.Builder
+	var total UnitSize
+	b.WriteString("Unit      Start   Code   Data  Lit  Total\n")
+	for _, u := range obj.Units {
+		fmt.Fprintf(&b, "%-8s  #%s  %5d  %5d  %3d  %5d\n", u.Name, hex(u.Start, 4), u.Code, u.Data, u.Literals, u.Total())
+		total.Code += u.Code
+		total.Data += u.Data
+		total.Literals += u.Literals
+	}
+	fmt.Fprintf(&b, "%-8s         %5d  %5d  %3d  %5d\n", "Total", total.Code, total.Data, total.Literals, total.Total())
+	if len(obj.Bin) > 0 {
+		last := len(obj.Bin) - 1
+		fmt.Fprintf(&b, "Highest address: #%s (%d words below the stack at #%s)\n", hex(last, 4), STACK_TOP-last-1, hex(STACK_TOP, 4))
+	}
+	return b.String()
+}
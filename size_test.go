@@ -0,0 +1,35 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestUnitSizes(t *testing.T) {
+	obj := assembleObject(t, `MAIN	START
+	LD	GR1,=3
+	ADDA	GR1,GR1
+	CALL	SUB
+	RET
+BUF	DS	4
+	END
+SUB	START
+	LAD	GR2,='AB'
+	RET
+VAL	DC	1,2
+	END
+`)
+	want := []UnitSize{
+		{Name: "MAIN", Start: 0, Entry: 0, Code: 6, Data: 4, Literals: 1},
+		{Name: "SUB", Start: 11, Entry: 11, Code: 3, Data: 2, Literals: 3},
+	}
+	if !reflect.DeepEqual(obj.Units, want) {
+		t.Errorf("Expected %v, got %v", want, obj.Units)
+	}
+
+	text := formatSizes(obj)
+	if !strings.Contains(text, "Highest address: #0012") {
+		t.Errorf("Expected highest address #0012 in %q", text)
+	}
+}
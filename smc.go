@@ -0,0 +1,42 @@
+package main
+
+// smcTracker reports execution of words that the program itself has
+// overwritten (self-modifying code).
+type smcTracker struct {
+	BaseObserver
+	strict  bool
+	pc      int
+	writers map[int]int
+}
+
+func newSmcTracker(strict bool) *smcTracker {
+	return &smcTracker{strict: strict, writers: make(map[int]int)}
+}
+
+func (t *smcTracker) BeforeStep(c *Comet2, pc int) {
+	t.pc = pc
+
+	_, _, size := parse(c.Memory, c.State)
+	for addr := pc; addr < pc+size; addr++ {
+		writer, ok := t.writers[addr]
+		if !ok {
+			continue
+		}
+		delete(t.writers, addr)
+
+		err := runtimeError(RUNTIME_MODIFIED_INSTRUCTION, addr,
+			"Executing modified instruction at %s (written by %s%s)",
+			c.formatAddr(addr), c.formatAddr(writer), c.locate(writer))
+		if t.strict {
+			c.Halt(err)
+			return
+		}
+		c.warn(err.Error())
+	}
+}
+
+func (t *smcTracker) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	if old != val {
+		t.writers[addr] = t.pc
+	}
+}
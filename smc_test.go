@@ -0,0 +1,55 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+)
+
+func TestSmcTracker(t *testing.T) {
+	src := `MAIN	START
+	LD	GR1,NEW
+	ST	GR1,PATCH+1
+PATCH	LAD	GR2,5
+	RET
+NEW	DC	9
+	END
+`
+	c := assembleSource(t, src)
+	c.AddObserver(newSmcTracker(true))
+	var rerr *RuntimeError
+	if err := c.Run(); !errors.As(err, &rerr) || rerr.Kind != RUNTIME_MODIFIED_INSTRUCTION || rerr.PC != 5 {
+		t.Fatalf("Expected a modified instruction at #0005, got %v", err)
+	}
+	if c.State[PC] != 4 || c.State[GR2] != 0 {
+		t.Errorf("Expected the patched LAD not to run, got PC #%s, GR2 %d", hex(c.State[PC], 4), c.State[GR2])
+	}
+
+	// Without error the patched instruction runs after the report
+	c = assembleSource(t, src)
+	c.AddObserver(newSmcTracker(false))
+	var warnings []string
+	c.Warn = func(msg string) { warnings = append(warnings, msg) }
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if c.State[GR2] != 9 {
+		t.Errorf("Expected the patched LAD to load 9, got %d", c.State[GR2])
+	}
+	if len(warnings) != 1 || !strings.Contains(warnings[0], "Executing modified instruction at #0005") {
+		t.Errorf("Expected a warning for #0005, got %v", warnings)
+	}
+
+	// Quiet drops the report like any other diagnostic
+	c = assembleSource(t, src)
+	c.AddObserver(newSmcTracker(false))
+	c.Quiet = true
+	warnings = nil
+	c.Warn = func(msg string) { warnings = append(warnings, msg) }
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if len(warnings) != 0 {
+		t.Errorf("Expected no warning when quiet, got %v", warnings)
+	}
+}
@@ -0,0 +1,85 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"path/filepath"
+	"regexp"
+	"strings"
+)
+
+// SourceProvider loads the source files the assembler reads: the program
+// itself and the files of INCLUDE 'NAME'. The assembler never reads files
+// directly, so an editor can assemble unsaved buffers and a sandboxed
+// grader can keep submissions in memory.
+type SourceProvider interface {
+	// ReadSource returns the text of the file name.
+	ReadSource(name string) (string, error)
+}
+
+// FileSources reads sources from the file system. It is the provider of
+// newAssemblerState.
+type FileSources struct{}
+
+func (FileSources) ReadSource(name string) (string, error) {
+	data, err := os.ReadFile(name)
+	if err != nil {
+		return "", err
+	}
+	return string(data), nil
+}
+
+// MemorySources serves sources from memory, keyed by file name as INCLUDE
+// resolves it.
+type MemorySources map[string]string
+
+func (m MemorySources) ReadSource(name string) (string, error) {
+	source, ok := m[filepath.Clean(name)]
+	if !ok {
+		return "", fmt.Errorf("open %s: no such file", name)
+	}
+	return source, nil
+}
+
+// INCLUDE directive for a source file
+var includeFilePattern = regexp.MustCompile(`^\s+INCLUDE\s+'([^']+)'\s*$`)
+
+// includeFiles blanks out INCLUDE 'NAME' lines and appends the named files
+// after the user's source, like the library programs, each once. NAME is
+// relative to the directory of the including file. Included files may
+// include others and are preprocessed with the same definitions.
+func includeFiles(lines []SourceLine, defines map[string]string, sources SourceProvider) ([]SourceLine, error) {
+	included := make(map[string]bool)
+	for _, l := range lines {
+		included[filepath.Clean(l.File)] = true
+	}
+
+	for i := 0; i < len(lines); i++ {
+		line := lines[i]
+		code := line.Text
+		if idx := strings.Index(code, ";"); idx >= 0 {
+			code = code[:idx]
+		}
+		matches := includeFilePattern.FindStringSubmatch(code)
+		if matches == nil {
+			continue
+		}
+		lines[i].Text = ""
+
+		path := filepath.Join(filepath.Dir(line.File), matches[1])
+		if included[path] {
+			continue
+		}
+		included[path] = true
+		source, err := sources.ReadSource(path)
+		if err != nil {
+			return nil, &AssembleError{File: line.File, Line: line.Line, Msg: fmt.Sprintf("Cannot include \"%s\": %v", matches[1], err)}
+		}
+		added := sourceLines(path, source)
+		if err := assembleConditionals(added, defines); err != nil {
+			return nil, err
+		}
+		lines = append(lines, added...)
+	}
+	return lines, nil
+}
@@ -0,0 +1,37 @@
+package main
+
+import (
+	"errors"
+	"testing"
+)
+
+func TestIncludeMemorySources(t *testing.T) {
+	sources := MemorySources{
+		"main.cas":     "MAIN\tSTART\n\tCALL\tSUB\n\tRET\n\tEND\n\tINCLUDE\t'lib/sub.cas'\n",
+		"lib/sub.cas":  "SUB\tSTART\n\tCALL\tUTIL\n\tRET\n\tEND\n\tINCLUDE\t'util.cas'\n\tINCLUDE\t'util.cas'\n",
+		"lib/util.cas": "UTIL\tSTART\n\tLAD\tGR0,7\n\tRET\n\tEND\n",
+		"bad.cas":      "MAIN\tSTART\n\tRET\n\tEND\n\tINCLUDE\t'nope.cas'\n",
+	}
+	asmState := newAssemblerState()
+	asmState.sources = sources
+	obj, err := assemble("main.cas", asmState)
+	if err != nil {
+		t.Fatalf("assemble failed: %v", err)
+	}
+	c := LoadObject(obj)
+	c.Output = nil
+	c.Run()
+	if c.State[GR0] != 7 {
+		t.Errorf("Expected the included units to be linked, got GR0 %d", c.State[GR0])
+	}
+	if loc := obj.SourceMap[obj.Symbols["UTIL:UTIL"]]; loc.File != "lib/util.cas" || loc.Line != 2 {
+		t.Errorf("Expected UTIL at lib/util.cas line 2, got %+v", loc)
+	}
+
+	asmState = newAssemblerState()
+	asmState.sources = sources
+	var aerr *AssembleError
+	if _, err := assemble("bad.cas", asmState); !errors.As(err, &aerr) || aerr.File != "bad.cas" || aerr.Line != 4 {
+		t.Errorf("Expected an error at bad.cas line 4, got %v", err)
+	}
+}
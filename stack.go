@@ -0,0 +1,151 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"strings"
+)
+
+// Maximum number of stack words kept per snapshot
+const STACK_SNAPSHOT_WORDS = 32
+
+// stackWord is one word of the stack with a note on where it came from.
+type stackWord struct {
+	Addr int
+	Val  int
+	Note string
+}
+
+// stackSnapshot is the stack right after a CALL or RET.
+type stackSnapshot struct {
+	Step  int
+	Event string
+	SP    int
+	Words []stackWord
+}
+
+// stackRecorder records the stack at every CALL and RET so the frames can
+// be rendered over time.
+type stackRecorder struct {
+	BaseObserver
+	inst    string
+	note    string
+	notes   map[int]string
+	history []stackSnapshot
+}
+
+func newStackRecorder() *stackRecorder {
+	return &stackRecorder{notes: make(map[int]string)}
+}
+
+func (r *stackRecorder) BeforeStep(c *Comet2, pc int) {
+	r.inst, _, _ = parse(c.Memory, c.State)
+
+	word := memGet(c.Memory, pc)
+	xr := word & 0x7
+	adr := memGet(c.Memory, pc+1)
+
+	switch r.inst {
+	case "CALL":
+		r.note = fmt.Sprintf("return to #%s", hex(pc+2, 4))
+	case "PUSH":
+		if xr > 0 && adr == 0 {
+			r.note = fmt.Sprintf("saved GR%d", xr)
+		} else if xr > 0 {
+			r.note = fmt.Sprintf("pushed #%s+GR%d", hex(adr, 4), xr)
+		} else {
+			r.note = fmt.Sprintf("pushed #%s", hex(adr, 4))
+		}
+	default:
+		r.note = "local"
+	}
+}
+
+func (r *stackRecorder) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	if addr >= c.State[SP]-1 && addr < c.StackTop {
+		r.notes[addr] = r.note
+	}
+}
+
+func (r *stackRecorder) AfterStep(c *Comet2, pc int) {
+	var event string
+	switch r.inst {
+	case "CALL":
+		event = fmt.Sprintf("CALL #%s (from #%s)", hex(c.State[PC], 4), hex(pc, 4))
+	case "RET":
+		event = fmt.Sprintf("RET to #%s (from #%s)", hex(c.State[PC], 4), hex(pc, 4))
+	default:
+		return
+	}
+
+	snap := stackSnapshot{Step: c.Steps, Event: event, SP: c.State[SP]}
+	for addr := c.State[SP]; addr < c.StackTop && len(snap.Words) < STACK_SNAPSHOT_WORDS; addr++ {
+		snap.Words = append(snap.Words, stackWord{addr, memGet(c.Memory, addr), r.notes[addr]})
+	}
+	r.history = append(r.history, snap)
+}
+
+// render draws every snapshot as an ASCII stack, top of stack first.
+func (r *stackRecorder) render() string {
+	var b strings.Builder
+	for _, snap := range r.history {
+		fmt.Fprintf(&b, "=== step %d: %s ===\n", snap.Step, snap.Event)
+		fmt.Fprintf(&b, "SP=#%s\n", hex(snap.SP, 4))
+		if len(snap.Words) == 0 {
+			b.WriteString("  (empty)\n")
+		}
+		b.WriteString("  +-------+------+\n")
+		for _, w := range snap.Words {
+			fmt.Fprintf(&b, "  | #%s | %s | %s\n", hex(w.Addr, 4), hex(w.Val, 4), w.Note)
+		}
+		b.WriteString("  +-------+------+\n\n")
+	}
+	return b.String()
+}
+
+// writeStackHistory saves the rendered history to filepath.
+func (r *stackRecorder) writeStackHistory(filepath string) error {
+	if err := os.WriteFile(filepath, []byte(r.render()), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write stack history: %v", err)
+	}
+	return nil
+}
+
+// stackTracer prints every PUSH, POP, CALL and RET for -trace-stack with
+// SP before and after and the word pushed or popped, and nothing else.
+type stackTracer struct {
+	BaseObserver
+	print  func(text string)
+	inst   string
+	opr    string
+	sp     int
+	popped int
+}
+
+func newStackTracer(print func(text string)) *stackTracer {
+	return &stackTracer{print: print}
+}
+
+func (t *stackTracer) BeforeStep(c *Comet2, pc int) {
+	t.inst, t.opr, _ = parse(c.Memory, c.State)
+	t.sp = c.State[SP]
+	t.popped = memGet(c.Memory, t.sp)
+}
+
+func (t *stackTracer) AfterStep(c *Comet2, pc int) {
+	sp := c.State[SP]
+	var word string
+	switch t.inst {
+	case "PUSH":
+		word = fmt.Sprintf("pushed #%s at #%s", hex(memGet(c.Memory, sp), 4), hex(sp, 4))
+	case "CALL":
+		word = fmt.Sprintf("pushed return address %s at #%s", c.formatAddr(memGet(c.Memory, sp)), hex(sp, 4))
+	case "POP":
+		word = fmt.Sprintf("popped #%s from #%s", hex(t.popped, 4), hex(t.sp, 4))
+	case "RET":
+		word = fmt.Sprintf("popped return address %s from #%s", c.formatAddr(t.popped), hex(t.sp, 4))
+	default:
+		return
+	}
+	t.print(fmt.Sprintf("%s %s: SP #%s -> #%s, %s", c.formatAddr(pc), strings.TrimSpace(t.inst+" "+t.opr), hex(t.sp, 4), hex(sp, 4), word))
+}
@@ -0,0 +1,78 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestTraceStack(t *testing.T) {
+	var lines []string
+	c := NewComet2Builder().WithObject(assembleObject(t, `MAIN	START
+	LAD	GR1,5
+	PUSH	0,GR1
+	CALL	SUB
+	POP	GR2
+	RET
+SUB	RET
+	END
+`)).WithObserver(newStackTracer(func(text string) { lines = append(lines, text) })).Build()
+	c.Run()
+
+	top := c.StackTop
+	sp := func(n int) string { return "#" + hex(top-n, 4) }
+	want := []string{
+		"#0002 <MAIN+2> PUSH #0000, GR1: SP " + sp(0) + " -> " + sp(1) + ", pushed #0005 at " + sp(1),
+		"#0004 <MAIN+4> CALL #0008: SP " + sp(1) + " -> " + sp(2) + ", pushed return address #0006 <MAIN+6> at " + sp(2),
+		"#0008 <SUB> RET: SP " + sp(2) + " -> " + sp(1) + ", popped return address #0006 <MAIN+6> from " + sp(2),
+		"#0006 <MAIN+6> POP GR2: SP " + sp(1) + " -> " + sp(0) + ", popped #0005 from " + sp(1),
+	}
+	if !reflect.DeepEqual(lines, want) {
+		t.Errorf("Expected\n%s\ngot\n%s", strings.Join(want, "\n"), strings.Join(lines, "\n"))
+	}
+}
+
+func TestStackHistory(t *testing.T) {
+	r := newStackRecorder()
+	c := NewComet2Builder().WithObject(assembleObject(t, `MAIN	START
+	LAD	GR1,5
+	PUSH	0,GR1
+	CALL	SUB
+	POP	GR2
+	RET
+SUB	RET
+	END
+`)).WithObserver(r).Build()
+	c.Run()
+
+	top := c.StackTop
+	want := []stackSnapshot{
+		{Step: 3, Event: "CALL #0008 (from #0004)", SP: top - 2, Words: []stackWord{
+			{top - 2, 6, "return to #0006"},
+			{top - 1, 5, "saved GR1"},
+		}},
+		{Step: 4, Event: "RET to #0006 (from #0008)", SP: top - 1, Words: []stackWord{
+			{top - 1, 5, "saved GR1"},
+		}},
+	}
+	if len(r.history) < 2 || !reflect.DeepEqual(r.history[:2], want) {
+		t.Fatalf("Expected frames %+v, got %+v", want, r.history)
+	}
+
+	path := filepath.Join(t.TempDir(), "stack.txt")
+	if err := r.writeStackHistory(path); err != nil {
+		t.Fatal(err)
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		t.Fatal(err)
+	}
+	frame := "=== step 3: CALL #0008 (from #0004) ===\nSP=#" + hex(top-2, 4) + "\n  +-------+------+\n" +
+		"  | #" + hex(top-2, 4) + " | 0006 | return to #0006\n" +
+		"  | #" + hex(top-1, 4) + " | 0005 | saved GR1\n  +-------+------+\n\n"
+	if !strings.HasPrefix(string(data), frame) {
+		t.Errorf("Expected the history to start with\n%s\ngot\n%s", frame, data)
+	}
+}
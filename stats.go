@@ -0,0 +1,117 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// statsCollector counts executed instructions, memory accesses and SVC
+// calls for -stats. With a cost table it also adds up the cost of the
+// instructions for -cost.
+type statsCollector struct {
+	BaseObserver
+	counts map[string]int
+	reads  int
+	writes int
+	svcs   int
+
+	cost      *costTable
+	costs     map[string]int
+	costTotal int
+}
+
+func newStatsCollector() *statsCollector {
+	return &statsCollector{counts: make(map[string]int), costs: make(map[string]int)}
+}
+
+func (s *statsCollector) BeforeStep(c *Comet2, pc int) {
+	inst, _, _ := parse(c.Memory, c.State)
+	s.counts[inst]++
+	if s.cost != nil {
+		s.costOf(c, pc, inst)
+	}
+}
+
+func (s *statsCollector) MemoryRead(c *Comet2, addr int, val int) {
+	s.reads++
+}
+
+func (s *statsCollector) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	s.writes++
+}
+
+func (s *statsCollector) Svc(c *Comet2, entry int) {
+	s.svcs++
+}
+
+// instructionCount is one row of the histogram.
+type instructionCount struct {
+	Inst    string  `json:"inst"`
+	Count   int     `json:"count"`
+	Percent float64 `json:"percent"`
+}
+
+// statsReport is the JSON form of the statistics.
+type statsReport struct {
+	Instructions []instructionCount `json:"instructions"`
+	Total        int                `json:"total"`
+	MemoryReads  int                `json:"memoryReads"`
+	MemoryWrites int                `json:"memoryWrites"`
+	SvcCalls     int                `json:"svcCalls"`
+	Cost         *costReport        `json:"cost,omitempty"`
+}
+
+// report builds the histogram, most executed instruction first.
+func (s *statsCollector) report() statsReport {
+	r := statsReport{MemoryReads: s.reads, MemoryWrites: s.writes, SvcCalls: s.svcs}
+	for inst, n := range s.counts {
+		r.Instructions = append(r.Instructions, instructionCount{Inst: inst, Count: n})
+		r.Total += n
+	}
+	sort.Slice(r.Instructions, func(i, j int) bool {
+		a, b := r.Instructions[i], r.Instructions[j]
+		if a.Count != b.Count {
+			return a.Count > b.Count
+		}
+		return a.Inst < b.Inst
+	})
+	for i := range r.Instructions {
+		r.Instructions[i].Percent = 100 * float64(r.Instructions[i].Count) / float64(r.Total)
+	}
+	if s.cost != nil {
+		r.Cost = s.costReport()
+	}
+	return r
+}
+
+// render formats the statistics as text or JSON.
+func (s *statsCollector) render(format string) (string, error) {
+	r := s.report()
+	switch format {
+	case "json":
+		data, err := json.MarshalIndent(r, "", "  ")
+		if err != nil {
+			return "", fmt.Errorf("[COMET2 ERROR] Cannot encode statistics: %v", err)
+		}
+		return string(data) + "\n", nil
+	case "text":
+		var b strings.Builder
+		b.WriteString("Inst      Count       %\n")
+		for _, row := range r.Instructions {
+			fmt.Fprintf(&b, "%-6s %8d  %6.2f\n", row.Inst, row.Count, row.Percent)
+		}
+		fmt.Fprintf(&b, "Total  %8d\n", r.Total)
+		fmt.Fprintf(&b, "Memory reads: %d, writes: %d, SVC calls: %d\n", r.MemoryReads, r.MemoryWrites, r.SvcCalls)
+		if r.Cost != nil {
+			b.WriteString("Label        Cost\n")
+			for _, row := range r.Cost.Labels {
+				fmt.Fprintf(&b, "%-8s %8d\n", row.Label, row.Cost)
+			}
+			fmt.Fprintf(&b, "Total cost %6d\n", r.Cost.Total)
+		}
+		return b.String(), nil
+	}
+	return "", fmt.Errorf("[COMET2 ERROR] Invalid -stats format \"%s\"", format)
+}
@@ -0,0 +1,30 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestStatsCollector(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,1
+	LAD	GR2,2
+	ADDA	GR1,GR2
+	ST	GR1,VAR
+	RET
+VAR	DS	1
+	END
+`)
+	stats := newStatsCollector()
+	c.AddObserver(stats)
+	if err := c.Run(); err == nil {
+		t.Fatalf("Expected program to finish")
+	}
+
+	r := stats.report()
+	if r.Total != 5 || r.Instructions[0].Inst != "LAD" || r.Instructions[0].Count != 2 {
+		t.Errorf("Unexpected histogram: %+v", r)
+	}
+	if r.MemoryWrites != 1 || r.MemoryReads != 1 {
+		t.Errorf("Expected 1 read (RET) and 1 write, got %d and %d", r.MemoryReads, r.MemoryWrites)
+	}
+}
@@ -0,0 +1,213 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+)
+
+// Bundled library subroutines, linked with INCLUDE <STD/NAME> or -with-stdlib.
+// Each one is a separate START/END program, so its labels are scoped and
+// do not clash with the user's program.
+var stdlib = map[string]string{
+	// OUTNUM: write GR1 as a signed decimal number with OUT.
+	// All registers are preserved.
+	"OUTNUM": `OUTNUM	START
+	RPUSH
+	LD	GR2,GR1
+	LAD	GR4,0
+	LD	GR2,GR2
+	JPL	CONV
+	JZE	CONV
+	LAD	GR4,1
+	XOR	GR2,ALLONE
+	LAD	GR2,1,GR2
+CONV	LAD	GR3,6
+LOOP	LD	GR5,GR2
+	DIVL	GR5,TEN
+	LD	GR6,GR5
+	MULL	GR6,TEN
+	LD	GR7,GR2
+	SUBL	GR7,GR6
+	ADDL	GR7,ZERO
+	LAD	GR3,-1,GR3
+	ST	GR7,BUF,GR3
+	LD	GR2,GR5
+	JNZ	LOOP
+	LD	GR4,GR4
+	JZE	COPYI
+	LAD	GR3,-1,GR3
+	LD	GR7,MINUS
+	ST	GR7,BUF,GR3
+COPYI	LAD	GR6,0
+COPY	CPA	GR3,SIX
+	JZE	PRINT
+	LD	GR7,BUF,GR3
+	ST	GR7,OBUF,GR6
+	LAD	GR3,1,GR3
+	LAD	GR6,1,GR6
+	JUMP	COPY
+PRINT	ST	GR6,OLEN
+	OUT	OBUF,OLEN
+	RPOP
+	RET
+ALLONE	DC	#FFFF
+TEN	DC	10
+ZERO	DC	48
+SIX	DC	6
+MINUS	DC	45
+BUF	DS	6
+OBUF	DS	6
+OLEN	DS	1
+	END
+`,
+
+	// INNUM: read a signed decimal number with IN into GR0.
+	// GR1-GR7 are preserved.
+	"INNUM": `INNUM	START
+	RPUSH
+	IN	IBUF,ILEN
+	LAD	GR0,0
+	LAD	GR3,0
+	LAD	GR4,0
+	LD	GR5,ILEN
+	JZE	FIN
+	LD	GR6,IBUF
+	CPA	GR6,MINUS
+	JNZ	DIGIT
+	LAD	GR4,1
+	LAD	GR3,1
+DIGIT	CPA	GR3,GR5
+	JZE	SIGN
+	LD	GR6,IBUF,GR3
+	SUBA	GR6,ZERO
+	MULA	GR0,TEN
+	ADDA	GR0,GR6
+	LAD	GR3,1,GR3
+	JUMP	DIGIT
+SIGN	LD	GR4,GR4
+	JZE	FIN
+	LAD	GR6,0
+	SUBA	GR6,GR0
+	LD	GR0,GR6
+FIN	RPOP
+	RET
+TEN	DC	10
+ZERO	DC	48
+MINUS	DC	45
+IBUF	DS	256
+ILEN	DS	1
+	END
+`,
+
+	// STRCMP: compare the string at GR1 (length GR2) with the string at
+	// GR3 (length GR4). GR0 = 0 if equal, -1 if the first is smaller,
+	// 1 otherwise. GR1-GR7 are preserved.
+	"STRCMP": `STRCMP	START
+	RPUSH
+	LAD	GR5,0
+LOOP	CPL	GR5,GR2
+	JZE	EOS
+	CPL	GR5,GR4
+	JZE	GT
+	LD	GR6,GR1
+	ADDL	GR6,GR5
+	LD	GR6,0,GR6
+	LD	GR7,GR3
+	ADDL	GR7,GR5
+	LD	GR7,0,GR7
+	CPL	GR6,GR7
+	JMI	LT
+	JNZ	GT
+	LAD	GR5,1,GR5
+	JUMP	LOOP
+EOS	CPL	GR5,GR4
+	JZE	EQ
+LT	LAD	GR0,-1
+	JUMP	FIN
+GT	LAD	GR0,1
+	JUMP	FIN
+EQ	LAD	GR0,0
+FIN	RPOP
+	RET
+	END
+`,
+
+	// MULT: GR0 = GR1 * GR2 (lower 16 bits) using shifts and additions.
+	// GR1-GR7 are preserved.
+	"MULT": `MULT	START
+	RPUSH
+	LAD	GR0,0
+	LD	GR3,GR1
+	LD	GR4,GR2
+LOOP	LD	GR4,GR4
+	JZE	FIN
+	LD	GR5,GR4
+	AND	GR5,ONE
+	JZE	NEXT
+	ADDL	GR0,GR3
+NEXT	SLL	GR3,1
+	SRL	GR4,1
+	JUMP	LOOP
+FIN	RPOP
+	RET
+ONE	DC	1
+	END
+`,
+}
+
+// INCLUDE directive for the bundled library
+var includeStdPattern = regexp.MustCompile(`^\s+INCLUDE\s+<STD/([0-9A-Za-z]+)>\s*$`)
+
+// stdlibNames returns the names of all bundled subroutines in sorted order.
+func stdlibNames() []string {
+	names := make([]string, 0, len(stdlib))
+	for name := range stdlib {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// expandIncludes blanks out INCLUDE <STD/NAME> lines and appends the
+// requested library programs after the user's source. The library lines
+// keep their own origin, "<STD/NAME>" and their line in the program.
+func expandIncludes(lines []SourceLine) ([]SourceLine, error) {
+	var modules []string
+	if *optStdlib {
+		modules = stdlibNames()
+	}
+
+	for i, line := range lines {
+		code := line.Text
+		if idx := strings.Index(code, ";"); idx >= 0 {
+			code = code[:idx]
+		}
+
+		matches := includeStdPattern.FindStringSubmatch(code)
+		if matches == nil {
+			continue
+		}
+
+		name := strings.ToUpper(matches[1])
+		if _, ok := stdlib[name]; !ok {
+			return nil, &AssembleError{File: line.File, Line: line.Line, Msg: fmt.Sprintf("Unknown library \"<STD/%s>\"", matches[1])}
+		}
+		modules = append(modules, name)
+		lines[i].Text = ""
+	}
+
+	linked := make(map[string]bool)
+	for _, name := range modules {
+		if linked[name] {
+			continue
+		}
+		linked[name] = true
+		for j, text := range strings.Split(stdlib[name], "\n") {
+			lines = append(lines, SourceLine{Text: text, File: "<STD/" + name + ">", Line: j + 1})
+		}
+	}
+
+	return lines, nil
+}
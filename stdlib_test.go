@@ -0,0 +1,31 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestStdlibOutnum(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,-123
+	CALL	OUTNUM
+	LAD	GR1,0
+	CALL	OUTNUM
+	RET
+	INCLUDE	<STD/OUTNUM>
+	END
+`)
+
+	var outputs []string
+	c.Output = func(text string) {
+		outputs = append(outputs, text)
+	}
+
+	err := c.Run()
+	if err == nil || !strings.Contains(err.Error(), "Program finished") {
+		t.Fatalf("Expected program to finish, got %v", err)
+	}
+	if strings.Join(outputs, ",") != "-123,0" {
+		t.Errorf("Unexpected outputs: %q", outputs)
+	}
+}
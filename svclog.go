@@ -0,0 +1,111 @@
+package main
+
+import (
+	"fmt"
+	"os"
+	"strconv"
+	"strings"
+)
+
+// Meaning of the exit codes of SVC
+var svcExitNames = map[int]string{
+	EXIT_USR: "normal exit",
+	EXIT_OVF: "overflow",
+	EXIT_DVZ: "division by zero",
+	EXIT_ROV: "range over",
+}
+
+// svcCall is one SVC executed by the program, with the registers it
+// takes its arguments from.
+type svcCall struct {
+	Step   int
+	PC     int
+	Entry  int
+	Buffer int
+	LenAt  int
+	// Handled is set when an SvcHandler replaced the entry.
+	Handled bool
+	// Io is the index in the transcript of the line read or written.
+	Io int
+}
+
+// svcLogger records every SVC for -svc-log, so the I/O of a program can
+// be audited apart from an instruction trace.
+type svcLogger struct {
+	BaseObserver
+	calls []svcCall
+}
+
+func newSvcLogger() *svcLogger {
+	return &svcLogger{}
+}
+
+func (l *svcLogger) Svc(c *Comet2, entry int) {
+	_, handled := c.SvcHandlers[entry]
+	l.calls = append(l.calls, svcCall{
+		Step:    c.Steps + 1,
+		PC:      c.State[PC],
+		Entry:   entry,
+		Buffer:  c.State[GR1],
+		LenAt:   c.State[GR2],
+		Handled: handled,
+		Io:      len(c.Transcript),
+	})
+}
+
+// describe decodes call: the buffer and length word of IN and OUT with the
+// line read or written, or the meaning of an exit code.
+func (l *svcLogger) describe(c *Comet2, call svcCall) string {
+	line := func(kind IoEventKind) (IoEvent, bool) {
+		if call.Io < len(c.Transcript) && c.Transcript[call.Io].Kind == kind {
+			return c.Transcript[call.Io], true
+		}
+		return IoEvent{}, false
+	}
+	args := fmt.Sprintf("buffer %s, length %s", c.formatAddr(call.Buffer), c.formatAddr(call.LenAt))
+
+	switch {
+	case call.Handled:
+		return fmt.Sprintf("SVC #%s (host handler)", hex(call.Entry, 4))
+	case call.Entry == SYS_IN:
+		ev, ok := line(IO_IN)
+		if !ok {
+			return fmt.Sprintf("IN   %s (max %d): no input", args, IN_MAX_LENGTH)
+		}
+		text := fmt.Sprintf("IN   %s (max %d): %s (%d characters)", args, IN_MAX_LENGTH, strconv.Quote(ev.Text), len(ev.Text))
+		if ev.Truncated > 0 {
+			text += fmt.Sprintf(", %d dropped", ev.Truncated)
+		}
+		if ev.Synthesized {
+			text += ", default after timeout"
+		}
+		return text
+	case call.Entry == SYS_OUT:
+		ev, ok := line(IO_OUT)
+		if !ok {
+			return fmt.Sprintf("OUT  %s: not written", args)
+		}
+		return fmt.Sprintf("OUT  %s: %s (%d characters)", args, strconv.Quote(ev.Text), len(ev.Text))
+	}
+	if name, ok := svcExitNames[call.Entry]; ok {
+		return fmt.Sprintf("EXIT %d (%s)", call.Entry, name)
+	}
+	return fmt.Sprintf("SVC #%s (no such entry, ignored)", hex(call.Entry, 4))
+}
+
+// render lists the calls, one per line.
+func (l *svcLogger) render(c *Comet2) string {
+	var b strings.Builder
+	for _, call := range l.calls {
+		fmt.Fprintf(&b, "step %d  %s  %s\n", call.Step, c.formatAddr(call.PC), l.describe(c, call))
+	}
+	return b.String()
+}
+
+// writeSvcLog saves the rendered log to filepath.
+func (l *svcLogger) writeSvcLog(filepath string, c *Comet2) error {
+	if err := os.WriteFile(filepath, []byte(l.render(c)), 0644); err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot write SVC log: %v", err)
+	}
+	return nil
+}
@@ -0,0 +1,35 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestSvcLog(t *testing.T) {
+	obj := assembleObject(t, echoProgram)
+	c := LoadObject(obj)
+	c.Output = nil
+	c.Input = NewInputLines([]string{"abc"})
+	log := newSvcLogger()
+	c.AddObserver(log)
+	c.Run()
+
+	lines := strings.Split(strings.TrimSuffix(log.render(c), "\n"), "\n")
+	if len(lines) != 2 ||
+		!strings.Contains(lines[0], "IN   buffer ") || !strings.Contains(lines[0], "<BUF>") || !strings.HasSuffix(lines[0], `(max 256): "abc" (3 characters)`) ||
+		!strings.Contains(lines[1], "OUT  buffer ") || !strings.HasSuffix(lines[1], `: "abc" (3 characters)`) {
+		t.Errorf("Unexpected SVC log:\n%s", strings.Join(lines, "\n"))
+	}
+	buf, _ := lookupSymbol(obj, "BUF")
+	if ev := c.Transcript[0]; ev.Buffer != buf || ev.PC != log.calls[0].PC {
+		t.Errorf("Expected the IN event to record the SVC at #%s and BUF, got %+v", hex(log.calls[0].PC, 4), ev)
+	}
+
+	c = assembleSource(t, "MAIN\tSTART\n\tSVC\t2\n\tEND\n")
+	log = newSvcLogger()
+	c.AddObserver(log)
+	c.Run()
+	if text := log.render(c); !strings.HasSuffix(text, "EXIT 2 (division by zero)\n") {
+		t.Errorf("Expected the exit code to be decoded, got %q", text)
+	}
+}
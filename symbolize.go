@@ -0,0 +1,89 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// symbolAddr is an entry of the address-sorted symbol table.
+type symbolAddr struct {
+	name string
+	addr int
+}
+
+// Symbolizer names addresses after the nearest label at or before them,
+// for every message that shows an address of the program: traces, dumps,
+// monitor output and runtime errors.
+type Symbolizer struct {
+	symbols []symbolAddr
+	size    int
+}
+
+// NewSymbolizer builds a symbolizer over symbols, as in Object.Symbols, for
+// a program of size words. Addresses outside the program have no label.
+func NewSymbolizer(symbols map[string]int, size int) *Symbolizer {
+	s := &Symbolizer{size: size}
+	for name, a := range symbols {
+		if idx := strings.LastIndex(name, ":"); idx >= 0 {
+			name = name[idx+1:]
+		}
+		s.symbols = append(s.symbols, symbolAddr{name, a})
+	}
+	sort.Slice(s.symbols, func(i, j int) bool {
+		a, b := s.symbols[i], s.symbols[j]
+		if a.addr != b.addr {
+			return a.addr < b.addr
+		}
+		return a.name < b.name
+	})
+	return s
+}
+
+// Label returns the nearest label at or before addr within the program,
+// as "LABEL" or "LABEL+n", or "" when there is none.
+func (s *Symbolizer) Label(addr int) string {
+	if addr < 0 || addr >= s.size {
+		return ""
+	}
+	i := sort.Search(len(s.symbols), func(i int) bool {
+		return s.symbols[i].addr > addr
+	})
+	if i == 0 {
+		return ""
+	}
+	sym := s.symbols[i-1]
+	if sym.addr == addr {
+		return sym.name
+	}
+	return fmt.Sprintf("%s+%d", sym.name, addr-sym.addr)
+}
+
+// Format renders an address as "#0012 <LOOP+2>", or "#0012" when no label
+// is near. The offset is decimal, so the label can be typed back as an
+// address.
+func (s *Symbolizer) Format(addr int) string {
+	if label := s.Label(addr); label != "" {
+		return fmt.Sprintf("#%s <%s>", hex(addr, 4), label)
+	}
+	return "#" + hex(addr, 4)
+}
+
+// symbolizer returns the symbolizer of the loaded program, building it on
+// first use.
+func (c *Comet2) symbolizer() *Symbolizer {
+	if c.symbols == nil {
+		c.symbols = NewSymbolizer(c.Symbols, c.AddressMax)
+	}
+	return c.symbols
+}
+
+// labelAt returns the nearest label at or before addr within the program.
+func (c *Comet2) labelAt(addr int) string {
+	return c.symbolizer().Label(addr)
+}
+
+// formatAddr renders an address with the nearest label.
+func (c *Comet2) formatAddr(addr int) string {
+	return c.symbolizer().Format(addr)
+}
@@ -0,0 +1,29 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestSymbolizer(t *testing.T) {
+	s := NewSymbolizer(map[string]int{"MAIN:MAIN": 0, "MAIN:LOOP": 2, "MAIN:END": 2, "DATA": 8}, 10)
+	for addr, want := range map[int]string{
+		0:  "#0000 <MAIN>",
+		2:  "#0002 <END>",
+		5:  "#0005 <END+3>",
+		9:  "#0009 <DATA+1>",
+		10: "#000a",
+	} {
+		if got := s.Format(addr); got != want {
+			t.Errorf("Format(%d): expected %q, got %q", addr, want, got)
+		}
+	}
+	if got := NewSymbolizer(map[string]int{"A": 4}, 10).Label(3); got != "" {
+		t.Errorf("Expected no label before the first symbol, got %q", got)
+	}
+
+	c := assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,1\nLOOP\tPOP\tGR1\n\tEND\n")
+	if err := c.Run(); err == nil || !strings.Contains(err.Error(), "Stack underflow at #0002 <LOOP>:") {
+		t.Errorf("Expected a symbolized stack underflow, got %v", err)
+	}
+}
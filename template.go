@@ -0,0 +1,200 @@
+package main
+
+import (
+	"encoding/json"
+	"flag"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+// programTemplate is a skeleton created by "c2c2 new". {{NAME}} is
+// replaced with the program label; inputs go into the test spec.
+type programTemplate struct {
+	desc   string
+	inputs []string
+	source string
+}
+
+// Skeletons offered by "c2c2 new -template"
+var programTemplates = map[string]programTemplate{
+	"io": {
+		desc:   "read a line with IN and write it with OUT",
+		inputs: []string{"hello"},
+		source: `; {{NAME}}: read a line and write the result
+{{NAME}}	START
+	IN	BUF,LEN
+	; TODO: process the LEN characters in BUF
+	OUT	BUF,LEN
+	RET
+BUF	DS	256
+LEN	DS	1
+	END
+`,
+	},
+
+	"subroutine": {
+		desc: "main program calling a subroutine that saves registers with RPUSH/RPOP",
+		source: `; {{NAME}}: main program
+{{NAME}}	START
+	LAD	GR1,1
+	LAD	GR2,2
+	CALL	CALC
+	; TODO: use the result in GR0
+	RET
+	END
+; CALC: GR0 = result computed from GR1 and GR2
+; GR1-GR7 are preserved
+CALC	START
+	RPUSH
+	LD	GR0,GR1
+	ADDA	GR0,GR2		; TODO: replace with the real computation
+	RPOP
+	RET
+	END
+`,
+	},
+
+	"array": {
+		desc: "loop over an array of N words and print a result",
+		source: `; {{NAME}}: go through the N words at DATA
+{{NAME}}	START
+	LAD	GR1,0		; GR1 = index
+	LAD	GR2,0		; GR2 = result
+LOOP	CPA	GR1,N
+	JZE	FIN
+	ADDA	GR2,DATA,GR1	; TODO: process DATA[GR1]
+	LAD	GR1,1,GR1
+	JUMP	LOOP
+FIN	LD	GR1,GR2
+	CALL	OUTNUM
+	RET
+N	DC	5
+DATA	DC	3,1,4,1,5
+	INCLUDE	<STD/OUTNUM>
+	END
+`,
+	},
+}
+
+// templateNames returns the names of the templates in order.
+func templateNames() []string {
+	names := make([]string, 0, len(programTemplates))
+	for name := range programTemplates {
+		names = append(names, name)
+	}
+	sort.Strings(names)
+	return names
+}
+
+// programLabel derives the START label of a new program from its path.
+func programLabel(path string) (string, error) {
+	label := strings.ToUpper(strings.TrimSuffix(filepath.Base(path), filepath.Ext(path)))
+	if !labelPattern.MatchString(label) || registerPattern.MatchString(label) {
+		return "", fmt.Errorf("[CASL2 ERROR] \"%s\" cannot be used as a label", label)
+	}
+	return label, nil
+}
+
+// createProgram writes NAME.cas from the template, NAME.json with one test
+// for it and NAME.out with the output the skeleton gives, so that
+// "c2c2 test -spec NAME.json" passes until the program is changed. It
+// returns the created files.
+func createProgram(path string, tmplName string, force bool) ([]string, error) {
+	tmpl, ok := programTemplates[tmplName]
+	if !ok {
+		return nil, fmt.Errorf("[CASL2 ERROR] Unknown template \"%s\" (%s)", tmplName, strings.Join(templateNames(), ", "))
+	}
+	base := strings.TrimSuffix(path, ".cas")
+	label, err := programLabel(base)
+	if err != nil {
+		return nil, err
+	}
+	source := strings.ReplaceAll(tmpl.source, "{{NAME}}", label)
+
+	sourcePath := base + ".cas"
+	obj, err := assembleText(sourcePath, source, newAssemblerState())
+	if err != nil {
+		return nil, err
+	}
+	expected, _ := runTranscript(obj, tmpl.inputs, TEST_MAX_STEPS, TEST_TIMEOUT)
+
+	name := filepath.Base(base)
+	spec, err := json.MarshalIndent(map[string]TestCase{
+		name + ".cas": {Inputs: tmpl.inputs, Expect: name + ".out"},
+	}, "", "  ")
+	if err != nil {
+		return nil, err
+	}
+
+	files := []struct {
+		path    string
+		content string
+	}{
+		{sourcePath, source},
+		{base + ".json", string(spec) + "\n"},
+		{base + ".out", expected},
+	}
+	if !force {
+		for _, f := range files {
+			if _, err := os.Stat(f.path); err == nil {
+				return nil, fmt.Errorf("[CASL2 ERROR] %s already exists (use -force to overwrite)", f.path)
+			}
+		}
+	}
+	var created []string
+	for _, f := range files {
+		if err := os.WriteFile(f.path, []byte(f.content), 0644); err != nil {
+			return created, fmt.Errorf("[CASL2 ERROR] Cannot write file: %v", err)
+		}
+		created = append(created, f.path)
+	}
+	return created, nil
+}
+
+// runNewCommand implements "c2c2 new". It returns the exit status.
+func runNewCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("new", flag.ContinueOnError)
+	fs.SetOutput(w)
+	tmpl := fs.String("template", "io", "skeleton to start from: "+strings.Join(templateNames(), ", "))
+	force := fs.Bool("force", false, "overwrite existing files")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 new NAME [-template NAME] [-force]\n\nTemplates:\n")
+		for _, name := range templateNames() {
+			fmt.Fprintf(w, "  %-12s %s\n", name, programTemplates[name].desc)
+		}
+		fmt.Fprintf(w, "\nOptions:\n")
+		fs.PrintDefaults()
+	}
+
+	// The name may come before or after the options
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() == 0 {
+		fs.Usage()
+		return 2
+	}
+	path := fs.Arg(0)
+	if err := fs.Parse(fs.Args()[1:]); err != nil {
+		return 2
+	}
+	if fs.NArg() > 0 {
+		fs.Usage()
+		return 2
+	}
+
+	created, err := createProgram(path, *tmpl, *force)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+	for _, f := range created {
+		fmt.Fprintf(w, "Created %s\n", f)
+	}
+	fmt.Fprintf(w, "Run its test with: c2c2 test -spec %s\n", created[1])
+	return 0
+}
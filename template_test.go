@@ -0,0 +1,34 @@
+package main
+
+import (
+	"bytes"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestNewProgram(t *testing.T) {
+	dir := t.TempDir()
+	for _, name := range templateNames() {
+		base := filepath.Join(dir, "prog_"+name)
+		var out bytes.Buffer
+		if status := runNewCommand([]string{base, "-template", name}, &out); status != 0 {
+			t.Fatalf("%s: expected status 0, got %d\n%s", name, status, out.String())
+		}
+		// The generated test passes on the untouched skeleton
+		total, failed, err := runSpec(base+".json", &out, specOptions{jobs: 1})
+		if err != nil || total != 1 || failed != 0 {
+			t.Errorf("%s: expected the generated test to pass (%v)\n%s", name, err, out.String())
+		}
+	}
+
+	source, err := os.ReadFile(filepath.Join(dir, "prog_io.cas"))
+	if err != nil || !strings.Contains(string(source), "PROG_IO\tSTART") {
+		t.Errorf("Expected the label PROG_IO, got %s (%v)", source, err)
+	}
+	var out bytes.Buffer
+	if status := runNewCommand([]string{filepath.Join(dir, "prog_io")}, &out); status != 1 || !strings.Contains(out.String(), "already exists") {
+		t.Errorf("Expected existing files to be kept, got %d: %s", status, out.String())
+	}
+}
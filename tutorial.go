@@ -0,0 +1,107 @@
+package main
+
+import (
+	"bufio"
+	"fmt"
+	"os"
+	"strings"
+)
+
+// Program stepped through by "c2c2 tutorial"
+const TUTORIAL_SOURCE = `; Add two numbers, keep the sum and tell whether it is above LIMIT
+LESSON	START
+	LAD	GR1,5
+	LAD	GR2,7
+	ADDA	GR1,GR2
+	ST	GR1,RESULT
+	CPA	GR1,LIMIT
+	JPL	BIG
+	LAD	GR0,0
+	RET
+BIG	LAD	GR0,1
+	RET
+RESULT	DS	1
+LIMIT	DC	10
+	END
+`
+
+// tutorialStep is the note shown before one executed instruction. At a
+// checkpoint the user predicts its result instead of just pressing Enter.
+type tutorialStep struct {
+	note       string
+	checkpoint bool
+}
+
+// Notes for the instructions of TUTORIAL_SOURCE in execution order
+var tutorialSteps = []tutorialStep{
+	{note: "LAD puts the operand itself, here 5, into GR1. It does not read memory and leaves FR alone."},
+	{note: "Another LAD: what will GR2 hold?", checkpoint: true},
+	{note: "ADDA adds GR2 to GR1 as signed numbers and sets FR from the result: SF when negative, ZF when zero, OF on overflow.", checkpoint: true},
+	{note: "ST copies GR1 to the word labeled RESULT. Registers and FR stay the same."},
+	{note: "CPA compares GR1 with LIMIT (10) as signed numbers and only sets FR. FR reads as the bits OF SF ZF, so #0002 means SF=1.", checkpoint: true},
+	{note: "JPL jumps when the last result was positive, that is SF=0 and ZF=0. Watch PR."},
+	{note: "The jump was taken: GR0 = 1 tells the caller that the sum is above LIMIT."},
+	{note: "RET returns to the caller. At the top level this ends the program."},
+}
+
+// runTutorial walks through TUTORIAL_SOURCE one instruction at a time,
+// explaining each one and quizzing the user at the checkpoints. Answers
+// and Enter presses are read from scanner. It returns the quiz score.
+func runTutorial(scanner *bufio.Scanner) (correct int, asked int) {
+	obj, err := assembleText("tutorial.cas", TUTORIAL_SOURCE, newAssemblerState())
+	if err != nil {
+		cometPrint(err.Error())
+		return 0, 0
+	}
+	c := NewComet2Builder().
+		WithObject(obj).
+		WithObserver(&explainer{print: cometPrint}).
+		Build()
+
+	cometPrint("Welcome to the CASL2 tutorial. This is the program we will run:")
+	fmt.Print(TUTORIAL_SOURCE)
+	cometPrint("Each instruction is explained before it runs. Press Enter to execute it,")
+	cometPrint("or answer the question at a checkpoint (decimal or #hex). Enter \"q\" to stop.")
+
+	for i := 0; ; i++ {
+		step := tutorialStep{}
+		if i < len(tutorialSteps) {
+			step = tutorialSteps[i]
+		}
+		fmt.Println()
+		if step.note != "" {
+			cometPrint(step.note)
+		}
+
+		if step.checkpoint {
+			cmdPrint(c, []string{})
+			right, ok, err := quizAsk(c, scanner, os.Stdout)
+			if !ok {
+				break
+			}
+			asked++
+			if right {
+				correct++
+			}
+			if err != nil {
+				cometPrint(colorWhiteGreen(err.Error()))
+				break
+			}
+			continue
+		}
+
+		cmdPrint(c, []string{})
+		fmt.Print("[Enter] ")
+		if !scanner.Scan() || strings.TrimSpace(scanner.Text()) == "q" {
+			break
+		}
+		if err := quizStep(c); err != nil {
+			cometPrint(colorWhiteGreen(err.Error()))
+			break
+		}
+	}
+
+	fmt.Println()
+	cometPrint(fmt.Sprintf("Score: %d / %d", correct, asked))
+	return correct, asked
+}
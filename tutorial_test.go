@@ -0,0 +1,21 @@
+package main
+
+import (
+	"bufio"
+	"strings"
+	"testing"
+)
+
+func TestTutorial(t *testing.T) {
+	// Enter, three checkpoint answers (GR2, GR1, FR) and Enter to the end
+	answers := "\n7\n12\n\n0\n\n\n\n"
+	correct, asked := runTutorial(bufio.NewScanner(strings.NewReader(answers)))
+	if correct != 3 || asked != 3 {
+		t.Errorf("Expected a score of 3 / 3, got %d / %d", correct, asked)
+	}
+
+	correct, asked = runTutorial(bufio.NewScanner(strings.NewReader("\n5\nq\n")))
+	if correct != 0 || asked != 1 {
+		t.Errorf("Expected a score of 0 / 1, got %d / %d", correct, asked)
+	}
+}
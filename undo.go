@@ -0,0 +1,134 @@
+package main
+
+import "fmt"
+
+// Number of instructions the monitor can undo
+const UNDO_DEPTH = 16
+
+// memoryDelta is the old value of one word written by an instruction.
+type memoryDelta struct {
+	addr int
+	old  int
+}
+
+// undoEntry is what one instruction, or one monitor edit, changed: the
+// registers before it and the words it wrote, in order.
+type undoEntry struct {
+	state  []int
+	writes []memoryDelta
+	edit   bool
+}
+
+// undoLog keeps the changes made by the last few instructions so that the
+// monitor can revert them. Words written by IN count towards the SVC that
+// requested the input. OUT text already written stays written.
+type undoLog struct {
+	BaseObserver
+	depth   int
+	entries []undoEntry
+}
+
+func newUndoLog(depth int) *undoLog {
+	return &undoLog{depth: depth}
+}
+
+func (u *undoLog) BeforeStep(c *Comet2, pc int) {
+	// Reuse the oldest entry once the log is full
+	var e undoEntry
+	if len(u.entries) == u.depth {
+		e = u.entries[0]
+		copy(u.entries, u.entries[1:])
+		u.entries = u.entries[:len(u.entries)-1]
+	}
+	e.state = append(e.state[:0], c.State...)
+	e.writes = e.writes[:0]
+	e.edit = false
+	u.entries = append(u.entries, e)
+}
+
+// beginEdit starts an entry for a change made from the monitor, which
+// undo reverts like an instruction but without counting a step back.
+func (u *undoLog) beginEdit(c *Comet2) {
+	u.BeforeStep(c, c.State[PC])
+	u.entries[len(u.entries)-1].edit = true
+}
+
+func (u *undoLog) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	if n := len(u.entries); n > 0 {
+		u.entries[n-1].writes = append(u.entries[n-1].writes, memoryDelta{addr, old})
+	}
+}
+
+// undo reverts the last instruction recorded and reports whether there
+// was one.
+func (u *undoLog) undo(c *Comet2) bool {
+	// Entries from before a Reset no longer apply: keep the last Steps
+	// instructions and the edits made after the first of them
+	keep, steps := 0, 0
+	for i := len(u.entries) - 1; i >= 0; i-- {
+		if !u.entries[i].edit {
+			if steps == c.Steps {
+				break
+			}
+			steps++
+		}
+		keep++
+	}
+	u.entries = u.entries[len(u.entries)-keep:]
+	n := len(u.entries)
+	if n == 0 {
+		return false
+	}
+	e := u.entries[n-1]
+	u.entries = u.entries[:n-1]
+	for i := len(e.writes) - 1; i >= 0; i-- {
+		c.poke(e.writes[i].addr, e.writes[i].old)
+	}
+	copy(c.State, e.state)
+	c.InputMode = INPUT_MODE_CMD
+	c.LastFlagChange = nil
+	if !e.edit {
+		c.Steps--
+	}
+	return true
+}
+
+// findUndoLog returns the undo log observing c, or nil.
+func findUndoLog(c *Comet2) *undoLog {
+	var log *undoLog
+	for _, o := range c.observers {
+		if l, ok := o.(*undoLog); ok {
+			log = l
+		}
+	}
+	return log
+}
+
+// cmdUndo reverts the last N (default 1) instructions or monitor edits.
+func cmdUndo(c *Comet2, args []string) error {
+	log := findUndoLog(c)
+	if log == nil {
+		return fmt.Errorf("Undo is not available.")
+	}
+
+	count := 1
+	if len(args) > 0 {
+		n, ok := expandNumber(args[0])
+		if !ok || n < 1 {
+			return fmt.Errorf("Invalid count \"%s\".", args[0])
+		}
+		count = n
+	}
+	done := 0
+	for done < count && log.undo(c) {
+		done++
+	}
+	if done == 0 {
+		return fmt.Errorf("Nothing to undo (at most %d instructions or edits are kept).", UNDO_DEPTH)
+	}
+	cometPrint(fmt.Sprintf("Undid %d instructions or edits, PR=#%s.", done, hex(c.State[PC], 4)))
+	if verbosity.dump {
+		cmdPrint(c, []string{})
+	}
+	return nil
+}
@@ -0,0 +1,48 @@
+package main
+
+import (
+	"testing"
+)
+
+func TestUndo(t *testing.T) {
+	c := assembleSource(t, `MAIN	START
+	LAD	GR1,5
+	ST	GR1,A
+	LAD	GR1,7
+	ST	GR1,A
+	RET
+A	DS	1
+	END
+`)
+	if err := executeCommand("undo", nil, c); err == nil {
+		t.Errorf("Expected undo to need the undo log")
+	}
+	c.AddObserver(newUndoLog(3))
+	for i := 0; i < 4; i++ {
+		if _, err := c.stepExec(); err != nil {
+			t.Fatalf("step %d: %v", i, err)
+		}
+	}
+	if c.Memory[9] != 7 {
+		t.Fatalf("Expected A = 7, got %d", c.Memory[9])
+	}
+
+	if err := executeCommand("u", []string{"2"}, c); err != nil {
+		t.Fatalf("undo failed: %v", err)
+	}
+	if c.State[PC] != 4 || c.State[GR1] != 5 || c.Memory[9] != 5 || c.Steps != 2 {
+		t.Errorf("Expected the state after the first ST, got PC #%s GR1 %d A %d steps %d",
+			hex(c.State[PC], 4), c.State[GR1], c.Memory[9], c.Steps)
+	}
+
+	// Only the last 3 instructions were kept
+	if err := executeCommand("undo", []string{"5"}, c); err != nil {
+		t.Fatalf("undo failed: %v", err)
+	}
+	if c.State[PC] != 2 || c.Memory[9] != 0 {
+		t.Errorf("Expected to stop undoing at #0002, got PC #%s A %d", hex(c.State[PC], 4), c.Memory[9])
+	}
+	if err := executeCommand("undo", nil, c); err == nil {
+		t.Errorf("Expected nothing left to undo")
+	}
+}
@@ -0,0 +1,99 @@
+package main
+
+import (
+	"crypto/sha256"
+	"encoding/binary"
+	"flag"
+	"fmt"
+	"io"
+)
+
+// objectChecksum returns the SHA-256 of the start address and the memory
+// image of obj as "sha256:HEX". Symbols and source locations are not
+// covered, so they do not change the checksum of the same program.
+func objectChecksum(obj *Object) string {
+	h := sha256.New()
+	h.Write(encodeWords([]uint16{uint16(obj.Start)}, binary.BigEndian))
+	h.Write(encodeWords(obj.Bin, binary.BigEndian))
+	return fmt.Sprintf("sha256:%x", h.Sum(nil))
+}
+
+// verifyChecksum reports an object whose memory image does not match the
+// checksum stored when it was written. Objects without a checksum, from
+// older versions, are accepted.
+func verifyChecksum(obj *Object) error {
+	if obj.Checksum == "" || obj.Checksum == objectChecksum(obj) {
+		return nil
+	}
+	return fmt.Errorf("checksum mismatch, the object file was modified after it was written")
+}
+
+// runVerifyCommand implements "c2c2 verify". It prints the checksum,
+// version, entry point and units of an object file and, with -source,
+// checks that the source file assembles to the same image. It returns the
+// exit status.
+func runVerifyCommand(args []string, w io.Writer) int {
+	fs := flag.NewFlagSet("verify", flag.ContinueOnError)
+	fs.SetOutput(w)
+	source := fs.String("source", "", "check that the given casl2 source assembles to this object")
+	fs.Usage = func() {
+		fmt.Fprintf(w, "Usage: c2c2 verify [-source FILE.cas] FILE%s\n\nOptions:\n", OBJECT_EXT)
+		fs.PrintDefaults()
+	}
+	if err := fs.Parse(args); err != nil {
+		return 2
+	}
+	if fs.NArg() != 1 {
+		fs.Usage()
+		return 2
+	}
+
+	path := fs.Arg(0)
+	obj, err := readObjectFile(path)
+	if err != nil {
+		fmt.Fprintln(w, err)
+		return 1
+	}
+
+	status := 0
+	sum := objectChecksum(obj)
+	fmt.Fprintf(w, "File:     %s\n", path)
+	fmt.Fprintf(w, "Version:  %s\n", obj.Version)
+	switch {
+	case obj.Checksum == "":
+		fmt.Fprintf(w, "Checksum: %s (not recorded)\n", sum)
+	case obj.Checksum == sum:
+		fmt.Fprintf(w, "Checksum: %s (OK)\n", sum)
+	default:
+		fmt.Fprintf(w, "Checksum: %s (MISMATCH, recorded %s)\n", sum, obj.Checksum)
+		status = 1
+	}
+	entry := obj.Entry
+	if entry == "" {
+		entry = "-"
+	}
+	fmt.Fprintf(w, "Entry:    %s (#%s)\n", entry, hex(obj.Start, 4))
+	if obj.Version != "" && obj.Version != VERSION {
+		fmt.Fprintf(w, "Note: written by version %s, this is %s\n", obj.Version, VERSION)
+	}
+	if len(obj.Units) > 0 {
+		fmt.Fprint(w, formatSizes(obj))
+	} else {
+		fmt.Fprintf(w, "Size:     %d words\n", len(obj.Bin))
+	}
+
+	if *source != "" {
+		built, err := assemble(*source, newAssemblerState())
+		if err != nil {
+			fmt.Fprintln(w, err)
+			return 1
+		}
+		if objectChecksum(built) == sum {
+			fmt.Fprintf(w, "Source:   %s matches\n", *source)
+		} else {
+			fmt.Fprintf(w, "Source:   %s does NOT match (assembles to %s)\n", *source, objectChecksum(built))
+			status = 1
+		}
+	}
+	return status
+}
@@ -0,0 +1,55 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+func TestVerifyObject(t *testing.T) {
+	dir := t.TempDir()
+	source := filepath.Join(dir, "prog.cas")
+	os.WriteFile(source, []byte(echoProgram), 0644)
+	obj, err := assemble(source, newAssemblerState())
+	if err != nil {
+		t.Fatalf("Failed to assemble: %v", err)
+	}
+	path := filepath.Join(dir, "prog.c2o")
+	if err := writeObjectFile(path, obj); err != nil {
+		t.Fatalf("writeObjectFile: %v", err)
+	}
+	if _, err := loadObjectFile(path); err != nil {
+		t.Fatalf("loadObjectFile: %v", err)
+	}
+
+	var out bytes.Buffer
+	if status := runVerifyCommand([]string{"-source", source, path}, &out); status != 0 {
+		t.Fatalf("Expected the object to verify, got %d:\n%s", status, out.String())
+	}
+	if !strings.Contains(out.String(), obj.Checksum+" (OK)") || !strings.Contains(out.String(), "matches") {
+		t.Errorf("Unexpected report:\n%s", out.String())
+	}
+
+	// A source that assembles differently is reported
+	other := filepath.Join(dir, "other.cas")
+	os.WriteFile(other, []byte(strings.Replace(echoProgram, "DS\t16", "DS\t8", 1)), 0644)
+	out.Reset()
+	if status := runVerifyCommand([]string{"-source", other, path}, &out); status != 1 || !strings.Contains(out.String(), "does NOT match") {
+		t.Errorf("Expected a source mismatch, got %d:\n%s", status, out.String())
+	}
+
+	// A changed image no longer loads
+	obj.Bin[0]++
+	data, _ := json.Marshal(obj)
+	os.WriteFile(path, data, 0644)
+	if _, err := loadObjectFile(path); err == nil || !strings.Contains(err.Error(), "checksum mismatch") {
+		t.Errorf("Expected a checksum mismatch, got %v", err)
+	}
+	out.Reset()
+	if status := runVerifyCommand([]string{path}, &out); status != 1 || !strings.Contains(out.String(), "MISMATCH") {
+		t.Errorf("Expected verify to fail, got %d:\n%s", status, out.String())
+	}
+}
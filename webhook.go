@@ -0,0 +1,101 @@
+package main
+
+import (
+	"bytes"
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"os"
+	"time"
+)
+
+// Environment variable holding the key -webhook signs payloads with, so
+// that it does not show up in the process list
+const WEBHOOK_SECRET_ENV = "C2C2_WEBHOOK_SECRET"
+
+// Header carrying the signature of a webhook payload: "sha256=HEX", the
+// HMAC-SHA256 of the body with the secret
+const WEBHOOK_SIGNATURE_HEADER = "X-C2C2-Signature"
+
+// Wait before the first retry of a failed delivery; it doubles after each
+// attempt
+var webhookBackoff = time.Second
+
+// webhookPayload is the JSON body POSTed when a run completes.
+type webhookPayload struct {
+	Event  string     `json:"event"`
+	Result *RunResult `json:"result"`
+}
+
+// webhookClient delivers payloads to one URL.
+type webhookClient struct {
+	url     string
+	secret  string
+	retries int
+	client  *http.Client
+}
+
+func newWebhookClient(url string, retries int) *webhookClient {
+	return &webhookClient{
+		url:     url,
+		secret:  os.Getenv(WEBHOOK_SECRET_ENV),
+		retries: retries,
+		client:  &http.Client{Timeout: 10 * time.Second},
+	}
+}
+
+// sign returns the signature header value of body, or "" without a
+// secret.
+func (w *webhookClient) sign(body []byte) string {
+	if w.secret == "" {
+		return ""
+	}
+	mac := hmac.New(sha256.New, []byte(w.secret))
+	mac.Write(body)
+	return fmt.Sprintf("sha256=%x", mac.Sum(nil))
+}
+
+// post sends body once. A failure is temporary when the request could not
+// be sent or the server answered 429 or 5xx.
+func (w *webhookClient) post(body []byte) (temporary bool, err error) {
+	req, err := http.NewRequest("POST", w.url, bytes.NewReader(body))
+	if err != nil {
+		return false, err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if sig := w.sign(body); sig != "" {
+		req.Header.Set(WEBHOOK_SIGNATURE_HEADER, sig)
+	}
+	resp, err := w.client.Do(req)
+	if err != nil {
+		return true, err
+	}
+	resp.Body.Close()
+	if resp.StatusCode >= 200 && resp.StatusCode < 300 {
+		return false, nil
+	}
+	return resp.StatusCode == http.StatusTooManyRequests || resp.StatusCode >= 500, fmt.Errorf("server answered %s", resp.Status)
+}
+
+// deliver POSTs the result of a run, retrying temporary failures up to
+// retries times.
+func (w *webhookClient) deliver(result *RunResult) error {
+	body, err := json.Marshal(webhookPayload{Event: "run.finished", Result: result})
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot encode webhook payload: %v", err)
+	}
+	wait := webhookBackoff
+	for attempt := 0; ; attempt++ {
+		temporary, err := w.post(body)
+		if err == nil {
+			return nil
+		}
+		if !temporary || attempt >= w.retries {
+			return fmt.Errorf("[COMET2 ERROR] Webhook %s failed after %d attempts: %v", w.url, attempt+1, err)
+		}
+		time.Sleep(wait)
+		wait *= 2
+	}
+}
@@ -0,0 +1,61 @@
+package main
+
+import (
+	"crypto/hmac"
+	"crypto/sha256"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"net/http/httptest"
+	"testing"
+	"time"
+)
+
+func TestWebhook(t *testing.T) {
+	defer func(d time.Duration) { webhookBackoff = d }(webhookBackoff)
+	webhookBackoff = time.Millisecond
+	t.Setenv(WEBHOOK_SECRET_ENV, "s3cret")
+
+	var bodies [][]byte
+	var signatures []string
+	server := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		body, _ := io.ReadAll(r.Body)
+		bodies = append(bodies, body)
+		signatures = append(signatures, r.Header.Get(WEBHOOK_SIGNATURE_HEADER))
+		if len(bodies) == 1 {
+			w.WriteHeader(http.StatusServiceUnavailable)
+		}
+	}))
+	defer server.Close()
+
+	c := assembleSource(t, "MAIN\tSTART\n\tLAD\tGR1,3\n\tRET\n\tEND\n")
+	c.Run()
+	result := newRunResult(&Object{Source: "prog.cas"}, c, "Program finished (RET)")
+	if err := newWebhookClient(server.URL, 1).deliver(result); err != nil {
+		t.Fatalf("deliver failed: %v", err)
+	}
+	if len(bodies) != 2 {
+		t.Fatalf("Expected a retry after 503, got %d requests", len(bodies))
+	}
+	mac := hmac.New(sha256.New, []byte("s3cret"))
+	mac.Write(bodies[1])
+	if want := fmt.Sprintf("sha256=%x", mac.Sum(nil)); signatures[1] != want {
+		t.Errorf("Expected signature %s, got %s", want, signatures[1])
+	}
+	var payload webhookPayload
+	if err := json.Unmarshal(bodies[1], &payload); err != nil || payload.Event != "run.finished" || payload.Result.GR[1] != 3 {
+		t.Errorf("Unexpected payload %s (%v)", bodies[1], err)
+	}
+
+	// Client errors are not retried
+	bodies = nil
+	reject := httptest.NewServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		bodies = append(bodies, nil)
+		w.WriteHeader(http.StatusBadRequest)
+	}))
+	defer reject.Close()
+	if err := newWebhookClient(reject.URL, 3).deliver(result); err == nil || len(bodies) != 1 {
+		t.Errorf("Expected one failed attempt, got %d (%v)", len(bodies), err)
+	}
+}
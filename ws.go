@@ -0,0 +1,372 @@
+package main
+
+import (
+	"bufio"
+	"crypto/sha1"
+	"encoding/base64"
+	"encoding/binary"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/url"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+// GUID appended to Sec-WebSocket-Key in the handshake (RFC 6455)
+const WS_GUID = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11"
+
+// Number of events buffered per client; further events are dropped
+// rather than slowing down the emulator
+const WS_CLIENT_BUFFER = 4096
+
+// How long to wait for clients to receive the remaining events at exit
+const WS_FLUSH_TIMEOUT = time.Second
+
+// Events sent to WebSocket clients, one JSON object per message
+type stepEvent struct {
+	Type  string `json:"type"`
+	Step  int    `json:"step"`
+	PC    int    `json:"pc"`
+	Inst  string `json:"inst"`
+	State []int  `json:"state"`
+}
+
+type writeEvent struct {
+	Type string `json:"type"`
+	Step int    `json:"step"`
+	Addr int    `json:"addr"`
+	Old  int    `json:"old"`
+	Val  int    `json:"val"`
+}
+
+type ioStreamEvent struct {
+	Type string      `json:"type"`
+	Step int         `json:"step"`
+	Kind IoEventKind `json:"kind"`
+	Text string      `json:"text"`
+}
+
+type haltEvent struct {
+	Type   string `json:"type"`
+	Step   int    `json:"step"`
+	PC     int    `json:"pc"`
+	Reason string `json:"reason"`
+}
+
+// streamFilter selects the events a client subscribed to. It is given as
+// query parameters: events=step,write,io,halt and from/to limiting step
+// and write events to an address range.
+type streamFilter struct {
+	types map[string]bool
+	from  int
+	to    int
+}
+
+func parseStreamFilter(query map[string][]string) (streamFilter, error) {
+	f := streamFilter{from: 0, to: 0xffff}
+	if v := query["events"]; len(v) > 0 && v[0] != "" {
+		f.types = make(map[string]bool)
+		for _, name := range strings.Split(v[0], ",") {
+			switch name {
+			case "step", "write", "io", "halt":
+				f.types[name] = true
+			default:
+				return f, fmt.Errorf("unknown event \"%s\"", name)
+			}
+		}
+	}
+	for _, bound := range []struct {
+		name string
+		dst  *int
+	}{{"from", &f.from}, {"to", &f.to}} {
+		if v := query[bound.name]; len(v) > 0 {
+			n, ok := expandNumber(v[0])
+			if !ok {
+				return f, fmt.Errorf("invalid address \"%s\"", v[0])
+			}
+			*bound.dst = n
+		}
+	}
+	return f, nil
+}
+
+// accepts reports whether an event of type typ concerning addr passes
+// the filter. The address range applies to step and write events only.
+func (f streamFilter) accepts(typ string, addr int) bool {
+	if f.types != nil && !f.types[typ] {
+		return false
+	}
+	if typ == "step" || typ == "write" {
+		return addr >= f.from && addr <= f.to
+	}
+	return true
+}
+
+// streamClient is one connected WebSocket client.
+type streamClient struct {
+	filter streamFilter
+	events chan []byte
+}
+
+// eventStreamer is an observer broadcasting execution events to the
+// WebSocket clients connected to /events.
+type eventStreamer struct {
+	BaseObserver
+	mu      sync.Mutex
+	clients map[*streamClient]bool
+	writers sync.WaitGroup
+	lastIo  int
+	// origins are the page origins, besides the loopback ones, whose
+	// scripts may connect, e.g. the one the web debugger is served from
+	origins []string
+}
+
+func newEventStreamer() *eventStreamer {
+	return &eventStreamer{clients: make(map[*streamClient]bool)}
+}
+
+func (s *eventStreamer) AfterStep(c *Comet2, pc int) {
+	s.sendIo(c)
+	// Copying the state and disassembling is wasted without a client
+	if s.idle() {
+		return
+	}
+	state := append([]int{}, c.State...)
+	state[PC] = pc
+	inst, _, _ := parse(c.Memory, state)
+	state[PC] = c.State[PC]
+	s.broadcast("step", pc, &stepEvent{Type: "step", Step: c.Steps, PC: pc, Inst: inst, State: state})
+}
+
+func (s *eventStreamer) MemoryWrite(c *Comet2, addr int, old int, val int) {
+	s.broadcast("write", addr, &writeEvent{Type: "write", Step: c.Steps, Addr: addr, Old: old, Val: val})
+}
+
+// sendIo sends the transcript entries recorded since the last call.
+func (s *eventStreamer) sendIo(c *Comet2) {
+	for ; s.lastIo < len(c.Transcript); s.lastIo++ {
+		ev := c.Transcript[s.lastIo]
+		s.broadcast("io", 0, &ioStreamEvent{Type: "io", Step: ev.Step, Kind: ev.Kind, Text: ev.Text})
+	}
+}
+
+// halt sends the halt notification and waits briefly for the clients to
+// receive everything before the process exits.
+func (s *eventStreamer) halt(c *Comet2, reason string) {
+	s.sendIo(c)
+	s.broadcast("halt", 0, &haltEvent{Type: "halt", Step: c.Steps, PC: c.State[PC], Reason: reason})
+
+	s.mu.Lock()
+	for client := range s.clients {
+		close(client.events)
+		delete(s.clients, client)
+	}
+	s.mu.Unlock()
+
+	done := make(chan struct{})
+	go func() {
+		s.writers.Wait()
+		close(done)
+	}()
+	select {
+	case <-done:
+	case <-time.After(WS_FLUSH_TIMEOUT):
+	}
+}
+
+// idle reports whether no client is connected.
+func (s *eventStreamer) idle() bool {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	return len(s.clients) == 0
+}
+
+// broadcast sends ev to the clients whose filter accepts it.
+func (s *eventStreamer) broadcast(typ string, addr int, ev interface{}) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if len(s.clients) == 0 {
+		return
+	}
+
+	var data []byte
+	for client := range s.clients {
+		if !client.filter.accepts(typ, addr) {
+			continue
+		}
+		if data == nil {
+			var err error
+			if data, err = json.Marshal(ev); err != nil {
+				return
+			}
+		}
+		select {
+		case client.events <- data:
+		default:
+		}
+	}
+}
+
+// allowOrigin reports whether a browser page from origin may connect. A
+// request without Origin does not come from a page and is allowed; the
+// port itself only listens on the loopback interface.
+func (s *eventStreamer) allowOrigin(origin string) bool {
+	if origin == "" {
+		return true
+	}
+	for _, o := range s.origins {
+		if strings.EqualFold(o, origin) {
+			return true
+		}
+	}
+	u, err := url.Parse(origin)
+	if err != nil {
+		return false
+	}
+	if host := u.Hostname(); host == "localhost" {
+		return true
+	} else if ip := net.ParseIP(host); ip != nil && ip.IsLoopback() {
+		return true
+	}
+	return false
+}
+
+// headerHasToken reports whether the comma separated header name of r
+// contains token.
+func headerHasToken(r *http.Request, name string, token string) bool {
+	for _, v := range r.Header.Values(name) {
+		for _, t := range strings.Split(v, ",") {
+			if strings.EqualFold(strings.TrimSpace(t), token) {
+				return true
+			}
+		}
+	}
+	return false
+}
+
+// ServeHTTP upgrades a request for /events to a WebSocket and streams the
+// subscribed events to it. Pages from other origins are refused, so that
+// a web site open in the browser cannot watch the program.
+func (s *eventStreamer) ServeHTTP(w http.ResponseWriter, r *http.Request) {
+	if !s.allowOrigin(r.Header.Get("Origin")) {
+		http.Error(w, "Origin not allowed", http.StatusForbidden)
+		return
+	}
+	if !strings.EqualFold(r.Header.Get("Upgrade"), "websocket") || !headerHasToken(r, "Connection", "upgrade") {
+		http.Error(w, "WebSocket upgrade required", http.StatusBadRequest)
+		return
+	}
+	if r.Header.Get("Sec-WebSocket-Version") != "13" {
+		w.Header().Set("Sec-WebSocket-Version", "13")
+		http.Error(w, "Unsupported WebSocket version", http.StatusUpgradeRequired)
+		return
+	}
+	key := r.Header.Get("Sec-WebSocket-Key")
+	if key == "" {
+		http.Error(w, "Missing Sec-WebSocket-Key", http.StatusBadRequest)
+		return
+	}
+	filter, err := parseStreamFilter(r.URL.Query())
+	if err != nil {
+		http.Error(w, err.Error(), http.StatusBadRequest)
+		return
+	}
+	hijacker, ok := w.(http.Hijacker)
+	if !ok {
+		http.Error(w, "WebSocket is not supported", http.StatusInternalServerError)
+		return
+	}
+	conn, rw, err := hijacker.Hijack()
+	if err != nil {
+		return
+	}
+
+	// Subscribe before answering, so no event after the handshake is lost
+	client := &streamClient{filter: filter, events: make(chan []byte, WS_CLIENT_BUFFER)}
+	s.mu.Lock()
+	s.clients[client] = true
+	s.writers.Add(1)
+	s.mu.Unlock()
+	unsubscribe := func() {
+		s.mu.Lock()
+		if s.clients[client] {
+			close(client.events)
+			delete(s.clients, client)
+		}
+		s.mu.Unlock()
+	}
+
+	fmt.Fprintf(rw, "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: %s\r\n\r\n", wsAccept(key))
+	if err := rw.Flush(); err != nil {
+		unsubscribe()
+		s.writers.Done()
+		conn.Close()
+		return
+	}
+
+	// Incoming frames are not used; reading only notices the disconnect
+	go func() {
+		io.Copy(io.Discard, rw.Reader)
+		unsubscribe()
+	}()
+
+	go func() {
+		defer s.writers.Done()
+		defer conn.Close()
+		for data := range client.events {
+			if err := writeWsFrame(rw.Writer, 0x1, data); err != nil {
+				break
+			}
+		}
+		writeWsFrame(rw.Writer, 0x8, nil)
+	}()
+}
+
+// wsAccept computes Sec-WebSocket-Accept for key.
+func wsAccept(key string) string {
+	sum := sha1.Sum([]byte(key + WS_GUID))
+	return base64.StdEncoding.EncodeToString(sum[:])
+}
+
+// writeWsFrame writes one unmasked, unfragmented frame.
+func writeWsFrame(w *bufio.Writer, opcode byte, payload []byte) error {
+	header := []byte{0x80 | opcode}
+	switch n := len(payload); {
+	case n < 126:
+		header = append(header, byte(n))
+	case n <= 0xffff:
+		header = append(header, 126)
+		header = binary.BigEndian.AppendUint16(header, uint16(n))
+	default:
+		header = append(header, 127)
+		header = binary.BigEndian.AppendUint64(header, uint64(n))
+	}
+	if _, err := w.Write(header); err != nil {
+		return err
+	}
+	if _, err := w.Write(payload); err != nil {
+		return err
+	}
+	return w.Flush()
+}
+
+// startEventServer serves /events for s on port in the background. It
+// listens on the loopback interface only, as the events show the whole
+// run of the program.
+func startEventServer(port int, s *eventStreamer) error {
+	ln, err := net.Listen("tcp", net.JoinHostPort("127.0.0.1", strconv.Itoa(port)))
+	if err != nil {
+		return fmt.Errorf("[COMET2 ERROR] Cannot open event port %d: %v", port, err)
+	}
+	mux := http.NewServeMux()
+	mux.Handle("/events", s)
+	go http.Serve(ln, mux)
+
+	cometPrint(fmt.Sprintf("[Events] Streaming on ws://%s/events", ln.Addr()))
+	return nil
+}
@@ -0,0 +1,186 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/http/httptest"
+	"strings"
+	"testing"
+)
+
+func TestEventStreamer(t *testing.T) {
+	events := newEventStreamer()
+	server := httptest.NewServer(events)
+	defer server.Close()
+
+	conn, err := net.Dial("tcp", strings.TrimPrefix(server.URL, "http://"))
+	if err != nil {
+		t.Fatalf("Failed to connect: %v", err)
+	}
+	defer conn.Close()
+	key := "dGhlIHNhbXBsZSBub25jZQ=="
+	fmt.Fprintf(conn, "GET /events?events=write,io,halt&from=25&to=41 HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: %s\r\nSec-WebSocket-Version: 13\r\n\r\n", key)
+
+	r := bufio.NewReader(conn)
+	resp, err := http.ReadResponse(r, nil)
+	if err != nil || resp.StatusCode != http.StatusSwitchingProtocols {
+		t.Fatalf("Handshake failed: %v %v", resp, err)
+	}
+	if got := resp.Header.Get("Sec-WebSocket-Accept"); got != "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=" {
+		t.Errorf("Unexpected accept key %q", got)
+	}
+
+	c := NewComet2Builder().
+		WithObject(assembleObject(t, echoProgram)).
+		WithInputs([]string{"ab"}).
+		WithOutput(nil).
+		WithObserver(events).
+		Build()
+	err = c.Run()
+	events.halt(c, err.Error())
+
+	var types []string
+	for {
+		header := make([]byte, 2)
+		if _, err := io.ReadFull(r, header); err != nil {
+			t.Fatalf("Failed to read frame: %v", err)
+		}
+		if header[0]&0x0f == 0x8 {
+			break
+		}
+		n := int(header[1])
+		if n == 126 {
+			ext := make([]byte, 2)
+			io.ReadFull(r, ext)
+			n = int(ext[0])<<8 | int(ext[1])
+		}
+		payload := make([]byte, n)
+		if _, err := io.ReadFull(r, payload); err != nil {
+			t.Fatalf("Failed to read payload: %v", err)
+		}
+		var ev struct {
+			Type string `json:"type"`
+			Addr int    `json:"addr"`
+		}
+		if err := json.Unmarshal(payload, &ev); err != nil {
+			t.Fatalf("Invalid event %q: %v", payload, err)
+		}
+		if ev.Type == "write" && (ev.Addr < 25 || ev.Addr > 41) {
+			t.Errorf("Write to #%s passed the address filter", hex(ev.Addr, 4))
+		}
+		types = append(types, ev.Type)
+	}
+
+	// IN writes LEN and the two characters in BUF; the PUSHes of the IN
+	// and OUT macros write outside BUF..LEN and are filtered out
+	if got := strings.Join(types, ","); got != "write,write,write,io,io,halt" {
+		t.Errorf("Unexpected events %s", got)
+	}
+}
+
+func TestEventStreamerRejects(t *testing.T) {
+	server := httptest.NewServer(newEventStreamer())
+	defer server.Close()
+
+	tests := []struct {
+		name    string
+		query   string
+		headers map[string]string
+	}{
+		{"no upgrade", "", nil},
+		{"no key", "", map[string]string{"Upgrade": "websocket", "Connection": "Upgrade"}},
+		{"no connection upgrade", "", map[string]string{"Upgrade": "websocket", "Sec-WebSocket-Version": "13", "Sec-WebSocket-Key": "dGhlIHNhbXBsZSBub25jZQ=="}},
+		{"bad filter", "?events=jump", map[string]string{"Upgrade": "websocket", "Connection": "Upgrade", "Sec-WebSocket-Version": "13", "Sec-WebSocket-Key": "dGhlIHNhbXBsZSBub25jZQ=="}},
+	}
+	for _, tt := range tests {
+		req, err := http.NewRequest("GET", server.URL+"/events"+tt.query, nil)
+		if err != nil {
+			t.Fatal(err)
+		}
+		for k, v := range tt.headers {
+			req.Header.Set(k, v)
+		}
+		resp, err := http.DefaultClient.Do(req)
+		if err != nil {
+			t.Fatalf("%s: %v", tt.name, err)
+		}
+		resp.Body.Close()
+		if resp.StatusCode != http.StatusBadRequest {
+			t.Errorf("%s: expected status 400, got %d", tt.name, resp.StatusCode)
+		}
+	}
+}
+
+func TestEventStreamerOrigin(t *testing.T) {
+	events := newEventStreamer()
+	events.origins = []string{"http://debugger.example:8000"}
+	server := httptest.NewServer(events)
+	defer server.Close()
+
+	tests := []struct {
+		origin  string
+		version string
+		status  int
+	}{
+		{"https://evil.example", "13", http.StatusForbidden},
+		{"null", "13", http.StatusForbidden},
+		{"http://localhost:8080", "8", http.StatusUpgradeRequired},
+		{"http://127.0.0.1:3000", "8", http.StatusUpgradeRequired},
+		{"http://debugger.example:8000", "8", http.StatusUpgradeRequired},
+	}
+	for _, tt := range tests {
+		req, err := http.NewRequest("GET", server.URL+"/events", nil)
+		if err != nil {
+			t.Fatal(err)
+		}
+		req.Header.Set("Origin", tt.origin)
+		req.Header.Set("Upgrade", "websocket")
+		req.Header.Set("Connection", "Upgrade")
+		req.Header.Set("Sec-WebSocket-Version", tt.version)
+		req.Header.Set("Sec-WebSocket-Key", "dGhlIHNhbXBsZSBub25jZQ==")
+		resp, err := http.DefaultClient.Do(req)
+		if err != nil {
+			t.Fatalf("%s: %v", tt.origin, err)
+		}
+		resp.Body.Close()
+		if resp.StatusCode != tt.status {
+			t.Errorf("%s: expected status %d, got %d", tt.origin, tt.status, resp.StatusCode)
+		}
+	}
+}
+
+func TestWriteWsFrame(t *testing.T) {
+	tests := []struct {
+		size   int
+		header []byte
+	}{
+		{0, []byte{0x81, 0}},
+		{125, []byte{0x81, 125}},
+		{126, []byte{0x81, 126, 0, 126}},
+		{0xffff, []byte{0x81, 126, 0xff, 0xff}},
+		{0x10000, []byte{0x81, 127, 0, 0, 0, 0, 0, 1, 0, 0}},
+	}
+	for _, tt := range tests {
+		var b bytes.Buffer
+		payload := bytes.Repeat([]byte("x"), tt.size)
+		if err := writeWsFrame(bufio.NewWriter(&b), 0x1, payload); err != nil {
+			t.Fatal(err)
+		}
+		frame := b.Bytes()
+		if !bytes.HasPrefix(frame, tt.header) || !bytes.Equal(frame[len(tt.header):], payload) {
+			t.Errorf("%d bytes: unexpected frame header % x", tt.size, frame[:min(len(frame), 10)])
+		}
+	}
+
+	// A close frame has no payload
+	var b bytes.Buffer
+	writeWsFrame(bufio.NewWriter(&b), 0x8, nil)
+	if !bytes.Equal(b.Bytes(), []byte{0x88, 0}) {
+		t.Errorf("Unexpected close frame % x", b.Bytes())
+	}
+}